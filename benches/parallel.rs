@@ -0,0 +1,32 @@
+#![feature(test)]
+
+extern crate test;
+
+use comrak::{format_html, format_html_parallel, parse_document, Arena, Options, Plugins};
+use test::Bencher;
+
+fn progit() -> String {
+    std::fs::read_to_string("script/progit.md").unwrap()
+}
+
+#[bench]
+fn bench_progit_sequential(b: &mut Bencher) {
+    let s = progit();
+    let arena = Arena::new();
+    let root = parse_document(&arena, &s, &Options::default());
+    b.iter(|| {
+        let mut output = vec![];
+        format_html(root, &Options::default(), &mut output).unwrap()
+    });
+}
+
+#[bench]
+fn bench_progit_parallel(b: &mut Bencher) {
+    let s = progit();
+    let arena = Arena::new();
+    let root = parse_document(&arena, &s, &Options::default());
+    b.iter(|| {
+        let mut output = vec![];
+        format_html_parallel(root, &Options::default(), &mut output, &Plugins::default()).unwrap()
+    });
+}
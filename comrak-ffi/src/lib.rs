@@ -0,0 +1,5 @@
+//! Builds [`comrak::ffi`], comrak's implementation of the cmark-gfm C API, as a shared and a
+//! static library.  Generate a header with `cbindgen --config cbindgen.toml --output comrak.h`,
+//! or use cmark-gfm's own headers for the functions comrak implements.
+
+pub use comrak::ffi::*;
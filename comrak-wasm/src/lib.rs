@@ -0,0 +1,27 @@
+//! Exposes comrak to JavaScript through `wasm-bindgen`.  Build with
+//! `wasm-pack build --target web` (or `--target nodejs`) and call
+//! `markdown_to_html(input, optionsJson)`.
+//!
+//! The options are [`comrak::Options`] as JSON, with the same field names:
+//! `{"extension": {"table": true}, "render": {"unsafe": true}}`.  Omitted
+//! fields take their default values, and an empty string selects the
+//! defaults for everything.
+
+use comrak::Options;
+use wasm_bindgen::prelude::*;
+
+/// Renders `input` to HTML with the options in `options_json`.  Throws if the
+/// options are not valid JSON or don't match [`comrak::Options`].
+#[wasm_bindgen]
+pub fn markdown_to_html(input: &str, options_json: &str) -> Result<String, JsError> {
+    let options = parse_options(options_json).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(comrak::markdown_to_html(input, &options))
+}
+
+fn parse_options(options_json: &str) -> serde_json::Result<Options<'static>> {
+    if options_json.trim().is_empty() {
+        Ok(Options::default())
+    } else {
+        serde_json::from_str(options_json)
+    }
+}
@@ -3,8 +3,8 @@
 use libfuzzer_sys::fuzz_target;
 
 use comrak::{
-    markdown_to_html, BrokenLinkReference, ExtensionOptions, ListStyleType, Options, ParseOptions,
-    RenderOptions, ResolvedReference,
+    markdown_to_html, BrokenLinkReference, CodeFenceStyleType, ExtensionOptions, HeaderIdStyle,
+    HeadingStyleType, HtmlCommentStyle, ListStyleType, Options, ParseOptions, RenderOptions, ResolvedReference, SmartQuotes,
 };
 use std::sync::{Arc, Mutex};
 
@@ -13,11 +13,16 @@ fuzz_target!(|s: &str| {
     extension.strikethrough = true;
     extension.tagfilter = true;
     extension.table = true;
+    extension.table_colspans = true;
     extension.autolink = true;
     extension.tasklist = true;
     extension.superscript = true;
+    extension.subscript = true;
     extension.header_ids = Some("user-content-".to_string());
+    extension.header_ids_reserved = vec!["user-content-readme".to_string()];
+    extension.header_id_style = HeaderIdStyle::PercentEncoded;
     extension.footnotes = true;
+    extension.inline_footnotes = true;
     extension.description_lists = true;
     extension.front_matter_delimiter = Some("---".to_string());
     extension.multiline_block_quotes = true;
@@ -29,12 +34,29 @@ fuzz_target!(|s: &str| {
     extension.underline = true;
     extension.spoiler = true;
     extension.greentext = true;
+    extension.abbreviations = true;
+    extension.template_variables = true;
+    extension.template_tags = true;
+    extension.conditional_blocks = true;
+    extension.details_blocks = true;
+    extension.container_blocks = true;
+    extension.citations = true;
+    extension.jsx_components = true;
+    extension.raw_regions = true;
+    extension.alerts = true;
+    extension.block_attributes = true;
+    extension.mentions = Some("https://github.com/{user}".to_string());
+    extension.image_dimensions = true;
 
     let mut parse = ParseOptions::default();
     parse.smart = true;
+    parse.smart_quotes = SmartQuotes::german();
     parse.default_info_string = Some("rust".to_string());
     parse.relaxed_tasklist_matching = true;
     parse.relaxed_autolinks = true;
+    parse.preserve_entities = true;
+    parse.tab_width = 8;
+    parse.max_input_size = Some(1 << 20);
     let mut cb = |link_ref: BrokenLinkReference| {
         Some(ResolvedReference {
             url: link_ref.normalized.to_string(),
@@ -45,11 +67,13 @@ fuzz_target!(|s: &str| {
 
     let mut render = RenderOptions::default();
     render.hardbreaks = true;
+    render.cjk_soft_breaks = true;
     render.github_pre_lang = true;
     render.full_info_string = true;
     render.width = 80;
     render.unsafe_ = true;
     render.escape = true;
+    render.html_comments = HtmlCommentStyle::Keep;
     render.list_style = ListStyleType::Star;
     render.sourcepos = true;
     render.escaped_char_spans = true;
@@ -57,6 +81,17 @@ fuzz_target!(|s: &str| {
     render.ignore_empty_links = true;
     render.gfm_quirks = true;
     render.prefer_fenced = true;
+    render.heading_style = HeadingStyleType::Preserve;
+    render.code_fence_style = CodeFenceStyleType::Preserve;
+    render.text_link_urls = true;
+    render.lazy_images = true;
+    render.external_link_rel = true;
+    render.external_link_target = true;
+    render
+        .node_classes
+        .insert("paragraph".into(), "prose".into());
+    render.strings.footnote_backref = "Zurück zur Referenz".into();
+    render.footnote_style.id_prefix = "doc-".into();
 
     markdown_to_html(
         s,
@@ -64,6 +99,7 @@ fuzz_target!(|s: &str| {
             extension,
             parse,
             render,
+            extensions: Default::default(),
         },
     );
 });
@@ -27,6 +27,7 @@ fuzz_target!(|s: &str| {
             extension,
             parse: Default::default(),
             render,
+            extensions: Default::default(),
         },
     );
 });
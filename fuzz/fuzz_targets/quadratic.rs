@@ -177,6 +177,7 @@ impl FuzzOptions {
             extension: self.extension.to_options(),
             parse: self.parse.to_options(),
             render: self.render.to_options(),
+            extensions: Default::default(),
         }
     }
 }
@@ -5,7 +5,7 @@
 use std::collections::HashMap;
 use std::io::{self, Write};
 
-use crate::nodes::Sourcepos;
+use crate::nodes::{Sourcepos, TableAlignment};
 
 /// Implement this adapter for creating a plugin for custom syntax highlighting of codefence blocks.
 pub trait SyntaxHighlighterAdapter: Send + Sync {
@@ -39,6 +39,166 @@ pub trait SyntaxHighlighterAdapter: Send + Sync {
         output: &mut dyn Write,
         attributes: HashMap<String, String>,
     ) -> io::Result<()>;
+
+    /// Returns a description of the highlighter's configuration (theme, class style, etc.), used
+    /// as part of the [`CodeBlockCacheKey`] so that cached output produced under one
+    /// configuration is never served for another.  Highlighters whose output differs must return
+    /// different keys.
+    ///
+    /// The default implementation returns an empty string, which is only correct if the
+    /// adapter's output depends on nothing but the language and the code.
+    fn config_key(&self) -> String {
+        String::new()
+    }
+}
+
+/// The key under which a [`CodeBlockCache`] stores highlighted code block output.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CodeBlockCacheKey {
+    /// The language of the code block, as passed to
+    /// [`SyntaxHighlighterAdapter::write_highlighted`].
+    pub lang: Option<String>,
+
+    /// The code block's literal content.
+    pub literal: String,
+
+    /// The highlighter's [`SyntaxHighlighterAdapter::config_key`].
+    pub config: String,
+}
+
+impl CodeBlockCacheKey {
+    /// Builds the key for a code block with the given language and content, highlighted by
+    /// a highlighter with the given configuration key.
+    pub fn new(lang: Option<&str>, literal: &str, config: String) -> Self {
+        CodeBlockCacheKey {
+            lang: lang.map(String::from),
+            literal: literal.to_string(),
+            config,
+        }
+    }
+}
+
+/// Implement this adapter to cache the output of a [`SyntaxHighlighterAdapter`] across renders.
+///
+/// When set in [`RenderPlugins::codefence_highlight_cache`](crate::RenderPlugins) alongside a
+/// syntax highlighter, the renderer looks up each fenced code block before invoking the
+/// highlighter, and stores the highlighter's output on a miss. Only the highlighted body is
+/// cached; the `<pre>` and `<code>` tags are always written fresh, since they carry per-document
+/// attributes such as sourcepos.
+///
+/// ## Invalidation
+///
+/// Keys hold the code itself, so editing a code block simply produces a new key; stale entries
+/// are never served, they just linger. Implementations should bound their size or be cleared
+/// periodically if documents change often. Changes to the highlighter itself are only picked up
+/// when reflected in [`SyntaxHighlighterAdapter::config_key`]; if you swap syntax definitions or
+/// otherwise change the highlighter in a way its key does not capture, clear the cache.
+pub trait CodeBlockCache: Send + Sync {
+    /// Returns the cached output for `key`, if any.
+    fn get(&self, key: &CodeBlockCacheKey) -> Option<Vec<u8>>;
+
+    /// Stores the highlighter's output for `key`.
+    fn insert(&self, key: CodeBlockCacheKey, output: Vec<u8>);
+}
+
+/// Implement this adapter to transform the titles of links and images before they are rendered,
+/// e.g. to trim or localize them.
+///
+/// The title is only passed to the adapter when it is non-empty. Returning an empty string omits
+/// the title. How the result is written is controlled by
+/// [`RenderOptions::link_titles`](crate::RenderOptions::link_titles).
+pub trait LinkTitleAdapter: Send + Sync {
+    /// Returns the title to render for a link or image with the given destination `url`.
+    fn transform(&self, title: &str, url: &str) -> String;
+}
+
+/// Implement this adapter to rewrite the destinations of links or images before they are
+/// rendered, e.g. to make relative links absolute or route images through a proxy.  Closures of
+/// type `Fn(&str) -> String` implement it.
+///
+/// The rewritten URL is escaped, and dropped if it is dangerous unless
+/// [`RenderOptions::unsafe_`](crate::RenderOptions::unsafe_) is set, as the original would be.
+pub trait UrlRewriter: Send + Sync {
+    /// Returns the URL to render in place of `url`.
+    fn rewrite(&self, url: &str) -> String;
+}
+
+impl<F> UrlRewriter for F
+where
+    F: Fn(&str) -> String + Send + Sync,
+{
+    fn rewrite(&self, url: &str) -> String {
+        self(url)
+    }
+}
+
+/// The image passed to an [`ImageResolver`].
+#[derive(Clone, Debug)]
+pub struct ImageMeta {
+    /// The image's source, as written in the document.
+    pub url: String,
+
+    /// The image's alt text, as plain text.
+    pub alt: String,
+
+    /// The image's title, or the empty string if it has none.
+    pub title: String,
+}
+
+/// Implement this adapter to replace the sources of images when rendering HTML, e.g. with `data:`
+/// URIs holding their contents so the output is self-contained.  Closures of type
+/// `Fn(&ImageMeta) -> Option<String>` implement it.  See
+/// [`FileImageEmbedder`](crate::plugins::embed::FileImageEmbedder) for one which embeds local
+/// files.
+///
+/// The resolved URL is escaped, and dropped if it is dangerous unless
+/// [`RenderOptions::unsafe_`](crate::RenderOptions::unsafe_) is set, as the original would be:
+/// `data:` URIs are only allowed for PNG, GIF, JPEG and WebP images.
+pub trait ImageResolver: Send + Sync {
+    /// Returns the URL to render in place of the image's source, or `None` to keep it.
+    fn resolve(&self, image: &ImageMeta) -> Option<String>;
+}
+
+impl<F> ImageResolver for F
+where
+    F: Fn(&ImageMeta) -> Option<String> + Send + Sync,
+{
+    fn resolve(&self, image: &ImageMeta) -> Option<String> {
+        self(image)
+    }
+}
+
+/// Implement this adapter to supply the values of template variable placeholders when
+/// rendering HTML.  See
+/// [`ExtensionOptions::template_variables`](crate::ExtensionOptions::template_variables).
+pub trait TemplateResolver: Send + Sync {
+    /// Returns the value of the variable `name` with `filters` applied, or `None` to leave the
+    /// placeholder as written.  The value is escaped when written.
+    fn resolve(&self, name: &str, filters: &[String]) -> Option<String>;
+}
+
+/// The intrinsic size and responsive sources of an image, as returned by an
+/// [`ImageDimensionAdapter`].  Each field is written as the attribute of the same name if set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImageDimensions {
+    /// The intrinsic width of the image, in pixels.
+    pub width: Option<u32>,
+
+    /// The intrinsic height of the image, in pixels.
+    pub height: Option<u32>,
+
+    /// Alternative sources for the image, in `srcset` syntax, e.g. `a.png 1x, a@2x.png 2x`.
+    pub srcset: Option<String>,
+}
+
+/// Implement this adapter to supply the dimensions of images when rendering HTML, so browsers
+/// can reserve space for them before they load.
+///
+/// The adapter is called once for each image rendered, so implementations which probe files or
+/// the network should cache their results.
+pub trait ImageDimensionAdapter: Send + Sync {
+    /// Returns the dimensions of the image at `url`, or `None` to write no extra attributes.
+    fn dimensions(&self, url: &str) -> Option<ImageDimensions>;
 }
 
 /// The struct passed to the [`HeadingAdapter`] for custom heading implementations.
@@ -69,3 +229,36 @@ pub trait HeadingAdapter: Send + Sync {
     /// Render the closing tag.
     fn exit(&self, output: &mut dyn Write, heading: &HeadingMeta) -> io::Result<()>;
 }
+
+/// The struct passed to the [`TableCellAdapter`] for custom table cells.
+#[derive(Clone, Copy, Debug)]
+pub struct TableCellMeta {
+    /// Whether the cell is in the header row.
+    pub header: bool,
+
+    /// The index of the cell's column, from 0; the first of them if it spans several.
+    pub column: usize,
+
+    /// The number of columns the cell spans.
+    pub colspan: usize,
+
+    /// The alignment of the cell's column.
+    pub alignment: TableAlignment,
+}
+
+/// Implement this adapter for creating a plugin for custom table cells, e.g. to add classes or
+/// data attributes. The `enter` method renders the opening `th` or `td` tag in place of the
+/// default one, and the `exit` method the closing tag; the cell's content is rendered between
+/// them as usual.
+pub trait TableCellAdapter: Send + Sync {
+    /// Render the opening tag.
+    fn enter(
+        &self,
+        output: &mut dyn Write,
+        cell: &TableCellMeta,
+        sourcepos: Option<Sourcepos>,
+    ) -> io::Result<()>;
+
+    /// Render the closing tag.
+    fn exit(&self, output: &mut dyn Write, cell: &TableCellMeta) -> io::Result<()>;
+}
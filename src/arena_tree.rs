@@ -13,6 +13,18 @@ or [`typed_arena::Arena`](https://crates.io/crates/typed-arena).
 If you need mutability in the node’s `data`,
 make it a cell (`Cell` or `RefCell`) or use cells inside of it.
 
+# Mutating while iterating
+
+`children()` and the sibling iterators read the next node before yielding the
+current one, so the node just yielded may be detached, replaced, or have
+siblings inserted around it; siblings inserted after it are not visited.
+Other nodes ahead of the iterator must not be moved.
+
+`traverse()`, `descendants()` and their reverse counterparts find the next
+node lazily from the last one yielded, and panic if they find a node which
+has been detached.  To restructure a tree found by them, collect the nodes of
+interest into a `Vec` first, then mutate.
+
 */
 
 use std::cell::Cell;
@@ -190,6 +202,17 @@ impl<'a, T> Node<'a, T> {
         }
     }
 
+    /// Put `new_node` in this node's place, detaching this node.
+    ///
+    /// The children of each node are not affected; append this node to
+    /// `new_node` afterwards to wrap it.
+    pub fn replace_with(&'a self, new_node: &'a Node<'a, T>) {
+        if !self.same_node(new_node) {
+            self.insert_before(new_node);
+            self.detach();
+        }
+    }
+
     /// Append a new child to this node, after existing children.
     pub fn append(&'a self, new_child: &'a Node<'a, T>) {
         new_child.detach();
@@ -436,3 +459,34 @@ fn it_works() {
 
     assert_eq!(drop_counter.get(), 10);
 }
+
+#[test]
+fn replace_with() {
+    let arena = typed_arena::Arena::new();
+    let root = arena.alloc(Node::new(0));
+    let a = arena.alloc(Node::new(1));
+    let b = arena.alloc(Node::new(2));
+    let c = arena.alloc(Node::new(3));
+    root.append(a);
+    root.append(b);
+    a.append(c);
+
+    for child in root.children() {
+        if child.data == 1 {
+            let wrapper = arena.alloc(Node::new(4));
+            child.replace_with(wrapper);
+            wrapper.append(child);
+        }
+    }
+
+    assert_eq!(
+        root.descendants().map(|node| node.data).collect::<Vec<_>>(),
+        [0, 4, 1, 3, 2]
+    );
+    assert!(root
+        .first_child()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .same_node(root));
+}
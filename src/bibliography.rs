@@ -0,0 +1,280 @@
+//! Resolution of citations against a bibliography, in author–date style.
+//!
+//! Citations are parsed with
+//! [`ExtensionOptions::citations`](crate::ExtensionOptions::citations).  The
+//! works they cite are looked up through the [`Bibliography`] trait, which can
+//! be implemented over CSL-JSON, BibTeX or any other data source.
+
+use crate::nodes::{AstNode, CitationItem, ListDelimType, ListType, NodeLink, NodeList, NodeValue};
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use typed_arena::Arena;
+
+/// An author of a [`Reference`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Author {
+    /// The family name, e.g. `Knuth`.
+    pub family: String,
+
+    /// The given names, e.g. `Donald E.`; may be empty.
+    pub given: String,
+}
+
+/// A work which can be cited.  Empty fields are omitted when formatting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reference {
+    /// The authors of the work, in order.
+    pub authors: Vec<Author>,
+
+    /// The year the work was issued.
+    pub year: String,
+
+    /// The title of the work.
+    pub title: String,
+
+    /// The journal, book or site the work appeared in.
+    pub container: String,
+
+    /// Where the work can be found online.
+    pub url: String,
+}
+
+/// A source of [`Reference`]s by citation key.
+pub trait Bibliography {
+    /// Looks up the work cited as `key`.
+    fn reference(&self, key: &str) -> Option<Reference>;
+}
+
+impl<S: BuildHasher> Bibliography for HashMap<String, Reference, S> {
+    fn reference(&self, key: &str) -> Option<Reference> {
+        self.get(key).cloned()
+    }
+}
+
+/// The outcome of [`resolve_citations`].
+#[derive(Debug)]
+pub struct ResolvedCitations<'a> {
+    /// A bullet list of the works cited, sorted by author and year.  It is
+    /// not attached to the document; insert it wherever the references
+    /// should appear.  If nothing was cited, the list is empty.
+    pub references: &'a AstNode<'a>,
+
+    /// The keys which weren't found in the bibliography, in order of first
+    /// citation.
+    pub missing: Vec<String>,
+}
+
+/// Replaces each citation in the document with its in-text form, such as
+/// "(Knuth 1984, p. 3)", and returns a list of the works cited.
+///
+/// Keys not found in the bibliography are written with a trailing `?`, and
+/// are reported in [`ResolvedCitations::missing`].
+///
+/// ```
+/// # use comrak::{format_commonmark, parse_document, Arena, Options};
+/// # use comrak::bibliography::{resolve_citations, Author, Reference};
+/// # use std::collections::HashMap;
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.citations = true;
+/// let root = parse_document(&arena, "Literate programming [@knuth84, p. 97; @ref].\n", &options);
+///
+/// let mut bibliography = HashMap::new();
+/// bibliography.insert(
+///     "knuth84".to_string(),
+///     Reference {
+///         authors: vec![Author { family: "Knuth".into(), given: "Donald E.".into() }],
+///         year: "1984".into(),
+///         title: "Literate Programming".into(),
+///         container: "The Computer Journal".into(),
+///         ..Default::default()
+///     },
+/// );
+///
+/// let resolved = resolve_citations(&arena, root, &bibliography);
+/// root.append(resolved.references);
+/// assert_eq!(resolved.missing, ["ref"]);
+///
+/// let mut md = vec![];
+/// format_commonmark(root, &options, &mut md).unwrap();
+/// assert_eq!(
+///     String::from_utf8(md).unwrap(),
+///     "Literate programming (Knuth 1984, p. 97; ref?).\n\n\
+///      - Knuth, Donald E. 1984. *Literate Programming*. The Computer Journal.\n"
+/// );
+/// ```
+pub fn resolve_citations<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    root: &'a AstNode<'a>,
+    bibliography: &dyn Bibliography,
+) -> ResolvedCitations<'a> {
+    let mut cited: Vec<Reference> = vec![];
+    let mut seen: HashMap<String, bool> = HashMap::new();
+    let mut missing = vec![];
+
+    let citations: Vec<_> = root
+        .descendants()
+        .filter(|n| matches!(n.data.borrow().value, NodeValue::Citation(..)))
+        .collect();
+
+    for node in citations {
+        let mut ast = node.data.borrow_mut();
+        let items = match ast.value {
+            NodeValue::Citation(ref nc) => nc.items.clone(),
+            _ => unreachable!(),
+        };
+
+        let mut parts = vec![];
+        for item in &items {
+            let reference = match seen.get(&item.key) {
+                Some(true) => bibliography.reference(&item.key),
+                Some(false) => None,
+                None => {
+                    let reference = bibliography.reference(&item.key);
+                    seen.insert(item.key.clone(), reference.is_some());
+                    match reference {
+                        Some(ref reference) => cited.push(reference.clone()),
+                        None => missing.push(item.key.clone()),
+                    }
+                    reference
+                }
+            };
+            parts.push(in_text(item, reference.as_ref()));
+        }
+
+        ast.value = NodeValue::Text(format!("({})", parts.join("; ")));
+    }
+
+    cited.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+
+    let nl = NodeList {
+        list_type: ListType::Bullet,
+        marker_offset: 0,
+        padding: 2,
+        start: 1,
+        delimiter: ListDelimType::Period,
+        bullet_char: b'-',
+        tight: true,
+    };
+    let list = alloc(arena, NodeValue::List(nl));
+    for reference in &cited {
+        let item = alloc(arena, NodeValue::Item(nl));
+        item.append(entry(arena, reference));
+        list.append(item);
+    }
+
+    ResolvedCitations {
+        references: list,
+        missing,
+    }
+}
+
+fn in_text(item: &CitationItem, reference: Option<&Reference>) -> String {
+    let mut s = item.prefix.clone();
+    if !s.is_empty() {
+        s.push(' ');
+    }
+
+    match reference {
+        Some(reference) => {
+            let families: Vec<&str> = reference
+                .authors
+                .iter()
+                .map(|a| a.family.as_str())
+                .collect();
+            s.push_str(&match families[..] {
+                [] => reference.title.clone(),
+                [one] => one.to_string(),
+                [one, two] => format!("{} and {}", one, two),
+                [one, ..] => format!("{} et al.", one),
+            });
+            if !reference.year.is_empty() {
+                s.push(' ');
+                s.push_str(&reference.year);
+            }
+        }
+        None => {
+            s.push_str(&item.key);
+            s.push('?');
+        }
+    }
+
+    if !item.locator.is_empty() {
+        s.push_str(", ");
+        s.push_str(&item.locator);
+    }
+    s
+}
+
+fn sort_key(reference: &Reference) -> (String, &str, &str) {
+    let family = reference
+        .authors
+        .first()
+        .map_or(reference.title.as_str(), |a| a.family.as_str());
+    (family.to_lowercase(), &reference.year, &reference.title)
+}
+
+fn entry<'a>(arena: &'a Arena<AstNode<'a>>, reference: &Reference) -> &'a AstNode<'a> {
+    let paragraph = alloc(arena, NodeValue::Paragraph);
+
+    let names: Vec<String> = reference
+        .authors
+        .iter()
+        .enumerate()
+        .map(|(i, author)| match (i, author.given.is_empty()) {
+            (_, true) => author.family.clone(),
+            (0, false) => format!("{}, {}", author.family, author.given),
+            (_, false) => format!("{} {}", author.given, author.family),
+        })
+        .collect();
+    let authors = match names.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+    };
+    let lead: String = [&authors, &reference.year]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .map(|part| format!("{} ", terminate(part)))
+        .collect();
+    if !lead.is_empty() {
+        paragraph.append(alloc(arena, NodeValue::Text(lead)));
+    }
+
+    if !reference.title.is_empty() {
+        let emph = alloc(arena, NodeValue::Emph);
+        emph.append(alloc(arena, NodeValue::Text(reference.title.clone())));
+        paragraph.append(emph);
+        paragraph.append(alloc(arena, NodeValue::Text(".".to_string())));
+    }
+
+    if !reference.container.is_empty() {
+        let container = format!(" {}", terminate(&reference.container));
+        paragraph.append(alloc(arena, NodeValue::Text(container)));
+    }
+
+    if !reference.url.is_empty() {
+        paragraph.append(alloc(arena, NodeValue::Text(" ".to_string())));
+        let link = alloc(
+            arena,
+            NodeValue::Link(NodeLink::new(reference.url.clone(), "")),
+        );
+        link.append(alloc(arena, NodeValue::Text(reference.url.clone())));
+        paragraph.append(link);
+    }
+
+    paragraph
+}
+
+// Appends a full stop unless `s` already ends with one.
+fn terminate(s: &str) -> String {
+    if s.ends_with('.') {
+        s.to_string()
+    } else {
+        format!("{}.", s)
+    }
+}
+
+fn alloc<'a>(arena: &'a Arena<AstNode<'a>>, value: NodeValue) -> &'a AstNode<'a> {
+    arena.alloc(value.into())
+}
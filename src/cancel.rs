@@ -0,0 +1,204 @@
+//! Cancelling parsing and rendering part way through, so that a service can
+//! enforce a timeout on a hostile or huge input without abandoning a thread.
+//!
+//! A [`CancellationToken`] is passed to the cancellable entry points, which
+//! check it at intervals while scanning blocks, parsing inlines and
+//! rendering, and stop with [`Cancelled`] once it's cancelled or its deadline
+//! has passed.
+//!
+//! The parser's work is otherwise bounded by the size of its input: known
+//! quadratic inputs, such as runs of unclosed brackets or emphasis
+//! delimiters, are handled in linear time, and the expansion of link
+//! reference definitions and autocompleted table cells is capped.  Block
+//! nesting is limited by
+//! [`ParseOptions::max_nesting`](crate::ParseOptions::max_nesting).  A
+//! deadline bounds the time taken by the rest.
+//!
+//! ```
+//! # use comrak::{markdown_to_html_cancellable, Options, Plugins};
+//! # use comrak::cancel::{CancellationToken, RenderError};
+//! # use std::time::Duration;
+//! let options = Options::default();
+//! let plugins = Plugins::default();
+//!
+//! let token = CancellationToken::with_timeout(Duration::from_secs(5));
+//! assert_eq!(
+//!     markdown_to_html_cancellable("*Hi*", &options, &plugins, &token).unwrap(),
+//!     "<p><em>Hi</em></p>\n"
+//! );
+//!
+//! // Cancelling a clone of the token cancels the original, e.g. from another
+//! // thread.
+//! token.clone().cancel();
+//! assert!(matches!(
+//!     markdown_to_html_cancellable("*Hi*", &options, &plugins, &token),
+//!     Err(RenderError::Cancelled)
+//! ));
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// The number of lines, blocks, inlines or nodes processed between checks of
+// a token, since reading the clock for a deadline costs more than most of
+// the work between checks.
+const CHECK_INTERVAL: u32 = 64;
+
+/// A flag which cancels parsing or rendering, set explicitly or by a
+/// deadline passing.  Clones share the flag, so one can be kept to cancel
+/// work done with another.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// A token which is only cancelled by [`cancel`](CancellationToken::cancel).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token which is cancelled once `deadline` has passed.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        CancellationToken {
+            cancelled: Arc::default(),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// A token which is cancelled once `timeout` has elapsed from now.
+    ///
+    /// # Panics
+    ///
+    /// On `wasm32-unknown-unknown`, which has no clock.  Use
+    /// [`cancel`](CancellationToken::cancel) from a host timer there instead.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    /// Cancels the token and all its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the token has been cancelled, or its deadline has passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .map_or(false, |deadline| Instant::now() >= deadline)
+    }
+}
+
+/// The error returned when parsing or rendering is stopped by a
+/// [`CancellationToken`].  Renderers return it wrapped in an [`io::Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Cancelled {
+    /// Whether `err` is a wrapped `Cancelled`.
+    pub fn is_cancelled(err: &io::Error) -> bool {
+        err.get_ref().map_or(false, |inner| inner.is::<Cancelled>())
+    }
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+impl From<Cancelled> for io::Error {
+    fn from(cancelled: Cancelled) -> Self {
+        io::Error::new(io::ErrorKind::Other, cancelled)
+    }
+}
+
+/// The error returned by
+/// [`markdown_to_html_cancellable`](crate::markdown_to_html_cancellable).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RenderError {
+    /// The token was cancelled before parsing and rendering finished.
+    Cancelled,
+
+    /// Rendering failed otherwise, e.g. on a colliding header ID with
+    /// [`HeaderIdCollisions::Error`](crate::HeaderIdCollisions::Error), or
+    /// an error returned by a plugin.
+    Io(io::Error),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::Cancelled => Cancelled.fmt(f),
+            RenderError::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for RenderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RenderError::Cancelled => None,
+            RenderError::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<Cancelled> for RenderError {
+    fn from(_: Cancelled) -> Self {
+        RenderError::Cancelled
+    }
+}
+
+impl From<io::Error> for RenderError {
+    fn from(err: io::Error) -> Self {
+        if Cancelled::is_cancelled(&err) {
+            RenderError::Cancelled
+        } else {
+            RenderError::Io(err)
+        }
+    }
+}
+
+/// Checks an optional token every [`CHECK_INTERVAL`] calls, remembering once
+/// it's been cancelled.
+#[derive(Debug, Default)]
+pub(crate) struct Checkpoint {
+    token: Option<CancellationToken>,
+    countdown: u32,
+    cancelled: bool,
+}
+
+impl Checkpoint {
+    pub(crate) fn new(token: Option<&CancellationToken>) -> Self {
+        Checkpoint {
+            token: token.cloned(),
+            countdown: 0,
+            cancelled: false,
+        }
+    }
+
+    pub(crate) fn cancelled(&mut self) -> bool {
+        if self.cancelled {
+            return true;
+        }
+        if let Some(ref token) = self.token {
+            if self.countdown == 0 {
+                self.countdown = CHECK_INTERVAL;
+                self.cancelled = token.is_cancelled();
+            } else {
+                self.countdown -= 1;
+            }
+        }
+        self.cancelled
+    }
+}
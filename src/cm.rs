@@ -1,12 +1,16 @@
 use crate::ctype::{isalpha, isdigit, ispunct, isspace};
 use crate::nodes::{
-    AstNode, ListDelimType, ListType, NodeCodeBlock, NodeHeading, NodeHtmlBlock, NodeLink,
-    NodeMath, NodeTable, NodeValue, NodeWikiLink,
+    soft_break_joins_cjk, table_cell_colspan, AstNode, ContainerKind, ListDelimType, ListType,
+    NodeAbbreviationDefinition, NodeAlert, NodeCitation, NodeCodeBlock, NodeComponent,
+    NodeContainer, NodeEntity, NodeExtension, NodeHeading, NodeHtmlBlock, NodeLink, NodeMath,
+    NodeTable, NodeTemplateVariable, NodeValue, NodeWikiLink,
 };
 use crate::nodes::{NodeList, TableAlignment};
 #[cfg(feature = "shortcodes")]
 use crate::parser::shortcodes::NodeShortCode;
-use crate::parser::Options;
+use crate::parser::{
+    CodeFenceStyleType, HeadingStyleType, ListStyleType, Options, OrderedListStyleType,
+};
 use crate::scanners;
 use crate::strings::trim_start_match;
 use crate::{nodes, Plugins};
@@ -341,7 +345,7 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
             .map_or(true, |next| next.data.borrow().value.block());
 
         match node.data.borrow().value {
-            NodeValue::Document => (),
+            NodeValue::Document | NodeValue::Truncated(_) => (),
             NodeValue::FrontMatter(ref fm) => self.format_front_matter(fm.as_bytes(), entering),
             NodeValue::BlockQuote => self.format_block_quote(entering),
             NodeValue::List(..) => self.format_list(node, entering),
@@ -350,33 +354,41 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
             NodeValue::DescriptionItem(..) => (),
             NodeValue::DescriptionTerm => (),
             NodeValue::DescriptionDetails => self.format_description_details(entering),
-            NodeValue::Heading(ref nch) => self.format_heading(nch, entering),
+            NodeValue::Heading(ref nch) => self.format_heading(node, nch, entering),
             NodeValue::CodeBlock(ref ncb) => self.format_code_block(node, ncb, entering),
-            NodeValue::HtmlBlock(ref nhb) => self.format_html_block(nhb, entering),
+            NodeValue::HtmlBlock(NodeHtmlBlock { ref literal, .. })
+            | NodeValue::ComponentBlock(NodeComponent { ref literal, .. })
+            | NodeValue::ExtensionBlock(NodeExtension { ref literal, .. }) => {
+                self.format_html_block(literal, entering)
+            }
+            NodeValue::Raw(ref literal) => self.format_raw(literal, entering),
             NodeValue::ThematicBreak => self.format_thematic_break(entering),
             NodeValue::Paragraph => self.format_paragraph(entering),
             NodeValue::Text(ref literal) => {
                 self.format_text(literal.as_bytes(), allow_wrap, entering)
             }
             NodeValue::LineBreak => self.format_line_break(entering, next_is_block),
-            NodeValue::SoftBreak => self.format_soft_break(allow_wrap, entering),
+            NodeValue::SoftBreak => self.format_soft_break(node, allow_wrap, entering),
             NodeValue::Code(ref code) => {
                 self.format_code(code.literal.as_bytes(), allow_wrap, entering)
             }
-            NodeValue::HtmlInline(ref literal) => {
+            NodeValue::HtmlInline(ref literal)
+            | NodeValue::ComponentInline(NodeComponent { ref literal, .. })
+            | NodeValue::ExtensionInline(NodeExtension { ref literal, .. }) => {
                 self.format_html_inline(literal.as_bytes(), entering)
             }
             NodeValue::Strong => {
                 if parent_node.is_none()
                     || !matches!(parent_node.unwrap().data.borrow().value, NodeValue::Strong)
                 {
-                    self.format_strong();
+                    self.format_strong(node);
                 }
             }
             NodeValue::Emph => self.format_emph(node),
             NodeValue::TaskItem(symbol) => self.format_task_item(symbol, node, entering),
             NodeValue::Strikethrough => self.format_strikethrough(),
             NodeValue::Superscript => self.format_superscript(),
+            NodeValue::Subscript => self.format_subscript(),
             NodeValue::Link(ref nl) => return self.format_link(node, nl, entering),
             NodeValue::Image(ref nl) => self.format_image(nl, allow_wrap, entering),
             #[cfg(feature = "shortcodes")]
@@ -399,6 +411,15 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
             NodeValue::Underline => self.format_underline(),
             NodeValue::SpoileredText => self.format_spoiler(),
             NodeValue::EscapedTag(ref net) => self.format_escaped_tag(net),
+            NodeValue::Entity(ref ne) => self.format_entity(ne, entering),
+            NodeValue::TemplateVariable(ref ntv) => self.format_template_variable(ntv, entering),
+            NodeValue::TemplateTag(ref literal) => self.format_template_tag(literal, entering),
+            NodeValue::Container(ref nc) => self.format_container(nc, entering),
+            NodeValue::Alert(ref nalert) => self.format_alert(nalert, entering),
+            NodeValue::Citation(ref nc) => self.format_citation(nc, entering),
+            NodeValue::AbbreviationDefinition(ref nad) => {
+                self.format_abbreviation_definition(nad, entering)
+            }
         };
         true
     }
@@ -470,7 +491,11 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
             if entering {
                 *last_stack += 1;
             }
-            let list_delim = parent.delimiter;
+            let list_delim = match self.options.render.ordered_list_style {
+                OrderedListStyleType::Preserve => parent.delimiter,
+                OrderedListStyleType::Period => ListDelimType::Period,
+                OrderedListStyleType::Paren => ListDelimType::Paren,
+            };
             write!(
                 listmarker,
                 "{}{}{}",
@@ -488,7 +513,13 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
 
         if entering {
             if parent.list_type == ListType::Bullet {
-                let bullet = char::from(self.options.render.list_style as u8);
+                let bullet = match self.options.render.list_style {
+                    ListStyleType::Preserve if parent.bullet_char != 0 => {
+                        char::from(parent.bullet_char)
+                    }
+                    ListStyleType::Preserve => '-',
+                    style => char::from(style as u8),
+                };
                 write!(self, "{} ", bullet).unwrap();
             } else {
                 self.write_all(&listmarker).unwrap();
@@ -510,16 +541,43 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
         }
     }
 
-    fn format_heading(&mut self, nch: &NodeHeading, entering: bool) {
+    fn format_heading(&mut self, node: &'a AstNode<'a>, nch: &NodeHeading, entering: bool) {
+        // A setext heading needs content for its underline to apply to.
+        let setext = match self.options.render.heading_style {
+            HeadingStyleType::Atx => false,
+            HeadingStyleType::Setext => true,
+            HeadingStyleType::Preserve => nch.setext,
+        } && nch.level <= 2
+            && node.first_child().is_some();
+
         if entering {
-            for _ in 0..nch.level {
-                write!(self, "#").unwrap();
+            if !setext {
+                for _ in 0..nch.level {
+                    write!(self, "#").unwrap();
+                }
+                write!(self, " ").unwrap();
             }
-            write!(self, " ").unwrap();
             self.begin_content = true;
             self.no_linebreaks = true;
         } else {
             self.no_linebreaks = false;
+            if !nch.attributes.is_empty() {
+                write!(self, " {}", nch.attributes).unwrap();
+            }
+            if setext {
+                // Underline the heading's text, not counting any prefix of
+                // its container.
+                let line_start = self
+                    .v
+                    .iter()
+                    .rposition(|&c| c == b'\n')
+                    .map_or(0, |i| i + 1);
+                let text = &self.v[line_start + self.prefix.len().min(self.v.len() - line_start)..];
+                let width = String::from_utf8_lossy(text).chars().count().max(1);
+                let underline = if nch.level == 1 { "=" } else { "-" };
+                self.cr();
+                write!(self, "{}", underline.repeat(width)).unwrap();
+            }
             self.blankline();
         }
     }
@@ -541,8 +599,17 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
                 self.blankline();
             }
 
-            let info = ncb.info.as_bytes();
+            let mut info = ncb.info.clone();
+            if !ncb.attributes.is_empty() {
+                if !info.is_empty() {
+                    info.push(' ');
+                }
+                info.push_str(&ncb.attributes.to_string());
+            }
+            let info = info.as_bytes();
             let literal = ncb.literal.as_bytes();
+            let preserve_fence =
+                self.options.render.code_fence_style == CodeFenceStyleType::Preserve && ncb.fenced;
 
             #[allow(clippy::len_zero)]
             if !(info.len() > 0
@@ -550,6 +617,7 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
                 || isspace(literal[0])
                 || first_in_list_item
                 || self.options.render.prefer_fenced
+                || preserve_fence
                 || isspace(literal[literal.len() - 1]) && isspace(literal[literal.len() - 2]))
             {
                 write!(self, "    ").unwrap();
@@ -558,8 +626,22 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
                 let new_len = self.prefix.len() - 4;
                 self.prefix.truncate(new_len);
             } else {
-                let fence_char = if info.contains(&b'`') { b'~' } else { b'`' };
-                let numticks = max(3, longest_char_sequence(literal, fence_char) + 1);
+                let fence_char = if info.contains(&b'`') {
+                    b'~'
+                } else if preserve_fence {
+                    ncb.fence_char
+                } else {
+                    match self.options.render.code_fence_style {
+                        CodeFenceStyleType::Tilde => b'~',
+                        CodeFenceStyleType::Backtick | CodeFenceStyleType::Preserve => b'`',
+                    }
+                };
+                let mut numticks = max(3, longest_char_sequence(literal, fence_char) + 1);
+                if preserve_fence && ncb.fence_char == fence_char {
+                    numticks = max(numticks, ncb.fence_length);
+                } else {
+                    numticks = max(numticks, self.options.render.code_fence_length);
+                }
                 for _ in 0..numticks {
                     write!(self, "{}", fence_char as char).unwrap();
                 }
@@ -578,10 +660,20 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
         }
     }
 
-    fn format_html_block(&mut self, nhb: &NodeHtmlBlock, entering: bool) {
+    fn format_html_block(&mut self, literal: &str, entering: bool) {
+        if entering {
+            self.blankline();
+            self.write_all(literal.as_bytes()).unwrap();
+            self.blankline();
+        }
+    }
+
+    fn format_raw(&mut self, literal: &str, entering: bool) {
         if entering {
             self.blankline();
-            self.write_all(nhb.literal.as_bytes()).unwrap();
+            writeln!(self, "<!-- comrak:off -->").unwrap();
+            self.write_all(literal.as_bytes()).unwrap();
+            write!(self, "<!-- comrak:on -->").unwrap();
             self.blankline();
         }
     }
@@ -594,6 +686,55 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
         }
     }
 
+    fn format_container(&mut self, nc: &NodeContainer, entering: bool) {
+        self.blankline();
+        for _ in 0..nc.fence_length {
+            write!(self, ":").unwrap();
+        }
+        if entering {
+            if !nc.name.is_empty() {
+                write!(self, " {}", nc.name).unwrap();
+            }
+            match nc.kind {
+                ContainerKind::Named => {
+                    if !nc.attributes.is_empty() {
+                        write!(self, " {}", nc.attributes).unwrap();
+                    }
+                }
+                ContainerKind::Conditional => write!(self, " {}", nc.condition()).unwrap(),
+                ContainerKind::Details => {
+                    if !nc.info.is_empty() {
+                        write!(self, " {}", nc.info).unwrap();
+                    }
+                }
+            }
+        }
+        self.blankline();
+    }
+
+    fn format_alert(&mut self, nalert: &NodeAlert, entering: bool) {
+        if entering {
+            write!(self, "> [!{}]", nalert.alert_type.marker()).unwrap();
+            if let Some(ref title) = nalert.title {
+                write!(self, " {}", title).unwrap();
+            }
+            write!(self.prefix, "> ").unwrap();
+            self.cr();
+        } else {
+            let new_len = self.prefix.len() - 2;
+            self.prefix.truncate(new_len);
+            self.blankline();
+        }
+    }
+
+    fn format_abbreviation_definition(&mut self, nad: &NodeAbbreviationDefinition, entering: bool) {
+        if entering {
+            self.blankline();
+            write!(self, "*[{}]: {}", nad.label, nad.title).unwrap();
+            self.blankline();
+        }
+    }
+
     fn format_paragraph(&mut self, entering: bool) {
         if !entering {
             self.blankline();
@@ -619,9 +760,18 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
         }
     }
 
-    fn format_soft_break(&mut self, allow_wrap: bool, entering: bool) {
+    fn format_soft_break(&mut self, node: &'a AstNode<'a>, allow_wrap: bool, entering: bool) {
         if entering {
-            if !self.no_linebreaks
+            if self.options.render.cjk_soft_breaks
+                && !self.options.render.hardbreaks
+                && soft_break_joins_cjk(node)
+            {
+                // Joining the lines with a space would add one between the
+                // characters.
+                if !self.no_linebreaks {
+                    self.cr();
+                }
+            } else if !self.no_linebreaks
                 && self.options.render.width == 0
                 && !self.options.render.hardbreaks
             {
@@ -667,12 +817,20 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
         }
     }
 
-    fn format_strong(&mut self) {
-        write!(self, "**").unwrap();
+    fn format_strong(&mut self, node: &'a AstNode<'a>) {
+        let delim_char = node.data.borrow().emphasis.map(|e| e.delim_char);
+        // With the underline extension, `__` would produce an underline.
+        if delim_char == Some(b'_') && !self.options.extension.underline {
+            write!(self, "__").unwrap();
+        } else {
+            write!(self, "**").unwrap();
+        }
     }
 
     fn format_emph(&mut self, node: &'a AstNode<'a>) {
-        let emph_delim = if match node.parent() {
+        let emph_delim = if let Some(emphasis) = node.data.borrow().emphasis {
+            emphasis.delim_char
+        } else if match node.parent() {
             Some(parent) => matches!(parent.data.borrow().value, NodeValue::Emph),
             _ => false,
         } && node.next_sibling().is_none()
@@ -694,13 +852,22 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
     }
 
     fn format_strikethrough(&mut self) {
-        write!(self, "~").unwrap();
+        // Single tildes are subscript with the extension enabled.
+        if self.options.extension.subscript {
+            write!(self, "~~").unwrap();
+        } else {
+            write!(self, "~").unwrap();
+        }
     }
 
     fn format_superscript(&mut self) {
         write!(self, "^").unwrap();
     }
 
+    fn format_subscript(&mut self) {
+        write!(self, "~").unwrap();
+    }
+
     fn format_underline(&mut self) {
         write!(self, "__").unwrap();
     }
@@ -713,6 +880,30 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
         self.output(net.as_bytes(), false, Escaping::Literal);
     }
 
+    fn format_entity(&mut self, ne: &NodeEntity, entering: bool) {
+        if entering {
+            self.output(ne.literal.as_bytes(), false, Escaping::Literal);
+        }
+    }
+
+    fn format_template_variable(&mut self, ntv: &NodeTemplateVariable, entering: bool) {
+        if entering {
+            self.output(ntv.placeholder().as_bytes(), false, Escaping::Literal);
+        }
+    }
+
+    fn format_template_tag(&mut self, literal: &str, entering: bool) {
+        if entering {
+            self.output(literal.as_bytes(), false, Escaping::Literal);
+        }
+    }
+
+    fn format_citation(&mut self, nc: &NodeCitation, entering: bool) {
+        if entering {
+            self.output(nc.source().as_bytes(), false, Escaping::Literal);
+        }
+    }
+
     fn format_link(&mut self, node: &'a AstNode<'a>, nl: &NodeLink, entering: bool) -> bool {
         if is_autolink(node, nl) {
             if entering {
@@ -724,6 +915,16 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
         } else {
             write!(self, "](").unwrap();
             self.output(nl.url.as_bytes(), false, Escaping::Url);
+            if nl.width.is_some() || nl.height.is_some() {
+                write!(self, " =").unwrap();
+                if let Some(width) = nl.width {
+                    write!(self, "{}", width).unwrap();
+                }
+                write!(self, "x").unwrap();
+                if let Some(height) = nl.height {
+                    write!(self, "{}", height).unwrap();
+                }
+            }
             if !nl.title.is_empty() {
                 write!(self, " \"").unwrap();
                 self.output(nl.title.as_bytes(), false, Escaping::Title);
@@ -759,6 +960,16 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
         } else {
             write!(self, "](").unwrap();
             self.output(nl.url.as_bytes(), false, Escaping::Url);
+            if nl.width.is_some() || nl.height.is_some() {
+                write!(self, " =").unwrap();
+                if let Some(width) = nl.width {
+                    write!(self, "{}", width).unwrap();
+                }
+                write!(self, "x").unwrap();
+                if let Some(height) = nl.height {
+                    write!(self, "{}", height).unwrap();
+                }
+            }
             if !nl.title.is_empty() {
                 self.output(&[b' ', b'"'], allow_wrap, Escaping::Literal);
                 self.output(nl.title.as_bytes(), false, Escaping::Title);
@@ -797,7 +1008,7 @@ impl<'a, 'o, 'c> CommonMarkFormatter<'a, 'o, 'c> {
         if entering {
             write!(self, " ").unwrap();
         } else {
-            write!(self, " |").unwrap();
+            write!(self, " {}", "|".repeat(table_cell_colspan(node))).unwrap();
 
             let row = &node.parent().unwrap().data.borrow().value;
             let in_header = match *row {
@@ -921,7 +1132,7 @@ fn shortest_unused_sequence(literal: &[u8], f: u8) -> usize {
     i
 }
 
-fn is_autolink<'a>(node: &'a AstNode<'a>, nl: &NodeLink) -> bool {
+pub(crate) fn is_autolink<'a>(node: &'a AstNode<'a>, nl: &NodeLink) -> bool {
     if nl.url.is_empty() || scanners::scheme(nl.url.as_bytes()).is_none() {
         return false;
     }
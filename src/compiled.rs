@@ -0,0 +1,134 @@
+//! Options and plugins checked once and shared between threads.
+//!
+//! [`Options`] and [`Plugins`] are [`Send`] and [`Sync`], but a server
+//! rendering on many threads would otherwise rebuild or clone them, along
+//! with their adapters, for every request.  A [`CompiledOptions`] holds them
+//! behind an [`Arc`], so it's built once at startup and cloned cheaply.
+//!
+//! ```
+//! # use comrak::{Options, Plugins};
+//! # use comrak::adapters::LinkTitleAdapter;
+//! # use comrak::compiled::CompiledOptions;
+//! # use std::thread;
+//! struct Upcase;
+//! impl LinkTitleAdapter for Upcase {
+//!     fn transform(&self, title: &str, _url: &str) -> String {
+//!         title.to_uppercase()
+//!     }
+//! }
+//!
+//! let mut plugins = Plugins::default();
+//! plugins.render.link_title_adapter = Some(&Upcase);
+//! let compiled = CompiledOptions::new(Options::gfm(), plugins).unwrap();
+//!
+//! let handles: Vec<_> = (1..=2)
+//!     .map(|i| {
+//!         let compiled = compiled.clone();
+//!         thread::spawn(move || compiled.markdown_to_html(&format!("[~~{}~~](/ \"t\")", i)))
+//!     })
+//!     .collect();
+//! let html: Vec<String> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+//! assert_eq!(html, [
+//!     "<p><a href=\"/\" title=\"T\"><del>1</del></a></p>\n",
+//!     "<p><a href=\"/\" title=\"T\"><del>2</del></a></p>\n",
+//! ]);
+//! ```
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{markdown_to_html_with_plugins, Options, Plugins};
+
+/// Options and plugins which have been checked by [`CompiledOptions::new`],
+/// shared between clones.
+#[derive(Debug, Clone)]
+pub struct CompiledOptions {
+    inner: Arc<Compiled>,
+}
+
+#[derive(Debug)]
+struct Compiled {
+    options: Options<'static>,
+    plugins: Plugins<'static>,
+}
+
+impl CompiledOptions {
+    /// Checks `options` and `plugins` for settings which can't work, or do
+    /// nothing, and holds them for sharing.
+    pub fn new(options: Options<'static>, plugins: Plugins<'static>) -> Result<Self, OptionsError> {
+        if let Some(ref delimiter) = options.extension.front_matter_delimiter {
+            if delimiter.is_empty() {
+                return Err(OptionsError::EmptyFrontMatterDelimiter);
+            }
+        }
+
+        if plugins.render.codefence_highlight_cache.is_some()
+            && plugins.render.codefence_syntax_highlighter.is_none()
+        {
+            return Err(OptionsError::HighlightCacheWithoutHighlighter);
+        }
+
+        let mut names = HashSet::new();
+        for extension in options.extensions.iter() {
+            if !names.insert(extension.name()) {
+                return Err(OptionsError::DuplicateExtension(
+                    extension.name().to_string(),
+                ));
+            }
+        }
+
+        Ok(CompiledOptions {
+            inner: Arc::new(Compiled { options, plugins }),
+        })
+    }
+
+    /// The options.
+    pub fn options(&self) -> &Options<'static> {
+        &self.inner.options
+    }
+
+    /// The plugins.
+    pub fn plugins(&self) -> &Plugins<'static> {
+        &self.inner.plugins
+    }
+
+    /// Render Markdown to HTML with the options and plugins.
+    pub fn markdown_to_html(&self, md: &str) -> String {
+        markdown_to_html_with_plugins(md, self.options(), self.plugins())
+    }
+}
+
+/// A problem with the options given to [`CompiledOptions::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OptionsError {
+    /// `extension.front_matter_delimiter` is set to an empty string.
+    EmptyFrontMatterDelimiter,
+
+    /// A `codefence_highlight_cache` plugin is set without a
+    /// `codefence_syntax_highlighter` for it to cache.
+    HighlightCacheWithoutHighlighter,
+
+    /// More than one registered extension has the given name.
+    DuplicateExtension(String),
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            OptionsError::EmptyFrontMatterDelimiter => {
+                f.write_str("the front matter delimiter is empty")
+            }
+            OptionsError::HighlightCacheWithoutHighlighter => {
+                f.write_str("a highlight cache is set without a syntax highlighter")
+            }
+            OptionsError::DuplicateExtension(ref name) => {
+                write!(f, "more than one extension is named {:?}", name)
+            }
+        }
+    }
+}
+
+impl Error for OptionsError {}
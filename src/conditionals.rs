@@ -0,0 +1,79 @@
+//! Resolution of conditional blocks, enabled with
+//! [`ExtensionOptions::conditional_blocks`](crate::ExtensionOptions::conditional_blocks).
+
+use crate::nodes::{AstNode, ContainerKind, NodeContainer, NodeValue};
+
+/// Keeps or drops each conditional block in the document according to
+/// `keep`.  A kept block is replaced by its contents; a dropped one is removed
+/// along with them.  Blocks nested within a dropped block are not considered.
+///
+/// [`NodeContainer::matches`] checks a condition against a set of flags:
+///
+/// ```
+/// # use comrak::{format_html, parse_document, Arena, Options};
+/// # use comrak::conditionals::resolve_conditionals;
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.conditional_blocks = true;
+/// let root = parse_document(
+///     &arena,
+///     concat!(
+///         "::: if edition=\"enterprise\"\n",
+///         "Contact your account manager.\n",
+///         ":::\n",
+///         "\n",
+///         "::: if edition=\"community\"\n",
+///         "Ask on the forum.\n",
+///         ":::\n",
+///     ),
+///     &options,
+/// );
+///
+/// let flags = [("edition", "community")];
+/// resolve_conditionals(root, |ncond| ncond.matches(&flags));
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(String::from_utf8(html).unwrap(), "<p>Ask on the forum.</p>\n");
+/// ```
+pub fn resolve_conditionals<'a, F>(root: &'a AstNode<'a>, mut keep: F)
+where
+    F: FnMut(&NodeContainer) -> bool,
+{
+    resolve(root, &mut keep);
+}
+
+fn resolve<'a, F>(node: &'a AstNode<'a>, keep: &mut F)
+where
+    F: FnMut(&NodeContainer) -> bool,
+{
+    let mut child = node.first_child();
+    while let Some(c) = child {
+        let kept = match c.data.borrow().value {
+            NodeValue::Container(ref nc) if nc.kind == ContainerKind::Conditional => Some(keep(nc)),
+            _ => None,
+        };
+
+        match kept {
+            Some(true) => {
+                child = c.first_child().or_else(|| c.next_sibling());
+                while let Some(grandchild) = c.first_child() {
+                    c.insert_before(grandchild);
+                }
+                c.detach();
+            }
+            Some(false) => {
+                child = c.next_sibling();
+                c.detach();
+            }
+            None => {
+                // Conditional blocks are only found among blocks, and inlines
+                // can be nested deeply enough to overflow the stack.
+                if c.data.borrow().value.block() {
+                    resolve(c, keep);
+                }
+                child = c.next_sibling();
+            }
+        }
+    }
+}
@@ -0,0 +1,555 @@
+use crate::arena_tree::NodeEdge;
+use crate::cm::is_autolink;
+use crate::line_writer::LineWriter;
+use crate::nodes::{
+    table_cell_colspan, AstNode, ContainerKind, ListDelimType, ListType, NodeCodeBlock,
+    NodeComponent, NodeHtmlBlock, NodeLink, NodeList, NodeMath, NodeValue, TableAlignment,
+};
+use crate::parser::{Options, Plugins};
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Formats an AST as [Djot](https://djot.net), modified by the given options.
+///
+/// Constructs without a Djot equivalent are written as closely as possible:
+/// underline as an insertion, spoilers as spans with the `spoiler` class,
+/// raw HTML as raw `html` content, extension nodes as text, conditional blocks as divs with the
+/// condition's attributes, and details blocks as divs with the `details` class
+/// and a `summary` attribute.  Abbreviation definitions are dropped, and front
+/// matter is written as-is.  Note that Djot always applies smart punctuation.
+pub fn format_document<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    format_document_with_plugins(root, options, output, &Plugins::default())
+}
+
+/// Formats an AST as Djot, modified by the given options. Accepts custom
+/// plugins.
+pub fn format_document_with_plugins<'a>(
+    root: &'a AstNode<'a>,
+    _options: &Options,
+    output: &mut dyn Write,
+    _plugins: &Plugins,
+) -> io::Result<()> {
+    let mut f = DjotFormatter {
+        w: LineWriter::new(),
+        fences: div_fences(root),
+        numbers: vec![],
+        single_line: false,
+        start: 0,
+    };
+    f.format(root);
+    f.w.finish(output)
+}
+
+struct DjotFormatter<'a> {
+    w: LineWriter,
+    fences: HashMap<*const AstNode<'a>, usize>,
+    // The number of the next item of each list being written.
+    numbers: Vec<usize>,
+    // Whether line breaks are written as spaces, as in headings and table
+    // cells.
+    single_line: bool,
+    // Where the heading being written starts in the output.
+    start: usize,
+}
+
+impl<'a> DjotFormatter<'a> {
+    fn format(&mut self, root: &'a AstNode<'a>) {
+        enum Phase {
+            Pre,
+            Post,
+        }
+        let mut stack = vec![(root, Phase::Pre)];
+
+        while let Some((node, phase)) = stack.pop() {
+            let block = is_block(&node.data.borrow().value);
+            match phase {
+                Phase::Pre => {
+                    if block {
+                        self.w.open(separator(&node.data.borrow().value));
+                    }
+                    if self.format_node(node, true) {
+                        stack.push((node, Phase::Post));
+                        for ch in node.reverse_children() {
+                            stack.push((ch, Phase::Pre));
+                        }
+                    } else if block {
+                        self.w.close();
+                    }
+                }
+                Phase::Post => {
+                    self.format_node(node, false);
+                    if block {
+                        self.w.close();
+                    }
+                }
+            }
+        }
+    }
+
+    // Writes the start or end of `node`, returning whether its children
+    // should be written.
+    fn format_node(&mut self, node: &'a AstNode<'a>, entering: bool) -> bool {
+        match node.data.borrow().value {
+            NodeValue::Document => (),
+            NodeValue::FrontMatter(ref front_matter) => {
+                self.w.write(front_matter.trim_end());
+                return false;
+            }
+            NodeValue::List(ref nl) => {
+                if entering {
+                    self.numbers.push(nl.start);
+                } else {
+                    self.numbers.pop();
+                }
+            }
+            NodeValue::BlockQuote
+            | NodeValue::MultilineBlockQuote(_)
+            | NodeValue::Item(_)
+            | NodeValue::TaskItem(_)
+            | NodeValue::FootnoteDefinition(_)
+                if !entering =>
+            {
+                if !self.w.block_written() {
+                    self.w.write_prefix();
+                }
+                self.w.pop_prefix();
+            }
+            NodeValue::BlockQuote | NodeValue::MultilineBlockQuote(_) => {
+                self.w.push_prefix("> ".to_string(), "> ".to_string());
+            }
+            NodeValue::Item(_) | NodeValue::TaskItem(_) => {
+                let number = self.numbers.last_mut().unwrap();
+                let mut marker = match node.parent().unwrap().data.borrow().value {
+                    NodeValue::List(ref nl) => list_marker(nl, *number),
+                    _ => "-".to_string(),
+                };
+                *number += 1;
+                if let NodeValue::TaskItem(symbol) = node.data.borrow().value {
+                    marker.push_str(if symbol.is_some() { " [x]" } else { " [ ]" });
+                }
+                marker.push(' ');
+                let indent = " ".repeat(marker.len());
+                self.w.push_prefix(marker, indent);
+            }
+            NodeValue::FootnoteDefinition(ref nfd) => {
+                self.w
+                    .push_prefix(format!("[^{}]: ", nfd.name), "  ".to_string());
+            }
+            NodeValue::DescriptionList | NodeValue::DescriptionItem(_) => (),
+            NodeValue::DescriptionTerm => {
+                if entering {
+                    self.w.write(": ");
+                    self.w.start_contents("");
+                }
+                self.w.set_single_line(entering);
+            }
+            NodeValue::DescriptionDetails => {
+                if entering {
+                    self.w.push_prefix("  ".to_string(), "  ".to_string());
+                } else {
+                    self.w.pop_prefix();
+                }
+            }
+            NodeValue::CodeBlock(ref ncb) => {
+                self.w.write(&code_block(ncb));
+                return false;
+            }
+            NodeValue::HtmlBlock(NodeHtmlBlock { ref literal, .. })
+            | NodeValue::ComponentBlock(NodeComponent { ref literal, .. })
+            | NodeValue::Raw(ref literal) => {
+                let fence = "`".repeat(fence_length(literal, b'`', 3));
+                self.w.write(&format!(
+                    "{} =html\n{}{}",
+                    fence,
+                    with_newline(literal),
+                    fence
+                ));
+                return false;
+            }
+            NodeValue::ExtensionBlock(ref ne) => {
+                let mut s = String::new();
+                escape(ne.literal.trim_end(), &mut s);
+                if let Some(at) = block_start_escape(&s) {
+                    s.insert(at, '\\');
+                }
+                self.w.write(&s);
+                return false;
+            }
+            NodeValue::Paragraph => {
+                if entering {
+                    self.w.mark();
+                } else if let Some(start) = self.w.marked() {
+                    if let Some(at) = block_start_escape(self.w.since(start)) {
+                        self.w.insert(start + at, "\\");
+                    }
+                }
+            }
+            NodeValue::Heading(ref nh) => {
+                if entering {
+                    self.w.write(&"#".repeat(nh.level as usize));
+                    self.start = self.w.len();
+                    self.w.write(" ");
+                } else {
+                    self.w.trim_end(self.start);
+                }
+                self.single_line = entering;
+            }
+            NodeValue::ThematicBreak => {
+                self.w.write("* * *");
+                return false;
+            }
+            NodeValue::Table(..) => (),
+            NodeValue::TableRow(header) => {
+                if entering {
+                    self.w.write("| ");
+                } else {
+                    self.w.write(" |");
+                    if header {
+                        if let NodeValue::Table(ref nt) = node.parent().unwrap().data.borrow().value
+                        {
+                            let separators: Vec<&str> = nt
+                                .alignments
+                                .iter()
+                                .map(|alignment| match alignment {
+                                    TableAlignment::None => "---",
+                                    TableAlignment::Left => ":--",
+                                    TableAlignment::Center => ":-:",
+                                    TableAlignment::Right => "--:",
+                                })
+                                .collect();
+                            self.w.write(&format!("\n|{}|", separators.join("|")));
+                        }
+                    }
+                }
+            }
+            NodeValue::TableCell => {
+                if entering {
+                    if node.previous_sibling().is_some() {
+                        self.w.write(" | ");
+                    }
+                } else {
+                    // Djot has no spanning cells, so a cell spanning several
+                    // columns is followed by empty ones.
+                    for _ in 1..table_cell_colspan(node) {
+                        self.w.write(" | ");
+                    }
+                }
+                self.single_line = entering;
+            }
+            NodeValue::Container(ref nc) if nc.kind == ContainerKind::Conditional => {
+                if entering {
+                    let attributes: Vec<String> = nc
+                        .attributes
+                        .attributes
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", key, quote(value)))
+                        .collect();
+                    self.w.write(&format!(
+                        "{{{}}}\n{}",
+                        attributes.join(" "),
+                        self.fence(node)
+                    ));
+                }
+                self.div_contents(node, entering);
+            }
+            NodeValue::Alert(ref nalert) => {
+                if entering {
+                    self.w.push_prefix("> ".to_string(), "> ".to_string());
+                    let mut s = "*".to_string();
+                    escape(nalert.title(), &mut s);
+                    s.push('*');
+                    self.w.write(&s);
+                } else {
+                    self.w.pop_prefix();
+                }
+            }
+            NodeValue::Container(ref nc) if nc.kind == ContainerKind::Details => {
+                if entering {
+                    if let Some(summary) = nc.summary() {
+                        self.w.write(&format!("{{summary={}}}\n", quote(summary)));
+                    }
+                    self.w.write(&format!("{} details", self.fence(node)));
+                }
+                self.div_contents(node, entering);
+            }
+            NodeValue::Container(ref nc) => {
+                if entering {
+                    if !nc.attributes.is_empty() {
+                        self.w.write(&format!("{}\n", nc.attributes));
+                    }
+                    self.w.write(&format!("{} {}", self.fence(node), nc.name));
+                }
+                self.div_contents(node, entering);
+            }
+            NodeValue::AbbreviationDefinition(_) | NodeValue::Truncated(_) => return false,
+
+            NodeValue::Emph => self.w.write("_"),
+            NodeValue::Strong => self.w.write("*"),
+            NodeValue::Strikethrough => self.w.write(if entering { "{-" } else { "-}" }),
+            NodeValue::Superscript => self.w.write("^"),
+            NodeValue::Subscript => self.w.write("~"),
+            NodeValue::Underline => self.w.write(if entering { "{+" } else { "+}" }),
+            NodeValue::SpoileredText => self.w.write(if entering { "[" } else { "]{.spoiler}" }),
+            NodeValue::Link(ref nl) if is_autolink(node, nl) => {
+                self.w.write(&format!("<{}>", nl.url));
+                return false;
+            }
+            NodeValue::Link(ref nl) => {
+                if entering {
+                    self.w.write("[");
+                } else {
+                    self.w.write(&link_destination(nl));
+                }
+            }
+            NodeValue::Image(ref nl) => {
+                if entering {
+                    self.w.write("![");
+                } else {
+                    self.w.write(&link_destination(nl));
+                }
+            }
+            NodeValue::WikiLink(ref nwl) => {
+                if entering {
+                    self.w.write("[");
+                } else {
+                    self.w.write(&format!("]({})", nwl.url));
+                }
+            }
+            _ if !node.data.borrow().value.block() && node.first_child().is_none() => {
+                let mut s = String::new();
+                self.inline_leaf(node, &mut s);
+                self.w.write(&s);
+                return false;
+            }
+            _ => (),
+        }
+        true
+    }
+
+    // Writes an inline node without children.
+    fn inline_leaf(&self, node: &'a AstNode<'a>, s: &mut String) {
+        match node.data.borrow().value {
+            NodeValue::Text(ref literal) => escape(literal, s),
+            NodeValue::Entity(ref ne) => escape(&ne.characters, s),
+            NodeValue::SoftBreak | NodeValue::LineBreak if self.single_line => s.push(' '),
+            NodeValue::SoftBreak => s.push('\n'),
+            NodeValue::LineBreak => s.push_str("\\\n"),
+            NodeValue::Code(ref nc) => verbatim(&nc.literal, s),
+            NodeValue::HtmlInline(ref literal)
+            | NodeValue::TemplateTag(ref literal)
+            | NodeValue::ComponentInline(NodeComponent { ref literal, .. }) => {
+                verbatim(literal, s);
+                s.push_str("{=html}");
+            }
+            NodeValue::ExtensionInline(ref ne) => escape(&ne.literal, s),
+            NodeValue::FootnoteReference(ref nfr) => {
+                s.push_str("[^");
+                s.push_str(&nfr.name);
+                s.push(']');
+            }
+            NodeValue::Math(NodeMath {
+                ref literal,
+                display_math,
+                ..
+            }) => {
+                s.push_str(if display_math { "$$" } else { "$" });
+                verbatim(literal, s);
+            }
+            #[cfg(feature = "shortcodes")]
+            NodeValue::ShortCode(ref nsc) => {
+                s.push(':');
+                s.push_str(&nsc.code);
+                s.push(':');
+            }
+            NodeValue::EscapedTag(ref net) => escape(net, s),
+            NodeValue::TemplateVariable(ref ntv) => escape(&ntv.placeholder(), s),
+            NodeValue::Citation(ref nc) => escape(&nc.source(), s),
+            _ => (),
+        }
+    }
+
+    fn fence(&self, node: &'a AstNode<'a>) -> String {
+        let key: *const AstNode<'a> = node;
+        ":".repeat(self.fences[&key])
+    }
+
+    // Writes the line breaks around the contents of a div.
+    fn div_contents(&mut self, node: &'a AstNode<'a>, entering: bool) {
+        if entering {
+            self.w.start_contents("\n");
+        } else {
+            let before = if self.w.end_contents() { "\n\n" } else { "\n" };
+            self.w.write(&format!("{}{}", before, self.fence(node)));
+        }
+    }
+}
+
+fn is_block(value: &NodeValue) -> bool {
+    value.block() || matches!(value, NodeValue::FrontMatter(_) | NodeValue::Truncated(_))
+}
+
+// The separator between the children of a block.
+fn separator(value: &NodeValue) -> Option<&'static str> {
+    match *value {
+        NodeValue::List(ref nl) if nl.tight => Some("\n"),
+        NodeValue::Table(..) => Some("\n"),
+        NodeValue::DescriptionTerm => Some(" "),
+        NodeValue::Paragraph
+        | NodeValue::Heading(..)
+        | NodeValue::TableRow(..)
+        | NodeValue::TableCell => None,
+        _ => Some("\n\n"),
+    }
+}
+
+// The fence length of each div, longer than that of any div within it.
+fn div_fences<'a>(root: &'a AstNode<'a>) -> HashMap<*const AstNode<'a>, usize> {
+    let mut fences = HashMap::new();
+    // The depth of the divs nested within each node being visited.
+    let mut depths = vec![0];
+    for edge in root.traverse() {
+        match edge {
+            NodeEdge::Start(_) => depths.push(0),
+            NodeEdge::End(node) => {
+                let mut depth = depths.pop().unwrap();
+                if matches!(node.data.borrow().value, NodeValue::Container(_)) {
+                    let key: *const AstNode<'a> = node;
+                    fences.insert(key, 3 + depth);
+                    depth += 1;
+                }
+                let parent = depths.last_mut().unwrap();
+                *parent = (*parent).max(depth);
+            }
+        }
+    }
+    fences
+}
+
+fn list_marker(nl: &NodeList, number: usize) -> String {
+    match nl.list_type {
+        ListType::Bullet => match nl.bullet_char {
+            b'*' | b'+' => (nl.bullet_char as char).to_string(),
+            _ => "-".to_string(),
+        },
+        ListType::Ordered => match nl.delimiter {
+            ListDelimType::Period => format!("{}.", number),
+            ListDelimType::Paren => format!("{})", number),
+        },
+    }
+}
+
+fn code_block(ncb: &NodeCodeBlock) -> String {
+    let fence = "`".repeat(fence_length(&ncb.literal, b'`', 3));
+    let lang = ncb.info.split_whitespace().next().unwrap_or("");
+    let mut s = fence.clone();
+    if !lang.is_empty() {
+        s.push(' ');
+        s.push_str(lang);
+    }
+    s.push('\n');
+    s.push_str(&with_newline(&ncb.literal));
+    s.push_str(&fence);
+    s
+}
+
+// The length of a run of `c` longer than any in `literal`, and at least `min`.
+fn fence_length(literal: &str, c: u8, min: usize) -> usize {
+    let longest = literal
+        .as_bytes()
+        .split(|&b| b != c)
+        .map(<[u8]>::len)
+        .max()
+        .unwrap_or(0);
+    (longest + 1).max(min)
+}
+
+fn with_newline(s: &str) -> String {
+    if s.is_empty() || s.ends_with('\n') {
+        s.to_string()
+    } else {
+        format!("{}\n", s)
+    }
+}
+
+// The end of a link's text, and its destination.
+fn link_destination(nl: &NodeLink) -> String {
+    let mut s = format!("]({})", nl.url);
+    if !nl.title.is_empty() {
+        s.push_str("{title=");
+        s.push_str(&quote(&nl.title));
+        s.push('}');
+    }
+    s
+}
+
+fn quote(value: &str) -> String {
+    let mut s = "\"".to_string();
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s.push('"');
+    s
+}
+
+// Writes `literal` as a verbatim span, with a backtick fence longer than any
+// run of backticks within it.
+fn verbatim(literal: &str, s: &mut String) {
+    let fence = "`".repeat(fence_length(literal, b'`', 1));
+    let pad = if literal.starts_with('`') || literal.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    s.push_str(&fence);
+    s.push_str(pad);
+    s.push_str(literal);
+    s.push_str(pad);
+    s.push_str(&fence);
+}
+
+fn escape(literal: &str, s: &mut String) {
+    for c in literal.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '`' | '[' | ']' | '{' | '}' | '<' | '~' | '^' | '|'
+        ) {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+}
+
+// Where to escape the start of a paragraph which would otherwise begin
+// another kind of block: a heading, block quote, list item, definition or
+// table.
+fn block_start_escape(paragraph: &str) -> Option<usize> {
+    let bytes = paragraph.as_bytes();
+    if let Some(b'#' | b'>' | b'-' | b'+' | b':' | b'(') = bytes.first() {
+        return Some(0);
+    }
+
+    // Djot's ordered list markers are numbers, single letters and roman
+    // numerals.
+    let marker = bytes
+        .iter()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .count();
+    let roman = |c: &u8| b"ivxlcdm".contains(&c.to_ascii_lowercase());
+    if (marker == 1
+        || bytes[..marker].iter().all(u8::is_ascii_digit)
+        || bytes[..marker].iter().all(roman))
+        && marker > 0
+        && matches!(bytes.get(marker), Some(b'.' | b')'))
+        && matches!(bytes.get(marker + 1), None | Some(b' ' | b'\n'))
+    {
+        return Some(marker);
+    }
+    None
+}
@@ -0,0 +1,142 @@
+use crate::nodes::{
+    AstNode, NodeCode, NodeCodeBlock, NodeComponent, NodeEntity, NodeExtension, NodeHtmlBlock,
+    NodeLink, NodeMath, NodeValue, NodeWikiLink,
+};
+use crate::parser::{Options, Plugins};
+use std::io::{self, Write};
+
+// The number of characters of a node's literal to show in its label.
+const LITERAL_LENGTH: usize = 20;
+
+/// Formats an AST as a [Graphviz](https://graphviz.org) DOT graph, for
+/// visualizing its structure.
+///
+/// Each node is labelled with its type, as named in the XML output, its
+/// literal or destination if it has one, truncated, and its source position.
+///
+/// ```
+/// # use comrak::{format_dot, parse_document, Arena, Options};
+/// let arena = Arena::new();
+/// let root = parse_document(&arena, "Hi *you*\n", &Options::default());
+/// let mut dot = vec![];
+/// format_dot(root, &Options::default(), &mut dot).unwrap();
+/// assert_eq!(
+///     String::from_utf8(dot).unwrap(),
+///     concat!(
+///         "digraph ast {\n",
+///         "  node [shape=box, fontname=\"monospace\"];\n",
+///         "  n0 [label=\"document\\n1:1-1:8\"];\n",
+///         "  n1 [label=\"paragraph\\n1:1-1:8\"];\n",
+///         "  n0 -> n1;\n",
+///         "  n2 [label=\"text \\\"Hi \\\"\\n1:1-1:3\"];\n",
+///         "  n1 -> n2;\n",
+///         "  n3 [label=\"emph\\n1:4-1:8\"];\n",
+///         "  n1 -> n3;\n",
+///         "  n4 [label=\"text \\\"you\\\"\\n1:5-1:7\"];\n",
+///         "  n3 -> n4;\n",
+///         "}\n",
+///     )
+/// );
+/// ```
+pub fn format_document<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    format_document_with_plugins(root, options, output, &Plugins::default())
+}
+
+/// Formats an AST as a Graphviz DOT graph. Accepts custom plugins.
+pub fn format_document_with_plugins<'a>(
+    root: &'a AstNode<'a>,
+    _options: &Options,
+    output: &mut dyn Write,
+    _plugins: &Plugins,
+) -> io::Result<()> {
+    output.write_all(b"digraph ast {\n")?;
+    output.write_all(b"  node [shape=box, fontname=\"monospace\"];\n")?;
+
+    // Each node's id is its index in a preorder traversal, and its parent's
+    // id is found on a stack of the ids of its ancestors.
+    let mut ancestors: Vec<(&'a AstNode<'a>, usize)> = vec![];
+    for (id, node) in root.descendants().enumerate() {
+        while let Some(&(ancestor, _)) = ancestors.last() {
+            if node
+                .parent()
+                .map_or(false, |parent| parent.same_node(ancestor))
+            {
+                break;
+            }
+            ancestors.pop();
+        }
+
+        let ast = node.data.borrow();
+        let mut label = ast.value.xml_node_name().to_string();
+        if let Some(literal) = literal(&ast.value) {
+            label.push_str(" \"");
+            label.push_str(&truncate(literal));
+            label.push('"');
+        }
+        label.push('\n');
+        label.push_str(&ast.sourcepos.to_string());
+        writeln!(output, "  n{} [label=\"{}\"];", id, escape(&label))?;
+
+        if let Some(&(_, parent)) = ancestors.last() {
+            writeln!(output, "  n{} -> n{};", parent, id)?;
+        }
+        ancestors.push((node, id));
+    }
+
+    output.write_all(b"}\n")
+}
+
+fn literal(value: &NodeValue) -> Option<&str> {
+    match *value {
+        NodeValue::FrontMatter(ref literal)
+        | NodeValue::Text(ref literal)
+        | NodeValue::Entity(NodeEntity { ref literal, .. })
+        | NodeValue::HtmlInline(ref literal)
+        | NodeValue::EscapedTag(ref literal)
+        | NodeValue::TemplateTag(ref literal)
+        | NodeValue::Raw(ref literal)
+        | NodeValue::CodeBlock(NodeCodeBlock { ref literal, .. })
+        | NodeValue::HtmlBlock(NodeHtmlBlock { ref literal, .. })
+        | NodeValue::Code(NodeCode { ref literal, .. })
+        | NodeValue::Math(NodeMath { ref literal, .. })
+        | NodeValue::ComponentBlock(NodeComponent { ref literal, .. })
+        | NodeValue::ComponentInline(NodeComponent { ref literal, .. })
+        | NodeValue::ExtensionBlock(NodeExtension { ref literal, .. })
+        | NodeValue::ExtensionInline(NodeExtension { ref literal, .. })
+        | NodeValue::Link(NodeLink {
+            url: ref literal, ..
+        })
+        | NodeValue::Image(NodeLink {
+            url: ref literal, ..
+        })
+        | NodeValue::WikiLink(NodeWikiLink { url: ref literal }) => Some(literal),
+        _ => None,
+    }
+}
+
+fn truncate(literal: &str) -> String {
+    match literal.char_indices().nth(LITERAL_LENGTH) {
+        Some((end, _)) => format!("{}…", &literal[..end]),
+        None => literal.to_string(),
+    }
+}
+
+fn escape(label: &str) -> String {
+    let mut s = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' | '\\' => {
+                s.push('\\');
+                s.push(c);
+            }
+            '\n' => s.push_str("\\n"),
+            '\r' => {}
+            _ => s.push(c),
+        }
+    }
+    s
+}
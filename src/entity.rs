@@ -1,7 +1,9 @@
 use crate::ctype::isdigit;
-use entities::ENTITIES;
+use entities::{Codepoints, ENTITIES};
+use once_cell::sync::Lazy;
 use std::char;
 use std::cmp::min;
+use std::collections::HashMap;
 use std::str;
 
 pub const ENTITY_MIN_LENGTH: usize = 2;
@@ -86,9 +88,7 @@ pub fn unescape_html(src: &[u8]) -> Vec<u8> {
 
     while i < size {
         let org = i;
-        while i < size && src[i] != b'&' {
-            i += 1;
-        }
+        i = memchr::memchr(b'&', &src[i..]).map_or(size, |n| i + n);
 
         if i > org {
             if org == 0 && i >= size {
@@ -114,3 +114,27 @@ pub fn unescape_html(src: &[u8]) -> Vec<u8> {
 
     v
 }
+
+/// Returns the shortest named character reference for `c`, including the
+/// leading `&` and trailing `;`, if HTML defines one.
+pub fn named_reference(c: char) -> Option<&'static str> {
+    static NAMES: Lazy<HashMap<char, &'static str>> = Lazy::new(|| {
+        let mut names: HashMap<char, &'static str> = HashMap::new();
+        for e in ENTITIES.iter() {
+            if !e.entity.ends_with(';') {
+                continue;
+            }
+            if let Codepoints::Single(cp) = e.codepoints {
+                if let Some(c) = char::from_u32(cp) {
+                    let name = names.entry(c).or_insert(e.entity);
+                    if e.entity.len() < name.len() {
+                        *name = e.entity;
+                    }
+                }
+            }
+        }
+        names
+    });
+
+    NAMES.get(&c).copied()
+}
@@ -0,0 +1,278 @@
+//! A registry of third-party extensions.
+//!
+//! An extension bundles hooks into block and inline parsing, which add nodes
+//! carrying its own data, with overrides of how nodes are rendered to HTML.
+//! Extensions are registered on [`Options::extensions`](crate::Options::extensions):
+//!
+//! ```
+//! # use comrak::{markdown_to_html, Options};
+//! # use comrak::extensions::ComrakExtension;
+//! # use comrak::nodes::{AstNode, NodeExtension, NodeValue};
+//! # use std::io::{self, Write};
+//! // Renders `@name` as a link to the user's profile.
+//! struct Mentions;
+//!
+//! impl ComrakExtension for Mentions {
+//!     fn name(&self) -> &str {
+//!         "mentions"
+//!     }
+//!
+//!     fn inline_chars(&self) -> &[u8] {
+//!         b"@"
+//!     }
+//!
+//!     fn parse_inline(&self, input: &str) -> Option<(usize, NodeExtension)> {
+//!         let len = 1 + input[1..]
+//!             .find(|c: char| !c.is_ascii_alphanumeric())
+//!             .unwrap_or(input.len() - 1);
+//!         (len > 1).then(|| (len, NodeExtension::default()))
+//!     }
+//!
+//!     fn render_html<'a>(
+//!         &self,
+//!         node: &'a AstNode<'a>,
+//!         entering: bool,
+//!         output: &mut dyn Write,
+//!     ) -> io::Result<bool> {
+//!         match node.data.borrow().value {
+//!             NodeValue::ExtensionInline(ref ne) if entering => {
+//!                 let name = &ne.literal[1..];
+//!                 write!(output, "<a href=\"/users/{}\">@{}</a>", name, name)?;
+//!                 Ok(true)
+//!             }
+//!             _ => Ok(false),
+//!         }
+//!     }
+//! }
+//!
+//! let mut options = Options::default();
+//! options.extensions.register(Box::new(Mentions));
+//! assert_eq!(markdown_to_html("Thanks, @kivikakk!", &options),
+//!            "<p>Thanks, <a href=\"/users/kivikakk\">@kivikakk</a>!</p>\n");
+//! ```
+
+use std::any::Any;
+use std::fmt::{self, Debug};
+use std::io::{self, Write};
+use std::panic::RefUnwindSafe;
+use std::sync::Arc;
+
+use crate::nodes::AstNode;
+
+/// Implement this trait to create an extension.  Every hook has a default
+/// implementation which does nothing, so an extension need only implement
+/// those it uses.
+pub trait ComrakExtension: Send + Sync + RefUnwindSafe {
+    /// The name of the extension.  Nodes it creates carry the name, so it
+    /// should be unique among registered extensions.
+    fn name(&self) -> &str;
+
+    /// Called with a line which could start a block, from its first
+    /// non-space character.  Returning a node opens a block of that node,
+    /// starting with the line.
+    fn open_block(&self, line: &str) -> Option<NodeExtension> {
+        let _ = line;
+        None
+    }
+
+    /// Called with each line following the first of a block the extension
+    /// opened, not including any container markers, to decide whether it
+    /// belongs to the block.
+    fn continue_block(&self, node: &NodeExtension, line: &str) -> BlockContinuation {
+        let _ = (node, line);
+        BlockContinuation::End
+    }
+
+    /// The characters which may start the extension's inline syntax.  Only
+    /// ASCII characters are supported.
+    fn inline_chars(&self) -> &[u8] {
+        &[]
+    }
+
+    /// Called at each of [`inline_chars`](ComrakExtension::inline_chars),
+    /// with the rest of the block's text.  Returning the number of bytes
+    /// matched and a node adds the node in place of those bytes.
+    fn parse_inline(&self, input: &str) -> Option<(usize, NodeExtension)> {
+        let _ = input;
+        None
+    }
+
+    /// Called when entering and leaving each node while formatting HTML,
+    /// before it's rendered as usual.  Returning `true` means the node has
+    /// been written to `output`, and replaces the usual rendering of the node
+    /// itself, but not of its children.
+    fn render_html<'a>(
+        &self,
+        node: &'a AstNode<'a>,
+        entering: bool,
+        output: &mut dyn Write,
+    ) -> io::Result<bool> {
+        let _ = (node, entering, output);
+        Ok(false)
+    }
+
+    /// Called when entering and leaving each node while formatting HTML,
+    /// after it's been rendered, whether as usual or by
+    /// [`render_html`](ComrakExtension::render_html).  Together the two
+    /// hooks can wrap the usual rendering of a node:
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// # use comrak::extensions::ComrakExtension;
+    /// # use comrak::nodes::{AstNode, NodeValue};
+    /// # use std::io::{self, Write};
+    /// struct Figures;
+    ///
+    /// impl ComrakExtension for Figures {
+    ///     fn name(&self) -> &str {
+    ///         "figures"
+    ///     }
+    ///
+    ///     fn render_html<'a>(
+    ///         &self,
+    ///         node: &'a AstNode<'a>,
+    ///         entering: bool,
+    ///         output: &mut dyn Write,
+    ///     ) -> io::Result<bool> {
+    ///         if entering && matches!(node.data.borrow().value, NodeValue::Image(..)) {
+    ///             output.write_all(b"<figure>")?;
+    ///         }
+    ///         Ok(false)
+    ///     }
+    ///
+    ///     fn render_html_after<'a>(
+    ///         &self,
+    ///         node: &'a AstNode<'a>,
+    ///         entering: bool,
+    ///         output: &mut dyn Write,
+    ///     ) -> io::Result<()> {
+    ///         if !entering && matches!(node.data.borrow().value, NodeValue::Image(..)) {
+    ///             output.write_all(b"</figure>")?;
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut options = Options::default();
+    /// options.extensions.register(Box::new(Figures));
+    /// assert_eq!(markdown_to_html("![*a* cat](cat.png)", &options),
+    ///            "<p><figure><img src=\"cat.png\" alt=\"a cat\" /></figure></p>\n");
+    /// ```
+    fn render_html_after<'a>(
+        &self,
+        node: &'a AstNode<'a>,
+        entering: bool,
+        output: &mut dyn Write,
+    ) -> io::Result<()> {
+        let _ = (node, entering, output);
+        Ok(())
+    }
+}
+
+/// Whether a line belongs to an extension's block.  See
+/// [`ComrakExtension::continue_block`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockContinuation {
+    /// The line belongs to the block, which continues after it.
+    Continue,
+
+    /// The line is the last of the block.
+    Close,
+
+    /// The block ended before the line, which is parsed as usual.
+    End,
+}
+
+/// The extensions registered on [`Options`](crate::Options), consulted in the
+/// order they were registered.
+#[derive(Default, Clone)]
+pub struct ExtensionRegistry {
+    extensions: Vec<Arc<dyn ComrakExtension>>,
+}
+
+impl ExtensionRegistry {
+    /// Registers an extension.
+    pub fn register(&mut self, extension: Box<dyn ComrakExtension>) {
+        self.extensions.push(Arc::from(extension));
+    }
+
+    /// The extension registered with the given name, if any.
+    pub fn get(&self, name: &str) -> Option<&dyn ComrakExtension> {
+        self.iter().find(|extension| extension.name() == name)
+    }
+
+    /// Iterates over the registered extensions.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ComrakExtension> {
+        self.extensions.iter().map(|extension| extension.as_ref())
+    }
+
+    /// Whether no extensions are registered.
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty()
+    }
+}
+
+impl Debug for ExtensionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|extension| extension.name()))
+            .finish()
+    }
+}
+
+/// A node created by an extension.  The parser fills in its
+/// [`extension`](NodeExtension::extension) and
+/// [`literal`](NodeExtension::literal); an extension need only attach data.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeExtension {
+    /// The name of the extension which created the node.
+    pub extension: String,
+
+    /// The text of the node: the bytes matched for an inline, or the lines of
+    /// a block.
+    pub literal: String,
+
+    /// Data the extension attached to the node.  Not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub data: Option<UserData>,
+}
+
+impl NodeExtension {
+    /// Attaches `data` to the node.
+    pub fn with_data<T: Any + Send + Sync>(mut self, data: T) -> Self {
+        self.data = Some(UserData::new(data));
+        self
+    }
+}
+
+/// Data of any type attached to a [`NodeExtension`].  Clones share the data,
+/// and are equal only to each other.
+#[derive(Clone)]
+pub struct UserData(Arc<dyn Any + Send + Sync>);
+
+impl UserData {
+    /// Wraps `data`.
+    pub fn new<T: Any + Send + Sync>(data: T) -> Self {
+        UserData(Arc::new(data))
+    }
+
+    /// The data, if it's a `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+}
+
+impl Debug for UserData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("UserData(..)")
+    }
+}
+
+impl PartialEq for UserData {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for UserData {}
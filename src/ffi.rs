@@ -0,0 +1,1104 @@
+//! A C API compatible with the core of cmark-gfm's, so that programs written against
+//! `cmark-gfm.h` can link against comrak instead.  Enabled by the `ffi` feature; the
+//! `comrak-ffi` crate in the repository builds it as a shared and static library, and its
+//! `cbindgen.toml` generates a header.
+//!
+//! Parsing, rendering to HTML, CommonMark and XML, walking the tree with `cmark_iter`, reading
+//! node properties, and the `table`, `strikethrough`, `autolink`, `tagfilter` and `tasklist`
+//! extensions are supported.  Creating and editing nodes is not.
+//!
+//! # Memory
+//!
+//! Comrak allocates the nodes of a document in an arena which is freed all at once, where
+//! cmark-gfm allocates and frees each node individually.  Here each document owns its arena:
+//!
+//! * [`cmark_parse_document`] and [`cmark_parser_finish`] return a document node which owns
+//!   every node parsed into it, and which the caller frees with [`cmark_node_free`].  All of its
+//!   nodes, and all strings returned by accessors such as [`cmark_node_get_literal`], are freed
+//!   with it.
+//! * [`cmark_node_free`] on any other node unlinks it from the tree; its memory is reclaimed when
+//!   its document is freed.  Until then it can still be used, and rendered with its document's
+//!   extensions.
+//! * Rendered output is allocated with `malloc`, and the caller frees it with `free`, as with
+//!   cmark-gfm's default allocator.
+//!
+//! The extensions attached to a parser and its options are remembered by the document, and used
+//! when rendering any of its nodes.
+//!
+//! A panic within any of these functions is caught, and the function returns null, zero or
+//! nothing, rather than unwinding into C.
+
+#![allow(non_camel_case_types)]
+
+use crate::nodes::{can_contain_type, AstNode, ListType, NodeValue};
+use crate::{format_commonmark, format_html, format_xml, Arena, Options, StreamingParser};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::str;
+
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+}
+
+/// A node in a parsed document.  See the [module documentation](self) for its lifetime.
+///
+/// Each node has a single handle, owned by its document, so that handles can be compared.
+#[derive(Debug, Clone, Copy)]
+pub struct cmark_node {
+    node: &'static AstNode<'static>,
+    document: *const Document,
+}
+
+/// A parser fed its input in pieces.
+#[derive(Debug)]
+pub struct cmark_parser {
+    options: c_int,
+    extensions: Vec<&'static cmark_syntax_extension>,
+    parsing: Option<Parsing>,
+}
+
+/// A syntax extension, found by name with [`cmark_find_syntax_extension`].
+#[derive(Debug, Clone, Copy)]
+pub struct cmark_syntax_extension {
+    name: &'static CStr,
+    enable: fn(&mut Options),
+}
+
+/// A list of extensions, as passed to [`cmark_render_html`].  Unused: a document is always
+/// rendered with the extensions it was parsed with.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct cmark_llist {
+    _private: [u8; 0],
+}
+
+/// An iterator over the nodes of a tree.
+#[derive(Debug)]
+pub struct cmark_iter {
+    root: *mut cmark_node,
+    current: (*mut cmark_node, c_int),
+    next: (*mut cmark_node, c_int),
+}
+
+/// Include a `data-sourcepos` attribute on block elements.
+pub const CMARK_OPT_SOURCEPOS: c_int = 1 << 1;
+/// Render soft breaks as hard line breaks.
+pub const CMARK_OPT_HARDBREAKS: c_int = 1 << 2;
+/// Use smart punctuation.
+pub const CMARK_OPT_SMART: c_int = 1 << 10;
+/// Use GitHub-style `<pre lang="x">` for fenced code blocks.
+pub const CMARK_OPT_GITHUB_PRE_LANG: c_int = 1 << 11;
+/// Parse footnotes.
+pub const CMARK_OPT_FOOTNOTES: c_int = 1 << 13;
+/// Include the remainder of the info string in code blocks in a `data-meta` attribute.
+pub const CMARK_OPT_FULL_INFO_STRING: c_int = 1 << 16;
+/// Render raw HTML and dangerous URLs.
+pub const CMARK_OPT_UNSAFE: c_int = 1 << 17;
+
+/// Returned by [`cmark_iter_next`] before iteration starts.
+pub const CMARK_EVENT_NONE: c_int = 0;
+/// Returned by [`cmark_iter_next`] once every node has been visited.
+pub const CMARK_EVENT_DONE: c_int = 1;
+/// Returned by [`cmark_iter_next`] on entering a node.
+pub const CMARK_EVENT_ENTER: c_int = 2;
+/// Returned by [`cmark_iter_next`] on leaving a node which may have children.
+pub const CMARK_EVENT_EXIT: c_int = 3;
+
+/// Returned by [`cmark_node_get_type`] for a null node.
+pub const CMARK_NODE_NONE: c_int = 0;
+/// A document.
+pub const CMARK_NODE_DOCUMENT: c_int = 0x8001;
+/// A block quote.
+pub const CMARK_NODE_BLOCK_QUOTE: c_int = 0x8002;
+/// A list.
+pub const CMARK_NODE_LIST: c_int = 0x8003;
+/// A list item, including a task list item.
+pub const CMARK_NODE_ITEM: c_int = 0x8004;
+/// A code block.
+pub const CMARK_NODE_CODE_BLOCK: c_int = 0x8005;
+/// A block of raw HTML.
+pub const CMARK_NODE_HTML_BLOCK: c_int = 0x8006;
+/// Any block with no cmark-gfm equivalent, such as a description list.
+pub const CMARK_NODE_CUSTOM_BLOCK: c_int = 0x8007;
+/// A paragraph.
+pub const CMARK_NODE_PARAGRAPH: c_int = 0x8008;
+/// A heading.
+pub const CMARK_NODE_HEADING: c_int = 0x8009;
+/// A thematic break.
+pub const CMARK_NODE_THEMATIC_BREAK: c_int = 0x800a;
+/// A footnote definition.
+pub const CMARK_NODE_FOOTNOTE_DEFINITION: c_int = 0x800b;
+/// Text.
+pub const CMARK_NODE_TEXT: c_int = 0xc001;
+/// A soft line break.
+pub const CMARK_NODE_SOFTBREAK: c_int = 0xc002;
+/// A hard line break.
+pub const CMARK_NODE_LINEBREAK: c_int = 0xc003;
+/// A code span.
+pub const CMARK_NODE_CODE: c_int = 0xc004;
+/// Inline raw HTML.
+pub const CMARK_NODE_HTML_INLINE: c_int = 0xc005;
+/// Any inline with no cmark-gfm equivalent, such as a wikilink.
+pub const CMARK_NODE_CUSTOM_INLINE: c_int = 0xc006;
+/// Emphasis.
+pub const CMARK_NODE_EMPH: c_int = 0xc007;
+/// Strong emphasis.
+pub const CMARK_NODE_STRONG: c_int = 0xc008;
+/// A link.
+pub const CMARK_NODE_LINK: c_int = 0xc009;
+/// An image.
+pub const CMARK_NODE_IMAGE: c_int = 0xc00a;
+/// A footnote reference.
+pub const CMARK_NODE_FOOTNOTE_REFERENCE: c_int = 0xc00b;
+
+// The types of the extensions' nodes, which cmark-gfm exports as variables as they're assigned
+// when the extensions are registered.
+
+/// The type of a table node.
+#[no_mangle]
+pub static CMARK_NODE_TABLE: c_int = 0x800c;
+/// The type of a table row node.
+#[no_mangle]
+pub static CMARK_NODE_TABLE_ROW: c_int = 0x800d;
+/// The type of a table cell node.
+#[no_mangle]
+pub static CMARK_NODE_TABLE_CELL: c_int = 0x800e;
+/// The type of a strikethrough node.
+#[no_mangle]
+pub static CMARK_NODE_STRIKETHROUGH: c_int = 0xc00c;
+
+/// Returned by [`cmark_node_get_list_type`] for a node which isn't a list.
+pub const CMARK_NO_LIST: c_int = 0;
+/// A bullet list.
+pub const CMARK_BULLET_LIST: c_int = 1;
+/// An ordered list.
+pub const CMARK_ORDERED_LIST: c_int = 2;
+
+static EXTENSIONS: [cmark_syntax_extension; 5] = [
+    cmark_syntax_extension {
+        name: extension_name(b"table\0"),
+        enable: |options| options.extension.table = true,
+    },
+    cmark_syntax_extension {
+        name: extension_name(b"strikethrough\0"),
+        enable: |options| options.extension.strikethrough = true,
+    },
+    cmark_syntax_extension {
+        name: extension_name(b"autolink\0"),
+        enable: |options| options.extension.autolink = true,
+    },
+    cmark_syntax_extension {
+        name: extension_name(b"tagfilter\0"),
+        enable: |options| options.extension.tagfilter = true,
+    },
+    cmark_syntax_extension {
+        name: extension_name(b"tasklist\0"),
+        enable: |options| options.extension.tasklist = true,
+    },
+];
+
+const fn extension_name(name: &'static [u8]) -> &'static CStr {
+    // SAFETY: every name is NUL-terminated with no interior NUL.
+    unsafe { CStr::from_bytes_with_nul_unchecked(name) }
+}
+
+// A parsed document, owning the arena its nodes are allocated in.
+struct Document {
+    arena: *mut Arena<AstNode<'static>>,
+    options: *mut Options<'static>,
+    root: Option<&'static AstNode<'static>>,
+    // The handles given out for nodes, by node.
+    handles: RefCell<HashMap<usize, Box<cmark_node>>>,
+    // NUL-terminated copies of strings handed out by accessors, by node and field.
+    strings: RefCell<HashMap<(usize, Field), CString>>,
+}
+
+impl Drop for Document {
+    fn drop(&mut self) {
+        self.handles.get_mut().clear();
+        // SAFETY: the arena and options were leaked by `Parsing::new`, and are only freed here,
+        // once nothing refers to them.
+        unsafe {
+            drop(Box::from_raw(self.arena));
+            drop(Box::from_raw(self.options));
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Field {
+    Type,
+    Literal,
+    Info,
+    Url,
+    Title,
+}
+
+impl Document {
+    fn options(&self) -> &Options<'static> {
+        // SAFETY: the options live as long as the document.
+        unsafe { &*self.options }
+    }
+
+    // The handle for `node`, the same each time it's asked for.
+    fn handle(&self, node: Option<&'static AstNode<'static>>) -> *mut cmark_node {
+        let node = match node {
+            Some(node) => node,
+            None => return ptr::null_mut(),
+        };
+        let mut handles = self.handles.borrow_mut();
+        let handle = handles.entry(address(node)).or_insert_with(|| {
+            Box::new(cmark_node {
+                node,
+                document: self,
+            })
+        });
+        &mut **handle
+    }
+
+    // A NUL-terminated copy of `s` for `field` of `node`, which lives as long as the document.
+    fn string(&self, node: &'static AstNode<'static>, field: Field, s: &str) -> *const c_char {
+        let key = (address(node), field);
+        let mut strings = self.strings.borrow_mut();
+        let s = CString::new(s.replace('\0', "\u{fffd}")).unwrap();
+        match strings.get(&key) {
+            Some(existing) if *existing == s => existing.as_ptr(),
+            _ => {
+                let ptr = s.as_ptr();
+                strings.insert(key, s);
+                ptr
+            }
+        }
+    }
+}
+
+impl cmark_node {
+    fn document(&self) -> &Document {
+        // SAFETY: the caller promises not to use a document's nodes after freeing it.
+        unsafe { &*self.document }
+    }
+}
+
+// A document being parsed, by a `cmark_parser` or `cmark_parse_document`.
+#[derive(Debug)]
+struct Parsing {
+    // Declared before `document`, so it's dropped before the arena and options it borrows.
+    parser: StreamingParser<'static, 'static, 'static>,
+    // The start of a character split between the pieces fed so far.
+    partial: Vec<u8>,
+    document: Box<Document>,
+}
+
+impl Parsing {
+    fn new(options: Options<'static>) -> Self {
+        let document = Box::new(Document {
+            arena: Box::into_raw(Box::new(Arena::new())),
+            options: Box::into_raw(Box::new(options)),
+            root: None,
+            handles: RefCell::default(),
+            strings: RefCell::default(),
+        });
+        // SAFETY: the arena and options live until the document is freed, which the caller
+        // promises is after its last use of the document's nodes.
+        let parser = unsafe { StreamingParser::new(&*document.arena, &*document.options) };
+        Parsing {
+            parser,
+            partial: vec![],
+            document,
+        }
+    }
+
+    // Parses the next piece of the document; any character split at its end is held until the
+    // next.  Invalid UTF-8 is replaced, as by `String::from_utf8_lossy`.
+    fn feed(&mut self, input: &[u8]) {
+        let joined;
+        let mut input = if self.partial.is_empty() {
+            input
+        } else {
+            self.partial.extend_from_slice(input);
+            joined = std::mem::take(&mut self.partial);
+            &joined[..]
+        };
+        loop {
+            match str::from_utf8(input) {
+                Ok(valid) => return self.parser.feed(valid),
+                Err(e) => {
+                    let (valid, rest) = input.split_at(e.valid_up_to());
+                    // SAFETY: `from_utf8` checked the bytes up to here.
+                    self.parser.feed(unsafe { str::from_utf8_unchecked(valid) });
+                    match e.error_len() {
+                        Some(len) => {
+                            self.parser.feed("\u{fffd}");
+                            input = &rest[len..];
+                        }
+                        None => {
+                            self.partial = rest.to_vec();
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> *mut cmark_node {
+        let Parsing {
+            mut parser,
+            partial,
+            mut document,
+        } = self;
+        if !partial.is_empty() {
+            parser.feed("\u{fffd}");
+        }
+        let root = parser.finish();
+        document.root = Some(root);
+        // The document is freed by `cmark_node_free`.
+        let document = Box::leak(document);
+        document.handle(Some(root))
+    }
+}
+
+impl std::fmt::Debug for Document {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Document").finish_non_exhaustive()
+    }
+}
+
+// Runs the body of an exported function, returning `default` should it panic, as unwinding into
+// C is undefined behaviour.
+fn guard<T>(default: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(default)
+}
+
+unsafe fn handle<'h>(node: *mut cmark_node) -> Option<&'h cmark_node> {
+    node.as_ref()
+}
+
+fn address(node: &AstNode) -> usize {
+    let ptr: *const AstNode = node;
+    ptr as usize
+}
+
+fn parse_options(options: c_int) -> Options<'static> {
+    let mut parsed = Options::default();
+    parsed.parse.smart = options & CMARK_OPT_SMART != 0;
+    parsed.extension.footnotes = options & CMARK_OPT_FOOTNOTES != 0;
+    parsed
+}
+
+fn render_options(node: &cmark_node, options: c_int) -> Options<'static> {
+    let mut rendered = node.document().options().clone();
+    rendered.render.sourcepos = options & CMARK_OPT_SOURCEPOS != 0;
+    rendered.render.hardbreaks = options & CMARK_OPT_HARDBREAKS != 0;
+    rendered.render.github_pre_lang = options & CMARK_OPT_GITHUB_PRE_LANG != 0;
+    rendered.render.full_info_string = options & CMARK_OPT_FULL_INFO_STRING != 0;
+    rendered.render.unsafe_ = options & CMARK_OPT_UNSAFE != 0;
+    rendered
+}
+
+// Copies `output` into a NUL-terminated buffer from `malloc`.
+fn to_malloced(output: &[u8]) -> *mut c_char {
+    // SAFETY: the buffer is allocated with room for the output and its terminator.
+    unsafe {
+        let buffer = malloc(output.len() + 1) as *mut u8;
+        if buffer.is_null() {
+            return ptr::null_mut();
+        }
+        ptr::copy_nonoverlapping(output.as_ptr(), buffer, output.len());
+        *buffer.add(output.len()) = 0;
+        buffer as *mut c_char
+    }
+}
+
+unsafe fn input<'i>(buffer: *const c_char, len: usize) -> &'i [u8] {
+    if buffer.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(buffer as *const u8, len)
+    }
+}
+
+/// Converts `len` bytes of CommonMark at `text` to HTML, returning a string the caller frees.
+///
+/// # Safety
+///
+/// `text` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_markdown_to_html(
+    text: *const c_char,
+    len: usize,
+    options: c_int,
+) -> *mut c_char {
+    let root = cmark_parse_document(text, len, options);
+    let html = cmark_render_html(root, options, ptr::null_mut());
+    cmark_node_free(root);
+    html
+}
+
+/// Parses `len` bytes of CommonMark at `buffer` into a document, without extensions.
+///
+/// # Safety
+///
+/// `buffer` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_parse_document(
+    buffer: *const c_char,
+    len: usize,
+    options: c_int,
+) -> *mut cmark_node {
+    guard(ptr::null_mut(), || {
+        let mut parsing = Parsing::new(parse_options(options));
+        parsing.feed(input(buffer, len));
+        parsing.finish()
+    })
+}
+
+/// Creates a parser, which the caller frees with [`cmark_parser_free`].
+#[no_mangle]
+pub extern "C" fn cmark_parser_new(options: c_int) -> *mut cmark_parser {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(cmark_parser {
+            options,
+            extensions: vec![],
+            parsing: None,
+        }))
+    })
+}
+
+/// Frees a parser, and any document it was part way through parsing.
+///
+/// # Safety
+///
+/// `parser` must be null or have been returned by [`cmark_parser_new`], and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_parser_free(parser: *mut cmark_parser) {
+    guard((), || {
+        if !parser.is_null() {
+            drop(Box::from_raw(parser));
+        }
+    })
+}
+
+/// Parses the next `len` bytes of input at `buffer`.  Lines, and even characters, may be split
+/// between pieces.  Syntax extensions must be attached before the first piece is fed.
+///
+/// # Safety
+///
+/// `parser` must be a live parser, and `buffer` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_parser_feed(
+    parser: *mut cmark_parser,
+    buffer: *const c_char,
+    len: usize,
+) {
+    guard((), || {
+        if let Some(parser) = parser.as_mut() {
+            parser.parsing().feed(input(buffer, len));
+        }
+    })
+}
+
+/// Finishes parsing the input fed to the parser, returning the document, which the caller frees
+/// with [`cmark_node_free`].  The parser can then be fed another document.
+///
+/// # Safety
+///
+/// `parser` must be a live parser.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_parser_finish(parser: *mut cmark_parser) -> *mut cmark_node {
+    guard(ptr::null_mut(), || match parser.as_mut() {
+        Some(parser) => {
+            parser.parsing();
+            parser.parsing.take().unwrap().finish()
+        }
+        None => ptr::null_mut(),
+    })
+}
+
+impl cmark_parser {
+    // The document being parsed, started with the parser's options and extensions if need be.
+    fn parsing(&mut self) -> &mut Parsing {
+        if self.parsing.is_none() {
+            let mut options = parse_options(self.options);
+            for extension in &self.extensions {
+                (extension.enable)(&mut options);
+            }
+            self.parsing = Some(Parsing::new(options));
+        }
+        self.parsing.as_mut().unwrap()
+    }
+}
+
+/// Finds a syntax extension by name: one of `table`, `strikethrough`, `autolink`, `tagfilter` or
+/// `tasklist`.  Returns null for any other name.
+///
+/// # Safety
+///
+/// `name` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_find_syntax_extension(
+    name: *const c_char,
+) -> *mut cmark_syntax_extension {
+    guard(ptr::null_mut(), || {
+        if name.is_null() {
+            return ptr::null_mut();
+        }
+        let name = CStr::from_ptr(name);
+        EXTENSIONS
+            .iter()
+            .find(|extension| extension.name == name)
+            .map_or(ptr::null_mut(), |extension| {
+                let ptr: *const cmark_syntax_extension = extension;
+                ptr as *mut cmark_syntax_extension
+            })
+    })
+}
+
+/// Enables a syntax extension for the documents the parser parses.  Returns 1 on success.
+///
+/// # Safety
+///
+/// `parser` must be a live parser, and `extension` null or returned by
+/// [`cmark_find_syntax_extension`].
+#[no_mangle]
+pub unsafe extern "C" fn cmark_parser_attach_syntax_extension(
+    parser: *mut cmark_parser,
+    extension: *mut cmark_syntax_extension,
+) -> c_int {
+    guard(0, || match (parser.as_mut(), extension.as_ref()) {
+        (Some(parser), Some(extension)) => {
+            parser.extensions.push(extension);
+            1
+        }
+        _ => 0,
+    })
+}
+
+/// Does nothing: the extensions are always available.
+#[no_mangle]
+pub extern "C" fn cmark_gfm_core_extensions_ensure_registered() {}
+
+/// Renders the tree rooted at `root` as HTML, returning a string the caller frees.  The
+/// extensions the document was parsed with are used, whatever `extensions` holds.
+///
+/// # Safety
+///
+/// `root` must be a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_render_html(
+    root: *mut cmark_node,
+    options: c_int,
+    _extensions: *mut cmark_llist,
+) -> *mut c_char {
+    guard(ptr::null_mut(), || match handle(root) {
+        Some(root) => {
+            let mut output = vec![];
+            format_html(root.node, &render_options(root, options), &mut output).unwrap();
+            to_malloced(&output)
+        }
+        None => ptr::null_mut(),
+    })
+}
+
+/// Renders the tree rooted at `root` as CommonMark wrapped at `width` columns, or not at all if
+/// `width` is zero, returning a string the caller frees.
+///
+/// # Safety
+///
+/// `root` must be a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_render_commonmark(
+    root: *mut cmark_node,
+    options: c_int,
+    width: c_int,
+) -> *mut c_char {
+    guard(ptr::null_mut(), || match handle(root) {
+        Some(root) => {
+            let mut options = render_options(root, options);
+            options.render.width = width.max(0) as usize;
+            let mut output = vec![];
+            format_commonmark(root.node, &options, &mut output).unwrap();
+            to_malloced(&output)
+        }
+        None => ptr::null_mut(),
+    })
+}
+
+/// Renders the tree rooted at `root` as CommonMark XML, returning a string the caller frees.
+///
+/// # Safety
+///
+/// `root` must be a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_render_xml(root: *mut cmark_node, options: c_int) -> *mut c_char {
+    guard(ptr::null_mut(), || match handle(root) {
+        Some(root) => {
+            let mut output = vec![];
+            format_xml(root.node, &render_options(root, options), &mut output).unwrap();
+            to_malloced(&output)
+        }
+        None => ptr::null_mut(),
+    })
+}
+
+/// Frees a document and all of its nodes, or unlinks any other node from its tree.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.  After freeing a document, none of its
+/// nodes or strings may be used.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_free(node: *mut cmark_node) {
+    guard((), || {
+        if let Some(&cmark_node { node, document }) = handle(node) {
+            if (*document).root.map_or(false, |root| ptr::eq(root, node)) {
+                drop(Box::from_raw(document as *mut Document));
+            } else {
+                node.detach();
+            }
+        }
+    })
+}
+
+// The node related to `node` by `related`, or null.
+unsafe fn related(
+    node: *mut cmark_node,
+    related: impl FnOnce(&'static AstNode<'static>) -> Option<&'static AstNode<'static>>,
+) -> *mut cmark_node {
+    guard(ptr::null_mut(), || {
+        handle(node).map_or(ptr::null_mut(), |handle| {
+            handle.document().handle(related(handle.node))
+        })
+    })
+}
+
+/// The next sibling of `node`, or null.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_next(node: *mut cmark_node) -> *mut cmark_node {
+    related(node, |n| n.next_sibling())
+}
+
+/// The previous sibling of `node`, or null.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_previous(node: *mut cmark_node) -> *mut cmark_node {
+    related(node, |n| n.previous_sibling())
+}
+
+/// The parent of `node`, or null.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_parent(node: *mut cmark_node) -> *mut cmark_node {
+    related(node, |n| n.parent())
+}
+
+/// The first child of `node`, or null.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_first_child(node: *mut cmark_node) -> *mut cmark_node {
+    related(node, |n| n.first_child())
+}
+
+/// The last child of `node`, or null.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_last_child(node: *mut cmark_node) -> *mut cmark_node {
+    related(node, |n| n.last_child())
+}
+
+/// The type of `node`, one of the `CMARK_NODE_*` constants.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_type(node: *mut cmark_node) -> c_int {
+    guard(CMARK_NODE_NONE, || {
+        let node = match handle(node) {
+            Some(handle) => handle.node,
+            None => return CMARK_NODE_NONE,
+        };
+        let value = &node.data.borrow().value;
+        match *value {
+            NodeValue::Document => CMARK_NODE_DOCUMENT,
+            NodeValue::BlockQuote => CMARK_NODE_BLOCK_QUOTE,
+            NodeValue::List(..) => CMARK_NODE_LIST,
+            NodeValue::Item(..) | NodeValue::TaskItem(..) => CMARK_NODE_ITEM,
+            NodeValue::CodeBlock(..) => CMARK_NODE_CODE_BLOCK,
+            NodeValue::HtmlBlock(..) => CMARK_NODE_HTML_BLOCK,
+            NodeValue::Paragraph => CMARK_NODE_PARAGRAPH,
+            NodeValue::Heading(..) => CMARK_NODE_HEADING,
+            NodeValue::ThematicBreak => CMARK_NODE_THEMATIC_BREAK,
+            NodeValue::FootnoteDefinition(..) => CMARK_NODE_FOOTNOTE_DEFINITION,
+            NodeValue::Table(..) => CMARK_NODE_TABLE,
+            NodeValue::TableRow(..) => CMARK_NODE_TABLE_ROW,
+            NodeValue::TableCell => CMARK_NODE_TABLE_CELL,
+            NodeValue::Text(..) => CMARK_NODE_TEXT,
+            NodeValue::SoftBreak => CMARK_NODE_SOFTBREAK,
+            NodeValue::LineBreak => CMARK_NODE_LINEBREAK,
+            NodeValue::Code(..) => CMARK_NODE_CODE,
+            NodeValue::HtmlInline(..) => CMARK_NODE_HTML_INLINE,
+            NodeValue::Emph => CMARK_NODE_EMPH,
+            NodeValue::Strong => CMARK_NODE_STRONG,
+            NodeValue::Link(..) => CMARK_NODE_LINK,
+            NodeValue::Image(..) => CMARK_NODE_IMAGE,
+            NodeValue::FootnoteReference(..) => CMARK_NODE_FOOTNOTE_REFERENCE,
+            NodeValue::Strikethrough => CMARK_NODE_STRIKETHROUGH,
+            ref value if value.block() => CMARK_NODE_CUSTOM_BLOCK,
+            _ => CMARK_NODE_CUSTOM_INLINE,
+        }
+    })
+}
+
+/// The name of the type of `node`, as used in CommonMark XML, e.g. `paragraph`; task list items
+/// are `tasklist`, as in cmark-gfm.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_type_string(node: *mut cmark_node) -> *const c_char {
+    guard(ptr::null(), || {
+        let handle = match handle(node) {
+            Some(handle) => handle,
+            None => return b"NONE\0".as_ptr() as *const c_char,
+        };
+        let name = match handle.node.data.borrow().value {
+            NodeValue::TaskItem(..) => "tasklist",
+            ref value => value.xml_node_name(),
+        };
+        handle.document().string(handle.node, Field::Type, name)
+    })
+}
+
+// A string field of `node`, as given by `field`, or null if it has none.
+unsafe fn string_field(
+    node: *mut cmark_node,
+    field: Field,
+    value: impl FnOnce(&NodeValue) -> Option<&String>,
+) -> *const c_char {
+    guard(ptr::null(), || {
+        let handle = match handle(node) {
+            Some(handle) => handle,
+            None => return ptr::null(),
+        };
+        let ast = handle.node.data.borrow();
+        match value(&ast.value) {
+            Some(s) => handle.document().string(handle.node, field, s),
+            None => ptr::null(),
+        }
+    })
+}
+
+/// The literal content of a text, code, HTML or code block node, or null for other nodes.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_literal(node: *mut cmark_node) -> *const c_char {
+    string_field(node, Field::Literal, |value| match *value {
+        NodeValue::Text(ref literal) | NodeValue::HtmlInline(ref literal) => Some(literal),
+        NodeValue::Code(ref code) => Some(&code.literal),
+        NodeValue::CodeBlock(ref ncb) => Some(&ncb.literal),
+        NodeValue::HtmlBlock(ref nhb) => Some(&nhb.literal),
+        _ => None,
+    })
+}
+
+/// The info string of a code block, or null for other nodes.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_fence_info(node: *mut cmark_node) -> *const c_char {
+    string_field(node, Field::Info, |value| match *value {
+        NodeValue::CodeBlock(ref ncb) => Some(&ncb.info),
+        _ => None,
+    })
+}
+
+/// The destination of a link or image, or null for other nodes.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_url(node: *mut cmark_node) -> *const c_char {
+    string_field(node, Field::Url, |value| match *value {
+        NodeValue::Link(ref nl) | NodeValue::Image(ref nl) => Some(&nl.url),
+        _ => None,
+    })
+}
+
+/// The title of a link or image, or null for other nodes.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_title(node: *mut cmark_node) -> *const c_char {
+    string_field(node, Field::Title, |value| match *value {
+        NodeValue::Link(ref nl) | NodeValue::Image(ref nl) => Some(&nl.title),
+        _ => None,
+    })
+}
+
+// An integer property of `node`, as given by `property`, or 0 for a null node.
+unsafe fn int_field(node: *mut cmark_node, property: impl FnOnce(&AstNode) -> c_int) -> c_int {
+    guard(0, || handle(node).map_or(0, |handle| property(handle.node)))
+}
+
+/// The level of a heading, or 0 for other nodes.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_heading_level(node: *mut cmark_node) -> c_int {
+    int_field(node, |n| match n.data.borrow().value {
+        NodeValue::Heading(ref nh) => nh.level as c_int,
+        _ => 0,
+    })
+}
+
+/// The type of a list, [`CMARK_BULLET_LIST`] or [`CMARK_ORDERED_LIST`], or [`CMARK_NO_LIST`]
+/// for other nodes.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_list_type(node: *mut cmark_node) -> c_int {
+    int_field(node, |n| match n.data.borrow().value {
+        NodeValue::List(ref nl) => match nl.list_type {
+            ListType::Bullet => CMARK_BULLET_LIST,
+            ListType::Ordered => CMARK_ORDERED_LIST,
+        },
+        _ => CMARK_NO_LIST,
+    })
+}
+
+/// The starting number of an ordered list, or 0 for other nodes.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_list_start(node: *mut cmark_node) -> c_int {
+    int_field(node, |n| match n.data.borrow().value {
+        NodeValue::List(ref nl) if nl.list_type == ListType::Ordered => nl.start as c_int,
+        _ => 0,
+    })
+}
+
+/// The line `node` starts on, or 0 for a null node.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_start_line(node: *mut cmark_node) -> c_int {
+    int_field(node, |n| n.data.borrow().sourcepos.start.line as c_int)
+}
+
+/// The column `node` starts at, or 0 for a null node.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_start_column(node: *mut cmark_node) -> c_int {
+    int_field(node, |n| n.data.borrow().sourcepos.start.column as c_int)
+}
+
+/// The line `node` ends on, or 0 for a null node.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_end_line(node: *mut cmark_node) -> c_int {
+    int_field(node, |n| n.data.borrow().sourcepos.end.line as c_int)
+}
+
+/// The column `node` ends at, or 0 for a null node.
+///
+/// # Safety
+///
+/// `node` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_node_get_end_column(node: *mut cmark_node) -> c_int {
+    int_field(node, |n| n.data.borrow().sourcepos.end.column as c_int)
+}
+
+/// Creates an iterator over the tree rooted at `root`, which the caller frees with
+/// [`cmark_iter_free`].
+///
+/// Each node is entered, and then exited after its children, except for nodes which can't have
+/// children: code blocks, HTML, thematic breaks, text, breaks and code, which are only entered.
+///
+/// # Safety
+///
+/// `root` must be null or a node of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_iter_new(root: *mut cmark_node) -> *mut cmark_iter {
+    guard(ptr::null_mut(), || {
+        if root.is_null() {
+            return ptr::null_mut();
+        }
+        Box::into_raw(Box::new(cmark_iter {
+            root,
+            current: (ptr::null_mut(), CMARK_EVENT_NONE),
+            next: (root, CMARK_EVENT_ENTER),
+        }))
+    })
+}
+
+/// Frees an iterator.
+///
+/// # Safety
+///
+/// `iter` must be null or have been returned by [`cmark_iter_new`], and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_iter_free(iter: *mut cmark_iter) {
+    guard((), || {
+        if !iter.is_null() {
+            drop(Box::from_raw(iter));
+        }
+    })
+}
+
+/// Moves to the next node, returning the event: [`CMARK_EVENT_ENTER`], [`CMARK_EVENT_EXIT`], or
+/// [`CMARK_EVENT_DONE`] once the whole tree has been visited.
+///
+/// # Safety
+///
+/// `iter` must be a live iterator over a live document.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_iter_next(iter: *mut cmark_iter) -> c_int {
+    guard(CMARK_EVENT_DONE, || {
+        let iter = match iter.as_mut() {
+            Some(iter) => iter,
+            None => return CMARK_EVENT_DONE,
+        };
+        iter.current = iter.next;
+        let (current, event) = iter.current;
+        let (current, document) = match handle(current) {
+            Some(handle) if event != CMARK_EVENT_DONE => (handle.node, handle.document()),
+            _ => return CMARK_EVENT_DONE,
+        };
+
+        iter.next = if event == CMARK_EVENT_ENTER && !is_leaf(current) {
+            match current.first_child() {
+                Some(child) => (document.handle(Some(child)), CMARK_EVENT_ENTER),
+                None => (iter.current.0, CMARK_EVENT_EXIT),
+            }
+        } else if iter.current.0 == iter.root {
+            (ptr::null_mut(), CMARK_EVENT_DONE)
+        } else if let Some(next) = current.next_sibling() {
+            (document.handle(Some(next)), CMARK_EVENT_ENTER)
+        } else if let Some(parent) = current.parent() {
+            (document.handle(Some(parent)), CMARK_EVENT_EXIT)
+        } else {
+            (ptr::null_mut(), CMARK_EVENT_DONE)
+        };
+        event
+    })
+}
+
+/// The node the iterator is at.
+///
+/// # Safety
+///
+/// `iter` must be a live iterator.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_iter_get_node(iter: *mut cmark_iter) -> *mut cmark_node {
+    guard(ptr::null_mut(), || {
+        iter.as_ref().map_or(ptr::null_mut(), |iter| iter.current.0)
+    })
+}
+
+/// The event the iterator last returned.
+///
+/// # Safety
+///
+/// `iter` must be a live iterator.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_iter_get_event_type(iter: *mut cmark_iter) -> c_int {
+    guard(CMARK_EVENT_NONE, || {
+        iter.as_ref()
+            .map_or(CMARK_EVENT_NONE, |iter| iter.current.1)
+    })
+}
+
+/// The root of the tree the iterator walks.
+///
+/// # Safety
+///
+/// `iter` must be a live iterator.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_iter_get_root(iter: *mut cmark_iter) -> *mut cmark_node {
+    guard(ptr::null_mut(), || {
+        iter.as_ref().map_or(ptr::null_mut(), |iter| iter.root)
+    })
+}
+
+/// Moves the iterator to `current`, with the event `event`, so that iteration continues from
+/// there.
+///
+/// # Safety
+///
+/// `iter` must be a live iterator, and `current` a node within its tree.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_iter_reset(
+    iter: *mut cmark_iter,
+    current: *mut cmark_node,
+    event: c_int,
+) {
+    guard((), || {
+        if let Some(iter) = iter.as_mut() {
+            iter.next = (current, event);
+            cmark_iter_next(iter);
+        }
+    })
+}
+
+fn is_leaf(node: &'static AstNode<'static>) -> bool {
+    match node.data.borrow().value {
+        NodeValue::CodeBlock(..)
+        | NodeValue::HtmlBlock(..)
+        | NodeValue::ThematicBreak
+        | NodeValue::Text(..)
+        | NodeValue::SoftBreak
+        | NodeValue::LineBreak
+        | NodeValue::Code(..)
+        | NodeValue::HtmlInline(..) => return true,
+        _ => {}
+    }
+    node.first_child().is_none()
+        && !can_contain_type(node, &NodeValue::Paragraph)
+        && !can_contain_type(node, &NodeValue::Text(String::new()))
+}
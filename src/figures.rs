@@ -0,0 +1,192 @@
+//! Numbering of captioned figures and tables, and generation of lists of
+//! figures and tables.
+//!
+//! A figure is an image with a title, which
+//! [`RenderOptions::figure_with_caption`](crate::RenderOptions::figure_with_caption)
+//! renders as its caption.  A table's caption is the paragraph following it,
+//! if that paragraph begins with `Table:`.
+//!
+//! Numbering a figure or table records its label and anchor in
+//! [`Ast::figure`](crate::nodes::Ast::figure).  The HTML formatter writes the
+//! anchor as the `id` of the `<figure>` or `<table>` element, and the label
+//! before an image's caption.
+
+use crate::html::Anchorizer;
+use crate::nodes::{AstNode, ListDelimType, ListType, NodeFigure, NodeLink, NodeList, NodeValue};
+use typed_arena::Arena;
+
+/// Numbers each figure in the document with a label such as "Figure 1", and
+/// returns an ordered list of links to the figures, by their captions, in
+/// document order.
+///
+/// The list is not attached to the document; insert it wherever the list of
+/// figures should appear.  If the document has no figures, the list is empty.
+///
+/// ```
+/// # use comrak::{format_html, parse_document, Arena, Options};
+/// # use comrak::figures::number_figures;
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.render.figure_with_caption = true;
+/// let root = parse_document(
+///     &arena,
+///     "![](cat.png \"A cat\")\n\n![](dog.png \"A dog\")\n",
+///     &options,
+/// );
+///
+/// let list = number_figures(&arena, root, "Figure");
+/// root.prepend(list);
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(
+///     String::from_utf8(html).unwrap(),
+///     "<ol>\n<li><a href=\"#figure-1\">A cat</a></li>\n\
+///      <li><a href=\"#figure-2\">A dog</a></li>\n</ol>\n\
+///      <p><figure id=\"figure-1\"><img src=\"cat.png\" alt=\"\" title=\"A cat\" />\
+///      <figcaption>Figure 1: A cat</figcaption></figure></p>\n\
+///      <p><figure id=\"figure-2\"><img src=\"dog.png\" alt=\"\" title=\"A dog\" />\
+///      <figcaption>Figure 2: A dog</figcaption></figure></p>\n"
+/// );
+/// ```
+pub fn number_figures<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    root: &'a AstNode<'a>,
+    label: &str,
+) -> &'a AstNode<'a> {
+    let mut list = List::new(arena, label);
+    for node in root.descendants() {
+        let caption = match node.data.borrow().value {
+            NodeValue::Image(ref link) if !link.title.is_empty() => link.title.clone(),
+            _ => continue,
+        };
+        list.add(node, caption);
+    }
+    list.node
+}
+
+/// Numbers each captioned table in the document with a label such as
+/// "Table 1", which replaces the `Table:` beginning its caption, and returns
+/// an ordered list of links to the tables, by their captions, in document
+/// order.
+///
+/// The list is not attached to the document; insert it wherever the list of
+/// tables should appear.  If the document has no captioned tables, the list
+/// is empty.
+///
+/// ```
+/// # use comrak::{format_html, parse_document, Arena, Options};
+/// # use comrak::figures::number_tables;
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.table = true;
+/// let root = parse_document(&arena, "| a |\n|---|\n| b |\n\nTable: Letters\n", &options);
+///
+/// let list = number_tables(&arena, root, "Table");
+/// root.prepend(list);
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(
+///     String::from_utf8(html).unwrap(),
+///     "<ol>\n<li><a href=\"#table-1\">Letters</a></li>\n</ol>\n\
+///      <table id=\"table-1\">\n<thead>\n<tr>\n<th>a</th>\n</tr>\n</thead>\n\
+///      <tbody>\n<tr>\n<td>b</td>\n</tr>\n</tbody>\n</table>\n\
+///      <p>Table 1: Letters</p>\n"
+/// );
+/// ```
+pub fn number_tables<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    root: &'a AstNode<'a>,
+    label: &str,
+) -> &'a AstNode<'a> {
+    let mut list = List::new(arena, label);
+    for node in root.descendants() {
+        if !matches!(node.data.borrow().value, NodeValue::Table(..)) {
+            continue;
+        }
+        let paragraph = match node.next_sibling() {
+            Some(next) if matches!(next.data.borrow().value, NodeValue::Paragraph) => next,
+            _ => continue,
+        };
+        let text = match paragraph.first_child() {
+            Some(text) => text,
+            None => continue,
+        };
+        match text.data.borrow_mut().value {
+            NodeValue::Text(ref mut literal) => match literal.strip_prefix("Table:") {
+                Some(rest) => *literal = rest.trim_start().to_string(),
+                None => continue,
+            },
+            _ => continue,
+        }
+
+        let label = list.add(node, paragraph.text_content());
+        if let NodeValue::Text(ref mut literal) = text.data.borrow_mut().value {
+            literal.insert_str(0, &format!("{}: ", label));
+        }
+    }
+    list.node
+}
+
+// An ordered list of links to numbered figures or tables.
+struct List<'a, 'l> {
+    arena: &'a Arena<AstNode<'a>>,
+    label: &'l str,
+    anchorizer: Anchorizer,
+    nl: NodeList,
+    node: &'a AstNode<'a>,
+}
+
+impl<'a, 'l> List<'a, 'l> {
+    fn new(arena: &'a Arena<AstNode<'a>>, label: &'l str) -> Self {
+        let nl = NodeList {
+            list_type: ListType::Ordered,
+            marker_offset: 0,
+            padding: 3,
+            start: 1,
+            delimiter: ListDelimType::Period,
+            bullet_char: 0,
+            tight: true,
+        };
+        List {
+            arena,
+            label,
+            anchorizer: Anchorizer::new(),
+            nl,
+            node: alloc(arena, NodeValue::List(nl)),
+        }
+    }
+
+    // Numbers `node`, and adds a link to it to the list.  Returns its label.
+    fn add(&mut self, node: &'a AstNode<'a>, caption: String) -> String {
+        let label = format!("{} {}", self.label, self.node.children().count() + 1);
+        let id = self.anchorizer.anchorize(label.clone());
+
+        let link = alloc(
+            self.arena,
+            NodeValue::Link(NodeLink {
+                url: format!("#{}", id),
+                title: String::new(),
+                width: None,
+                height: None,
+            }),
+        );
+        link.append(alloc(self.arena, NodeValue::Text(caption)));
+        let paragraph = alloc(self.arena, NodeValue::Paragraph);
+        paragraph.append(link);
+        let item = alloc(self.arena, NodeValue::Item(self.nl));
+        item.append(paragraph);
+        self.node.append(item);
+
+        node.data.borrow_mut().figure = Some(NodeFigure {
+            label: label.clone(),
+            id,
+        });
+        label
+    }
+}
+
+fn alloc<'a>(arena: &'a Arena<AstNode<'a>>, value: NodeValue) -> &'a AstNode<'a> {
+    arena.alloc(value.into())
+}
@@ -0,0 +1,135 @@
+//! Deserialization of front matter into typed values.
+//!
+//! Front matter delimited by `+++` is parsed as TOML; any other delimiter, such
+//! as the usual `---`, is parsed as YAML.
+
+use crate::nodes::{AstNode, LineColumn, NodeValue};
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use std::fmt;
+
+/// An error encountered while deserializing front matter, as returned by
+/// [`front_matter_as`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontMatterError {
+    message: String,
+    position: Option<LineColumn>,
+}
+
+impl FrontMatterError {
+    /// A description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The position in the source document at which the error occurred, if
+    /// known.
+    pub fn position(&self) -> Option<LineColumn> {
+        self.position
+    }
+}
+
+impl fmt::Display for FrontMatterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(
+                f,
+                "{} at line {} column {}",
+                self.message, position.line, position.column
+            ),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl Error for FrontMatterError {}
+
+/// Deserializes the front matter of a document parsed with
+/// [`ExtensionOptions::front_matter_delimiter`](crate::ExtensionOptions::front_matter_delimiter)
+/// set.  Returns `Ok(None)` if the document has no front matter.
+///
+/// ```
+/// # use comrak::{parse_document, Arena, Options};
+/// # use comrak::front_matter::front_matter_as;
+/// # use std::collections::HashMap;
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.front_matter_delimiter = Some("---".to_owned());
+/// let root = parse_document(&arena, "---\ntitle: Hello\n---\n\n# Hello\n", &options);
+///
+/// let meta: HashMap<String, String> = front_matter_as(root).unwrap().unwrap();
+/// assert_eq!(meta["title"], "Hello");
+///
+/// let root = parse_document(&arena, "---\ntitle: [Hello\n---\n", &options);
+/// let err = front_matter_as::<HashMap<String, String>>(root).unwrap_err();
+/// assert_eq!(err.position(), Some((3, 1).into()));
+/// ```
+pub fn front_matter_as<'a, T: DeserializeOwned>(
+    root: &'a AstNode<'a>,
+) -> Result<Option<T>, FrontMatterError> {
+    let node = match root.first_child() {
+        Some(node) => node,
+        None => return Ok(None),
+    };
+    let ast = node.data.borrow();
+    let front_matter = match ast.value {
+        NodeValue::FrontMatter(ref front_matter) => front_matter,
+        _ => return Ok(None),
+    };
+
+    let front_matter = front_matter.trim_start_matches('\u{feff}');
+    let (delimiter, rest) = front_matter.split_once('\n').unwrap_or((front_matter, ""));
+    let delimiter = delimiter.trim_end_matches('\r');
+
+    // The closing delimiter is always preceded by a newline.
+    let mut body = rest;
+    let mut offset = 0;
+    while let Some(line_end) = body[offset..].find('\n') {
+        let line = &body[offset..offset + line_end];
+        if line.trim_end_matches('\r') == delimiter {
+            break;
+        }
+        offset += line_end + 1;
+    }
+    body = &body[..offset];
+
+    // The body begins on the line after the opening delimiter.
+    let first_line = ast.sourcepos.start.line.max(1) + 1;
+
+    if delimiter == "+++" {
+        toml::from_str(body)
+            .map(Some)
+            .map_err(|e| FrontMatterError {
+                message: e.message().to_string(),
+                position: e
+                    .span()
+                    .map(|span| offset_position(body, span.start, first_line)),
+            })
+    } else {
+        serde_yaml::from_str(body).map(Some).map_err(|e| {
+            let mut message = e.to_string();
+            let position = e.location().map(|location| {
+                // The message ends with the position in the body, which
+                // is replaced by the position in the document.
+                message = message.replacen(
+                    &format!(" at line {} column {}", location.line(), location.column()),
+                    "",
+                    1,
+                );
+                LineColumn {
+                    line: first_line + location.line() - 1,
+                    column: location.column(),
+                }
+            });
+            FrontMatterError { message, position }
+        })
+    }
+}
+
+fn offset_position(body: &str, offset: usize, first_line: usize) -> LineColumn {
+    let before = &body[..offset];
+    LineColumn {
+        line: first_line + before.matches('\n').count(),
+        column: before.len() - before.rfind('\n').map_or(0, |n| n + 1) + 1,
+    }
+}
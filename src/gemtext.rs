@@ -0,0 +1,261 @@
+use crate::cm::is_autolink;
+use crate::nodes::{
+    AstNode, ListType, NodeCode, NodeEntity, NodeLink, NodeMath, NodeValue, NodeWikiLink,
+};
+use crate::parser::{Options, Plugins};
+use std::io::{self, Write};
+
+/// Formats an AST as [Gemini](https://geminiprotocol.net) gemtext, modified
+/// by the given options.
+///
+/// Gemtext has no inline formatting, so emphasis, code spans and the like are
+/// written as their text.  Links and images are written as their text within
+/// a paragraph, followed by a link line for each after the paragraph.  Lists
+/// are flattened, headings deeper than level 3 are written at level 3, and
+/// tables are written as preformatted text.  Raw HTML, components, extension
+/// nodes, front matter, thematic breaks and abbreviation definitions are
+/// dropped.
+pub fn format_document<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    format_document_with_plugins(root, options, output, &Plugins::default())
+}
+
+/// Formats an AST as gemtext, modified by the given options. Accepts custom
+/// plugins.
+pub fn format_document_with_plugins<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+    _plugins: &Plugins,
+) -> io::Result<()> {
+    let gemtext = GemtextFormatter { _options: options }.block(root);
+    output.write_all(gemtext.as_bytes())?;
+    if !gemtext.is_empty() {
+        output.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+struct GemtextFormatter<'o, 'c> {
+    _options: &'o Options<'c>,
+}
+
+impl<'o, 'c> GemtextFormatter<'o, 'c> {
+    // Formats a block node, without a trailing newline.
+    fn block<'a>(&self, node: &'a AstNode<'a>) -> String {
+        match node.data.borrow().value {
+            NodeValue::Document
+            | NodeValue::DescriptionList
+            | NodeValue::DescriptionItem(_)
+            | NodeValue::DescriptionDetails => self.blocks(node, "\n\n"),
+            NodeValue::Container(ref nc) => {
+                let mut blocks = vec![nc.summary().unwrap_or_default().to_string()];
+                blocks.push(self.blocks(node, "\n\n"));
+                blocks.retain(|block| !block.is_empty());
+                blocks.join("\n\n")
+            }
+            NodeValue::Alert(ref nalert) => {
+                let mut blocks = vec![nalert.title().to_string()];
+                blocks.push(self.blocks(node, "\n\n"));
+                blocks.retain(|block| !block.is_empty());
+                quote_lines(&blocks.join("\n\n"))
+            }
+            NodeValue::BlockQuote | NodeValue::MultilineBlockQuote(_) => {
+                quote_lines(&self.blocks(node, "\n\n"))
+            }
+            NodeValue::List(ref nl) => {
+                let mut items = vec![];
+                for (number, item) in (nl.start..).zip(node.children()) {
+                    let marker = match nl.list_type {
+                        ListType::Bullet => "* ".to_string(),
+                        ListType::Ordered => format!("{}. ", number),
+                    };
+                    items.push(self.item(item, &marker));
+                }
+                items.join("\n")
+            }
+            NodeValue::CodeBlock(ref ncb) => {
+                let alt = ncb.info.split_whitespace().next().unwrap_or("");
+                let mut s = format!("```{}\n", alt);
+                s.push_str(&ncb.literal);
+                if !ncb.literal.is_empty() && !ncb.literal.ends_with('\n') {
+                    s.push('\n');
+                }
+                s.push_str("```");
+                s
+            }
+            NodeValue::Heading(ref nh) => {
+                let level = nh.level.min(3) as usize;
+                self.with_links(node, |text| format!("{} {}", "#".repeat(level), text))
+            }
+            NodeValue::Table(_) => {
+                let mut s = "```\n".to_string();
+                for row in node.children() {
+                    let cells: Vec<String> = row
+                        .children()
+                        .map(|cell| self.inlines(cell, &mut vec![]).replace('\n', " "))
+                        .collect();
+                    s.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+                s.push_str("```");
+                s
+            }
+            NodeValue::FootnoteDefinition(ref nfd) => {
+                let content = self.blocks(node, "\n\n");
+                format!("[{}] {}", nfd.name, content)
+            }
+            NodeValue::FrontMatter(_)
+            | NodeValue::Truncated(_)
+            | NodeValue::HtmlBlock(_)
+            | NodeValue::ComponentBlock(_)
+            | NodeValue::ExtensionBlock(_)
+            | NodeValue::Raw(_)
+            | NodeValue::ThematicBreak
+            | NodeValue::AbbreviationDefinition(_) => String::new(),
+            _ => self.with_links(node, |text| text),
+        }
+    }
+
+    fn blocks<'a>(&self, node: &'a AstNode<'a>, separator: &str) -> String {
+        node.children()
+            .map(|child| self.block(child))
+            .filter(|block| !block.is_empty())
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    // Formats a list item.  Its first paragraph follows the marker; nested
+    // lists are flattened into the outer one.
+    fn item<'a>(&self, item: &'a AstNode<'a>, marker: &str) -> String {
+        let mut lines = vec![];
+        for (i, child) in item.children().enumerate() {
+            let block = match child.data.borrow().value {
+                NodeValue::Paragraph if i == 0 => {
+                    self.with_links(child, |text| format!("{}{}", marker, text))
+                }
+                _ => self.block(child),
+            };
+            if !block.is_empty() {
+                lines.push(block);
+            }
+        }
+        if lines.is_empty() {
+            return marker.trim_end().to_string();
+        }
+        lines.join("\n")
+    }
+
+    // Formats the inline content of `node` as text passed to `f`, followed by
+    // a link line for each link and image within it.
+    fn with_links<'a>(&self, node: &'a AstNode<'a>, f: impl FnOnce(String) -> String) -> String {
+        let mut links = vec![];
+        let text = self
+            .inlines(node, &mut links)
+            .split('\n')
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut s = if text.is_empty() {
+            String::new()
+        } else {
+            f(text)
+        };
+        for link in links {
+            if !s.is_empty() {
+                s.push('\n');
+            }
+            s.push_str(&link);
+        }
+        s
+    }
+
+    // Flattens the inline children of `node` to text, collecting a link line
+    // for each link and image.
+    fn inlines<'a>(&self, node: &'a AstNode<'a>, links: &mut Vec<String>) -> String {
+        let mut s = String::new();
+        for child in node.children() {
+            self.inline(child, links, &mut s);
+        }
+        s
+    }
+
+    fn inline<'a>(&self, node: &'a AstNode<'a>, links: &mut Vec<String>, s: &mut String) {
+        match node.data.borrow().value {
+            NodeValue::Text(ref literal)
+            | NodeValue::Entity(NodeEntity {
+                characters: ref literal,
+                ..
+            })
+            | NodeValue::Code(NodeCode { ref literal, .. })
+            | NodeValue::Math(NodeMath { ref literal, .. })
+            | NodeValue::EscapedTag(ref literal)
+            | NodeValue::TemplateTag(ref literal) => s.push_str(literal),
+            NodeValue::SoftBreak => s.push(' '),
+            NodeValue::LineBreak => s.push('\n'),
+            NodeValue::HtmlInline(_)
+            | NodeValue::ComponentInline(_)
+            | NodeValue::ExtensionInline(_) => {}
+            NodeValue::Link(ref nl) if is_autolink(node, nl) => {
+                s.push_str(&nl.url);
+                links.push(format!("=> {}", nl.url));
+            }
+            NodeValue::Link(NodeLink { ref url, .. })
+            | NodeValue::WikiLink(NodeWikiLink { ref url }) => {
+                let text = self.inlines(node, links);
+                links.push(link_line(url, &text));
+                s.push_str(&text);
+            }
+            NodeValue::Image(ref nl) => {
+                let mut nested = vec![];
+                let alt = self.inlines(node, &mut nested);
+                links.push(link_line(&nl.url, &alt));
+            }
+            NodeValue::FootnoteReference(ref nfr) => {
+                s.push('[');
+                s.push_str(&nfr.name);
+                s.push(']');
+            }
+            #[cfg(feature = "shortcodes")]
+            NodeValue::ShortCode(ref nsc) => s.push_str(&nsc.emoji),
+            NodeValue::TemplateVariable(ref ntv) => s.push_str(&ntv.placeholder()),
+            NodeValue::Citation(ref nc) => s.push_str(&nc.source()),
+            _ => {
+                for child in node.children() {
+                    self.inline(child, links, s);
+                }
+            }
+        }
+    }
+}
+
+fn link_line(url: &str, text: &str) -> String {
+    let text = text.replace('\n', " ");
+    if text.is_empty() {
+        format!("=> {}", url)
+    } else {
+        format!("=> {} {}", url, text)
+    }
+}
+
+// Quotes each text line of `s`.  Link lines and preformatted text can't be
+// quoted, so are left as they are.
+fn quote_lines(s: &str) -> String {
+    let mut preformatted = false;
+    let mut lines = vec![];
+    for line in s.split('\n') {
+        if line.starts_with("```") {
+            preformatted = !preformatted;
+            lines.push(line.to_string());
+        } else if preformatted || line.starts_with("=>") {
+            lines.push(line.to_string());
+        } else if line.is_empty() {
+            lines.push(">".to_string());
+        } else {
+            lines.push(format!("> {}", line));
+        }
+    }
+    lines.join("\n")
+}
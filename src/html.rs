@@ -1,20 +1,31 @@
 //! The HTML renderer for the CommonMark AST, as well as helper functions.
 use crate::ctype::isspace;
+use crate::entity;
 use crate::nodes::{
-    AstNode, ListType, NodeCode, NodeFootnoteDefinition, NodeMath, NodeTable, NodeValue,
-    TableAlignment,
+    soft_break_joins_cjk, table_cell_colspan, AstNode, ContainerKind, ListType, NodeAttributes,
+    NodeCode, NodeComponent, NodeEntity, NodeFootnoteDefinition, NodeHtmlBlock, NodeLink, NodeMath,
+    NodeTable, NodeValue, Sourcepos, TableAlignment,
 };
-use crate::parser::{Options, Plugins};
+use crate::parser::{
+    HeaderIdCollisions, HeaderIdStyle, HtmlCommentStyle, LinkTitleStyle, NonAsciiStyle, Options,
+    Plugins, SoftBreakStyle,
+};
+use crate::punycode;
 use crate::scanners;
+use crate::strings::{ltrim_slice, trim_slice};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::borrow::Cow;
 use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::io::{self, Write};
+use std::mem;
 use std::str;
+use unicode_normalization::UnicodeNormalization;
 
-use crate::adapters::HeadingMeta;
+use crate::adapters::{CodeBlockCacheKey, HeadingMeta, ImageMeta, TableCellMeta, UrlRewriter};
+use crate::cancel::{CancellationToken, Cancelled, Checkpoint};
 
 /// Formats an AST as HTML, modified by the given options.
 pub fn format_document<'a>(
@@ -37,13 +48,376 @@ pub fn format_document_with_plugins<'a>(
         last_was_lf: Cell::new(true),
     };
     let mut f = HtmlFormatter::new(options, &mut writer, plugins);
-    f.format(root, false)?;
-    if f.footnote_ix > 0 {
-        f.output.write_all(b"</ol>\n</section>\n")?;
+    if options.extension.abbreviations {
+        f.abbreviations = collect_abbreviations(root);
+    }
+    f.format(root, false)
+}
+
+/// Formats an AST as HTML, modified by the given options, stopping with an
+/// error wrapping [`Cancelled`] if `token` is cancelled first. Accepts custom
+/// plugins. See [`cancel`](crate::cancel).
+///
+/// ```
+/// # use comrak::{format_html_cancellable, parse_document, Arena, Options, Plugins};
+/// # use comrak::cancel::{CancellationToken, Cancelled};
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let root = parse_document(&arena, "Hi\n", &options);
+///
+/// let token = CancellationToken::new();
+/// token.cancel();
+/// let mut html = vec![];
+/// let err = format_html_cancellable(root, &options, &mut html, &Plugins::default(), &token)
+///     .unwrap_err();
+/// assert!(Cancelled::is_cancelled(&err));
+/// ```
+pub fn format_document_cancellable<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+    plugins: &Plugins,
+    token: &CancellationToken,
+) -> io::Result<()> {
+    let mut writer = WriteWithLast {
+        output,
+        last_was_lf: Cell::new(true),
+    };
+    let mut f = HtmlFormatter::new(options, &mut writer, plugins);
+    f.checkpoint = Checkpoint::new(Some(token));
+    if options.extension.abbreviations {
+        f.abbreviations = collect_abbreviations(root);
+    }
+    f.format(root, false)
+}
+
+/// Formats a node of a document and its descendants as HTML, as they're
+/// rendered as part of the whole document.  Heading IDs account for the
+/// headings before the node, abbreviations defined anywhere in the document
+/// apply, table cells keep the alignment of their column, and the alt text
+/// of images is rendered as plain text.  Accepts custom plugins.
+///
+/// ```
+/// # use comrak::{format_html_node, parse_document, Arena, Options, Plugins};
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.header_ids = Some("".to_string());
+/// let root = parse_document(&arena, "# Usage\n\nFirst *words*.\n\n# Usage\n", &options);
+///
+/// let mut html = vec![];
+/// let summary = root.children().nth(1).unwrap();
+/// format_html_node(summary, &options, &mut html, &Plugins::default()).unwrap();
+/// assert_eq!(String::from_utf8(html).unwrap(), "<p>First <em>words</em>.</p>\n");
+///
+/// let mut html = vec![];
+/// let heading = root.last_child().unwrap();
+/// format_html_node(heading, &options, &mut html, &Plugins::default()).unwrap();
+/// assert!(String::from_utf8(html).unwrap().contains("id=\"usage-1\""));
+/// ```
+pub fn format_node<'a>(
+    node: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+    plugins: &Plugins,
+) -> io::Result<()> {
+    let mut writer = WriteWithLast {
+        output,
+        last_was_lf: Cell::new(true),
+    };
+    let mut f = HtmlFormatter::new(options, &mut writer, plugins);
+    let root = node.ancestors().last().unwrap();
+    if options.extension.abbreviations {
+        f.abbreviations = collect_abbreviations(root);
+    }
+    if options.extension.header_ids.is_some() {
+        for before in root.descendants().take_while(|n| !n.same_node(node)) {
+            if let NodeValue::Heading(ref nh) = before.data.borrow().value {
+                match nh.attributes.id {
+                    Some(ref id) => f.anchorizer.reserve(id.clone()),
+                    None => {
+                        let text = f.collect_heading_text(before);
+                        f.anchorizer.anchorize(text);
+                    }
+                }
+            }
+        }
+    }
+    if let Some(info) = node.table_cell_info() {
+        f.table_cell_ix = info.column;
+    }
+
+    let plain = node
+        .ancestors()
+        .skip(1)
+        .any(|n| matches!(n.data.borrow().value, NodeValue::Image(..)));
+    f.format(node, plain)
+}
+
+/// Formats an AST as HTML into a [`fmt::Write`] sink, such as a `String` or
+/// a [`fmt::Formatter`].  Accepts custom plugins.
+///
+/// ```
+/// # use comrak::{format_html_fmt, parse_document, Arena, Options, Plugins};
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let root = parse_document(&arena, "*Hi* \u{1f44b}\n", &options);
+///
+/// let mut html = String::new();
+/// format_html_fmt(root, &options, &mut html, &Plugins::default()).unwrap();
+/// assert_eq!(html, "<p><em>Hi</em> \u{1f44b}</p>\n");
+/// ```
+pub fn format_document_fmt<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn fmt::Write,
+    plugins: &Plugins,
+) -> fmt::Result {
+    let mut writer = FmtWriter {
+        output,
+        pending: vec![],
+    };
+    format_document_with_plugins(root, options, &mut writer, plugins).map_err(|_| fmt::Error)?;
+    if writer.pending.is_empty() {
+        Ok(())
+    } else {
+        Err(fmt::Error)
+    }
+}
+
+// Passes output on to a `fmt::Write`, holding back any UTF-8 sequence split
+// between writes until it's complete.
+struct FmtWriter<'w> {
+    output: &'w mut dyn fmt::Write,
+    pending: Vec<u8>,
+}
+
+impl<'w> Write for FmtWriter<'w> {
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let bytes = if self.pending.is_empty() {
+            buf
+        } else {
+            self.pending.extend_from_slice(buf);
+            &self.pending
+        };
+        let valid = match str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(err) if err.error_len().is_none() => {
+                str::from_utf8(&bytes[..err.valid_up_to()]).unwrap()
+            }
+            Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+        };
+        self.output
+            .write_str(valid)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+        let rest = bytes[valid.len()..].to_vec();
+        self.pending = rest;
+        Ok(buf.len())
+    }
+}
+
+/// Caller-owned buffers the HTML renderer writes into, so that services
+/// rendering many documents can reuse the same allocations between them.
+///
+/// ```
+/// # use comrak::{markdown_to_html_with_buffers, Options, Plugins};
+/// # use comrak::html::RenderBuffers;
+/// let options = Options::default();
+/// let plugins = Plugins::default();
+/// let mut buffers = RenderBuffers::new();
+///
+/// for (md, html) in [("*a*", "<p><em>a</em></p>\n"), ("b", "<p>b</p>\n")] {
+///     assert_eq!(markdown_to_html_with_buffers(md, &options, &plugins, &mut buffers), html);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct RenderBuffers {
+    output: Vec<u8>,
+    text: Vec<u8>,
+}
+
+impl RenderBuffers {
+    /// Construct a new, empty set of buffers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct a set of buffers whose output buffer can hold `capacity`
+    /// bytes without reallocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        RenderBuffers {
+            output: Vec::with_capacity(capacity),
+            text: vec![],
+        }
+    }
+
+    /// The output of the most recent render.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// The output of the most recent render, as a string.
+    pub fn as_str(&self) -> &str {
+        str::from_utf8(&self.output).unwrap()
+    }
+
+    /// Empties the buffers, retaining their allocations. Rendering clears the
+    /// buffers itself; this is only needed to drop a finished document's
+    /// output early.
+    pub fn clear(&mut self) {
+        self.output.clear();
+        self.text.clear();
+    }
+}
+
+/// Formats an AST as HTML into caller-owned `buffers`, replacing their
+/// previous contents. Accepts custom plugins. Read the result with
+/// [`RenderBuffers::output`] or [`RenderBuffers::as_str`].
+pub fn format_document_with_buffers<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    plugins: &Plugins,
+    buffers: &mut RenderBuffers,
+) -> io::Result<()> {
+    buffers.output.clear();
+    let mut writer = WriteWithLast {
+        output: &mut buffers.output,
+        last_was_lf: Cell::new(true),
+    };
+    let mut f = HtmlFormatter::new(options, &mut writer, plugins);
+    f.text_buffer = mem::take(&mut buffers.text);
+    if options.extension.abbreviations {
+        f.abbreviations = collect_abbreviations(root);
+    }
+    let result = f.format(root, false);
+    buffers.text = mem::take(&mut f.text_buffer);
+    result
+}
+
+/// Formats an AST as HTML, modified by the given options, rendering each
+/// top-level block on the rayon thread pool. Accepts custom plugins.
+///
+/// The output is identical to [`format_document_with_plugins`]. This only pays
+/// off for very large documents.  The AST is not thread-safe, so each
+/// top-level block is detached from the document while it's rendered, and
+/// reattached afterwards.
+///
+/// Header IDs and footnotes number things across the whole document, so when
+/// either `extension.header_ids` or `extension.footnotes` is enabled this falls
+/// back to rendering sequentially.
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+pub fn format_document_parallel<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+    plugins: &Plugins,
+) -> io::Result<()> {
+    use rayon::prelude::*;
+
+    if options.extension.header_ids.is_some() || options.extension.footnotes {
+        return format_document_with_plugins(root, options, output, plugins);
+    }
+
+    let abbreviations = if options.extension.abbreviations {
+        collect_abbreviations(root)
+    } else {
+        vec![]
+    };
+
+    // Consecutive blocks are rendered together, a few chunks per thread, as
+    // most blocks are too small to be worth a task of their own.
+    let blocks = DetachedBlocks::new(root);
+    let chunk_size = 1 + blocks.blocks.len() / (4 * rayon::current_num_threads());
+    let chunks: Vec<io::Result<Vec<u8>>> = blocks
+        .blocks
+        .par_chunks(chunk_size)
+        .map(|chunk| render_blocks(chunk, options, plugins, &abbreviations, true))
+        .collect();
+
+    // Chunks are rendered as if they followed a newline; in the rare case the
+    // previous chunk didn't end with one, re-render so `cr()` behaves as it
+    // would have sequentially.
+    let mut last_was_lf = true;
+    for (blocks, chunk) in blocks.blocks.chunks(chunk_size).zip(chunks) {
+        let chunk = if last_was_lf {
+            chunk?
+        } else {
+            render_blocks(blocks, options, plugins, &abbreviations, false)?
+        };
+        if let Some(&last) = chunk.last() {
+            last_was_lf = last == b'\n';
+        }
+        output.write_all(&chunk)?;
     }
+
     Ok(())
 }
 
+/// A top-level block detached from its document.  No node is reachable from
+/// more than one block, so each can be rendered on its own thread without
+/// their `RefCell`s being borrowed from two at once.
+#[cfg(feature = "rayon")]
+#[derive(Clone, Copy)]
+struct DetachedBlock<'a>(&'a AstNode<'a>);
+
+#[cfg(feature = "rayon")]
+unsafe impl Send for DetachedBlock<'_> {}
+#[cfg(feature = "rayon")]
+unsafe impl Sync for DetachedBlock<'_> {}
+
+/// The top-level blocks of a document, detached from it until dropped.
+#[cfg(feature = "rayon")]
+struct DetachedBlocks<'a> {
+    root: &'a AstNode<'a>,
+    blocks: Vec<DetachedBlock<'a>>,
+}
+
+#[cfg(feature = "rayon")]
+impl<'a> DetachedBlocks<'a> {
+    fn new(root: &'a AstNode<'a>) -> Self {
+        let blocks: Vec<_> = root.children().map(DetachedBlock).collect();
+        for block in &blocks {
+            block.0.detach();
+        }
+        DetachedBlocks { root, blocks }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl Drop for DetachedBlocks<'_> {
+    fn drop(&mut self) {
+        for block in &self.blocks {
+            self.root.append(block.0);
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn render_blocks(
+    blocks: &[DetachedBlock],
+    options: &Options,
+    plugins: &Plugins,
+    abbreviations: &[(String, String)],
+    last_was_lf: bool,
+) -> io::Result<Vec<u8>> {
+    let mut output = vec![];
+    let mut writer = WriteWithLast {
+        output: &mut output,
+        last_was_lf: Cell::new(last_was_lf),
+    };
+    let mut f = HtmlFormatter::new(options, &mut writer, plugins);
+    f.abbreviations = abbreviations.to_vec();
+    for block in blocks {
+        f.format(block.0, false)?;
+    }
+    Ok(output)
+}
+
 struct WriteWithLast<'w> {
     output: &'w mut dyn Write,
     last_was_lf: Cell<bool>,
@@ -81,12 +455,57 @@ impl<'w> Write for WriteWithLast<'w> {
 /// ```
 #[derive(Debug, Default)]
 #[doc(hidden)]
-pub struct Anchorizer(HashSet<String>);
+pub struct Anchorizer {
+    used: HashSet<String>,
+    collisions: HeaderIdCollisions,
+    style: HeaderIdStyle,
+}
 
 impl Anchorizer {
     /// Construct a new anchorizer.
     pub fn new() -> Self {
-        Anchorizer(HashSet::new())
+        Anchorizer::default()
+    }
+
+    /// Construct a new anchorizer which resolves collisions as given by
+    /// `collisions`.
+    ///
+    /// ```
+    /// # use comrak::{Anchorizer, HeaderIdCollisions};
+    /// let mut anchorizer = Anchorizer::with_collisions(HeaderIdCollisions::Hash);
+    /// assert_eq!("stuff".to_string(), anchorizer.anchorize("Stuff".to_string()));
+    /// assert_eq!("stuff-21b5fc".to_string(), anchorizer.anchorize("Stuff".to_string()));
+    /// ```
+    pub fn with_collisions(collisions: HeaderIdCollisions) -> Self {
+        Anchorizer {
+            used: HashSet::new(),
+            collisions,
+            style: HeaderIdStyle::default(),
+        }
+    }
+
+    /// Sets how non-ASCII characters are written in anchors.
+    ///
+    /// ```
+    /// # use comrak::{Anchorizer, HeaderIdStyle};
+    /// let mut anchorizer = Anchorizer::new();
+    /// anchorizer.set_style(HeaderIdStyle::Ascii);
+    /// assert_eq!("francais".to_string(), anchorizer.anchorize("Français".to_string()));
+    /// ```
+    pub fn set_style(&mut self, style: HeaderIdStyle) {
+        self.style = style;
+    }
+
+    /// Marks `anchor` as already in use, so that it is never returned.
+    ///
+    /// ```
+    /// # use comrak::Anchorizer;
+    /// let mut anchorizer = Anchorizer::new();
+    /// anchorizer.reserve("stuff".to_string());
+    /// assert_eq!("stuff-1".to_string(), anchorizer.anchorize("Stuff".to_string()));
+    /// ```
+    pub fn reserve(&mut self, anchor: String) {
+        self.used.insert(anchor);
     }
 
     /// Returns a String that has been converted into an anchor using the
@@ -94,6 +513,9 @@ impl Anchorizer {
     /// problem characters and, if needed, adding a suffix to make the
     /// resultant anchor unique.
     ///
+    /// With [`HeaderIdCollisions::Error`], a colliding anchor is returned
+    /// unchanged; use [`try_anchorize`](Self::try_anchorize) to detect it.
+    ///
     /// ```
     /// # use comrak::Anchorizer;
     /// let mut anchorizer = Anchorizer::new();
@@ -101,29 +523,172 @@ impl Anchorizer {
     /// assert_eq!("ticks-arent-in".to_string(), anchorizer.anchorize(source.to_string()));
     /// ```
     pub fn anchorize(&mut self, header: String) -> String {
+        match self.try_anchorize(header) {
+            Ok(id) | Err(id) => id,
+        }
+    }
+
+    /// Like [`anchorize`](Self::anchorize), but returns the colliding anchor
+    /// as an error if it is already in use and the anchorizer was constructed
+    /// with [`HeaderIdCollisions::Error`].
+    ///
+    /// ```
+    /// # use comrak::{Anchorizer, HeaderIdCollisions};
+    /// let mut anchorizer = Anchorizer::with_collisions(HeaderIdCollisions::Error);
+    /// assert_eq!(Ok("stuff".to_string()), anchorizer.try_anchorize("Stuff".to_string()));
+    /// assert_eq!(Err("stuff".to_string()), anchorizer.try_anchorize("Stuff".to_string()));
+    /// ```
+    pub fn try_anchorize(&mut self, header: String) -> Result<String, String> {
         static REJECTED_CHARS: Lazy<Regex> =
             Lazy::new(|| Regex::new(r"[^\p{L}\p{M}\p{N}\p{Pc} -]").unwrap());
 
-        let mut id = header.to_lowercase();
+        let header: String = header.nfc().collect();
+        let mut id = match self.style {
+            HeaderIdStyle::Ascii => deunicode::deunicode(&header)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase(),
+            _ => header.to_lowercase(),
+        };
         id = REJECTED_CHARS.replace_all(&id, "").replace(' ', "-");
+        if self.style == HeaderIdStyle::PercentEncoded {
+            id = id.bytes().fold(String::new(), |mut s, b| {
+                if b.is_ascii() {
+                    s.push(b as char);
+                } else {
+                    s.push_str(&format!("%{:02X}", b));
+                }
+                s
+            });
+        }
+
+        if self.used.contains(&id) && self.collisions == HeaderIdCollisions::Error {
+            return Err(id);
+        }
 
         let mut uniq = 0;
         id = loop {
-            let anchor = if uniq == 0 {
-                Cow::from(&id)
-            } else {
-                Cow::from(format!("{}-{}", id, uniq))
+            let anchor = match (uniq, self.collisions) {
+                (0, _) => Cow::from(&id),
+                (_, HeaderIdCollisions::Hash) => {
+                    Cow::from(format!("{}-{:06x}", id, fnv1a(&id, uniq) & 0xff_ffff))
+                }
+                _ => Cow::from(format!("{}-{}", id, uniq)),
             };
 
-            if !self.0.contains(&*anchor) {
+            if !self.used.contains(&*anchor) {
                 break anchor.into_owned();
             }
 
             uniq += 1;
         };
-        self.0.insert(id.clone());
-        id
+        self.used.insert(id.clone());
+        Ok(id)
+    }
+}
+
+// The abbreviations defined in the document, with longer labels before any
+// they contain.
+fn collect_abbreviations<'a>(root: &'a AstNode<'a>) -> Vec<(String, String)> {
+    let mut definitions = HashMap::new();
+    for node in root.descendants() {
+        if let NodeValue::AbbreviationDefinition(ref nad) = node.data.borrow().value {
+            definitions.insert(nad.label.clone(), nad.title.clone());
+        }
+    }
+
+    let mut abbreviations: Vec<_> = definitions.into_iter().collect();
+    abbreviations.sort_unstable_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+    abbreviations
+}
+
+/// Hashes `id` and the number of collisions seen so far with 32-bit FNV-1a.
+fn fnv1a(id: &str, uniq: u32) -> u32 {
+    id.bytes()
+        .chain(uniq.to_le_bytes())
+        .fold(0x811c_9dc5, |hash, b| {
+            (hash ^ b as u32).wrapping_mul(0x0100_0193)
+        })
+}
+
+/// Returns an anchorizer configured by `options`, with every reserved id that
+/// could collide with a generated one already marked as in use.
+fn options_anchorizer(options: &Options) -> Anchorizer {
+    let mut anchorizer = Anchorizer::with_collisions(options.extension.header_id_collisions);
+    anchorizer.set_style(options.extension.header_id_style);
+    let prefix = options.extension.header_ids.as_deref().unwrap_or("");
+    for reserved in &options.extension.header_ids_reserved {
+        if let Some(anchor) = reserved.strip_prefix(prefix) {
+            anchorizer.reserve(anchor.to_string());
+        }
     }
+    anchorizer
+}
+
+/// A heading anchor, as returned by [`collect_anchors`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadingAnchor {
+    /// The anchor generated for the heading.  The `id` attribute rendered
+    /// with [`ExtensionOptions::header_ids`](crate::ExtensionOptions::header_ids)
+    /// is this anchor preceded by the configured prefix.
+    pub id: String,
+    /// The plain text content of the heading.
+    pub text: String,
+    /// The level of the heading.
+    pub level: u8,
+    /// The position of the heading in the source document.
+    pub sourcepos: Sourcepos,
+}
+
+/// Returns the anchor of every heading in the document, in document order,
+/// exactly as the HTML renderer generates them with
+/// [`ExtensionOptions::header_ids`](crate::ExtensionOptions::header_ids).
+///
+/// Collisions are resolved as configured by
+/// [`ExtensionOptions::header_id_collisions`](crate::ExtensionOptions::header_id_collisions);
+/// with [`HeaderIdCollisions::Error`], colliding anchors are returned as-is.
+///
+/// ```
+/// # use comrak::{parse_document, Arena, Options};
+/// # use comrak::html::collect_anchors;
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let root = parse_document(&arena, "# Intro\n\n## Setup `cargo`\n\n# Intro\n", &options);
+/// let anchors = collect_anchors(root, &options);
+/// let ids = anchors.iter().map(|a| a.id.as_str()).collect::<Vec<_>>();
+/// assert_eq!(ids, ["intro", "setup-cargo", "intro-1"]);
+/// assert_eq!(anchors[1].text, "Setup cargo");
+/// assert_eq!(anchors[1].sourcepos.start.line, 3);
+/// ```
+pub fn collect_anchors<'a>(root: &'a AstNode<'a>, options: &Options) -> Vec<HeadingAnchor> {
+    let mut anchorizer = options_anchorizer(options);
+    let mut anchors = vec![];
+
+    for node in root.descendants() {
+        let ast = node.data.borrow();
+        if let NodeValue::Heading(ref nh) = ast.value {
+            let mut text_content = Vec::with_capacity(20);
+            node.collect_text_content(&mut text_content);
+            let text = String::from_utf8(text_content).unwrap();
+
+            let id = match nh.attributes.id {
+                Some(ref id) => {
+                    anchorizer.reserve(id.clone());
+                    id.clone()
+                }
+                None => anchorizer.anchorize(text.clone()),
+            };
+            anchors.push(HeadingAnchor {
+                id,
+                text,
+                level: nh.level,
+                sourcepos: ast.sourcepos,
+            });
+        }
+    }
+
+    anchors
 }
 
 struct HtmlFormatter<'o, 'c> {
@@ -133,6 +698,12 @@ struct HtmlFormatter<'o, 'c> {
     footnote_ix: u32,
     written_footnote_ix: u32,
     plugins: &'o Plugins<'o>,
+    abbreviations: Vec<(String, String)>,
+    text_buffer: Vec<u8>,
+    // The column of the table cell being rendered, counted as cells are
+    // left rather than by walking back through the row.
+    table_cell_ix: usize,
+    checkpoint: Checkpoint,
 }
 
 #[rustfmt::skip]
@@ -240,6 +811,21 @@ fn dangerous_url(input: &[u8]) -> bool {
     scanners::dangerous_url(input).is_some()
 }
 
+/// The scheme of an absolute URL, or `None` if it is relative.
+fn url_scheme(url: &str) -> Option<&str> {
+    let colon = url.find([':', '/', '?', '#'])?;
+    let scheme = &url[..colon];
+    if url.as_bytes()[colon] != b':'
+        || !scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !scheme
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"+-.".contains(&b))
+    {
+        return None;
+    }
+    Some(scheme)
+}
+
 /// Writes buffer to output, escaping anything that could be interpreted as an
 /// HTML tag.
 ///
@@ -273,6 +859,35 @@ pub fn escape(output: &mut dyn Write, buffer: &[u8]) -> io::Result<()> {
     Ok(())
 }
 
+/// Writes buffer to output, escaping anything special in HTML as [`escape`]
+/// does, and writing non-ASCII characters as directed by `non_ascii`.
+pub fn escape_with(
+    output: &mut dyn Write,
+    buffer: &[u8],
+    non_ascii: NonAsciiStyle,
+) -> io::Result<()> {
+    let text = match (non_ascii, str::from_utf8(buffer)) {
+        (NonAsciiStyle::Raw, _) | (_, Err(_)) => return escape(output, buffer),
+        (_, Ok(text)) => text,
+    };
+
+    let mut offset = 0;
+    for (i, c) in text.char_indices() {
+        if c.is_ascii() {
+            continue;
+        }
+        escape(output, &buffer[offset..i])?;
+        match non_ascii {
+            NonAsciiStyle::Named if entity::named_reference(c).is_some() => {
+                output.write_all(entity::named_reference(c).unwrap().as_bytes())?
+            }
+            _ => write!(output, "&#x{:X};", c as u32)?,
+        }
+        offset = i + c.len_utf8();
+    }
+    escape(output, &buffer[offset..])
+}
+
 /// Writes buffer to output, escaping in a manner appropriate for URLs in HTML
 /// attributes.
 ///
@@ -348,13 +963,25 @@ pub fn write_opening_tag<Str>(
     tag: &str,
     attributes: impl IntoIterator<Item = (Str, Str)>,
 ) -> io::Result<()>
+where
+    Str: AsRef<str>,
+{
+    write_opening_tag_with(output, tag, attributes, NonAsciiStyle::Raw)
+}
+
+fn write_opening_tag_with<Str>(
+    output: &mut dyn Write,
+    tag: &str,
+    attributes: impl IntoIterator<Item = (Str, Str)>,
+    non_ascii: NonAsciiStyle,
+) -> io::Result<()>
 where
     Str: AsRef<str>,
 {
     write!(output, "<{}", tag)?;
     for (attr, val) in attributes {
         write!(output, " {}=\"", attr.as_ref())?;
-        escape(output, val.as_ref().as_bytes())?;
+        escape_with(output, val.as_ref().as_bytes(), non_ascii)?;
         output.write_all(b"\"")?;
     }
     output.write_all(b">")?;
@@ -370,10 +997,14 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
         HtmlFormatter {
             options,
             output,
-            anchorizer: Anchorizer::new(),
+            anchorizer: options_anchorizer(options),
             footnote_ix: 0,
             written_footnote_ix: 0,
             plugins,
+            abbreviations: vec![],
+            text_buffer: vec![],
+            table_cell_ix: 0,
+            checkpoint: Checkpoint::default(),
         }
     }
 
@@ -385,11 +1016,142 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
     }
 
     fn escape(&mut self, buffer: &[u8]) -> io::Result<()> {
-        escape(&mut self.output, buffer)
+        escape_with(&mut self.output, buffer, self.options.render.non_ascii)
+    }
+
+    fn link_title<'t>(&self, title: &'t str, url: &str) -> Cow<'t, str> {
+        match self.plugins.render.link_title_adapter {
+            Some(adapter) if !title.is_empty() => Cow::Owned(adapter.transform(title, url)),
+            _ => Cow::Borrowed(title),
+        }
+    }
+
+    fn rewrite_url<'u>(rewriter: Option<&dyn UrlRewriter>, url: &'u str) -> Cow<'u, str> {
+        match rewriter {
+            Some(rewriter) => Cow::Owned(rewriter.rewrite(url)),
+            None => Cow::Borrowed(url),
+        }
+    }
+
+    fn resolve_image<'a>(&mut self, node: &'a AstNode<'a>, nl: &NodeLink) -> Option<String> {
+        let resolver = self.plugins.render.image_resolver?;
+        self.text_buffer.clear();
+        node.collect_text_content(&mut self.text_buffer);
+        resolver.resolve(&ImageMeta {
+            url: nl.url.clone(),
+            alt: String::from_utf8_lossy(&self.text_buffer).into_owned(),
+            title: nl.title.clone(),
+        })
+    }
+
+    fn render_title_attribute(&mut self, title: &str) -> io::Result<()> {
+        if title.is_empty() {
+            return Ok(());
+        }
+        match self.options.render.link_titles {
+            LinkTitleStyle::Title => self.output.write_all(b"\" title=\"")?,
+            LinkTitleStyle::AriaLabel => self.output.write_all(b"\" aria-label=\"")?,
+            LinkTitleStyle::Omit => return Ok(()),
+        }
+        self.escape(title.as_bytes())
+    }
+
+    fn allowed_url(&self, url: &str) -> bool {
+        let render = &self.options.render;
+        if let (Some(schemes), Some(scheme)) = (&render.url_schemes, url_scheme(url)) {
+            if !schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme)) {
+                return false;
+            }
+        }
+        render.unsafe_ || !dangerous_url(url.as_bytes())
+    }
+
+    // The style to render `literal` in, if it's only HTML comments and
+    // they're to be rendered apart from other raw HTML.
+    fn html_comment(&self, literal: &[u8]) -> Option<HtmlCommentStyle> {
+        let style = self.options.render.html_comments;
+        if style == HtmlCommentStyle::Raw {
+            return None;
+        }
+
+        let mut rest = trim_slice(literal);
+        if rest.is_empty() {
+            return None;
+        }
+        while !rest.is_empty() {
+            let body = rest.strip_prefix(b"<!--")?;
+            // Browsers also end a comment at `<!-->`, `<!--->` and `--!>`, so
+            // whatever follows those would be live markup.
+            if body.starts_with(b">") || body.starts_with(b"->") {
+                return None;
+            }
+            let end = memchr::memmem::find(body, b"-->")?;
+            if memchr::memmem::find(&body[..end], b"--!>").is_some() {
+                return None;
+            }
+            rest = ltrim_slice(&body[end + 3..]);
+        }
+        Some(style)
+    }
+
+    fn is_external_link(&self, url: &str) -> bool {
+        let render = &self.options.render;
+        if !render.external_link_rel && !render.external_link_target {
+            return false;
+        }
+        if !self.allowed_url(url) {
+            return false;
+        }
+        let host = match punycode::host_range(url) {
+            Some((start, end)) => &url[start..end],
+            None => return false,
+        };
+        match render
+            .external_link_base
+            .as_deref()
+            .and_then(|base| punycode::host_range(base).map(|(start, end)| &base[start..end]))
+        {
+            Some(base) => !host.eq_ignore_ascii_case(base),
+            None => true,
+        }
+    }
+
+    // Sizes given in the document take precedence over the adapter's.
+    fn render_image_dimensions(&mut self, nl: &NodeLink) -> io::Result<()> {
+        let mut dimensions = if !self.allowed_url(&nl.url) {
+            None
+        } else {
+            self.plugins
+                .render
+                .image_dimension_adapter
+                .and_then(|adapter| adapter.dimensions(&nl.url))
+        }
+        .unwrap_or_default();
+        if nl.width.is_some() || nl.height.is_some() {
+            dimensions.width = nl.width;
+            dimensions.height = nl.height;
+        }
+
+        if let Some(width) = dimensions.width {
+            write!(self.output, " width=\"{}\"", width)?;
+        }
+        if let Some(height) = dimensions.height {
+            write!(self.output, " height=\"{}\"", height)?;
+        }
+        if let Some(srcset) = dimensions.srcset {
+            self.output.write_all(b" srcset=\"")?;
+            self.escape(srcset.as_bytes())?;
+            self.output.write_all(b"\"")?;
+        }
+        Ok(())
     }
 
     fn escape_href(&mut self, buffer: &[u8]) -> io::Result<()> {
-        escape_href(&mut self.output, buffer)
+        if self.options.render.punycode_hosts {
+            escape_href(&mut self.output, &punycode::url_to_ascii(buffer))
+        } else {
+            escape_href(&mut self.output, buffer)
+        }
     }
 
     fn format<'a>(&mut self, node: &'a AstNode<'a>, plain: bool) -> io::Result<()> {
@@ -406,11 +1168,19 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
         let mut stack = vec![(node, plain, Phase::Pre)];
 
         while let Some((node, plain, phase)) = stack.pop() {
+            if self.checkpoint.cancelled() {
+                return Err(Cancelled.into());
+            }
+
             match phase {
                 Phase::Pre => {
                     let new_plain = if plain {
                         match node.data.borrow().value {
                             NodeValue::Text(ref literal)
+                            | NodeValue::Entity(NodeEntity {
+                                characters: ref literal,
+                                ..
+                            })
                             | NodeValue::Code(NodeCode { ref literal, .. })
                             | NodeValue::HtmlInline(ref literal) => {
                                 self.escape(literal.as_bytes())?;
@@ -443,31 +1213,40 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
         Ok(())
     }
 
-    fn collect_text<'a>(node: &'a AstNode<'a>, output: &mut Vec<u8>) {
-        match node.data.borrow().value {
-            NodeValue::Text(ref literal) | NodeValue::Code(NodeCode { ref literal, .. }) => {
-                output.extend_from_slice(literal.as_bytes())
-            }
-            NodeValue::LineBreak | NodeValue::SoftBreak => output.push(b' '),
-            NodeValue::Math(NodeMath { ref literal, .. }) => {
-                output.extend_from_slice(literal.as_bytes())
-            }
-            _ => {
-                for n in node.children() {
-                    Self::collect_text(n, output);
-                }
+    /// Collects the text of a heading via the reusable text buffer.
+    fn collect_heading_text<'a>(&mut self, node: &'a AstNode<'a>) -> String {
+        self.text_buffer.clear();
+        node.collect_text_content(&mut self.text_buffer);
+        str::from_utf8(&self.text_buffer).unwrap().to_string()
+    }
+
+    fn format_node<'a>(&mut self, node: &'a AstNode<'a>, entering: bool) -> io::Result<bool> {
+        let mut plain = false;
+        let mut rendered = false;
+        for extension in self.options.extensions.iter() {
+            if extension.render_html(node, entering, self.output)? {
+                rendered = true;
+                break;
             }
         }
+        if !rendered {
+            plain = self.format_node_value(node, entering)?;
+        }
+        for extension in self.options.extensions.iter() {
+            extension.render_html_after(node, entering, self.output)?;
+        }
+        Ok(plain)
     }
 
-    fn format_node<'a>(&mut self, node: &'a AstNode<'a>, entering: bool) -> io::Result<bool> {
+    fn format_node_value<'a>(&mut self, node: &'a AstNode<'a>, entering: bool) -> io::Result<bool> {
         match node.data.borrow().value {
             NodeValue::Document => (),
-            NodeValue::FrontMatter(_) => (),
+            NodeValue::FrontMatter(_) | NodeValue::Truncated(_) => (),
             NodeValue::BlockQuote => {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<blockquote")?;
+                    self.render_class(node, "")?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">\n")?;
                 } else {
@@ -480,14 +1259,17 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     self.cr()?;
                     if nl.list_type == ListType::Bullet {
                         self.output.write_all(b"<ul")?;
+                        self.render_class(node, "")?;
                         self.render_sourcepos(node)?;
                         self.output.write_all(b">\n")?;
                     } else if nl.start == 1 {
                         self.output.write_all(b"<ol")?;
+                        self.render_class(node, "")?;
                         self.render_sourcepos(node)?;
                         self.output.write_all(b">\n")?;
                     } else {
                         self.output.write_all(b"<ol")?;
+                        self.render_class(node, "")?;
                         self.render_sourcepos(node)?;
                         writeln!(self.output, " start=\"{}\">", nl.start)?;
                     }
@@ -501,6 +1283,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<li")?;
+                    self.render_class(node, "")?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">")?;
                 } else {
@@ -511,6 +1294,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<dl")?;
+                    self.render_class(node, "")?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">")?;
                 } else {
@@ -521,6 +1305,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
             NodeValue::DescriptionTerm => {
                 if entering {
                     self.output.write_all(b"<dt")?;
+                    self.render_class(node, "")?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">")?;
                 } else {
@@ -530,6 +1315,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
             NodeValue::DescriptionDetails => {
                 if entering {
                     self.output.write_all(b"<dd")?;
+                    self.render_class(node, "")?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">")?;
                 } else {
@@ -541,31 +1327,51 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     if entering {
                         self.cr()?;
                         write!(self.output, "<h{}", nch.level)?;
+                        if self.options.extension.header_ids.is_none() {
+                            if let Some(ref id) = nch.attributes.id {
+                                self.output.write_all(b" id=\"")?;
+                                self.escape(id.as_bytes())?;
+                                self.output.write_all(b"\"")?;
+                            }
+                        }
+                        let classes = nch.attributes.classes.join(" ");
+                        self.render_class(node, &classes)?;
+                        self.render_attributes(&nch.attributes)?;
                         self.render_sourcepos(node)?;
                         self.output.write_all(b">")?;
 
                         if let Some(ref prefix) = self.options.extension.header_ids {
-                            let mut text_content = Vec::with_capacity(20);
-                            Self::collect_text(node, &mut text_content);
-
-                            let mut id = String::from_utf8(text_content).unwrap();
-                            id = self.anchorizer.anchorize(id);
+                            let id = match nch.attributes.id {
+                                Some(ref id) => {
+                                    self.anchorizer.reserve(id.clone());
+                                    id.clone()
+                                }
+                                None => {
+                                    let id = self.collect_heading_text(node);
+                                    self.anchorizer.try_anchorize(id).map_err(|id| {
+                                        io::Error::new(
+                                            io::ErrorKind::InvalidData,
+                                            format!("duplicate heading id \"{}{}\"", prefix, id),
+                                        )
+                                    })?
+                                }
+                            };
+                            self.output.write_all(b"<a href=\"#")?;
+                            self.escape(id.as_bytes())?;
                             write!(
-                                        self.output,
-                                        "<a href=\"#{}\" aria-hidden=\"true\" class=\"anchor\" id=\"{}{}\"></a>",
-                                        id,
-                                        prefix,
-                                        id
-                                    )?;
+                                self.output,
+                                "\" aria-hidden=\"true\" class=\"anchor\" id=\"{}",
+                                prefix
+                            )?;
+                            self.escape(id.as_bytes())?;
+                            self.output.write_all(b"\"></a>")?;
                         }
                     } else {
                         writeln!(self.output, "</h{}>", nch.level)?;
                     }
                 }
                 Some(adapter) => {
-                    let mut text_content = Vec::with_capacity(20);
-                    Self::collect_text(node, &mut text_content);
-                    let content = String::from_utf8(text_content).unwrap();
+                    let content = self.collect_heading_text(node);
                     let heading = HeadingMeta {
                         level: nch.level,
                         content,
@@ -620,7 +1426,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                                     );
                                 }
                             } else {
-                                code_attr = format!("language-{}", lang_str);
+                                code_attr = format!("{}{}", self.code_class_prefix(), lang_str);
                                 code_attributes.insert(String::from("class"), code_attr);
 
                                 if self.options.render.full_info_string && !info_str.is_empty() {
@@ -630,6 +1436,21 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                             }
                         }
 
+                        if let Some(ref id) = ncb.attributes.id {
+                            pre_attributes.insert("id".to_string(), id.clone());
+                        }
+                        let classes = ncb.attributes.classes.join(" ");
+                        if let Some(class) = self.class_attribute(node, &classes) {
+                            pre_attributes.insert("class".to_string(), class);
+                        }
+                        if self.options.render.unsafe_ {
+                            for (key, value) in &ncb.attributes.attributes {
+                                pre_attributes
+                                    .entry(key.clone())
+                                    .or_insert_with(|| value.clone());
+                            }
+                        }
+
                         if self.options.render.sourcepos {
                             let ast = node.data.borrow();
                             pre_attributes
@@ -638,8 +1459,18 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
 
                         match self.plugins.render.codefence_syntax_highlighter {
                             None => {
-                                write_opening_tag(self.output, "pre", pre_attributes)?;
-                                write_opening_tag(self.output, "code", code_attributes)?;
+                                write_opening_tag_with(
+                                    self.output,
+                                    "pre",
+                                    pre_attributes,
+                                    self.options.render.non_ascii,
+                                )?;
+                                write_opening_tag_with(
+                                    self.output,
+                                    "code",
+                                    code_attributes,
+                                    self.options.render.non_ascii,
+                                )?;
 
                                 self.escape(literal)?;
 
@@ -649,14 +1480,41 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                                 highlighter.write_pre_tag(self.output, pre_attributes)?;
                                 highlighter.write_code_tag(self.output, code_attributes)?;
 
-                                highlighter.write_highlighted(
-                                    self.output,
-                                    match str::from_utf8(&info[..first_tag]) {
-                                        Ok(lang) => Some(lang),
-                                        Err(_) => None,
-                                    },
-                                    &ncb.literal,
-                                )?;
+                                let lang = match str::from_utf8(&info[..first_tag]) {
+                                    Ok(lang) => Some(lang),
+                                    Err(_) => None,
+                                };
+                                match self.plugins.render.codefence_highlight_cache {
+                                    None => {
+                                        highlighter.write_highlighted(
+                                            self.output,
+                                            lang,
+                                            &ncb.literal,
+                                        )?;
+                                    }
+                                    Some(cache) => {
+                                        let key = CodeBlockCacheKey::new(
+                                            lang,
+                                            &ncb.literal,
+                                            highlighter.config_key(),
+                                        );
+                                        match cache.get(&key) {
+                                            Some(highlighted) => {
+                                                self.output.write_all(&highlighted)?
+                                            }
+                                            None => {
+                                                let mut highlighted = vec![];
+                                                highlighter.write_highlighted(
+                                                    &mut highlighted,
+                                                    lang,
+                                                    &ncb.literal,
+                                                )?;
+                                                self.output.write_all(&highlighted)?;
+                                                cache.insert(key, highlighted);
+                                            }
+                                        }
+                                    }
+                                }
 
                                 self.output.write_all(b"</code></pre>\n")?
                             }
@@ -664,12 +1522,17 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     }
                 }
             }
-            NodeValue::HtmlBlock(ref nhb) => {
+            NodeValue::HtmlBlock(NodeHtmlBlock { ref literal, .. })
+            | NodeValue::ComponentBlock(NodeComponent { ref literal, .. })
+            | NodeValue::Raw(ref literal) => {
                 // No sourcepos.
-                if entering {
+                let literal = literal.as_bytes();
+                let comment = self.html_comment(literal);
+                if entering && comment != Some(HtmlCommentStyle::Strip) {
                     self.cr()?;
-                    let literal = nhb.literal.as_bytes();
-                    if self.options.render.escape {
+                    if comment == Some(HtmlCommentStyle::Keep) {
+                        self.output.write_all(literal)?;
+                    } else if self.options.render.escape {
                         self.escape(literal)?;
                     } else if !self.options.render.unsafe_ {
                         self.output.write_all(b"<!-- raw HTML omitted -->")?;
@@ -681,10 +1544,19 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     self.cr()?;
                 }
             }
+            NodeValue::ExtensionBlock(ref ne) => {
+                // Written as text unless its extension renders it.
+                if entering {
+                    self.cr()?;
+                    self.escape(ne.literal.as_bytes())?;
+                    self.cr()?;
+                }
+            }
             NodeValue::ThematicBreak => {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<hr")?;
+                    self.render_class(node, "")?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b" />\n")?;
                 }
@@ -709,15 +1581,18 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     if entering {
                         self.cr()?;
                         self.output.write_all(b"<p")?;
+                        self.render_class(node, "")?;
                         self.render_sourcepos(node)?;
                         self.output.write_all(b">")?;
                     } else {
-                        if let NodeValue::FootnoteDefinition(nfd) =
-                            &node.parent().unwrap().data.borrow().value
+                        if let Some(NodeValue::FootnoteDefinition(nfd)) =
+                            node.parent().map(|n| n.data.borrow().value.clone())
                         {
-                            if node.next_sibling().is_none() {
+                            if node.next_sibling().is_none()
+                                && self.options.render.footnote_style.backrefs
+                            {
                                 self.output.write_all(b" ")?;
-                                self.put_footnote_backref(nfd)?;
+                                self.put_footnote_backref(&nfd)?;
                             }
                         }
                         self.output.write_all(b"</p>\n")?;
@@ -727,9 +1602,20 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
             NodeValue::Text(ref literal) => {
                 // Nowhere to put sourcepos.
                 if entering {
-                    self.escape(literal.as_bytes())?;
+                    if self.abbreviations.is_empty() {
+                        self.escape(literal.as_bytes())?;
+                    } else {
+                        self.render_abbreviated_text(literal)?;
+                    }
                 }
             }
+            NodeValue::Entity(ref ne) => {
+                // Nowhere to put sourcepos.
+                if entering {
+                    self.escape(ne.characters.as_bytes())?;
+                }
+            }
+            NodeValue::AbbreviationDefinition(_) => (),
             NodeValue::LineBreak => {
                 // Unreliable sourcepos.
                 if entering {
@@ -750,7 +1636,20 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                         }
                         self.output.write_all(b" />\n")?;
                     } else {
-                        self.output.write_all(b"\n")?;
+                        match self.options.render.soft_breaks {
+                            SoftBreakStyle::Newline | SoftBreakStyle::Space
+                                if self.options.render.cjk_soft_breaks
+                                    && soft_break_joins_cjk(node) => {}
+                            SoftBreakStyle::Newline => self.output.write_all(b"\n")?,
+                            SoftBreakStyle::Space => self.output.write_all(b" ")?,
+                            SoftBreakStyle::LineBreak => {
+                                self.output.write_all(b"<br")?;
+                                if self.options.render.experimental_inline_sourcepos {
+                                    self.render_sourcepos(node)?;
+                                }
+                                self.output.write_all(b" />")?;
+                            }
+                        }
                     }
                 }
             }
@@ -758,6 +1657,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 // Unreliable sourcepos.
                 if entering {
                     self.output.write_all(b"<code")?;
+                    self.render_class(node, "")?;
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
@@ -766,22 +1666,32 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     self.output.write_all(b"</code>")?;
                 }
             }
-            NodeValue::HtmlInline(ref literal) => {
+            NodeValue::HtmlInline(ref literal)
+            | NodeValue::ComponentInline(NodeComponent { ref literal, .. }) => {
                 // No sourcepos.
                 if entering {
                     let literal = literal.as_bytes();
-                    if self.options.render.escape {
-                        self.escape(literal)?;
-                    } else if !self.options.render.unsafe_ {
-                        self.output.write_all(b"<!-- raw HTML omitted -->")?;
-                    } else if self.options.extension.tagfilter && tagfilter(literal) {
-                        self.output.write_all(b"&lt;")?;
-                        self.output.write_all(&literal[1..])?;
-                    } else {
-                        self.output.write_all(literal)?;
+                    match self.html_comment(literal) {
+                        Some(HtmlCommentStyle::Keep) => self.output.write_all(literal)?,
+                        Some(HtmlCommentStyle::Strip) => (),
+                        _ if self.options.render.escape => self.escape(literal)?,
+                        _ if !self.options.render.unsafe_ => {
+                            self.output.write_all(b"<!-- raw HTML omitted -->")?
+                        }
+                        _ if self.options.extension.tagfilter && tagfilter(literal) => {
+                            self.output.write_all(b"&lt;")?;
+                            self.output.write_all(&literal[1..])?;
+                        }
+                        _ => self.output.write_all(literal)?,
                     }
                 }
             }
+            NodeValue::ExtensionInline(ref ne) => {
+                // Written as text unless its extension renders it.
+                if entering {
+                    self.escape(ne.literal.as_bytes())?;
+                }
+            }
             NodeValue::Strong => {
                 // Unreliable sourcepos.
                 let parent_node = node.parent();
@@ -791,6 +1701,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 {
                     if entering {
                         self.output.write_all(b"<strong")?;
+                        self.render_class(node, "")?;
                         if self.options.render.experimental_inline_sourcepos {
                             self.render_sourcepos(node)?;
                         }
@@ -804,6 +1715,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 // Unreliable sourcepos.
                 if entering {
                     self.output.write_all(b"<em")?;
+                    self.render_class(node, "")?;
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
@@ -816,6 +1728,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 // Unreliable sourcepos.
                 if entering {
                     self.output.write_all(b"<del")?;
+                    self.render_class(node, "")?;
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
@@ -828,6 +1741,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 // Unreliable sourcepos.
                 if entering {
                     self.output.write_all(b"<sup")?;
+                    self.render_class(node, "")?;
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
@@ -836,6 +1750,19 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     self.output.write_all(b"</sup>")?;
                 }
             }
+            NodeValue::Subscript => {
+                // Unreliable sourcepos.
+                if entering {
+                    self.output.write_all(b"<sub")?;
+                    self.render_class(node, "")?;
+                    if self.options.render.experimental_inline_sourcepos {
+                        self.render_sourcepos(node)?;
+                    }
+                    self.output.write_all(b">")?;
+                } else {
+                    self.output.write_all(b"</sub>")?;
+                }
+            }
             NodeValue::Link(ref nl) => {
                 // Unreliable sourcepos.
                 let parent_node = node.parent();
@@ -849,19 +1776,27 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 {
                     if entering {
                         self.output.write_all(b"<a")?;
+                        self.render_class(node, "")?;
                         if self.options.render.experimental_inline_sourcepos {
                             self.render_sourcepos(node)?;
                         }
                         self.output.write_all(b" href=\"")?;
-                        let url = nl.url.as_bytes();
-                        if self.options.render.unsafe_ || !dangerous_url(url) {
-                            self.escape_href(url)?;
+                        let url = Self::rewrite_url(self.plugins.render.link_url_rewriter, &nl.url);
+                        if self.allowed_url(&url) {
+                            self.escape_href(url.as_bytes())?;
                         }
-                        if !nl.title.is_empty() {
-                            self.output.write_all(b"\" title=\"")?;
-                            self.escape(nl.title.as_bytes())?;
+                        let title = self.link_title(&nl.title, &nl.url);
+                        self.render_title_attribute(&title)?;
+                        self.output.write_all(b"\"")?;
+                        if self.is_external_link(&url) {
+                            if self.options.render.external_link_rel {
+                                self.output.write_all(b" rel=\"nofollow noopener\"")?;
+                            }
+                            if self.options.render.external_link_target {
+                                self.output.write_all(b" target=\"_blank\"")?;
+                            }
                         }
-                        self.output.write_all(b"\">")?;
+                        self.output.write_all(b">")?;
                     } else {
                         self.output.write_all(b"</a>")?;
                     }
@@ -871,29 +1806,43 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 // Unreliable sourcepos.
                 if entering {
                     if self.options.render.figure_with_caption {
-                        self.output.write_all(b"<figure>")?;
+                        self.output.write_all(b"<figure")?;
+                        self.render_figure_id(node)?;
+                        self.output.write_all(b">")?;
                     }
                     self.output.write_all(b"<img")?;
+                    self.render_class(node, "")?;
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
                     self.output.write_all(b" src=\"")?;
-                    let url = nl.url.as_bytes();
-                    if self.options.render.unsafe_ || !dangerous_url(url) {
-                        self.escape_href(url)?;
+                    let url = match self.resolve_image(node, nl) {
+                        Some(url) => Cow::Owned(url),
+                        None => Self::rewrite_url(self.plugins.render.image_url_rewriter, &nl.url),
+                    };
+                    if self.allowed_url(&url) {
+                        self.escape_href(url.as_bytes())?;
                     }
                     self.output.write_all(b"\" alt=\"")?;
                     return Ok(true);
                 } else {
-                    if !nl.title.is_empty() {
-                        self.output.write_all(b"\" title=\"")?;
-                        self.escape(nl.title.as_bytes())?;
+                    let title = self.link_title(&nl.title, &nl.url);
+                    self.render_title_attribute(&title)?;
+                    self.output.write_all(b"\"")?;
+                    self.render_image_dimensions(nl)?;
+                    if self.options.render.lazy_images {
+                        self.output
+                            .write_all(b" loading=\"lazy\" decoding=\"async\"")?;
                     }
-                    self.output.write_all(b"\" />")?;
+                    self.output.write_all(b" />")?;
                     if self.options.render.figure_with_caption {
-                        if !nl.title.is_empty() {
+                        if !title.is_empty() {
                             self.output.write_all(b"<figcaption>")?;
-                            self.escape(nl.title.as_bytes())?;
+                            if let Some(ref nf) = node.data.borrow().figure {
+                                self.escape(nf.label.as_bytes())?;
+                                self.output.write_all(b": ")?;
+                            }
+                            self.escape(title.as_bytes())?;
                             self.output.write_all(b"</figcaption>")?;
                         }
                         self.output.write_all(b"</figure>")?;
@@ -904,13 +1853,15 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
             NodeValue::ShortCode(ref nsc) => {
                 // Nowhere to put sourcepos.
                 if entering {
-                    self.output.write_all(nsc.emoji.as_bytes())?;
+                    self.escape(nsc.emoji.as_bytes())?;
                 }
             }
             NodeValue::Table(..) => {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<table")?;
+                    self.render_figure_id(node)?;
+                    self.render_class(node, "")?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">\n")?;
                 } else {
@@ -937,6 +1888,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                         }
                     }
                     self.output.write_all(b"<tr")?;
+                    self.render_class(node, "")?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">")?;
                 } else {
@@ -949,6 +1901,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 }
             }
             NodeValue::TableCell => {
+                let colspan = table_cell_colspan(node);
                 let row = &node.parent().unwrap().data.borrow().value;
                 let in_header = match *row {
                     NodeValue::TableRow(header) => header,
@@ -961,24 +1914,49 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     _ => panic!(),
                 };
 
-                if entering {
+                if entering && node.previous_sibling().is_none() {
+                    self.table_cell_ix = 0;
+                }
+
+                if let Some(adapter) = self.plugins.render.table_cell_adapter {
+                    let cell = TableCellMeta {
+                        header: in_header,
+                        column: self.table_cell_ix,
+                        colspan,
+                        alignment: alignments[self.table_cell_ix],
+                    };
+                    if entering {
+                        self.cr()?;
+                        adapter.enter(
+                            self.output,
+                            &cell,
+                            if self.options.render.sourcepos {
+                                Some(node.data.borrow().sourcepos)
+                            } else {
+                                None
+                            },
+                        )?;
+                    } else {
+                        adapter.exit(self.output, &cell)?;
+                        self.table_cell_ix += colspan;
+                    }
+                } else if entering {
                     self.cr()?;
                     if in_header {
                         self.output.write_all(b"<th")?;
+                        self.render_class(node, "")?;
                         self.render_sourcepos(node)?;
                     } else {
                         self.output.write_all(b"<td")?;
+                        self.render_class(node, "")?;
                         self.render_sourcepos(node)?;
                     }
 
-                    let mut start = node.parent().unwrap().first_child().unwrap();
-                    let mut i = 0;
-                    while !start.same_node(node) {
-                        i += 1;
-                        start = start.next_sibling().unwrap();
+                    if colspan > 1 {
+                        write!(self.output, " colspan=\"{}\"", colspan)?;
                     }
 
-                    match alignments[i] {
+                    match alignments[self.table_cell_ix] {
                         TableAlignment::Left => {
                             self.output.write_all(b" align=\"left\"")?;
                         }
@@ -992,24 +1970,46 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     }
 
                     self.output.write_all(b">")?;
-                } else if in_header {
-                    self.output.write_all(b"</th>")?;
                 } else {
-                    self.output.write_all(b"</td>")?;
+                    self.table_cell_ix += colspan;
+                    if in_header {
+                        self.output.write_all(b"</th>")?;
+                    } else {
+                        self.output.write_all(b"</td>")?;
+                    }
                 }
             }
             NodeValue::FootnoteDefinition(ref nfd) => {
                 if entering {
-                    if self.footnote_ix == 0 {
-                        self.output.write_all(b"<section")?;
+                    let ix = match nfd.ix {
+                        0 => self.footnote_ix + 1,
+                        ix => ix,
+                    };
+                    if !node.previous_sibling().map_or(false, |n| {
+                        matches!(n.data.borrow().value, NodeValue::FootnoteDefinition(..))
+                    }) {
+                        let style = &self.options.render.footnote_style;
+                        write!(self.output, "<{}", style.section_element)?;
                         self.render_sourcepos(node)?;
-                        self.output
-                            .write_all(b" class=\"footnotes\" data-footnotes>\n<ol>\n")?;
+                        if !style.section_class.is_empty() {
+                            self.output.write_all(b" class=\"")?;
+                            self.escape(style.section_class.as_bytes())?;
+                            self.output.write_all(b"\"")?;
+                        }
+                        self.output.write_all(b" data-footnotes>\n")?;
+                        if ix == 1 {
+                            self.output.write_all(b"<ol>\n")?;
+                        } else {
+                            writeln!(self.output, "<ol start=\"{}\">", ix)?;
+                        }
                     }
-                    self.footnote_ix += 1;
+                    self.footnote_ix = ix;
                     self.output.write_all(b"<li")?;
+                    self.render_class(node, "")?;
                     self.render_sourcepos(node)?;
-                    self.output.write_all(b" id=\"fn-")?;
+                    self.output.write_all(b" id=\"")?;
+                    self.escape_href(self.options.render.footnote_style.id_prefix.as_bytes())?;
+                    self.output.write_all(b"fn-")?;
                     self.escape_href(nfd.name.as_bytes())?;
                     self.output.write_all(b"\">")?;
                 } else {
@@ -1017,12 +2017,22 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                         self.output.write_all(b"\n")?;
                     }
                     self.output.write_all(b"</li>\n")?;
+                    if !node.next_sibling().map_or(false, |n| {
+                        matches!(n.data.borrow().value, NodeValue::FootnoteDefinition(..))
+                    }) {
+                        writeln!(
+                            self.output,
+                            "</ol>\n</{}>",
+                            self.options.render.footnote_style.section_element
+                        )?;
+                    }
                 }
             }
             NodeValue::FootnoteReference(ref nfr) => {
                 // Unreliable sourcepos.
                 if entering {
-                    let mut ref_id = format!("fnref-{}", nfr.name);
+                    let prefix = &self.options.render.footnote_style.id_prefix;
+                    let mut ref_id = format!("{}fnref-{}", prefix, nfr.name);
                     if nfr.ref_num > 1 {
                         ref_id = format!("{}-{}", ref_id, nfr.ref_num);
                     }
@@ -1031,8 +2041,10 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
-                    self.output
-                        .write_all(b" class=\"footnote-ref\"><a href=\"#fn-")?;
+                    self.render_class(node, "footnote-ref")?;
+                    self.output.write_all(b"><a href=\"#")?;
+                    self.escape_href(prefix.as_bytes())?;
+                    self.output.write_all(b"fn-")?;
                     self.escape_href(nfr.name.as_bytes())?;
                     self.output.write_all(b"\" id=\"")?;
                     self.escape_href(ref_id.as_bytes())?;
@@ -1043,6 +2055,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<li")?;
+                    self.render_class(node, "")?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">")?;
                     write!(
@@ -1058,10 +2071,77 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     self.output.write_all(b"</li>\n")?;
                 }
             }
+            NodeValue::Container(ref nc) => match nc.kind {
+                ContainerKind::Conditional => {
+                    // Rendered transparently until resolved.
+                }
+                ContainerKind::Details => {
+                    if entering {
+                        self.cr()?;
+                        self.output.write_all(b"<details")?;
+                        self.render_class(node, "")?;
+                        self.render_sourcepos(node)?;
+                        self.output.write_all(b">\n")?;
+                        if let Some(summary) = nc.summary() {
+                            self.output.write_all(b"<summary>")?;
+                            self.escape(summary.as_bytes())?;
+                            self.output.write_all(b"</summary>\n")?;
+                        }
+                    } else {
+                        self.cr()?;
+                        self.output.write_all(b"</details>\n")?;
+                    }
+                }
+                ContainerKind::Named => {
+                    if entering {
+                        self.cr()?;
+                        self.output.write_all(b"<div")?;
+                        if let Some(ref id) = nc.attributes.id {
+                            self.output.write_all(b" id=\"")?;
+                            self.escape(id.as_bytes())?;
+                            self.output.write_all(b"\"")?;
+                        }
+                        let mut classes = nc.name.clone();
+                        for class in &nc.attributes.classes {
+                            classes.push(' ');
+                            classes.push_str(class);
+                        }
+                        self.render_class(node, &classes)?;
+                        self.render_attributes(&nc.attributes)?;
+                        self.render_sourcepos(node)?;
+                        self.output.write_all(b">\n")?;
+                    } else {
+                        self.cr()?;
+                        self.output.write_all(b"</div>\n")?;
+                    }
+                }
+            },
+            NodeValue::Alert(ref nalert) => {
+                if entering {
+                    self.cr()?;
+                    self.output.write_all(b"<div")?;
+                    self.render_class(
+                        node,
+                        &format!(
+                            "markdown-alert markdown-alert-{}",
+                            nalert.alert_type.css_class()
+                        ),
+                    )?;
+                    self.render_sourcepos(node)?;
+                    self.output
+                        .write_all(b">\n<p class=\"markdown-alert-title\">")?;
+                    self.escape(nalert.title().as_bytes())?;
+                    self.output.write_all(b"</p>\n")?;
+                } else {
+                    self.cr()?;
+                    self.output.write_all(b"</div>\n")?;
+                }
+            }
             NodeValue::MultilineBlockQuote(_) => {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<blockquote")?;
+                    self.render_class(node, "")?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">\n")?;
                 } else {
@@ -1097,13 +2177,14 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 // Unreliable sourcepos.
                 if entering {
                     self.output.write_all(b"<a")?;
+                    self.render_class(node, "")?;
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
                     self.output.write_all(b" href=\"")?;
-                    let url = nl.url.as_bytes();
-                    if self.options.render.unsafe_ || !dangerous_url(url) {
-                        self.escape_href(url)?;
+                    let url = Self::rewrite_url(self.plugins.render.link_url_rewriter, &nl.url);
+                    if self.allowed_url(&url) {
+                        self.escape_href(url.as_bytes())?;
                     }
                     self.output.write_all(b"\" data-wikilink=\"true")?;
                     self.output.write_all(b"\">")?;
@@ -1115,6 +2196,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 // Unreliable sourcepos.
                 if entering {
                     self.output.write_all(b"<u")?;
+                    self.render_class(node, "")?;
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
@@ -1130,7 +2212,8 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
-                    self.output.write_all(b" class=\"spoiler\">")?;
+                    self.render_class(node, "spoiler")?;
+                    self.output.write_all(b">")?;
                 } else {
                     self.output.write_all(b"</span>")?;
                 }
@@ -1139,10 +2222,97 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 // Nowhere to put sourcepos.
                 self.output.write_all(net.as_bytes())?;
             }
+            NodeValue::TemplateVariable(ref ntv) => {
+                // Nowhere to put sourcepos.
+                if entering {
+                    let value = self
+                        .plugins
+                        .render
+                        .template_resolver
+                        .and_then(|resolver| resolver.resolve(&ntv.name, &ntv.filters))
+                        .unwrap_or_else(|| ntv.placeholder());
+                    self.escape(value.as_bytes())?;
+                }
+            }
+            NodeValue::TemplateTag(ref literal) => {
+                // Nowhere to put sourcepos.
+                if entering {
+                    if self.options.render.unsafe_ && !self.options.render.escape {
+                        self.output.write_all(literal.as_bytes())?;
+                    } else {
+                        self.escape(literal.as_bytes())?;
+                    }
+                }
+            }
+            NodeValue::Citation(ref nc) => {
+                if entering {
+                    let keys: Vec<&str> = nc.items.iter().map(|item| item.key.as_str()).collect();
+                    self.output.write_all(b"<span")?;
+                    self.render_class(node, "citation")?;
+                    self.output.write_all(b" data-cites=\"")?;
+                    self.escape(keys.join(" ").as_bytes())?;
+                    self.output.write_all(b"\"")?;
+                    self.render_sourcepos(node)?;
+                    self.output.write_all(b">")?;
+                    self.escape(nc.source().as_bytes())?;
+                    self.output.write_all(b"</span>")?;
+                }
+            }
         }
         Ok(false)
     }
 
+    // The classes for `node`: `base`, followed by any configured for its kind
+    // with the `node_classes` render option.
+    fn class_attribute<'a>(&self, node: &'a AstNode<'a>, base: &str) -> Option<String> {
+        let ast = node.data.borrow();
+        let extra = self
+            .options
+            .render
+            .node_classes
+            .get(ast.value.xml_node_name())
+            .map_or("", |classes| classes.trim());
+        let class = format!("{} {}", base, extra);
+        let class = class.trim();
+        if class.is_empty() {
+            None
+        } else {
+            Some(class.to_string())
+        }
+    }
+
+    fn render_class<'a>(&mut self, node: &'a AstNode<'a>, base: &str) -> io::Result<()> {
+        if let Some(class) = self.class_attribute(node, base) {
+            self.output.write_all(b" class=\"")?;
+            self.escape(class.as_bytes())?;
+            self.output.write_all(b"\"")?;
+        }
+        Ok(())
+    }
+
+    // Writes the id given to a numbered figure or table.
+    fn render_figure_id<'a>(&mut self, node: &'a AstNode<'a>) -> io::Result<()> {
+        if let Some(ref nf) = node.data.borrow().figure {
+            self.output.write_all(b" id=\"")?;
+            self.escape(nf.id.as_bytes())?;
+            self.output.write_all(b"\"")?;
+        }
+        Ok(())
+    }
+
+    // Writes the attributes of an attribute block other than its id and
+    // classes, which may include event handlers, so only when unsafe.
+    fn render_attributes(&mut self, attributes: &NodeAttributes) -> io::Result<()> {
+        if self.options.render.unsafe_ {
+            for (key, value) in &attributes.attributes {
+                write!(self.output, " {}=\"", key)?;
+                self.escape(value.as_bytes())?;
+                self.output.write_all(b"\"")?;
+            }
+        }
+        Ok(())
+    }
+
     fn render_sourcepos<'a>(&mut self, node: &'a AstNode<'a>) -> io::Result<()> {
         if self.options.render.sourcepos {
             let ast = node.data.borrow();
@@ -1153,8 +2323,56 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
         Ok(())
     }
 
+    fn render_abbreviated_text(&mut self, text: &str) -> io::Result<()> {
+        fn is_word_char(c: char) -> bool {
+            c.is_alphanumeric() || c == '_'
+        }
+
+        let mut written = 0;
+        let mut chars = text.char_indices().peekable();
+        let mut prev = None;
+
+        while let Some((i, c)) = chars.next() {
+            if prev.map_or(true, |p| !is_word_char(p)) {
+                let found = self.abbreviations.iter().find(|(label, _)| {
+                    text[i..].starts_with(label.as_str())
+                        && !text[i + label.len()..]
+                            .chars()
+                            .next()
+                            .map_or(false, is_word_char)
+                });
+
+                if let Some((label, title)) = found {
+                    let end = i + label.len();
+                    let title = title.clone();
+
+                    self.escape(&text.as_bytes()[written..i])?;
+                    if title.is_empty() {
+                        self.output.write_all(b"<abbr>")?;
+                    } else {
+                        self.output.write_all(b"<abbr title=\"")?;
+                        self.escape(title.as_bytes())?;
+                        self.output.write_all(b"\">")?;
+                    }
+                    self.escape(&text.as_bytes()[i..end])?;
+                    self.output.write_all(b"</abbr>")?;
+                    written = end;
+
+                    while chars.peek().map_or(false, |&(j, _)| j < end) {
+                        prev = chars.next().map(|(_, c)| c);
+                    }
+                    continue;
+                }
+            }
+            prev = Some(c);
+        }
+
+        self.escape(&text.as_bytes()[written..])
+    }
+
     fn put_footnote_backref(&mut self, nfd: &NodeFootnoteDefinition) -> io::Result<bool> {
-        if self.written_footnote_ix >= self.footnote_ix {
+        let style = &self.options.render.footnote_style;
+        if !style.backrefs || self.written_footnote_ix >= self.footnote_ix {
             return Ok(false);
         }
 
@@ -1170,13 +2388,19 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
                 write!(self.output, " ")?;
             }
 
-            self.output.write_all(b"<a href=\"#fnref-")?;
+            self.output.write_all(b"<a href=\"#")?;
+            self.escape_href(style.id_prefix.as_bytes())?;
+            self.output.write_all(b"fnref-")?;
             self.escape_href(nfd.name.as_bytes())?;
             write!(
                 self.output,
-                "{}\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"{}{}\" aria-label=\"Back to reference {}{}\">↩{}</a>",
-                ref_suffix, self.footnote_ix, ref_suffix, self.footnote_ix, ref_suffix, superscript
+                "{}\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"{}{}\" aria-label=\"",
+                ref_suffix, self.footnote_ix, ref_suffix
             )?;
+            self.escape(self.options.render.strings.footnote_backref.as_bytes())?;
+            write!(self.output, " {}{}\">", self.footnote_ix, ref_suffix)?;
+            self.escape(style.backref.as_bytes())?;
+            write!(self.output, "{}</a>", superscript)?;
         }
         Ok(true)
     }
@@ -1202,13 +2426,26 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
             tag_attributes.push(("data-sourcepos".to_string(), ast.sourcepos.to_string()));
         }
 
-        write_opening_tag(self.output, tag, tag_attributes)?;
+        write_opening_tag_with(
+            self.output,
+            tag,
+            tag_attributes,
+            self.options.render.non_ascii,
+        )?;
         self.escape(literal.as_bytes())?;
         write!(self.output, "</{}>", tag)?;
 
         Ok(())
     }
 
+    fn code_class_prefix(&self) -> &str {
+        self.options
+            .render
+            .code_class_prefix
+            .as_deref()
+            .unwrap_or("language-")
+    }
+
     // Renders a math code block, ```` ```math ```` using `<pre><code>`
     fn render_math_code_block<'a>(
         &mut self,
@@ -1227,7 +2464,7 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
             pre_attributes.push((String::from("lang"), lang_str.to_string()));
             pre_attributes.push((String::from("data-math-style"), String::from("display")));
         } else {
-            let code_attr = format!("language-{}", lang_str);
+            let code_attr = format!("{}{}", self.code_class_prefix(), lang_str);
             code_attributes.push((String::from("class"), code_attr));
             code_attributes.push((String::from("data-math-style"), String::from("display")));
         }
@@ -1237,8 +2474,18 @@ impl<'o, 'c: 'o> HtmlFormatter<'o, 'c> {
             pre_attributes.push(("data-sourcepos".to_string(), ast.sourcepos.to_string()));
         }
 
-        write_opening_tag(self.output, "pre", pre_attributes)?;
-        write_opening_tag(self.output, "code", code_attributes)?;
+        write_opening_tag_with(
+            self.output,
+            "pre",
+            pre_attributes,
+            self.options.render.non_ascii,
+        )?;
+        write_opening_tag_with(
+            self.output,
+            "code",
+            code_attributes,
+            self.options.render.non_ascii,
+        )?;
 
         self.escape(literal.as_bytes())?;
         self.output.write_all(b"</code></pre>\n")?;
@@ -0,0 +1,459 @@
+//! Re-parsing of a document after an edit, for editors which keep a preview
+//! up to date as the user types.
+//!
+//! A [`Document`] holds the source of a document and its AST.  When part of
+//! the source is replaced with [`Document::edit`], only the top-level blocks
+//! around the edit are parsed again and spliced into the AST in place of the
+//! old ones.  The nodes of the rest of the document are kept, with their
+//! source positions and offsets moved to match the new source, and the
+//! result is the same as parsing the edited source from scratch.
+//!
+//! ```
+//! # use comrak::{format_html, Arena, Options};
+//! # use comrak::incremental::Document;
+//! let arena = Arena::new();
+//! let options = Options::default();
+//! let mut document = Document::new(&arena, "# Title\n\nSome text.\n\n- a\n- b\n\nEnd.\n", &options);
+//! let end = document.root().last_child().unwrap();
+//!
+//! let changed = document.edit(9..13, "*New*");
+//! assert_eq!(document.text(), "# Title\n\n*New* text.\n\n- a\n- b\n\nEnd.\n");
+//! assert_eq!(changed.len(), 2);
+//! assert!(document.root().last_child().unwrap().same_node(end));
+//!
+//! let mut html = vec![];
+//! format_html(document.root(), &options, &mut html).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(html).unwrap(),
+//!     "<h1>Title</h1>\n<p><em>New</em> text.</p>\n<ul>\n<li>a</li>\n<li>b</li>\n</ul>\n<p>End.</p>\n"
+//! );
+//! ```
+//!
+//! Edits which add or remove a link reference definition, or which change
+//! whether the document starts with front matter, can change how the whole
+//! document is parsed, so the whole document is parsed again.  So is any
+//! edit with [`footnotes`](crate::ExtensionOptions::footnotes) enabled, as
+//! footnotes are numbered and gathered across the whole document.
+//!
+//! [`diff`] compares two parsed documents block by block, so that a preview
+//! can replace only the parts of the rendered HTML which changed:
+//!
+//! ```
+//! # use comrak::{parse_document, Arena, Options};
+//! # use comrak::incremental::diff;
+//! let arena = Arena::new();
+//! let options = Options::default();
+//! let old = parse_document(&arena, "# Title\n\nOne.\n\nTwo.\n\nEnd.\n", &options);
+//! let new = parse_document(&arena, "# Title\n\nOne.\n\n*Three*.\n\nEnd.\n", &options);
+//!
+//! let changes = diff(old, new, &options);
+//! assert_eq!(changes.len(), 1);
+//! assert_eq!((changes[0].old.clone(), changes[0].new.clone()), (2..3, 2..3));
+//! assert_eq!(changes[0].blocks[0].sourcepos, (5, 1, 5, 8).into());
+//! assert_eq!(changes[0].blocks[0].html, "<p><em>Three</em>.</p>\n");
+//! ```
+
+use std::mem;
+use std::ops::Range;
+
+use crate::html;
+use crate::nodes::{AstNode, Sourcepos};
+use crate::parser::{parse_document_with_context, Options, ParseContext, Plugins};
+use crate::xml;
+use typed_arena::Arena;
+
+/// A parsed document which can be edited and re-parsed in part.  See the
+/// [module documentation](self).
+pub struct Document<'a, 'o, 'c> {
+    arena: &'a Arena<AstNode<'a>>,
+    options: &'o Options<'c>,
+    root: &'a AstNode<'a>,
+    text: String,
+    context: ParseContext,
+}
+
+impl<'a, 'o, 'c> Document<'a, 'o, 'c> {
+    /// Parses `text` into `arena`.  The same `options` are used to re-parse
+    /// the document after each edit.
+    pub fn new(arena: &'a Arena<AstNode<'a>>, text: &str, options: &'o Options<'c>) -> Self {
+        let mut context = ParseContext::new();
+        let root = parse_document_with_context(arena, text, options, &mut context);
+        Document {
+            arena,
+            options,
+            root,
+            text: text.to_string(),
+            context,
+        }
+    }
+
+    /// The root of the document's AST.  The root stays the same node across
+    /// edits.
+    pub fn root(&self) -> &'a AstNode<'a> {
+        self.root
+    }
+
+    /// The current source of the document.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The link reference definitions and other state of the last parse.
+    pub fn context(&self) -> &ParseContext {
+        &self.context
+    }
+
+    /// Replaces the bytes of the source in `range` with `replacement`, and
+    /// re-parses the blocks it affects.
+    ///
+    /// Returns the new top-level blocks which were spliced into the AST, in
+    /// order.  Every other top-level block is the same node as before the
+    /// edit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or doesn't lie on `char`
+    /// boundaries, as with [`String::replace_range`].
+    pub fn edit(&mut self, range: Range<usize>, replacement: &str) -> Vec<&'a AstNode<'a>> {
+        let mut text = self.text.clone();
+        text.replace_range(range.clone(), replacement);
+        let old = mem::replace(&mut self.text, text);
+
+        if self.options.extension.footnotes {
+            return self.reparse();
+        }
+
+        let blocks: Vec<_> = self.root.children().collect();
+        // The first block the edit touches, and the one before it, which
+        // may continue into the edit.
+        let first = blocks
+            .iter()
+            .position(|block| block.data.borrow().end_offset >= range.start)
+            .unwrap_or(blocks.len())
+            .saturating_sub(1);
+        if blocks.is_empty()
+            || (first == 0 && self.options.extension.front_matter_delimiter.is_some())
+        {
+            return self.reparse();
+        }
+        let last = blocks
+            .iter()
+            .rposition(|block| line_start(block) <= range.end)
+            .map_or(first, |last| last.max(first));
+
+        let (region_start, start_line) = match first {
+            0 => (0, 1),
+            _ => (
+                line_start(blocks[first]),
+                blocks[first].data.borrow().sourcepos.start.line,
+            ),
+        };
+        let delta = replacement.len() as isize - range.len() as isize;
+
+        let mut options = self.options.clone();
+        options.extension.front_matter_delimiter = None;
+
+        // The region re-parsed runs from the start of `blocks[first]` to the
+        // start of `blocks[end]`.  Unless it runs to the end of the document,
+        // its last block must parse the same as before, showing that the
+        // edit doesn't reach the blocks after it; until it does, the region
+        // is grown.
+        let mut extra = 1;
+        loop {
+            let end = (last + 1 + extra).min(blocks.len());
+            let (old_end, end_line) = match blocks.get(end) {
+                Some(block) => (line_start(block), block.data.borrow().sourcepos.start.line),
+                None => (old.len(), usize::MAX),
+            };
+            let new_end = (old_end as isize + delta) as usize;
+
+            if self
+                .context
+                .definitions
+                .iter()
+                .any(|def| (start_line..end_line).contains(&def.sourcepos.start.line))
+            {
+                return self.reparse();
+            }
+
+            let mut context = self.context.clone();
+            let region = parse_document_with_context(
+                self.arena,
+                &self.text[region_start..new_end],
+                &options,
+                &mut context,
+            );
+            if context.definitions.len() > self.context.definitions.len() {
+                return self.reparse();
+            }
+
+            for node in region.descendants().skip(1) {
+                shift(node, start_line as isize - 1, region_start as isize);
+            }
+            let line_delta = line_ends(&self.text.as_bytes()[region_start..new_end]) as isize
+                - line_ends(&old.as_bytes()[region_start..old_end]) as isize;
+
+            let mut nodes: Vec<_> = region.children().collect();
+            let kept = if end < blocks.len() {
+                let sync = blocks[end - 1];
+                match nodes.last() {
+                    Some(node) if same_block(sync, node, line_delta, delta, self.options) => {}
+                    _ => {
+                        extra *= 2;
+                        continue;
+                    }
+                }
+                nodes.pop();
+                end - 1
+            } else {
+                end
+            };
+
+            for &node in &nodes {
+                match blocks.get(kept) {
+                    Some(block) => block.insert_before(node),
+                    None => self.root.append(node),
+                }
+            }
+            for block in &blocks[first..kept] {
+                block.detach();
+            }
+            for block in &blocks[kept..] {
+                for node in block.descendants() {
+                    shift(node, line_delta, delta);
+                }
+            }
+
+            let mut root = self.root.data.borrow_mut();
+            if kept < blocks.len() {
+                root.sourcepos.end.line = (root.sourcepos.end.line as isize + line_delta) as usize;
+                root.end_offset = (root.end_offset as isize + delta) as usize;
+                for def in &mut context.definitions {
+                    if def.sourcepos.start.line >= end_line {
+                        def.sourcepos.start.line =
+                            (def.sourcepos.start.line as isize + line_delta) as usize;
+                        def.sourcepos.end.line =
+                            (def.sourcepos.end.line as isize + line_delta) as usize;
+                    }
+                }
+            } else {
+                let region = region.data.borrow();
+                root.sourcepos.end = region.sourcepos.end;
+                root.sourcepos.end.line += start_line - 1;
+                root.end_offset = region_start + region.end_offset;
+            }
+            self.context = context;
+
+            return nodes;
+        }
+    }
+
+    // Parses the whole document again, moving the new blocks into the root.
+    fn reparse(&mut self) -> Vec<&'a AstNode<'a>> {
+        let mut context = ParseContext::new();
+        let root = parse_document_with_context(self.arena, &self.text, self.options, &mut context);
+        self.context = context;
+
+        for block in self.root.children().collect::<Vec<_>>() {
+            block.detach();
+        }
+        let nodes: Vec<_> = root.children().collect();
+        for &node in &nodes {
+            self.root.append(node);
+        }
+
+        let root = root.data.borrow();
+        let mut ast = self.root.data.borrow_mut();
+        ast.sourcepos = root.sourcepos;
+        ast.start_offset = root.start_offset;
+        ast.end_offset = root.end_offset;
+        nodes
+    }
+}
+
+impl<'a, 'o, 'c> std::fmt::Debug for Document<'a, 'o, 'c> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Document")
+            .field("text", &self.text)
+            .finish_non_exhaustive()
+    }
+}
+
+// The offset of the start of the line a block starts on.
+fn line_start<'a>(block: &'a AstNode<'a>) -> usize {
+    let ast = block.data.borrow();
+    ast.start_offset
+        .saturating_sub(ast.sourcepos.start.column.saturating_sub(1))
+}
+
+// The number of lines ended in `s`, where `\r\n` ends a single line.
+fn line_ends(s: &[u8]) -> usize {
+    s.iter()
+        .enumerate()
+        .filter(|&(i, &c)| c == b'\n' || (c == b'\r' && s.get(i + 1) != Some(&b'\n')))
+        .count()
+}
+
+fn shift<'a>(node: &'a AstNode<'a>, lines: isize, bytes: isize) {
+    let mut ast = node.data.borrow_mut();
+    ast.sourcepos.start.line = (ast.sourcepos.start.line as isize + lines) as usize;
+    ast.sourcepos.end.line = (ast.sourcepos.end.line as isize + lines) as usize;
+    ast.start_offset = (ast.start_offset as isize + bytes) as usize;
+    ast.end_offset = (ast.end_offset as isize + bytes) as usize;
+}
+
+// Whether `new` was parsed the same as `old`, once moved by the edit.
+fn same_block<'a>(
+    old: &'a AstNode<'a>,
+    new: &'a AstNode<'a>,
+    lines: isize,
+    bytes: isize,
+    options: &Options,
+) -> bool {
+    {
+        let (old, new) = (old.data.borrow(), new.data.borrow());
+        let moved = |line: usize| (line as isize + lines) as usize;
+        let moved_offset = |offset: usize| (offset as isize + bytes) as usize;
+        if moved(old.sourcepos.start.line) != new.sourcepos.start.line
+            || moved(old.sourcepos.end.line) != new.sourcepos.end.line
+            || old.sourcepos.start.column != new.sourcepos.start.column
+            || old.sourcepos.end.column != new.sourcepos.end.column
+            || moved_offset(old.start_offset) != new.start_offset
+            || moved_offset(old.end_offset) != new.end_offset
+        {
+            return false;
+        }
+    }
+
+    let mut options = options.clone();
+    options.render.sourcepos = false;
+    let (mut old_xml, mut new_xml) = (vec![], vec![]);
+    xml::format_document(old, &options, &mut old_xml).unwrap();
+    xml::format_document(new, &options, &mut new_xml).unwrap();
+    old_xml == new_xml
+}
+
+/// A run of top-level blocks of a document which differ from those of an
+/// earlier version of it.  See [`diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockChange {
+    /// The indices of the old document's top-level blocks which were
+    /// replaced.  Empty if blocks were only inserted, in which case its start
+    /// is the index of the old block they were inserted before.
+    pub old: Range<usize>,
+
+    /// The indices of the new document's top-level blocks which replace them.
+    /// Empty if blocks were only removed.
+    pub new: Range<usize>,
+
+    /// The new blocks, in order.
+    pub blocks: Vec<RenderedBlock>,
+}
+
+/// A top-level block of a document and its HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedBlock {
+    /// The position of the block in the new document's source.
+    pub sourcepos: Sourcepos,
+
+    /// The block rendered as HTML, as it's rendered as part of the whole
+    /// document.
+    pub html: String,
+}
+
+/// Compares the top-level blocks of two versions of a document, returning
+/// the fewest runs of blocks which must be replaced in the old one's HTML
+/// to give the new one's, in order.  Blocks are the same if they render as
+/// the same HTML, so with
+/// [`RenderOptions::sourcepos`](crate::RenderOptions::sourcepos) set, blocks
+/// moved to other lines differ too.
+pub fn diff<'a>(old: &'a AstNode<'a>, new: &'a AstNode<'a>, options: &Options) -> Vec<BlockChange> {
+    diff_with_plugins(old, new, options, &Plugins::default())
+}
+
+/// Compares the top-level blocks of two versions of a document, as with
+/// [`diff`].  Accepts custom plugins.
+pub fn diff_with_plugins<'a>(
+    old: &'a AstNode<'a>,
+    new: &'a AstNode<'a>,
+    options: &Options,
+    plugins: &Plugins,
+) -> Vec<BlockChange> {
+    let render = |root: &'a AstNode<'a>| -> Vec<(&'a AstNode<'a>, String)> {
+        root.children()
+            .map(|block| {
+                let mut out = vec![];
+                html::format_node(block, options, &mut out, plugins).unwrap();
+                (block, String::from_utf8(out).unwrap())
+            })
+            .collect()
+    };
+    let (old, new) = (render(old), render(new));
+
+    let prefix = old.iter().zip(&new).take_while(|(o, n)| o.1 == n.1).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| o.1 == n.1)
+        .count();
+    let (old_mid, new_mid) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    // lcs[i][j] is the length of the longest common subsequence of
+    // old_mid[i..] and new_mid[j..].
+    let mut lcs = vec![vec![0usize; new_mid.len() + 1]; old_mid.len() + 1];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i][j] = if old_mid[i].1 == new_mid[j].1 {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = vec![];
+    let mut change: Option<(usize, usize)> = None;
+    let (mut i, mut j) = (0, 0);
+    while i < old_mid.len() || j < new_mid.len() {
+        if i < old_mid.len() && j < new_mid.len() && old_mid[i].1 == new_mid[j].1 {
+            if let Some(start) = change.take() {
+                changes.push((start, (i, j)));
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+        change.get_or_insert((i, j));
+        if j == new_mid.len() || (i < old_mid.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    if let Some(start) = change {
+        changes.push((start, (i, j)));
+    }
+
+    changes
+        .into_iter()
+        .map(|((old_start, new_start), (old_end, new_end))| {
+            let new = prefix + new_start..prefix + new_end;
+            let blocks = new_mid[new_start..new_end]
+                .iter()
+                .map(|(block, html)| RenderedBlock {
+                    sourcepos: block.data.borrow().sourcepos,
+                    html: html.clone(),
+                })
+                .collect();
+            BlockChange {
+                old: prefix + old_start..prefix + old_end,
+                new,
+                blocks,
+            }
+        })
+        .collect()
+}
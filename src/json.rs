@@ -0,0 +1,46 @@
+//! Serializing ASTs to JSON and back, with the `serde` feature.
+//!
+//! A tree is written as nested [`SerializedNode`]s: each node's [`Ast`]
+//! fields, with its children in a `children` array.  Data attached to
+//! extension nodes is not serialized.
+//!
+//! ```
+//! # use comrak::{format_html, parse_document, Arena, Options};
+//! # use comrak::json;
+//! let arena = Arena::new();
+//! let options = Options::default();
+//! let root = parse_document(&arena, "Hi *there*\n", &options);
+//!
+//! let json = json::to_json(root);
+//! assert!(json.contains(r#"{"value":{"Text":"there"}"#));
+//!
+//! let copy = json::from_json(&arena, &json).unwrap();
+//! let mut html = vec![];
+//! format_html(copy, &options, &mut html).unwrap();
+//! assert_eq!(String::from_utf8(html).unwrap(), "<p>Hi <em>there</em></p>\n");
+//! ```
+
+use crate::nodes::AstNode;
+use crate::owned::OwnedNode;
+use crate::Arena;
+
+/// An owned copy of a node and its descendants, which can be serialized.
+/// See [`owned`](crate::owned).
+pub type SerializedNode = OwnedNode;
+
+/// Serializes `root` and its descendants to JSON.
+pub fn to_json<'a>(root: &'a AstNode<'a>) -> String {
+    serde_json::to_string(&SerializedNode::from_node(root)).unwrap()
+}
+
+/// Rebuilds a tree serialized by [`to_json`] in `arena`, returning its root.
+///
+/// Fails if `json` isn't a serialized tree, or nests more deeply than
+/// `serde_json` allows.
+pub fn from_json<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    json: &str,
+) -> serde_json::Result<&'a AstNode<'a>> {
+    let root: SerializedNode = serde_json::from_str(json)?;
+    Ok(root.into_node(arena))
+}
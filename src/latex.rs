@@ -0,0 +1,441 @@
+use crate::cm::is_autolink;
+use crate::line_writer::LineWriter;
+use crate::nodes::{
+    table_cell_colspan, AstNode, ListDelimType, ListType, NodeLink, NodeList, NodeMath, NodeValue,
+    NodeWikiLink, TableAlignment,
+};
+use crate::parser::{Options, Plugins};
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Formats an AST as the body of a [LaTeX](https://www.latex-project.org)
+/// document, modified by the given options.
+///
+/// Links and images use the `hyperref` and `graphicx` packages, and
+/// strikethrough the `ulem` package, which the preamble should load.  Code
+/// blocks are written as `verbatim` environments, and math is passed through
+/// as written.  Footnote references are written as `\footnote` commands
+/// containing their definitions.  Raw HTML, components, extension nodes, front
+/// matter and abbreviation definitions are dropped; other constructs without a
+/// LaTeX equivalent are written as their contents.
+pub fn format_document<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    format_document_with_plugins(root, options, output, &Plugins::default())
+}
+
+/// Formats an AST as the body of a LaTeX document, modified by the given
+/// options. Accepts custom plugins.
+pub fn format_document_with_plugins<'a>(
+    root: &'a AstNode<'a>,
+    _options: &Options,
+    output: &mut dyn Write,
+    _plugins: &Plugins,
+) -> io::Result<()> {
+    let mut footnotes = HashMap::new();
+    for node in root.descendants() {
+        if let NodeValue::FootnoteDefinition(ref nfd) = node.data.borrow().value {
+            footnotes.insert(nfd.name.clone(), node);
+        }
+    }
+
+    let mut f = LatexFormatter {
+        w: LineWriter::new(),
+        footnotes,
+        open_footnotes: vec![],
+        numbers: vec![],
+        columns: vec![],
+        column: 0,
+        single_line: false,
+    };
+    f.format(root);
+    f.w.finish(output)
+}
+
+struct LatexFormatter<'a> {
+    w: LineWriter,
+    footnotes: HashMap<String, &'a AstNode<'a>>,
+    // The footnotes whose definitions are being written, so that a definition
+    // referring to itself isn't written within itself.
+    open_footnotes: Vec<String>,
+    // The number of the next item of each list being written.
+    numbers: Vec<usize>,
+    // The alignment of each column of the table being written, and the
+    // column of the next cell.
+    columns: Vec<char>,
+    column: usize,
+    // Whether line breaks are written as spaces, as in headings and table
+    // cells.
+    single_line: bool,
+}
+
+impl<'a> LatexFormatter<'a> {
+    fn format(&mut self, root: &'a AstNode<'a>) {
+        enum Phase {
+            Pre,
+            Post,
+            Footnote { single_line: bool },
+        }
+        let mut stack = vec![(root, Phase::Pre)];
+
+        while let Some((node, phase)) = stack.pop() {
+            let block = is_block(&node.data.borrow().value);
+            match phase {
+                Phase::Pre => {
+                    if block {
+                        self.w.open(separator(&node.data.borrow().value));
+                    }
+                    if let NodeValue::FootnoteReference(ref nfr) = node.data.borrow().value {
+                        let definition = self.footnotes.get(&nfr.name).copied();
+                        if let Some(definition) = definition {
+                            if !self.open_footnotes.contains(&nfr.name) {
+                                self.write("\\footnote{");
+                                self.w.open(Some("\n\n"));
+                                stack.push((
+                                    node,
+                                    Phase::Footnote {
+                                        single_line: self.single_line,
+                                    },
+                                ));
+                                for ch in definition.reverse_children() {
+                                    stack.push((ch, Phase::Pre));
+                                }
+                                self.open_footnotes.push(nfr.name.clone());
+                            }
+                        }
+                    } else if self.format_node(node, true) {
+                        stack.push((node, Phase::Post));
+                        for ch in node.reverse_children() {
+                            stack.push((ch, Phase::Pre));
+                        }
+                    } else if block {
+                        self.w.close();
+                    }
+                }
+                Phase::Post => {
+                    self.format_node(node, false);
+                    if block {
+                        self.w.close();
+                    }
+                }
+                Phase::Footnote { single_line } => {
+                    self.w.close();
+                    self.write("}");
+                    self.open_footnotes.pop();
+                    self.single_line = single_line;
+                }
+            }
+        }
+    }
+
+    // Writes the start or end of `node`, returning whether its children
+    // should be written.
+    fn format_node(&mut self, node: &'a AstNode<'a>, entering: bool) -> bool {
+        match node.data.borrow().value {
+            NodeValue::Document => (),
+            NodeValue::BlockQuote | NodeValue::MultilineBlockQuote(_) => {
+                self.environment(entering, "quote", "");
+            }
+            NodeValue::List(ref nl) => {
+                let name = match nl.list_type {
+                    ListType::Bullet => "itemize",
+                    ListType::Ordered => "enumerate",
+                };
+                self.environment(entering, name, "");
+                if entering {
+                    self.numbers.push(nl.start);
+                } else {
+                    self.numbers.pop();
+                }
+            }
+            NodeValue::Item(_) | NodeValue::TaskItem(_) => {
+                if entering {
+                    let number = self.numbers.last_mut().unwrap();
+                    let label = match (
+                        &node.data.borrow().value,
+                        &node.parent().unwrap().data.borrow().value,
+                    ) {
+                        (NodeValue::TaskItem(Some(_)), _) => "[{[x]}]".to_string(),
+                        (NodeValue::TaskItem(None), _) => "[{[ ]}]".to_string(),
+                        (_, NodeValue::List(ref nl)) => list_label(nl, *number),
+                        _ => String::new(),
+                    };
+                    *number += 1;
+                    self.w
+                        .push_prefix(format!("\\item{} ", label), String::new());
+                } else {
+                    if !self.w.block_written() {
+                        self.w.write_prefix();
+                    }
+                    self.w.pop_prefix();
+                }
+            }
+            NodeValue::DescriptionList => self.environment(entering, "description", ""),
+            NodeValue::DescriptionItem(_) => (),
+            NodeValue::DescriptionTerm => {
+                if entering {
+                    self.write("\\item[{");
+                    self.w.start_contents("");
+                } else {
+                    self.write("}]");
+                }
+            }
+            NodeValue::DescriptionDetails => {
+                if entering {
+                    self.w.push_prefix(" ".to_string(), String::new());
+                } else {
+                    self.w.pop_prefix();
+                }
+            }
+            NodeValue::CodeBlock(ref ncb) => {
+                let mut literal = ncb.literal.clone();
+                if !literal.is_empty() && !literal.ends_with('\n') {
+                    literal.push('\n');
+                }
+                self.write(&format!(
+                    "\\begin{{verbatim}}\n{}\\end{{verbatim}}",
+                    literal
+                ));
+                return false;
+            }
+            NodeValue::Paragraph => self.single_line = false,
+            NodeValue::Heading(ref nh) => {
+                if entering {
+                    let command = match nh.level {
+                        1 => "section",
+                        2 => "subsection",
+                        3 => "subsubsection",
+                        4 => "paragraph",
+                        _ => "subparagraph",
+                    };
+                    self.write(&format!("\\{}{{", command));
+                } else {
+                    self.write("}");
+                }
+                self.single_line = entering;
+            }
+            NodeValue::ThematicBreak => {
+                self.write("\\noindent\\rule{\\linewidth}{0.4pt}");
+                return false;
+            }
+            NodeValue::Table(ref nt) => {
+                if entering {
+                    self.columns = nt
+                        .alignments
+                        .iter()
+                        .map(|alignment| match alignment {
+                            TableAlignment::None | TableAlignment::Left => 'l',
+                            TableAlignment::Center => 'c',
+                            TableAlignment::Right => 'r',
+                        })
+                        .collect();
+                }
+                let columns: String = self.columns.iter().collect();
+                self.environment(entering, "tabular", &format!("{{{}}}", columns));
+            }
+            NodeValue::TableRow(header) => {
+                if entering {
+                    self.column = 0;
+                } else {
+                    self.write(" \\\\");
+                    if header {
+                        self.write("\n\\hline");
+                    }
+                }
+            }
+            NodeValue::TableCell => {
+                let colspan = table_cell_colspan(node);
+                if entering {
+                    if node.previous_sibling().is_some() {
+                        self.write(" & ");
+                    }
+                    if colspan > 1 {
+                        let align = self.columns.get(self.column).copied().unwrap_or('l');
+                        self.write(&format!("\\multicolumn{{{}}}{{{}}}{{", colspan, align));
+                    }
+                    self.column += colspan;
+                } else if colspan > 1 {
+                    self.write("}");
+                }
+                self.single_line = entering;
+            }
+            NodeValue::FrontMatter(_)
+            | NodeValue::Truncated(_)
+            | NodeValue::HtmlBlock(_)
+            | NodeValue::ComponentBlock(_)
+            | NodeValue::ExtensionBlock(_)
+            | NodeValue::Raw(_)
+            | NodeValue::FootnoteDefinition(_)
+            | NodeValue::AbbreviationDefinition(_) => return false,
+            NodeValue::Alert(ref nalert) => {
+                if entering {
+                    let mut s = "\\begin{quote}\n\\textbf{".to_string();
+                    escape(nalert.title(), &mut s);
+                    s.push('}');
+                    self.write(&s);
+                } else {
+                    self.write("\n\\end{quote}");
+                }
+            }
+            NodeValue::Container(ref nc) => {
+                if let Some(summary) = nc.summary().filter(|_| entering) {
+                    let mut s = "\\textbf{".to_string();
+                    escape(summary, &mut s);
+                    s.push('}');
+                    self.write(&s);
+                }
+            }
+
+            NodeValue::Emph => self.write(if entering { "\\emph{" } else { "}" }),
+            NodeValue::Strong => self.write(if entering { "\\textbf{" } else { "}" }),
+            NodeValue::Strikethrough => self.write(if entering { "\\sout{" } else { "}" }),
+            NodeValue::Superscript => self.write(if entering { "\\textsuperscript{" } else { "}" }),
+            NodeValue::Subscript => self.write(if entering { "\\textsubscript{" } else { "}" }),
+            NodeValue::Underline => self.write(if entering { "\\underline{" } else { "}" }),
+            NodeValue::Link(ref nl) if is_autolink(node, nl) => {
+                self.write(&format!("\\url{{{}}}", escape_url(&nl.url)));
+                return false;
+            }
+            NodeValue::Link(NodeLink { ref url, .. })
+            | NodeValue::WikiLink(NodeWikiLink { ref url }) => {
+                if entering {
+                    self.write(&format!("\\href{{{}}}{{", escape_url(url)));
+                } else {
+                    self.write("}");
+                }
+            }
+            NodeValue::Image(ref nl) => {
+                self.write(&format!("\\includegraphics{{{}}}", escape_url(&nl.url)));
+                return false;
+            }
+            _ if !node.data.borrow().value.block() && node.first_child().is_none() => {
+                let mut s = String::new();
+                self.inline_leaf(node, &mut s);
+                self.write(&s);
+                return false;
+            }
+            _ => (),
+        }
+        true
+    }
+
+    // Writes an inline node without children.
+    fn inline_leaf(&self, node: &'a AstNode<'a>, s: &mut String) {
+        match node.data.borrow().value {
+            NodeValue::Text(ref literal) => escape(literal, s),
+            NodeValue::Entity(ref ne) => escape(&ne.characters, s),
+            NodeValue::SoftBreak | NodeValue::LineBreak if self.single_line => s.push(' '),
+            NodeValue::SoftBreak => s.push('\n'),
+            NodeValue::LineBreak => s.push_str("\\\\\n"),
+            NodeValue::Code(ref nc) => {
+                s.push_str("\\texttt{");
+                escape(&nc.literal, s);
+                s.push('}');
+            }
+            NodeValue::Math(NodeMath {
+                ref literal,
+                display_math,
+                ..
+            }) => {
+                if display_math {
+                    s.push_str("\\[");
+                    s.push_str(literal.trim());
+                    s.push_str("\\]");
+                } else {
+                    s.push('$');
+                    s.push_str(literal.trim());
+                    s.push('$');
+                }
+            }
+            #[cfg(feature = "shortcodes")]
+            NodeValue::ShortCode(ref nsc) => s.push_str(&nsc.emoji),
+            NodeValue::EscapedTag(ref net) => escape(net, s),
+            NodeValue::TemplateVariable(ref ntv) => escape(&ntv.placeholder(), s),
+            NodeValue::TemplateTag(ref literal) => escape(literal, s),
+            NodeValue::Citation(ref nc) => escape(&nc.source(), s),
+            _ => (),
+        }
+    }
+
+    fn write(&mut self, s: &str) {
+        if s.starts_with('[') {
+            // Which would otherwise be taken as an item's label.
+            self.w.replace_first_prefix("\\item ", "\\item{} ");
+        }
+        self.w.write(s);
+    }
+
+    // Writes the start or end of an environment, whose contents are on the
+    // lines between.
+    fn environment(&mut self, entering: bool, name: &str, arguments: &str) {
+        if entering {
+            self.write(&format!("\\begin{{{}}}{}", name, arguments));
+            self.w.start_contents("\n");
+        } else {
+            let before = if self.w.end_contents() { "\n\n" } else { "\n" };
+            self.write(&format!("{}\\end{{{}}}", before, name));
+        }
+    }
+}
+
+fn is_block(value: &NodeValue) -> bool {
+    value.block() || matches!(value, NodeValue::FrontMatter(_) | NodeValue::Truncated(_))
+}
+
+// The separator between the children of a block.
+fn separator(value: &NodeValue) -> Option<&'static str> {
+    match *value {
+        NodeValue::List(..) | NodeValue::DescriptionList | NodeValue::Table(..) => Some("\n"),
+        NodeValue::DescriptionTerm => Some(" "),
+        NodeValue::DescriptionItem(_)
+        | NodeValue::Paragraph
+        | NodeValue::Heading(..)
+        | NodeValue::TableRow(..)
+        | NodeValue::TableCell => None,
+        _ => Some("\n\n"),
+    }
+}
+
+// The optional argument of an `\item`, for ordered lists which number items
+// differently to LaTeX's default of `1.`, `2.` and so on.
+fn list_label(nl: &NodeList, number: usize) -> String {
+    match (nl.list_type, nl.start, nl.delimiter) {
+        (ListType::Bullet, _, _) | (ListType::Ordered, 1, ListDelimType::Period) => String::new(),
+        (ListType::Ordered, _, ListDelimType::Period) => format!("[{}.]", number),
+        (ListType::Ordered, _, ListDelimType::Paren) => format!("[{})]", number),
+    }
+}
+
+fn escape_url(url: &str) -> String {
+    let mut s = String::with_capacity(url.len());
+    for c in url.chars() {
+        if matches!(c, '\\' | '#' | '%' | '{' | '}') {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s
+}
+
+fn escape(literal: &str, s: &mut String) {
+    let mut chars = literal.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                s.push('\\');
+                s.push(c);
+            }
+            '\\' => s.push_str("\\textbackslash{}"),
+            '^' => s.push_str("\\textasciicircum{}"),
+            '~' => s.push_str("\\textasciitilde{}"),
+            '<' => s.push_str("\\textless{}"),
+            '>' => s.push_str("\\textgreater{}"),
+            '|' => s.push_str("\\textbar{}"),
+            // Break up the `--` and `---` ligatures.
+            '-' if chars.peek() == Some(&'-') => s.push_str("-{}"),
+            _ => s.push(c),
+        }
+    }
+}
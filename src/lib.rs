@@ -44,6 +44,26 @@
 //!      </ol>\n");
 //! # }
 //! ```
+//!
+//! The nodes of a document are allocated in its [`Arena`], and freed together when the arena is
+//! dropped.  An arena grows in chunks as nodes are allocated; when parsing many documents of a
+//! similar size, as a server rendering comments might, creating each arena with
+//! [`Arena::with_capacity`] avoids growing it.  [`stats::memory_usage`] reports how many nodes a
+//! document needed, and the memory it holds:
+//!
+//! ```
+//! use comrak::{Arena, parse_document, Options};
+//! use comrak::stats::memory_usage;
+//!
+//! let options = Options::default();
+//! let mut nodes = 0;
+//! for md in ["*One*\n", "Two\n"] {
+//!     let arena = Arena::with_capacity(nodes);
+//!     let root = parse_document(&arena, md, &options);
+//!     nodes = memory_usage(&arena, root).nodes;
+//! }
+//! assert_eq!(nodes, 3);
+//! ```
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![deny(
@@ -63,38 +83,102 @@
     clippy::too_many_arguments
 )]
 
+use std::borrow::Cow;
 use std::io::BufWriter;
 
 pub mod adapters;
 pub mod arena_tree;
+pub mod bibliography;
+pub mod cancel;
 mod cm;
+pub mod compiled;
+pub mod conditionals;
 mod ctype;
+mod djot;
+mod dot;
 mod entity;
+pub mod extensions;
+#[cfg(feature = "ffi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
+pub mod ffi;
+pub mod figures;
+#[cfg(feature = "front-matter")]
+#[cfg_attr(docsrs, doc(cfg(feature = "front-matter")))]
+pub mod front_matter;
+mod gemtext;
 pub mod html;
+pub mod incremental;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod json;
+mod latex;
+mod line_writer;
+pub mod links;
+pub mod lint;
+pub mod merge;
 pub mod nodes;
+pub mod owned;
 mod parser;
 pub mod plugins;
+mod punycode;
+#[cfg(feature = "sanitize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sanitize")))]
+pub mod sanitize;
 mod scanners;
+pub mod search;
+pub mod sections;
+mod slack;
+pub mod spec;
+pub mod stats;
 mod strings;
 #[cfg(test)]
 mod tests;
+mod text;
+pub mod toc;
+mod typst;
+pub mod workspace;
 mod xml;
 
 pub use cm::format_document as format_commonmark;
 pub use cm::format_document_with_plugins as format_commonmark_with_plugins;
+pub use djot::format_document as format_djot;
+pub use djot::format_document_with_plugins as format_djot_with_plugins;
+pub use dot::format_document as format_dot;
+pub use dot::format_document_with_plugins as format_dot_with_plugins;
+pub use gemtext::format_document as format_gemtext;
+pub use gemtext::format_document_with_plugins as format_gemtext_with_plugins;
 pub use html::format_document as format_html;
+pub use html::format_document_cancellable as format_html_cancellable;
+pub use html::format_document_fmt as format_html_fmt;
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+pub use html::format_document_parallel as format_html_parallel;
 pub use html::format_document_with_plugins as format_html_with_plugins;
+pub use html::format_node as format_html_node;
 #[doc(inline)]
 pub use html::Anchorizer;
+pub use latex::format_document as format_latex;
+pub use latex::format_document_with_plugins as format_latex_with_plugins;
 #[allow(deprecated)]
 pub use parser::parse_document_with_broken_link_callback;
 pub use parser::{
-    parse_document, BrokenLinkCallback, BrokenLinkReference, ExtensionOptions,
-    ExtensionOptionsBuilder, ListStyleType, Options, ParseOptions, ParseOptionsBuilder, Plugins,
-    PluginsBuilder, RenderOptions, RenderOptionsBuilder, RenderPlugins, RenderPluginsBuilder,
-    ResolvedReference,
+    parse_document, parse_document_bytes, parse_document_cancellable, parse_document_with_context,
+    parse_inline, try_parse_document, BrokenLinkCallback, BrokenLinkReference, CodeFenceStyleType,
+    Diagnostic, DiagnosticCallback, ExtensionOptions, ExtensionOptionsBuilder, FootnotePlacement,
+    FootnoteStyle, HeaderIdCollisions, HeaderIdStyle, HeadingStyleType, HtmlCommentStyle,
+    InputTooLarge, LinkTitleStyle, ListStyleType, NonAsciiStyle, Options, OrderedListStyleType,
+    OversizedInput, ParseContext, ParseOptions, ParseOptionsBuilder, Plugins, PluginsBuilder,
+    ReferenceDefinition, RenderOptions, RenderOptionsBuilder, RenderPlugins, RenderPluginsBuilder,
+    RenderStrings, ResolvedReference, SmartQuotes, SoftBreakStyle, StreamingParser,
+    TableRowMismatch,
 };
+pub use slack::format_document as format_slack;
+pub use slack::format_document_with_plugins as format_slack_with_plugins;
+pub use text::format_document as format_text;
+pub use text::format_document_with_plugins as format_text_with_plugins;
 pub use typed_arena::Arena;
+pub use typst::format_document as format_typst;
+pub use typst::format_document_with_plugins as format_typst_with_plugins;
 pub use xml::format_document as format_xml;
 pub use xml::format_document_with_plugins as format_xml_with_plugins;
 
@@ -122,6 +206,7 @@ pub fn markdown_to_html(md: &str, options: &Options) -> String {
 ///
 /// See the documentation of the crate root for an example.
 pub fn markdown_to_html_with_plugins(md: &str, options: &Options, plugins: &Plugins) -> String {
+    let options = &*suffix_header_id_collisions(options);
     let arena = Arena::new();
     let root = parse_document(&arena, md, options);
     let mut bw = BufWriter::new(Vec::new());
@@ -129,6 +214,123 @@ pub fn markdown_to_html_with_plugins(md: &str, options: &Options, plugins: &Plug
     String::from_utf8(bw.into_inner().unwrap()).unwrap()
 }
 
+/// Render Markdown to HTML using plugins, returning statistics about parsing
+/// and rendering along with the HTML.
+///
+/// See the [`stats`] module for an example.
+pub fn markdown_to_html_with_stats(
+    md: &str,
+    options: &Options,
+    plugins: &Plugins,
+) -> (String, stats::Stats) {
+    let options = &*suffix_header_id_collisions(options);
+    let arena = Arena::new();
+    let (root, parse_time) = stats::timed(|| parse_document(&arena, md, options));
+
+    let (html, render_time) = stats::timed(|| {
+        let mut bw = BufWriter::new(Vec::new());
+        format_html_with_plugins(root, options, &mut bw, plugins).unwrap();
+        String::from_utf8(bw.into_inner().unwrap()).unwrap()
+    });
+
+    let stats = stats::Stats {
+        bytes: md.len(),
+        nodes: arena.len(),
+        parse_time,
+        render_time,
+        output_bytes: html.len(),
+        largest_block: stats::largest_block(root),
+    };
+    (html, stats)
+}
+
+/// Render Markdown to HTML using plugins, stopping with
+/// [`RenderError::Cancelled`](cancel::RenderError::Cancelled) if `token` is
+/// cancelled before parsing and rendering finish.
+///
+/// See the [`cancel`] module for an example.
+pub fn markdown_to_html_cancellable(
+    md: &str,
+    options: &Options,
+    plugins: &Plugins,
+    token: &cancel::CancellationToken,
+) -> Result<String, cancel::RenderError> {
+    let arena = Arena::new();
+    let root = parse_document_cancellable(&arena, md, options, token)?;
+    let mut bw = BufWriter::new(Vec::new());
+    format_html_cancellable(root, options, &mut bw, plugins, token)?;
+    Ok(String::from_utf8(bw.into_inner().unwrap()).unwrap())
+}
+
+/// Render Markdown to HTML using plugins, into caller-owned buffers that can
+/// be reused between documents. Returns the rendered HTML, borrowed from
+/// `buffers`.
+///
+/// See [`html::RenderBuffers`] for an example.
+pub fn markdown_to_html_with_buffers<'b>(
+    md: &str,
+    options: &Options,
+    plugins: &Plugins,
+    buffers: &'b mut html::RenderBuffers,
+) -> &'b str {
+    let options = &*suffix_header_id_collisions(options);
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    html::format_document_with_buffers(root, options, plugins, buffers).unwrap();
+    buffers.as_str()
+}
+
+/// Render many independent Markdown documents to HTML using plugins, in
+/// parallel on the rayon thread pool. Returns the HTML of each document, in
+/// the order given.
+///
+/// Each worker reuses one set of [`html::RenderBuffers`] for all the documents
+/// it renders, and allocates each arena with room for as many nodes as the
+/// previous document it parsed needed.
+///
+/// ```
+/// # use comrak::{markdown_to_html_many, Options, Plugins};
+/// let pages = ["# One\n", "*Two*\n", "Three\n"];
+/// let html = markdown_to_html_many(&pages, &Options::default(), &Plugins::default());
+/// assert_eq!(html, ["<h1>One</h1>\n", "<p><em>Two</em></p>\n", "<p>Three</p>\n"]);
+/// ```
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+pub fn markdown_to_html_many<S: AsRef<str> + Sync>(
+    documents: &[S],
+    options: &Options,
+    plugins: &Plugins,
+) -> Vec<String> {
+    use rayon::prelude::*;
+
+    let options = &*suffix_header_id_collisions(options);
+
+    documents
+        .par_iter()
+        .map_init(
+            || (html::RenderBuffers::new(), 0),
+            |(buffers, nodes), md| {
+                let arena = Arena::with_capacity(*nodes);
+                let root = parse_document(&arena, md.as_ref(), options);
+                html::format_document_with_buffers(root, options, plugins, buffers).unwrap();
+                *nodes = arena.len();
+                buffers.as_str().to_string()
+            },
+        )
+        .collect()
+}
+
+// The `markdown_to_html` functions can't fail, so they suffix colliding
+// header IDs rather than return the error of `HeaderIdCollisions::Error`.
+fn suffix_header_id_collisions<'o, 'c>(options: &'o Options<'c>) -> Cow<'o, Options<'c>> {
+    if options.extension.header_id_collisions != HeaderIdCollisions::Error {
+        return Cow::Borrowed(options);
+    }
+    let mut options = options.clone();
+    options.extension.header_id_collisions = HeaderIdCollisions::Suffix;
+    Cow::Owned(options)
+}
+
 /// Return the version of the crate.
 pub fn version() -> &'static str {
     env!("CARGO_PKG_VERSION")
@@ -143,6 +345,60 @@ pub fn markdown_to_commonmark(md: &str, options: &Options) -> String {
     String::from_utf8(bw.into_inner().unwrap()).unwrap()
 }
 
+/// Render Markdown to Djot.
+pub fn markdown_to_djot(md: &str, options: &Options) -> String {
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    let mut bw = BufWriter::new(Vec::new());
+    format_djot(root, options, &mut bw).unwrap();
+    String::from_utf8(bw.into_inner().unwrap()).unwrap()
+}
+
+/// Render Markdown to Gemini gemtext.
+pub fn markdown_to_gemtext(md: &str, options: &Options) -> String {
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    let mut bw = BufWriter::new(Vec::new());
+    format_gemtext(root, options, &mut bw).unwrap();
+    String::from_utf8(bw.into_inner().unwrap()).unwrap()
+}
+
+/// Render Markdown to the body of a LaTeX document.
+pub fn markdown_to_latex(md: &str, options: &Options) -> String {
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    let mut bw = BufWriter::new(Vec::new());
+    format_latex(root, options, &mut bw).unwrap();
+    String::from_utf8(bw.into_inner().unwrap()).unwrap()
+}
+
+/// Render Markdown to Slack mrkdwn.
+pub fn markdown_to_slack(md: &str, options: &Options) -> String {
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    let mut bw = BufWriter::new(Vec::new());
+    format_slack(root, options, &mut bw).unwrap();
+    String::from_utf8(bw.into_inner().unwrap()).unwrap()
+}
+
+/// Render Markdown to plain text.
+pub fn markdown_to_text(md: &str, options: &Options) -> String {
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    let mut bw = BufWriter::new(Vec::new());
+    format_text(root, options, &mut bw).unwrap();
+    String::from_utf8(bw.into_inner().unwrap()).unwrap()
+}
+
+/// Render Markdown to Typst markup.
+pub fn markdown_to_typst(md: &str, options: &Options) -> String {
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    let mut bw = BufWriter::new(Vec::new());
+    format_typst(root, options, &mut bw).unwrap();
+    String::from_utf8(bw.into_inner().unwrap()).unwrap()
+}
+
 /// Render Markdown to CommonMark XML.
 /// See <https://github.com/commonmark/commonmark-spec/blob/master/CommonMark.dtd>.
 pub fn markdown_to_commonmark_xml(md: &str, options: &Options) -> String {
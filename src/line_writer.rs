@@ -0,0 +1,248 @@
+//! Output buffering for the formatters which write nested blocks by
+//! prefixing their lines, and separate sibling blocks with blank lines.
+
+use std::io::{self, Write};
+
+// A prefix written at the start of each line of a block, such as a list
+// item's marker followed by the indentation of its later lines.
+struct Prefix {
+    first: String,
+    rest: String,
+    used: bool,
+}
+
+// A block being written, whose children are separated by `separator`.
+struct Block {
+    start: usize,
+    separator: Option<&'static str>,
+    saved: Option<(&'static str, usize)>,
+}
+
+/// Buffers the output of a formatter.  Separators between blocks are held
+/// until more is written, so blocks which turn out to be empty aren't
+/// separated from their siblings; prefixes are written at the start of each
+/// line, with trailing whitespace trimmed from those of empty lines.
+pub(crate) struct LineWriter {
+    v: String,
+    prefixes: Vec<Prefix>,
+    blocks: Vec<Block>,
+    // A separator to write before anything more, and the number of prefixes
+    // which apply to it.
+    pending: Option<(&'static str, usize)>,
+    begin_line: bool,
+    single_line: bool,
+    marking: bool,
+    mark: Option<usize>,
+}
+
+impl LineWriter {
+    pub(crate) fn new() -> Self {
+        LineWriter {
+            v: String::new(),
+            prefixes: vec![],
+            blocks: vec![],
+            pending: None,
+            begin_line: true,
+            single_line: false,
+            marking: false,
+            mark: None,
+        }
+    }
+
+    /// The length of the output so far.
+    pub(crate) fn len(&self) -> usize {
+        self.v.len()
+    }
+
+    /// The output from `start` on.
+    pub(crate) fn since(&self, start: usize) -> &str {
+        &self.v[start..]
+    }
+
+    /// Opens a block within the current one, separated from whatever
+    /// precedes it there by the current block's separator.  The block's own
+    /// children are separated by `separator`.
+    pub(crate) fn open(&mut self, separator: Option<&'static str>) {
+        let saved = self.pending;
+        if let Some(parent) = self.blocks.last() {
+            if let Some(sep) = parent.separator {
+                if self.v.len() > parent.start {
+                    self.pending = Some((sep, self.prefixes.len()));
+                }
+            }
+        }
+        self.blocks.push(Block {
+            start: self.v.len(),
+            separator,
+            saved,
+        });
+    }
+
+    /// Closes the current block, returning whether anything was written in
+    /// it.  The separator before an empty block is dropped.
+    pub(crate) fn close(&mut self) -> bool {
+        let block = self.blocks.pop().unwrap();
+        let written = self.v.len() > block.start;
+        if !written {
+            self.pending = block.saved;
+        }
+        written
+    }
+
+    /// Whether anything has been written in the current block.
+    pub(crate) fn block_written(&self) -> bool {
+        self.v.len() > self.blocks.last().unwrap().start
+    }
+
+    /// Marks the start of the current block's contents, after any opening
+    /// already written, and holds `before` to be written before them.
+    pub(crate) fn start_contents(&mut self, before: &'static str) {
+        self.blocks.last_mut().unwrap().start = self.v.len();
+        self.pending = Some((before, self.prefixes.len()));
+    }
+
+    /// Ends the current block's contents, returning whether there were
+    /// none, in which case what was held before them is dropped.
+    pub(crate) fn end_contents(&mut self) -> bool {
+        let empty = self.v.len() == self.blocks.last().unwrap().start;
+        if empty {
+            self.pending = None;
+        }
+        empty
+    }
+
+    /// Adds a prefix for the lines written from now on: `first` for the
+    /// first, wherever it starts, and `rest` for later ones.
+    pub(crate) fn push_prefix(&mut self, first: String, rest: String) {
+        self.prefixes.push(Prefix {
+            first,
+            rest,
+            used: false,
+        });
+    }
+
+    pub(crate) fn pop_prefix(&mut self) {
+        self.prefixes.pop();
+    }
+
+    /// Replaces the innermost prefix's prefix for its first line, if it's
+    /// `from` and nothing has been written with it yet.
+    pub(crate) fn replace_first_prefix(&mut self, from: &str, to: &str) {
+        if let Some(prefix) = self.prefixes.last_mut() {
+            if !prefix.used && prefix.first == from {
+                prefix.first = to.to_string();
+            }
+        }
+    }
+
+    /// With `single_line`, line breaks are written as spaces.
+    pub(crate) fn set_single_line(&mut self, single_line: bool) {
+        self.single_line = single_line;
+    }
+
+    pub(crate) fn write(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        self.write_pending();
+        if self.marking {
+            if !s.starts_with('\n') {
+                self.write_line_prefix(self.prefixes.len());
+            }
+            self.mark = Some(self.v.len());
+            self.marking = false;
+        }
+        self.emit(s, self.prefixes.len());
+    }
+
+    /// Records where the next text written starts, after any separator and
+    /// prefix before it.
+    pub(crate) fn mark(&mut self) {
+        self.marking = true;
+        self.mark = None;
+    }
+
+    /// Where the text written since `mark` was called starts, if any was.
+    pub(crate) fn marked(&self) -> Option<usize> {
+        self.mark
+    }
+
+    /// Writes any held separator and the prefix of the current line, as for
+    /// a line with nothing more on it.
+    pub(crate) fn write_prefix(&mut self) {
+        self.write_pending();
+        let start = self.v.len();
+        self.write_line_prefix(self.prefixes.len());
+        let end = self.v.trim_end().len().max(start);
+        self.v.truncate(end);
+        self.begin_line = false;
+    }
+
+    /// Inserts `s` into the output at `at`, such as an escape for what
+    /// turned out to follow it.
+    pub(crate) fn insert(&mut self, at: usize, s: &str) {
+        self.v.insert_str(at, s);
+    }
+
+    /// Removes trailing whitespace from the output, back to `start` at most.
+    pub(crate) fn trim_end(&mut self, start: usize) {
+        let end = self.v.trim_end().len().max(start);
+        if end < self.v.len() {
+            self.v.truncate(end);
+            self.begin_line = false;
+        }
+    }
+
+    /// Writes the output, followed by a newline unless it's empty.
+    pub(crate) fn finish(self, output: &mut dyn Write) -> io::Result<()> {
+        output.write_all(self.v.as_bytes())?;
+        if !self.v.is_empty() {
+            output.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn write_pending(&mut self) {
+        if let Some((separator, prefixes)) = self.pending.take() {
+            self.emit(separator, prefixes);
+        }
+    }
+
+    // Writes `s` with the first `prefixes` prefixes.
+    fn emit(&mut self, s: &str, prefixes: usize) {
+        for (i, line) in s.split('\n').enumerate() {
+            if i > 0 && self.single_line {
+                self.v.push(' ');
+            } else if i > 0 {
+                if self.begin_line {
+                    let start = self.v.len();
+                    self.write_line_prefix(prefixes);
+                    let end = self.v.trim_end().len().max(start);
+                    self.v.truncate(end);
+                }
+                self.v.push('\n');
+                self.begin_line = true;
+            }
+            if !line.is_empty() {
+                self.write_line_prefix(prefixes);
+                self.v.push_str(line);
+                self.begin_line = false;
+            }
+        }
+    }
+
+    // Writes the prefixes due before more is written on the current line:
+    // all of them at the start of a line, and otherwise those whose first
+    // line this is.
+    fn write_line_prefix(&mut self, prefixes: usize) {
+        for prefix in &mut self.prefixes[..prefixes] {
+            if !prefix.used {
+                self.v.push_str(&prefix.first);
+                prefix.used = true;
+            } else if self.begin_line {
+                self.v.push_str(&prefix.rest);
+            }
+        }
+        self.begin_line = false;
+    }
+}
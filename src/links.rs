@@ -0,0 +1,77 @@
+//! Extraction of the links and images in a document, e.g. for link checkers.
+//!
+//! ```
+//! # use comrak::{parse_document, Arena, Options};
+//! # use comrak::links::{collect_links, LinkKind};
+//! let arena = Arena::new();
+//! let root = parse_document(
+//!     &arena,
+//!     "See [the *docs*](/docs \"Docs\").\n\n![A cat](cat.png)\n",
+//!     &Options::default(),
+//! );
+//!
+//! let links = collect_links(root);
+//! assert_eq!(links.len(), 2);
+//! assert_eq!(links[0].kind, LinkKind::Link);
+//! assert_eq!(links[0].url, "/docs");
+//! assert_eq!(links[0].title, "Docs");
+//! assert_eq!(links[0].text, "the docs");
+//! assert_eq!(links[0].sourcepos.start.column, 5);
+//! assert_eq!(links[1].kind, LinkKind::Image);
+//! assert_eq!(links[1].text, "A cat");
+//! ```
+
+use crate::nodes::{AstNode, NodeValue, Sourcepos};
+
+/// The kind of node a [`Link`] was found in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// A link, including autolinks.
+    Link,
+    /// An image.
+    Image,
+    /// A wikilink.
+    WikiLink,
+}
+
+/// A link or image in a document.  See [`collect_links`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// The kind of node the link was found in.
+    pub kind: LinkKind,
+
+    /// The destination of a link, or the source of an image.
+    pub url: String,
+
+    /// The title, or an empty string.  Wikilinks have none.
+    pub title: String,
+
+    /// The text of the link, or the alt text of an image, without markup.
+    pub text: String,
+
+    /// The position of the link in the document.
+    pub sourcepos: Sourcepos,
+}
+
+/// Returns every link, image and wikilink at or below `root`, in document
+/// order.
+pub fn collect_links<'a>(root: &'a AstNode<'a>) -> Vec<Link> {
+    let mut links = vec![];
+    for node in root.descendants() {
+        let ast = node.data.borrow();
+        let (kind, url, title) = match ast.value {
+            NodeValue::Link(ref nl) => (LinkKind::Link, &nl.url, nl.title.as_str()),
+            NodeValue::Image(ref nl) => (LinkKind::Image, &nl.url, nl.title.as_str()),
+            NodeValue::WikiLink(ref nwl) => (LinkKind::WikiLink, &nwl.url, ""),
+            _ => continue,
+        };
+        links.push(Link {
+            kind,
+            url: url.clone(),
+            title: title.to_string(),
+            text: node.text_content(),
+            sourcepos: ast.sourcepos,
+        });
+    }
+    links
+}
@@ -0,0 +1,183 @@
+//! Automatic fixes for lint [`Violation`](super::Violation)s.
+
+use super::{Finding, Linter};
+use crate::nodes::{AstNode, LineColumn, Sourcepos};
+use crate::{format_commonmark, parse_document, Arena, Options};
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+/// A replacement of part of the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The source text to replace, inclusive of both ends, as in the AST.
+    pub sourcepos: Sourcepos,
+
+    /// The text to put in its place.
+    pub replacement: String,
+}
+
+/// A way to fix a [`Violation`](super::Violation).
+#[derive(Clone)]
+pub enum Fix {
+    /// Replace parts of the source text.  The edits of one fix must not overlap.
+    Text(Vec<TextEdit>),
+
+    /// Modify the AST in place.  The function is called with the document root and is
+    /// responsible for finding the node(s) to change, typically by the sourcepos the violation
+    /// was reported at.
+    Ast(Arc<dyn for<'a> Fn(&'a AstNode<'a>) + Send + Sync>),
+}
+
+impl Fix {
+    /// A fix replacing the source text at `sourcepos` with `replacement`.
+    pub fn replace(sourcepos: Sourcepos, replacement: impl Into<String>) -> Self {
+        Fix::Text(vec![TextEdit {
+            sourcepos,
+            replacement: replacement.into(),
+        }])
+    }
+
+    /// A fix modifying the AST with `f`.
+    pub fn ast(f: impl for<'a> Fn(&'a AstNode<'a>) + Send + Sync + 'static) -> Self {
+        Fix::Ast(Arc::new(f))
+    }
+}
+
+impl Debug for Fix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Fix::Text(edits) => f.debug_tuple("Text").field(edits).finish(),
+            Fix::Ast(_) => f.debug_tuple("Ast").field(&"impl Fn").finish(),
+        }
+    }
+}
+
+/// The result of [`Linter::fix`].
+#[derive(Debug, Clone)]
+pub struct Fixed {
+    /// The fixed document, formatted as CommonMark.
+    pub output: String,
+
+    /// The number of fixes applied.
+    pub applied: usize,
+
+    /// The findings remaining in `output`.
+    pub findings: Vec<Finding>,
+}
+
+impl Linter {
+    /// Applies the fixes of all fixable findings in `source`, and returns the result formatted as
+    /// CommonMark.
+    ///
+    /// Text fixes are applied first, skipping any which overlap an earlier one, after which the
+    /// document is parsed and checked again.  AST fixes are then applied, skipping any whose
+    /// violation overlaps that of an earlier one.  Fixes skipped because of a conflict are left
+    /// for another run.
+    ///
+    /// ```
+    /// # use comrak::Options;
+    /// # use comrak::lint::Linter;
+    /// let linter = Linter::with_builtin_rules();
+    /// let fixed = linter.fix("# Title\n\n### Section\n", &Options::default());
+    /// assert_eq!(fixed.output, "# Title\n\n## Section\n");
+    /// assert_eq!(fixed.applied, 1);
+    /// assert!(fixed.findings.is_empty());
+    /// ```
+    pub fn fix(&self, source: &str, options: &Options) -> Fixed {
+        let mut applied = 0;
+
+        let arena = Arena::new();
+        let mut root = parse_document(&arena, source, options);
+
+        let mut accepted: Vec<Vec<TextEdit>> = vec![];
+        for (_, violation) in self.violations(root, source, options) {
+            if let Some(Fix::Text(mut edits)) = violation.fix {
+                edits.sort_by_key(|e| e.sourcepos);
+                let conflicts = edits.iter().any(|e| {
+                    accepted
+                        .iter()
+                        .flatten()
+                        .any(|a| overlaps(a.sourcepos, e.sourcepos))
+                });
+                if !conflicts {
+                    accepted.push(edits);
+                }
+            }
+        }
+
+        let mut source = source.to_string();
+        if !accepted.is_empty() {
+            applied += accepted.len();
+            let mut edits: Vec<TextEdit> = accepted.into_iter().flatten().collect();
+            edits.sort_by_key(|e| e.sourcepos);
+            source = apply_text_edits(&source, &edits);
+            root = parse_document(&arena, &source, options);
+        }
+
+        let mut fixed: Vec<Sourcepos> = vec![];
+        for (_, violation) in self.violations(root, &source, options) {
+            let sourcepos = violation.sourcepos;
+            if let Some(Fix::Ast(f)) = violation.fix {
+                if !fixed.iter().any(|&sp| overlaps(sp, sourcepos)) {
+                    f(root);
+                    fixed.push(sourcepos);
+                }
+            }
+        }
+        applied += fixed.len();
+
+        let mut output = vec![];
+        format_commonmark(root, options, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        let findings = self.check(parse_document(&arena, &output, options), &output, options);
+
+        Fixed {
+            output,
+            applied,
+            findings,
+        }
+    }
+}
+
+fn overlaps(a: Sourcepos, b: Sourcepos) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
+
+fn apply_text_edits(source: &str, edits: &[TextEdit]) -> String {
+    let mut line_starts = vec![0];
+    line_starts.extend(
+        source
+            .bytes()
+            .enumerate()
+            .filter(|&(_, c)| c == b'\n')
+            .map(|(i, _)| i + 1),
+    );
+    let offset = |lc: LineColumn| {
+        let mut offset = line_starts
+            .get(lc.line.saturating_sub(1))
+            .map_or(source.len(), |start| start + lc.column.saturating_sub(1))
+            .min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    };
+
+    let mut out = String::with_capacity(source.len());
+    let mut last = 0;
+    for edit in edits {
+        let start = offset(edit.sourcepos.start).max(last);
+        let mut end = (offset(edit.sourcepos.end) + 1)
+            .min(source.len())
+            .max(start);
+        while !source.is_char_boundary(end) {
+            end += 1;
+        }
+        out.push_str(&source[last..start]);
+        out.push_str(&edit.replacement);
+        last = end;
+    }
+    out.push_str(&source[last..]);
+    out
+}
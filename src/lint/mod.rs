@@ -0,0 +1,401 @@
+//! Linting of parsed documents.
+//!
+//! A [`Linter`] holds a set of [`Rule`]s, each of which inspects the AST and reports
+//! [`Violation`]s.  The linter tags each violation with its rule's name and configured
+//! [`Severity`], drops any suppressed by comments in the document, and returns the resulting
+//! [`Finding`]s in source order.
+//!
+//! Rules can be suppressed from within a document with HTML comments:
+//!
+//! * `<!-- comrak-disable rule-a rule-b -->` disables the named rules from the comment onwards,
+//!   and `<!-- comrak-enable rule-a rule-b -->` re-enables them.  Without any rule names, all
+//!   rules are affected.
+//! * `<!-- comrak-disable-next-line rule-a -->` disables the named rules (or all rules) for the
+//!   line following the comment only.
+//!
+//! ```
+//! # use comrak::{parse_document, Arena, Options};
+//! # use comrak::lint::{Linter, Severity};
+//! let arena = Arena::new();
+//! let source =
+//!     "# Title\n\n### Skipped a level\n\n<!-- comrak-disable heading-increment -->\n\n##### Again\n";
+//! let options = Options::default();
+//! let root = parse_document(&arena, source, &options);
+//!
+//! let mut linter = Linter::with_builtin_rules();
+//! linter.set_severity("heading-increment", Severity::Error);
+//!
+//! let findings = linter.check(root, source, &options);
+//! assert_eq!(findings.len(), 1);
+//! assert_eq!(findings[0].rule, "heading-increment");
+//! assert_eq!(findings[0].severity, Severity::Error);
+//! assert_eq!(findings[0].sourcepos, (3, 1, 3, 19).into());
+//! ```
+//!
+//! Rules are given the source text as well as the AST, so that they can see how something was
+//! written and not only what it parsed to.  Some take options, set with [`Linter::set_option`].
+//!
+//! Rules may attach a [`Fix`] to their violations, which [`Linter::fix`] applies.
+
+mod fix;
+pub mod rules;
+
+pub use fix::{Fix, Fixed, TextEdit};
+
+use crate::nodes::{AstNode, LineColumn, NodeValue, Sourcepos};
+use crate::Options;
+use std::error::Error;
+use std::fmt::{self, Debug, Formatter};
+
+/// How serious a [`Finding`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth knowing about, but not necessarily a problem.
+    Info,
+    /// A probable problem.
+    Warning,
+    /// A definite problem.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A problem reported by a [`Rule`].
+#[derive(Debug, Clone)]
+pub struct Violation {
+    /// The source position of the offending content.
+    pub sourcepos: Sourcepos,
+
+    /// A description of the problem.
+    pub message: String,
+
+    /// How to fix the problem automatically, if possible.
+    pub fix: Option<Fix>,
+}
+
+impl Violation {
+    /// Creates a violation at `sourcepos`, with no fix.
+    pub fn new(sourcepos: Sourcepos, message: impl Into<String>) -> Self {
+        Violation {
+            sourcepos,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    /// Attaches a fix to the violation.
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// A [`Violation`] which survived suppression, tagged with the rule that reported it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// The name of the rule which reported the violation.
+    pub rule: &'static str,
+
+    /// The configured severity of the rule.
+    pub severity: Severity,
+
+    /// The source position of the offending content.
+    pub sourcepos: Sourcepos,
+
+    /// A description of the problem.
+    pub message: String,
+}
+
+/// A lint rule.  Implement this to add checks to a [`Linter`].
+pub trait Rule: Send + Sync {
+    /// The rule's name, used in [`Finding`]s, severity configuration and suppression comments.
+    /// By convention this is lowercase and hyphenated, e.g. `heading-increment`.
+    fn name(&self) -> &'static str;
+
+    /// The severity of the rule's findings unless configured otherwise.
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    /// Inspects the document, given the source text and options it was parsed with, and returns
+    /// any violations found.
+    fn check<'a>(&self, root: &'a AstNode<'a>, source: &str, options: &Options) -> Vec<Violation>;
+
+    /// Sets the rule's option `name` to `value`.  Rules without options needn't override this,
+    /// which rejects every option.
+    fn set_option(&mut self, name: &str, value: &str) -> Result<(), OptionError> {
+        let _ = value;
+        Err(OptionError::UnknownOption(name.to_string()))
+    }
+}
+
+/// A problem with a rule option given to [`Linter::set_option`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OptionError {
+    /// No rule of the given name has been added.
+    UnknownRule(String),
+
+    /// The rule has no option of the given name.
+    UnknownOption(String),
+
+    /// The value isn't valid for the named option.
+    InvalidValue {
+        /// The name of the option.
+        option: String,
+        /// The value given.
+        value: String,
+    },
+}
+
+impl fmt::Display for OptionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            OptionError::UnknownRule(ref rule) => write!(f, "no rule is named {:?}", rule),
+            OptionError::UnknownOption(ref option) => {
+                write!(f, "the rule has no option {:?}", option)
+            }
+            OptionError::InvalidValue {
+                ref option,
+                ref value,
+            } => write!(f, "{:?} isn't a valid value for {:?}", value, option),
+        }
+    }
+}
+
+impl Error for OptionError {}
+
+struct Entry {
+    rule: Box<dyn Rule>,
+    severity: Severity,
+    enabled: bool,
+}
+
+/// A registry of [`Rule`]s and their configuration.
+#[derive(Default)]
+pub struct Linter {
+    entries: Vec<Entry>,
+}
+
+impl Debug for Linter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|e| {
+                (
+                    e.rule.name(),
+                    if e.enabled { Some(e.severity) } else { None },
+                )
+            }))
+            .finish()
+    }
+}
+
+impl Linter {
+    /// Creates a linter with no rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a linter with all of the rules in [`rules`].
+    pub fn with_builtin_rules() -> Self {
+        let mut linter = Self::new();
+        linter.add_rule(rules::HeadingIncrement);
+        linter.add_rule(rules::FencedCodeLanguage::default());
+        linter.add_rule(rules::NoEmptyLinks);
+        linter.add_rule(rules::NoTrailingSpaceBreaks);
+        linter.add_rule(rules::NoBareUrls);
+        linter.add_rule(rules::NoDuplicateHeadings::default());
+        linter
+    }
+
+    /// Adds a rule, enabled at its default severity.  A rule with the same name as one already
+    /// added replaces it.
+    pub fn add_rule(&mut self, rule: impl Rule + 'static) {
+        let entry = Entry {
+            severity: rule.default_severity(),
+            rule: Box::new(rule),
+            enabled: true,
+        };
+        match self.entry_mut(entry.rule.name()) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// The names of all added rules, in the order they were added.
+    pub fn rule_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.iter().map(|e| e.rule.name())
+    }
+
+    /// Sets the severity of the named rule's findings.  Returns `false` if no such rule has been
+    /// added.
+    pub fn set_severity(&mut self, rule: &str, severity: Severity) -> bool {
+        self.entry_mut(rule)
+            .map(|e| e.severity = severity)
+            .is_some()
+    }
+
+    /// Enables or disables the named rule.  Returns `false` if no such rule has been added.
+    pub fn set_enabled(&mut self, rule: &str, enabled: bool) -> bool {
+        self.entry_mut(rule).map(|e| e.enabled = enabled).is_some()
+    }
+
+    /// Sets the named rule's option `option` to `value`.  The options each rule takes are listed
+    /// in its documentation.
+    ///
+    /// ```
+    /// # use comrak::lint::{Linter, OptionError};
+    /// let mut linter = Linter::with_builtin_rules();
+    /// assert!(linter
+    ///     .set_option("fenced-code-language", "languages", "rust, toml")
+    ///     .is_ok());
+    /// assert_eq!(
+    ///     linter.set_option("heading-increment", "levels", "2"),
+    ///     Err(OptionError::UnknownOption("levels".to_string()))
+    /// );
+    /// ```
+    pub fn set_option(&mut self, rule: &str, option: &str, value: &str) -> Result<(), OptionError> {
+        match self.entry_mut(rule) {
+            Some(entry) => entry.rule.set_option(option, value),
+            None => Err(OptionError::UnknownRule(rule.to_string())),
+        }
+    }
+
+    fn entry_mut(&mut self, rule: &str) -> Option<&mut Entry> {
+        self.entries.iter_mut().find(|e| e.rule.name() == rule)
+    }
+
+    /// Runs every enabled rule over the document parsed from `source` with `options`, returning
+    /// the unsuppressed findings ordered by source position.
+    ///
+    /// ```
+    /// # use comrak::{parse_document, Arena, Options};
+    /// # use comrak::lint::Linter;
+    /// let arena = Arena::new();
+    /// let source = "Line one  \nline two.\n";
+    /// let options = Options::default();
+    /// let root = parse_document(&arena, source, &options);
+    ///
+    /// let findings = Linter::with_builtin_rules().check(root, source, &options);
+    /// assert_eq!(findings.len(), 1);
+    /// assert_eq!(findings[0].rule, "no-trailing-space-breaks");
+    /// assert_eq!(findings[0].sourcepos, (1, 9, 1, 10).into());
+    /// ```
+    pub fn check<'a>(
+        &self,
+        root: &'a AstNode<'a>,
+        source: &str,
+        options: &Options,
+    ) -> Vec<Finding> {
+        Self::findings(self.violations(root, source, options))
+    }
+
+    fn findings(violations: Vec<(&Entry, Violation)>) -> Vec<Finding> {
+        violations
+            .into_iter()
+            .map(|(entry, violation)| Finding {
+                rule: entry.rule.name(),
+                severity: entry.severity,
+                sourcepos: violation.sourcepos,
+                message: violation.message,
+            })
+            .collect()
+    }
+
+    fn violations<'a>(
+        &self,
+        root: &'a AstNode<'a>,
+        source: &str,
+        options: &Options,
+    ) -> Vec<(&Entry, Violation)> {
+        let suppressions = Suppressions::collect(root);
+
+        let mut violations = vec![];
+        for entry in self.entries.iter().filter(|e| e.enabled) {
+            for violation in entry.rule.check(root, source, options) {
+                if !suppressions.suppresses(entry.rule.name(), violation.sourcepos.start) {
+                    violations.push((entry, violation));
+                }
+            }
+        }
+
+        violations.sort_by_key(|(_, v)| v.sourcepos);
+        violations
+    }
+}
+
+enum Directive {
+    Disable,
+    Enable,
+    DisableNextLine,
+}
+
+struct Suppression {
+    at: LineColumn,
+    directive: Directive,
+    // Empty for all rules.
+    rules: Vec<String>,
+}
+
+struct Suppressions(Vec<Suppression>);
+
+impl Suppressions {
+    fn collect<'a>(root: &'a AstNode<'a>) -> Self {
+        let mut suppressions = vec![];
+        for node in root.descendants() {
+            let ast = node.data.borrow();
+            let html = match ast.value {
+                NodeValue::HtmlBlock(ref nhb) => &nhb.literal,
+                NodeValue::HtmlInline(ref literal) => literal,
+                _ => continue,
+            };
+            let inner = match html
+                .trim()
+                .strip_prefix("<!--")
+                .and_then(|s| s.strip_suffix("-->"))
+            {
+                Some(inner) => inner,
+                None => continue,
+            };
+
+            let mut words = inner.split_whitespace();
+            let directive = match words.next() {
+                Some("comrak-disable") => Directive::Disable,
+                Some("comrak-enable") => Directive::Enable,
+                Some("comrak-disable-next-line") => Directive::DisableNextLine,
+                _ => continue,
+            };
+            suppressions.push(Suppression {
+                at: ast.sourcepos.start,
+                directive,
+                rules: words.map(String::from).collect(),
+            });
+        }
+        suppressions.sort_by_key(|s| s.at);
+        Suppressions(suppressions)
+    }
+
+    fn suppresses(&self, rule: &str, at: LineColumn) -> bool {
+        let mut disabled = false;
+        for s in self.0.iter().take_while(|s| s.at <= at) {
+            if !s.rules.is_empty() && !s.rules.iter().any(|r| r == rule) {
+                continue;
+            }
+            match s.directive {
+                Directive::Disable => disabled = true,
+                Directive::Enable => disabled = false,
+                Directive::DisableNextLine if s.at.line + 1 == at.line => return true,
+                Directive::DisableNextLine => {}
+            }
+        }
+        disabled
+    }
+}
@@ -0,0 +1,399 @@
+//! Built-in lint rules.  [`Linter::with_builtin_rules`](super::Linter::with_builtin_rules)
+//! enables all of them, with their default options.
+
+use super::{Fix, OptionError, Rule, Violation};
+use crate::nodes::{AstNode, NodeValue, Sourcepos};
+use crate::Options;
+use std::collections::HashMap;
+
+fn find_node<'a>(
+    root: &'a AstNode<'a>,
+    sourcepos: Sourcepos,
+    pred: impl Fn(&NodeValue) -> bool,
+) -> Option<&'a AstNode<'a>> {
+    root.descendants().find(|node| {
+        let ast = node.data.borrow();
+        ast.sourcepos == sourcepos && pred(&ast.value)
+    })
+}
+
+/// `heading-increment`: heading levels should only increase by one at a time, so that no level
+/// is skipped.  Fixed by lowering the level of the offending heading.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeadingIncrement;
+
+impl Rule for HeadingIncrement {
+    fn name(&self) -> &'static str {
+        "heading-increment"
+    }
+
+    fn check<'a>(
+        &self,
+        root: &'a AstNode<'a>,
+        _source: &str,
+        _options: &Options,
+    ) -> Vec<Violation> {
+        let mut violations = vec![];
+        let mut previous = None;
+        for node in root.descendants() {
+            let ast = node.data.borrow();
+            if let NodeValue::Heading(ref nh) = ast.value {
+                if let Some(previous) = previous {
+                    if nh.level > previous + 1 {
+                        let sourcepos = ast.sourcepos;
+                        let level = previous + 1;
+                        violations.push(
+                            Violation::new(
+                                sourcepos,
+                                format!(
+                                    "heading level {} follows level {}; expected at most {}",
+                                    nh.level, previous, level
+                                ),
+                            )
+                            .with_fix(Fix::ast(move |root| {
+                                let heading = find_node(root, sourcepos, |value| {
+                                    matches!(value, NodeValue::Heading(..))
+                                });
+                                if let Some(heading) = heading {
+                                    if let NodeValue::Heading(ref mut nh) =
+                                        heading.data.borrow_mut().value
+                                    {
+                                        nh.level = level;
+                                    }
+                                }
+                            })),
+                        );
+                    }
+                }
+                previous = Some(nh.level);
+            }
+        }
+        violations
+    }
+}
+
+/// `fenced-code-language`: fenced code blocks should have an info string naming their language.
+///
+/// Options:
+///
+/// * `languages`: a comma-separated list of the languages allowed.  Empty, the default, allows
+///   any language.
+#[derive(Debug, Clone, Default)]
+pub struct FencedCodeLanguage {
+    /// The languages allowed, or empty for any.
+    pub languages: Vec<String>,
+}
+
+impl Rule for FencedCodeLanguage {
+    fn name(&self) -> &'static str {
+        "fenced-code-language"
+    }
+
+    fn check<'a>(
+        &self,
+        root: &'a AstNode<'a>,
+        _source: &str,
+        _options: &Options,
+    ) -> Vec<Violation> {
+        root.descendants()
+            .filter_map(|node| {
+                let ast = node.data.borrow();
+                let ncb = match ast.value {
+                    NodeValue::CodeBlock(ref ncb) if ncb.fenced => ncb,
+                    _ => return None,
+                };
+                let language = ncb.info.split_whitespace().next().unwrap_or("");
+                if language.is_empty() {
+                    Some(Violation::new(
+                        ast.sourcepos,
+                        "fenced code block has no language",
+                    ))
+                } else if !self.languages.is_empty()
+                    && !self.languages.iter().any(|l| l == language)
+                {
+                    Some(Violation::new(
+                        ast.sourcepos,
+                        format!("fenced code block language {} isn't allowed", language),
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) -> Result<(), OptionError> {
+        match name {
+            "languages" => {
+                self.languages = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .map(String::from)
+                    .collect();
+                Ok(())
+            }
+            _ => Err(OptionError::UnknownOption(name.to_string())),
+        }
+    }
+}
+
+/// `no-empty-links`: links should have a destination other than nothing or a bare `#`.  Fixed
+/// by replacing the link with its text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoEmptyLinks;
+
+impl Rule for NoEmptyLinks {
+    fn name(&self) -> &'static str {
+        "no-empty-links"
+    }
+
+    fn check<'a>(
+        &self,
+        root: &'a AstNode<'a>,
+        _source: &str,
+        _options: &Options,
+    ) -> Vec<Violation> {
+        root.descendants()
+            .filter_map(|node| {
+                let ast = node.data.borrow();
+                match ast.value {
+                    NodeValue::Link(ref nl) if nl.url.is_empty() || nl.url == "#" => {
+                        let sourcepos = ast.sourcepos;
+                        Some(
+                            Violation::new(sourcepos, "link has no destination").with_fix(
+                                Fix::ast(move |root| {
+                                    let link = find_node(root, sourcepos, |value| {
+                                        matches!(value, NodeValue::Link(..))
+                                    });
+                                    if let Some(link) = link {
+                                        while let Some(child) = link.first_child() {
+                                            link.insert_before(child);
+                                        }
+                                        link.detach();
+                                    }
+                                }),
+                            ),
+                        )
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// `no-trailing-space-breaks`: hard line breaks should be written with a backslash rather than
+/// trailing spaces, which are invisible and easily lost.  Fixed by replacing the spaces with a
+/// backslash.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoTrailingSpaceBreaks;
+
+impl Rule for NoTrailingSpaceBreaks {
+    fn name(&self) -> &'static str {
+        "no-trailing-space-breaks"
+    }
+
+    fn check<'a>(&self, root: &'a AstNode<'a>, source: &str, _options: &Options) -> Vec<Violation> {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut violations = vec![];
+        for node in root.descendants() {
+            let ast = node.data.borrow();
+            if !matches!(ast.value, NodeValue::Paragraph)
+                || !node
+                    .descendants()
+                    .any(|n| matches!(n.data.borrow().value, NodeValue::LineBreak))
+            {
+                continue;
+            }
+
+            // Every line of the paragraph but the last ends in a break of some kind.
+            for line in ast.sourcepos.start.line..ast.sourcepos.end.line {
+                let text = match lines.get(line - 1) {
+                    Some(text) => text.trim_end_matches('\r'),
+                    None => break,
+                };
+                let content = text.trim_end_matches(' ');
+                if text.len() - content.len() >= 2 && !content.trim().is_empty() {
+                    let sourcepos = (line, content.len() + 1, line, text.len()).into();
+                    violations.push(
+                        Violation::new(sourcepos, "hard line break made with trailing spaces")
+                            .with_fix(Fix::replace(sourcepos, "\\")),
+                    );
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// `no-bare-urls`: URLs in text should be written as autolinks, `<https://example.com>`, so
+/// that they are linked whether or not the
+/// [`autolink`](crate::ExtensionOptions::autolink) extension is enabled.  Fixed by making
+/// each URL a link, where it's written as it appears in the text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoBareUrls;
+
+impl Rule for NoBareUrls {
+    fn name(&self) -> &'static str {
+        "no-bare-urls"
+    }
+
+    fn check<'a>(&self, root: &'a AstNode<'a>, source: &str, _options: &Options) -> Vec<Violation> {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut violations = vec![];
+        for node in root.descendants() {
+            let ast = node.data.borrow();
+            let text = match ast.value {
+                NodeValue::Text(ref text) => text,
+                _ => continue,
+            };
+            if node.ancestors().any(|n| {
+                matches!(
+                    n.data.borrow().value,
+                    NodeValue::Link(..) | NodeValue::Image(..)
+                )
+            }) {
+                continue;
+            }
+
+            for (start, end) in bare_urls(text) {
+                let url = &text[start..end];
+                let message = format!("bare URL {}", url);
+
+                // The text's position is only a guide to where the URL is written: entities
+                // and escapes before it change its column.
+                let line = ast.sourcepos.start.line;
+                let column = ast.sourcepos.start.column + start;
+                let written = lines
+                    .get(line.wrapping_sub(1))
+                    .and_then(|l| l.get(column - 1..column - 1 + url.len()));
+                if written != Some(url) {
+                    violations.push(Violation::new(ast.sourcepos, message));
+                    continue;
+                }
+
+                let sourcepos = (line, column, line, column + url.len() - 1).into();
+                let replacement = if url.starts_with("www.") {
+                    format!("[{}](http://{})", url, url)
+                } else {
+                    format!("<{}>", url)
+                };
+                violations.push(
+                    Violation::new(sourcepos, message)
+                        .with_fix(Fix::replace(sourcepos, replacement)),
+                );
+            }
+        }
+        violations
+    }
+}
+
+// The byte ranges of the URLs starting `http://`, `https://` or `www.` in `text`, ending at
+// whitespace or `<`, less any trailing punctuation.
+fn bare_urls(text: &str) -> Vec<(usize, usize)> {
+    let mut urls = vec![];
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        let found = ["https://", "http://", "www."]
+            .iter()
+            .filter_map(|prefix| rest.find(prefix).map(|at| (at, prefix.len())))
+            .min();
+        let (at, prefix_len) = match found {
+            Some(found) => found,
+            None => break,
+        };
+        let start = i + at;
+        let preceded_by_word = text[..start]
+            .chars()
+            .next_back()
+            .map_or(false, |c| c.is_alphanumeric());
+        let end = start
+            + text[start..]
+                .find(|c: char| c.is_whitespace() || c == '<')
+                .unwrap_or(text.len() - start);
+        let end = start
+            + text[start..end]
+                .trim_end_matches(['.', ',', ':', ';', '!', '?', ')', '\'', '"'])
+                .len();
+        if !preceded_by_word && end > start + prefix_len {
+            urls.push((start, end));
+        }
+        i = end.max(start + prefix_len);
+    }
+    urls
+}
+
+/// `no-duplicate-headings`: headings should have text distinct from every other heading, so
+/// that each can be told apart in a table of contents and given its own anchor.
+///
+/// Options:
+///
+/// * `siblings-only`: `true` to only compare headings under the same parent heading, so that,
+///   e.g., each chapter can have its own "Examples" section.  Defaults to `false`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoDuplicateHeadings {
+    /// Whether to only compare headings under the same parent heading.
+    pub siblings_only: bool,
+}
+
+impl Rule for NoDuplicateHeadings {
+    fn name(&self) -> &'static str {
+        "no-duplicate-headings"
+    }
+
+    fn check<'a>(
+        &self,
+        root: &'a AstNode<'a>,
+        _source: &str,
+        _options: &Options,
+    ) -> Vec<Violation> {
+        let mut seen = HashMap::new();
+        let mut violations = vec![];
+        // The levels and texts of the headings enclosing the current one.
+        let mut parents: Vec<(u8, String)> = vec![];
+        for node in root.descendants() {
+            let ast = node.data.borrow();
+            let level = match ast.value {
+                NodeValue::Heading(ref nh) => nh.level,
+                _ => continue,
+            };
+            let text = node.text_content();
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            while parents.last().map_or(false, |&(l, _)| l >= level) {
+                parents.pop();
+            }
+            let scope: Vec<String> = if self.siblings_only {
+                parents.iter().map(|(_, t)| t.clone()).collect()
+            } else {
+                vec![]
+            };
+            let key = (scope, text.clone());
+            match seen.get(&key) {
+                Some(&line) => violations.push(Violation::new(
+                    ast.sourcepos,
+                    format!("heading \"{}\" duplicates the one on line {}", text, line),
+                )),
+                None => {
+                    seen.insert(key, ast.sourcepos.start.line);
+                }
+            }
+            parents.push((level, text));
+        }
+        violations
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) -> Result<(), OptionError> {
+        match name {
+            "siblings-only" => {
+                self.siblings_only = value.parse().map_err(|_| OptionError::InvalidValue {
+                    option: name.to_string(),
+                    value: value.to_string(),
+                })?;
+                Ok(())
+            }
+            _ => Err(OptionError::UnknownOption(name.to_string())),
+        }
+    }
+}
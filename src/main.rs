@@ -1,9 +1,10 @@
 //! The `comrak` binary.
 
 use comrak::{
-    adapters::SyntaxHighlighterAdapter, plugins::syntect::SyntectAdapter, Arena,
-    ExtensionOptionsBuilder, ListStyleType, Options, ParseOptionsBuilder, Plugins,
-    RenderOptionsBuilder,
+    adapters::SyntaxHighlighterAdapter, conditionals::resolve_conditionals, lint::Linter,
+    plugins::embed::FileImageEmbedder, plugins::syntect::SyntectAdapter, spec, Arena,
+    CodeFenceStyleType, ExtensionOptionsBuilder, HeadingStyleType, HtmlCommentStyle, ListStyleType,
+    Options, OrderedListStyleType, ParseOptionsBuilder, Plugins, RenderOptionsBuilder, SmartQuotes,
 };
 use std::boxed::Box;
 use std::env;
@@ -12,6 +13,7 @@ use std::fs;
 use std::io::{BufWriter, Read, Write};
 use std::path::PathBuf;
 use std::process;
+use std::str;
 
 use clap::{Parser, ValueEnum};
 
@@ -19,6 +21,7 @@ const EXIT_SUCCESS: i32 = 0;
 const EXIT_PARSE_CONFIG: i32 = 2;
 const EXIT_READ_INPUT: i32 = 3;
 const EXIT_CHECK_FILE_NUM: i32 = 4;
+const EXIT_SPEC_FAILED: i32 = 5;
 
 #[derive(Debug, Parser)]
 #[command(about, author, version)]
@@ -40,18 +43,41 @@ struct Cli {
     #[arg(short, long, conflicts_with_all(["format", "output"]))]
     inplace: bool,
 
+    /// Apply automatic fixes for the built-in lint rules, and output the result
+    /// as CommonMark. Findings which could not be fixed are reported on stderr
+    #[arg(long, conflicts_with("format"))]
+    fix: bool,
+
+    /// Treat the input as a spec file in the format of the CommonMark spec, and
+    /// report whether each example renders to the expected HTML with the given
+    /// options. Syntax highlighting is not applied
+    #[arg(long, conflicts_with_all(["inplace", "fix", "format", "output"]))]
+    spec: bool,
+
     /// Treat newlines as hard line breaks
     #[arg(long)]
     hardbreaks: bool,
 
+    /// Omit soft line breaks between Chinese or Japanese characters from HTML
+    #[arg(long)]
+    cjk_soft_breaks: bool,
+
     /// Use smart punctuation
     #[arg(long)]
     smart: bool,
 
+    /// The quotation marks smart punctuation uses
+    #[arg(long, value_enum, value_name = "LANGUAGE", default_value_t = Quotes::English)]
+    smart_quotes: Quotes,
+
     /// Use GitHub-style <pre lang> for code blocks
     #[arg(long)]
     github_pre_lang: bool,
 
+    /// Prefix for the class of code blocks' <code> elements [default: language-]
+    #[arg(long, value_name = "PREFIX")]
+    code_class_prefix: Option<String>,
+
     /// Enable full info strings for code blocks
     #[arg(long)]
     full_info_string: bool,
@@ -76,6 +102,10 @@ struct Cli {
     #[arg(long)]
     relaxed_autolinks: bool,
 
+    /// Keep entity and numeric character references as written when formatting CommonMark
+    #[arg(long)]
+    preserve_entities: bool,
+
     /// Default value for fenced code block's info strings if none is given
     #[arg(long, value_name = "INFO")]
     default_info_string: Option<String>,
@@ -84,6 +114,12 @@ struct Cli {
     #[arg(long = "unsafe")]
     unsafe_: bool,
 
+    /// Only allow links and images to relative URLs or URLs with the given schemes
+    ///
+    /// Multiple schemes can be delimited with ",", e.g. --url-schemes http,https,mailto
+    #[arg(long, value_name = "SCHEME", value_delimiter = ',')]
+    url_schemes: Vec<String>,
+
     /// Translate gemojis into UTF-8 characters
     #[arg(long)]
     #[cfg(feature = "shortcodes")]
@@ -93,6 +129,15 @@ struct Cli {
     #[arg(long)]
     escape: bool,
 
+    /// Embed images with relative sources in HTML output as data: URIs, reading
+    /// them relative to the first input file
+    #[arg(long)]
+    embed_images: bool,
+
+    /// How to render HTML comments: like other raw HTML, always, or never
+    #[arg(long, value_enum, default_value_t = HtmlComments::Raw)]
+    html_comments: HtmlComments,
+
     /// Wrap escaped characters in span tags
     #[arg(long)]
     escaped_char_spans: bool,
@@ -117,6 +162,14 @@ struct Cli {
     #[arg(short, long, value_name = "FILE")]
     output: Option<PathBuf>,
 
+    /// Specify tab stop width when parsing (0 = 4)
+    #[arg(long, default_value_t = 0)]
+    tab_width: usize,
+
+    /// Parse at most BYTES of the input, truncating it at a block boundary
+    #[arg(long, value_name = "BYTES")]
+    max_input_size: Option<usize>,
+
     /// Specify wrap width (0 = nowrap)
     #[arg(long, default_value_t = 0)]
     width: usize,
@@ -129,6 +182,10 @@ struct Cli {
     #[arg(long, value_name = "DELIMITER", allow_hyphen_values = true)]
     front_matter_delimiter: Option<String>,
 
+    /// Link @mentions to the URL given by the template, with {user} replaced by the username
+    #[arg(long, value_name = "TEMPLATE")]
+    mentions: Option<String>,
+
     /// Syntax highlighting for codefence blocks. Choose a theme or 'none' for disabling.
     #[arg(long, value_name = "THEME", default_value = "base16-ocean.dark")]
     syntax_highlighting: String,
@@ -137,6 +194,22 @@ struct Cli {
     #[arg(long, value_enum, default_value_t = ListStyle::Dash)]
     list_style: ListStyle,
 
+    /// Specify delimiter for ordered lists in CommonMark output
+    #[arg(long, value_enum, default_value_t = OrderedListStyle::Preserve)]
+    ordered_list_style: OrderedListStyle,
+
+    /// Specify style of level 1 and 2 headings in CommonMark output
+    #[arg(long, value_enum, default_value_t = HeadingStyle::Atx)]
+    heading_style: HeadingStyle,
+
+    /// Specify character for code fences in CommonMark output
+    #[arg(long, value_enum, default_value_t = CodeFenceStyle::Backtick)]
+    code_fence_style: CodeFenceStyle,
+
+    /// Minimum length of code fences in CommonMark output
+    #[arg(long, value_name = "LENGTH", default_value_t = 3)]
+    code_fence_length: usize,
+
     /// Include source position attribute in HTML and XML output
     #[arg(long)]
     sourcepos: bool,
@@ -152,6 +225,11 @@ struct Cli {
     /// Ignore empty links
     #[arg(long)]
     ignore_empty_links: bool,
+
+    /// Set a flag for resolving conditional blocks, as KEY=VALUE or KEY. Blocks whose
+    /// conditions the flags don't satisfy are dropped. Has no effect with --inplace or --fix
+    #[arg(long, value_name = "FLAG")]
+    condition_flag: Vec<String>,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -162,6 +240,22 @@ enum Format {
 
     #[value(name = "commonmark")]
     CommonMark,
+
+    Djot,
+
+    Typst,
+
+    Gemtext,
+
+    Slack,
+
+    Dot,
+
+    Latex,
+
+    Text,
+
+    Ast,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
@@ -172,7 +266,9 @@ enum Extension {
     Autolink,
     Tasklist,
     Superscript,
+    Subscript,
     Footnotes,
+    InlineFootnotes,
     DescriptionLists,
     MultilineBlockQuotes,
     MathDollars,
@@ -182,6 +278,19 @@ enum Extension {
     Underline,
     Spoiler,
     Greentext,
+    Abbreviations,
+    TemplateVariables,
+    TemplateTags,
+    ConditionalBlocks,
+    DetailsBlocks,
+    ContainerBlocks,
+    Citations,
+    JsxComponents,
+    RawRegions,
+    Alerts,
+    BlockAttributes,
+    ImageDimensions,
+    TableColspans,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -189,6 +298,7 @@ enum ListStyle {
     Dash,
     Plus,
     Star,
+    Preserve,
 }
 
 impl From<ListStyle> for ListStyleType {
@@ -197,6 +307,92 @@ impl From<ListStyle> for ListStyleType {
             ListStyle::Dash => Self::Dash,
             ListStyle::Plus => Self::Plus,
             ListStyle::Star => Self::Star,
+            ListStyle::Preserve => Self::Preserve,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum OrderedListStyle {
+    Preserve,
+    Period,
+    Paren,
+}
+
+impl From<OrderedListStyle> for OrderedListStyleType {
+    fn from(style: OrderedListStyle) -> Self {
+        match style {
+            OrderedListStyle::Preserve => Self::Preserve,
+            OrderedListStyle::Period => Self::Period,
+            OrderedListStyle::Paren => Self::Paren,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum HeadingStyle {
+    Atx,
+    Setext,
+    Preserve,
+}
+
+impl From<HeadingStyle> for HeadingStyleType {
+    fn from(style: HeadingStyle) -> Self {
+        match style {
+            HeadingStyle::Atx => Self::Atx,
+            HeadingStyle::Setext => Self::Setext,
+            HeadingStyle::Preserve => Self::Preserve,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum CodeFenceStyle {
+    Backtick,
+    Tilde,
+    Preserve,
+}
+
+impl From<CodeFenceStyle> for CodeFenceStyleType {
+    fn from(style: CodeFenceStyle) -> Self {
+        match style {
+            CodeFenceStyle::Backtick => Self::Backtick,
+            CodeFenceStyle::Tilde => Self::Tilde,
+            CodeFenceStyle::Preserve => Self::Preserve,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Quotes {
+    English,
+    German,
+    French,
+}
+
+impl From<Quotes> for SmartQuotes {
+    fn from(quotes: Quotes) -> Self {
+        match quotes {
+            Quotes::English => Self::english(),
+            Quotes::German => Self::german(),
+            Quotes::French => Self::french(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum HtmlComments {
+    Raw,
+    Keep,
+    Strip,
+}
+
+impl From<HtmlComments> for HtmlCommentStyle {
+    fn from(style: HtmlComments) -> Self {
+        match style {
+            HtmlComments::Raw => Self::Raw,
+            HtmlComments::Keep => Self::Keep,
+            HtmlComments::Strip => Self::Strip,
         }
     }
 }
@@ -246,17 +442,24 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
 
     let exts = &cli.extensions;
+    let gfm = if cli.gfm {
+        Options::gfm()
+    } else {
+        Options::default()
+    };
 
     let mut extension = ExtensionOptionsBuilder::default();
     extension
-        .strikethrough(exts.contains(&Extension::Strikethrough) || cli.gfm)
-        .tagfilter(exts.contains(&Extension::Tagfilter) || cli.gfm)
-        .table(exts.contains(&Extension::Table) || cli.gfm)
-        .autolink(exts.contains(&Extension::Autolink) || cli.gfm)
-        .tasklist(exts.contains(&Extension::Tasklist) || cli.gfm)
+        .strikethrough(exts.contains(&Extension::Strikethrough) || gfm.extension.strikethrough)
+        .tagfilter(exts.contains(&Extension::Tagfilter) || gfm.extension.tagfilter)
+        .table(exts.contains(&Extension::Table) || gfm.extension.table)
+        .autolink(exts.contains(&Extension::Autolink) || gfm.extension.autolink)
+        .tasklist(exts.contains(&Extension::Tasklist) || gfm.extension.tasklist)
         .superscript(exts.contains(&Extension::Superscript))
+        .subscript(exts.contains(&Extension::Subscript))
         .header_ids(cli.header_ids)
         .footnotes(exts.contains(&Extension::Footnotes))
+        .inline_footnotes(exts.contains(&Extension::InlineFootnotes))
         .description_lists(exts.contains(&Extension::DescriptionLists))
         .multiline_block_quotes(exts.contains(&Extension::MultilineBlockQuotes))
         .math_dollars(exts.contains(&Extension::MathDollars))
@@ -266,6 +469,20 @@ fn main() -> Result<(), Box<dyn Error>> {
         .underline(exts.contains(&Extension::Underline))
         .spoiler(exts.contains(&Extension::Spoiler))
         .greentext(exts.contains(&Extension::Greentext))
+        .abbreviations(exts.contains(&Extension::Abbreviations))
+        .template_variables(exts.contains(&Extension::TemplateVariables))
+        .template_tags(exts.contains(&Extension::TemplateTags))
+        .conditional_blocks(exts.contains(&Extension::ConditionalBlocks))
+        .details_blocks(exts.contains(&Extension::DetailsBlocks))
+        .container_blocks(exts.contains(&Extension::ContainerBlocks))
+        .citations(exts.contains(&Extension::Citations))
+        .jsx_components(exts.contains(&Extension::JsxComponents))
+        .raw_regions(exts.contains(&Extension::RawRegions))
+        .alerts(exts.contains(&Extension::Alerts))
+        .block_attributes(exts.contains(&Extension::BlockAttributes))
+        .image_dimensions(exts.contains(&Extension::ImageDimensions))
+        .table_colspans(exts.contains(&Extension::TableColspans))
+        .mentions(cli.mentions)
         .front_matter_delimiter(cli.front_matter_delimiter);
 
     #[cfg(feature = "shortcodes")]
@@ -277,31 +494,48 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let parse = ParseOptionsBuilder::default()
         .smart(cli.smart)
+        .smart_quotes(cli.smart_quotes.into())
         .default_info_string(cli.default_info_string)
         .relaxed_tasklist_matching(cli.relaxed_tasklist_character)
         .relaxed_autolinks(cli.relaxed_autolinks)
+        .preserve_entities(cli.preserve_entities)
+        .tab_width(cli.tab_width)
+        .max_input_size(cli.max_input_size)
         .build()?;
 
     let render = RenderOptionsBuilder::default()
         .hardbreaks(cli.hardbreaks)
-        .github_pre_lang(cli.github_pre_lang || cli.gfm)
+        .cjk_soft_breaks(cli.cjk_soft_breaks)
+        .github_pre_lang(cli.github_pre_lang || gfm.render.github_pre_lang)
+        .code_class_prefix(cli.code_class_prefix)
         .full_info_string(cli.full_info_string)
         .width(cli.width)
         .unsafe_(cli.unsafe_)
+        .url_schemes(if cli.url_schemes.is_empty() {
+            None
+        } else {
+            Some(cli.url_schemes)
+        })
         .escape(cli.escape)
+        .html_comments(cli.html_comments.into())
         .list_style(cli.list_style.into())
+        .ordered_list_style(cli.ordered_list_style.into())
+        .heading_style(cli.heading_style.into())
+        .code_fence_style(cli.code_fence_style.into())
+        .code_fence_length(cli.code_fence_length)
         .sourcepos(cli.sourcepos)
         .experimental_inline_sourcepos(cli.experimental_inline_sourcepos)
         .escaped_char_spans(cli.escaped_char_spans)
         .ignore_setext(cli.ignore_setext)
         .ignore_empty_links(cli.ignore_empty_links)
-        .gfm_quirks(cli.gfm_quirks || cli.gfm)
+        .gfm_quirks(cli.gfm_quirks || gfm.render.gfm_quirks)
         .build()?;
 
     let options = Options {
         extension,
         parse,
         render,
+        extensions: Default::default(),
     };
 
     let syntax_highlighter: Option<&dyn SyntaxHighlighterAdapter>;
@@ -316,6 +550,20 @@ fn main() -> Result<(), Box<dyn Error>> {
         syntax_highlighter = Some(&adapter);
     }
 
+    let embedder: FileImageEmbedder;
+    if cli.embed_images {
+        let root = match cli.files {
+            Some(ref fs) => fs
+                .first()
+                .and_then(|f| f.parent())
+                .map(PathBuf::from)
+                .unwrap_or_default(),
+            None => PathBuf::new(),
+        };
+        embedder = FileImageEmbedder::new(root);
+        plugins.render.image_resolver = Some(&embedder);
+    }
+
     let mut s: Vec<u8> = Vec::with_capacity(2048);
 
     match cli.files {
@@ -337,10 +585,68 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     };
 
+    if cli.spec {
+        let report = spec::run(str::from_utf8(&s)?, &options);
+        for result in &report.results {
+            let example = &result.example;
+            let status = if result.skipped() {
+                "skipped"
+            } else if result.passed() {
+                "passed"
+            } else {
+                "FAILED"
+            };
+            println!(
+                "example {} (line {}, {}): {}",
+                example.number, example.line, example.section, status
+            );
+            if let (false, Some(output)) = (result.passed(), &result.output) {
+                println!("  markdown: {:?}", example.markdown);
+                println!("  expected: {:?}", example.html);
+                println!("  got:      {:?}", output);
+            }
+        }
+        println!(
+            "{} passed, {} failed, {} skipped",
+            report.passed(),
+            report.failed(),
+            report.skipped()
+        );
+        process::exit(if report.failed() == 0 {
+            EXIT_SUCCESS
+        } else {
+            EXIT_SPEC_FAILED
+        });
+    }
+
+    if cli.fix {
+        let fixed = Linter::with_builtin_rules().fix(str::from_utf8(&s)?, &options);
+        for finding in &fixed.findings {
+            eprintln!(
+                "{}:{}: {}: {} [{}]",
+                finding.sourcepos.start.line,
+                finding.sourcepos.start.column,
+                finding.severity,
+                finding.message,
+                finding.rule
+            );
+        }
+        s = fixed.output.into_bytes();
+    }
+
     let arena = Arena::new();
-    let root = comrak::parse_document(&arena, &String::from_utf8(s)?, &options);
+    let root = comrak::parse_document_bytes(&arena, &s, &options)?;
+
+    if options.extension.conditional_blocks && !cli.inplace && !cli.fix {
+        let flags: Vec<(&str, &str)> = cli
+            .condition_flag
+            .iter()
+            .map(|flag| flag.split_once('=').unwrap_or((flag, "")))
+            .collect();
+        resolve_conditionals(root, |ncond| ncond.matches(&flags));
+    }
 
-    let formatter = if cli.inplace {
+    let formatter = if cli.inplace || cli.fix {
         comrak::format_commonmark_with_plugins
     } else {
         match cli.format {
@@ -350,6 +656,14 @@ fn main() -> Result<(), Box<dyn Error>> {
             }
             Format::Xml => comrak::format_xml_with_plugins,
             Format::CommonMark => comrak::format_commonmark_with_plugins,
+            Format::Djot => comrak::format_djot_with_plugins,
+            Format::Typst => comrak::format_typst_with_plugins,
+            Format::Gemtext => comrak::format_gemtext_with_plugins,
+            Format::Slack => comrak::format_slack_with_plugins,
+            Format::Dot => comrak::format_dot_with_plugins,
+            Format::Latex => comrak::format_latex_with_plugins,
+            Format::Text => comrak::format_text_with_plugins,
+            Format::Ast => format_ast,
         }
     };
 
@@ -372,6 +686,15 @@ fn main() -> Result<(), Box<dyn Error>> {
     process::exit(EXIT_SUCCESS);
 }
 
+fn format_ast<'a>(
+    root: &'a comrak::nodes::AstNode<'a>,
+    _options: &Options,
+    output: &mut dyn Write,
+    _plugins: &Plugins,
+) -> std::io::Result<()> {
+    output.write_all(root.ast_dump().as_bytes())
+}
+
 #[cfg(all(not(windows), not(target_arch = "wasm32")))]
 fn get_default_config_path() -> String {
     if let Ok(xdg_dirs) = xdg::BaseDirectories::with_prefix("comrak") {
@@ -0,0 +1,147 @@
+//! Merging of several Markdown documents into one, e.g. to build a single-page
+//! book from chapter files.
+
+use crate::nodes::{AstNode, NodeValue};
+use crate::parser::{parse_document_with_context, Options, ParseContext, ResolvedReference};
+use std::collections::HashMap;
+use typed_arena::Arena;
+
+/// A document to merge with [`merge_documents`].
+#[derive(Debug, Clone, Default)]
+pub struct MergeSource<'s> {
+    /// The Markdown source of the document.
+    pub source: &'s str,
+
+    /// The number of levels to demote the document's headings by.  Headings
+    /// are never demoted past level 6.
+    pub demote_headings: u8,
+
+    /// A prefix for the names of the document's footnotes, so they don't clash
+    /// with those of other documents.
+    pub footnote_prefix: Option<String>,
+}
+
+/// How a link reference label defined in more than one document is resolved.
+/// See [`merge_documents`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReferenceConflicts {
+    /// The first definition in any document is used everywhere, as though the
+    /// documents were one file.
+    #[default]
+    First,
+    /// Each document uses its own definition of a label if it has one, and
+    /// the first definition in any other document otherwise.
+    Local,
+    /// Documents don't share definitions at all.
+    Isolated,
+}
+
+/// Parses each of `sources` and merges them into a single document, in order.
+///
+/// Link reference definitions aren't kept in the AST, so documents are merged
+/// from source rather than after parsing; `references` determines how labels
+/// defined in more than one document are resolved.  Footnotes are renumbered
+/// to run on across documents, each document's footnotes remaining at its
+/// end.  Front matter is kept only from the first document.  Source
+/// positions in the result refer to each node's own source.
+///
+/// ```
+/// # use comrak::{format_commonmark, Arena, Options};
+/// # use comrak::merge::{merge_documents, MergeSource, ReferenceConflicts};
+/// let arena = Arena::new();
+/// let sources = [
+///     MergeSource {
+///         source: "# Book\n\nSee [the site].\n\n[the site]: https://example.com\n",
+///         ..Default::default()
+///     },
+///     MergeSource {
+///         source: "# Chapter\n\nBack to [the site].\n",
+///         demote_headings: 1,
+///         ..Default::default()
+///     },
+/// ];
+/// let root = merge_documents(&arena, &sources, &Options::default(), ReferenceConflicts::First);
+///
+/// let mut md = vec![];
+/// format_commonmark(root, &Options::default(), &mut md).unwrap();
+/// assert_eq!(
+///     String::from_utf8(md).unwrap(),
+///     "# Book\n\nSee [the site](https://example.com).\n\n## Chapter\n\n\
+///      Back to [the site](https://example.com).\n"
+/// );
+/// ```
+pub fn merge_documents<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    sources: &[MergeSource],
+    options: &Options,
+    references: ReferenceConflicts,
+) -> &'a AstNode<'a> {
+    let defined: Vec<HashMap<String, ResolvedReference>> = match references {
+        ReferenceConflicts::Isolated => vec![],
+        _ => {
+            let scratch = Arena::new();
+            sources
+                .iter()
+                .map(|s| {
+                    let mut context = ParseContext::new();
+                    parse_document_with_context(&scratch, s.source, options, &mut context);
+                    context.references
+                })
+                .collect()
+        }
+    };
+
+    let merged = arena.alloc(NodeValue::Document.into());
+    let mut footnotes = 0;
+
+    for (i, source) in sources.iter().enumerate() {
+        let mut seed = HashMap::new();
+        if references == ReferenceConflicts::Local {
+            seed.extend(defined[i].clone());
+        }
+        for refs in &defined {
+            for (label, reference) in refs {
+                seed.entry(label.clone())
+                    .or_insert_with(|| reference.clone());
+            }
+        }
+
+        let mut context = ParseContext {
+            references: seed,
+            footnotes,
+            ..ParseContext::default()
+        };
+        let root = parse_document_with_context(arena, source.source, options, &mut context);
+        footnotes = context.footnotes;
+
+        for node in root.descendants() {
+            match node.data.borrow_mut().value {
+                NodeValue::Heading(ref mut nh) if source.demote_headings > 0 => {
+                    nh.level = nh.level.saturating_add(source.demote_headings).min(6);
+                    nh.setext &= nh.level <= 2;
+                }
+                NodeValue::FootnoteDefinition(ref mut nfd) => {
+                    if let Some(ref prefix) = source.footnote_prefix {
+                        nfd.name.insert_str(0, prefix);
+                    }
+                }
+                NodeValue::FootnoteReference(ref mut nfr) => {
+                    if let Some(ref prefix) = source.footnote_prefix {
+                        nfr.name.insert_str(0, prefix);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        while let Some(child) = root.first_child() {
+            if i > 0 && matches!(child.data.borrow().value, NodeValue::FrontMatter(..)) {
+                child.detach();
+                continue;
+            }
+            merged.append(child);
+        }
+    }
+
+    merged
+}
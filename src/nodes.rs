@@ -1,17 +1,27 @@
 //! The CommonMark AST.
 
 use crate::arena_tree::Node;
+use crate::strings::is_cjk;
 use std::cell::RefCell;
 use std::convert::TryFrom;
 
 #[cfg(feature = "shortcodes")]
 pub use crate::parser::shortcodes::NodeShortCode;
 
+pub use crate::extensions::{NodeExtension, UserData};
+pub use crate::parser::abbreviation::NodeAbbreviationDefinition;
+pub use crate::parser::alert::{AlertType, NodeAlert};
+pub use crate::parser::attributes::NodeAttributes;
+pub use crate::parser::citation::{CitationItem, NodeCitation};
+pub use crate::parser::component::NodeComponent;
+pub use crate::parser::container::{ContainerKind, NodeContainer};
 pub use crate::parser::math::NodeMath;
 pub use crate::parser::multiline_block_quote::NodeMultilineBlockQuote;
+pub use crate::parser::template::NodeTemplateVariable;
 
 /// The core AST node enum.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NodeValue {
     /// The root of every CommonMark document.  Contains **blocks**.
     Document,
@@ -19,6 +29,11 @@ pub enum NodeValue {
     /// Non-Markdown front matter.  Treated as an opaque blob.
     FrontMatter(String),
 
+    /// The end of a document whose input was cut short, as the last child of
+    /// the document.  The `usize` is the number of bytes of input left
+    /// unparsed.  See [`ParseOptions::max_input_size`](crate::ParseOptions::max_input_size).
+    Truncated(usize),
+
     /// **Block**. A [block quote](https://github.github.com/gfm/#block-quotes).  Contains other
     /// **blocks**.
     ///
@@ -101,7 +116,10 @@ pub enum NodeValue {
     /// Contains table cells.
     TableRow(bool),
 
-    /// **Block**.  A table cell.  Contains **inlines**.
+    /// **Block**.  A table cell.  Contains **inlines**.  Its column, alignment
+    /// and whether it's in the header row are given by
+    /// [`Node::table_cell_info`](crate::arena_tree::Node::table_cell_info),
+    /// and any span beyond one column by [`Ast::table_cell`].
     TableCell,
 
     /// **Inline**.  [Textual content](https://github.github.com/gfm/#textual-content).  All text
@@ -127,6 +145,12 @@ pub enum NodeValue {
     /// **Inline**.  [Raw HTML](https://github.github.com/gfm/#raw-html) contained inline.
     HtmlInline(String),
 
+    /// **Inline**.  An [entity or numeric character
+    /// reference](https://github.github.com/gfm/#entity-and-numeric-character-references),
+    /// kept as written.  Only produced with the `preserve_entities` option;
+    /// otherwise references are resolved into `Text`.
+    Entity(NodeEntity),
+
     /// **Inline**.  [Emphasized](https://github.github.com/gfm/#emphasis-and-strong-emphasis)
     /// text.
     Emph,
@@ -141,6 +165,9 @@ pub enum NodeValue {
     /// **Inline**.  Superscript.  Enabled with `ext_superscript` option.
     Superscript,
 
+    /// **Inline**.  Subscript.  Enabled with `subscript` option.
+    Subscript,
+
     /// **Inline**.  A [link](https://github.github.com/gfm/#links) to some URL, with possible
     /// title.
     Link(NodeLink),
@@ -195,10 +222,105 @@ pub enum NodeValue {
     /// **Inline**. Text surrounded by escaped markup. Enabled with `spoiler` option.
     /// The `String` is the tag to be escaped.
     EscapedTag(String),
+
+    /// **Block**. An abbreviation definition.  Enabled with `abbreviations` option.
+    ///
+    /// ``` md
+    /// *[HTML]: HyperText Markup Language
+    /// ```
+    AbbreviationDefinition(NodeAbbreviationDefinition),
+
+    /// **Inline**. A template variable placeholder.  Enabled with
+    /// `template_variables` option.
+    ///
+    /// ``` md
+    /// {{ name | filter }}
+    /// ```
+    TemplateVariable(NodeTemplateVariable),
+
+    /// **Inline**. A template tag, kept verbatim.  Enabled with
+    /// `template_tags` option.
+    ///
+    /// ``` md
+    /// {% include footer.html %}
+    /// ```
+    TemplateTag(String),
+
+    /// **Block**. A block fenced by lines of three or more colons: a named
+    /// container, or fenced div, a conditional block or a collapsible
+    /// section, as given by its [`kind`](NodeContainer::kind).  Enabled with
+    /// the `container_blocks`, `conditional_blocks` and `details_blocks`
+    /// options.  Contains other **blocks**.
+    ///
+    /// ``` md
+    /// ::: warning {#careful}
+    /// Mind the gap.
+    /// :::
+    ///
+    /// ::: if feature="enterprise"
+    /// Contact your account manager.
+    /// :::
+    ///
+    /// ::: details Show the answer
+    /// It is 42.
+    /// :::
+    /// ```
+    Container(NodeContainer),
+
+    /// **Block**. A GitHub-style alert: a block quote with a type, and
+    /// optionally a title.  Enabled with `alerts` option.  Contains other
+    /// **blocks**.
+    ///
+    /// ``` md
+    /// > [!WARNING] Careful
+    /// > This can't be undone.
+    /// ```
+    Alert(NodeAlert),
+
+    /// **Inline**. A citation of one or more works.  Enabled with
+    /// `citations` option.
+    ///
+    /// ``` md
+    /// [see @key, p. 4; @other]
+    /// ```
+    Citation(NodeCitation),
+
+    /// **Block**. A JSX-style component starting a line, kept verbatim.
+    /// Enabled with `jsx_components` option.
+    ///
+    /// ``` md
+    /// <Widget prop="x">
+    /// ...
+    /// </Widget>
+    /// ```
+    ComponentBlock(NodeComponent),
+
+    /// **Inline**. A JSX-style component within a paragraph, kept verbatim.
+    /// Enabled with `jsx_components` option.
+    ComponentInline(NodeComponent),
+
+    /// **Block**. A region of source kept verbatim.  Enabled with
+    /// `raw_regions` option.
+    ///
+    /// ``` md
+    /// <!-- comrak:off -->
+    /// ...
+    /// <!-- comrak:on -->
+    /// ```
+    Raw(String),
+
+    /// **Block**. A block opened by a registered
+    /// [`ComrakExtension`](crate::extensions::ComrakExtension).
+    ExtensionBlock(NodeExtension),
+
+    /// **Inline**. An inline parsed by a registered
+    /// [`ComrakExtension`](crate::extensions::ComrakExtension).
+    ExtensionInline(NodeExtension),
 }
 
 /// Alignment of a single table cell.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TableAlignment {
     /// Cell content is unaligned.
     None,
@@ -226,6 +348,7 @@ impl TableAlignment {
 
 /// The metadata of a table
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeTable {
     /// The table alignments
     pub alignments: Vec<TableAlignment>,
@@ -233,15 +356,59 @@ pub struct NodeTable {
     /// Number of columns of the table
     pub num_columns: usize,
 
-    /// Number of rows of the table
+    /// Number of rows of the table, not including the header row
     pub num_rows: usize,
 
     /// Number of non-empty, non-autocompleted cells
     pub num_nonempty_cells: usize,
 }
 
+/// The metadata of a table cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeTableCell {
+    /// The number of columns the cell spans.  Only ever more than 1 with
+    /// [`ExtensionOptions::table_colspans`](crate::ExtensionOptions::table_colspans).
+    pub colspan: usize,
+}
+
+impl Default for NodeTableCell {
+    fn default() -> Self {
+        NodeTableCell { colspan: 1 }
+    }
+}
+
+/// The label and anchor of a numbered figure or table, as assigned by
+/// [`figures::number_figures`](crate::figures::number_figures) and
+/// [`figures::number_tables`](crate::figures::number_tables).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeFigure {
+    /// The numbered label, e.g. `Figure 1`.
+    pub label: String,
+
+    /// The `id` of the figure or table in HTML, e.g. `figure-1`.
+    pub id: String,
+}
+
+/// The position and alignment of a table cell, as returned by
+/// [`Node::table_cell_info`](crate::arena_tree::Node::table_cell_info).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableCellInfo {
+    /// The index of the cell's column, from 0.
+    pub column: usize,
+
+    /// The alignment of the cell's column.
+    pub alignment: TableAlignment,
+
+    /// Whether the cell is in the header row.
+    pub header: bool,
+}
+
 /// An inline [code span](https://github.github.com/gfm/#code-spans).
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeCode {
     /// The number of backticks
     pub num_backticks: usize,
@@ -253,8 +420,32 @@ pub struct NodeCode {
     pub literal: String,
 }
 
+/// An entity or numeric character reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeEntity {
+    /// The reference as written, e.g. `&copy;` or `&#169;`.
+    pub literal: String,
+
+    /// The characters the reference stands for, e.g. `©`.
+    pub characters: String,
+}
+
+/// The delimiters of an emphasis or strong emphasis node.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeEmphasis {
+    /// The delimiter character, `*` or `_`.
+    pub delim_char: u8,
+
+    /// The length of the delimiter run which opened the node; for example,
+    /// both nodes produced by `***a***` have a run length of 3.
+    pub delim_run_length: usize,
+}
+
 /// The details of a link's destination, or an image's source.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeLink {
     /// The URL for the link destination or image source.
     pub url: String,
@@ -264,10 +455,33 @@ pub struct NodeLink {
     /// Note this field is used for the `title` attribute by the HTML formatter even for images;
     /// `alt` text is supplied in the image inline text.
     pub title: String,
+
+    /// For images, the width given by a size, `![alt](url =640x480)`, with
+    /// [`ExtensionOptions::image_dimensions`](crate::ExtensionOptions::image_dimensions).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub width: Option<u32>,
+
+    /// For images, the height given by a size.  See [`width`](Self::width).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub height: Option<u32>,
+}
+
+impl NodeLink {
+    /// A link destination or image source with the given title, which may be
+    /// empty.
+    pub fn new(url: impl Into<String>, title: impl Into<String>) -> Self {
+        NodeLink {
+            url: url.into(),
+            title: title.into(),
+            width: None,
+            height: None,
+        }
+    }
 }
 
 /// The details of a wikilink's destination.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeWikiLink {
     /// The URL for the link destination.
     pub url: String,
@@ -275,6 +489,7 @@ pub struct NodeWikiLink {
 
 /// The metadata of a list; the kind of list, the delimiter used and so on.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeList {
     /// The kind of list (bullet (unordered) or ordered).
     pub list_type: ListType,
@@ -299,8 +514,35 @@ pub struct NodeList {
     pub tight: bool,
 }
 
+impl NodeList {
+    /// A tight bullet list, or item of one, marked with `-`.
+    pub fn bullet() -> Self {
+        NodeList {
+            list_type: ListType::Bullet,
+            marker_offset: 0,
+            padding: 2,
+            start: 1,
+            delimiter: ListDelimType::Period,
+            bullet_char: b'-',
+            tight: true,
+        }
+    }
+
+    /// A tight ordered list, or item of one, numbered from `start` and
+    /// delimited with `.`.
+    pub fn ordered(start: usize) -> Self {
+        NodeList {
+            list_type: ListType::Ordered,
+            padding: start.to_string().len() + 2,
+            start,
+            ..NodeList::bullet()
+        }
+    }
+}
+
 /// The metadata of a description list
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeDescriptionItem {
     /// Number of spaces before the list marker.
     pub marker_offset: usize,
@@ -311,6 +553,7 @@ pub struct NodeDescriptionItem {
 
 /// The type of list.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ListType {
     /// A bullet list, i.e. an unordered list.
     #[default]
@@ -322,6 +565,7 @@ pub enum ListType {
 
 /// The delimiter for ordered lists, i.e. the character which appears after each number.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ListDelimType {
     /// A period character `.`.
     #[default]
@@ -342,6 +586,7 @@ impl ListDelimType {
 
 /// The metadata and data of a code block (fenced or indented).
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeCodeBlock {
     /// Whether the code block is fenced.
     pub fenced: bool,
@@ -363,20 +608,58 @@ pub struct NodeCodeBlock {
     /// all, they are contained within this structure, rather than inserted into a child inline of
     /// any kind.
     pub literal: String,
+
+    /// For fenced code blocks, the attributes given by an attribute block at
+    /// the end of the info string, with
+    /// [`ExtensionOptions::block_attributes`](crate::ExtensionOptions::block_attributes).
+    /// The attribute block is not part of [`info`](Self::info).
+    pub attributes: NodeAttributes,
+}
+
+impl NodeCodeBlock {
+    /// A code block fenced with three backticks, with the given info string,
+    /// which may be empty.  The literal should end with a newline.
+    pub fn fenced(info: impl Into<String>, literal: impl Into<String>) -> Self {
+        NodeCodeBlock {
+            fenced: true,
+            fence_char: b'`',
+            fence_length: 3,
+            fence_offset: 0,
+            info: info.into(),
+            literal: literal.into(),
+            attributes: NodeAttributes::default(),
+        }
+    }
 }
 
 /// The metadata of a heading.
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeHeading {
     /// The level of the header; from 1 to 6 for ATX headings, 1 or 2 for setext headings.
     pub level: u8,
 
     /// Whether the heading is setext (if not, ATX).
     pub setext: bool,
+
+    /// The attributes given by an attribute block at the end of the heading,
+    /// with [`ExtensionOptions::block_attributes`](crate::ExtensionOptions::block_attributes).
+    pub attributes: NodeAttributes,
+}
+
+impl NodeHeading {
+    /// An ATX heading of the given level, from 1 to 6.
+    pub fn new(level: u8) -> Self {
+        NodeHeading {
+            level,
+            ..NodeHeading::default()
+        }
+    }
 }
 
 /// The metadata of an included HTML block.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeHtmlBlock {
     /// The HTML block's type
     pub block_type: u8,
@@ -388,16 +671,21 @@ pub struct NodeHtmlBlock {
 
 /// The metadata of a footnote definition.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeFootnoteDefinition {
     /// The name of the footnote.
     pub name: String,
 
     /// Total number of references to this footnote
     pub total_references: u32,
+
+    /// The number of the footnote, as shown to readers; 0 if not yet numbered.
+    pub ix: u32,
 }
 
 /// The metadata of a footnote reference.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeFootnoteReference {
     /// The name of the footnote.
     pub name: String,
@@ -433,6 +721,12 @@ impl NodeValue {
                 | NodeValue::TableCell
                 | NodeValue::TaskItem(..)
                 | NodeValue::MultilineBlockQuote(_)
+                | NodeValue::AbbreviationDefinition(_)
+                | NodeValue::Container(_)
+                | NodeValue::Alert(_)
+                | NodeValue::ComponentBlock(_)
+                | NodeValue::Raw(_)
+                | NodeValue::ExtensionBlock(_)
         )
     }
 
@@ -444,6 +738,11 @@ impl NodeValue {
         )
     }
 
+    /// A `Text` inline of the given text.
+    pub fn new_text(text: impl Into<String>) -> Self {
+        NodeValue::Text(text.into())
+    }
+
     /// Return a reference to the text of a `Text` inline, if this node is one.
     ///
     /// Convenience method.
@@ -499,10 +798,13 @@ impl NodeValue {
             NodeValue::Strong => "strong",
             NodeValue::Code(..) => "code",
             NodeValue::HtmlInline(..) => "html_inline",
+            NodeValue::Entity(..) => "entity",
             NodeValue::Strikethrough => "strikethrough",
             NodeValue::FrontMatter(_) => "frontmatter",
+            NodeValue::Truncated(_) => "truncated",
             NodeValue::TaskItem { .. } => "taskitem",
             NodeValue::Superscript => "superscript",
+            NodeValue::Subscript => "subscript",
             NodeValue::FootnoteReference(..) => "footnote_reference",
             #[cfg(feature = "shortcodes")]
             NodeValue::ShortCode(_) => "shortcode",
@@ -513,6 +815,21 @@ impl NodeValue {
             NodeValue::Underline => "underline",
             NodeValue::SpoileredText => "spoiler",
             NodeValue::EscapedTag(_) => "escaped_tag",
+            NodeValue::AbbreviationDefinition(_) => "abbreviation_definition",
+            NodeValue::TemplateVariable(_) => "template_variable",
+            NodeValue::TemplateTag(_) => "template_tag",
+            NodeValue::Container(ref nc) => match nc.kind {
+                ContainerKind::Named => "container",
+                ContainerKind::Conditional => "conditional",
+                ContainerKind::Details => "details",
+            },
+            NodeValue::Alert(_) => "alert",
+            NodeValue::Citation(_) => "citation",
+            NodeValue::ComponentBlock(_) => "component_block",
+            NodeValue::Raw(_) => "raw",
+            NodeValue::ExtensionBlock(_) => "extension_block",
+            NodeValue::ComponentInline(_) => "component_inline",
+            NodeValue::ExtensionInline(_) => "extension_inline",
         }
     }
 }
@@ -522,23 +839,65 @@ impl NodeValue {
 /// The struct contains metadata about the node's position in the original document, and the core
 /// enum, `NodeValue`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ast {
     /// The node value itself.
     pub value: NodeValue,
 
     /// The positions in the source document this node comes from.
     pub sourcepos: Sourcepos,
+
+    /// The byte offset in the parsed input of the start of the node, as given
+    /// by [`sourcepos`](Ast::sourcepos).  Zero for nodes not created by the
+    /// parser.
+    pub start_offset: usize,
+
+    /// The byte offset in the parsed input just past the end of the node, so
+    /// that `&input[start_offset..end_offset]` is its source.
+    pub end_offset: usize,
+
+    /// For [`Emph`](NodeValue::Emph) and [`Strong`](NodeValue::Strong) nodes
+    /// produced by the parser, the delimiters that opened them.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub emphasis: Option<NodeEmphasis>,
+
+    /// For [`TableCell`](NodeValue::TableCell) nodes spanning more than one
+    /// column, their span.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub table_cell: Option<NodeTableCell>,
+
+    /// For [`Image`](NodeValue::Image) and [`Table`](NodeValue::Table) nodes
+    /// which have been numbered, their label and anchor.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub figure: Option<NodeFigure>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) internal_offset: usize,
 
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) content: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) open: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) last_line_blank: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) table_visited: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) line_offsets: Vec<usize>,
 }
 
 /// Represents the position in the source Markdown this node was rendered from.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sourcepos {
     /// The line and column of the first character of this node.
     pub start: LineColumn,
@@ -573,6 +932,7 @@ impl From<(usize, usize, usize, usize)> for Sourcepos {
 
 /// Represents the 1-based line and column positions of a given character.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineColumn {
     /// The 1-based line number of the character.
     pub line: usize,
@@ -606,6 +966,11 @@ impl Ast {
             value,
             content: String::new(),
             sourcepos: (start.line, start.column, start.line, 0).into(),
+            start_offset: 0,
+            end_offset: 0,
+            emphasis: None,
+            table_cell: None,
+            figure: None,
             internal_offset: 0,
             open: true,
             last_line_blank: false,
@@ -665,6 +1030,114 @@ impl<'a> From<Ast> for AstNode<'a> {
     }
 }
 
+impl From<NodeValue> for Ast {
+    /// Create a new AST node with the given value. The sourcepos is set to (0,0)-(0,0).
+    fn from(value: NodeValue) -> Self {
+        Ast::new(value, LineColumn::default())
+    }
+}
+
+/// Builds a tree of nodes in an arena, appending children in order.
+///
+/// Nodes built this way have no source positions, which the formatters don't
+/// need; this suits programs which generate documents to render.
+///
+/// ```
+/// # use comrak::{format_commonmark, Arena, Options};
+/// # use comrak::nodes::{AstBuilder, NodeHeading, NodeLink, NodeList, NodeValue};
+/// let arena = Arena::new();
+/// let mut builder = AstBuilder::new(&arena);
+/// builder
+///     .child(NodeValue::Heading(NodeHeading::new(2)), |b| {
+///         b.text("v1.2.0");
+///     })
+///     .child(NodeValue::List(NodeList::bullet()), |b| {
+///         for change in ["Faster parsing", "Fewer bugs"] {
+///             b.child(NodeValue::Item(NodeList::bullet()), |b| {
+///                 b.child(NodeValue::Paragraph, |b| {
+///                     b.text(change);
+///                 });
+///             });
+///         }
+///     })
+///     .child(NodeValue::Paragraph, |b| {
+///         b.child(NodeValue::Link(NodeLink::new("/log", "")), |b| {
+///             b.text("Full log");
+///         });
+///     });
+///
+/// let mut md = vec![];
+/// format_commonmark(builder.root(), &Options::default(), &mut md).unwrap();
+/// assert_eq!(
+///     String::from_utf8(md).unwrap(),
+///     "## v1.2.0\n\n- Faster parsing\n- Fewer bugs\n\n[Full log](/log)\n"
+/// );
+/// ```
+pub struct AstBuilder<'a> {
+    arena: &'a typed_arena::Arena<AstNode<'a>>,
+    root: &'a AstNode<'a>,
+    current: &'a AstNode<'a>,
+}
+
+impl<'a> AstBuilder<'a> {
+    /// Starts building a document, under a new `Document` root.
+    pub fn new(arena: &'a typed_arena::Arena<AstNode<'a>>) -> Self {
+        let root = arena.alloc(NodeValue::Document.into());
+        AstBuilder::with_root(arena, root)
+    }
+
+    /// Starts building under `root`, after any children it already has.
+    pub fn with_root(arena: &'a typed_arena::Arena<AstNode<'a>>, root: &'a AstNode<'a>) -> Self {
+        AstBuilder {
+            arena,
+            root,
+            current: root,
+        }
+    }
+
+    /// Appends a node of the given value, whose children are built by `f`.
+    pub fn child<F>(&mut self, value: NodeValue, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut Self),
+    {
+        let parent = self.current;
+        self.current = self.append(value);
+        f(self);
+        self.current = parent;
+        self
+    }
+
+    /// Appends a node of the given value with no children.
+    pub fn leaf(&mut self, value: NodeValue) -> &mut Self {
+        self.append(value);
+        self
+    }
+
+    /// Appends a `Text` inline.
+    pub fn text(&mut self, text: impl Into<String>) -> &mut Self {
+        self.leaf(NodeValue::new_text(text))
+    }
+
+    /// The root the tree is built under.
+    pub fn root(&self) -> &'a AstNode<'a> {
+        self.root
+    }
+
+    fn append(&self, value: NodeValue) -> &'a AstNode<'a> {
+        let node = self.arena.alloc(value.into());
+        self.current.append(node);
+        node
+    }
+}
+
+impl<'a> std::fmt::Debug for AstBuilder<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AstBuilder")
+            .field("root", &self.root)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Validation errors produced by [Node::validate].
 #[derive(Debug, Clone)]
 pub enum ValidationError<'a> {
@@ -678,7 +1151,342 @@ pub enum ValidationError<'a> {
     },
 }
 
+/// Counts of task list items, as returned by [Node::task_progress].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TaskProgress {
+    /// The number of checked task items.
+    pub completed: usize,
+    /// The total number of task items.
+    pub total: usize,
+}
+
+/// The task list items found in one section of a document, as returned by
+/// [Node::task_progress_by_section].
+#[derive(Debug, Clone, Copy)]
+pub struct SectionTaskProgress<'a> {
+    /// The heading which begins the section, or `None` for any content before
+    /// the first heading.
+    pub heading: Option<&'a AstNode<'a>>,
+    /// The task items within the section.
+    pub progress: TaskProgress,
+}
+
+// Writes the properties and literal text of a node for Node::ast_dump.
+fn ast_dump_details(ast: &Ast, output: &mut String) {
+    fn literal(text: &str, output: &mut String) {
+        match text.char_indices().nth(40) {
+            Some((end, _)) => output.push_str(&format!(" {:?}…", &text[..end])),
+            None => output.push_str(&format!(" {:?}", text)),
+        }
+    }
+
+    match ast.value {
+        NodeValue::Heading(ref nh) => {
+            output.push_str(&format!(" level={}", nh.level));
+            if nh.setext {
+                output.push_str(" setext=true");
+            }
+        }
+        NodeValue::List(ref nl) => match nl.list_type {
+            ListType::Bullet => output.push_str(&format!(" type=bullet tight={}", nl.tight)),
+            ListType::Ordered => output.push_str(&format!(
+                " type=ordered start={} tight={}",
+                nl.start, nl.tight
+            )),
+        },
+        NodeValue::CodeBlock(ref ncb) => {
+            output.push_str(&format!(" fenced={}", ncb.fenced));
+            if !ncb.info.is_empty() {
+                output.push_str(&format!(" info={:?}", ncb.info));
+            }
+            literal(&ncb.literal, output);
+        }
+        NodeValue::Link(ref nl) | NodeValue::Image(ref nl) => {
+            output.push_str(&format!(" url={:?}", nl.url));
+            if !nl.title.is_empty() {
+                output.push_str(&format!(" title={:?}", nl.title));
+            }
+        }
+        NodeValue::WikiLink(ref nwl) => output.push_str(&format!(" url={:?}", nwl.url)),
+        NodeValue::FootnoteDefinition(NodeFootnoteDefinition { ref name, .. })
+        | NodeValue::FootnoteReference(NodeFootnoteReference { ref name, .. }) => {
+            output.push_str(&format!(" name={:?}", name))
+        }
+        NodeValue::TaskItem(symbol) => output.push_str(&format!(" checked={}", symbol.is_some())),
+        NodeValue::TableCell => {
+            if let Some(ntc) = ast.table_cell {
+                output.push_str(&format!(" colspan={}", ntc.colspan))
+            }
+        }
+        NodeValue::Text(ref text)
+        | NodeValue::Code(NodeCode {
+            literal: ref text, ..
+        })
+        | NodeValue::Math(NodeMath {
+            literal: ref text, ..
+        })
+        | NodeValue::HtmlBlock(NodeHtmlBlock {
+            literal: ref text, ..
+        })
+        | NodeValue::HtmlInline(ref text)
+        | NodeValue::Entity(NodeEntity {
+            literal: ref text, ..
+        })
+        | NodeValue::Raw(ref text)
+        | NodeValue::FrontMatter(ref text)
+        | NodeValue::TemplateTag(ref text)
+        | NodeValue::EscapedTag(ref text) => literal(text, output),
+        _ => (),
+    }
+}
+
 impl<'a> Node<'a, RefCell<Ast>> {
+    /// The text of this node and its descendants, without any markup: the
+    /// content of text, code and math inlines and of code blocks.  Line
+    /// breaks become spaces, and blocks are separated by newlines.
+    ///
+    /// ```
+    /// # use comrak::{parse_document, Arena, Options};
+    /// let arena = Arena::new();
+    /// let root = parse_document(&arena, "# The `main` *fn*\n\nRuns\nfirst.\n", &Options::default());
+    /// assert_eq!(root.first_child().unwrap().text_content(), "The main fn");
+    /// assert_eq!(root.text_content(), "The main fn\nRuns first.");
+    /// ```
+    pub fn text_content(&'a self) -> String {
+        let mut text = vec![];
+        self.collect_text_content(&mut text);
+        String::from_utf8(text).unwrap()
+    }
+
+    pub(crate) fn collect_text_content(&'a self, output: &mut Vec<u8>) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node.data.borrow().value {
+                NodeValue::Text(ref literal)
+                | NodeValue::Entity(NodeEntity {
+                    characters: ref literal,
+                    ..
+                })
+                | NodeValue::Code(NodeCode { ref literal, .. })
+                | NodeValue::Math(NodeMath { ref literal, .. })
+                | NodeValue::TemplateTag(ref literal)
+                | NodeValue::ExtensionInline(NodeExtension { ref literal, .. }) => {
+                    output.extend_from_slice(literal.as_bytes())
+                }
+                NodeValue::LineBreak | NodeValue::SoftBreak => output.push(b' '),
+                NodeValue::TemplateVariable(ref ntv) => {
+                    output.extend_from_slice(ntv.placeholder().as_bytes())
+                }
+                NodeValue::Citation(ref nc) => output.extend_from_slice(nc.source().as_bytes()),
+                ref value => {
+                    if value.block() && !output.is_empty() && output.last() != Some(&b'\n') {
+                        output.push(b'\n');
+                    }
+                    if let NodeValue::CodeBlock(ref ncb) = *value {
+                        output.extend_from_slice(ncb.literal.trim_end_matches('\n').as_bytes());
+                    }
+                    stack.extend(node.reverse_children());
+                }
+            }
+        }
+    }
+
+    /// A readable dump of this node and its descendants: one node per line,
+    /// indented by depth, with its kind as in the XML output, its main
+    /// properties, any literal text (shortened to 40 characters), and its
+    /// source position.
+    ///
+    /// ```
+    /// # use comrak::{parse_document, Arena, Options};
+    /// let arena = Arena::new();
+    /// let root = parse_document(&arena, "# Hi\n\n1. [*a*](/a \"A\")\n", &Options::default());
+    /// assert_eq!(
+    ///     root.ast_dump(),
+    ///     "document (1:1-3:16)\n\
+    ///     \x20 heading level=1 (1:1-1:4)\n\
+    ///     \x20   text \"Hi\" (1:3-1:4)\n\
+    ///     \x20 list type=ordered start=1 tight=true (3:1-3:16)\n\
+    ///     \x20   item (3:1-3:16)\n\
+    ///     \x20     paragraph (3:4-3:16)\n\
+    ///     \x20       link url=\"/a\" title=\"A\" (3:4-3:16)\n\
+    ///     \x20         emph (3:5-3:7)\n\
+    ///     \x20           text \"a\" (3:6-3:6)\n"
+    /// );
+    /// ```
+    pub fn ast_dump(&'a self) -> String {
+        let mut output = String::new();
+        let mut stack = vec![(self, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            let ast = node.data.borrow();
+            for _ in 0..depth {
+                output.push_str("  ");
+            }
+            output.push_str(ast.value.xml_node_name());
+            ast_dump_details(&ast, &mut output);
+            output.push_str(&format!(" ({})\n", ast.sourcepos));
+            stack.extend(node.reverse_children().map(|child| (child, depth + 1)));
+        }
+        output
+    }
+
+    /// The column, alignment and header status of this node, if it's a table
+    /// cell within a table.  A cell spanning several columns is given the
+    /// first of them.
+    ///
+    /// ```
+    /// # use comrak::{parse_document, Arena, Options};
+    /// # use comrak::nodes::{TableAlignment, TableCellInfo};
+    /// let arena = Arena::new();
+    /// let mut options = Options::default();
+    /// options.extension.table = true;
+    /// let root = parse_document(&arena, "| a | b |\n| - | -: |\n| c | d |\n", &options);
+    /// let cell = root.last_child().unwrap().last_child().unwrap().last_child().unwrap();
+    /// assert_eq!(
+    ///     cell.table_cell_info(),
+    ///     Some(TableCellInfo { column: 1, alignment: TableAlignment::Right, header: false })
+    /// );
+    /// assert_eq!(root.table_cell_info(), None);
+    /// ```
+    pub fn table_cell_info(&'a self) -> Option<TableCellInfo> {
+        if !matches!(self.data.borrow().value, NodeValue::TableCell) {
+            return None;
+        }
+        let row = self.parent()?;
+        let header = match row.data.borrow().value {
+            NodeValue::TableRow(header) => header,
+            _ => return None,
+        };
+        let column = self
+            .preceding_siblings()
+            .skip(1)
+            .map(table_cell_colspan)
+            .sum();
+        let table = row.parent()?;
+        let alignment = match table.data.borrow().value {
+            NodeValue::Table(ref nt) => nt.alignments.get(column).copied()?,
+            _ => return None,
+        };
+        Some(TableCellInfo {
+            column,
+            alignment,
+            header,
+        })
+    }
+
+    /// Counts the task list items at or below this node, and how many of them
+    /// are checked.  Requires [`ExtensionOptions::tasklist`](crate::ExtensionOptions::tasklist)
+    /// when parsing.
+    ///
+    /// ```
+    /// # use comrak::{parse_document, Arena, Options};
+    /// # use comrak::nodes::TaskProgress;
+    /// let arena = Arena::new();
+    /// let mut options = Options::default();
+    /// options.extension.tasklist = true;
+    /// let root = parse_document(&arena, "- [x] a\n- [ ] b\n  - [x] c\n", &options);
+    /// assert_eq!(root.task_progress(), TaskProgress { completed: 2, total: 3 });
+    /// ```
+    pub fn task_progress(&'a self) -> TaskProgress {
+        let mut progress = TaskProgress::default();
+        for node in self.descendants() {
+            if let NodeValue::TaskItem(symbol) = node.data.borrow().value {
+                progress.total += 1;
+                if symbol.is_some() {
+                    progress.completed += 1;
+                }
+            }
+        }
+        progress
+    }
+
+    /// Counts the task list items in each top-level section of this document.
+    ///
+    /// A section begins at each heading that is a child of this node and has
+    /// the outermost heading level used among them; content before the first
+    /// such heading forms a section with no heading, which is only reported if
+    /// it is non-empty.
+    ///
+    /// ```
+    /// # use comrak::{parse_document, Arena, Options};
+    /// # use comrak::nodes::TaskProgress;
+    /// let arena = Arena::new();
+    /// let mut options = Options::default();
+    /// options.extension.tasklist = true;
+    /// let root = parse_document(
+    ///     &arena,
+    ///     "# One\n- [x] a\n- [ ] b\n## Sub\n- [x] c\n# Two\n- [ ] d\n",
+    ///     &options,
+    /// );
+    /// let sections = root.task_progress_by_section();
+    /// assert_eq!(sections.len(), 2);
+    /// assert_eq!(sections[0].progress, TaskProgress { completed: 2, total: 3 });
+    /// assert_eq!(sections[1].progress, TaskProgress { completed: 0, total: 1 });
+    /// ```
+    pub fn task_progress_by_section(&'a self) -> Vec<SectionTaskProgress<'a>> {
+        let top_level = self
+            .children()
+            .filter_map(|child| match child.data.borrow().value {
+                NodeValue::Heading(ref nh) => Some(nh.level),
+                _ => None,
+            })
+            .min();
+
+        let mut sections = vec![];
+        let mut current = SectionTaskProgress {
+            heading: None,
+            progress: TaskProgress::default(),
+        };
+        let mut current_empty = true;
+
+        for child in self.children() {
+            let starts_section = match child.data.borrow().value {
+                NodeValue::Heading(ref nh) => Some(nh.level) == top_level,
+                _ => false,
+            };
+            if starts_section {
+                if current.heading.is_some() || !current_empty {
+                    sections.push(current);
+                }
+                current = SectionTaskProgress {
+                    heading: Some(child),
+                    progress: TaskProgress::default(),
+                };
+            }
+            current_empty = false;
+
+            let progress = child.task_progress();
+            current.progress.completed += progress.completed;
+            current.progress.total += progress.total;
+        }
+
+        if current.heading.is_some() || !current_empty {
+            sections.push(current);
+        }
+
+        sections
+    }
+
+    /// Replaces the value of this node, returning the old one.  The node's
+    /// position in the tree, children and sourcepos are not affected.
+    ///
+    /// ```
+    /// # use comrak::{format_html, parse_document, Arena, Options};
+    /// # use comrak::nodes::NodeValue;
+    /// let arena = Arena::new();
+    /// let options = Options::default();
+    /// let root = parse_document(&arena, "*Hi*\n", &options);
+    /// let emph = root.first_child().unwrap().first_child().unwrap();
+    /// let old = emph.replace_value(NodeValue::Strikethrough);
+    /// assert!(matches!(old, NodeValue::Emph));
+    ///
+    /// let mut html = vec![];
+    /// format_html(root, &options, &mut html).unwrap();
+    /// assert_eq!(String::from_utf8(html).unwrap(), "<p><del>Hi</del></p>\n");
+    /// ```
+    pub fn replace_value(&self, value: NodeValue) -> NodeValue {
+        std::mem::replace(&mut self.data.borrow_mut().value, value)
+    }
+
     /// The comrak representation of a markdown node in Rust isn't strict enough to rule out
     /// invalid trees according to the CommonMark specification. One simple example is that block
     /// containers, such as lists, should only contain blocks, but it's possible to put naked
@@ -721,7 +1529,7 @@ pub fn can_contain_type<'a>(node: &'a AstNode<'a>, child: &NodeValue) -> bool {
         NodeValue::Document => {
             return false;
         }
-        NodeValue::FrontMatter(_) => {
+        NodeValue::FrontMatter(_) | NodeValue::Truncated(_) => {
             return matches!(node.data.borrow().value, NodeValue::Document);
         }
         _ => {}
@@ -730,6 +1538,7 @@ pub fn can_contain_type<'a>(node: &'a AstNode<'a>, child: &NodeValue) -> bool {
     match node.data.borrow().value {
         NodeValue::Document
         | NodeValue::BlockQuote
+        | NodeValue::Alert(_)
         | NodeValue::FootnoteDefinition(_)
         | NodeValue::DescriptionTerm
         | NodeValue::DescriptionDetails
@@ -759,6 +1568,7 @@ pub fn can_contain_type<'a>(node: &'a AstNode<'a>, child: &NodeValue) -> bool {
         | NodeValue::WikiLink(..)
         | NodeValue::Strikethrough
         | NodeValue::Superscript
+        | NodeValue::Subscript
         | NodeValue::SpoileredText
         | NodeValue::Underline
         // XXX: this is quite a hack: the EscapedTag _contains_ whatever was
@@ -782,12 +1592,19 @@ pub fn can_contain_type<'a>(node: &'a AstNode<'a>, child: &NodeValue) -> bool {
                 | NodeValue::Image(..)
                 | NodeValue::Strikethrough
                 | NodeValue::HtmlInline(..)
+                | NodeValue::Entity(..)
                 | NodeValue::Math(..)
                 | NodeValue::WikiLink(..)
                 | NodeValue::FootnoteReference(..)
                 | NodeValue::Superscript
+                | NodeValue::Subscript
                 | NodeValue::SpoileredText
                 | NodeValue::Underline
+                | NodeValue::TemplateVariable(..)
+                | NodeValue::TemplateTag(..)
+                | NodeValue::Citation(..)
+                | NodeValue::ComponentInline(..)
+                | NodeValue::ExtensionInline(..)
         ),
 
         #[cfg(feature = "shortcodes")]
@@ -801,16 +1618,24 @@ pub fn can_contain_type<'a>(node: &'a AstNode<'a>, child: &NodeValue) -> bool {
             | NodeValue::Image(..)
             | NodeValue::Strikethrough
             | NodeValue::HtmlInline(..)
+            | NodeValue::Entity(..)
             | NodeValue::Math(..)
             | NodeValue::WikiLink(..)
             | NodeValue::FootnoteReference(..)
             | NodeValue::Superscript
+            | NodeValue::Subscript
             | NodeValue::SpoileredText
             | NodeValue::Underline
             | NodeValue::ShortCode(..)
+            | NodeValue::TemplateVariable(..)
+            | NodeValue::TemplateTag(..)
+            | NodeValue::Citation(..)
+            | NodeValue::ComponentInline(..)
+            | NodeValue::ExtensionInline(..)
         ),
 
-        NodeValue::MultilineBlockQuote(_) => {
+        NodeValue::MultilineBlockQuote(_)
+        | NodeValue::Container(_) => {
             child.block() && !matches!(*child, NodeValue::Item(..) | NodeValue::TaskItem(..))
         }
 
@@ -818,6 +1643,11 @@ pub fn can_contain_type<'a>(node: &'a AstNode<'a>, child: &NodeValue) -> bool {
     }
 }
 
+/// The number of columns `node` spans, if it's a table cell, or 1.
+pub(crate) fn table_cell_colspan<'a>(node: &'a AstNode<'a>) -> usize {
+    node.data.borrow().table_cell.map_or(1, |ntc| ntc.colspan)
+}
+
 pub(crate) fn ends_with_blank_line<'a>(node: &'a AstNode<'a>) -> bool {
     let mut it = Some(node);
     while let Some(cur) = it {
@@ -834,6 +1664,41 @@ pub(crate) fn ends_with_blank_line<'a>(node: &'a AstNode<'a>) -> bool {
     false
 }
 
+// Whether the soft break `node` lies between two CJK characters, so that it
+// shouldn't be rendered as a space.
+pub(crate) fn soft_break_joins_cjk<'a>(node: &'a AstNode<'a>) -> bool {
+    fn edge_char<'a>(node: &'a AstNode<'a>, last: bool) -> Option<char> {
+        match node.data.borrow().value {
+            NodeValue::Text(ref literal)
+            | NodeValue::Entity(NodeEntity {
+                characters: ref literal,
+                ..
+            })
+            | NodeValue::Code(NodeCode { ref literal, .. }) => {
+                return if last {
+                    literal.chars().next_back()
+                } else {
+                    literal.chars().next()
+                };
+            }
+            NodeValue::SoftBreak | NodeValue::LineBreak => return None,
+            _ => (),
+        }
+        edge_char(
+            if last {
+                node.last_child()?
+            } else {
+                node.first_child()?
+            },
+            last,
+        )
+    }
+
+    let before = node.previous_sibling().and_then(|n| edge_char(n, true));
+    let after = node.next_sibling().and_then(|n| edge_char(n, false));
+    matches!((before, after), (Some(b), Some(a)) if is_cjk(b) && is_cjk(a))
+}
+
 pub(crate) fn containing_block<'a>(node: &'a AstNode<'a>) -> Option<&'a AstNode<'a>> {
     let mut ch = Some(node);
     while let Some(n) = ch {
@@ -0,0 +1,101 @@
+//! Owned copies of ASTs, independent of the arena they were parsed into.
+//!
+//! Nodes allocated in an [`Arena`] borrow it and are wrapped in
+//! [`RefCell`]s, so a parsed tree can't outlive its arena or be shared
+//! between threads.  An [`OwnedNode`] holds a copy of a tree which is
+//! [`Send`] and [`Sync`]: it can be cached, or parsed on one thread and
+//! rendered on another, by rebuilding it in a new arena.
+//!
+//! ```
+//! # use comrak::{format_html, parse_document, Arena, Options};
+//! # use comrak::owned::OwnedNode;
+//! # use std::thread;
+//! let options = Options::default();
+//! let tree = {
+//!     let arena = Arena::new();
+//!     OwnedNode::from_node(parse_document(&arena, "Hi *there*\n", &options))
+//! };
+//!
+//! let html = thread::spawn(move || {
+//!     let arena = Arena::new();
+//!     let mut html = vec![];
+//!     format_html(tree.to_node(&arena), &Options::default(), &mut html).unwrap();
+//!     String::from_utf8(html).unwrap()
+//! })
+//! .join()
+//! .unwrap();
+//! assert_eq!(html, "<p>Hi <em>there</em></p>\n");
+//! ```
+
+use std::cell::RefCell;
+
+use crate::arena_tree::Node;
+use crate::nodes::{Ast, AstNode};
+use crate::Arena;
+
+/// An owned copy of a node and its descendants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedNode {
+    /// The node's value and position.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub ast: Ast,
+
+    /// The node's children.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub children: Vec<OwnedNode>,
+}
+
+impl OwnedNode {
+    /// Copies `node` and its descendants.
+    pub fn from_node<'a>(node: &'a AstNode<'a>) -> Self {
+        OwnedNode {
+            ast: node.data.borrow().clone(),
+            children: node.children().map(OwnedNode::from_node).collect(),
+        }
+    }
+
+    /// Allocates a copy of the tree in `arena`, returning its root.  The
+    /// owned tree is left as it was, to be used again.
+    pub fn to_node<'a>(&self, arena: &'a Arena<AstNode<'a>>) -> &'a AstNode<'a> {
+        let node = arena.alloc(Node::new(RefCell::new(self.ast.clone())));
+        for child in &self.children {
+            node.append(child.to_node(arena));
+        }
+        node
+    }
+
+    /// Allocates the nodes of the tree in `arena`, returning its root.
+    pub fn into_node<'a>(self, arena: &'a Arena<AstNode<'a>>) -> &'a AstNode<'a> {
+        let node = arena.alloc(Node::new(RefCell::new(self.ast)));
+        for child in self.children {
+            node.append(child.into_node(arena));
+        }
+        node
+    }
+
+    /// Iterates over the node and its descendants, in document order.
+    pub fn descendants(&self) -> Descendants<'_> {
+        Descendants { stack: vec![self] }
+    }
+}
+
+/// An iterator over an [`OwnedNode`] and its descendants.  See
+/// [`OwnedNode::descendants`].
+#[derive(Debug, Clone)]
+pub struct Descendants<'a> {
+    stack: Vec<&'a OwnedNode>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = &'a OwnedNode;
+
+    fn next(&mut self) -> Option<&'a OwnedNode> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.iter().rev());
+        Some(node)
+    }
+}
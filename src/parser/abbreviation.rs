@@ -0,0 +1,38 @@
+use crate::strings::is_line_end_char;
+
+/// The details of an abbreviation definition.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeAbbreviationDefinition {
+    /// The abbreviated text, as it appears in the document.
+    pub label: String,
+
+    /// The expansion of the abbreviation.
+    pub title: String,
+}
+
+/// Scans an abbreviation definition of the form `*[label]: title` at the start
+/// of `line`, returning the label and title.
+pub(crate) fn definition(line: &[u8]) -> Option<NodeAbbreviationDefinition> {
+    if !line.starts_with(b"*[") {
+        return None;
+    }
+
+    let label_end = 2 + line[2..]
+        .iter()
+        .position(|&c| c == b']' || is_line_end_char(c))?;
+    if label_end == 2 || line[label_end] != b']' || line.get(label_end + 1) != Some(&b':') {
+        return None;
+    }
+
+    let label = std::str::from_utf8(&line[2..label_end]).ok()?.trim();
+    let title = std::str::from_utf8(&line[label_end + 2..]).ok()?.trim();
+    if label.is_empty() {
+        return None;
+    }
+
+    Some(NodeAbbreviationDefinition {
+        label: label.to_string(),
+        title: title.to_string(),
+    })
+}
@@ -0,0 +1,111 @@
+/// The metadata of an alert.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeAlert {
+    /// The type of alert.
+    pub alert_type: AlertType,
+
+    /// The title following the marker, if any, used instead of the alert
+    /// type's default title.
+    pub title: Option<String>,
+}
+
+/// The type of an alert, as named in its marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlertType {
+    /// `[!NOTE]`
+    #[default]
+    Note,
+
+    /// `[!TIP]`
+    Tip,
+
+    /// `[!IMPORTANT]`
+    Important,
+
+    /// `[!WARNING]`
+    Warning,
+
+    /// `[!CAUTION]`
+    Caution,
+}
+
+impl NodeAlert {
+    /// The title of the alert, or the default title of its type if it has
+    /// none.
+    pub fn title(&self) -> &str {
+        match self.title {
+            Some(ref title) => title,
+            None => self.alert_type.default_title(),
+        }
+    }
+}
+
+impl AlertType {
+    /// The name of the alert type, as written in its marker.
+    pub fn marker(&self) -> &'static str {
+        match *self {
+            AlertType::Note => "NOTE",
+            AlertType::Tip => "TIP",
+            AlertType::Important => "IMPORTANT",
+            AlertType::Warning => "WARNING",
+            AlertType::Caution => "CAUTION",
+        }
+    }
+
+    /// The title of an alert without one of its own.
+    pub fn default_title(&self) -> &'static str {
+        match *self {
+            AlertType::Note => "Note",
+            AlertType::Tip => "Tip",
+            AlertType::Important => "Important",
+            AlertType::Warning => "Warning",
+            AlertType::Caution => "Caution",
+        }
+    }
+
+    /// The suffix of the CSS class given to the alert in HTML, after
+    /// `markdown-alert-`.
+    pub fn css_class(&self) -> &'static str {
+        match *self {
+            AlertType::Note => "note",
+            AlertType::Tip => "tip",
+            AlertType::Important => "important",
+            AlertType::Warning => "warning",
+            AlertType::Caution => "caution",
+        }
+    }
+}
+
+/// Scans an alert marker, `[!NOTE] Title`, which makes up the rest of `line`.
+/// The type is matched case-insensitively.
+pub(crate) fn open_marker(line: &[u8]) -> Option<NodeAlert> {
+    let line = std::str::from_utf8(line).ok()?;
+    let rest = line.strip_prefix("[!")?;
+    let end = rest.find(']')?;
+
+    let alert_type = match rest[..end].to_ascii_uppercase().as_str() {
+        "NOTE" => AlertType::Note,
+        "TIP" => AlertType::Tip,
+        "IMPORTANT" => AlertType::Important,
+        "WARNING" => AlertType::Warning,
+        "CAUTION" => AlertType::Caution,
+        _ => return None,
+    };
+
+    let rest = &rest[end + 1..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t', '\r', '\n']) {
+        return None;
+    }
+    let title = rest.trim_matches([' ', '\t', '\r', '\n']);
+
+    Some(NodeAlert {
+        alert_type,
+        title: if title.is_empty() {
+            None
+        } else {
+            Some(title.to_string())
+        },
+    })
+}
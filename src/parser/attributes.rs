@@ -0,0 +1,121 @@
+use std::fmt;
+
+use crate::strings::is_space_or_tab;
+
+/// The attributes given to a heading or fenced code block by an attribute
+/// block, `{#id .class key=value}`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeAttributes {
+    /// The identifier, from `#id`.  The last one given is used.
+    pub id: Option<String>,
+
+    /// The classes, from `.class`, in source order.
+    pub classes: Vec<String>,
+
+    /// Any other attributes in source order, from `key=value` or
+    /// `key="value"`.
+    pub attributes: Vec<(String, String)>,
+}
+
+impl NodeAttributes {
+    /// Whether no attributes are given.
+    pub fn is_empty(&self) -> bool {
+        self.id.is_none() && self.classes.is_empty() && self.attributes.is_empty()
+    }
+}
+
+/// Writes the attribute block, e.g. `{#id .class key="some value"}`.
+impl fmt::Display for NodeAttributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut items = vec![];
+        if let Some(ref id) = self.id {
+            items.push(format!("#{}", id));
+        }
+        for class in &self.classes {
+            items.push(format!(".{}", class));
+        }
+        for (key, value) in &self.attributes {
+            if value.is_empty() || value.contains(is_space) {
+                items.push(format!("{}=\"{}\"", key, value));
+            } else {
+                items.push(format!("{}={}", key, value));
+            }
+        }
+        write!(f, "{{{}}}", items.join(" "))
+    }
+}
+
+/// Splits an attribute block off the end of `s`, returning the length of
+/// `s` before it and its attributes.  The block must be at the start of `s`
+/// or follow a space or tab, and may only be followed by whitespace.
+pub(crate) fn split_trailing(s: &str) -> Option<(usize, NodeAttributes)> {
+    let trimmed = s.trim_end_matches(|c: char| c.is_ascii_whitespace());
+    let inner = trimmed.strip_suffix('}')?;
+    let open = inner.rfind('{')?;
+    if open > 0 && !is_space_or_tab(s.as_bytes()[open - 1]) {
+        return None;
+    }
+    let attributes = parse(&inner[open + 1..])?;
+    let before = s[..open].trim_end_matches(is_space);
+    Some((before.len(), attributes))
+}
+
+/// Parses the inside of an attribute block.  An empty block, or one with
+/// anything other than `#id`, `.class` and `key=value` items, is not an
+/// attribute block.
+fn parse(s: &str) -> Option<NodeAttributes> {
+    let mut attributes = NodeAttributes::default();
+    let mut s = s.trim_matches(is_space);
+    while !s.is_empty() {
+        let end = s.find(|c: char| is_space(c) || c == '=').unwrap_or(s.len());
+        let (item, rest) = s.split_at(end);
+        if let Some(id) = item.strip_prefix('#') {
+            attributes.id = Some(name(id)?.to_string());
+            s = rest;
+        } else if let Some(class) = item.strip_prefix('.') {
+            attributes.classes.push(name(class)?.to_string());
+            s = rest;
+        } else {
+            let key = name(item)?;
+            let rest = rest.strip_prefix('=')?;
+            let (value, rest) = match rest.strip_prefix('"') {
+                Some(quoted) => {
+                    let end = quoted.find('"')?;
+                    (&quoted[..end], &quoted[end + 1..])
+                }
+                None => {
+                    let end = rest.find(is_space).unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+            attributes
+                .attributes
+                .push((key.to_string(), value.to_string()));
+            s = rest;
+        }
+        if !s.is_empty() && !s.starts_with(is_space) {
+            return None;
+        }
+        s = s.trim_start_matches(is_space);
+    }
+    if attributes.is_empty() {
+        None
+    } else {
+        Some(attributes)
+    }
+}
+
+fn is_space(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+// An identifier, class or key: a non-empty run of characters with no
+// quotes, braces or `=`.
+fn name(s: &str) -> Option<&str> {
+    if s.is_empty() || s.contains(['"', '\'', '{', '}', '=', '#', '<', '>']) {
+        None
+    } else {
+        Some(s)
+    }
+}
@@ -111,10 +111,7 @@ pub fn www_match<'a>(
 
     let inl = make_inline(
         arena,
-        NodeValue::Link(NodeLink {
-            url,
-            title: String::new(),
-        }),
+        NodeValue::Link(NodeLink::new(url, "")),
         (0, 1, 0, 1).into(),
     );
 
@@ -290,10 +287,7 @@ pub fn url_match<'a>(
         .to_string();
     let inl = make_inline(
         arena,
-        NodeValue::Link(NodeLink {
-            url: url.clone(),
-            title: String::new(),
-        }),
+        NodeValue::Link(NodeLink::new(url.clone(), "")),
         (0, 1, 0, 1).into(),
     );
 
@@ -398,10 +392,7 @@ fn email_match<'a>(
 
     let inl = make_inline(
         arena,
-        NodeValue::Link(NodeLink {
-            url,
-            title: String::new(),
-        }),
+        NodeValue::Link(NodeLink::new(url, "")),
         (0, 1, 0, 1).into(),
     );
 
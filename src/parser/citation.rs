@@ -0,0 +1,100 @@
+/// A citation, `[@key]` or `[see @key, p. 4; @other]`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeCitation {
+    /// The works cited, in order.
+    pub items: Vec<CitationItem>,
+}
+
+/// A single work cited by a [`NodeCitation`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CitationItem {
+    /// The text preceding the key, e.g. `see`.
+    pub prefix: String,
+
+    /// The citation key, without the `@`.
+    pub key: String,
+
+    /// The text following the key, without a leading comma, e.g. `p. 4`.
+    pub locator: String,
+}
+
+impl NodeCitation {
+    /// The citation as it would be written in Markdown.
+    pub fn source(&self) -> String {
+        let mut s = "[".to_string();
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                s.push_str("; ");
+            }
+            if !item.prefix.is_empty() {
+                s.push_str(&item.prefix);
+                s.push(' ');
+            }
+            s.push('@');
+            s.push_str(&item.key);
+            if !item.locator.is_empty() {
+                s.push_str(", ");
+                s.push_str(&item.locator);
+            }
+        }
+        s.push(']');
+        s
+    }
+}
+
+/// Scans a citation at the start of `input`, returning it and its length.
+/// Keys consist of ASCII alphanumerics and `_`, `-`, `:`, `.`, `/` and `#`,
+/// ending with an alphanumeric.  A bracketed span directly followed by `(` or
+/// `[` is a link, not a citation.
+pub(crate) fn citation(input: &[u8]) -> Option<(NodeCitation, usize)> {
+    if !input.starts_with(b"[") {
+        return None;
+    }
+
+    // Stopping at a nested bracket, which citations can't contain, keeps a
+    // run of unclosed brackets from being scanned quadratically.
+    let close = 1 + input[1..].iter().position(|&c| c == b']' || c == b'[')?;
+    if input[close] == b'[' || matches!(input.get(close + 1), Some(b'(') | Some(b'[')) {
+        return None;
+    }
+    let inner = std::str::from_utf8(&input[1..close]).ok()?;
+
+    let items = inner
+        .split(';')
+        .map(item)
+        .collect::<Option<Vec<CitationItem>>>()?;
+    Some((NodeCitation { items }, close + 1))
+}
+
+fn item(s: &str) -> Option<CitationItem> {
+    let s = s.trim();
+    let at = s
+        .char_indices()
+        .find(|&(i, c)| c == '@' && s[..i].chars().last().map_or(true, char::is_whitespace))?
+        .0;
+
+    let rest = &s[at + 1..];
+    let mut key_len = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "_-:./#".contains(c)))
+        .unwrap_or(rest.len());
+    key_len = rest[..key_len]
+        .trim_end_matches(|c: char| !c.is_ascii_alphanumeric())
+        .len();
+    if key_len == 0 {
+        return None;
+    }
+
+    let locator = rest[key_len..].trim_start();
+    let locator = locator.strip_prefix(',').unwrap_or(locator);
+    Some(CitationItem {
+        prefix: collapse_whitespace(&s[..at]),
+        key: rest[..key_len].to_string(),
+        locator: collapse_whitespace(locator),
+    })
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,93 @@
+/// A JSX-style component, `<Widget prop="x">...</Widget>` or `<Widget />`,
+/// kept verbatim.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeComponent {
+    /// The name of the outermost component, e.g. `Widget` or `Tabs.Item`.
+    pub name: String,
+
+    /// The source of the component, including its tags and any content.
+    pub literal: String,
+}
+
+/// Scans the name of a component tag at the start of `input`, which must be
+/// `<` followed by an ASCII uppercase letter.
+pub(crate) fn name(input: &[u8]) -> Option<String> {
+    if input.first() != Some(&b'<') || !input.get(1).map_or(false, u8::is_ascii_uppercase) {
+        return None;
+    }
+    let len = input[1..]
+        .iter()
+        .take_while(|&&c| c.is_ascii_alphanumeric() || matches!(c, b'_' | b'.' | b'-'))
+        .count();
+    Some(String::from_utf8(input[1..1 + len].to_vec()).unwrap())
+}
+
+/// Finds the end of a component, which may span several lines.  Only tags
+/// whose names start with an uppercase letter are tracked; anything else
+/// within the component is opaque.  Quoted attribute values and `{...}`
+/// expressions may contain `>`.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ComponentScanner {
+    depth: usize,
+    // Whether within a tag, and whether that tag is a closing one.
+    tag: Option<bool>,
+    quote: Option<u8>,
+    braces: usize,
+}
+
+impl ComponentScanner {
+    /// Scans `input`, which continues from any input previously scanned.
+    /// Returns the length of `input` up to the end of the outermost component,
+    /// if it ends within it.
+    pub(crate) fn scan(&mut self, input: &[u8]) -> Option<usize> {
+        let mut i = 0;
+        while i < input.len() {
+            let c = input[i];
+            match self.tag {
+                None => {
+                    if c == b'<' && input.get(i + 1).map_or(false, u8::is_ascii_uppercase) {
+                        self.tag = Some(false);
+                    } else if c == b'<'
+                        && input.get(i + 1) == Some(&b'/')
+                        && input.get(i + 2).map_or(false, u8::is_ascii_uppercase)
+                    {
+                        self.tag = Some(true);
+                        i += 1;
+                    }
+                }
+                Some(_) if self.quote.is_some() => {
+                    if self.quote == Some(c) {
+                        self.quote = None;
+                    }
+                }
+                Some(closing) => match c {
+                    b'"' | b'\'' | b'`' => self.quote = Some(c),
+                    b'{' => self.braces += 1,
+                    b'}' => self.braces = self.braces.saturating_sub(1),
+                    b'/' if self.braces == 0 && input.get(i + 1) == Some(&b'>') => {
+                        i += 1;
+                        self.tag = None;
+                        if self.depth == 0 {
+                            return Some(i + 1);
+                        }
+                    }
+                    b'>' if self.braces == 0 => {
+                        self.tag = None;
+                        if !closing {
+                            self.depth += 1;
+                        } else {
+                            self.depth = self.depth.saturating_sub(1);
+                            if self.depth == 0 {
+                                return Some(i + 1);
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+            }
+            i += 1;
+        }
+        None
+    }
+}
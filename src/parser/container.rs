@@ -0,0 +1,217 @@
+use crate::parser::attributes::{self, NodeAttributes};
+use crate::parser::ExtensionOptions;
+use crate::strings::{is_line_end_char, is_space_or_tab};
+
+/// The metadata of a container block, fenced by lines of three or more
+/// colons.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeContainer {
+    /// The length of the fence.
+    pub fence_length: usize,
+
+    /// The indentation level of the fence marker.
+    pub fence_offset: usize,
+
+    /// What the block is, as given by its name.
+    pub kind: ContainerKind,
+
+    /// The name following the opening fence, e.g. `warning` for
+    /// `::: warning`, or empty if there is none.
+    pub name: String,
+
+    /// The rest of the opening fence of a conditional or details block: its
+    /// condition, or its summary.  Empty for a named container.
+    pub info: String,
+
+    /// The attributes given by an attribute block on the opening fence of a
+    /// named container, e.g. `::: warning {#careful .big}`, or the condition
+    /// of a conditional block, e.g. `("feature", "enterprise")` for
+    /// `::: if feature="enterprise"`.  A bare word in a condition has an
+    /// empty value.
+    pub attributes: NodeAttributes,
+}
+
+/// The kind of a container block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContainerKind {
+    /// A named container, or fenced div, `::: name {attributes}`.  Enabled
+    /// with [`ExtensionOptions::container_blocks`].
+    #[default]
+    Named,
+
+    /// A block kept or dropped depending on a condition, `::: if condition`.
+    /// Enabled with [`ExtensionOptions::conditional_blocks`].
+    Conditional,
+
+    /// A collapsible section, `::: details Summary`.  Enabled with
+    /// [`ExtensionOptions::details_blocks`].
+    Details,
+}
+
+impl NodeContainer {
+    /// The value of the attribute `key`, if present.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The condition of a conditional block, with every value quoted.
+    pub fn condition(&self) -> String {
+        let mut s = String::new();
+        for (key, value) in &self.attributes.attributes {
+            if !s.is_empty() {
+                s.push(' ');
+            }
+            s.push_str(key);
+            if !value.is_empty() {
+                s.push_str("=\"");
+                s.push_str(value);
+                s.push('"');
+            }
+        }
+        s
+    }
+
+    /// Whether `flags` satisfy every attribute of the condition: a
+    /// `key="value"` attribute needs the pair `(key, value)`, and a bare
+    /// `key` any pair with that key.
+    pub fn matches(&self, flags: &[(&str, &str)]) -> bool {
+        self.attributes.attributes.iter().all(|(key, value)| {
+            flags
+                .iter()
+                .any(|&(k, v)| k == key && (value.is_empty() || v == value))
+        })
+    }
+
+    /// The summary of a details block, if it has one.
+    pub fn summary(&self) -> Option<&str> {
+        match self.kind {
+            ContainerKind::Details if !self.info.is_empty() => Some(&self.info),
+            _ => None,
+        }
+    }
+}
+
+/// Scans an opening fence, `::: name rest`, at the start of `line`, for
+/// whichever kinds of container block are enabled.  The fence offset of the
+/// result is left at zero.
+pub(crate) fn open_fence(line: &[u8], extension: &ExtensionOptions) -> Option<NodeContainer> {
+    let fence_length = line.iter().take_while(|&&c| c == b':').count();
+    if fence_length < 3 {
+        return None;
+    }
+
+    let rest = std::str::from_utf8(&line[fence_length..]).ok()?;
+    let rest = rest.trim_matches([' ', '\t', '\r', '\n']);
+    let name_end = rest.find([' ', '\t', '{']).unwrap_or(rest.len());
+    let (name, rest) = rest.split_at(name_end);
+    if name.contains(':') {
+        return None;
+    }
+    let rest = rest.trim_start_matches([' ', '\t']);
+
+    let mut container = NodeContainer {
+        fence_length,
+        fence_offset: 0,
+        kind: ContainerKind::Named,
+        name: name.to_string(),
+        info: String::new(),
+        attributes: NodeAttributes::default(),
+    };
+    match name {
+        "if" if extension.conditional_blocks => {
+            if rest.starts_with('{') {
+                return None;
+            }
+            container.kind = ContainerKind::Conditional;
+            container.info = rest.to_string();
+            container.attributes.attributes = condition(rest)?;
+        }
+        "details" if extension.details_blocks => {
+            if rest.starts_with('{') {
+                return None;
+            }
+            container.kind = ContainerKind::Details;
+            container.info = rest.to_string();
+        }
+        _ if extension.container_blocks => {
+            if rest.is_empty() {
+                if name.is_empty() {
+                    return None;
+                }
+            } else {
+                match attributes::split_trailing(rest) {
+                    Some((0, attributes)) => container.attributes = attributes,
+                    _ => return None,
+                }
+            }
+        }
+        _ => return None,
+    }
+    Some(container)
+}
+
+/// Scans a closing fence at the start of `line`, returning its length.
+pub(crate) fn close_fence(line: &[u8]) -> Option<usize> {
+    let fence_length = line.iter().take_while(|&&c| c == b':').count();
+    if fence_length < 3
+        || !line[fence_length..]
+            .iter()
+            .take_while(|&&c| !is_line_end_char(c))
+            .all(|&c| is_space_or_tab(c))
+    {
+        return None;
+    }
+    Some(fence_length)
+}
+
+// Parses a condition: `key="value"` attributes, with the quotes optional, or
+// bare keys.  There must be at least one.
+fn condition(mut s: &str) -> Option<Vec<(String, String)>> {
+    let mut attributes = vec![];
+    while !s.is_empty() {
+        let key_end = s
+            .find(|c: char| c == '=' || c.is_ascii_whitespace())
+            .unwrap_or(s.len());
+        let key = &s[..key_end];
+        if key.is_empty() {
+            return None;
+        }
+        s = &s[key_end..];
+
+        let value = match s.strip_prefix('=') {
+            Some(rest) => {
+                let (value, rest) = match rest.strip_prefix('"') {
+                    Some(quoted) => {
+                        let end = quoted.find('"')?;
+                        (&quoted[..end], &quoted[end + 1..])
+                    }
+                    None => {
+                        let end = rest
+                            .find(|c: char| c.is_ascii_whitespace())
+                            .unwrap_or(rest.len());
+                        (&rest[..end], &rest[end..])
+                    }
+                };
+                s = rest;
+                value
+            }
+            None => "",
+        };
+        if !s.is_empty() && !s.starts_with(|c: char| c.is_ascii_whitespace()) {
+            return None;
+        }
+
+        attributes.push((key.to_string(), value.to_string()));
+        s = s.trim_start();
+    }
+    if attributes.is_empty() {
+        return None;
+    }
+    Some(attributes)
+}
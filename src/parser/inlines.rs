@@ -2,19 +2,22 @@ use crate::arena_tree::Node;
 use crate::ctype::{isdigit, ispunct, isspace};
 use crate::entity;
 use crate::nodes::{
-    Ast, AstNode, NodeCode, NodeFootnoteReference, NodeLink, NodeMath, NodeValue, NodeWikiLink,
-    Sourcepos,
+    Ast, AstNode, NodeCode, NodeEmphasis, NodeEntity, NodeFootnoteDefinition,
+    NodeFootnoteReference, NodeLink, NodeMath, NodeValue, NodeWikiLink, Sourcepos,
 };
 use crate::parser::autolink;
+use crate::parser::citation;
+use crate::parser::component::{self, ComponentScanner, NodeComponent};
 #[cfg(feature = "shortcodes")]
 use crate::parser::shortcodes::NodeShortCode;
+use crate::parser::template;
 use crate::parser::{
     unwrap_into_2, unwrap_into_copy, AutolinkType, BrokenLinkReference, Options, ResolvedReference,
 };
 use crate::scanners;
 use crate::strings::{self, is_blank, Case};
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::ptr;
 use std::str;
@@ -44,7 +47,7 @@ pub struct Subject<'a: 'd, 'r, 'o, 'c, 'd, 'i> {
     no_link_openers: bool,
     special_chars: [bool; 256],
     skip_chars: [bool; 256],
-    smart_chars: [bool; 256],
+    extension_chars: [bool; 256],
 }
 
 #[derive(Default)]
@@ -57,6 +60,8 @@ struct Flags {
 
 pub struct RefMap {
     pub map: HashMap<String, ResolvedReference>,
+    // The normalized labels which links have been resolved with.
+    pub(crate) used: HashSet<String>,
     pub(crate) max_ref_size: usize,
     ref_size: usize,
 }
@@ -65,6 +70,7 @@ impl RefMap {
     pub fn new() -> Self {
         Self {
             map: HashMap::new(),
+            used: HashSet::new(),
             max_ref_size: usize::MAX,
             ref_size: 0,
         }
@@ -78,6 +84,7 @@ impl RefMap {
                     None
                 } else {
                     self.ref_size += size;
+                    self.used.insert(lab.to_string());
                     Some(entry.clone())
                 }
             }
@@ -101,6 +108,7 @@ struct Bracket<'a> {
     inl_text: &'a AstNode<'a>,
     position: usize,
     image: bool,
+    inline_footnote: bool,
     bracket_after: bool,
 }
 
@@ -138,7 +146,7 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
             no_link_openers: true,
             special_chars: [false; 256],
             skip_chars: [false; 256],
-            smart_chars: [false; 256],
+            extension_chars: [false; 256],
         };
         for &c in &[
             b'\n', b'\r', b'_', b'*', b'"', b'`', b'\\', b'&', b'<', b'[', b']', b'!', b'$',
@@ -149,7 +157,7 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
             s.special_chars[b':' as usize] = true;
             s.special_chars[b'w' as usize] = true;
         }
-        if options.extension.strikethrough {
+        if options.extension.strikethrough || options.extension.subscript {
             s.special_chars[b'~' as usize] = true;
             s.skip_chars[b'~' as usize] = true;
         }
@@ -166,8 +174,25 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
         if options.extension.spoiler {
             s.special_chars[b'|' as usize] = true;
         }
-        for &c in &[b'"', b'\'', b'.', b'-'] {
-            s.smart_chars[c as usize] = true;
+        if options.extension.template_variables || options.extension.template_tags {
+            s.special_chars[b'{' as usize] = true;
+        }
+        if options.extension.mentions.is_some() {
+            s.special_chars[b'@' as usize] = true;
+        }
+        if options.extension.footnotes && options.extension.inline_footnotes {
+            s.special_chars[b'^' as usize] = true;
+        }
+        for extension in options.extensions.iter() {
+            for &c in extension.inline_chars().iter().filter(|c| c.is_ascii()) {
+                s.special_chars[c as usize] = true;
+                s.extension_chars[c as usize] = true;
+            }
+        }
+        if options.parse.smart {
+            for &c in &[b'"', b'\'', b'.', b'-'] {
+                s.special_chars[c as usize] = true;
+            }
         }
         s
     }
@@ -186,12 +211,23 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
         let adjusted_line = self.line - node_ast.sourcepos.start.line;
         self.line_offset = node_ast.line_offsets[adjusted_line];
 
+        if self.extension_chars[c as usize] {
+            if let Some(inl) = self.handle_extension() {
+                node.append(inl);
+                return true;
+            }
+        }
+
         let new_inl: Option<&'a AstNode<'a>> = match c {
             '\0' => return false,
             '\r' | '\n' => Some(self.handle_newline()),
             '`' => Some(self.handle_backticks()),
             '\\' => Some(self.handle_backslash()),
             '&' => Some(self.handle_entity()),
+            '<' if self.options.extension.jsx_components => match self.handle_component() {
+                Some(inl) => Some(inl),
+                None => Some(self.handle_pointy_brace()),
+            },
             '<' => Some(self.handle_pointy_brace()),
             ':' => {
                 let mut res = None;
@@ -227,9 +263,25 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
                     ))
                 }
             },
+            '@' if self.options.extension.mentions.is_some() => match self.handle_mention() {
+                Some(inl) => Some(inl),
+                None => {
+                    self.pos += 1;
+                    Some(self.make_inline(
+                        NodeValue::Text("@".to_string()),
+                        self.pos - 1,
+                        self.pos - 1,
+                    ))
+                }
+            },
             '*' | '_' | '\'' | '"' => Some(self.handle_delim(c as u8)),
             '-' => Some(self.handle_hyphen()),
             '.' => Some(self.handle_period()),
+            '[' if self.options.extension.citations
+                && citation::citation(&self.input[self.pos..]).is_some() =>
+            {
+                self.handle_citation()
+            }
             '[' => {
                 self.pos += 1;
 
@@ -281,14 +333,43 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
                     ))
                 }
             }
-            '~' if self.options.extension.strikethrough => Some(self.handle_delim(b'~')),
+            '~' if self.options.extension.strikethrough || self.options.extension.subscript => {
+                Some(self.handle_delim(b'~'))
+            }
+            '^' if self.options.extension.footnotes
+                && self.options.extension.inline_footnotes
+                && !self.within_brackets
+                && self.peek_char_n(1) == Some(&(b'[')) =>
+            {
+                self.pos += 2;
+                let inl = self.make_inline(
+                    NodeValue::Text("^[".to_string()),
+                    self.pos - 2,
+                    self.pos - 1,
+                );
+                // Pushed like an image's, so as not to allow links inside
+                // a link which has already been closed.
+                self.push_bracket(true, inl);
+                self.brackets.last_mut().unwrap().inline_footnote = true;
+                self.within_brackets = true;
+                Some(inl)
+            }
             '^' if self.options.extension.superscript && !self.within_brackets => {
                 Some(self.handle_delim(b'^'))
             }
             '$' => Some(self.handle_dollars()),
             '|' if self.options.extension.spoiler => Some(self.handle_delim(b'|')),
+            '{' if self.options.extension.template_variables
+                || self.options.extension.template_tags =>
+            {
+                Some(self.handle_template())
+            }
             _ => {
-                let endpos = self.find_special_char();
+                let mut endpos = self.find_special_char();
+                if endpos == self.pos {
+                    // An extension's character which it didn't match.
+                    endpos += 1;
+                }
                 let mut contents = self.input[self.pos..endpos].to_vec();
                 let startpos = self.pos;
                 self.pos = endpos;
@@ -461,7 +542,8 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
                 // both get passed.
                 if c.delim_char == b'*'
                     || c.delim_char == b'_'
-                    || (self.options.extension.strikethrough && c.delim_char == b'~')
+                    || ((self.options.extension.strikethrough || self.options.extension.subscript)
+                        && c.delim_char == b'~')
                     || (self.options.extension.superscript && c.delim_char == b'^')
                     || (self.options.extension.spoiler && c.delim_char == b'|')
                 {
@@ -487,8 +569,13 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
                         closer = c.next.get();
                     }
                 } else if c.delim_char == b'\'' || c.delim_char == b'"' {
-                    *c.inl.data.borrow_mut().value.text_mut().unwrap() =
-                        if c.delim_char == b'\'' { "’" } else { "”" }.to_string();
+                    let quotes = &self.options.parse.smart_quotes;
+                    *c.inl.data.borrow_mut().value.text_mut().unwrap() = match c.delim_char {
+                        b'"' => &quotes.double_close,
+                        _ if opener_found => &quotes.single_close,
+                        _ => &quotes.apostrophe,
+                    }
+                    .clone();
                     closer = c.next.get();
 
                     if opener_found {
@@ -500,11 +587,11 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
                             .value
                             .text_mut()
                             .unwrap() = if old_c.delim_char == b'\'' {
-                            "‘"
+                            &quotes.single_open
                         } else {
-                            "“"
+                            &quotes.double_open
                         }
-                        .to_string();
+                        .clone();
                         self.remove_delimiter(opener.unwrap());
                         self.remove_delimiter(old_c);
                     }
@@ -583,20 +670,10 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
     }
 
     pub fn find_special_char(&self) -> usize {
-        for n in self.pos..self.input.len() {
-            if self.special_chars[self.input[n] as usize] {
-                if self.input[n] == b'^' && self.within_brackets {
-                    // NO OP
-                } else {
-                    return n;
-                }
-            }
-            if self.options.parse.smart && self.smart_chars[self.input[n] as usize] {
-                return n;
-            }
-        }
-
-        self.input.len()
+        self.input[self.pos..]
+            .iter()
+            .position(|&c| self.special_chars[c as usize] && !(c == b'^' && self.within_brackets))
+            .map_or(self.input.len(), |n| self.pos + n)
     }
 
     fn adjust_node_newlines(&mut self, node: &'a AstNode<'a>, matchlen: usize, extra: usize) {
@@ -857,13 +934,14 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
     pub fn handle_delim(&mut self, c: u8) -> &'a AstNode<'a> {
         let (numdelims, can_open, can_close) = self.scan_delims(c);
 
+        let quotes = &self.options.parse.smart_quotes;
         let contents = if c == b'\'' && self.options.parse.smart {
-            "’".to_string()
+            quotes.apostrophe.clone()
         } else if c == b'"' && self.options.parse.smart {
             if can_close {
-                "”".to_string()
+                quotes.double_close.clone()
             } else {
-                "“".to_string()
+                quotes.double_open.clone()
             }
         } else {
             str::from_utf8(&self.input[self.pos - numdelims..self.pos])
@@ -1068,12 +1146,17 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
         opener_num_chars -= use_delims;
         closer_num_chars -= use_delims;
 
-        if self.options.extension.strikethrough
-            && opener_char == b'~'
-            && (opener_num_chars != closer_num_chars || opener_num_chars > 0)
-        {
+        let tilde_value = if opener_char != b'~' {
+            None
+        } else if opener_num_chars != closer_num_chars || opener_num_chars > 0 {
             return None;
-        }
+        } else if self.options.extension.subscript && use_delims == 1 {
+            Some(NodeValue::Subscript)
+        } else if self.options.extension.strikethrough {
+            Some(NodeValue::Strikethrough)
+        } else {
+            return None;
+        };
 
         opener
             .inl
@@ -1101,8 +1184,8 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
         }
 
         let emph = self.make_inline(
-            if self.options.extension.strikethrough && opener_char == b'~' {
-                NodeValue::Strikethrough
+            if let Some(value) = tilde_value {
+                value
             } else if self.options.extension.superscript && opener_char == b'^' {
                 NodeValue::Superscript
             } else if self.options.extension.spoiler && opener_char == b'|' {
@@ -1137,6 +1220,15 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
                 closer.inl.data.borrow().sourcepos.end.column - triple_adjustment,
             )
                 .into();
+            if matches!(
+                emph.data.borrow().value,
+                NodeValue::Emph | NodeValue::Strong
+            ) {
+                emph.data.borrow_mut().emphasis = Some(NodeEmphasis {
+                    delim_char: opener_char,
+                    delim_run_length: opener.length,
+                });
+            }
         }
 
         // Drop all the interior AST nodes into the emphasis node
@@ -1219,11 +1311,18 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
             None => self.make_inline(NodeValue::Text("&".to_string()), self.pos - 1, self.pos - 1),
             Some((entity, len)) => {
                 self.pos += len;
-                self.make_inline(
-                    NodeValue::Text(String::from_utf8(entity).unwrap()),
-                    self.pos - 1 - len,
-                    self.pos - 1,
-                )
+                let characters = String::from_utf8(entity).unwrap();
+                let value = if self.options.parse.preserve_entities {
+                    NodeValue::Entity(NodeEntity {
+                        literal: str::from_utf8(&self.input[self.pos - 1 - len..self.pos])
+                            .unwrap()
+                            .to_string(),
+                        characters,
+                    })
+                } else {
+                    NodeValue::Text(characters)
+                };
+                self.make_inline(value, self.pos - 1 - len, self.pos - 1)
             }
         }
     }
@@ -1307,6 +1406,44 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
         self.handle_autolink_with(node, autolink::www_match)
     }
 
+    pub fn handle_mention(&mut self) -> Option<&'a AstNode<'a>> {
+        let template = self.options.extension.mentions.as_ref()?;
+        if self.within_brackets {
+            return None;
+        }
+        if self.pos > 0 {
+            let prev = self.input[self.pos - 1];
+            if prev >= 0x80 || prev.is_ascii_alphanumeric() || b"_-./@`".contains(&prev) {
+                return None;
+            }
+        }
+
+        let start = self.pos + 1;
+        let mut end = start;
+        while end < self.input.len()
+            && (self.input[end].is_ascii_alphanumeric() || self.input[end] == b'-')
+        {
+            end += 1;
+        }
+        let run = end - start;
+        while end > start && self.input[end - 1] == b'-' {
+            end -= 1;
+        }
+        if end == start || self.input[start] == b'-' || run > 39 {
+            return None;
+        }
+
+        let user = str::from_utf8(&self.input[start..end]).unwrap();
+        let inl = self.make_inline(
+            NodeValue::Link(NodeLink::new(template.replace("{user}", user), "")),
+            self.pos,
+            end - 1,
+        );
+        inl.append(self.make_inline(NodeValue::Text(format!("@{}", user)), self.pos, end - 1));
+        self.pos = end;
+        Some(inl)
+    }
+
     pub fn handle_pointy_brace(&mut self) -> &'a AstNode<'a> {
         self.pos += 1;
 
@@ -1417,6 +1554,7 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
             inl_text,
             position: self.pos,
             image,
+            inline_footnote: false,
             bracket_after: false,
         });
         if !image {
@@ -1437,6 +1575,10 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
             ));
         }
 
+        if self.brackets[brackets_len - 1].inline_footnote {
+            return self.close_inline_footnote();
+        }
+
         let is_image = self.brackets[brackets_len - 1].image;
 
         if !is_image && self.no_link_openers {
@@ -1492,7 +1634,18 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
                 )
         } {
             let starturl = self.pos + 1 + sps;
-            let endurl = starturl + n;
+            let mut endurl = starturl + n;
+            let mut dimensions = (None, None);
+            if is_image && self.options.extension.image_dimensions {
+                let start = endurl + scanners::spacechars(&self.input[endurl..]).unwrap_or(0);
+                if start > endurl {
+                    if let Some((len, width, height)) = scan_image_dimensions(&self.input[start..])
+                    {
+                        endurl = start + len;
+                        dimensions = (width, height);
+                    }
+                }
+            }
             let starttitle = endurl + scanners::spacechars(&self.input[endurl..]).unwrap_or(0);
             let endtitle = if starttitle == endurl {
                 starttitle
@@ -1505,11 +1658,13 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
                 self.pos = endall + 1;
                 let url = strings::clean_url(url);
                 let title = strings::clean_title(&self.input[starttitle..endtitle]);
-                self.close_bracket_match(
-                    is_image,
+                let mut nl = NodeLink::new(
                     String::from_utf8(url).unwrap(),
                     String::from_utf8(title).unwrap(),
                 );
+                nl.width = dimensions.0;
+                nl.height = dimensions.1;
+                self.close_bracket_match(is_image, nl);
                 return None;
             } else {
                 self.pos = after_link_text_pos;
@@ -1556,7 +1711,7 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
         }
 
         if let Some(reff) = reff {
-            self.close_bracket_match(is_image, reff.url.clone(), reff.title);
+            self.close_bracket_match(is_image, NodeLink::new(reff.url, reff.title));
             return None;
         }
 
@@ -1597,6 +1752,7 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
                     NodeValue::Text(ref literal) | NodeValue::HtmlInline(ref literal) => {
                         text.push_str(literal);
                     }
+                    NodeValue::Entity(ref ne) => text.push_str(&ne.characters),
                     _ => {}
                 };
             }
@@ -1624,7 +1780,7 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
                 sibling_iterator = bracket_inl_text.following_siblings();
                 for sibling in sibling_iterator {
                     match sibling.data.borrow().value {
-                        NodeValue::Text(_) | NodeValue::HtmlInline(_) => {
+                        NodeValue::Text(_) | NodeValue::HtmlInline(_) | NodeValue::Entity(_) => {
                             sibling.detach();
                         }
                         _ => {}
@@ -1645,10 +1801,56 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
         Some(self.make_inline(NodeValue::Text("]".to_string()), self.pos - 1, self.pos - 1))
     }
 
-    pub fn close_bracket_match(&mut self, is_image: bool, url: String, title: String) {
+    // The definition of an inline footnote is made a child of the reference
+    // until footnotes are processed, which names it and moves it to the end
+    // of the document with the others.
+    fn close_inline_footnote(&mut self) -> Option<&'a AstNode<'a>> {
+        let bracket = self.brackets.pop().unwrap();
+        if bracket.inl_text.next_sibling().is_none() {
+            return Some(self.make_inline(
+                NodeValue::Text("]".to_string()),
+                self.pos - 1,
+                self.pos - 1,
+            ));
+        }
+
+        let inl = self.make_inline(
+            NodeValue::FootnoteReference(NodeFootnoteReference::default()),
+            // Manually set below.
+            self.pos,
+            self.pos,
+        );
+        let mut sourcepos = bracket.inl_text.data.borrow().sourcepos;
+        sourcepos.end.column =
+            usize::try_from(self.pos as isize + self.column_offset + self.line_offset as isize)
+                .unwrap();
+        inl.data.borrow_mut().sourcepos = sourcepos;
+
+        let definition = self.make_inline(
+            NodeValue::FootnoteDefinition(NodeFootnoteDefinition::default()),
+            self.pos,
+            self.pos,
+        );
+        let paragraph = self.make_inline(NodeValue::Paragraph, self.pos, self.pos);
+        definition.data.borrow_mut().sourcepos = sourcepos;
+        paragraph.data.borrow_mut().sourcepos = sourcepos;
+        inl.append(definition);
+        definition.append(paragraph);
+
+        bracket.inl_text.insert_before(inl);
+        let mut tmpch = bracket.inl_text.next_sibling();
+        while let Some(tmp) = tmpch {
+            tmpch = tmp.next_sibling();
+            paragraph.append(tmp);
+        }
+        bracket.inl_text.detach();
+        self.process_emphasis(bracket.position);
+        None
+    }
+
+    pub fn close_bracket_match(&mut self, is_image: bool, nl: NodeLink) {
         let brackets_len = self.brackets.len();
 
-        let nl = NodeLink { url, title };
         let inl = self.make_inline(
             if is_image {
                 NodeValue::Image(nl)
@@ -1723,9 +1925,70 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
         }
     }
 
-    // Handles wikilink syntax
-    //   [[link text|url]]
-    //   [[url|link text]]
+    // Handles template syntax
+    //   {{ name | filter }}
+    //   {% tag %}
+    pub fn handle_template(&mut self) -> &'a AstNode<'a> {
+        let startpos = self.pos;
+        let input = &self.input[self.pos..];
+        if self.options.extension.template_variables {
+            if let Some((ntv, len)) = template::placeholder(input) {
+                self.pos += len;
+                return self.make_inline(NodeValue::TemplateVariable(ntv), startpos, self.pos - 1);
+            }
+        }
+        if self.options.extension.template_tags {
+            if let Some(len) = template::tag(input) {
+                self.pos += len;
+                let literal = String::from_utf8(input[..len].to_vec()).unwrap();
+                return self.make_inline(NodeValue::TemplateTag(literal), startpos, self.pos - 1);
+            }
+        }
+        self.pos += 1;
+        self.make_inline(NodeValue::Text("{".to_string()), startpos, startpos)
+    }
+
+    // Handles the syntax of registered extensions.
+    pub fn handle_extension(&mut self) -> Option<&'a AstNode<'a>> {
+        let startpos = self.pos;
+        let input = str::from_utf8(&self.input[self.pos..]).ok()?;
+        let c = self.input[self.pos];
+        let options = self.options;
+        for extension in options.extensions.iter() {
+            if !extension.inline_chars().contains(&c) {
+                continue;
+            }
+            let (len, mut ne) = match extension.parse_inline(input) {
+                Some((len, ne)) if len > 0 && input.is_char_boundary(len) => (len, ne),
+                _ => continue,
+            };
+            ne.extension = extension.name().to_string();
+            ne.literal = input[..len].to_string();
+            self.pos += len;
+            return Some(self.make_inline(NodeValue::ExtensionInline(ne), startpos, self.pos - 1));
+        }
+        None
+    }
+
+    pub fn handle_component(&mut self) -> Option<&'a AstNode<'a>> {
+        let startpos = self.pos;
+        let name = component::name(&self.input[self.pos..])?;
+        let len = ComponentScanner::default().scan(&self.input[self.pos..])?;
+        self.pos += len;
+        let nc = NodeComponent {
+            name,
+            literal: String::from_utf8(self.input[startpos..self.pos].to_vec()).unwrap(),
+        };
+        Some(self.make_inline(NodeValue::ComponentInline(nc), startpos, self.pos - 1))
+    }
+
+    pub fn handle_citation(&mut self) -> Option<&'a AstNode<'a>> {
+        let startpos = self.pos;
+        let (nc, len) = citation::citation(&self.input[self.pos..])?;
+        self.pos += len;
+        Some(self.make_inline(NodeValue::Citation(nc), startpos, self.pos - 1))
+    }
+
     pub fn handle_wikilink(&mut self) -> Option<&'a AstNode<'a>> {
         let startpos = self.pos;
         let component = self.wikilink_url_link_label()?;
@@ -1871,6 +2134,11 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
                 usize::try_from(end_column).unwrap(),
             )
                 .into(),
+            start_offset: 0,
+            end_offset: 0,
+            emphasis: None,
+            table_cell: None,
+            figure: None,
             internal_offset: 0,
             open: false,
             last_line_blank: false,
@@ -1888,10 +2156,10 @@ impl<'a, 'r, 'o, 'c, 'd, 'i> Subject<'a, 'r, 'o, 'c, 'd, 'i> {
         end_column: usize,
     ) -> &'a AstNode<'a> {
         let inl = self.make_inline(
-            NodeValue::Link(NodeLink {
-                url: String::from_utf8(strings::clean_autolink(url, kind)).unwrap(),
-                title: String::new(),
-            }),
+            NodeValue::Link(NodeLink::new(
+                String::from_utf8(strings::clean_autolink(url, kind)).unwrap(),
+                "",
+            )),
             start_column + 1,
             end_column + 1,
         );
@@ -1971,6 +2239,35 @@ pub fn manual_scan_link_url_2(input: &[u8]) -> Option<(&[u8], usize)> {
     }
 }
 
+// Scans the size of an image, `=640x480`, in which either dimension may be
+// omitted, returning its length and the dimensions.  It must be followed by
+// whitespace or the end of the link.
+fn scan_image_dimensions(input: &[u8]) -> Option<(usize, Option<u32>, Option<u32>)> {
+    fn number(input: &[u8]) -> (usize, Option<u32>) {
+        let len = input.iter().take_while(|&&c| isdigit(c)).count();
+        let value = str::from_utf8(&input[..len]).unwrap().parse().ok();
+        (len, value)
+    }
+
+    if input.first() != Some(&b'=') {
+        return None;
+    }
+    let (width_len, width) = number(&input[1..]);
+    if input.get(1 + width_len) != Some(&b'x') {
+        return None;
+    }
+    let (height_len, height) = number(&input[2 + width_len..]);
+    let len = 2 + width_len + height_len;
+    if width_len + height_len == 0
+        || (width_len > 0 && width.is_none())
+        || (height_len > 0 && height.is_none())
+        || !input.get(len).map_or(false, |&c| c == b')' || isspace(c))
+    {
+        return None;
+    }
+    Some((len, width, height))
+}
+
 pub fn make_inline<'a>(
     arena: &'a Arena<AstNode<'a>>,
     value: NodeValue,
@@ -1980,6 +2277,11 @@ pub fn make_inline<'a>(
         value,
         content: String::new(),
         sourcepos,
+        start_offset: 0,
+        end_offset: 0,
+        emphasis: None,
+        table_cell: None,
+        figure: None,
         internal_offset: 0,
         open: false,
         last_line_blank: false,
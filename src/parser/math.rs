@@ -1,5 +1,6 @@
 /// An inline math span
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeMath {
     /// Whether this is dollar math (`$` or `$$`).
     /// `false` indicates it is code math
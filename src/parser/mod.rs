@@ -1,34 +1,56 @@
+pub mod abbreviation;
+pub mod alert;
+pub mod attributes;
 mod autolink;
+pub mod citation;
+pub mod component;
+pub mod container;
 mod inlines;
 #[cfg(feature = "shortcodes")]
 pub mod shortcodes;
 mod table;
+pub mod template;
 
 pub mod math;
 pub mod multiline_block_quote;
+mod raw;
+mod streaming;
 
-use crate::adapters::SyntaxHighlighterAdapter;
+pub use self::streaming::StreamingParser;
+
+use crate::adapters::{CodeBlockCache, SyntaxHighlighterAdapter};
 use crate::arena_tree::Node;
+use crate::cancel::{CancellationToken, Cancelled, Checkpoint};
 use crate::ctype::{isdigit, isspace};
 use crate::entity;
-use crate::nodes::{self, NodeFootnoteDefinition, Sourcepos};
+use crate::extensions::{BlockContinuation, ExtensionRegistry, NodeExtension};
+use crate::nodes::{
+    self, LineColumn, NodeAbbreviationDefinition, NodeFootnoteDefinition, Sourcepos,
+};
 use crate::nodes::{
-    Ast, AstNode, ListDelimType, ListType, NodeCodeBlock, NodeDescriptionItem, NodeHeading,
-    NodeHtmlBlock, NodeList, NodeValue,
+    Ast, AstNode, ListDelimType, ListType, NodeAttributes, NodeCodeBlock, NodeDescriptionItem,
+    NodeHeading, NodeHtmlBlock, NodeList, NodeValue,
 };
 use crate::scanners::{self, SetextChar};
 use crate::strings::{self, split_off_front_matter, Case};
 use derive_builder::Builder;
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::cmp::min;
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Debug, Formatter};
 use std::mem;
 use std::str;
 use std::sync::{Arc, Mutex};
 use typed_arena::Arena;
 
-use crate::adapters::HeadingAdapter;
+use crate::adapters::{
+    HeadingAdapter, ImageDimensionAdapter, ImageResolver, LinkTitleAdapter, TableCellAdapter,
+    TemplateResolver, UrlRewriter,
+};
+use crate::parser::component::{ComponentScanner, NodeComponent};
+use crate::parser::container::NodeContainer;
 use crate::parser::multiline_block_quote::NodeMultilineBlockQuote;
 
 use self::inlines::RefMap;
@@ -42,6 +64,10 @@ const CODE_INDENT: usize = 4;
 // be nested this deeply.
 const MAX_LIST_DEPTH: usize = 100;
 
+// The default limit on the nesting of block containers.  Formatters other
+// than HTML recurse into the tree, so this keeps their stack use bounded.
+const MAX_NESTING: usize = 256;
+
 macro_rules! node_matches {
     ($node:expr, $( $pat:pat )|+) => {{
         matches!(
@@ -59,20 +85,452 @@ pub fn parse_document<'a>(
     buffer: &str,
     options: &Options,
 ) -> &'a AstNode<'a> {
-    let root: &'a AstNode<'a> = arena.alloc(Node::new(RefCell::new(Ast {
+    parse_document_with_context(arena, buffer, options, &mut ParseContext::default())
+}
+
+/// Parse a Markdown document to an AST, continuing from the state left in
+/// `context` by any documents parsed with it before.
+///
+/// Link reference definitions from earlier documents are in effect, taking
+/// precedence over any of the same label in this one, as they would in a
+/// single file.  Footnotes are numbered on from those of earlier documents.
+/// Footnote names are not made unique across documents, so documents
+/// rendered onto the same page should not reuse them.
+///
+/// ```
+/// # use comrak::{format_html, parse_document_with_context, Arena, Options, ParseContext};
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.footnotes = true;
+/// let mut context = ParseContext::new();
+///
+/// parse_document_with_context(&arena, "[AST]: https://example.com/ast\n", &options, &mut context);
+/// parse_document_with_context(&arena, "Trees[^a].\n\n[^a]: One.\n", &options, &mut context);
+/// let root =
+///     parse_document_with_context(&arena, "An [AST] too[^b].\n\n[^b]: Two.\n", &options, &mut context);
+/// assert_eq!(context.footnote_count(), 2);
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(
+///     String::from_utf8(html).unwrap(),
+///     "<p>An <a href=\"https://example.com/ast\">AST</a> too<sup class=\"footnote-ref\">\
+///      <a href=\"#fn-b\" id=\"fnref-b\" data-footnote-ref>2</a></sup>.</p>\n\
+///      <section class=\"footnotes\" data-footnotes>\n\
+///      <ol start=\"2\">\n\
+///      <li id=\"fn-b\">\n\
+///      <p>Two. <a href=\"#fnref-b\" class=\"footnote-backref\" data-footnote-backref \
+///      data-footnote-backref-idx=\"2\" aria-label=\"Back to reference 2\">↩</a></p>\n\
+///      </li>\n\
+///      </ol>\n\
+///      </section>\n"
+/// );
+/// ```
+pub fn parse_document_with_context<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    buffer: &str,
+    options: &Options,
+    context: &mut ParseContext,
+) -> &'a AstNode<'a> {
+    parse_document_checked(arena, buffer, options, context, None)
+        .expect("parsing without a token can't be cancelled")
+}
+
+/// Parse text as inline content only, such as a commit title or a form
+/// field, without any block structure.  Returns the inline nodes in order,
+/// detached from any parent.
+///
+/// Line endings in `text` become soft or hard line breaks.  With no block
+/// structure there are no link reference definitions, so only inline links
+/// are recognised.
+///
+/// ```
+/// # use comrak::{format_html, parse_inline, Arena, Options};
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let nodes = parse_inline(&arena, "# Fix *the* [bug](/1)", &options);
+/// assert_eq!(nodes.len(), 4);
+///
+/// let mut html = vec![];
+/// for node in nodes {
+///     format_html(node, &options, &mut html).unwrap();
+/// }
+/// assert_eq!(
+///     String::from_utf8(html).unwrap(),
+///     "# Fix <em>the</em> <a href=\"/1\">bug</a>"
+/// );
+/// ```
+pub fn parse_inline<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    text: &str,
+    options: &Options,
+) -> Vec<&'a AstNode<'a>> {
+    let text = text.replace('\0', "\u{fffd}");
+    let mut line_starts = LineStarts::default();
+    line_starts.push(text.as_bytes());
+    let line_starts = line_starts.finish();
+
+    // The inline parser appends the nodes to a parent, which holds the text.
+    let parent = alloc_document(arena);
+    {
+        let mut ast = parent.data.borrow_mut();
+        ast.content = text.clone();
+        ast.line_offsets = vec![0; line_starts.len()];
+    }
+
+    let mut parser = Parser::new(arena, parent, options);
+    parser.parse_inlines(parent);
+    parser.postprocess_text_nodes(parent);
+    set_offsets_from_line_starts(parent, &line_starts, 0, text.len());
+
+    let nodes: Vec<_> = parent.children().collect();
+    for node in &nodes {
+        node.detach();
+    }
+    nodes
+}
+
+/// Parse a Markdown document to an AST, stopping with [`Cancelled`] if
+/// `token` is cancelled first.  See [`cancel`](crate::cancel).
+///
+/// ```
+/// # use comrak::{parse_document_cancellable, Arena, Options};
+/// # use comrak::cancel::{CancellationToken, Cancelled};
+/// # use std::time::Instant;
+/// let arena = Arena::new();
+/// let token = CancellationToken::with_deadline(Instant::now());
+/// assert_eq!(
+///     parse_document_cancellable(&arena, "Too late.\n", &Options::default(), &token).err(),
+///     Some(Cancelled)
+/// );
+/// ```
+pub fn parse_document_cancellable<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    buffer: &str,
+    options: &Options,
+    token: &CancellationToken,
+) -> Result<&'a AstNode<'a>, Cancelled> {
+    parse_document_checked(
+        arena,
+        buffer,
+        options,
+        &mut ParseContext::default(),
+        Some(token),
+    )
+}
+
+/// Parse a Markdown document to an AST, unless it's longer than
+/// [`ParseOptions::max_input_size`] and [`OversizedInput::Reject`] is set,
+/// in which case [`InputTooLarge`] is returned.  Longer documents are
+/// otherwise truncated as with [`parse_document`].
+///
+/// ```
+/// # use comrak::{try_parse_document, Arena, Options};
+/// # use comrak::nodes::NodeValue;
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.parse.max_input_size = Some(16);
+/// let root = try_parse_document(&arena, "First\n\nSecond paragraph.\n", &options).unwrap();
+/// assert_eq!(root.last_child().unwrap().data.borrow().value, NodeValue::Truncated(18));
+/// ```
+pub fn try_parse_document<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    buffer: &str,
+    options: &Options,
+) -> Result<&'a AstNode<'a>, InputTooLarge> {
+    match options.parse.max_input_size {
+        Some(limit)
+            if buffer.len() > limit && options.parse.oversized_input == OversizedInput::Reject =>
+        {
+            Err(InputTooLarge {
+                size: buffer.len(),
+                limit,
+            })
+        }
+        _ => Ok(parse_document(arena, buffer, options)),
+    }
+}
+
+/// The error returned by [`try_parse_document`] when the input is longer
+/// than [`ParseOptions::max_input_size`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputTooLarge {
+    /// The length of the input, in bytes.
+    pub size: usize,
+
+    /// The limit it exceeded, in bytes.
+    pub limit: usize,
+}
+
+impl fmt::Display for InputTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "input of {} bytes exceeds the limit of {} bytes",
+            self.size, self.limit
+        )
+    }
+}
+
+impl std::error::Error for InputTooLarge {}
+
+// The length of the input to parse of `buffer`, given the limit on its size.
+pub(crate) fn input_limit(buffer: &str, options: &Options) -> usize {
+    match options.parse.max_input_size {
+        Some(limit) if buffer.len() > limit => match options.parse.oversized_input {
+            OversizedInput::Truncate => truncation_point(buffer, limit),
+            OversizedInput::Reject => 0,
+        },
+        _ => buffer.len(),
+    }
+}
+
+// The length of the longest prefix of `buffer` no longer than `limit` which
+// ends with a blank line, or failing that a line break, or failing that a
+// whole character.
+fn truncation_point(buffer: &str, limit: usize) -> usize {
+    let bytes = &buffer.as_bytes()[..limit];
+    let mut last_line_end = None;
+    let mut end = bytes.len();
+    while let Some(newline) = memchr::memrchr(b'\n', &bytes[..end]) {
+        last_line_end.get_or_insert(newline + 1);
+        let start = memchr::memrchr(b'\n', &bytes[..newline]).map_or(0, |i| i + 1);
+        if bytes[start..newline]
+            .iter()
+            .all(|&c| matches!(c, b' ' | b'\t' | b'\r'))
+        {
+            return newline + 1;
+        }
+        end = newline;
+    }
+    last_line_end.unwrap_or_else(|| {
+        let mut end = limit;
+        while !buffer.is_char_boundary(end) {
+            end -= 1;
+        }
+        end
+    })
+}
+
+// Marks `root` as parsed from input cut short by `omitted` bytes.
+pub(crate) fn append_truncated<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    root: &'a AstNode<'a>,
+    parsed: usize,
+    omitted: usize,
+) {
+    let line = match root.last_child() {
+        Some(last) => last.data.borrow().sourcepos.end.line + 1,
+        None => 1,
+    };
+    let mut ast = Ast::new(NodeValue::Truncated(omitted), (line, 1).into());
+    ast.open = false;
+    ast.start_offset = parsed;
+    ast.end_offset = parsed;
+    root.append(arena.alloc(Node::new(RefCell::new(ast))));
+}
+
+fn parse_document_checked<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    buffer: &str,
+    options: &Options,
+    context: &mut ParseContext,
+    token: Option<&CancellationToken>,
+) -> Result<&'a AstNode<'a>, Cancelled> {
+    let (buffer, omitted) = buffer.split_at(input_limit(buffer, options));
+    let root = alloc_document(arena);
+    let mut parser = Parser::new(arena, root, options);
+    parser.checkpoint = Checkpoint::new(token);
+    parser.refmap.map = mem::take(&mut context.references);
+    parser.refmap.used = mem::take(&mut context.used_references);
+    parser.footnote_ix = context.footnotes;
+    let mut linebuf = Vec::with_capacity(buffer.len());
+    parser.feed(&mut linebuf, buffer, true);
+    let root = parser.finish(linebuf);
+    if parser.checkpoint.cancelled() {
+        return Err(Cancelled);
+    }
+    set_offsets(root, buffer, parser.front_matter_len);
+    if !omitted.is_empty() {
+        append_truncated(arena, root, buffer.len(), omitted.len());
+    }
+    context.references = mem::take(&mut parser.refmap.map);
+    context.used_references = mem::take(&mut parser.refmap.used);
+    context.definitions.append(&mut parser.definitions);
+    context.footnotes = parser.footnote_ix;
+    Ok(root)
+}
+
+fn alloc_document<'a>(arena: &'a Arena<AstNode<'a>>) -> &'a AstNode<'a> {
+    arena.alloc(Node::new(RefCell::new(Ast {
         value: NodeValue::Document,
         content: String::new(),
         sourcepos: (1, 1, 1, 1).into(),
+        start_offset: 0,
+        end_offset: 0,
+        emphasis: None,
+        table_cell: None,
+        figure: None,
         internal_offset: 0,
         open: true,
         last_line_blank: false,
         table_visited: false,
         line_offsets: Vec::with_capacity(0),
-    })));
-    let mut parser = Parser::new(arena, root, options);
-    let mut linebuf = Vec::with_capacity(buffer.len());
-    parser.feed(&mut linebuf, buffer, true);
-    parser.finish(linebuf)
+    })))
+}
+
+// Finds the lines and columns of bytes of the content of a block which starts
+// on `start_line`, given the offsets of the content's lines from the start of
+// their source lines.  Bytes must be looked up in order, so that the content
+// is only scanned once.
+struct ContentPositions<'c> {
+    content: &'c [u8],
+    start_line: usize,
+    line_offsets: &'c [usize],
+    // The number of bytes scanned, the number of lines they end, and the
+    // index of the start of the last.
+    scanned: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl<'c> ContentPositions<'c> {
+    fn new(content: &'c [u8], start_line: usize, line_offsets: &'c [usize]) -> Self {
+        ContentPositions {
+            content,
+            start_line,
+            line_offsets,
+            scanned: 0,
+            line: 0,
+            line_start: 0,
+        }
+    }
+
+    fn position(&mut self, index: usize) -> LineColumn {
+        debug_assert!(index >= self.scanned);
+        for i in self.scanned..index {
+            if self.content[i] == b'\n' {
+                self.line += 1;
+                self.line_start = i + 1;
+            }
+        }
+        self.scanned = index;
+
+        let offset = self.line_offsets.get(self.line).copied().unwrap_or(0);
+        LineColumn {
+            line: self.start_line + self.line,
+            column: offset + index - self.line_start + 1,
+        }
+    }
+}
+
+// Sets the byte offsets of each node from its source position.  Line numbers
+// count from the end of the front matter, the first `base` bytes of `buffer`.
+fn set_offsets<'a>(root: &'a AstNode<'a>, buffer: &str, base: usize) {
+    let mut line_starts = LineStarts::default();
+    line_starts.push(&buffer.as_bytes()[base..]);
+    set_offsets_from_line_starts(root, &line_starts.finish(), base, buffer.len());
+}
+
+// Sets the byte offsets of each node from its source position, given the
+// offsets of the lines after the front matter, the first `base` of `len`
+// bytes of input.
+fn set_offsets_from_line_starts<'a>(
+    root: &'a AstNode<'a>,
+    line_starts: &[usize],
+    base: usize,
+    len: usize,
+) {
+    let offset = |line: usize, column: usize| match line_starts.get(line.wrapping_sub(1)) {
+        Some(&start) => min(base + start + column, len),
+        None => len,
+    };
+
+    for node in root.descendants() {
+        let mut ast = node.data.borrow_mut();
+        if let NodeValue::FrontMatter(_) = ast.value {
+            ast.start_offset = 0;
+            ast.end_offset = base;
+            continue;
+        }
+        let sourcepos = ast.sourcepos;
+        ast.start_offset = offset(
+            sourcepos.start.line,
+            sourcepos.start.column.saturating_sub(1),
+        );
+        ast.end_offset = offset(sourcepos.end.line, sourcepos.end.column).max(ast.start_offset);
+    }
+}
+
+// The offsets of the starts of lines of text given in pieces, where `\r\n`
+// ends a single line.
+#[derive(Default)]
+struct LineStarts {
+    starts: Vec<usize>,
+    len: usize,
+    // Whether the last piece ended with a `\r`, which may be followed by a
+    // `\n` in the next.
+    pending_cr: bool,
+}
+
+impl LineStarts {
+    fn push(&mut self, text: &[u8]) {
+        for &c in text {
+            self.len += 1;
+            if mem::take(&mut self.pending_cr) {
+                if c == b'\n' {
+                    self.starts.push(self.len);
+                    continue;
+                }
+                self.starts.push(self.len - 1);
+            }
+            match c {
+                b'\r' => self.pending_cr = true,
+                b'\n' => self.starts.push(self.len),
+                _ => {}
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<usize> {
+        if self.pending_cr {
+            self.starts.push(self.len);
+        }
+        self.starts.insert(0, 0);
+        self.starts
+    }
+}
+
+/// Parse a Markdown document given as bytes to an AST.
+///
+/// Invalid UTF-8 is replaced with U+FFFD REPLACEMENT CHARACTER if
+/// [`ParseOptions::lossy_utf8`] is set; otherwise the decoding error is
+/// returned.  A leading byte order mark is skipped, as with
+/// [`parse_document`].
+///
+/// ```
+/// # use comrak::{Arena, parse_document_bytes, format_html, Options};
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// assert!(parse_document_bytes(&arena, b"\xef\xbb\xbfhi \xff", &options).is_err());
+///
+/// options.parse.lossy_utf8 = true;
+/// let root = parse_document_bytes(&arena, b"\xef\xbb\xbfhi \xff", &options).unwrap();
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(String::from_utf8(html).unwrap(), "<p>hi \u{fffd}</p>\n");
+/// ```
+pub fn parse_document_bytes<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    buffer: &[u8],
+    options: &Options,
+) -> Result<&'a AstNode<'a>, str::Utf8Error> {
+    let buffer = if options.parse.lossy_utf8 {
+        String::from_utf8_lossy(buffer)
+    } else {
+        Cow::Borrowed(str::from_utf8(buffer)?)
+    };
+    Ok(parse_document(arena, &buffer, options))
 }
 
 /// Parse a Markdown document to an AST, specifying
@@ -99,8 +557,11 @@ pub fn parse_document_with_broken_link_callback<'a, 'c>(
 /// [`BrokenLinkReference`] argument. If a [`ResolvedReference`] is returned, it
 /// is used as the link; otherwise, no link is made and the reference text is
 /// preserved in its entirety.
+///
+/// The callback must be [`Send`], so that [`Options`] can be shared between
+/// threads.
 pub type BrokenLinkCallback<'c> =
-    &'c mut dyn FnMut(BrokenLinkReference) -> Option<ResolvedReference>;
+    &'c mut (dyn FnMut(BrokenLinkReference) -> Option<ResolvedReference> + Send);
 
 /// Struct to the broken link callback, containing details on the link reference
 /// which failed to find a match.
@@ -115,15 +576,35 @@ pub struct BrokenLinkReference<'l> {
     pub original: &'l str,
 }
 
+/// The type of the callback used to report [`Diagnostic`]s found while
+/// parsing.  Like [`BrokenLinkCallback`], it must be [`Send`].
+pub type DiagnosticCallback<'c> = &'c mut (dyn FnMut(Diagnostic) + Send);
+
+/// A problem found in the input while parsing, such as a malformed table row.
+/// Diagnostics never stop parsing; they are passed to
+/// [`ParseOptions::diagnostic_callback`] as they are found.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// The source position the problem was found at.
+    pub sourcepos: Sourcepos,
+
+    /// A description of the problem.
+    pub message: String,
+}
+
 pub struct Parser<'a, 'o, 'c> {
     arena: &'a Arena<AstNode<'a>>,
     refmap: RefMap,
+    // The link reference definitions parsed, in source order.
+    definitions: Vec<ReferenceDefinition>,
     root: &'a AstNode<'a>,
     current: &'a AstNode<'a>,
     line_number: usize,
     offset: usize,
     column: usize,
     thematic_break_kill_pos: usize,
+    // The column width of tab stops.
+    tab_stop: usize,
     first_nonspace: usize,
     first_nonspace_column: usize,
     indent: usize,
@@ -134,13 +615,41 @@ pub struct Parser<'a, 'o, 'c> {
     last_line_length: usize,
     last_buffer_ended_with_cr: bool,
     total_size: usize,
+    // The number of footnotes numbered, including those of documents parsed
+    // before with the same ParseContext.
+    footnote_ix: u32,
+    // The length of the front matter split off the input, which line numbers
+    // don't count.
+    front_matter_len: usize,
+    // Tracks the component block being parsed, if any.
+    component: ComponentScanner,
+    // Whether the current line closes the extension block being parsed.
+    close_extension_block: bool,
+    // Whether the fenced code block being finalized is closed by the current
+    // line.
+    closing_fence: bool,
+    checkpoint: Checkpoint,
     options: &'o Options<'c>,
 }
 
 #[derive(Default, Debug, Clone)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(default))]
 /// Umbrella options struct. `'c` represents the lifetime of any callback
 /// closure options may take.
+///
+/// With the `serde` feature, options can be deserialized, e.g. from JSON
+/// configuration.  Omitted fields take their defaults; callbacks and
+/// third-party extensions can't be deserialized and are left unset.
+///
+/// ```
+/// # #[cfg(feature = "serde")] {
+/// # use comrak::{markdown_to_html, Options};
+/// let options: Options =
+///     serde_json::from_str(r#"{"extension": {"strikethrough": true}}"#).unwrap();
+/// assert_eq!(markdown_to_html("~~hi~~\n", &options), "<p><del>hi</del></p>\n");
+/// # }
+/// ```
 pub struct Options<'c> {
     /// Enable CommonMark extensions.
     pub extension: ExtensionOptions,
@@ -150,12 +659,42 @@ pub struct Options<'c> {
 
     /// Configure render-time options.
     pub render: RenderOptions,
+
+    /// Third-party extensions, consulted while parsing and rendering.  See
+    /// [`extensions`](crate::extensions).
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub extensions: ExtensionRegistry,
+}
+
+impl<'c> Options<'c> {
+    /// The options for GitHub Flavored Markdown, as enabled by `--gfm` on the
+    /// command line: the strikethrough, tagfilter, table, autolink and
+    /// tasklist extensions, with `github_pre_lang` and `gfm_quirks`.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// assert_eq!(markdown_to_html("~~www.example.com~~\n", &Options::gfm()),
+    ///            "<p><del><a href=\"http://www.example.com\">www.example.com</a></del></p>\n");
+    /// ```
+    pub fn gfm() -> Self {
+        let mut options = Options::default();
+        options.extension.strikethrough = true;
+        options.extension.tagfilter = true;
+        options.extension.table = true;
+        options.extension.autolink = true;
+        options.extension.tasklist = true;
+        options.render.github_pre_lang = true;
+        options.render.gfm_quirks = true;
+        options
+    }
 }
 
 #[non_exhaustive]
 #[derive(Default, Debug, Clone, Builder)]
 #[builder(default)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(default))]
 /// Options to select extensions.
 pub struct ExtensionOptions {
     /// Enables the
@@ -198,6 +737,48 @@ pub struct ExtensionOptions {
     /// ```
     pub table: bool,
 
+    /// How table body rows whose cell count differs from the header row are
+    /// handled. Each such row is also reported to
+    /// [`ParseOptions::diagnostic_callback`].
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options, TableRowMismatch};
+    /// let mut options = Options::default();
+    /// options.extension.table = true;
+    /// let input = "| a | b |\n|---|---|\n| c |\n";
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<table>\n<thead>\n<tr>\n<th>a</th>\n<th>b</th>\n</tr>\n</thead>\n\
+    ///             <tbody>\n<tr>\n<td>c</td>\n<td></td>\n</tr>\n</tbody>\n</table>\n");
+    ///
+    /// options.extension.table_row_mismatch = TableRowMismatch::Truncate;
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<table>\n<thead>\n<tr>\n<th>a</th>\n<th>b</th>\n</tr>\n</thead>\n</table>\n\
+    ///             <p>| c |</p>\n");
+    ///
+    /// options.extension.table_row_mismatch = TableRowMismatch::Paragraph;
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p>| a | b |\n|---|---|\n| c |</p>\n");
+    /// ```
+    pub table_row_mismatch: TableRowMismatch,
+
+    /// Lets table cells span several columns, as in MultiMarkdown: a cell
+    /// followed directly by further pipes, with nothing between them, spans
+    /// one more column for each.  The span is given by
+    /// [`Ast::table_cell`](crate::nodes::Ast::table_cell).
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.table = true;
+    /// options.extension.table_colspans = true;
+    /// let input = "| a | b | c |\n|---|---|---|\n| d || e |\n| f |||\n";
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<table>\n<thead>\n<tr>\n<th>a</th>\n<th>b</th>\n<th>c</th>\n</tr>\n</thead>\n\
+    ///             <tbody>\n<tr>\n<td colspan=\"2\">d</td>\n<td>e</td>\n</tr>\n\
+    ///             <tr>\n<td colspan=\"3\">f</td>\n</tr>\n</tbody>\n</table>\n");
+    /// ```
+    pub table_colspans: bool,
+
     /// Enables the [autolink extension](https://github.github.com/gfm/#autolinks-extension-)
     /// from the GFM spec.
     ///
@@ -239,6 +820,21 @@ pub struct ExtensionOptions {
     /// ```
     pub superscript: bool,
 
+    /// Enables the subscript Comrak extension.
+    ///
+    /// With [`strikethrough`](ExtensionOptions::strikethrough) also enabled,
+    /// single tildes mark subscript and double tildes strikethrough.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.subscript = true;
+    /// options.extension.strikethrough = true;
+    /// assert_eq!(markdown_to_html("H~2~O is ~~not~~ water.\n", &options),
+    ///            "<p>H<sub>2</sub>O is <del>not</del> water.</p>\n");
+    /// ```
+    pub subscript: bool,
+
     /// Enables the header IDs Comrak extension.
     ///
     /// ```
@@ -250,6 +846,51 @@ pub struct ExtensionOptions {
     /// ```
     pub header_ids: Option<String>,
 
+    /// How the header IDs extension makes colliding IDs unique.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, HeaderIdCollisions, Options};
+    /// let mut options = Options::default();
+    /// options.extension.header_ids = Some("".to_string());
+    /// options.extension.header_id_collisions = HeaderIdCollisions::Hash;
+    /// assert_eq!(markdown_to_html("# A\n# A\n", &options),
+    ///            "<h1><a href=\"#a\" aria-hidden=\"true\" class=\"anchor\" id=\"a\"></a>A</h1>\n\
+    ///             <h1><a href=\"#a-accefd\" aria-hidden=\"true\" class=\"anchor\" id=\"a-accefd\"></a>A</h1>\n");
+    /// ```
+    pub header_id_collisions: HeaderIdCollisions,
+
+    /// How the header IDs extension writes non-ASCII characters in IDs.
+    /// Headings are normalized to Unicode NFC first, so the same text
+    /// always gives the same ID however it's encoded.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, HeaderIdStyle, Options};
+    /// let mut options = Options::default();
+    /// options.extension.header_ids = Some("".to_string());
+    /// options.extension.header_id_style = HeaderIdStyle::Ascii;
+    /// assert_eq!(markdown_to_html("# Crème brûlée\n", &options),
+    ///            "<h1><a href=\"#creme-brulee\" aria-hidden=\"true\" class=\"anchor\" id=\"creme-brulee\"></a>Crème brûlée</h1>\n");
+    ///
+    /// options.extension.header_id_style = HeaderIdStyle::PercentEncoded;
+    /// assert_eq!(markdown_to_html("# Crème brûlée\n", &options),
+    ///            "<h1><a href=\"#cr%C3%A8me-br%C3%BBl%C3%A9e\" aria-hidden=\"true\" class=\"anchor\" id=\"cr%C3%A8me-br%C3%BBl%C3%A9e\"></a>Crème brûlée</h1>\n");
+    /// ```
+    pub header_id_style: HeaderIdStyle,
+
+    /// IDs which the header IDs extension must not generate, such as those
+    /// already used by the page the output will be embedded into.  These are
+    /// compared with the full `id` attribute, including the prefix.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.header_ids = Some("user-content-".to_string());
+    /// options.extension.header_ids_reserved = vec!["user-content-readme".to_string()];
+    /// assert_eq!(markdown_to_html("# README\n", &options),
+    ///            "<h1><a href=\"#readme-1\" aria-hidden=\"true\" class=\"anchor\" id=\"user-content-readme-1\"></a>README</h1>\n");
+    /// ```
+    pub header_ids_reserved: Vec<String>,
+
     /// Enables the footnotes extension per `cmark-gfm`.
     ///
     /// For usage, see `src/tests.rs`.  The extension is modelled after
@@ -264,6 +905,34 @@ pub struct ExtensionOptions {
     /// ```
     pub footnotes: bool,
 
+    /// Where footnote definitions are placed in the document, when the
+    /// footnotes extension is enabled.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, FootnotePlacement, Options};
+    /// let mut options = Options::default();
+    /// options.extension.footnotes = true;
+    /// options.extension.footnote_placement = FootnotePlacement::Section;
+    /// assert_eq!(markdown_to_html("# A\nHi[^x].\n# B\n\n[^x]: A greeting.\n", &options),
+    ///            "<h1>A</h1>\n<p>Hi<sup class=\"footnote-ref\"><a href=\"#fn-x\" id=\"fnref-x\" data-footnote-ref>1</a></sup>.</p>\n<section class=\"footnotes\" data-footnotes>\n<ol>\n<li id=\"fn-x\">\n<p>A greeting. <a href=\"#fnref-x\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a></p>\n</li>\n</ol>\n</section>\n<h1>B</h1>\n");
+    /// ```
+    pub footnote_placement: FootnotePlacement,
+
+    /// Enables Pandoc-style inline footnotes, `^[like this]`, when the
+    /// footnotes extension is enabled.  Each is given a definition of its
+    /// own, named `inline-1`, `inline-2` and so on, and numbered and rendered
+    /// like any other footnote.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.footnotes = true;
+    /// options.extension.inline_footnotes = true;
+    /// assert_eq!(markdown_to_html("Hi^[A *greeting*.]\n", &options),
+    ///            "<p>Hi<sup class=\"footnote-ref\"><a href=\"#fn-inline-1\" id=\"fnref-inline-1\" data-footnote-ref>1</a></sup></p>\n<section class=\"footnotes\" data-footnotes>\n<ol>\n<li id=\"fn-inline-1\">\n<p>A <em>greeting</em>. <a href=\"#fnref-inline-1\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a></p>\n</li>\n</ol>\n</section>\n");
+    /// ```
+    pub inline_footnotes: bool,
+
     /// Enables the description lists extension.
     ///
     /// Each term must be defined in one paragraph, followed by a blank line,
@@ -505,72 +1174,441 @@ pub struct ExtensionOptions {
     ///             "</blockquote>\n"));
     /// ```
     pub greentext: bool,
-}
 
-#[non_exhaustive]
-#[derive(Default, Clone, Builder)]
-#[builder(default)]
-#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
-/// Options for parser functions.
-pub struct ParseOptions<'c> {
-    /// Punctuation (quotes, full-stops and hyphens) are converted into 'smart' punctuation.
+    /// Enables abbreviations, as in PHP Markdown Extra.
+    ///
+    /// Each definition, of the form `*[label]: title`, is kept in the AST and
+    /// applies to whole-word occurrences of its label throughout the document.
     ///
     /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// assert_eq!(markdown_to_html("'Hello,' \"world\" ...", &options),
-    ///            "<p>'Hello,' &quot;world&quot; ...</p>\n");
-    ///
-    /// options.parse.smart = true;
-    /// assert_eq!(markdown_to_html("'Hello,' \"world\" ...", &options),
-    ///            "<p>‘Hello,’ “world” …</p>\n");
+    /// options.extension.abbreviations = true;
+    /// assert_eq!(markdown_to_html("The HTML spec.\n\n*[HTML]: HyperText Markup Language\n", &options),
+    ///            "<p>The <abbr title=\"HyperText Markup Language\">HTML</abbr> spec.</p>\n");
     /// ```
-    pub smart: bool,
+    pub abbreviations: bool,
 
-    /// The default info string for fenced code blocks.
+    /// Enables template variable placeholders, `{{name}}` or
+    /// `{{ name | filter | ... }}`.
+    ///
+    /// Placeholders are parsed as single inline nodes, so their contents are
+    /// never interpreted as Markdown.  When rendering HTML, each is passed to
+    /// [`RenderPlugins::template_resolver`] if set; placeholders it doesn't
+    /// resolve are written as they appear in the source.
     ///
     /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// assert_eq!(markdown_to_html("```\nfn hello();\n```\n", &options),
-    ///            "<pre><code>fn hello();\n</code></pre>\n");
-    ///
-    /// options.parse.default_info_string = Some("rust".into());
-    /// assert_eq!(markdown_to_html("```\nfn hello();\n```\n", &options),
-    ///            "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
+    /// options.extension.template_variables = true;
+    /// assert_eq!(markdown_to_html("Hi {{ user_name | capitalize }}!", &options),
+    ///            "<p>Hi {{user_name | capitalize}}!</p>\n");
     /// ```
-    pub default_info_string: Option<String>,
+    pub template_variables: bool,
 
-    /// Whether or not a simple `x` or `X` is used for tasklist or any other symbol is allowed.
-    pub relaxed_tasklist_matching: bool,
+    /// Enables template tag protection for Liquid, Jinja and similar template
+    /// languages: `{% ... %}` and `{{ ... }}` on a single line are kept as
+    /// single inline nodes, so their contents are never interpreted as
+    /// Markdown nor escaped when formatting CommonMark.
+    ///
+    /// When rendering HTML, tags are written as they appear in the source
+    /// with [`render.unsafe_`](crate::RenderOptions::unsafe_), and escaped
+    /// otherwise.  Placeholders recognized by
+    /// [`template_variables`](Self::template_variables) take precedence.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_commonmark, markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.template_tags = true;
+    /// let input = "{% if page.*draft* %}_{{ site.title }}_{% endif %}\n";
+    /// assert_eq!(markdown_to_commonmark(input, &options), input);
+    ///
+    /// options.render.unsafe_ = true;
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p>{% if page.*draft* %}<em>{{ site.title }}</em>{% endif %}</p>\n");
+    /// ```
+    pub template_tags: bool,
 
-    /// Relax parsing of autolinks, allow links to be detected inside brackets
-    /// and allow all url schemes. It is intended to allow a very specific type of autolink
-    /// detection, such as `[this http://and.com that]` or `{http://foo.com}`, on a best can basis.
+    /// Enables conditional blocks, [container blocks](Self::container_blocks)
+    /// named `if`:
+    ///
+    /// ``` md
+    /// ::: if feature="enterprise"
+    /// Contact your account manager.
+    /// :::
+    /// ```
+    ///
+    /// The condition is a list of `key="value"` attributes (quotes optional)
+    /// or bare keys.  Blocks are rendered with their contents until resolved
+    /// with [`resolve_conditionals`](crate::conditionals::resolve_conditionals).
     ///
     /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// options.extension.autolink = true;
-    /// assert_eq!(markdown_to_html("[https://foo.com]", &options),
-    ///            "<p>[https://foo.com]</p>\n");
-    ///
-    /// options.parse.relaxed_autolinks = true;
-    /// assert_eq!(markdown_to_html("[https://foo.com]", &options),
-    ///            "<p>[<a href=\"https://foo.com\">https://foo.com</a>]</p>\n");
+    /// options.extension.conditional_blocks = true;
+    /// assert_eq!(markdown_to_html("::: if beta\nNew!\n:::\n", &options),
+    ///            "<p>New!</p>\n");
     /// ```
-    pub relaxed_autolinks: bool,
+    pub conditional_blocks: bool,
 
-    /// In case the parser encounters any potential links that have a broken
-    /// reference (e.g `[foo]` when there is no `[foo]: url` entry at the
-    /// bottom) the provided callback will be called with the reference name,
-    /// both in normalized form and unmodified, and the returned pair will be
-    /// used as the link destination and title if not [`None`].
+    /// Enables collapsible sections, [container blocks](Self::container_blocks)
+    /// named `details`, rendered as `<details>` elements:
     ///
+    /// ``` md
+    /// ::: details Show the answer
+    /// It is 42.
+    /// :::
     /// ```
-    /// # use std::{str, sync::{Arc, Mutex}};
-    /// # use comrak::{Arena, ResolvedReference, parse_document, format_html, Options, BrokenLinkReference, ParseOptionsBuilder};
-    /// # use comrak::nodes::{AstNode, NodeValue};
+    ///
+    /// The rest of the opening fence is the summary.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.details_blocks = true;
+    /// assert_eq!(markdown_to_html("::: details Answer\nIt is 42.\n:::\n", &options),
+    ///            "<details>\n<summary>Answer</summary>\n<p>It is 42.</p>\n</details>\n");
+    /// ```
+    pub details_blocks: bool,
+
+    /// Enables named containers, or fenced divs, fenced by lines of three or
+    /// more colons:
+    ///
+    /// ``` md
+    /// ::: warning {#careful}
+    /// Mind the gap.
+    /// :::
+    /// ```
+    ///
+    /// The opening fence has a name, an attribute block as with
+    /// [`block_attributes`](Self::block_attributes), or both.  The contents
+    /// are parsed as usual, and rendered in a `<div>` whose classes are the
+    /// name followed by any given classes; a [`ComrakExtension`](crate::extensions::ComrakExtension)
+    /// can render them differently.  A closing fence closes the innermost
+    /// open block with a fence no longer than it.
+    ///
+    /// The names `if` and `details` are left to
+    /// [`conditional_blocks`](Self::conditional_blocks) and
+    /// [`details_blocks`](Self::details_blocks) when they're enabled; all
+    /// three kinds are parsed as [`NodeValue::Container`](crate::nodes::NodeValue::Container).
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.container_blocks = true;
+    /// assert_eq!(markdown_to_html("::: warning\nMind the gap.\n:::\n", &options),
+    ///            "<div class=\"warning\">\n<p>Mind the gap.</p>\n</div>\n");
+    /// ```
+    pub container_blocks: bool,
+
+    /// Enables citations, `[@key]` or `[see @key, p. 4; @other]`.
+    ///
+    /// Citations are rendered as written until resolved against a
+    /// bibliography with
+    /// [`resolve_citations`](crate::bibliography::resolve_citations).
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.citations = true;
+    /// assert_eq!(markdown_to_html("As shown [@knuth84, p. 3].", &options),
+    ///            "<p>As shown <span class=\"citation\" data-cites=\"knuth84\">[@knuth84, p. 3]</span>.</p>\n");
+    /// ```
+    pub citations: bool,
+
+    /// Enables JSX-style components, as used by MDX: tags whose names start
+    /// with an uppercase letter, such as `<Widget prop="x">...</Widget>` or
+    /// `<Widget />`.
+    ///
+    /// A line starting with a component tag begins a block which runs to the
+    /// end of the line on which the component closes; elsewhere components
+    /// are inline.  Either way, a component and everything within it is kept
+    /// verbatim, and is rendered like raw HTML.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_commonmark, Options};
+    /// let mut options = Options::default();
+    /// options.extension.jsx_components = true;
+    /// assert_eq!(markdown_to_commonmark("<Tabs items={['a', 'b']}>\n\n*Not* Markdown.\n\n</Tabs>\n", &options),
+    ///            "<Tabs items={['a', 'b']}>\n\n*Not* Markdown.\n\n</Tabs>\n");
+    /// ```
+    pub jsx_components: bool,
+
+    /// Enables regions whose source is passed through verbatim, between lines
+    /// reading `<!-- comrak:off -->` and `<!-- comrak:on -->`.
+    ///
+    /// Nothing within a region is interpreted as Markdown or by any other
+    /// extension; it's kept as a single raw block, which is rendered like raw
+    /// HTML.  A region without a closing marker runs to the end of its
+    /// container.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.raw_regions = true;
+    /// options.render.unsafe_ = true;
+    /// assert_eq!(markdown_to_html("<!-- comrak:off -->\n<td>*a*</td>\n\n<td>:-)</td>\n<!-- comrak:on -->\n*b*\n", &options),
+    ///            "<td>*a*</td>\n\n<td>:-)</td>\n<p><em>b</em></p>\n");
+    /// ```
+    pub raw_regions: bool,
+
+    /// Enables GitHub-style alerts: block quotes whose first line is a marker
+    /// such as `[!NOTE]`, `[!TIP]`, `[!IMPORTANT]`, `[!WARNING]` or
+    /// `[!CAUTION]`, optionally followed by a title.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.alerts = true;
+    /// assert_eq!(markdown_to_html("> [!NOTE]\n> Read this.\n", &options),
+    ///            "<div class=\"markdown-alert markdown-alert-note\">\n\
+    ///             <p class=\"markdown-alert-title\">Note</p>\n<p>Read this.</p>\n</div>\n");
+    /// ```
+    pub alerts: bool,
+
+    /// Enables attribute blocks, `{#id .class key=value}`, at the end of
+    /// headings and of the info strings of fenced code blocks.  They are
+    /// parsed into [`NodeHeading::attributes`] and
+    /// [`NodeCodeBlock::attributes`], and give the `id` and `class` of the
+    /// heading or `<pre>` element in HTML.  Other attributes are only
+    /// written with [`RenderOptions::unsafe_`].
+    ///
+    /// With [`header_ids`](Self::header_ids), a heading's `#id` is used for
+    /// its anchor in place of one generated from its text.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.block_attributes = true;
+    /// assert_eq!(markdown_to_html("# Setup {#install .big}\n", &options),
+    ///            "<h1 id=\"install\" class=\"big\">Setup</h1>\n");
+    /// assert_eq!(markdown_to_html("``` rust {#main}\nfn main() {}\n```\n", &options),
+    ///            "<pre id=\"main\"><code class=\"language-rust\">fn main() {}\n</code></pre>\n");
+    /// ```
+    pub block_attributes: bool,
+
+    /// Enables @mentions, `@username`, linked to the URL given by this
+    /// template with `{user}` replaced by the username.
+    ///
+    /// As on GitHub, a username is made of ASCII letters, digits and
+    /// hyphens, starts with a letter or digit, and is at most 39 characters
+    /// long.  The `@` must not follow a letter, digit or any of `` _-./@` ``,
+    /// so email addresses aren't mentions; nor are mentions found in code
+    /// spans or link text.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.mentions = Some("https://github.com/{user}".to_string());
+    /// assert_eq!(markdown_to_html("Thanks, @octocat! Not `@code` or me@example.com.", &options),
+    ///            "<p>Thanks, <a href=\"https://github.com/octocat\">@octocat</a>! \
+    ///             Not <code>@code</code> or me@example.com.</p>\n");
+    /// ```
+    pub mentions: Option<String>,
+
+    /// Enables sizes on images, `![alt](url =640x480)`, between the URL and
+    /// any title.  Either dimension may be omitted, as in `=640x` or `=x480`.
+    /// They're parsed into [`NodeLink::width`](crate::nodes::NodeLink::width)
+    /// and [`height`](crate::nodes::NodeLink::height), and written as the
+    /// `width` and `height` of the `<img>` in HTML.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.image_dimensions = true;
+    /// assert_eq!(markdown_to_html("![Logo](logo.png =640x480 \"Ours\")\n", &options),
+    ///            "<p><img src=\"logo.png\" alt=\"Logo\" title=\"Ours\" width=\"640\" height=\"480\" /></p>\n");
+    /// ```
+    pub image_dimensions: bool,
+}
+
+#[non_exhaustive]
+#[derive(Default, Clone, Builder)]
+#[builder(default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(default))]
+/// Options for parser functions.
+pub struct ParseOptions<'c> {
+    /// Punctuation (quotes, full-stops and hyphens) are converted into 'smart' punctuation.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("'Hello,' \"world\" ...", &options),
+    ///            "<p>'Hello,' &quot;world&quot; ...</p>\n");
+    ///
+    /// options.parse.smart = true;
+    /// assert_eq!(markdown_to_html("'Hello,' \"world\" ...", &options),
+    ///            "<p>‘Hello,’ “world” …</p>\n");
+    /// ```
+    pub smart: bool,
+
+    /// The quotation marks and apostrophe `smart` punctuation converts quotes
+    /// into.  See [`SmartQuotes`].
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options, SmartQuotes};
+    /// let mut options = Options::default();
+    /// options.parse.smart = true;
+    /// options.parse.smart_quotes = SmartQuotes::german();
+    /// assert_eq!(markdown_to_html("\"Hallo,\" sagt's 'leise'.", &options),
+    ///            "<p>„Hallo,“ sagt’s ‚leise‘.</p>\n");
+    ///
+    /// options.parse.smart_quotes = SmartQuotes::french();
+    /// assert_eq!(markdown_to_html("\"Bonjour\" l'ami.", &options),
+    ///            "<p>«Bonjour» l’ami.</p>\n");
+    /// ```
+    pub smart_quotes: SmartQuotes,
+
+    /// [Entity and numeric character
+    /// references](https://github.github.com/gfm/#entity-and-numeric-character-references)
+    /// in text are kept as [`Entity`](crate::nodes::NodeValue::Entity)
+    /// nodes holding both the reference as written and the characters it
+    /// stands for, rather than being resolved into `Text`.  HTML output is
+    /// the same, but the CommonMark formatter writes references as they were
+    /// written.  References in link destinations, titles and info strings
+    /// are always resolved.
+    ///
+    /// ```
+    /// # use comrak::{format_commonmark, markdown_to_html, parse_document, Arena, Options};
+    /// # use comrak::nodes::NodeValue;
+    /// let mut options = Options::default();
+    /// options.parse.preserve_entities = true;
+    /// assert_eq!(markdown_to_html("&copy; &#169;\n", &options),
+    ///            "<p>© ©</p>\n");
+    ///
+    /// let arena = Arena::new();
+    /// let root = parse_document(&arena, "&copy; &#169;\n", &options);
+    /// let entity = root.first_child().unwrap().first_child().unwrap();
+    /// assert!(matches!(entity.data.borrow().value, NodeValue::Entity(ref ne) if ne.literal == "&copy;"));
+    ///
+    /// let mut md = vec![];
+    /// format_commonmark(root, &options, &mut md).unwrap();
+    /// assert_eq!(String::from_utf8(md).unwrap(), "&copy; &#169;\n");
+    /// ```
+    pub preserve_entities: bool,
+
+    /// The default info string for fenced code blocks.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("```\nfn hello();\n```\n", &options),
+    ///            "<pre><code>fn hello();\n</code></pre>\n");
+    ///
+    /// options.parse.default_info_string = Some("rust".into());
+    /// assert_eq!(markdown_to_html("```\nfn hello();\n```\n", &options),
+    ///            "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
+    /// ```
+    pub default_info_string: Option<String>,
+
+    /// Whether or not a simple `x` or `X` is used for tasklist or any other symbol is allowed.
+    pub relaxed_tasklist_matching: bool,
+
+    /// Relax parsing of autolinks, allow links to be detected inside brackets
+    /// and allow all url schemes. It is intended to allow a very specific type of autolink
+    /// detection, such as `[this http://and.com that]` or `{http://foo.com}`, on a best can basis.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.autolink = true;
+    /// assert_eq!(markdown_to_html("[https://foo.com]", &options),
+    ///            "<p>[https://foo.com]</p>\n");
+    ///
+    /// options.parse.relaxed_autolinks = true;
+    /// assert_eq!(markdown_to_html("[https://foo.com]", &options),
+    ///            "<p>[<a href=\"https://foo.com\">https://foo.com</a>]</p>\n");
+    /// ```
+    pub relaxed_autolinks: bool,
+
+    /// Replace invalid UTF-8 in input passed to [`parse_document_bytes`] with
+    /// U+FFFD REPLACEMENT CHARACTER, rather than returning an error.
+    ///
+    /// ```
+    /// # use comrak::{Arena, parse_document_bytes, Options};
+    /// let arena = Arena::new();
+    /// let mut options = Options::default();
+    /// assert!(parse_document_bytes(&arena, b"caf\xe9", &options).is_err());
+    ///
+    /// options.parse.lossy_utf8 = true;
+    /// assert!(parse_document_bytes(&arena, b"caf\xe9", &options).is_ok());
+    /// ```
+    pub lossy_utf8: bool,
+
+    /// The column width of tab stops used when expanding tabs in block
+    /// structure, such as list item and code block indentation.  Zero, the
+    /// default, means the CommonMark tab stop of 4.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("- a\n\n\tb\n", &options),
+    ///            "<ul>\n<li>\n<p>a</p>\n<p>b</p>\n</li>\n</ul>\n");
+    ///
+    /// options.parse.tab_width = 8;
+    /// assert_eq!(markdown_to_html("- a\n\n\tb\n", &options),
+    ///            "<ul>\n<li>\n<p>a</p>\n<pre><code>  b\n</code></pre>\n</li>\n</ul>\n");
+    /// ```
+    pub tab_width: usize,
+
+    /// The deepest block containers, such as block quotes and list items,
+    /// may be nested, and likewise inlines, such as emphasis and links,
+    /// within a block.  Markers which would open containers any deeper are
+    /// treated as text, and inlines any deeper are replaced by their
+    /// contents.  Zero, the default, means a limit of 256.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.parse.max_nesting = 2;
+    /// assert_eq!(markdown_to_html("> > > a\n", &options),
+    ///            "<blockquote>\n<blockquote>\n<p>&gt; a</p>\n</blockquote>\n</blockquote>\n");
+    /// assert_eq!(markdown_to_html("*a [b *c*](u)*\n", &options),
+    ///            "<p><em>a <a href=\"u\">b c</a></em></p>\n");
+    /// ```
+    pub max_nesting: usize,
+
+    /// The most bytes of input to parse.  Longer inputs are handled as given
+    /// by [`oversized_input`](ParseOptions::oversized_input), and the
+    /// document is marked by a [`NodeValue::Truncated`] node as its last
+    /// child.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.parse.max_input_size = Some(16);
+    /// assert_eq!(markdown_to_html("First\n\nSecond paragraph.\n", &options),
+    ///            "<p>First</p>\n");
+    /// ```
+    pub max_input_size: Option<usize>,
+
+    /// How inputs longer than [`max_input_size`](ParseOptions::max_input_size)
+    /// are handled.
+    ///
+    /// ```
+    /// # use comrak::{try_parse_document, Arena, InputTooLarge, Options, OversizedInput};
+    /// let arena = Arena::new();
+    /// let mut options = Options::default();
+    /// options.parse.max_input_size = Some(16);
+    /// options.parse.oversized_input = OversizedInput::Reject;
+    /// assert_eq!(
+    ///     try_parse_document(&arena, "First\n\nSecond paragraph.\n", &options).err(),
+    ///     Some(InputTooLarge { size: 25, limit: 16 })
+    /// );
+    /// ```
+    pub oversized_input: OversizedInput,
+
+    /// In case the parser encounters any potential links that have a broken
+    /// reference (e.g `[foo]` when there is no `[foo]: url` entry at the
+    /// bottom) the provided callback will be called with the reference name,
+    /// both in normalized form and unmodified, and the returned pair will be
+    /// used as the link destination and title if not [`None`].
+    ///
+    /// ```
+    /// # use std::{str, sync::{Arc, Mutex}};
+    /// # use comrak::{Arena, ResolvedReference, parse_document, format_html, Options, BrokenLinkReference, ParseOptionsBuilder};
+    /// # use comrak::nodes::{AstNode, NodeValue};
     /// #
     /// # fn main() -> std::io::Result<()> {
     /// let arena = Arena::new();
@@ -604,28 +1642,67 @@ pub struct ParseOptions<'c> {
     /// # Ok(())
     /// # }
     #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub broken_link_callback: Option<Arc<Mutex<BrokenLinkCallback<'c>>>>,
+
+    /// Called with each [`Diagnostic`] found while parsing: fenced code
+    /// blocks left unclosed, link reference definitions whose label is
+    /// already defined, references to undefined footnotes when
+    /// [`footnotes`](ExtensionOptions::footnotes) is enabled, and table rows
+    /// whose cell count differs from the header row.
+    ///
+    /// ```
+    /// # use std::sync::{Arc, Mutex};
+    /// # use comrak::{Arena, Diagnostic, parse_document, Options};
+    /// let arena = Arena::new();
+    /// let mut found = vec![];
+    /// let mut cb = |d: Diagnostic| found.push(d);
+    /// let mut options = Options::default();
+    /// options.extension.table = true;
+    /// options.parse.diagnostic_callback = Some(Arc::new(Mutex::new(&mut cb)));
+    /// parse_document(&arena, "| a | b |\n|---|---|\n| c |\n", &options);
+    /// drop(options);
+    ///
+    /// assert_eq!(found.len(), 1);
+    /// assert_eq!(found[0].sourcepos, (3, 1, 3, 5).into());
+    /// assert_eq!(found[0].message, "table row has 1 cell, expected 2");
+    /// ```
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub diagnostic_callback: Option<Arc<Mutex<DiagnosticCallback<'c>>>>,
 }
 
 impl<'c> fmt::Debug for ParseOptions<'c> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         let mut struct_fmt = f.debug_struct("ParseOptions");
         struct_fmt.field("smart", &self.smart);
+        struct_fmt.field("smart_quotes", &self.smart_quotes);
+        struct_fmt.field("preserve_entities", &self.preserve_entities);
         struct_fmt.field("default_info_string", &self.default_info_string);
         struct_fmt.field("relaxed_tasklist_matching", &self.relaxed_tasklist_matching);
         struct_fmt.field("relaxed_autolinks", &self.relaxed_autolinks);
+        struct_fmt.field("lossy_utf8", &self.lossy_utf8);
+        struct_fmt.field("tab_width", &self.tab_width);
+        struct_fmt.field("max_nesting", &self.max_nesting);
+        struct_fmt.field("max_input_size", &self.max_input_size);
+        struct_fmt.field("oversized_input", &self.oversized_input);
         struct_fmt.field(
             "broken_link_callback.is_some()",
             &self.broken_link_callback.is_some(),
         );
+        struct_fmt.field(
+            "diagnostic_callback.is_some()",
+            &self.diagnostic_callback.is_some(),
+        );
         struct_fmt.finish()
     }
 }
 
 #[non_exhaustive]
-#[derive(Default, Debug, Clone, Copy, Builder)]
+#[derive(Default, Debug, Clone, Builder)]
 #[builder(default)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(default))]
 /// Options for formatter functions.
 pub struct RenderOptions {
     /// [Soft line breaks](http://spec.commonmark.org/0.27/#soft-line-breaks) in the input
@@ -643,6 +1720,40 @@ pub struct RenderOptions {
     /// ```
     pub hardbreaks: bool,
 
+    /// How [soft line breaks](http://spec.commonmark.org/0.27/#soft-line-breaks)
+    /// are rendered in HTML.  Ignored when `hardbreaks` is set.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options, SoftBreakStyle};
+    /// let mut options = Options::default();
+    /// options.render.soft_breaks = SoftBreakStyle::Space;
+    /// assert_eq!(markdown_to_html("Hello.\nWorld.\n", &options),
+    ///            "<p>Hello. World.</p>\n");
+    ///
+    /// options.render.soft_breaks = SoftBreakStyle::LineBreak;
+    /// assert_eq!(markdown_to_html("Hello.\nWorld.\n", &options),
+    ///            "<p>Hello.<br />World.</p>\n");
+    /// ```
+    pub soft_breaks: SoftBreakStyle,
+
+    /// Soft line breaks between two Chinese or Japanese characters are
+    /// omitted from HTML when rendered as newlines or spaces, since those
+    /// languages don't separate words with spaces.  The CommonMark formatter
+    /// keeps such breaks when wrapping, rather than joining the lines with a
+    /// space.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("日本語の\n文章です。\nThen\nEnglish.\n", &options),
+    ///            "<p>日本語の\n文章です。\nThen\nEnglish.</p>\n");
+    ///
+    /// options.render.cjk_soft_breaks = true;
+    /// assert_eq!(markdown_to_html("日本語の\n文章です。\nThen\nEnglish.\n", &options),
+    ///            "<p>日本語の文章です。\nThen\nEnglish.</p>\n");
+    /// ```
+    pub cjk_soft_breaks: bool,
+
     /// GitHub-style `<pre lang="xyz">` is used for fenced code blocks with info tags.
     ///
     /// ```
@@ -657,7 +1768,29 @@ pub struct RenderOptions {
     /// ```
     pub github_pre_lang: bool,
 
-    /// Enable full info strings for code blocks
+    /// The prefix of the class given to the `<code>` element of a fenced code
+    /// block with an info string, when not using
+    /// [`github_pre_lang`](Self::github_pre_lang).  `language-` if unset.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.code_class_prefix = Some("lang-".to_string());
+    /// assert_eq!(markdown_to_html("``` rust\nfn hello();\n```\n", &options),
+    ///            "<pre><code class=\"lang-rust\">fn hello();\n</code></pre>\n");
+    ///
+    /// options.render.code_class_prefix = Some(String::new());
+    /// assert_eq!(markdown_to_html("``` rust\nfn hello();\n```\n", &options),
+    ///            "<pre><code class=\"rust\">fn hello();\n</code></pre>\n");
+    /// ```
+    pub code_class_prefix: Option<String>,
+
+    /// Enable full info strings for code blocks.  The rest of a fenced code
+    /// block's info string after the language is written to a `data-meta`
+    /// attribute, on the `<code>` element, or on the `<pre>` element with
+    /// [`github_pre_lang`](Self::github_pre_lang), and is passed to a
+    /// [`SyntaxHighlighterAdapter`](crate::adapters::SyntaxHighlighterAdapter)
+    /// with the other attributes of that element.
     ///
     /// ```
     /// # use comrak::{markdown_to_html, Options};
@@ -717,8 +1850,26 @@ pub struct RenderOptions {
     ///             <p><a href=\"javascript:alert(document.cookie)\">Dangerous</a>.</p>\n\
     ///             <p><a href=\"http://commonmark.org\">Safe</a>.</p>\n");
     /// ```
+    #[cfg_attr(feature = "serde", serde(rename = "unsafe"))]
     pub unsafe_: bool,
 
+    /// The URL schemes links and images may use in HTML, compared without
+    /// regard to case.  A link or image to a URL with any other scheme is
+    /// rendered with an empty destination, as dangerous URLs are.  Relative
+    /// URLs, which have no scheme, are always allowed.  If unset, only
+    /// dangerous URLs are removed.  Applies even with `unsafe_`.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.url_schemes = Some(vec!["https".into(), "mailto".into()]);
+    /// let input = "[a](HTTPS://a.example) [b](ftp://b.example) [c](/c) ![d](data:image/png;base64,)\n";
+    ///
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p><a href=\"HTTPS://a.example\">a</a> <a href=\"\">b</a> <a href=\"/c\">c</a> <img src=\"\" alt=\"d\" /></p>\n");
+    /// ```
+    pub url_schemes: Option<Vec<String>>,
+
     /// Escape raw HTML instead of clobbering it.
     /// ```
     /// # use comrak::{markdown_to_html, Options};
@@ -734,11 +1885,91 @@ pub struct RenderOptions {
     /// ```
     pub escape: bool,
 
+    /// How HTML comments, whether blocks or inline, are rendered in HTML,
+    /// apart from other raw HTML.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, HtmlCommentStyle, Options};
+    /// let mut options = Options::default();
+    /// let input = "<!-- toc -->\n\nIntro <!-- more --> <i>rest</i>\n";
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<!-- raw HTML omitted -->\n\
+    ///             <p>Intro <!-- raw HTML omitted --> <!-- raw HTML omitted -->rest<!-- raw HTML omitted --></p>\n");
+    ///
+    /// options.render.html_comments = HtmlCommentStyle::Keep;
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<!-- toc -->\n\
+    ///             <p>Intro <!-- more --> <!-- raw HTML omitted -->rest<!-- raw HTML omitted --></p>\n");
+    ///
+    /// options.render.html_comments = HtmlCommentStyle::Strip;
+    /// options.render.unsafe_ = true;
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p>Intro  <i>rest</i></p>\n");
+    /// ```
+    pub html_comments: HtmlCommentStyle,
+
+    /// How non-ASCII characters in text and attribute values are written to
+    /// HTML. Raw HTML and syntax highlighter output are passed through as-is;
+    /// URLs are always percent-encoded.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, NonAsciiStyle, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("café — ☃\n", &options),
+    ///            "<p>café — ☃</p>\n");
+    ///
+    /// options.render.non_ascii = NonAsciiStyle::Numeric;
+    /// assert_eq!(markdown_to_html("café — ☃\n", &options),
+    ///            "<p>caf&#xE9; &#x2014; &#x2603;</p>\n");
+    ///
+    /// options.render.non_ascii = NonAsciiStyle::Named;
+    /// assert_eq!(markdown_to_html("café — ☃\n", &options),
+    ///            "<p>caf&eacute; &mdash; &#x2603;</p>\n");
+    /// ```
+    pub non_ascii: NonAsciiStyle,
+
+    /// Convert internationalized domain names in link and image destinations
+    /// to [Punycode](https://www.rfc-editor.org/rfc/rfc3492), so that hrefs
+    /// are valid ASCII URLs. Non-ASCII characters elsewhere in a URL are
+    /// percent-encoded regardless of this option.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.autolink = true;
+    /// assert_eq!(markdown_to_html("https://bücher.example/ß\n", &options),
+    ///            "<p><a href=\"https://b%C3%BCcher.example/%C3%9F\">https://bücher.example/ß</a></p>\n");
+    ///
+    /// options.render.punycode_hosts = true;
+    /// assert_eq!(markdown_to_html("https://bücher.example/ß\n", &options),
+    ///            "<p><a href=\"https://xn--bcher-kva.example/%C3%9F\">https://bücher.example/ß</a></p>\n");
+    /// ```
+    pub punycode_hosts: bool,
+
+    /// How link and image titles are rendered in HTML.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, LinkTitleStyle, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("[a](/b \"c\")\n", &options),
+    ///            "<p><a href=\"/b\" title=\"c\">a</a></p>\n");
+    ///
+    /// options.render.link_titles = LinkTitleStyle::AriaLabel;
+    /// assert_eq!(markdown_to_html("[a](/b \"c\")\n", &options),
+    ///            "<p><a href=\"/b\" aria-label=\"c\">a</a></p>\n");
+    ///
+    /// options.render.link_titles = LinkTitleStyle::Omit;
+    /// assert_eq!(markdown_to_html("[a](/b \"c\")\n", &options),
+    ///            "<p><a href=\"/b\">a</a></p>\n");
+    /// ```
+    pub link_titles: LinkTitleStyle,
+
     /// Set the type of [bullet list marker](https://spec.commonmark.org/0.30/#bullet-list-marker) to use. Options are:
     ///
     /// * [`ListStyleType::Dash`] to use `-` (default)
     /// * [`ListStyleType::Plus`] to use `+`
     /// * [`ListStyleType::Star`] to use `*`
+    /// * [`ListStyleType::Preserve`] to use each list's own bullet
     ///
     /// ```rust
     /// # use comrak::{markdown_to_commonmark, Options, ListStyleType};
@@ -754,9 +1985,88 @@ pub struct RenderOptions {
     /// options.render.list_style = ListStyleType::Star;
     /// assert_eq!(markdown_to_commonmark(input, &options),
     ///            "* one\n* two\n* three\n");
+    ///
+    /// options.render.list_style = ListStyleType::Preserve;
+    /// assert_eq!(markdown_to_commonmark("+ one\n+ two\n", &options),
+    ///            "+ one\n+ two\n");
     /// ```
     pub list_style: ListStyleType,
 
+    /// Set the delimiter written after the numbers of ordered lists when
+    /// outputting CommonMark.  Options are:
+    ///
+    /// * [`OrderedListStyleType::Preserve`] to use each list's own delimiter (default)
+    /// * [`OrderedListStyleType::Period`] to use `.`
+    /// * [`OrderedListStyleType::Paren`] to use `)`
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_commonmark, Options, OrderedListStyleType};
+    /// let mut options = Options::default();
+    /// let input = "1) one\n2) two\n";
+    /// assert_eq!(markdown_to_commonmark(input, &options),
+    ///            "1)  one\n2)  two\n");
+    ///
+    /// options.render.ordered_list_style = OrderedListStyleType::Period;
+    /// assert_eq!(markdown_to_commonmark(input, &options),
+    ///            "1.  one\n2.  two\n");
+    /// ```
+    pub ordered_list_style: OrderedListStyleType,
+
+    /// Set the style of level 1 and 2 headings when outputting CommonMark.
+    /// Headings of other levels, and empty headings, are always ATX
+    /// headings.  Options are:
+    ///
+    /// * [`HeadingStyleType::Atx`] to use `#` markers (default)
+    /// * [`HeadingStyleType::Setext`] to underline with `=` or `-`
+    /// * [`HeadingStyleType::Preserve`] to use each heading's own style
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_commonmark, Options, HeadingStyleType};
+    /// let mut options = Options::default();
+    /// let input = "# Title\n\n## Section\n\n### Sub\n";
+    /// assert_eq!(markdown_to_commonmark(input, &options), input);
+    ///
+    /// options.render.heading_style = HeadingStyleType::Setext;
+    /// assert_eq!(markdown_to_commonmark(input, &options),
+    ///            "Title\n=====\n\nSection\n-------\n\n### Sub\n");
+    /// ```
+    pub heading_style: HeadingStyleType,
+
+    /// Set the character of code fences when outputting CommonMark.
+    /// Options are:
+    ///
+    /// * [`CodeFenceStyleType::Backtick`] to use `` ` `` (default)
+    /// * [`CodeFenceStyleType::Tilde`] to use `~`
+    /// * [`CodeFenceStyleType::Preserve`] to use each fenced code block's own
+    ///   fence, and its length
+    ///
+    /// Backticks are never used for code blocks whose info string contains
+    /// one.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_commonmark, Options, CodeFenceStyleType};
+    /// let mut options = Options::default();
+    /// options.render.code_fence_style = CodeFenceStyleType::Tilde;
+    /// assert_eq!(markdown_to_commonmark("```rust\nfn main() {}\n```\n", &options),
+    ///            "~~~ rust\nfn main() {}\n~~~\n");
+    /// ```
+    pub code_fence_style: CodeFenceStyleType,
+
+    /// The minimum length of code fences when outputting CommonMark.  Fences
+    /// are at least 3 characters long, and always longer than any run of
+    /// the fence character in the code.  With
+    /// [`CodeFenceStyleType::Preserve`], fenced code blocks keep the length of
+    /// fence they had in the input.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_commonmark, Options};
+    /// let mut options = Options::default();
+    /// options.render.code_fence_length = 4;
+    /// assert_eq!(markdown_to_commonmark("```\na\n```\n", &options),
+    ///            "````\na\n````\n");
+    /// ```
+    pub code_fence_length: usize,
+
     /// Include source position attributes in HTML and XML output.
     ///
     /// Sourcepos information is reliable for all core block items, and most
@@ -856,42 +2166,153 @@ pub struct RenderOptions {
     /// ```
     pub gfm_quirks: bool,
 
-    /// Prefer fenced code blocks when outputting CommonMark.
+    /// Prefer fenced code blocks when outputting CommonMark.
+    ///
+    /// ```rust
+    /// # use std::str;
+    /// # use comrak::{Arena, Options, format_commonmark, parse_document};
+    /// let arena = Arena::new();
+    /// let mut options = Options::default();
+    /// let input = "```\nhello\n```\n";
+    /// let root = parse_document(&arena, input, &options);
+    ///
+    /// let mut buf = Vec::new();
+    /// format_commonmark(&root, &options, &mut buf);
+    /// assert_eq!(str::from_utf8(&buf).unwrap(), "    hello\n");
+    ///
+    /// buf.clear();
+    /// options.render.prefer_fenced = true;
+    /// format_commonmark(&root, &options, &mut buf);
+    /// assert_eq!(str::from_utf8(&buf).unwrap(), "```\nhello\n```\n");
+    /// ```
+    pub prefer_fenced: bool,
+
+    /// Render the image as a figure element with the title as its caption.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// let input = "![image](https://example.com/image.png \"this is an image\")";
+    ///
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p><img src=\"https://example.com/image.png\" alt=\"image\" title=\"this is an image\" /></p>\n");
+    ///
+    /// options.render.figure_with_caption = true;
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p><figure><img src=\"https://example.com/image.png\" alt=\"image\" title=\"this is an image\" /><figcaption>this is an image</figcaption></figure></p>\n");
+    /// ```
+    pub figure_with_caption: bool,
+
+    /// Follow the text of links with their URL in parentheses in plain text
+    /// output.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_text, Options};
+    /// let mut options = Options::default();
+    /// let input = "See [the docs](https://docs.rs) or <https://crates.io>.\n";
+    /// assert_eq!(markdown_to_text(input, &options), "See the docs or https://crates.io.\n");
+    ///
+    /// options.render.text_link_urls = true;
+    /// assert_eq!(markdown_to_text(input, &options),
+    ///            "See the docs (https://docs.rs) or https://crates.io.\n");
+    /// ```
+    pub text_link_urls: bool,
+
+    /// Add `loading="lazy"` and `decoding="async"` to all images in HTML, so
+    /// browsers defer loading them until they're about to be scrolled into
+    /// view.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.lazy_images = true;
+    /// assert_eq!(markdown_to_html("![Logo](logo.png)\n", &options),
+    ///            "<p><img src=\"logo.png\" alt=\"Logo\" loading=\"lazy\" decoding=\"async\" /></p>\n");
+    /// ```
+    pub lazy_images: bool,
+
+    /// The URL of the site the HTML is for.  Links to an absolute URL with a
+    /// different host are external, and are marked as set by
+    /// `external_link_rel` and `external_link_target`.  If unset, links to
+    /// every absolute URL are external.  Relative links are never external.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.external_link_base = Some("https://example.com/".into());
+    /// options.render.external_link_rel = true;
+    /// assert_eq!(markdown_to_html("[a](https://Example.com/a) [b](/b)\n", &options),
+    ///            "<p><a href=\"https://Example.com/a\">a</a> <a href=\"/b\">b</a></p>\n");
+    /// ```
+    pub external_link_base: Option<String>,
+
+    /// Add `rel="nofollow noopener"` to external links in HTML.  See
+    /// `external_link_base`.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.external_link_rel = true;
+    /// assert_eq!(markdown_to_html("[a](https://example.com)\n", &options),
+    ///            "<p><a href=\"https://example.com\" rel=\"nofollow noopener\">a</a></p>\n");
+    /// ```
+    pub external_link_rel: bool,
+
+    /// Add `target="_blank"` to external links in HTML, so they open in a
+    /// new tab.  See `external_link_base`.
     ///
     /// ```rust
-    /// # use std::str;
-    /// # use comrak::{Arena, Options, format_commonmark, parse_document};
-    /// let arena = Arena::new();
+    /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// let input = "```\nhello\n```\n";
-    /// let root = parse_document(&arena, input, &options);
-    ///
-    /// let mut buf = Vec::new();
-    /// format_commonmark(&root, &options, &mut buf);
-    /// assert_eq!(str::from_utf8(&buf).unwrap(), "    hello\n");
-    ///
-    /// buf.clear();
-    /// options.render.prefer_fenced = true;
-    /// format_commonmark(&root, &options, &mut buf);
-    /// assert_eq!(str::from_utf8(&buf).unwrap(), "```\nhello\n```\n");
+    /// options.render.external_link_target = true;
+    /// assert_eq!(markdown_to_html("[a](https://example.com)\n", &options),
+    ///            "<p><a href=\"https://example.com\" target=\"_blank\">a</a></p>\n");
     /// ```
-    pub prefer_fenced: bool,
+    pub external_link_target: bool,
 
-    /// Render the image as a figure element with the title as its caption.
+    /// Extra CSS classes to add to the HTML elements of nodes of each kind,
+    /// keyed by the kind's name as in the XML output, e.g. `"table"` or
+    /// `"block_quote"`.  Classes are added alongside any the element already
+    /// has.
     ///
     /// ```rust
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// let input = "![image](https://example.com/image.png \"this is an image\")";
+    /// options.render.node_classes.insert("block_quote".into(), "quote".into());
+    /// options.render.node_classes.insert("code".into(), "font-mono".into());
+    /// assert_eq!(markdown_to_html("> Hi `there`.\n", &options),
+    ///            "<blockquote class=\"quote\">\n<p>Hi <code class=\"font-mono\">there</code>.</p>\n</blockquote>\n");
+    /// ```
+    pub node_classes: HashMap<String, String>,
+
+    /// The text the renderer generates itself, such as accessible labels, so
+    /// it can be translated.  See [`RenderStrings`].
     ///
-    /// assert_eq!(markdown_to_html(input, &options),
-    ///            "<p><img src=\"https://example.com/image.png\" alt=\"image\" title=\"this is an image\" /></p>\n");
+    /// ```rust
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.footnotes = true;
+    /// options.render.strings.footnote_backref = "Retour à la référence".into();
+    /// assert!(markdown_to_html("Hi[^x]\n\n[^x]: A greeting.\n", &options)
+    ///     .contains("aria-label=\"Retour à la référence 1\""));
+    /// ```
+    pub strings: RenderStrings,
+
+    /// The markup of footnotes, when the footnotes extension is enabled.  See
+    /// [`FootnoteStyle`].
     ///
-    /// options.render.figure_with_caption = true;
-    /// assert_eq!(markdown_to_html(input, &options),
-    ///            "<p><figure><img src=\"https://example.com/image.png\" alt=\"image\" title=\"this is an image\" /><figcaption>this is an image</figcaption></figure></p>\n");
+    /// ```rust
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.footnotes = true;
+    /// options.render.footnote_style.section_element = "div".into();
+    /// options.render.footnote_style.section_class = "notes".into();
+    /// options.render.footnote_style.backrefs = false;
+    /// options.render.footnote_style.id_prefix = "post-1-".into();
+    /// assert_eq!(markdown_to_html("Hi[^x]\n\n[^x]: A greeting.\n", &options),
+    ///            "<p>Hi<sup class=\"footnote-ref\"><a href=\"#post-1-fn-x\" id=\"post-1-fnref-x\" data-footnote-ref>1</a></sup></p>\n<div class=\"notes\" data-footnotes>\n<ol>\n<li id=\"post-1-fn-x\">\n<p>A greeting.</p>\n</li>\n</ol>\n</div>\n");
     /// ```
-    pub figure_with_caption: bool,
+    pub footnote_style: FootnoteStyle,
 }
 
 #[non_exhaustive]
@@ -945,8 +2366,168 @@ pub struct RenderPlugins<'p> {
     /// ```
     pub codefence_syntax_highlighter: Option<&'p dyn SyntaxHighlighterAdapter>,
 
+    /// Optional cache for the output of `codefence_syntax_highlighter`. Consulted before the
+    /// highlighter is invoked for each fenced code block; has no effect without a highlighter.
+    /// See [`CodeBlockCache`] for invalidation guidance, and
+    /// [`InMemoryCodeBlockCache`](crate::plugins::cache::InMemoryCodeBlockCache) for a simple
+    /// implementation.
+    pub codefence_highlight_cache: Option<&'p dyn CodeBlockCache>,
+
     /// Optional heading adapter
     pub heading_adapter: Option<&'p dyn HeadingAdapter>,
+
+    /// Optional adapter to transform link and image titles before they are rendered.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html_with_plugins, Options, Plugins};
+    /// # use comrak::adapters::LinkTitleAdapter;
+    /// struct Upcase;
+    /// impl LinkTitleAdapter for Upcase {
+    ///     fn transform(&self, title: &str, _url: &str) -> String {
+    ///         title.trim().to_uppercase()
+    ///     }
+    /// }
+    ///
+    /// let options = Options::default();
+    /// let mut plugins = Plugins::default();
+    /// plugins.render.link_title_adapter = Some(&Upcase);
+    /// assert_eq!(markdown_to_html_with_plugins("[a](/b \" hi \")\n", &options, &plugins),
+    ///            "<p><a href=\"/b\" title=\"HI\">a</a></p>\n");
+    /// ```
+    pub link_title_adapter: Option<&'p dyn LinkTitleAdapter>,
+
+    /// Optional rewriter of link and wikilink destinations.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html_with_plugins, Options, Plugins};
+    /// let absolute = |url: &str| match url.strip_prefix('/') {
+    ///     Some(path) => format!("https://example.com/{}", path),
+    ///     None => url.to_string(),
+    /// };
+    ///
+    /// let options = Options::default();
+    /// let mut plugins = Plugins::default();
+    /// plugins.render.link_url_rewriter = Some(&absolute);
+    /// assert_eq!(markdown_to_html_with_plugins("[a](/b) [c](#d)\n", &options, &plugins),
+    ///            "<p><a href=\"https://example.com/b\">a</a> <a href=\"#d\">c</a></p>\n");
+    /// ```
+    pub link_url_rewriter: Option<&'p dyn UrlRewriter>,
+
+    /// Optional rewriter of image sources.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html_with_plugins, Options, Plugins};
+    /// let proxy = |url: &str| format!("https://cdn.example.com/?src={}", url);
+    ///
+    /// let options = Options::default();
+    /// let mut plugins = Plugins::default();
+    /// plugins.render.image_url_rewriter = Some(&proxy);
+    /// assert_eq!(markdown_to_html_with_plugins("![a](b.png)\n", &options, &plugins),
+    ///            "<p><img src=\"https://cdn.example.com/?src=b.png\" alt=\"a\" /></p>\n");
+    /// ```
+    pub image_url_rewriter: Option<&'p dyn UrlRewriter>,
+
+    /// Optional resolver of image sources, given each image's alt text and
+    /// title as well as its source.  Images it resolves aren't passed to
+    /// `image_url_rewriter`.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html_with_plugins, Options, Plugins};
+    /// # use comrak::adapters::ImageMeta;
+    /// let placeholder = |image: &ImageMeta| {
+    ///     (image.alt == "Logo").then(|| "data:image/png;base64,iVBORw0KGgo=".to_string())
+    /// };
+    ///
+    /// let options = Options::default();
+    /// let mut plugins = Plugins::default();
+    /// plugins.render.image_resolver = Some(&placeholder);
+    /// assert_eq!(markdown_to_html_with_plugins("![Logo](logo.png) ![*Photo*](a.jpg)\n", &options, &plugins),
+    ///            "<p><img src=\"data:image/png;base64,iVBORw0KGgo=\" alt=\"Logo\" /> \
+    ///             <img src=\"a.jpg\" alt=\"Photo\" /></p>\n");
+    /// ```
+    pub image_resolver: Option<&'p dyn ImageResolver>,
+
+    /// Optional resolver for template variable placeholders.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html_with_plugins, Options, Plugins};
+    /// # use comrak::adapters::TemplateResolver;
+    /// struct Vars;
+    /// impl TemplateResolver for Vars {
+    ///     fn resolve(&self, name: &str, filters: &[String]) -> Option<String> {
+    ///         let value = match name {
+    ///             "product" => "Comrak & co".to_string(),
+    ///             _ => return None,
+    ///         };
+    ///         Some(match filters {
+    ///             [f] if f == "upcase" => value.to_uppercase(),
+    ///             _ => value,
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let mut options = Options::default();
+    /// options.extension.template_variables = true;
+    /// let mut plugins = Plugins::default();
+    /// plugins.render.template_resolver = Some(&Vars);
+    /// assert_eq!(markdown_to_html_with_plugins("*{{product}}* {{ product | upcase }} {{other}}\n", &options, &plugins),
+    ///            "<p><em>Comrak &amp; co</em> COMRAK &amp; CO {{other}}</p>\n");
+    /// ```
+    pub template_resolver: Option<&'p dyn TemplateResolver>,
+
+    /// Optional adapter supplying the width, height and `srcset` of images.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html_with_plugins, Options, Plugins};
+    /// # use comrak::adapters::{ImageDimensionAdapter, ImageDimensions};
+    /// struct Sizes;
+    /// impl ImageDimensionAdapter for Sizes {
+    ///     fn dimensions(&self, url: &str) -> Option<ImageDimensions> {
+    ///         (url == "logo.png").then(|| ImageDimensions {
+    ///             width: Some(120),
+    ///             height: Some(40),
+    ///             srcset: Some("logo@2x.png 2x".to_string()),
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let options = Options::default();
+    /// let mut plugins = Plugins::default();
+    /// plugins.render.image_dimension_adapter = Some(&Sizes);
+    /// assert_eq!(markdown_to_html_with_plugins("![Logo](logo.png) ![](other.png)\n", &options, &plugins),
+    ///            "<p><img src=\"logo.png\" alt=\"Logo\" width=\"120\" height=\"40\" \
+    ///             srcset=\"logo@2x.png 2x\" /> <img src=\"other.png\" alt=\"\" /></p>\n");
+    /// ```
+    pub image_dimension_adapter: Option<&'p dyn ImageDimensionAdapter>,
+
+    /// Optional adapter rendering the tags of table cells.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html_with_plugins, Options, Plugins};
+    /// # use comrak::adapters::{TableCellAdapter, TableCellMeta};
+    /// # use comrak::nodes::Sourcepos;
+    /// # use std::io::{self, Write};
+    /// struct Columns;
+    /// impl TableCellAdapter for Columns {
+    ///     fn enter(&self, output: &mut dyn Write, cell: &TableCellMeta, _sourcepos: Option<Sourcepos>) -> io::Result<()> {
+    ///         let tag = if cell.header { "th" } else { "td" };
+    ///         write!(output, "<{} data-column=\"{}\">", tag, cell.column)
+    ///     }
+    ///
+    ///     fn exit(&self, output: &mut dyn Write, cell: &TableCellMeta) -> io::Result<()> {
+    ///         write!(output, "</{}>", if cell.header { "th" } else { "td" })
+    ///     }
+    /// }
+    ///
+    /// let mut options = Options::default();
+    /// options.extension.table = true;
+    /// let mut plugins = Plugins::default();
+    /// plugins.render.table_cell_adapter = Some(&Columns);
+    /// assert_eq!(markdown_to_html_with_plugins("| a | b |\n|---|---|\n", &options, &plugins),
+    ///            "<table>\n<thead>\n<tr>\n<th data-column=\"0\">a</th>\n\
+    ///             <th data-column=\"1\">b</th>\n</tr>\n</thead>\n</table>\n");
+    /// ```
+    pub table_cell_adapter: Option<&'p dyn TableCellAdapter>,
 }
 
 impl Debug for RenderPlugins<'_> {
@@ -956,10 +2537,123 @@ impl Debug for RenderPlugins<'_> {
                 "codefence_syntax_highlighter",
                 &"impl SyntaxHighlighterAdapter",
             )
+            .field("codefence_highlight_cache", &"impl CodeBlockCache")
+            .field("link_title_adapter", &"impl LinkTitleAdapter")
+            .field("link_url_rewriter", &"impl UrlRewriter")
+            .field("image_url_rewriter", &"impl UrlRewriter")
+            .field("image_resolver", &"impl ImageResolver")
+            .field("template_resolver", &"impl TemplateResolver")
+            .field("image_dimension_adapter", &"impl ImageDimensionAdapter")
+            .field("table_cell_adapter", &"impl TableCellAdapter")
             .finish()
     }
 }
 
+/// State carried between the parses of documents which are to be rendered
+/// together.  See [`parse_document_with_context`].
+#[derive(Clone, Debug, Default)]
+pub struct ParseContext {
+    // Keyed by normalized label.
+    pub(crate) references: HashMap<String, ResolvedReference>,
+    // The normalized labels which links have been resolved with.
+    pub(crate) used_references: HashSet<String>,
+    pub(crate) definitions: Vec<ReferenceDefinition>,
+    pub(crate) footnotes: u32,
+}
+
+impl ParseContext {
+    /// Creates a context for a first document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The link reference definition in effect for `label`, if any.
+    pub fn reference(&self, label: &str) -> Option<&ResolvedReference> {
+        self.references
+            .get(&strings::normalize_label(label, strings::Case::Fold))
+    }
+
+    /// Defines `label` for the documents parsed with this context, unless
+    /// it's already defined.  Like a definition in an earlier document, it
+    /// takes precedence over any of the same label in a later one.
+    ///
+    /// ```
+    /// # use comrak::{format_html, parse_document_with_context, Arena, Options};
+    /// # use comrak::{ParseContext, ResolvedReference};
+    /// let mut context = ParseContext::new();
+    /// context.define("docs", ResolvedReference {
+    ///     url: "https://docs.example.com".to_string(),
+    ///     title: String::new(),
+    /// });
+    ///
+    /// let arena = Arena::new();
+    /// let options = Options::default();
+    /// let root = parse_document_with_context(&arena, "See [the docs][Docs].\n", &options, &mut context);
+    /// let mut html = vec![];
+    /// format_html(root, &options, &mut html).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(html).unwrap(),
+    ///     "<p>See <a href=\"https://docs.example.com\">the docs</a>.</p>\n"
+    /// );
+    /// ```
+    pub fn define(&mut self, label: &str, reference: ResolvedReference) {
+        let label = strings::normalize_label(label, strings::Case::Fold);
+        if !label.is_empty() {
+            self.references.entry(label).or_insert(reference);
+        }
+    }
+
+    /// The link reference definitions parsed from each document, in source
+    /// order, including any which were overridden by an earlier definition
+    /// of the same label.
+    ///
+    /// ```
+    /// # use comrak::{parse_document_with_context, Arena, Options, ParseContext};
+    /// let mut context = ParseContext::new();
+    /// let arena = Arena::new();
+    /// let input = "[a]\n\n[a]: /one\n[b]: /two \"Two\"\n";
+    /// parse_document_with_context(&arena, input, &Options::default(), &mut context);
+    ///
+    /// let unused: Vec<_> = context
+    ///     .definitions()
+    ///     .iter()
+    ///     .filter(|definition| !context.is_referenced(&definition.label))
+    ///     .map(|definition| (definition.label.as_str(), definition.sourcepos.start.line))
+    ///     .collect();
+    /// assert_eq!(unused, [("b", 4)]);
+    /// ```
+    pub fn definitions(&self) -> &[ReferenceDefinition] {
+        &self.definitions
+    }
+
+    /// Whether a link has been resolved with the definition of `label`.
+    pub fn is_referenced(&self, label: &str) -> bool {
+        self.used_references
+            .contains(&strings::normalize_label(label, strings::Case::Fold))
+    }
+
+    /// The number of footnotes numbered so far.
+    pub fn footnote_count(&self) -> u32 {
+        self.footnotes
+    }
+}
+
+/// A link reference definition, as returned by [`ParseContext::definitions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReferenceDefinition {
+    /// The label of the definition, as written.
+    pub label: String,
+
+    /// The destination URL.
+    pub url: String,
+
+    /// The title, or an empty string.
+    pub title: String,
+
+    /// The position of the definition in the document it was parsed from.
+    pub sourcepos: Sourcepos,
+}
+
 /// A reference link's resolved details.
 #[derive(Clone, Debug)]
 pub struct ResolvedReference {
@@ -973,6 +2667,7 @@ pub struct ResolvedReference {
 struct FootnoteDefinition<'a> {
     ix: Option<u32>,
     node: &'a AstNode<'a>,
+    first_reference: Option<&'a AstNode<'a>>,
     name: String,
     total_references: u32,
 }
@@ -982,12 +2677,17 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
         Parser {
             arena,
             refmap: RefMap::new(),
+            definitions: vec![],
             root,
             current: root,
             line_number: 0,
             offset: 0,
             column: 0,
             thematic_break_kill_pos: 0,
+            tab_stop: match options.parse.tab_width {
+                0 => TAB_STOP,
+                width => width,
+            },
             first_nonspace: 0,
             first_nonspace_column: 0,
             indent: 0,
@@ -998,6 +2698,12 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             last_line_length: 0,
             last_buffer_ended_with_cr: false,
             total_size: 0,
+            footnote_ix: 0,
+            front_matter_len: 0,
+            component: ComponentScanner::default(),
+            close_extension_block: false,
+            closing_fence: false,
+            checkpoint: Checkpoint::default(),
             options,
         }
     }
@@ -1008,6 +2714,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             &self.options.extension.front_matter_delimiter,
         ) {
             if let Some((front_matter, rest)) = split_off_front_matter(s, delimiter) {
+                self.front_matter_len = s.len() - rest.len();
                 let node = self.add_child(
                     self.root,
                     NodeValue::FrontMatter(front_matter.to_string()),
@@ -1035,18 +2742,18 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
         let end = s.len();
 
         while buffer < end {
+            if self.checkpoint.cancelled() {
+                return;
+            }
+
             let mut process = false;
-            let mut eol = buffer;
-            while eol < end {
-                if strings::is_line_end_char(s[eol]) {
-                    process = true;
-                    break;
+            let eol = match memchr::memchr3(b'\n', b'\r', 0, &s[buffer..]) {
+                Some(n) => {
+                    process = s[buffer + n] != 0;
+                    buffer + n
                 }
-                if s[eol] == 0 {
-                    break;
-                }
-                eol += 1;
-            }
+                None => end,
+            };
 
             if eol >= end && eof {
                 process = true;
@@ -1132,7 +2839,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
     }
 
     fn find_first_nonspace(&mut self, line: &[u8]) {
-        let mut chars_to_tab = TAB_STOP - (self.column % TAB_STOP);
+        let mut chars_to_tab = self.tab_stop - (self.column % self.tab_stop);
 
         if self.first_nonspace <= self.offset {
             self.first_nonspace = self.offset;
@@ -1148,13 +2855,13 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                         self.first_nonspace_column += 1;
                         chars_to_tab -= 1;
                         if chars_to_tab == 0 {
-                            chars_to_tab = TAB_STOP;
+                            chars_to_tab = self.tab_stop;
                         }
                     }
                     9 => {
                         self.first_nonspace += 1;
                         self.first_nonspace_column += chars_to_tab;
-                        chars_to_tab = TAB_STOP;
+                        chars_to_tab = self.tab_stop;
                     }
                     _ => break,
                 }
@@ -1254,7 +2961,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             self.find_first_nonspace(line);
 
             match ast.value {
-                NodeValue::BlockQuote => {
+                NodeValue::BlockQuote | NodeValue::Alert(..) => {
                     if !self.parse_block_quote_prefix(line) {
                         return (false, container, should_continue);
                     }
@@ -1279,19 +2986,32 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                         return (false, container, should_continue);
                     }
                 }
+                NodeValue::ComponentBlock(..) => {}
+                NodeValue::ExtensionBlock(ref ne) => {
+                    if !self.parse_extension_block_prefix(line, ne) {
+                        return (false, container, should_continue);
+                    }
+                }
+                NodeValue::Raw(..) => {
+                    if !self.parse_raw_prefix(line, container, ast, &mut should_continue) {
+                        return (false, container, should_continue);
+                    }
+                }
                 NodeValue::Paragraph => {
                     if self.blank {
                         return (false, container, should_continue);
                     }
                 }
-                NodeValue::Table(..) => {
-                    if !table::matches(&line[self.first_nonspace..], self.options.extension.spoiler)
-                    {
+                NodeValue::Table(ref nt) => {
+                    if !table::matches_row(self, nt.num_columns, line) {
                         return (false, container, should_continue);
                     }
                     continue;
                 }
-                NodeValue::Heading(..) | NodeValue::TableRow(..) | NodeValue::TableCell => {
+                NodeValue::Heading(..)
+                | NodeValue::TableRow(..)
+                | NodeValue::TableCell
+                | NodeValue::AbbreviationDefinition(..) => {
                     return (false, container, should_continue);
                 }
                 NodeValue::FootnoteDefinition(..) => {
@@ -1309,6 +3029,11 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                         return (false, container, should_continue);
                     }
                 }
+                NodeValue::Container(..) => {
+                    if !self.parse_container_prefix(line, container, ast, &mut should_continue) {
+                        return (false, container, should_continue);
+                    }
+                }
                 _ => {}
             }
         }
@@ -1331,14 +3056,27 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
         let mut matched: usize = 0;
         let mut nl: NodeList = NodeList::default();
         let mut sc: scanners::SetextChar = scanners::SetextChar::Equals;
+        let mut nad = NodeAbbreviationDefinition::default();
+        let mut ncontainer = NodeContainer::default();
+        let mut nextension = NodeExtension::default();
         let mut maybe_lazy = node_matches!(self.current, NodeValue::Paragraph);
         let mut depth = 0;
+        let max_nesting = self.max_nesting();
+        let mut nesting = container.ancestors().count() - 1;
 
         while !node_matches!(
             container,
-            NodeValue::CodeBlock(..) | NodeValue::HtmlBlock(..)
+            NodeValue::CodeBlock(..)
+                | NodeValue::HtmlBlock(..)
+                | NodeValue::ComponentBlock(..)
+                | NodeValue::Raw(..)
+                | NodeValue::ExtensionBlock(..)
         ) {
+            if nesting >= max_nesting {
+                break;
+            }
             depth += 1;
+            nesting += 1;
             self.find_first_nonspace(line);
             let indented = self.indent >= CODE_INDENT;
 
@@ -1361,6 +3099,24 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                     self.first_nonspace + 1,
                 );
                 self.advance_offset(line, first_nonspace + matched - offset, false);
+            } else if !indented
+                && (self.options.extension.container_blocks
+                    || self.options.extension.conditional_blocks
+                    || self.options.extension.details_blocks)
+                && line[self.first_nonspace] == b':'
+                && unwrap_into(
+                    container::open_fence(&line[self.first_nonspace..], &self.options.extension),
+                    &mut ncontainer,
+                )
+            {
+                ncontainer.fence_offset = self.first_nonspace - self.offset;
+                *container = self.add_child(
+                    container,
+                    NodeValue::Container(mem::take(&mut ncontainer)),
+                    self.first_nonspace + 1,
+                );
+                let adv = line.len() - 1 - self.offset;
+                self.advance_offset(line, adv, false);
             } else if !indented && line[self.first_nonspace] == b'>' && self.is_not_greentext(line)
             {
                 let blockquote_startpos = self.first_nonspace;
@@ -1370,8 +3126,24 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                 if strings::is_space_or_tab(line[self.offset]) {
                     self.advance_offset(line, 1, true);
                 }
-                *container =
-                    self.add_child(container, NodeValue::BlockQuote, blockquote_startpos + 1);
+
+                let nalert = if self.options.extension.alerts {
+                    alert::open_marker(&line[self.offset..])
+                } else {
+                    None
+                };
+                if let Some(nalert) = nalert {
+                    *container = self.add_child(
+                        container,
+                        NodeValue::Alert(nalert),
+                        blockquote_startpos + 1,
+                    );
+                    let adv = line.len() - 1 - self.offset;
+                    self.advance_offset(line, adv, false);
+                } else {
+                    *container =
+                        self.add_child(container, NodeValue::BlockQuote, blockquote_startpos + 1);
+                }
             } else if !indented
                 && unwrap_into(
                     scanners::atx_heading_start(&line[self.first_nonspace..]),
@@ -1402,6 +3174,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                 container_ast.value = NodeValue::Heading(NodeHeading {
                     level,
                     setext: false,
+                    attributes: NodeAttributes::default(),
                 });
                 container_ast.internal_offset = matched;
             } else if !indented
@@ -1419,6 +3192,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                     fence_offset: first_nonspace - offset,
                     info: String::with_capacity(10),
                     literal: String::new(),
+                    attributes: NodeAttributes::default(),
                 };
                 *container = self.add_child(
                     container,
@@ -1426,6 +3200,40 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                     self.first_nonspace + 1,
                 );
                 self.advance_offset(line, first_nonspace + matched - offset, false);
+            } else if let Some(name) = (!indented && self.options.extension.jsx_components)
+                .then(|| component::name(&line[self.first_nonspace..]))
+                .flatten()
+            {
+                let nc = NodeComponent {
+                    name,
+                    literal: String::new(),
+                };
+                self.component = ComponentScanner::default();
+                *container = self.add_child(
+                    container,
+                    NodeValue::ComponentBlock(nc),
+                    self.first_nonspace + 1,
+                );
+            } else if !indented
+                && self.options.extension.raw_regions
+                && raw::is_marker(&line[self.first_nonspace..], "off")
+            {
+                *container = self.add_child(
+                    container,
+                    NodeValue::Raw(String::new()),
+                    self.first_nonspace + 1,
+                );
+            } else if !indented
+                && unwrap_into(
+                    self.open_extension_block(&line[self.first_nonspace..]),
+                    &mut nextension,
+                )
+            {
+                *container = self.add_child(
+                    container,
+                    NodeValue::ExtensionBlock(mem::take(&mut nextension)),
+                    self.first_nonspace + 1,
+                );
             } else if !indented
                 && (unwrap_into(
                     scanners::html_block_start(&line[self.first_nonspace..]),
@@ -1455,7 +3263,12 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             {
                 let has_content = {
                     let mut ast = container.data.borrow_mut();
-                    self.resolve_reference_link_definitions(&mut ast.content)
+                    let ast = &mut *ast;
+                    self.resolve_reference_link_definitions(
+                        &mut ast.content,
+                        ast.sourcepos.start.line,
+                        &ast.line_offsets,
+                    )
                 };
                 if has_content {
                     container.data.borrow_mut().value = NodeValue::Heading(NodeHeading {
@@ -1464,6 +3277,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                             scanners::SetextChar::Hyphen => 2,
                         },
                         setext: true,
+                        attributes: NodeAttributes::default(),
                     });
                     let adv = line.len() - 1 - self.offset;
                     self.advance_offset(line, adv, false);
@@ -1480,6 +3294,21 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                     self.add_child(container, NodeValue::ThematicBreak, self.first_nonspace + 1);
                 let adv = line.len() - 1 - self.offset;
                 self.advance_offset(line, adv, false);
+            } else if !indented
+                && self.options.extension.abbreviations
+                && line[self.first_nonspace] == b'*'
+                && unwrap_into(
+                    abbreviation::definition(&line[self.first_nonspace..]),
+                    &mut nad,
+                )
+            {
+                *container = self.add_child(
+                    container,
+                    NodeValue::AbbreviationDefinition(mem::take(&mut nad)),
+                    self.first_nonspace + 1,
+                );
+                let adv = line.len() - 1 - self.offset;
+                self.advance_offset(line, adv, false);
             } else if !indented
                 && self.options.extension.footnotes
                 && depth < MAX_LIST_DEPTH
@@ -1497,6 +3326,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                     NodeValue::FootnoteDefinition(NodeFootnoteDefinition {
                         name: str::from_utf8(c).unwrap().to_string(),
                         total_references: 0,
+                        ix: 0,
                     }),
                     self.first_nonspace + 1,
                 );
@@ -1568,6 +3398,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                     fence_offset: 0,
                     info: String::new(),
                     literal: String::new(),
+                    attributes: NodeAttributes::default(),
                 };
                 *container = self.add_child(container, NodeValue::CodeBlock(ncb), self.offset + 1);
             } else {
@@ -1606,7 +3437,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
         while count > 0 {
             match line[self.offset] {
                 9 => {
-                    let chars_to_tab = TAB_STOP - (self.column % TAB_STOP);
+                    let chars_to_tab = self.tab_stop - (self.column % self.tab_stop);
                     if columns {
                         self.partially_consumed_tab = chars_to_tab > count;
                         let chars_to_advance = min(count, chars_to_tab);
@@ -1728,6 +3559,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
         if matched >= fence_length {
             *should_continue = false;
             self.advance_offset(line, matched, false);
+            self.closing_fence = true;
             self.current = self.finalize_borrowed(container, ast).unwrap();
             return false;
         }
@@ -1811,17 +3643,105 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             let details =
                 self.add_child(item, NodeValue::DescriptionDetails, self.first_nonspace + 1);
 
-            term.append(last_child);
+            term.append(last_child);
+
+            *container = details;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_multiline_block_quote_prefix(
+        &mut self,
+        line: &[u8],
+        container: &'a AstNode<'a>,
+        ast: &mut Ast,
+        should_continue: &mut bool,
+    ) -> bool {
+        let (fence_length, fence_offset) = match ast.value {
+            NodeValue::MultilineBlockQuote(ref node_value) => {
+                (node_value.fence_length, node_value.fence_offset)
+            }
+            _ => unreachable!(),
+        };
+
+        let matched = if self.indent <= 3 && line[self.first_nonspace] == b'>' {
+            scanners::close_multiline_block_quote_fence(&line[self.first_nonspace..]).unwrap_or(0)
+        } else {
+            0
+        };
+
+        if matched >= fence_length {
+            *should_continue = false;
+            self.advance_offset(line, matched, false);
+
+            // The last child, like an indented codeblock, could be left open.
+            // Make sure it's finalized.
+            if nodes::last_child_is_open(container) {
+                let child = container.last_child().unwrap();
+                let child_ast = &mut *child.data.borrow_mut();
+
+                self.finalize_borrowed(child, child_ast).unwrap();
+            }
+
+            self.current = self.finalize_borrowed(container, ast).unwrap();
+            return false;
+        }
+
+        let mut i = fence_offset;
+        while i > 0 && strings::is_space_or_tab(line[self.offset]) {
+            self.advance_offset(line, 1, true);
+            i -= 1;
+        }
+        true
+    }
 
-            *container = details;
+    fn parse_raw_prefix(
+        &mut self,
+        line: &[u8],
+        container: &'a AstNode<'a>,
+        ast: &mut Ast,
+        should_continue: &mut bool,
+    ) -> bool {
+        if self.indent < CODE_INDENT && raw::is_marker(&line[self.first_nonspace..], "on") {
+            *should_continue = false;
+            self.current = self.finalize_borrowed(container, ast).unwrap();
+            return false;
+        }
+        true
+    }
 
-            true
-        } else {
-            false
+    fn open_extension_block(&self, line: &[u8]) -> Option<NodeExtension> {
+        if self.options.extensions.is_empty() {
+            return None;
         }
+        let line = str::from_utf8(line).ok()?;
+        self.options.extensions.iter().find_map(|extension| {
+            let mut ne = extension.open_block(line)?;
+            ne.extension = extension.name().to_string();
+            Some(ne)
+        })
     }
 
-    fn parse_multiline_block_quote_prefix(
+    fn parse_extension_block_prefix(&mut self, line: &[u8], ne: &NodeExtension) -> bool {
+        let extension = match self.options.extensions.get(&ne.extension) {
+            Some(extension) => extension,
+            None => return false,
+        };
+        let line = str::from_utf8(&line[self.offset..]).unwrap();
+        match extension.continue_block(ne, line) {
+            BlockContinuation::Continue => true,
+            BlockContinuation::Close => {
+                self.close_extension_block = true;
+                true
+            }
+            BlockContinuation::End => false,
+        }
+    }
+
+    fn parse_container_prefix(
         &mut self,
         line: &[u8],
         container: &'a AstNode<'a>,
@@ -1829,24 +3749,30 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
         should_continue: &mut bool,
     ) -> bool {
         let (fence_length, fence_offset) = match ast.value {
-            NodeValue::MultilineBlockQuote(ref node_value) => {
-                (node_value.fence_length, node_value.fence_offset)
-            }
+            NodeValue::Container(ref nc) => (nc.fence_length, nc.fence_offset),
             _ => unreachable!(),
         };
 
-        let matched = if self.indent <= 3 && line[self.first_nonspace] == b'>' {
-            scanners::close_multiline_block_quote_fence(&line[self.first_nonspace..]).unwrap_or(0)
+        let matched = if self.indent <= 3 && line[self.first_nonspace] == b':' {
+            container::close_fence(&line[self.first_nonspace..]).unwrap_or(0)
         } else {
             0
         };
 
-        if matched >= fence_length {
+        // Leave the fence to an open block nested within this one.
+        let mut nested = false;
+        let mut child = container.last_child();
+        while let Some(c) = child.filter(|c| c.data.borrow().open) {
+            if let NodeValue::Container(ref nc) = c.data.borrow().value {
+                nested |= matched >= nc.fence_length;
+            }
+            child = c.last_child();
+        }
+
+        if matched >= fence_length && !nested {
             *should_continue = false;
             self.advance_offset(line, matched, false);
 
-            // The last child, like an indented codeblock, could be left open.
-            // Make sure it's finalized.
             if nodes::last_child_is_open(container) {
                 let child = container.last_child().unwrap();
                 let child_ast = &mut *child.data.borrow_mut();
@@ -1900,13 +3826,16 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
 
         container.data.borrow_mut().last_line_blank = self.blank
             && match container.data.borrow().value {
-                NodeValue::BlockQuote | NodeValue::Heading(..) | NodeValue::ThematicBreak => false,
+                NodeValue::BlockQuote
+                | NodeValue::Alert(..)
+                | NodeValue::Heading(..)
+                | NodeValue::ThematicBreak => false,
                 NodeValue::CodeBlock(ref ncb) => !ncb.fenced,
                 NodeValue::Item(..) => {
                     container.first_child().is_some()
                         || container.data.borrow().sourcepos.start.line != self.line_number
                 }
-                NodeValue::MultilineBlockQuote(..) => false,
+                NodeValue::MultilineBlockQuote(..) | NodeValue::Container(..) => false,
                 _ => true,
             };
 
@@ -1922,7 +3851,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             && (!self.options.extension.greentext
                 || !matches!(
                     container.data.borrow().value,
-                    NodeValue::BlockQuote | NodeValue::Document
+                    NodeValue::BlockQuote | NodeValue::Alert(..) | NodeValue::Document
                 ))
             && node_matches!(self.current, NodeValue::Paragraph)
         {
@@ -1933,8 +3862,10 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             }
 
             let add_text_result = match container.data.borrow().value {
-                NodeValue::CodeBlock(..) => AddTextResult::LiteralText,
+                NodeValue::CodeBlock(..) | NodeValue::Raw(..) => AddTextResult::LiteralText,
                 NodeValue::HtmlBlock(ref nhb) => AddTextResult::HtmlBlock(nhb.block_type),
+                NodeValue::ComponentBlock(..) => AddTextResult::ComponentBlock,
+                NodeValue::ExtensionBlock(..) => AddTextResult::ExtensionBlock,
                 _ => AddTextResult::Otherwise,
             };
 
@@ -1958,6 +3889,26 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                         container = self.finalize(container).unwrap();
                     }
                 }
+                AddTextResult::ComponentBlock => {
+                    self.add_line(container, line);
+
+                    if self.component.scan(&line[self.offset..]).is_some() {
+                        let component = container;
+                        container = self.finalize(container).unwrap();
+                        component.data.borrow_mut().sourcepos.end =
+                            (self.line_number, self.curline_end_col).into();
+                    }
+                }
+                AddTextResult::ExtensionBlock => {
+                    self.add_line(container, line);
+
+                    if mem::take(&mut self.close_extension_block) {
+                        let block = container;
+                        container = self.finalize(container).unwrap();
+                        block.data.borrow_mut().sourcepos.end =
+                            (self.line_number, self.curline_end_col).into();
+                    }
+                }
                 _ => {
                     if self.blank {
                         // do nothing
@@ -2005,12 +3956,18 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
         }
     }
 
+    fn report(&self, sourcepos: Sourcepos, message: String) {
+        if let Some(callback) = &self.options.parse.diagnostic_callback {
+            callback.lock().unwrap()(Diagnostic { sourcepos, message });
+        }
+    }
+
     fn add_line(&mut self, node: &'a AstNode<'a>, line: &[u8]) {
         let mut ast = node.data.borrow_mut();
         assert!(ast.open);
         if self.partially_consumed_tab {
             self.offset += 1;
-            let chars_to_tab = TAB_STOP - (self.column % TAB_STOP);
+            let chars_to_tab = self.tab_stop - (self.column % self.tab_stop);
             for _ in 0..chars_to_tab {
                 ast.content.push(' ');
             }
@@ -2027,12 +3984,14 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
     }
 
     fn finish(&mut self, remaining: Vec<u8>) -> &'a AstNode<'a> {
-        if !remaining.is_empty() {
+        if !remaining.is_empty() && !self.checkpoint.cancelled() {
             self.process_line(&remaining);
         }
 
         self.finalize_document();
-        self.postprocess_text_nodes(self.root);
+        if !self.checkpoint.cancelled() {
+            self.postprocess_text_nodes(self.root);
+        }
         self.root
     }
 
@@ -2050,7 +4009,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
         };
 
         self.process_inlines();
-        if self.options.extension.footnotes {
+        if self.options.extension.footnotes && !self.checkpoint.cancelled() {
             self.process_footnotes();
         }
     }
@@ -2059,15 +4018,45 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
         self.finalize_borrowed(node, &mut node.data.borrow_mut())
     }
 
-    fn resolve_reference_link_definitions(&mut self, content: &mut String) -> bool {
+    fn resolve_reference_link_definitions(
+        &mut self,
+        content: &mut String,
+        start_line: usize,
+        line_offsets: &[usize],
+    ) -> bool {
         let mut seeked = 0;
         {
-            let mut pos = 0;
+            let mut positions = ContentPositions::new(content.as_bytes(), start_line, line_offsets);
             let mut seek: &[u8] = content.as_bytes();
-            while !seek.is_empty()
-                && seek[0] == b'['
-                && unwrap_into(self.parse_reference_inline(seek), &mut pos)
-            {
+            while !seek.is_empty() && seek[0] == b'[' {
+                let (pos, mut definition) = match self.parse_reference_inline(seek) {
+                    Some(parsed) => parsed,
+                    None => break,
+                };
+                let mut end = pos;
+                while end > 0 && strings::is_line_end_char(seek[end - 1]) {
+                    end -= 1;
+                }
+                definition.sourcepos = Sourcepos {
+                    start: positions.position(seeked),
+                    end: positions.position(seeked + end - 1),
+                };
+                let label = strings::normalize_label(&definition.label, Case::Fold);
+                if !label.is_empty() {
+                    match self.refmap.map.entry(label) {
+                        Entry::Vacant(entry) => {
+                            entry.insert(ResolvedReference {
+                                url: definition.url.clone(),
+                                title: definition.title.clone(),
+                            });
+                        }
+                        Entry::Occupied(_) => self.report(
+                            definition.sourcepos,
+                            format!("duplicate link reference definition [{}]", definition.label),
+                        ),
+                    }
+                }
+                self.definitions.push(definition);
                 seek = &seek[pos..];
                 seeked += pos;
             }
@@ -2088,6 +4077,14 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
         assert!(ast.open);
         ast.open = false;
 
+        if let NodeValue::Table(ref nt) = ast.value {
+            if self.options.extension.table_row_mismatch == TableRowMismatch::Paragraph
+                && table::has_mismatched_rows(node, nt.num_columns)
+            {
+                table::downgrade_to_paragraph(node, ast);
+            }
+        }
+
         let content = &mut ast.content;
         let parent = node.parent();
 
@@ -2096,7 +4093,9 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
         } else if match ast.value {
             NodeValue::Document => true,
             NodeValue::CodeBlock(ref ncb) => ncb.fenced,
-            NodeValue::MultilineBlockQuote(..) => true,
+            NodeValue::MultilineBlockQuote(..) | NodeValue::Container(..) | NodeValue::Raw(..) => {
+                true
+            }
             _ => false,
         } {
             ast.sourcepos.end = (self.line_number, self.curline_end_col).into();
@@ -2106,7 +4105,11 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
 
         match ast.value {
             NodeValue::Paragraph => {
-                let has_content = self.resolve_reference_link_definitions(content);
+                let has_content = self.resolve_reference_link_definitions(
+                    content,
+                    ast.sourcepos.start.line,
+                    &ast.line_offsets,
+                );
                 if !has_content {
                     node.detach();
                 }
@@ -2116,6 +4119,10 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                     strings::remove_trailing_blank_lines(content);
                     content.push('\n');
                 } else {
+                    if !mem::take(&mut self.closing_fence) {
+                        self.report(ast.sourcepos, "fenced code block is not closed".to_string());
+                    }
+
                     let mut pos = 0;
                     while pos < content.len() {
                         if strings::is_line_end_char(content.as_bytes()[pos]) {
@@ -2128,7 +4135,14 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                     let mut tmp = entity::unescape_html(&content.as_bytes()[..pos]);
                     strings::trim(&mut tmp);
                     strings::unescape(&mut tmp);
-                    if tmp.is_empty() {
+                    let mut info = String::from_utf8(tmp).unwrap();
+                    if self.options.extension.block_attributes {
+                        if let Some((len, attributes)) = attributes::split_trailing(&info) {
+                            info.truncate(len);
+                            ncb.attributes = attributes;
+                        }
+                    }
+                    if info.is_empty() {
                         ncb.info = self
                             .options
                             .parse
@@ -2136,7 +4150,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                             .as_ref()
                             .map_or(String::new(), |s| s.clone());
                     } else {
-                        ncb.info = String::from_utf8(tmp).unwrap();
+                        ncb.info = info;
                     }
 
                     if content.as_bytes()[pos] == b'\r' {
@@ -2150,9 +4164,27 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
                 }
                 mem::swap(&mut ncb.literal, content);
             }
+            NodeValue::Heading(ref mut nh) if self.options.extension.block_attributes => {
+                if let Some((len, attributes)) = attributes::split_trailing(content) {
+                    content.truncate(len);
+                    nh.attributes = attributes;
+                }
+            }
             NodeValue::HtmlBlock(ref mut nhb) => {
                 mem::swap(&mut nhb.literal, content);
             }
+            NodeValue::ComponentBlock(ref mut nc) => {
+                mem::swap(&mut nc.literal, content);
+            }
+            NodeValue::ExtensionBlock(ref mut ne) => {
+                mem::swap(&mut ne.literal, content);
+            }
+            NodeValue::Raw(ref mut literal) => {
+                // The first line is the opening marker.
+                let pos = content.find('\n').map_or(content.len(), |pos| pos + 1);
+                content.drain(..pos);
+                mem::swap(literal, content);
+            }
             NodeValue::List(ref mut nl) => {
                 nl.tight = true;
                 let mut ch = node.first_child();
@@ -2193,6 +4225,9 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
 
     fn process_inlines_node(&mut self, node: &'a AstNode<'a>) {
         for node in node.descendants() {
+            if self.checkpoint.cancelled() {
+                return;
+            }
             if node.data.borrow().value.contains_inlines() {
                 self.parse_inlines(node);
             }
@@ -2212,19 +4247,56 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             &delimiter_arena,
         );
 
-        while subj.parse_inline(node) {}
+        while !self.checkpoint.cancelled() && subj.parse_inline(node) {}
 
         subj.process_emphasis(0);
 
         while subj.pop_bracket() {}
+        drop(node_data);
+
+        self.limit_inline_nesting(node);
+    }
+
+    // Replaces inlines nested more than `max_nesting` deep within `node` by
+    // their contents, so that formatters walking the tree recursively needn't
+    // go any deeper.
+    fn limit_inline_nesting(&self, node: &'a AstNode<'a>) {
+        let max_nesting = self.max_nesting();
+        let mut stack: Vec<_> = node.children().map(|child| (child, 1)).collect();
+        while let Some((inline, depth)) = stack.pop() {
+            if depth > max_nesting && inline.first_child().is_some() {
+                let mut next = inline.first_child();
+                while let Some(child) = next {
+                    next = child.next_sibling();
+                    inline.insert_before(child);
+                    stack.push((child, depth));
+                }
+                inline.detach();
+            } else {
+                stack.extend(inline.children().map(|child| (child, depth + 1)));
+            }
+        }
+    }
+
+    fn max_nesting(&self) -> usize {
+        match self.options.parse.max_nesting {
+            0 => MAX_NESTING,
+            max_nesting => max_nesting,
+        }
     }
 
     fn process_footnotes(&mut self) {
+        if self.options.extension.inline_footnotes {
+            self.name_inline_footnotes();
+        }
+
         let mut map = HashMap::new();
         Self::find_footnote_definitions(self.root, &mut map);
 
-        let mut ix = 0;
-        Self::find_footnote_references(self.root, &mut map, &mut ix);
+        let start = self.footnote_ix;
+        let mut ix = start;
+        self.find_footnote_references(self.root, &mut map, &mut ix);
+        self.footnote_ix = ix;
 
         if !map.is_empty() {
             // In order for references to be found inside footnote definitions,
@@ -2233,99 +4305,226 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             Self::cleanup_footnote_definitions(self.root);
         }
 
-        if ix > 0 {
+        if ix > start {
             let mut v = map.into_values().collect::<Vec<_>>();
             v.sort_unstable_by(|a, b| a.ix.cmp(&b.ix));
             for f in v {
-                if f.ix.is_some() {
+                if let Some(ix) = f.ix {
                     match f.node.data.borrow_mut().value {
                         NodeValue::FootnoteDefinition(ref mut nfd) => {
                             nfd.name = f.name.to_string();
                             nfd.total_references = f.total_references;
+                            nfd.ix = ix;
                         }
                         _ => unreachable!(),
                     }
-                    self.root.append(f.node);
+                    match self.options.extension.footnote_placement {
+                        FootnotePlacement::Document => self.root.append(f.node),
+                        FootnotePlacement::Section => {
+                            match f.first_reference.and_then(|r| self.footnote_section_end(r)) {
+                                Some(end) => end.insert_before(f.node),
+                                None => self.root.append(f.node),
+                            }
+                        }
+                    }
+                }
+            }
+
+            if self.options.extension.footnote_placement == FootnotePlacement::Section {
+                self.renumber_footnote_references(start);
+            }
+        }
+    }
+
+    // Placing footnotes by section can change their order in the document
+    // from the order in which they were first referenced.  Number them in the
+    // order they are rendered instead.
+    fn renumber_footnote_references(&self, start: u32) {
+        let mut ix = start;
+        let mut order = HashMap::new();
+        for node in self.root.children() {
+            if let NodeValue::FootnoteDefinition(ref mut nfd) = node.data.borrow_mut().value {
+                ix += 1;
+                nfd.ix = ix;
+                order.insert(nfd.name.clone(), ix);
+            }
+        }
+
+        for node in self.root.descendants() {
+            if let NodeValue::FootnoteReference(ref mut nfr) = node.data.borrow_mut().value {
+                if let Some(&ix) = order.get(&nfr.name) {
+                    nfr.ix = ix;
+                }
+            }
+        }
+    }
+
+    // Find the node before which footnotes first referenced by `reference`
+    // should be placed: the next top-level heading which closes the section
+    // containing the reference.  `None` means the end of the document.
+    fn footnote_section_end(&self, reference: &'a AstNode<'a>) -> Option<&'a AstNode<'a>> {
+        let top = reference
+            .ancestors()
+            .find(|n| n.parent().map_or(false, |p| p.same_node(self.root)))?;
+
+        if node_matches!(top, NodeValue::FootnoteDefinition(..)) {
+            // Referenced from within a footnote which has already been
+            // placed; follow the rest of its group.
+            return top
+                .following_siblings()
+                .find(|n| !node_matches!(n, NodeValue::FootnoteDefinition(..)));
+        }
+
+        let level = top
+            .preceding_siblings()
+            .find_map(|n| match n.data.borrow().value {
+                NodeValue::Heading(ref nh) => Some(nh.level),
+                _ => None,
+            })
+            .unwrap_or(6);
+
+        top.following_siblings()
+            .skip(1)
+            .find(|n| match n.data.borrow().value {
+                NodeValue::Heading(ref nh) => nh.level <= level,
+                _ => false,
+            })
+    }
+
+    // Inline footnotes are parsed as a reference holding its definition,
+    // both unnamed.  Name them after the order they appear in, avoiding the
+    // names of other definitions.
+    fn name_inline_footnotes(&mut self) {
+        let taken: HashSet<String> = Self::footnote_definitions(self.root)
+            .into_iter()
+            .filter_map(|node| match node.data.borrow().value {
+                NodeValue::FootnoteDefinition(ref nfd) if !nfd.name.is_empty() => {
+                    Some(strings::normalize_label(&nfd.name, Case::Fold))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut n = 0;
+        for node in self.root.descendants() {
+            let definition = match node.first_child() {
+                Some(definition) if node_matches!(node, NodeValue::FootnoteReference(..)) => {
+                    definition
+                }
+                _ => continue,
+            };
+            let name = loop {
+                n += 1;
+                let name = format!("inline-{}", n);
+                if !taken.contains(&name) {
+                    break name;
                 }
+            };
+            if let NodeValue::FootnoteDefinition(ref mut nfd) = definition.data.borrow_mut().value {
+                nfd.name = name.clone();
+            }
+            if let NodeValue::FootnoteReference(ref mut nfr) = node.data.borrow_mut().value {
+                nfr.name = name;
+            }
+        }
+    }
+
+    // The footnote definitions within `node`, not counting any within
+    // others other than those of inline footnotes.  The tree is walked with
+    // a stack, as inlines can be nested deeply enough to overflow the call
+    // stack.
+    fn footnote_definitions(node: &'a AstNode<'a>) -> Vec<&'a AstNode<'a>> {
+        let mut definitions = vec![];
+        let mut stack = vec![(node, false)];
+        while let Some((node, nested)) = stack.pop() {
+            let definition = node_matches!(node, NodeValue::FootnoteDefinition(..));
+            if definition
+                && (!nested
+                    || node.parent().map_or(false, |p| {
+                        node_matches!(p, NodeValue::FootnoteReference(..))
+                    }))
+            {
+                definitions.push(node);
             }
+            stack.extend(node.reverse_children().map(|n| (n, nested || definition)));
         }
+        definitions
     }
 
     fn find_footnote_definitions(
         node: &'a AstNode<'a>,
         map: &mut HashMap<String, FootnoteDefinition<'a>>,
     ) {
-        match node.data.borrow().value {
-            NodeValue::FootnoteDefinition(ref nfd) => {
+        for node in Self::footnote_definitions(node) {
+            if let NodeValue::FootnoteDefinition(ref nfd) = node.data.borrow().value {
                 map.insert(
                     strings::normalize_label(&nfd.name, Case::Fold),
                     FootnoteDefinition {
                         ix: None,
                         node,
+                        first_reference: None,
                         name: strings::normalize_label(&nfd.name, Case::Preserve),
                         total_references: 0,
                     },
                 );
             }
-            _ => {
-                for n in node.children() {
-                    Self::find_footnote_definitions(n, map);
-                }
-            }
         }
     }
 
     fn find_footnote_references(
+        &self,
+        node: &'a AstNode<'a>,
+        map: &mut HashMap<String, FootnoteDefinition<'a>>,
+        ixp: &mut u32,
+    ) {
+        for node in node.descendants() {
+            self.find_footnote_reference(node, map, ixp);
+        }
+    }
+
+    fn find_footnote_reference(
+        &self,
         node: &'a AstNode<'a>,
-        map: &mut HashMap<String, FootnoteDefinition>,
+        map: &mut HashMap<String, FootnoteDefinition<'a>>,
         ixp: &mut u32,
     ) {
         let mut ast = node.data.borrow_mut();
         let mut replace = None;
-        match ast.value {
-            NodeValue::FootnoteReference(ref mut nfr) => {
-                let normalized = strings::normalize_label(&nfr.name, Case::Fold);
-                if let Some(ref mut footnote) = map.get_mut(&normalized) {
-                    let ix = match footnote.ix {
-                        Some(ix) => ix,
-                        None => {
-                            *ixp += 1;
-                            footnote.ix = Some(*ixp);
-                            *ixp
-                        }
-                    };
-                    footnote.total_references += 1;
-                    nfr.ref_num = footnote.total_references;
-                    nfr.ix = ix;
-                    nfr.name = strings::normalize_label(&footnote.name, Case::Preserve);
-                } else {
-                    replace = Some(nfr.name.clone());
-                }
-            }
-            _ => {
-                for n in node.children() {
-                    Self::find_footnote_references(n, map, ixp);
-                }
+        if let NodeValue::FootnoteReference(ref mut nfr) = ast.value {
+            let normalized = strings::normalize_label(&nfr.name, Case::Fold);
+            if let Some(ref mut footnote) = map.get_mut(&normalized) {
+                let ix = match footnote.ix {
+                    Some(ix) => ix,
+                    None => {
+                        *ixp += 1;
+                        footnote.ix = Some(*ixp);
+                        footnote.first_reference = Some(node);
+                        *ixp
+                    }
+                };
+                footnote.total_references += 1;
+                nfr.ref_num = footnote.total_references;
+                nfr.ix = ix;
+                nfr.name = strings::normalize_label(&footnote.name, Case::Preserve);
+            } else {
+                replace = Some(nfr.name.clone());
             }
         }
 
         if let Some(mut label) = replace {
             label.insert_str(0, "[^");
             label.push(']');
+            self.report(
+                ast.sourcepos,
+                format!("undefined footnote reference {}", label),
+            );
             ast.value = NodeValue::Text(label);
         }
     }
 
     fn cleanup_footnote_definitions(node: &'a AstNode<'a>) {
-        match node.data.borrow().value {
-            NodeValue::FootnoteDefinition(_) => {
-                node.detach();
-            }
-            _ => {
-                for n in node.children() {
-                    Self::cleanup_footnote_definitions(n);
-                }
-            }
+        for definition in Self::footnote_definitions(node) {
+            definition.detach();
         }
     }
 
@@ -2452,7 +4651,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             NodeValue::TaskItem(if symbol == ' ' { None } else { Some(symbol) });
     }
 
-    fn parse_reference_inline(&mut self, content: &[u8]) -> Option<usize> {
+    fn parse_reference_inline(&mut self, content: &[u8]) -> Option<(usize, ReferenceDefinition)> {
         // In this case reference inlines rarely have delimiters
         // so we often just need the minimal case
         let delimiter_arena = Arena::with_capacity(0);
@@ -2465,7 +4664,7 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             &delimiter_arena,
         );
 
-        let mut lab: String = match subj.link_label() {
+        let lab: String = match subj.link_label() {
             Some(lab) if !lab.is_empty() => lab.to_string(),
             _ => return None,
         };
@@ -2514,20 +4713,21 @@ impl<'a, 'o, 'c: 'o> Parser<'a, 'o, 'c> {
             }
         }
 
-        lab = strings::normalize_label(&lab, Case::Fold);
-        if !lab.is_empty() {
-            subj.refmap.map.entry(lab).or_insert(ResolvedReference {
-                url: String::from_utf8(strings::clean_url(url)).unwrap(),
-                title: String::from_utf8(strings::clean_title(&title)).unwrap(),
-            });
-        }
-        Some(subj.pos)
+        let definition = ReferenceDefinition {
+            label: lab,
+            url: String::from_utf8(strings::clean_url(url)).unwrap(),
+            title: String::from_utf8(strings::clean_title(&title)).unwrap(),
+            sourcepos: Sourcepos::default(),
+        };
+        Some((subj.pos, definition))
     }
 }
 
 enum AddTextResult {
     LiteralText,
     HtmlBlock(u8),
+    ComponentBlock,
+    ExtensionBlock,
     Otherwise,
 }
 
@@ -2680,8 +4880,308 @@ pub enum AutolinkType {
     Email,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// Where footnote definitions are placed. See `footnote_placement` in [`ExtensionOptions`] for more details.
+pub enum FootnotePlacement {
+    /// All footnotes are placed at the end of the document.
+    #[default]
+    Document,
+    /// Footnotes are placed at the end of the section in which they are first
+    /// referenced, i.e. before the next heading of the same or a higher level.
+    Section,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// How colliding header IDs are made unique. See `header_id_collisions` in [`ExtensionOptions`] for more details.
+pub enum HeaderIdCollisions {
+    /// A counter is appended, as on GitHub: `intro`, `intro-1`, `intro-2`.
+    #[default]
+    Suffix,
+    /// A short hash is appended: `intro`, `intro-3f9a1c`.  Unlike a counter,
+    /// this can't be confused with the ID of a heading ending in a number.
+    Hash,
+    /// Collisions are an error; rendering returns an
+    /// [`io::ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData) error
+    /// naming the ID.  [`markdown_to_html`](crate::markdown_to_html) and the
+    /// other functions returning the HTML directly can't fail, and append a
+    /// counter as with [`Suffix`](Self::Suffix) instead.
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// How non-ASCII characters are written in header IDs. See `header_id_style` in [`ExtensionOptions`] for more details.
+pub enum HeaderIdStyle {
+    /// As they are, lowercased, as on GitHub: `café`.
+    #[default]
+    Unicode,
+    /// Transliterated to ASCII: `cafe`.  Chinese and Japanese are
+    /// romanized a character at a time, e.g. `日本語` as `ri-ben-yu`.
+    Ascii,
+    /// Percent-encoded as UTF-8: `caf%C3%A9`.
+    PercentEncoded,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// How soft line breaks are rendered in HTML. See `soft_breaks` in [`RenderOptions`] for more details.
+pub enum SoftBreakStyle {
+    /// A newline, as in the source.
+    #[default]
+    Newline,
+    /// A single space.
+    Space,
+    /// A `<br />` tag, without a following newline.
+    LineBreak,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// How non-ASCII characters are written to HTML. See `non_ascii` in [`RenderOptions`] for more details.
+pub enum NonAsciiStyle {
+    /// As UTF-8.
+    #[default]
+    Raw,
+    /// As hexadecimal numeric character references, e.g. `&#xE9;`.
+    Numeric,
+    /// As named character references where HTML defines one, e.g. `&eacute;`,
+    /// otherwise as numeric character references.
+    Named,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// How table rows with the wrong number of cells are handled. See `table_row_mismatch` in
+/// [`ExtensionOptions`] for more details.
+pub enum TableRowMismatch {
+    /// Short rows are padded with empty cells and surplus cells are dropped, as on GitHub.
+    #[default]
+    Pad,
+    /// The table ends before the first mismatched row, which is parsed as ordinary text.
+    Truncate,
+    /// The whole table is parsed as a paragraph.
+    Paragraph,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// How inputs longer than the limit are handled.  See `max_input_size` in
+/// [`ParseOptions`] for more details.
+pub enum OversizedInput {
+    /// The input is cut at the end of the last blank line within the limit,
+    /// or failing that the last line break, so that no block is cut short
+    /// where it can be helped.
+    #[default]
+    Truncate,
+    /// None of the input is parsed.  [`try_parse_document`] returns
+    /// [`InputTooLarge`]; otherwise the document is left empty but for its
+    /// [`NodeValue::Truncated`] node.
+    Reject,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// How link and image titles are rendered in HTML. See `link_titles` in [`RenderOptions`] for more details.
+pub enum LinkTitleStyle {
+    /// As a `title` attribute.
+    #[default]
+    Title,
+    /// As an `aria-label` attribute.
+    AriaLabel,
+    /// Not at all. Image captions from `figure_with_caption` are still rendered.
+    Omit,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// How HTML comments are rendered in HTML. See `html_comments` in [`RenderOptions`] for more details.
+pub enum HtmlCommentStyle {
+    /// Like any other raw HTML: omitted unless `unsafe_` is set, or escaped
+    /// with `escape`.
+    #[default]
+    Raw,
+    /// Always, even without `unsafe_`.  Comments which browsers could end
+    /// early, as `<!-->`, `<!--->` or at a `--!>`, are treated as other raw
+    /// HTML.
+    Keep,
+    /// Never.
+    Strip,
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(default))]
+/// Text generated by the HTML renderer, rather than taken from the document.
+/// Defaults to English. See `strings` in [`RenderOptions`].
+pub struct RenderStrings {
+    /// The label of a link from a footnote back to a reference to it, followed
+    /// by the reference's number.
+    pub footnote_backref: String,
+}
+
+impl Default for RenderStrings {
+    fn default() -> Self {
+        RenderStrings {
+            footnote_backref: "Back to reference".to_string(),
+        }
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(default))]
+/// The markup of footnotes in HTML.  Defaults to GitHub's.  See
+/// `footnote_style` in [`RenderOptions`].
+pub struct FootnoteStyle {
+    /// The element wrapping the footnote definitions.
+    pub section_element: String,
+
+    /// The class of the element wrapping the footnote definitions.  No
+    /// `class` attribute is written if empty.
+    pub section_class: String,
+
+    /// The text of links from a footnote back to references to it.
+    pub backref: String,
+
+    /// Whether to write links from footnotes back to references to them.
+    pub backrefs: bool,
+
+    /// Prepended to the IDs of footnotes and references to them, so that
+    /// several documents can be rendered into one page.
+    pub id_prefix: String,
+}
+
+impl Default for FootnoteStyle {
+    fn default() -> Self {
+        FootnoteStyle {
+            section_element: "section".to_string(),
+            section_class: "footnotes".to_string(),
+            backref: "↩".to_string(),
+            backrefs: true,
+            id_prefix: String::new(),
+        }
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(default))]
+/// The characters `smart` punctuation converts quotes into.  Defaults to
+/// English typography.  See `smart_quotes` in [`ParseOptions`].
+pub struct SmartQuotes {
+    /// Opens a double-quoted span.
+    pub double_open: String,
+
+    /// Closes a double-quoted span.
+    pub double_close: String,
+
+    /// Opens a single-quoted span.
+    pub single_open: String,
+
+    /// Closes a single-quoted span.
+    pub single_close: String,
+
+    /// Replaces a single quote which doesn't close a span, as in "it's".
+    pub apostrophe: String,
+}
+
+impl SmartQuotes {
+    /// English quotation marks: “double” and ‘single’, and ’ as apostrophe.
+    pub fn english() -> Self {
+        Self::new("“", "”", "‘", "’", "’")
+    }
+
+    /// German quotation marks: „double“ and ‚single‘, and ’ as apostrophe.
+    pub fn german() -> Self {
+        Self::new("„", "“", "‚", "‘", "’")
+    }
+
+    /// French quotation marks: «double» and ‹single›, and ’ as apostrophe.
+    /// Spaces inside the marks are left as written.
+    pub fn french() -> Self {
+        Self::new("«", "»", "‹", "›", "’")
+    }
+
+    fn new(
+        double_open: &str,
+        double_close: &str,
+        single_open: &str,
+        single_close: &str,
+        apostrophe: &str,
+    ) -> Self {
+        SmartQuotes {
+            double_open: double_open.to_string(),
+            double_close: double_close.to_string(),
+            single_open: single_open.to_string(),
+            single_close: single_close.to_string(),
+            apostrophe: apostrophe.to_string(),
+        }
+    }
+}
+
+impl Default for SmartQuotes {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 /// Options for bulleted list redering in markdown. See `link_style` in [`RenderOptions`] for more details.
 pub enum ListStyleType {
     /// The `-` character
@@ -2691,4 +5191,62 @@ pub enum ListStyleType {
     Plus = 43,
     /// The `*` character
     Star = 42,
+    /// The bullet the list was parsed with, or `-` for a list built without
+    /// one
+    Preserve = 0,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// Options for ordered list delimiters in markdown. See `ordered_list_style` in [`RenderOptions`] for more details.
+pub enum OrderedListStyleType {
+    /// The delimiter the list was parsed with
+    #[default]
+    Preserve,
+    /// The `.` character
+    Period,
+    /// The `)` character
+    Paren,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// Options for headings in markdown. See `heading_style` in [`RenderOptions`] for more details.
+pub enum HeadingStyleType {
+    /// ATX headings, `# Heading`
+    #[default]
+    Atx,
+    /// Setext headings, underlined with `=` or `-`
+    Setext,
+    /// The style the heading was parsed with
+    Preserve,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+/// Options for code fences in markdown. See `code_fence_style` in [`RenderOptions`] for more details.
+pub enum CodeFenceStyleType {
+    /// The `` ` `` character
+    #[default]
+    Backtick,
+    /// The `~` character
+    Tilde,
+    /// The fence the code block was parsed with, or `` ` `` for an indented
+    /// code block
+    Preserve,
 }
@@ -0,0 +1,15 @@
+use crate::strings::trim_slice;
+
+/// Whether `line` is a region marker, `<!-- comrak:off -->` or
+/// `<!-- comrak:on -->` according to `state`.  Whitespace around the marker
+/// and within the comment delimiters is ignored.
+pub(crate) fn is_marker(line: &[u8], state: &str) -> bool {
+    let inner = trim_slice(line)
+        .strip_prefix(b"<!--")
+        .and_then(|s| s.strip_suffix(b"-->"))
+        .map(trim_slice);
+    match inner.and_then(|s| s.strip_prefix(b"comrak:")) {
+        Some(s) => s == state.as_bytes(),
+        None => false,
+    }
+}
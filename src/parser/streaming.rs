@@ -0,0 +1,203 @@
+use std::mem;
+
+use typed_arena::Arena;
+
+use crate::nodes::AstNode;
+use crate::parser::{
+    alloc_document, append_truncated, set_offsets_from_line_starts, LineStarts, Options,
+    OversizedInput, Parser,
+};
+use crate::strings::{split_off_front_matter, trim_start_match};
+
+/// Parses a Markdown document given in pieces, such as chunks read from the
+/// network, without holding all of the input at once.
+///
+/// Lines may be split between pieces.  The result is the same as that of
+/// [`parse_document`](crate::parse_document) given the pieces joined
+/// together.
+///
+/// ```
+/// # use comrak::{format_html, Arena, Options, StreamingParser};
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let mut parser = StreamingParser::new(&arena, &options);
+/// parser.feed("# Hel");
+/// parser.feed("lo\n\n*wor");
+/// parser.feed("ld*\n");
+/// let root = parser.finish();
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(
+///     String::from_utf8(html).unwrap(),
+///     "<h1>Hello</h1>\n<p><em>world</em></p>\n"
+/// );
+/// ```
+///
+/// With [`ExtensionOptions::front_matter_delimiter`](crate::ExtensionOptions::front_matter_delimiter)
+/// set, input which may begin with front matter is held until the front
+/// matter ends.
+///
+/// As input already parsed can't be taken back, input longer than
+/// [`ParseOptions::max_input_size`](crate::ParseOptions::max_input_size) is
+/// truncated at the last line break within the limit, rather than the last
+/// blank line.
+pub struct StreamingParser<'a, 'o, 'c> {
+    parser: Parser<'a, 'o, 'c>,
+    linebuf: Vec<u8>,
+    line_starts: LineStarts,
+    // Input held until it's known whether, and where, it starts with front
+    // matter.
+    front_matter: Option<String>,
+    // The bytes of input parsed, and how many of them follow the last line
+    // break.
+    fed: usize,
+    partial: usize,
+    // The bytes of input dropped once it exceeded the limit on its size.
+    omitted: Option<usize>,
+}
+
+impl<'a, 'o, 'c: 'o> StreamingParser<'a, 'o, 'c> {
+    /// Starts parsing a document into `arena`.
+    pub fn new(arena: &'a Arena<AstNode<'a>>, options: &'o Options<'c>) -> Self {
+        let root = alloc_document(arena);
+        StreamingParser {
+            parser: Parser::new(arena, root, options),
+            linebuf: vec![],
+            line_starts: LineStarts::default(),
+            front_matter: options
+                .extension
+                .front_matter_delimiter
+                .as_ref()
+                .map(|_| String::new()),
+            fed: 0,
+            partial: 0,
+            omitted: None,
+        }
+    }
+
+    /// Parses the next piece of the document.
+    pub fn feed(&mut self, s: &str) {
+        if let Some(ref mut held) = self.front_matter {
+            held.push_str(s);
+            let delimiter = self
+                .parser
+                .options
+                .extension
+                .front_matter_delimiter
+                .as_ref();
+            let oversized = self
+                .parser
+                .options
+                .parse
+                .max_input_size
+                .map_or(false, |limit| held.len() > limit);
+            if !oversized
+                && delimiter.map_or(false, |delimiter| front_matter_pending(held, delimiter))
+            {
+                return;
+            }
+            let held = self.front_matter.take().unwrap();
+            self.feed_lines(&held);
+        } else {
+            self.feed_lines(s);
+        }
+    }
+
+    /// Finishes parsing the document, returning its root.
+    pub fn finish(mut self) -> &'a AstNode<'a> {
+        if let Some(held) = self.front_matter.take() {
+            self.feed_lines(&held);
+        }
+
+        let arena = self.parser.arena;
+        let root = self.parser.finish(mem::take(&mut self.linebuf));
+        let base = self.parser.front_matter_len;
+        set_offsets_from_line_starts(root, &self.line_starts.finish(), base, self.fed);
+        if let Some(omitted) = self.omitted {
+            if self.parser.options.parse.oversized_input == OversizedInput::Reject {
+                while let Some(child) = root.first_child() {
+                    child.detach();
+                }
+            }
+            append_truncated(arena, root, self.fed, omitted);
+        }
+        root
+    }
+
+    fn feed_lines(&mut self, s: &str) {
+        if let Some(ref mut omitted) = self.omitted {
+            *omitted += s.len();
+            return;
+        }
+
+        let s = match self.parser.options.parse.max_input_size {
+            Some(limit) if self.fed + s.len() <= limit => s,
+            None => s,
+            Some(_) if self.parser.options.parse.oversized_input == OversizedInput::Reject => {
+                self.linebuf.clear();
+                self.omitted = Some(self.fed + s.len());
+                self.fed = 0;
+                return;
+            }
+            Some(limit) => {
+                let kept = memchr::memrchr2(b'\n', b'\r', &s.as_bytes()[..limit - self.fed]);
+                match kept.map(|i| i + 1) {
+                    Some(kept) => {
+                        self.omitted = Some(s.len() - kept);
+                        &s[..kept]
+                    }
+                    None => {
+                        // Drop the part of the last line already given.
+                        self.linebuf.clear();
+                        self.fed -= self.partial;
+                        self.omitted = Some(self.partial + s.len());
+                        return;
+                    }
+                }
+            }
+        };
+
+        self.fed += s.len();
+        self.partial = match memchr::memrchr2(b'\n', b'\r', s.as_bytes()) {
+            Some(i) => s.len() - i - 1,
+            None => self.partial + s.len(),
+        };
+
+        let before = self.parser.front_matter_len;
+        self.parser.feed(&mut self.linebuf, s, false);
+        // Front matter is split off the first piece fed to the parser.
+        let front_matter = self.parser.front_matter_len - before;
+        self.line_starts.push(&s.as_bytes()[front_matter..]);
+    }
+}
+
+impl<'a, 'o, 'c> std::fmt::Debug for StreamingParser<'a, 'o, 'c> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamingParser").finish_non_exhaustive()
+    }
+}
+
+// Whether more input could change whether `s` starts with front matter, or
+// where it ends.
+fn front_matter_pending(s: &str, delimiter: &str) -> bool {
+    let s = trim_start_match(s, "\u{feff}");
+    if !s.starts_with(delimiter) {
+        return delimiter.starts_with(s);
+    }
+
+    let after = &s[delimiter.len()..];
+    if after.is_empty() || after == "\r" {
+        return true;
+    }
+    if !after.starts_with('\n') && !after.starts_with("\r\n") {
+        return false;
+    }
+
+    match split_off_front_matter(s, delimiter) {
+        // A blank line after the closing delimiter is part of the front
+        // matter.
+        Some((_, rest)) => rest.is_empty() || rest == "\r",
+        None => true,
+    }
+}
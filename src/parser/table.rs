@@ -1,11 +1,12 @@
 use crate::arena_tree::Node;
 use crate::nodes;
-use crate::nodes::{Ast, AstNode, NodeTable, NodeValue, TableAlignment};
-use crate::parser::Parser;
+use crate::nodes::{Ast, AstNode, NodeTable, NodeTableCell, NodeValue, TableAlignment};
+use crate::parser::{Parser, TableRowMismatch};
 use crate::scanners;
-use crate::strings::trim;
+use crate::strings::{self, trim};
 use std::cell::RefCell;
 use std::cmp::min;
+use std::str;
 
 use super::inlines::count_newlines;
 
@@ -43,18 +44,23 @@ fn try_opening_header<'a>(
     }
 
     let spoiler = parser.options.extension.spoiler;
+    let colspans = parser.options.extension.table_colspans;
 
-    let delimiter_row = match row(&line[parser.first_nonspace..], spoiler) {
+    let delimiter_row = match row(&line[parser.first_nonspace..], spoiler, false) {
         Some(delimiter_row) => delimiter_row,
         None => return Some((container, false, true)),
     };
 
-    let header_row = match row(container.data.borrow().content.as_bytes(), spoiler) {
+    let header_row = match row(
+        container.data.borrow().content.as_bytes(),
+        spoiler,
+        colspans,
+    ) {
         Some(header_row) => header_row,
         None => return Some((container, false, true)),
     };
 
-    if header_row.cells.len() != delimiter_row.cells.len() {
+    if header_row.columns() != delimiter_row.cells.len() {
         return Some((container, false, true));
     }
 
@@ -79,15 +85,26 @@ fn try_opening_header<'a>(
     }
 
     let start = container.data.borrow().sourcepos.start;
-    let child = Ast::new(
+    let num_columns = alignments.len();
+    let mut child = Ast::new(
         NodeValue::Table(NodeTable {
             alignments,
-            num_columns: header_row.cells.len(),
+            num_columns,
             num_rows: 0,
             num_nonempty_cells: 0,
         }),
         start,
     );
+    if parser.options.extension.table_row_mismatch == TableRowMismatch::Paragraph {
+        // Keep the source lines in case the table is downgraded to a
+        // paragraph when finalized.
+        let container_ast = container.data.borrow();
+        let (newlines, _) =
+            count_newlines(&container_ast.content.as_bytes()[..header_row.paragraph_offset]);
+        child.content = container_ast.content[header_row.paragraph_offset..].to_string();
+        child.line_offsets = container_ast.line_offsets[newlines..].to_vec();
+        stash_line(&mut child, parser, line);
+    }
     let table = parser.arena.alloc(Node::new(RefCell::new(child)));
     container.append(table);
 
@@ -111,6 +128,7 @@ fn try_opening_header<'a>(
             start.column + cell.start_offset - header_row.paragraph_offset,
         );
         let ast = &mut ast_cell.data.borrow_mut();
+        ast.table_cell = table_cell(cell.colspan);
         ast.sourcepos.start.line = start.line;
         ast.sourcepos.end =
             start.column_add((cell.end_offset - header_row.paragraph_offset) as isize);
@@ -124,7 +142,7 @@ fn try_opening_header<'a>(
         i += 1;
     }
 
-    incr_table_row_count(container, i);
+    incr_table_row_count(container, header_row.columns());
 
     let offset = line.len() - 1 - parser.offset;
     parser.advance_offset(line, offset, false);
@@ -148,11 +166,20 @@ fn try_opening_row<'a>(
 
     let sourcepos = container.data.borrow().sourcepos;
     let spoiler = parser.options.extension.spoiler;
-    let this_row = match row(&line[parser.first_nonspace..], spoiler) {
+    let colspans = parser.options.extension.table_colspans;
+    let this_row = match row(&line[parser.first_nonspace..], spoiler, colspans) {
         Some(this_row) => this_row,
         None => return None,
     };
 
+    let downgrading = parser.options.extension.table_row_mismatch == TableRowMismatch::Paragraph;
+    if this_row.columns() != alignments.len() {
+        report_mismatch(parser, line, this_row.columns(), alignments.len());
+    }
+    if downgrading {
+        stash_line(&mut container.data.borrow_mut(), parser, line);
+    }
+
     let new_row = parser.add_child(
         container,
         NodeValue::TableRow(false),
@@ -165,14 +192,23 @@ fn try_opening_row<'a>(
     let mut i = 0;
     let mut last_column = sourcepos.start.column;
 
-    while i < min(alignments.len(), this_row.cells.len()) {
-        let cell = &this_row.cells[i];
+    // Rows are kept as written when the table may be downgraded, so that
+    // mismatches can be found again when it is finalized.
+    for cell in &this_row.cells {
+        let colspan = if downgrading {
+            cell.colspan
+        } else if i < alignments.len() {
+            min(cell.colspan, alignments.len() - i)
+        } else {
+            break;
+        };
         let cell_node = parser.add_child(
             new_row,
             NodeValue::TableCell,
             sourcepos.start.column + cell.start_offset,
         );
         let cell_ast = &mut cell_node.data.borrow_mut();
+        cell_ast.table_cell = table_cell(colspan);
         cell_ast.internal_offset = cell.internal_offset;
         cell_ast.sourcepos.end.column = sourcepos.start.column + cell.end_offset;
         cell_ast.content.clone_from(&cell.content);
@@ -182,12 +218,12 @@ fn try_opening_row<'a>(
 
         last_column = cell_ast.sourcepos.end.column;
 
-        i += 1;
+        i += colspan;
     }
 
     incr_table_row_count(container, i);
 
-    while i < alignments.len() {
+    while !downgrading && i < alignments.len() {
         parser.add_child(new_row, NodeValue::TableCell, last_column);
         i += 1;
     }
@@ -203,14 +239,33 @@ struct Row {
     cells: Vec<Cell>,
 }
 
+impl Row {
+    // The number of columns the row's cells span.
+    fn columns(&self) -> usize {
+        self.cells.iter().map(|cell| cell.colspan).sum()
+    }
+}
+
 struct Cell {
     start_offset: usize,
     end_offset: usize,
     internal_offset: usize,
     content: String,
+    colspan: usize,
+}
+
+// With `colspans`, a pipe directly following the one which ends a cell
+// makes the cell span another column instead of starting an empty one.
+// The span to record on a table cell, for those spanning several columns.
+fn table_cell(colspan: usize) -> Option<NodeTableCell> {
+    if colspan > 1 {
+        Some(NodeTableCell { colspan })
+    } else {
+        None
+    }
 }
 
-fn row(string: &[u8], spoiler: bool) -> Option<Row> {
+fn row(string: &[u8], spoiler: bool, colspans: bool) -> Option<Row> {
     let len = string.len();
     let mut cells: Vec<Cell> = vec![];
 
@@ -224,7 +279,16 @@ fn row(string: &[u8], spoiler: bool) -> Option<Row> {
         let cell_matched = scanners::table_cell(&string[offset..], spoiler).unwrap_or(0);
         let pipe_matched = scanners::table_cell_end(&string[offset + cell_matched..]).unwrap_or(0);
 
-        if cell_matched > 0 || pipe_matched > 0 {
+        let spans = colspans
+            && cell_matched == 0
+            && pipe_matched > 0
+            && offset > paragraph_offset
+            && string[offset - 1] == b'|'
+            && !cells.is_empty();
+
+        if spans {
+            cells.last_mut().unwrap().colspan += 1;
+        } else if cell_matched > 0 || pipe_matched > 0 {
             let mut cell = unescape_pipes(&string[offset..offset + cell_matched]);
             trim(&mut cell);
 
@@ -247,6 +311,7 @@ fn row(string: &[u8], spoiler: bool) -> Option<Row> {
                 end_offset: offset + cell_matched - 1,
                 internal_offset,
                 content: String::from_utf8(cell).unwrap(),
+                colspan: 1,
             });
         }
 
@@ -372,6 +437,64 @@ fn get_num_autocompleted_cells<'a>(container: &'a AstNode<'a>) -> usize {
     };
 }
 
-pub fn matches(line: &[u8], spoiler: bool) -> bool {
-    row(line, spoiler).is_some()
+/// Whether `line` continues a table with `num_columns` columns.
+pub fn matches_row(parser: &Parser, num_columns: usize, line: &[u8]) -> bool {
+    let spoiler = parser.options.extension.spoiler;
+    let colspans = parser.options.extension.table_colspans;
+    match row(&line[parser.first_nonspace..], spoiler, colspans) {
+        None => false,
+        Some(row) if row.columns() != num_columns => {
+            if parser.options.extension.table_row_mismatch != TableRowMismatch::Truncate {
+                return true;
+            }
+            report_mismatch(parser, line, row.columns(), num_columns);
+            false
+        }
+        Some(_) => true,
+    }
+}
+
+fn report_mismatch(parser: &Parser, line: &[u8], found: usize, expected: usize) {
+    let mut end = line.len();
+    while end > 0 && strings::is_line_end_char(line[end - 1]) {
+        end -= 1;
+    }
+    parser.report(
+        (
+            parser.line_number,
+            parser.first_nonspace + 1,
+            parser.line_number,
+            end,
+        )
+            .into(),
+        format!(
+            "table row has {} cell{}, expected {}",
+            found,
+            if found == 1 { "" } else { "s" },
+            expected
+        ),
+    );
+}
+
+fn stash_line(ast: &mut Ast, parser: &Parser, line: &[u8]) {
+    ast.line_offsets.push(parser.first_nonspace);
+    ast.content
+        .push_str(str::from_utf8(&line[parser.first_nonspace..]).unwrap());
+}
+
+/// Whether any body row of `table` spans other than `num_columns` columns. Only
+/// meaningful under [`TableRowMismatch::Paragraph`], where rows aren't
+/// normalized.
+pub fn has_mismatched_rows<'a>(table: &'a AstNode<'a>, num_columns: usize) -> bool {
+    table
+        .children()
+        .any(|row| row.children().map(nodes::table_cell_colspan).sum::<usize>() != num_columns)
+}
+
+/// Turns a table back into a paragraph of its source lines.
+pub fn downgrade_to_paragraph<'a>(table: &'a AstNode<'a>, ast: &mut Ast) {
+    while let Some(row) = table.first_child() {
+        row.detach();
+    }
+    ast.value = NodeValue::Paragraph;
 }
@@ -0,0 +1,81 @@
+/// A template variable placeholder, `{{name}}` or `{{ name | filter }}`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeTemplateVariable {
+    /// The name of the variable.
+    pub name: String,
+
+    /// The filters to apply to the variable's value, in order.  Each is
+    /// trimmed but otherwise uninterpreted, so may include arguments.
+    pub filters: Vec<String>,
+}
+
+impl NodeTemplateVariable {
+    /// The placeholder as it would be written in Markdown.
+    pub fn placeholder(&self) -> String {
+        let mut s = format!("{{{{{}", self.name);
+        for filter in &self.filters {
+            s.push_str(" | ");
+            s.push_str(filter);
+        }
+        s.push_str("}}");
+        s
+    }
+}
+
+/// Scans a placeholder at the start of `input`, returning it and its length.
+/// Variable names consist of ASCII alphanumerics, `_`, `-` and `.`; filters
+/// may contain anything but `|`, `}` and line endings.
+pub(crate) fn placeholder(input: &[u8]) -> Option<(NodeTemplateVariable, usize)> {
+    if !input.starts_with(b"{{") {
+        return None;
+    }
+
+    // Stopping at a line ending or another `{` as well as the first `}`
+    // keeps each scan short, however many placeholders are left unclosed.
+    let rest = &input[2..];
+    let end = memchr::memchr3(b'}', b'{', b'\n', rest).unwrap_or(rest.len());
+    let end = memchr::memchr(b'\r', &rest[..end]).unwrap_or(end);
+    if !rest[end..].starts_with(b"}}") {
+        return None;
+    }
+    let inner = std::str::from_utf8(&rest[..end]).ok()?;
+
+    let mut parts = inner.split('|').map(str::trim);
+    let name = parts.next()?;
+    if name.is_empty()
+        || !name
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, b'_' | b'-' | b'.'))
+    {
+        return None;
+    }
+
+    let filters: Vec<String> = parts.map(String::from).collect();
+    if filters.iter().any(String::is_empty) {
+        return None;
+    }
+
+    Some((
+        NodeTemplateVariable {
+            name: name.to_string(),
+            filters,
+        },
+        end + 4,
+    ))
+}
+
+/// Scans a template tag, `{% ... %}` or `{{ ... }}`, at the start of `input`,
+/// returning its length.  Tags end at the first matching closing delimiter
+/// and may not span lines.
+pub(crate) fn tag(input: &[u8]) -> Option<usize> {
+    let close: &[u8] = match input.get(..2)? {
+        b"{%" => b"%}",
+        b"{{" => b"}}",
+        _ => return None,
+    };
+    let rest = &input[2..];
+    let line_end = memchr::memchr2(b'\n', b'\r', rest).unwrap_or(rest.len());
+    let end = memchr::memmem::find(&rest[..line_end], close)?;
+    Some(end + 4)
+}
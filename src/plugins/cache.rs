@@ -0,0 +1,77 @@
+//! A simple in-memory [`CodeBlockCache`].
+
+use crate::adapters::{CodeBlockCache, CodeBlockCacheKey};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// An unbounded, thread-safe, in-memory [`CodeBlockCache`].
+///
+/// Entries are never evicted; call [`InMemoryCodeBlockCache::clear`] when the highlighter's
+/// configuration changes in a way its `config_key` doesn't capture, or to release memory.
+///
+/// ```
+/// # use comrak::{markdown_to_html_with_plugins, Options, Plugins};
+/// # use comrak::adapters::SyntaxHighlighterAdapter;
+/// # use comrak::plugins::cache::InMemoryCodeBlockCache;
+/// # use std::collections::HashMap;
+/// # use std::io::{self, Write};
+/// # struct Highlighter;
+/// # impl SyntaxHighlighterAdapter for Highlighter {
+/// #     fn write_highlighted(&self, output: &mut dyn Write, _lang: Option<&str>, code: &str) -> io::Result<()> {
+/// #         output.write_all(code.as_bytes())
+/// #     }
+/// #     fn write_pre_tag(&self, output: &mut dyn Write, _attributes: HashMap<String, String>) -> io::Result<()> {
+/// #         output.write_all(b"<pre>")
+/// #     }
+/// #     fn write_code_tag(&self, output: &mut dyn Write, _attributes: HashMap<String, String>) -> io::Result<()> {
+/// #         output.write_all(b"<code>")
+/// #     }
+/// # }
+/// let highlighter = Highlighter;
+/// let cache = InMemoryCodeBlockCache::new();
+/// let mut plugins = Plugins::default();
+/// plugins.render.codefence_syntax_highlighter = Some(&highlighter);
+/// plugins.render.codefence_highlight_cache = Some(&cache);
+///
+/// let input = "```rust\nfn main() {}\n```\n";
+/// let first = markdown_to_html_with_plugins(input, &Options::default(), &plugins);
+/// let second = markdown_to_html_with_plugins(input, &Options::default(), &plugins);
+/// assert_eq!(first, second);
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct InMemoryCodeBlockCache {
+    entries: Mutex<HashMap<CodeBlockCacheKey, Vec<u8>>>,
+}
+
+impl InMemoryCodeBlockCache {
+    /// Construct a new, empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of cached code blocks.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Returns `true` if nothing has been cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached entries.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+impl CodeBlockCache for InMemoryCodeBlockCache {
+    fn get(&self, key: &CodeBlockCacheKey) -> Option<Vec<u8>> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    fn insert(&self, key: CodeBlockCacheKey, output: Vec<u8>) {
+        self.entries.lock().unwrap().insert(key, output);
+    }
+}
@@ -0,0 +1,140 @@
+//! An [`ImageResolver`] which embeds local image files as `data:` URIs.
+
+use crate::adapters::{ImageMeta, ImageResolver};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Embeds images with relative sources as `data:` URIs holding the contents
+/// of the files they name, so that HTML can be viewed without them.
+///
+/// Sources are resolved against `root`, ignoring any query or fragment and
+/// decoding percent-escapes.  An image is left as it is if its source is an
+/// absolute URL or path, or a path leading out of `root` through `..`; if
+/// its file can't be read or is larger than `max_size`; or if its extension
+/// isn't that of a PNG, GIF, JPEG, WebP, AVIF or SVG image.  AVIF and SVG
+/// `data:` URIs are only rendered with
+/// [`RenderOptions::unsafe_`](crate::RenderOptions::unsafe_) set.
+///
+/// ```no_run
+/// # use comrak::{markdown_to_html_with_plugins, Options, Plugins};
+/// # use comrak::plugins::embed::FileImageEmbedder;
+/// let embedder = FileImageEmbedder::new("docs");
+/// let mut plugins = Plugins::default();
+/// plugins.render.image_resolver = Some(&embedder);
+/// let html = markdown_to_html_with_plugins("![Logo](img/logo.png)\n", &Options::default(), &plugins);
+/// assert!(html.starts_with("<p><img src=\"data:image/png;base64,"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileImageEmbedder {
+    /// The directory relative sources are resolved against.
+    pub root: PathBuf,
+
+    /// The size in bytes of the largest file to embed, if any.
+    pub max_size: Option<u64>,
+}
+
+impl FileImageEmbedder {
+    /// Construct an embedder resolving sources against `root`, with no
+    /// maximum size.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FileImageEmbedder {
+            root: root.into(),
+            max_size: None,
+        }
+    }
+}
+
+impl ImageResolver for FileImageEmbedder {
+    fn resolve(&self, image: &ImageMeta) -> Option<String> {
+        let url = &image.url;
+        let path = &url[..url.find(['?', '#']).unwrap_or(url.len())];
+        if path.is_empty()
+            || path.starts_with('/')
+            || path
+                .find(':')
+                .map_or(false, |colon| !path[..colon].contains('/'))
+        {
+            return None;
+        }
+
+        let path = percent_decode(path)?;
+        let path = Path::new(&path);
+        if !path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return None;
+        }
+
+        let path = self.root.join(path);
+        let mime = match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "png" => "image/png",
+            "gif" => "image/gif",
+            "jpg" | "jpeg" => "image/jpeg",
+            "webp" => "image/webp",
+            "avif" => "image/avif",
+            "svg" => "image/svg+xml",
+            _ => return None,
+        };
+        if let Some(max_size) = self.max_size {
+            if fs::metadata(&path).ok()?.len() > max_size {
+                return None;
+            }
+        }
+        let data = fs::read(&path).ok()?;
+        Some(data_uri(mime, &data))
+    }
+}
+
+/// Returns a `data:` URI holding `data` encoded as base64, with the given
+/// media type.
+///
+/// ```
+/// # use comrak::plugins::embed::data_uri;
+/// assert_eq!(data_uri("text/plain", b"Hi!"), "data:text/plain;base64,SGkh");
+/// ```
+pub fn data_uri(mime: &str, data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut uri = format!("data:{};base64,", mime);
+    uri.reserve((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                uri.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                uri.push('=');
+            }
+        }
+    }
+    uri
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match hex {
+            Some(b) if bytes[i] == b'%' => {
+                decoded.push(b);
+                i += 3;
+            }
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
@@ -1,5 +1,8 @@
 //! Plugins for enhancing the default implementation of comrak can be defined in this module.
 
+pub mod cache;
+pub mod embed;
+
 #[cfg(feature = "syntect")]
 #[cfg_attr(docsrs, doc(cfg(feature = "syntect")))]
 pub mod syntect;
@@ -2,7 +2,8 @@
 
 use crate::adapters::SyntaxHighlighterAdapter;
 use crate::html;
-use std::collections::{hash_map, HashMap};
+use std::collections::hash_map;
+use std::collections::HashMap;
 use std::io::{self, Write};
 use syntect::easy::HighlightLines;
 use syntect::highlighting::{Color, ThemeSet};
@@ -130,6 +131,15 @@ impl SyntaxHighlighterAdapter for SyntectAdapter {
     ) -> io::Result<()> {
         html::write_opening_tag(output, "code", attributes)
     }
+
+    fn config_key(&self) -> String {
+        // Custom syntax and theme sets can't be described; use a separate cache per adapter if
+        // they differ between adapters sharing a theme name.
+        match self.theme {
+            Some(ref theme) => format!("syntect inline {}", theme),
+            None => "syntect classes".to_string(),
+        }
+    }
 }
 
 struct SyntectPreAttributes {
@@ -0,0 +1,166 @@
+//! Punycode encoding of internationalized domain names, per
+//! [RFC 3492](https://www.rfc-editor.org/rfc/rfc3492).
+
+use std::borrow::Cow;
+
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 128;
+
+fn adapt(mut delta: u32, num_points: u32, first_time: bool) -> u32 {
+    delta /= if first_time { DAMP } else { 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (((BASE - T_MIN + 1) * delta) / (delta + SKEW))
+}
+
+fn digit(d: u32) -> char {
+    (if d < 26 {
+        b'a' + d as u8
+    } else {
+        b'0' + (d - 26) as u8
+    }) as char
+}
+
+/// Encodes a single label, without the `xn--` prefix. Returns `None` on
+/// overflow.
+pub fn encode(label: &str) -> Option<String> {
+    let input: Vec<u32> = label.chars().map(|c| c as u32).collect();
+    let mut output: String = label.chars().filter(char::is_ascii).collect();
+    let basic = output.len() as u32;
+    let mut handled = basic;
+    if basic > 0 {
+        output.push('-');
+    }
+
+    let mut n = INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+
+    while (handled as usize) < input.len() {
+        let m = *input.iter().filter(|&&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+
+        for &c in &input {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = if k <= bias {
+                        T_MIN
+                    } else if k >= bias + T_MAX {
+                        T_MAX
+                    } else {
+                        k - bias
+                    };
+                    if q < t {
+                        break;
+                    }
+                    output.push(digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push(digit(q));
+                bias = adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+
+        delta = delta.checked_add(1)?;
+        n += 1;
+    }
+
+    Some(output)
+}
+
+/// Converts a domain name to its ASCII form, encoding each label which
+/// contains non-ASCII characters. Such labels are lowercased first.
+pub fn domain_to_ascii(domain: &str) -> Option<String> {
+    let mut out = String::with_capacity(domain.len());
+    for (i, label) in domain.split('.').enumerate() {
+        if i > 0 {
+            out.push('.');
+        }
+        if label.is_ascii() {
+            out.push_str(label);
+        } else {
+            out.push_str("xn--");
+            out.push_str(&encode(&label.to_lowercase())?);
+        }
+    }
+    Some(out)
+}
+
+/// Converts the host of an absolute URL (`scheme://host/...`) or the domain
+/// of a `mailto:` address to its ASCII form. The rest of the URL is left
+/// as-is; non-ASCII bytes there are percent-encoded when written.
+pub fn url_to_ascii(url: &[u8]) -> Cow<'_, [u8]> {
+    let url = match std::str::from_utf8(url) {
+        Ok(url) if !url.is_ascii() => url,
+        _ => return Cow::Borrowed(url),
+    };
+
+    let (start, end) = if let Some(range) = host_range(url) {
+        range
+    } else if url
+        .get(..7)
+        .map_or(false, |scheme| scheme.eq_ignore_ascii_case("mailto:"))
+    {
+        let end = url.find('?').unwrap_or(url.len());
+        match url[..end].rfind('@') {
+            Some(at) => (at + 1, end),
+            None => return Cow::Borrowed(url.as_bytes()),
+        }
+    } else {
+        return Cow::Borrowed(url.as_bytes());
+    };
+
+    match domain_to_ascii(&url[start..end]) {
+        Some(host) => {
+            let mut out = Vec::with_capacity(url.len());
+            out.extend_from_slice(&url.as_bytes()[..start]);
+            out.extend_from_slice(host.as_bytes());
+            out.extend_from_slice(&url.as_bytes()[end..]);
+            Cow::Owned(out)
+        }
+        None => Cow::Borrowed(url.as_bytes()),
+    }
+}
+
+/// The byte range of the host of an absolute URL (`scheme://host/...`),
+/// without any userinfo or port.
+pub(crate) fn host_range(url: &str) -> Option<(usize, usize)> {
+    let scheme_end = url.find("://")?;
+    let scheme = &url.as_bytes()[..scheme_end];
+    if !scheme.first()?.is_ascii_alphabetic()
+        || !scheme
+            .iter()
+            .all(|&b| b.is_ascii_alphanumeric() || b"+-.".contains(&b))
+    {
+        return None;
+    }
+
+    let start = scheme_end + 3;
+    let end = url[start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |e| start + e);
+    // Skip any userinfo, and stop before any port.
+    let start = url[start..end]
+        .rfind('@')
+        .map_or(start, |at| start + at + 1);
+    let end = url[start..end].find(':').map_or(end, |colon| start + colon);
+    Some((start, end))
+}
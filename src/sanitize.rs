@@ -0,0 +1,390 @@
+//! Sanitization of raw HTML in a parsed document, before rendering.
+//!
+//! Raw HTML is omitted from the HTML output unless
+//! [`RenderOptions::unsafe_`](crate::RenderOptions::unsafe_) is set.
+//! [`sanitize_html`] makes the raw HTML in a document safe to render with
+//! it set, by passing each piece through an [`HtmlSanitizer`]: either the
+//! built-in [`Allowlist`], or a closure wrapping a sanitization library.
+//! Because the document is sanitized in place, node source positions are
+//! kept, and code blocks and other text aren't escaped twice.
+//!
+//! `unsafe_` also allows dangerous link and image URLs; set
+//! [`RenderOptions::url_schemes`](crate::RenderOptions::url_schemes) to
+//! restrict them.
+//!
+//! ```
+//! # use comrak::{format_html, parse_document, Arena, Options};
+//! # use comrak::sanitize::{sanitize_html, Allowlist};
+//! let arena = Arena::new();
+//! let mut options = Options::default();
+//! let root = parse_document(
+//!     &arena,
+//!     "<div onclick=\"steal()\"><b>Hi</b><script>steal()</script></div>\n\n\
+//!      <a href=\"javascript:steal()\" title=x>*there*</a>\n",
+//!     &options,
+//! );
+//!
+//! sanitize_html(root, &Allowlist::default());
+//!
+//! options.render.unsafe_ = true;
+//! let mut html = vec![];
+//! format_html(root, &options, &mut html).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(html).unwrap(),
+//!     "<div><b>Hi</b></div>\n<p><a title=\"x\"><em>there</em></a></p>\n"
+//! );
+//! ```
+
+use std::collections::HashSet;
+
+use crate::entity;
+use crate::html::escape;
+use crate::nodes::{
+    AstNode, ContainerKind, NodeComponent, NodeContainer, NodeHtmlBlock, NodeValue,
+};
+
+/// Makes raw HTML safe to render.
+///
+/// A closure from `&str` to `String` is a sanitizer which drops every
+/// attribute given by an attribute block.
+pub trait HtmlSanitizer {
+    /// Returns a safe version of a fragment of raw HTML.  Raw HTML blocks
+    /// are passed whole; inline raw HTML is passed one tag at a time.
+    fn sanitize_html(&self, html: &str) -> String;
+
+    /// Whether an attribute given to a heading or fenced code block by an
+    /// attribute block may be rendered.  Attributes which aren't are removed.
+    fn allow_attribute(&self, name: &str, value: &str) -> bool {
+        let _ = (name, value);
+        false
+    }
+}
+
+impl<F> HtmlSanitizer for F
+where
+    F: Fn(&str) -> String,
+{
+    fn sanitize_html(&self, html: &str) -> String {
+        self(html)
+    }
+}
+
+/// Sanitizes the raw HTML in the document: raw HTML blocks and inline
+/// HTML, raw regions, JSX components and template tags, along with the attributes given
+/// by attribute blocks to headings, fenced code blocks and container blocks.
+pub fn sanitize_html<'a>(root: &'a AstNode<'a>, sanitizer: &dyn HtmlSanitizer) {
+    for node in root.descendants() {
+        match node.data.borrow_mut().value {
+            NodeValue::HtmlBlock(NodeHtmlBlock {
+                ref mut literal, ..
+            })
+            | NodeValue::ComponentBlock(NodeComponent {
+                ref mut literal, ..
+            })
+            | NodeValue::ComponentInline(NodeComponent {
+                ref mut literal, ..
+            })
+            | NodeValue::Raw(ref mut literal)
+            | NodeValue::TemplateTag(ref mut literal)
+            | NodeValue::HtmlInline(ref mut literal) => {
+                *literal = sanitizer.sanitize_html(literal);
+            }
+            NodeValue::Heading(ref mut nh) => nh
+                .attributes
+                .attributes
+                .retain(|(name, value)| sanitizer.allow_attribute(name, value)),
+            NodeValue::CodeBlock(ref mut ncb) => ncb
+                .attributes
+                .attributes
+                .retain(|(name, value)| sanitizer.allow_attribute(name, value)),
+            NodeValue::Container(NodeContainer {
+                kind: ContainerKind::Named,
+                ref mut attributes,
+                ..
+            }) => attributes
+                .attributes
+                .retain(|(name, value)| sanitizer.allow_attribute(name, value)),
+            _ => (),
+        }
+    }
+}
+
+/// A sanitizer which keeps only the elements and attributes it lists.
+///
+/// Tags of other elements are removed, but their content is kept, except for
+/// `script` and `style` elements which are removed entirely.  Comments,
+/// processing instructions and declarations are removed.  URLs in attributes
+/// must be relative or use one of the listed schemes.
+#[derive(Debug, Clone)]
+pub struct Allowlist {
+    /// The names of the elements to keep, in lowercase.
+    ///
+    /// Defaults to common formatting and structural elements.
+    pub tags: HashSet<String>,
+
+    /// The names of the attributes to keep, in lowercase, on any element.
+    ///
+    /// Defaults to `alt`, `href`, `src`, `title`, `width`, `height`,
+    /// `align`, `colspan`, `rowspan`, `start` and `open`.
+    pub attributes: HashSet<String>,
+
+    /// The names of the attributes whose values are URLs, in lowercase.
+    ///
+    /// Defaults to `href`, `src` and `cite`.
+    pub url_attributes: HashSet<String>,
+
+    /// The schemes URLs may use, in lowercase.
+    ///
+    /// Defaults to `http`, `https` and `mailto`.
+    pub url_schemes: HashSet<String>,
+}
+
+fn set(items: &[&str]) -> HashSet<String> {
+    items.iter().map(|item| item.to_string()).collect()
+}
+
+impl Default for Allowlist {
+    fn default() -> Self {
+        Allowlist {
+            tags: set(&[
+                "a",
+                "abbr",
+                "b",
+                "blockquote",
+                "br",
+                "caption",
+                "code",
+                "dd",
+                "del",
+                "details",
+                "div",
+                "dl",
+                "dt",
+                "em",
+                "h1",
+                "h2",
+                "h3",
+                "h4",
+                "h5",
+                "h6",
+                "hr",
+                "i",
+                "img",
+                "ins",
+                "kbd",
+                "li",
+                "mark",
+                "ol",
+                "p",
+                "pre",
+                "q",
+                "s",
+                "samp",
+                "span",
+                "strong",
+                "sub",
+                "summary",
+                "sup",
+                "table",
+                "tbody",
+                "td",
+                "tfoot",
+                "th",
+                "thead",
+                "tr",
+                "u",
+                "ul",
+            ]),
+            attributes: set(&[
+                "alt", "href", "src", "title", "width", "height", "align", "colspan", "rowspan",
+                "start", "open",
+            ]),
+            url_attributes: set(&["href", "src", "cite"]),
+            url_schemes: set(&["http", "https", "mailto"]),
+        }
+    }
+}
+
+impl Allowlist {
+    fn allowed_url(&self, url: &str) -> bool {
+        // Browsers ignore surrounding whitespace and control characters, and
+        // tabs and newlines anywhere.
+        let url = url.trim_matches(|c: char| c <= ' ');
+        match url.find([':', '/', '?', '#']) {
+            Some(colon) if url.as_bytes()[colon] == b':' => {
+                let scheme: String = url[..colon]
+                    .chars()
+                    .filter(|&c| c > ' ')
+                    .collect::<String>()
+                    .to_ascii_lowercase();
+                self.url_schemes.contains(&scheme)
+            }
+            _ => true,
+        }
+    }
+
+    fn allowed(&self, name: &str, value: &str) -> bool {
+        self.attributes.contains(name)
+            && (!self.url_attributes.contains(name) || self.allowed_url(value))
+    }
+
+    // Writes the tag at the start of `html` if it's allowed, returning the
+    // tag's name, whether it's a closing tag, and its length; or None if
+    // `html` doesn't start with a tag.
+    fn tag(&self, html: &str, out: &mut Vec<u8>) -> Option<(String, bool, usize)> {
+        let bytes = html.as_bytes();
+        let closing = bytes.get(1) == Some(&b'/');
+        let mut i = if closing { 2 } else { 1 };
+        if !bytes.get(i)?.is_ascii_alphabetic() {
+            return None;
+        }
+        let start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'-') {
+            i += 1;
+        }
+        let name = html[start..i].to_ascii_lowercase();
+
+        let mut attributes = vec![];
+        let mut self_closing = false;
+        loop {
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            match bytes.get(i)? {
+                b'>' => break,
+                b'/' if bytes.get(i + 1) == Some(&b'>') => {
+                    self_closing = true;
+                    i += 1;
+                    break;
+                }
+                _ if closing => return None,
+                _ => (),
+            }
+
+            let start = i;
+            while i < bytes.len() && !b" \t\r\n\x0c/>=".contains(&bytes[i]) {
+                i += 1;
+            }
+            if i == start {
+                i += 1;
+                continue;
+            }
+            let attr = html[start..i].to_ascii_lowercase();
+
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            let value = if bytes.get(i) == Some(&b'=') {
+                i += 1;
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                let (start, end) = match bytes.get(i)? {
+                    &quote @ (b'"' | b'\'') => {
+                        let end = i + 1 + memchr::memchr(quote, &bytes[i + 1..])?;
+                        let range = (i + 1, end);
+                        i = end + 1;
+                        range
+                    }
+                    _ => {
+                        let start = i;
+                        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>'
+                        {
+                            i += 1;
+                        }
+                        (start, i)
+                    }
+                };
+                Some(String::from_utf8(entity::unescape_html(&bytes[start..end])).ok()?)
+            } else {
+                None
+            };
+            attributes.push((attr, value));
+        }
+
+        if self.tags.contains(&name) {
+            out.push(b'<');
+            if closing {
+                out.push(b'/');
+            }
+            out.extend_from_slice(name.as_bytes());
+            for (attr, value) in attributes {
+                match value {
+                    Some(value) if self.allowed(&attr, &value) => {
+                        out.push(b' ');
+                        out.extend_from_slice(attr.as_bytes());
+                        out.extend_from_slice(b"=\"");
+                        escape(out, value.as_bytes()).unwrap();
+                        out.push(b'"');
+                    }
+                    None if self.allowed(&attr, "") => {
+                        out.push(b' ');
+                        out.extend_from_slice(attr.as_bytes());
+                    }
+                    _ => (),
+                }
+            }
+            if self_closing {
+                out.extend_from_slice(b" /");
+            }
+            out.push(b'>');
+        }
+        Some((name, closing, i + 1))
+    }
+}
+
+impl HtmlSanitizer for Allowlist {
+    fn sanitize_html(&self, html: &str) -> String {
+        let mut out = Vec::with_capacity(html.len());
+        let mut dropping: Option<String> = None;
+        let mut i = 0;
+
+        while let Some(lt) = memchr::memchr(b'<', &html.as_bytes()[i..]) {
+            if dropping.is_none() {
+                out.extend_from_slice(&html.as_bytes()[i..i + lt]);
+            }
+            i += lt;
+            let rest = &html[i..];
+
+            let skip_to = |end: &str| rest.find(end).map_or(rest.len(), |e| e + end.len());
+            if rest.starts_with("<!--") {
+                i += skip_to("-->");
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                i += skip_to(">");
+            } else {
+                let mut tag = vec![];
+                match self.tag(rest, &mut tag) {
+                    Some((name, closing, len)) => {
+                        match dropping {
+                            Some(ref dropped) if closing && *dropped == name => dropping = None,
+                            Some(_) => (),
+                            None if !closing
+                                && (name == "script" || name == "style")
+                                && !self.tags.contains(&name) =>
+                            {
+                                dropping = Some(name)
+                            }
+                            None => out.extend_from_slice(&tag),
+                        }
+                        i += len;
+                    }
+                    None => {
+                        if dropping.is_none() {
+                            out.extend_from_slice(b"&lt;");
+                        }
+                        i += 1;
+                    }
+                }
+            }
+        }
+        if dropping.is_none() {
+            out.extend_from_slice(&html.as_bytes()[i..]);
+        }
+
+        String::from_utf8(out).unwrap()
+    }
+
+    fn allow_attribute(&self, name: &str, value: &str) -> bool {
+        self.allowed(&name.to_ascii_lowercase(), value)
+    }
+}
@@ -0,0 +1,172 @@
+//! Generation of search index records from documents.
+//!
+//! Each heading starts a new record, which runs until the next heading of any
+//! level.  Enable the `serde` feature to serialize records, e.g. to JSON for
+//! lunr or Meilisearch.
+
+use crate::html::{collect_anchors, HeadingAnchor};
+use crate::nodes::{AstNode, NodeCode, NodeEntity, NodeMath, NodeValue};
+use crate::Options;
+
+/// The searchable content of a section of a document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchRecord {
+    /// The `id` of the section's heading, as rendered with
+    /// [`header_ids`](crate::ExtensionOptions::header_ids), including any
+    /// prefix.  Empty for content preceding the first heading.
+    pub anchor: String,
+
+    /// The text of the section's heading and of the headings enclosing it,
+    /// outermost first.  Empty for content preceding the first heading.
+    pub heading_path: Vec<String>,
+
+    /// The plain text of the section, one line per paragraph or other block
+    /// of text.  Code blocks are excluded.
+    pub text: String,
+
+    /// The contents of the section's code blocks and code spans.
+    pub code: Vec<String>,
+}
+
+/// Returns a search record for each section of the document, in document
+/// order.  Content preceding the first heading only produces a record if it
+/// contains text or code.  Footnote definitions, raw HTML and front matter are
+/// not indexed.
+///
+/// ```
+/// # use comrak::{parse_document, Arena, Options};
+/// # use comrak::search::search_records;
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let root = parse_document(
+///     &arena,
+///     "# Install\n\nRun `cargo add comrak`.\n\n## From source\n\n```sh\ncargo build\n```\n",
+///     &options,
+/// );
+///
+/// let records = search_records(root, &options);
+/// assert_eq!(records.len(), 2);
+/// assert_eq!(records[0].anchor, "install");
+/// assert_eq!(records[0].text, "Run cargo add comrak.");
+/// assert_eq!(records[0].code, ["cargo add comrak"]);
+/// assert_eq!(records[1].heading_path, ["Install", "From source"]);
+/// assert_eq!(records[1].code, ["cargo build\n"]);
+/// ```
+pub fn search_records<'a>(root: &'a AstNode<'a>, options: &Options) -> Vec<SearchRecord> {
+    let prefix = options.extension.header_ids.as_deref().unwrap_or("");
+    let mut collector = Collector {
+        anchors: collect_anchors(root, options).into_iter(),
+        prefix,
+        path: vec![],
+        records: vec![SearchRecord::default()],
+    };
+    collector.collect(root);
+
+    let mut records = collector.records;
+    if records[0].text.is_empty() && records[0].code.is_empty() {
+        records.remove(0);
+    }
+    for record in &mut records {
+        record.text.truncate(record.text.trim_end().len());
+    }
+    records
+}
+
+struct Collector<'p> {
+    anchors: std::vec::IntoIter<HeadingAnchor>,
+    prefix: &'p str,
+    // The levels and texts of the enclosing headings.
+    path: Vec<(u8, String)>,
+    records: Vec<SearchRecord>,
+}
+
+impl<'p> Collector<'p> {
+    fn record(&mut self) -> &mut SearchRecord {
+        self.records.last_mut().unwrap()
+    }
+
+    fn collect<'a>(&mut self, node: &'a AstNode<'a>) {
+        match node.data.borrow().value {
+            NodeValue::Heading(ref nh) => {
+                let anchor = match self.anchors.next() {
+                    Some(anchor) => anchor,
+                    None => return,
+                };
+                while self.path.last().map_or(false, |&(l, _)| l >= nh.level) {
+                    self.path.pop();
+                }
+                self.path.push((nh.level, anchor.text));
+                self.records.push(SearchRecord {
+                    anchor: format!("{}{}", self.prefix, anchor.id),
+                    heading_path: self.path.iter().map(|(_, t)| t.clone()).collect(),
+                    ..SearchRecord::default()
+                });
+                return;
+            }
+            NodeValue::CodeBlock(ref ncb) => {
+                let literal = ncb.literal.clone();
+                self.record().code.push(literal);
+                return;
+            }
+            NodeValue::Code(NodeCode { ref literal, .. }) => {
+                let literal = literal.clone();
+                let record = self.record();
+                record.text.push_str(&literal);
+                record.code.push(literal);
+                return;
+            }
+            NodeValue::Text(ref literal)
+            | NodeValue::Entity(NodeEntity {
+                characters: ref literal,
+                ..
+            })
+            | NodeValue::Math(NodeMath { ref literal, .. }) => {
+                let literal = literal.clone();
+                self.record().text.push_str(&literal);
+                return;
+            }
+            NodeValue::TemplateVariable(ref ntv) => {
+                let placeholder = ntv.placeholder();
+                self.record().text.push_str(&placeholder);
+                return;
+            }
+            NodeValue::SoftBreak | NodeValue::LineBreak => {
+                self.record().text.push(' ');
+                return;
+            }
+            NodeValue::FootnoteDefinition(_) => {
+                // Keep the anchors in step with the headings.
+                let headings = node
+                    .descendants()
+                    .filter(|n| matches!(n.data.borrow().value, NodeValue::Heading(..)))
+                    .count();
+                if headings > 0 {
+                    self.anchors.nth(headings - 1);
+                }
+                return;
+            }
+            NodeValue::FrontMatter(_)
+            | NodeValue::HtmlBlock(_)
+            | NodeValue::HtmlInline(_)
+            | NodeValue::TemplateTag(_)
+            | NodeValue::ComponentBlock(_)
+            | NodeValue::Raw(_)
+            | NodeValue::ComponentInline(_)
+            | NodeValue::ExtensionBlock(_)
+            | NodeValue::ExtensionInline(_) => return,
+            _ => {}
+        }
+
+        for child in node.children() {
+            self.collect(child);
+        }
+
+        if node.data.borrow().value.contains_inlines() {
+            let text = &mut self.record().text;
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+    }
+}
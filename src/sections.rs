@@ -0,0 +1,164 @@
+//! Division of documents into sections by heading.
+//!
+//! A section is a heading together with everything that follows it up to the
+//! next heading of the same or a higher level (a lower or equal level number)
+//! in the same container.
+
+use crate::html::collect_anchors;
+use crate::nodes::{AstNode, NodeValue};
+use crate::Options;
+use typed_arena::Arena;
+
+/// Detaches the section of the first heading matching `slug_or_title` from
+/// the document, and returns it as the children of a new document node.
+///
+/// A heading matches if its plain text content equals `slug_or_title`
+/// ignoring surrounding whitespace, or if its anchor does, as generated by
+/// [`collect_anchors`] with `options`.  A leading `#` and the
+/// [`header_ids`](crate::ExtensionOptions::header_ids) prefix are ignored in
+/// the latter case, so a fragment taken from a link works as-is.
+///
+/// ```
+/// # use comrak::{format_commonmark, parse_document, Arena, Options};
+/// # use comrak::sections::extract_section;
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let root = parse_document(
+///     &arena,
+///     "# FAQ\n\n## Is it fast?\n\nYes.\n\n### Really?\n\nReally.\n\n## Is it safe?\n\nYes.\n",
+///     &options,
+/// );
+///
+/// let section = extract_section(&arena, root, &options, "#is-it-fast").unwrap();
+///
+/// let mut md = vec![];
+/// format_commonmark(section, &options, &mut md).unwrap();
+/// assert_eq!(
+///     String::from_utf8(md).unwrap(),
+///     "## Is it fast?\n\nYes.\n\n### Really?\n\nReally.\n"
+/// );
+///
+/// let mut md = vec![];
+/// format_commonmark(root, &options, &mut md).unwrap();
+/// assert_eq!(String::from_utf8(md).unwrap(), "# FAQ\n\n## Is it safe?\n\nYes.\n");
+/// ```
+pub fn extract_section<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    root: &'a AstNode<'a>,
+    options: &Options,
+    slug_or_title: &str,
+) -> Option<&'a AstNode<'a>> {
+    let title = slug_or_title.trim();
+    let slug = title.strip_prefix('#').unwrap_or(title);
+    let slug = options
+        .extension
+        .header_ids
+        .as_deref()
+        .and_then(|prefix| slug.strip_prefix(prefix))
+        .unwrap_or(slug);
+
+    let index = collect_anchors(root, options)
+        .iter()
+        .position(|anchor| anchor.text.trim() == title || anchor.id == slug)?;
+    let heading = root
+        .descendants()
+        .filter(|node| matches!(node.data.borrow().value, NodeValue::Heading(..)))
+        .nth(index)?;
+
+    let section = arena.alloc(NodeValue::Document.into());
+    for node in section_nodes(heading) {
+        section.append(node);
+    }
+    Some(section)
+}
+
+/// A section of a document, as returned by [`split_sections`].
+#[derive(Debug, Clone, Copy)]
+pub struct Section<'a> {
+    /// The heading the section starts with; `None` for any content preceding
+    /// the first heading.
+    pub heading: Option<&'a AstNode<'a>>,
+
+    /// A document node whose children are the rest of the section.
+    pub body: &'a AstNode<'a>,
+}
+
+/// Splits the document at each top-level heading of `level` or higher (a
+/// lower or equal level number), moving its children into the returned
+/// sections in order.  The document is left empty.
+///
+/// Deeper headings remain in the body of their section, as do headings
+/// nested in other blocks, such as block quotes.  Content before the first
+/// heading, if any, forms a section without one.
+///
+/// ```
+/// # use comrak::{format_commonmark, parse_document, Arena, Options};
+/// # use comrak::nodes::NodeValue;
+/// # use comrak::sections::split_sections;
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let root = parse_document(
+///     &arena,
+///     "Intro.\n\n# Slide one\n\nHello.\n\n## Detail\n\n# Slide two\n\nBye.\n",
+///     &options,
+/// );
+///
+/// let sections = split_sections(&arena, root, 1);
+/// assert_eq!(sections.len(), 3);
+/// assert!(sections[0].heading.is_none());
+///
+/// let heading = sections[1].heading.unwrap();
+/// assert!(matches!(heading.data.borrow().value, NodeValue::Heading(ref nh) if nh.level == 1));
+///
+/// let mut md = vec![];
+/// format_commonmark(sections[1].body, &options, &mut md).unwrap();
+/// assert_eq!(String::from_utf8(md).unwrap(), "Hello.\n\n## Detail\n");
+/// ```
+pub fn split_sections<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    root: &'a AstNode<'a>,
+    level: u8,
+) -> Vec<Section<'a>> {
+    let mut sections: Vec<Section<'a>> = vec![];
+    while let Some(node) = root.first_child() {
+        node.detach();
+        if heading_level(node).map_or(false, |l| l <= level) {
+            sections.push(Section {
+                heading: Some(node),
+                body: arena.alloc(NodeValue::Document.into()),
+            });
+            continue;
+        }
+
+        if sections.is_empty() {
+            sections.push(Section {
+                heading: None,
+                body: arena.alloc(NodeValue::Document.into()),
+            });
+        }
+        sections.last().unwrap().body.append(node);
+    }
+    sections
+}
+
+/// The heading and the nodes following it in its section.
+fn section_nodes<'a>(heading: &'a AstNode<'a>) -> Vec<&'a AstNode<'a>> {
+    let level = heading_level(heading).unwrap_or(0);
+    let mut nodes = vec![heading];
+    let mut next = heading.next_sibling();
+    while let Some(node) = next {
+        if heading_level(node).map_or(false, |l| l <= level) {
+            break;
+        }
+        nodes.push(node);
+        next = node.next_sibling();
+    }
+    nodes
+}
+
+fn heading_level<'a>(node: &'a AstNode<'a>) -> Option<u8> {
+    match node.data.borrow().value {
+        NodeValue::Heading(ref nh) => Some(nh.level),
+        _ => None,
+    }
+}
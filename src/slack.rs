@@ -0,0 +1,261 @@
+use crate::cm::is_autolink;
+use crate::nodes::{
+    AstNode, ListType, NodeCode, NodeEntity, NodeLink, NodeMath, NodeValue, NodeWikiLink,
+};
+use crate::parser::{Options, Plugins};
+use std::io::{self, Write};
+
+/// Formats an AST as Slack's
+/// [mrkdwn](https://api.slack.com/reference/surfaces/formatting), modified by
+/// the given options.
+///
+/// mrkdwn has no headings or lists, so headings are written in bold, and list
+/// items as lines starting with a bullet or number, indented when nested.
+/// Images are written as links, and tables as preformatted text.  `&`, `<`
+/// and `>` are escaped as entities.  Raw HTML, components, extension nodes,
+/// front matter, thematic breaks and abbreviation definitions are dropped.
+pub fn format_document<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    format_document_with_plugins(root, options, output, &Plugins::default())
+}
+
+/// Formats an AST as Slack mrkdwn, modified by the given options. Accepts
+/// custom plugins.
+pub fn format_document_with_plugins<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+    _plugins: &Plugins,
+) -> io::Result<()> {
+    let mrkdwn = SlackFormatter { _options: options }.block(root);
+    output.write_all(mrkdwn.as_bytes())?;
+    if !mrkdwn.is_empty() {
+        output.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+struct SlackFormatter<'o, 'c> {
+    _options: &'o Options<'c>,
+}
+
+impl<'o, 'c> SlackFormatter<'o, 'c> {
+    // Formats a block node, without a trailing newline.
+    fn block<'a>(&self, node: &'a AstNode<'a>) -> String {
+        match node.data.borrow().value {
+            NodeValue::Document
+            | NodeValue::DescriptionList
+            | NodeValue::DescriptionItem(_)
+            | NodeValue::DescriptionDetails => self.blocks(node, "\n\n"),
+            NodeValue::Container(ref nc) => {
+                let mut blocks = vec![];
+                if let Some(summary) = nc.summary() {
+                    blocks.push(format!("*{}*", escape(summary)));
+                }
+                blocks.push(self.blocks(node, "\n\n"));
+                blocks.retain(|block| !block.is_empty());
+                blocks.join("\n\n")
+            }
+            NodeValue::Alert(ref nalert) => {
+                let mut blocks = vec![format!("*{}*", escape(nalert.title()))];
+                blocks.push(self.blocks(node, "\n\n"));
+                blocks.retain(|block| !block.is_empty());
+                prefix_lines(&blocks.join("\n\n"), "> ", "> ")
+            }
+            NodeValue::BlockQuote | NodeValue::MultilineBlockQuote(_) => {
+                prefix_lines(&self.blocks(node, "\n\n"), "> ", "> ")
+            }
+            NodeValue::List(ref nl) => {
+                let separator = if nl.tight { "\n" } else { "\n\n" };
+                let mut items = vec![];
+                for (number, item) in (nl.start..).zip(node.children()) {
+                    let mut marker = match nl.list_type {
+                        ListType::Bullet => "•".to_string(),
+                        ListType::Ordered => format!("{}.", number),
+                    };
+                    if let NodeValue::TaskItem(symbol) = item.data.borrow().value {
+                        marker.push_str(if symbol.is_some() { " ☑" } else { " ☐" });
+                    }
+                    marker.push(' ');
+                    items.push(prefix_lines(&self.blocks(item, separator), &marker, "    "));
+                }
+                items.join(separator)
+            }
+            NodeValue::CodeBlock(ref ncb) => preformatted(&ncb.literal),
+            NodeValue::Heading(_) => {
+                let text = self.inlines(node);
+                if text.is_empty() {
+                    text
+                } else {
+                    format!("*{}*", text)
+                }
+            }
+            NodeValue::Table(_) => {
+                let mut rows = String::new();
+                for row in node.children() {
+                    let cells: Vec<String> = row.children().map(|cell| plain_text(cell)).collect();
+                    rows.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+                preformatted(&rows)
+            }
+            NodeValue::FootnoteDefinition(ref nfd) => {
+                format!("[{}] {}", escape(&nfd.name), self.blocks(node, "\n\n"))
+            }
+            NodeValue::FrontMatter(_)
+            | NodeValue::Truncated(_)
+            | NodeValue::HtmlBlock(_)
+            | NodeValue::ComponentBlock(_)
+            | NodeValue::ExtensionBlock(_)
+            | NodeValue::Raw(_)
+            | NodeValue::ThematicBreak
+            | NodeValue::AbbreviationDefinition(_) => String::new(),
+            _ => self.inlines(node),
+        }
+    }
+
+    fn blocks<'a>(&self, node: &'a AstNode<'a>, separator: &str) -> String {
+        node.children()
+            .map(|child| self.block(child))
+            .filter(|block| !block.is_empty())
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    fn inlines<'a>(&self, node: &'a AstNode<'a>) -> String {
+        let mut s = String::new();
+        for child in node.children() {
+            self.inline(child, &mut s);
+        }
+        s
+    }
+
+    fn inline<'a>(&self, node: &'a AstNode<'a>, s: &mut String) {
+        let wrap = |s: &mut String, delimiter: &str| {
+            let inner = self.inlines(node);
+            if !inner.is_empty() {
+                s.push_str(delimiter);
+                s.push_str(&inner);
+                s.push_str(delimiter);
+            }
+        };
+
+        match node.data.borrow().value {
+            NodeValue::Text(ref literal)
+            | NodeValue::Entity(NodeEntity {
+                characters: ref literal,
+                ..
+            })
+            | NodeValue::EscapedTag(ref literal)
+            | NodeValue::TemplateTag(ref literal) => s.push_str(&escape(literal)),
+            NodeValue::SoftBreak => s.push(' '),
+            NodeValue::LineBreak => s.push('\n'),
+            NodeValue::Code(NodeCode { ref literal, .. })
+            | NodeValue::Math(NodeMath { ref literal, .. }) => {
+                s.push('`');
+                s.push_str(&escape(&literal.replace('`', "'")));
+                s.push('`');
+            }
+            NodeValue::HtmlInline(_)
+            | NodeValue::ComponentInline(_)
+            | NodeValue::ExtensionInline(_) => {}
+            NodeValue::Emph => wrap(s, "_"),
+            NodeValue::Strong => wrap(s, "*"),
+            NodeValue::Strikethrough => wrap(s, "~"),
+            NodeValue::Link(ref nl) if is_autolink(node, nl) => {
+                s.push('<');
+                s.push_str(&escape_url(&nl.url));
+                s.push('>');
+            }
+            NodeValue::Link(NodeLink { ref url, .. })
+            | NodeValue::WikiLink(NodeWikiLink { ref url }) => link(url, &self.inlines(node), s),
+            NodeValue::Image(ref nl) => link(&nl.url, &escape(&plain_text(node)), s),
+            NodeValue::FootnoteReference(ref nfr) => {
+                s.push('[');
+                s.push_str(&escape(&nfr.name));
+                s.push(']');
+            }
+            #[cfg(feature = "shortcodes")]
+            NodeValue::ShortCode(ref nsc) => {
+                s.push(':');
+                s.push_str(&nsc.code);
+                s.push(':');
+            }
+            NodeValue::TemplateVariable(ref ntv) => s.push_str(&escape(&ntv.placeholder())),
+            NodeValue::Citation(ref nc) => s.push_str(&escape(&nc.source())),
+            _ => s.push_str(&self.inlines(node)),
+        }
+    }
+}
+
+// Writes a link.  Its text can't contain `|`, nor span lines.
+fn link(url: &str, text: &str, s: &mut String) {
+    s.push('<');
+    s.push_str(&escape_url(url));
+    let text = text.replace('|', "¦").replace('\n', " ");
+    if !text.is_empty() {
+        s.push('|');
+        s.push_str(&text);
+    }
+    s.push('>');
+}
+
+fn plain_text<'a>(node: &'a AstNode<'a>) -> String {
+    let mut s = String::new();
+    for child in node.descendants().skip(1) {
+        match child.data.borrow().value {
+            NodeValue::Text(ref literal)
+            | NodeValue::Entity(NodeEntity {
+                characters: ref literal,
+                ..
+            })
+            | NodeValue::Code(NodeCode { ref literal, .. })
+            | NodeValue::Math(NodeMath { ref literal, .. }) => s.push_str(literal),
+            NodeValue::LineBreak | NodeValue::SoftBreak => s.push(' '),
+            _ => {}
+        }
+    }
+    s
+}
+
+fn preformatted(literal: &str) -> String {
+    let literal = literal.strip_suffix('\n').unwrap_or(literal);
+    format!("```\n{}\n```", escape(literal))
+}
+
+fn escape(literal: &str) -> String {
+    let mut s = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        match c {
+            '&' => s.push_str("&amp;"),
+            '<' => s.push_str("&lt;"),
+            '>' => s.push_str("&gt;"),
+            _ => s.push(c),
+        }
+    }
+    s
+}
+
+// Escapes a URL within `<...>`, where `|` would begin the link text.
+fn escape_url(url: &str) -> String {
+    escape(url).replace('|', "%7C")
+}
+
+fn prefix_lines(s: &str, first: &str, rest: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for (i, line) in s.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let prefix = if i == 0 { first } else { rest };
+        if line.is_empty() {
+            out.push_str(prefix.trim_end());
+        } else {
+            out.push_str(prefix);
+            out.push_str(line);
+        }
+    }
+    out
+}
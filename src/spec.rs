@@ -0,0 +1,182 @@
+//! Running the examples of spec files in the format of the
+//! [CommonMark spec](https://spec.commonmark.org/), to check which dialect a
+//! set of options implements.
+//!
+//! Each example is Markdown and the HTML it should render as, separated by
+//! a line holding a single `.`, between fences of 32 backticks, the opening
+//! one followed by `example`.  `→` stands for a tab.  Words after `example`
+//! name extensions the example needs, as in the GFM spec; examples marked
+//! `disabled` are skipped.
+//!
+//! ```
+//! # use comrak::Options;
+//! # use comrak::spec::run;
+//! let spec = concat!(
+//!     "# Emphasis\n\n",
+//!     "```` example\n*a*\n.\n<p><em>a</em></p>\n````\n\n",
+//!     "# Strikethrough\n\n",
+//!     "```` example strikethrough\n~a~\n.\n<p><del>a</del></p>\n````\n",
+//! )
+//! .replace("````", &"`".repeat(32));
+//!
+//! let report = run(&spec, &Options::default());
+//! assert_eq!((report.passed(), report.failed(), report.skipped()), (2, 0, 0));
+//! assert_eq!(report.results[1].example.section, "Strikethrough");
+//! ```
+
+use crate::{markdown_to_html, Options};
+
+const FENCE: &str = "````````````````````````````````";
+
+/// An example from a spec file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    /// The example's number, counting from 1.
+    pub number: usize,
+
+    /// The line of the spec file the example starts on, counting from 1.
+    pub line: usize,
+
+    /// The heading of the section of the spec the example is in.
+    pub section: String,
+
+    /// The words following `example` on its opening fence.
+    pub extensions: Vec<String>,
+
+    /// The example's Markdown, with tabs in place of `→`.
+    pub markdown: String,
+
+    /// The HTML the Markdown should render as, with tabs in place of `→`.
+    pub html: String,
+}
+
+/// Parses the examples from a spec file.
+pub fn examples(spec: &str) -> Vec<Example> {
+    let mut examples = vec![];
+    let mut section = String::new();
+    let mut current: Option<Example> = None;
+    let mut in_html = false;
+
+    for (i, line) in spec.lines().enumerate() {
+        if let Some(ref mut example) = current {
+            if line == FENCE {
+                examples.extend(current.take());
+            } else if line == "." && !in_html {
+                in_html = true;
+            } else {
+                let text = if in_html {
+                    &mut example.html
+                } else {
+                    &mut example.markdown
+                };
+                text.push_str(&line.replace('→', "\t"));
+                text.push('\n');
+            }
+        } else if let Some(extensions) = line
+            .strip_prefix(FENCE)
+            .and_then(|rest| rest.trim().strip_prefix("example"))
+        {
+            current = Some(Example {
+                number: examples.len() + 1,
+                line: i + 1,
+                section: section.clone(),
+                extensions: extensions.split_whitespace().map(String::from).collect(),
+                markdown: String::new(),
+                html: String::new(),
+            });
+            in_html = false;
+        } else if line.starts_with('#') {
+            section = line.trim_start_matches('#').trim().to_string();
+        }
+    }
+
+    examples
+}
+
+/// The outcome of running an example.
+#[derive(Debug, Clone)]
+pub struct ExampleResult {
+    /// The example.
+    pub example: Example,
+
+    /// The HTML rendered from the example's Markdown, or `None` if the
+    /// example was skipped: it's disabled, or needs an extension other than
+    /// `table`, `strikethrough`, `autolink`, `tagfilter`, `tasklist` or
+    /// `footnotes`.
+    pub output: Option<String>,
+}
+
+impl ExampleResult {
+    /// Whether the example rendered as expected.
+    pub fn passed(&self) -> bool {
+        self.output.as_ref() == Some(&self.example.html)
+    }
+
+    /// Whether the example was skipped.
+    pub fn skipped(&self) -> bool {
+        self.output.is_none()
+    }
+}
+
+/// The outcome of running every example in a spec file.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// The outcome of each example, in order.
+    pub results: Vec<ExampleResult>,
+}
+
+impl Report {
+    /// The number of examples which rendered as expected.
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.passed()).count()
+    }
+
+    /// The number of examples which didn't render as expected.
+    pub fn failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| !r.passed() && !r.skipped())
+            .count()
+    }
+
+    /// The number of examples skipped.
+    pub fn skipped(&self) -> usize {
+        self.results.iter().filter(|r| r.skipped()).count()
+    }
+
+    /// The examples which didn't render as expected.
+    pub fn failures(&self) -> impl Iterator<Item = &ExampleResult> {
+        self.results.iter().filter(|r| !r.passed() && !r.skipped())
+    }
+}
+
+/// Renders each example in `spec` to HTML with `options`, along with any
+/// extensions the example names, and compares the output with the expected
+/// HTML.
+pub fn run(spec: &str, options: &Options) -> Report {
+    let results = examples(spec)
+        .into_iter()
+        .map(|example| {
+            let output = example_options(&example, options)
+                .map(|options| markdown_to_html(&example.markdown, &options));
+            ExampleResult { example, output }
+        })
+        .collect();
+    Report { results }
+}
+
+fn example_options<'c>(example: &Example, options: &Options<'c>) -> Option<Options<'c>> {
+    let mut options = options.clone();
+    for extension in &example.extensions {
+        match extension.as_str() {
+            "table" => options.extension.table = true,
+            "strikethrough" => options.extension.strikethrough = true,
+            "autolink" => options.extension.autolink = true,
+            "tagfilter" => options.extension.tagfilter = true,
+            "tasklist" => options.extension.tasklist = true,
+            "footnotes" => options.extension.footnotes = true,
+            _ => return None,
+        }
+    }
+    Some(options)
+}
@@ -0,0 +1,377 @@
+//! Statistics about parsing and rendering a document, for monitoring the cost
+//! of rendering and detecting pathological inputs, about the memory a parsed
+//! document holds, and about its content, such as its length in words.
+//!
+//! ```
+//! # use comrak::{markdown_to_html_with_stats, Options, Plugins};
+//! let (html, stats) = markdown_to_html_with_stats(
+//!     "# Title\n\nOne\ntwo\nthree\n",
+//!     &Options::default(),
+//!     &Plugins::default(),
+//! );
+//! assert_eq!(html, "<h1>Title</h1>\n<p>One\ntwo\nthree</p>\n");
+//! assert_eq!(stats.bytes, 23);
+//! assert_eq!(stats.output_bytes, html.len());
+//! assert_eq!(stats.nodes, 9);
+//! assert_eq!(stats.largest_block.unwrap().start.line, 3);
+//! ```
+
+use crate::nodes::{AstNode, NodeCode, NodeEntity, NodeLink, NodeValue, Sourcepos};
+use crate::strings::is_cjk;
+use std::mem;
+use std::time::Duration;
+use typed_arena::Arena;
+
+// `Instant::now` panics on `wasm32-unknown-unknown`, which has no clock, so
+// times are reported as zero there.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = std::time::Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    (f(), Duration::ZERO)
+}
+
+/// Statistics about parsing and rendering a document.  See
+/// [`markdown_to_html_with_stats`](crate::markdown_to_html_with_stats).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The length of the input, in bytes.
+    pub bytes: usize,
+
+    /// The number of nodes allocated while parsing, including any merged or
+    /// dropped before parsing finished.
+    pub nodes: usize,
+
+    /// The time spent parsing, including inline parsing and any
+    /// postprocessing of the AST.  Always zero on `wasm32-unknown-unknown`,
+    /// which has no clock.
+    pub parse_time: Duration,
+
+    /// The time spent rendering.  Always zero on `wasm32-unknown-unknown`.
+    pub render_time: Duration,
+
+    /// The length of the output, in bytes.
+    pub output_bytes: usize,
+
+    /// The position of the block spanning the most lines, not counting
+    /// blocks which contain other blocks, other than tables.  `None` if the
+    /// document is empty.
+    pub largest_block: Option<Sourcepos>,
+}
+
+/// Returns the position of the block in the document at `root` spanning the
+/// most lines, not counting blocks which contain other blocks, other than
+/// tables.  The first is returned if several are the same size.
+pub fn largest_block<'a>(root: &'a AstNode<'a>) -> Option<Sourcepos> {
+    let mut largest: Option<Sourcepos> = None;
+    for node in root.descendants() {
+        let ast = node.data.borrow();
+        let leaf = match ast.value {
+            NodeValue::Document => false,
+            NodeValue::Table(_) => true,
+            _ => {
+                ast.value.block()
+                    && !matches!(ast.value, NodeValue::TableRow(_) | NodeValue::TableCell)
+                    && node
+                        .first_child()
+                        .map_or(true, |child| !child.data.borrow().value.block())
+            }
+        };
+        if leaf && largest.map_or(true, |largest| lines(ast.sourcepos) > lines(largest)) {
+            largest = Some(ast.sourcepos);
+        }
+    }
+    largest
+}
+
+/// Memory held by an arena and the document parsed into it.  See
+/// [`memory_usage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The number of nodes allocated in the arena, whether or not they're
+    /// attached to the document.
+    pub nodes: usize,
+
+    /// The number of nodes the arena has room for without allocating again.
+    pub arena_capacity: usize,
+
+    /// The bytes the arena has allocated for nodes, not counting memory the
+    /// nodes hold on the heap.
+    pub arena_bytes: usize,
+
+    /// The bytes held on the heap by strings in the document's nodes, such as
+    /// text, code, link destinations and titles.  Counts each string's
+    /// capacity, not just its length.
+    pub literal_bytes: usize,
+}
+
+impl MemoryUsage {
+    /// The total bytes held by the arena and the document's strings.
+    pub fn total_bytes(&self) -> usize {
+        self.arena_bytes + self.literal_bytes
+    }
+}
+
+/// Reports the memory held by `arena` and the document at `root`, which
+/// should have been parsed into it.
+///
+/// ```
+/// # use comrak::{parse_document, Arena, Options};
+/// # use comrak::stats::memory_usage;
+/// let arena = Arena::new();
+/// let root = parse_document(&arena, "Hello, *world*!\n", &Options::default());
+/// let usage = memory_usage(&arena, root);
+/// // The emphasis delimiters were allocated as text nodes, then removed.
+/// assert_eq!(root.descendants().count(), 6);
+/// assert_eq!(usage.nodes, 8);
+/// assert!(usage.arena_capacity >= usage.nodes);
+/// assert!(usage.literal_bytes >= "Hello, world!".len());
+/// ```
+pub fn memory_usage<'a>(arena: &'a Arena<AstNode<'a>>, root: &'a AstNode<'a>) -> MemoryUsage {
+    // The arena's spare capacity is that of its current chunk; earlier
+    // chunks are always full.
+    let spare = arena.uninitialized_array();
+    // SAFETY: the slice is only measured, and nothing is allocated in the
+    // arena while it's held.
+    let spare = unsafe { (&*spare).len() };
+    let arena_capacity = arena.len() + spare;
+
+    let literal_bytes = root
+        .descendants()
+        .map(|node| {
+            let ast = node.data.borrow();
+            ast.content.capacity() + payload_bytes(&ast.value)
+        })
+        .sum();
+
+    MemoryUsage {
+        nodes: arena.len(),
+        arena_capacity,
+        arena_bytes: arena_capacity * mem::size_of::<AstNode>(),
+        literal_bytes,
+    }
+}
+
+fn payload_bytes(value: &NodeValue) -> usize {
+    match *value {
+        NodeValue::FrontMatter(ref s)
+        | NodeValue::Text(ref s)
+        | NodeValue::HtmlInline(ref s)
+        | NodeValue::EscapedTag(ref s)
+        | NodeValue::TemplateTag(ref s)
+        | NodeValue::Raw(ref s) => s.capacity(),
+        NodeValue::Entity(ref ne) => ne.literal.capacity() + ne.characters.capacity(),
+        NodeValue::CodeBlock(ref ncb) => ncb.info.capacity() + ncb.literal.capacity(),
+        NodeValue::HtmlBlock(ref nhb) => nhb.literal.capacity(),
+        NodeValue::Code(ref nc) => nc.literal.capacity(),
+        NodeValue::Math(ref nm) => nm.literal.capacity(),
+        NodeValue::Link(ref nl) | NodeValue::Image(ref nl) => {
+            nl.url.capacity() + nl.title.capacity()
+        }
+        NodeValue::WikiLink(ref nwl) => nwl.url.capacity(),
+        NodeValue::FootnoteDefinition(ref nfd) => nfd.name.capacity(),
+        NodeValue::FootnoteReference(ref nfr) => nfr.name.capacity(),
+        #[cfg(feature = "shortcodes")]
+        NodeValue::ShortCode(ref nsc) => nsc.code.capacity() + nsc.emoji.capacity(),
+        NodeValue::AbbreviationDefinition(ref nad) => nad.label.capacity() + nad.title.capacity(),
+        NodeValue::TemplateVariable(ref ntv) => {
+            ntv.name.capacity() + ntv.filters.iter().map(String::capacity).sum::<usize>()
+        }
+        NodeValue::Container(ref nc) => {
+            nc.name.capacity()
+                + nc.info.capacity()
+                + nc.attributes.id.as_ref().map_or(0, String::capacity)
+                + nc.attributes
+                    .classes
+                    .iter()
+                    .map(String::capacity)
+                    .sum::<usize>()
+                + nc.attributes
+                    .attributes
+                    .iter()
+                    .map(|(key, value)| key.capacity() + value.capacity())
+                    .sum::<usize>()
+        }
+        NodeValue::Alert(ref nalert) => nalert.title.as_ref().map_or(0, String::capacity),
+        NodeValue::Citation(ref nc) => nc
+            .items
+            .iter()
+            .map(|item| item.prefix.capacity() + item.key.capacity() + item.locator.capacity())
+            .sum(),
+        NodeValue::ComponentBlock(ref nc) | NodeValue::ComponentInline(ref nc) => {
+            nc.name.capacity() + nc.literal.capacity()
+        }
+        NodeValue::ExtensionBlock(ref ne) | NodeValue::ExtensionInline(ref ne) => {
+            ne.extension.capacity() + ne.literal.capacity()
+        }
+        _ => 0,
+    }
+}
+
+/// The reading speed assumed by [`DocumentStats::reading_time`] by blog
+/// platforms and the like, in words per minute.
+pub const DEFAULT_WORDS_PER_MINUTE: usize = 200;
+
+/// Statistics about the content of a document.  See [`document_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DocumentStats {
+    /// The number of words of prose, including inline code.  Each Chinese or
+    /// Japanese character counts as a word.
+    pub words: usize,
+
+    /// The number of characters of prose, not counting whitespace.
+    pub characters: usize,
+
+    /// The number of headings.
+    pub headings: usize,
+
+    /// The number of images.
+    pub images: usize,
+
+    /// The number of links, including autolinks and wikilinks.
+    pub links: usize,
+
+    /// The number of code blocks, fenced or indented.
+    pub code_blocks: usize,
+}
+
+impl DocumentStats {
+    /// The time it takes to read the document's words at `words_per_minute`,
+    /// rounded up to the second.
+    ///
+    /// ```
+    /// # use comrak::{parse_document, Arena, Options};
+    /// # use comrak::stats::{document_stats, DEFAULT_WORDS_PER_MINUTE};
+    /// # use std::time::Duration;
+    /// let arena = Arena::new();
+    /// let text = "word ".repeat(500);
+    /// let stats = document_stats(parse_document(&arena, &text, &Options::default()));
+    /// assert_eq!(stats.reading_time(DEFAULT_WORDS_PER_MINUTE), Duration::from_secs(150));
+    /// assert_eq!(stats.reading_time(300), Duration::from_secs(100));
+    /// ```
+    pub fn reading_time(&self, words_per_minute: usize) -> Duration {
+        let words_per_minute = words_per_minute.max(1);
+        Duration::from_secs(((self.words * 60 + words_per_minute - 1) / words_per_minute) as u64)
+    }
+}
+
+/// Counts the words, characters, headings, images, links and code blocks of
+/// the document at `root`.
+///
+/// Only prose is counted as words and characters: the contents of code
+/// blocks, raw HTML, math, front matter and the like are skipped, as are
+/// image descriptions and the text of autolinks, which is their URL.
+///
+/// ```
+/// # use comrak::{parse_document, Arena, Options};
+/// # use comrak::stats::{document_stats, DocumentStats};
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.front_matter_delimiter = Some("---".to_string());
+/// let root = parse_document(
+///     &arena,
+///     "---\ntitle: Hi\n---\n\n# Hello *wor*ld\n\n\
+///      See <https://example.com> and [the `docs`](/docs) ![a logo](logo.png).\n\n\
+///      ```\nlet x = 1;\n```\n",
+///     &options,
+/// );
+/// assert_eq!(
+///     document_stats(root),
+///     DocumentStats {
+///         words: 6,
+///         characters: 24,
+///         headings: 1,
+///         images: 1,
+///         links: 2,
+///         code_blocks: 1,
+///     }
+/// );
+/// ```
+pub fn document_stats<'a>(root: &'a AstNode<'a>) -> DocumentStats {
+    let mut stats = DocumentStats::default();
+    let mut text = String::new();
+    let mut stack = vec![root];
+
+    while let Some(node) = stack.pop() {
+        let ast = node.data.borrow();
+        if ast.value.block() {
+            text.push('\n');
+        }
+        match ast.value {
+            NodeValue::Heading(..) => stats.headings += 1,
+            NodeValue::CodeBlock(..) => {
+                stats.code_blocks += 1;
+                continue;
+            }
+            NodeValue::Image(..) => {
+                stats.images += 1;
+                continue;
+            }
+            NodeValue::Link(ref nl) => {
+                stats.links += 1;
+                if shows_url(node, nl) {
+                    continue;
+                }
+            }
+            NodeValue::WikiLink(..) => stats.links += 1,
+            NodeValue::Text(ref literal)
+            | NodeValue::Code(NodeCode { ref literal, .. })
+            | NodeValue::Entity(NodeEntity {
+                characters: ref literal,
+                ..
+            }) => text.push_str(literal),
+            NodeValue::SoftBreak | NodeValue::LineBreak => text.push('\n'),
+            NodeValue::FrontMatter(..)
+            | NodeValue::HtmlBlock(..)
+            | NodeValue::HtmlInline(..)
+            | NodeValue::Math(..)
+            | NodeValue::Raw(..)
+            | NodeValue::ComponentBlock(..)
+            | NodeValue::ComponentInline(..)
+            | NodeValue::ExtensionBlock(..)
+            | NodeValue::ExtensionInline(..) => continue,
+            _ => (),
+        }
+        stack.extend(node.reverse_children());
+    }
+
+    for word in text.split_whitespace() {
+        let mut in_word = false;
+        for c in word.chars() {
+            stats.characters += 1;
+            if is_cjk(c) {
+                // Punctuation isn't a word, but ends one.
+                stats.words += c.is_alphanumeric() as usize;
+                in_word = false;
+            } else if c.is_alphanumeric() && !in_word {
+                stats.words += 1;
+                in_word = true;
+            }
+        }
+    }
+    stats
+}
+
+// Whether the text of the link is its URL, as with autolinks.
+fn shows_url<'a>(node: &'a AstNode<'a>, nl: &NodeLink) -> bool {
+    let child = match node.first_child() {
+        Some(child) if child.next_sibling().is_none() => child,
+        _ => return false,
+    };
+    let text = match child.data.borrow().value {
+        NodeValue::Text(ref text) => text.clone(),
+        _ => return false,
+    };
+    ["", "mailto:", "http://"]
+        .iter()
+        .any(|scheme| nl.url.strip_prefix(scheme) == Some(&text))
+}
+
+fn lines(sourcepos: Sourcepos) -> usize {
+    sourcepos.end.line.saturating_sub(sourcepos.start.line) + 1
+}
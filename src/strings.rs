@@ -15,8 +15,9 @@ pub fn unescape(v: &mut Vec<u8>) {
     let mut prev = None;
     let mut found = 0;
 
-    while r < v.len() {
-        if v[r] == b'\\' && r + 1 < v.len() && ispunct(v[r + 1]) {
+    while let Some(n) = memchr::memchr(b'\\', &v[r..]) {
+        r += n;
+        if r + 1 < v.len() && ispunct(v[r + 1]) {
             if v[r + 1] == b'\\' {
                 r += 1;
             }
@@ -30,6 +31,7 @@ pub fn unescape(v: &mut Vec<u8>) {
         }
         r += 1;
     }
+    r = v.len();
 
     if let Some(prev) = prev {
         let window = &mut v[(prev + 1 - found)..r];
@@ -247,6 +249,26 @@ pub fn is_blank(s: &[u8]) -> bool {
     true
 }
 
+/// Whether `c` is a Chinese or Japanese character or punctuation mark, which
+/// aren't separated by spaces.  Hangul isn't included, as Korean separates
+/// words with spaces.
+pub fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{2E80}'..='\u{2FDF}'     // CJK radicals, Kangxi radicals
+        | '\u{3000}'..='\u{303F}'   // CJK symbols and punctuation
+        | '\u{3040}'..='\u{30FF}'   // Hiragana, Katakana
+        | '\u{3100}'..='\u{312F}'   // Bopomofo
+        | '\u{31A0}'..='\u{31FF}'   // Bopomofo extended, strokes, Katakana extension
+        | '\u{3200}'..='\u{4DBF}'   // enclosed CJK, compatibility, extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK unified ideographs
+        | '\u{F900}'..='\u{FAFF}'   // compatibility ideographs
+        | '\u{FE30}'..='\u{FE4F}'   // compatibility forms
+        | '\u{FF00}'..='\u{FF60}'   // fullwidth forms
+        | '\u{FF61}'..='\u{FF9F}'   // halfwidth Katakana
+        | '\u{20000}'..='\u{3FFFF}' // supplementary ideographs
+    )
+}
+
 pub fn normalize_label(i: &str, casing: Case) -> String {
     // trim_slice only removes bytes from start and end that match isspace();
     // result is UTF-8.
@@ -4,32 +4,87 @@ use std::collections::HashMap;
 use std::io::{self, Write};
 use std::panic;
 
+mod abbreviations;
+mod alerts;
 mod api;
+mod ast_dump;
 mod autolink;
+mod bibliography;
+mod block_attributes;
+mod cancel;
 mod commonmark;
+mod compiled;
+mod conditionals;
+mod containers;
 mod core;
 mod description_lists;
+mod details;
+mod diagnostics;
+mod djot;
+mod dot;
 mod empty;
 mod escaped_char_spans;
+mod extensions;
+mod external_links;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod figures;
 mod footnotes;
+#[cfg(feature = "front-matter")]
+mod front_matter_as;
 mod fuzz;
+mod gemtext;
+mod gfm;
 mod greentext;
 mod header_ids;
+mod html_comments;
+mod image_dimensions;
+mod incremental;
+#[cfg(feature = "serde")]
+mod json;
+mod jsx_components;
+mod latex;
+mod links;
+mod lint;
 mod math;
+mod max_input_size;
+mod mentions;
+mod merge;
 mod multiline_block_quotes;
+mod node_classes;
+mod offsets;
 mod options;
+mod owned;
+#[cfg(feature = "rayon")]
+mod parallel;
 mod pathological;
 mod plugins;
+mod raw_regions;
 mod regressions;
+#[cfg(feature = "sanitize")]
+mod sanitize;
+mod search;
+mod sections;
 mod shortcodes;
+mod slack;
+mod spec;
 mod spoiler;
+mod stats;
+mod streaming;
 mod strikethrough;
+mod subscript;
 mod superscript;
 mod table;
 mod tagfilter;
 mod tasklist;
+mod template_tags;
+mod template_variables;
+mod text;
+mod toc;
+mod typst;
 mod underline;
 mod wikilinks;
+mod workspace;
 mod xml;
 
 #[track_caller]
@@ -54,6 +109,21 @@ fn compare_strs(output: &str, expected: &str, kind: &str, original_input: &str)
     assert_eq!(output, expected);
 }
 
+// Runs each example of a spec file in the format of the CommonMark spec.
+#[track_caller]
+fn spec_examples(spec: &str, options: &Options) {
+    let report = crate::spec::run(spec, options);
+    for result in report.failures() {
+        compare_strs(
+            result.output.as_deref().unwrap(),
+            &result.example.html,
+            &format!("spec example {}", result.example.number),
+            &result.example.markdown,
+        );
+    }
+    assert!(report.passed() > 0);
+}
+
 #[track_caller]
 fn commonmark(input: &str, expected: &str, opts: Option<&Options>) {
     let arena = Arena::new();
@@ -71,6 +141,21 @@ fn commonmark(input: &str, expected: &str, opts: Option<&Options>) {
     );
 }
 
+#[track_caller]
+fn formatted<F>(format: F, kind: &str, input: &str, expected: &str, opts: impl Fn(&mut Options))
+where
+    F: for<'a> Fn(&'a AstNode<'a>, &Options, &mut dyn Write) -> io::Result<()>,
+{
+    let mut options = Options::default();
+    opts(&mut options);
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, input, &options);
+    let mut output = vec![];
+    format(root, &options, &mut output).unwrap();
+    compare_strs(&String::from_utf8(output).unwrap(), expected, kind, input);
+}
+
 #[track_caller]
 pub fn html(input: &str, expected: &str) {
     html_opts_i(input, expected, true, |_| ());
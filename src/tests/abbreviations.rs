@@ -0,0 +1,68 @@
+use super::*;
+
+#[test]
+fn abbreviations() {
+    html_opts!(
+        [extension.abbreviations],
+        concat!(
+            "*[HTML]: HyperText Markup Language\n",
+            "*[W3C]:  World Wide Web Consortium\n",
+            "*[HTML5]: HTML version 5 & up\n",
+            "\n",
+            "The HTML specification is maintained by the W3C; HTML5 is\n",
+            "its latest. HTMLish, XHTML and `HTML` are untouched.\n",
+            "\n",
+            "# About *HTML*\n",
+        ),
+        concat!(
+            "<p>The <abbr title=\"HyperText Markup Language\">HTML</abbr> specification is maintained by the \
+             <abbr title=\"World Wide Web Consortium\">W3C</abbr>; \
+             <abbr title=\"HTML version 5 &amp; up\">HTML5</abbr> is\n",
+            "its latest. HTMLish, XHTML and <code>HTML</code> are untouched.</p>\n",
+            "<h1>About <em><abbr title=\"HyperText Markup Language\">HTML</abbr></em></h1>\n",
+        ),
+        no_roundtrip,
+    );
+}
+
+#[test]
+fn abbreviation_definitions_in_ast() {
+    assert_ast_match!(
+        [extension.abbreviations],
+        "Some NASA text.\n"
+        "*[NASA]: National Aeronautics and Space Administration\n"
+        "> *[ESA]: European Space Agency\n",
+        (document (1:1-3:31) [
+            (paragraph (1:1-1:15) [
+                (text (1:1-1:15) "Some NASA text.")
+            ])
+            (abbreviation_definition (2:1-2:54))
+            (block_quote (3:1-3:31) [
+                (abbreviation_definition (3:3-3:31))
+            ])
+        ])
+    );
+}
+
+#[test]
+fn abbreviations_roundtrip() {
+    let input = concat!("A CSS file.\n", "\n", "*[CSS]: Cascading Style Sheets\n",);
+
+    html_opts!(
+        [extension.abbreviations],
+        input,
+        "<p>A <abbr title=\"Cascading Style Sheets\">CSS</abbr> file.</p>\n",
+    );
+
+    let mut options = Options::default();
+    options.extension.abbreviations = true;
+    commonmark(input, input, Some(&options));
+}
+
+#[test]
+fn abbreviations_disabled() {
+    html(
+        "*[HTML]: HyperText Markup Language\n",
+        "<p>*[HTML]: HyperText Markup Language</p>\n",
+    );
+}
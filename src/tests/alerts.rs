@@ -0,0 +1,108 @@
+use super::*;
+
+#[test]
+fn alerts() {
+    html_opts!(
+        [extension.alerts],
+        concat!(
+            "> [!NOTE]\n",
+            "> Useful *information*.\n",
+            ">\n",
+            "> - one\n",
+            "\n",
+            "> [!caution]\n",
+            "> Negative outcomes.\n",
+        ),
+        concat!(
+            "<div class=\"markdown-alert markdown-alert-note\">\n",
+            "<p class=\"markdown-alert-title\">Note</p>\n",
+            "<p>Useful <em>information</em>.</p>\n",
+            "<ul>\n",
+            "<li>one</li>\n",
+            "</ul>\n",
+            "</div>\n",
+            "<div class=\"markdown-alert markdown-alert-caution\">\n",
+            "<p class=\"markdown-alert-title\">Caution</p>\n",
+            "<p>Negative outcomes.</p>\n",
+            "</div>\n",
+        ),
+    );
+}
+
+#[test]
+fn alerts_with_title() {
+    html_opts!(
+        [extension.alerts],
+        "> [!WARNING] Mind the <gap>\n> Careful.\n",
+        concat!(
+            "<div class=\"markdown-alert markdown-alert-warning\">\n",
+            "<p class=\"markdown-alert-title\">Mind the &lt;gap&gt;</p>\n",
+            "<p>Careful.</p>\n",
+            "</div>\n",
+        ),
+    );
+}
+
+#[test]
+fn alerts_not_opened() {
+    html_opts!(
+        [extension.alerts],
+        concat!(
+            "> [!UNKNOWN]\n",
+            "> text\n",
+            "\n",
+            "> text\n",
+            "> [!NOTE]\n",
+            "\n",
+            "> [!TIP]x\n",
+        ),
+        concat!(
+            "<blockquote>\n",
+            "<p>[!UNKNOWN]\n",
+            "text</p>\n",
+            "</blockquote>\n",
+            "<blockquote>\n",
+            "<p>text\n",
+            "[!NOTE]</p>\n",
+            "</blockquote>\n",
+            "<blockquote>\n",
+            "<p>[!TIP]x</p>\n",
+            "</blockquote>\n",
+        ),
+        no_roundtrip,
+    );
+
+    html(
+        "> [!NOTE]\n> text\n",
+        "<blockquote>\n<p>[!NOTE]\ntext</p>\n</blockquote>\n",
+    );
+}
+
+#[test]
+fn alerts_commonmark() {
+    let mut options = Options::default();
+    options.extension.alerts = true;
+    assert_eq!(
+        markdown_to_commonmark(
+            "> [!important]   Read me\n> First.\n>\n> Second.\n",
+            &options
+        ),
+        "> [!IMPORTANT] Read me\n> First.\n> \n> Second.\n"
+    );
+}
+
+#[test]
+fn alerts_in_ast() {
+    assert_ast_match!(
+        [extension.alerts],
+        "> [!TIP]\n"
+        "> text\n",
+        (document (1:1-2:6) [
+            (alert (1:1-2:6) [
+                (paragraph (2:3-2:6) [
+                    (text (2:3-2:6) "text")
+                ])
+            ])
+        ])
+    );
+}
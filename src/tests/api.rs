@@ -3,7 +3,11 @@ use std::sync::{Arc, Mutex};
 use parser::BrokenLinkReference;
 
 use crate::{
-    adapters::{HeadingAdapter, HeadingMeta, SyntaxHighlighterAdapter},
+    adapters::{
+        HeadingAdapter, HeadingMeta, ImageDimensionAdapter, ImageDimensions, ImageMeta,
+        ImageResolver, LinkTitleAdapter, SyntaxHighlighterAdapter, TableCellAdapter, TableCellMeta,
+        TemplateResolver, UrlRewriter,
+    },
     nodes::Sourcepos,
 };
 
@@ -27,10 +31,39 @@ fn exercise_full_api() {
     let _: std::io::Result<()> =
         format_html_with_plugins(node, &default_options, &mut buffer, &default_plugins);
 
+    let mut render_buffers = html::RenderBuffers::with_capacity(1024);
+    let _: std::io::Result<()> = html::format_document_with_buffers(
+        node,
+        &default_options,
+        &default_plugins,
+        &mut render_buffers,
+    );
+    let _: &[u8] = render_buffers.output();
+    render_buffers.clear();
+
+    let (_, stats): (String, crate::stats::Stats) =
+        markdown_to_html_with_stats("document", &default_options, &default_plugins);
+    let _: usize = stats.bytes;
+    let _: usize = stats.nodes;
+    let _: std::time::Duration = stats.parse_time;
+    let _: std::time::Duration = stats.render_time;
+    let _: usize = stats.output_bytes;
+    let _: Option<Sourcepos> = stats.largest_block;
+
+    let usage = crate::stats::memory_usage(&arena, node);
+    let _: usize = usage.nodes;
+    let _: usize = usage.arena_capacity;
+    let _: usize = usage.arena_bytes;
+    let _: usize = usage.literal_bytes;
+    let _: usize = usage.total_bytes();
+
     let _: String = Anchorizer::new().anchorize("header".to_string());
 
     let _: &AstNode = parse_document(&arena, "document", &default_options);
 
+    let _: Result<&AstNode, std::str::Utf8Error> =
+        parse_document_bytes(&arena, b"document", &default_options);
+
     // Ensure the closure can modify its context.
     let mut blr_ctx_0 = 0;
     #[allow(deprecated)]
@@ -53,11 +86,19 @@ fn exercise_full_api() {
     extension.strikethrough(false);
     extension.tagfilter(false);
     extension.table(false);
+    extension.table_row_mismatch(TableRowMismatch::Paragraph);
+    extension.table_colspans(true);
     extension.autolink(false);
     extension.tasklist(false);
     extension.superscript(false);
+    extension.subscript(false);
     extension.header_ids(Some("abc".to_string()));
+    extension.header_id_collisions(HeaderIdCollisions::Hash);
+    extension.header_id_style(HeaderIdStyle::Ascii);
+    extension.header_ids_reserved(vec!["abc-def".to_string()]);
     extension.footnotes(false);
+    extension.footnote_placement(FootnotePlacement::Document);
+    extension.inline_footnotes(false);
     extension.description_lists(false);
     extension.math_dollars(false);
     extension.math_code(false);
@@ -72,12 +113,37 @@ fn exercise_full_api() {
     extension.underline(true);
     extension.spoiler(true);
     extension.greentext(true);
+    extension.abbreviations(true);
+    extension.template_variables(true);
+    extension.template_tags(true);
+    extension.conditional_blocks(true);
+    extension.details_blocks(true);
+    extension.container_blocks(true);
+    extension.citations(true);
+    extension.jsx_components(true);
+    extension.raw_regions(true);
+    extension.alerts(true);
+    extension.block_attributes(true);
+    extension.mentions(Some("https://example.com/{user}".to_string()));
+    extension.image_dimensions(false);
 
     let mut parse = ParseOptionsBuilder::default();
     parse.smart(false);
+    parse.smart_quotes(SmartQuotes::french());
     parse.default_info_string(Some("abc".to_string()));
     parse.relaxed_tasklist_matching(false);
     parse.relaxed_autolinks(false);
+    parse.preserve_entities(false);
+    parse.lossy_utf8(false);
+    parse.tab_width(8);
+    parse.max_nesting(10);
+    parse.max_input_size(Some(1 << 20));
+    parse.oversized_input(OversizedInput::Reject);
+    let mut diagnostic_callback = |d: Diagnostic| {
+        let _: Sourcepos = d.sourcepos;
+        let _: String = d.message;
+    };
+    parse.diagnostic_callback(Some(Arc::new(Mutex::new(&mut diagnostic_callback))));
     let mut blr_ctx_1 = 0;
     parse.broken_link_callback(Some(Arc::new(Mutex::new(
         &mut |blr: BrokenLinkReference| {
@@ -93,12 +159,24 @@ fn exercise_full_api() {
 
     let mut render = RenderOptionsBuilder::default();
     render.hardbreaks(false);
+    render.soft_breaks(SoftBreakStyle::Space);
+    render.cjk_soft_breaks(true);
+    render.non_ascii(NonAsciiStyle::Named);
+    render.punycode_hosts(true);
+    render.link_titles(LinkTitleStyle::AriaLabel);
     render.github_pre_lang(false);
+    render.code_class_prefix(Some("language-".into()));
     render.full_info_string(false);
     render.width(123456);
     render.unsafe_(false);
+    render.url_schemes(Some(vec!["https".into()]));
     render.escape(false);
+    render.html_comments(HtmlCommentStyle::Keep);
     render.list_style(ListStyleType::Dash);
+    render.ordered_list_style(OrderedListStyleType::Preserve);
+    render.heading_style(HeadingStyleType::Atx);
+    render.code_fence_style(CodeFenceStyleType::Backtick);
+    render.code_fence_length(3);
     render.sourcepos(false);
     render.experimental_inline_sourcepos(false);
     render.escaped_char_spans(false);
@@ -107,6 +185,14 @@ fn exercise_full_api() {
     render.gfm_quirks(true);
     render.prefer_fenced(true);
     render.figure_with_caption(true);
+    render.text_link_urls(true);
+    render.lazy_images(false);
+    render.external_link_base(Some("https://example.com".into()));
+    render.external_link_rel(true);
+    render.external_link_target(true);
+    render.node_classes(HashMap::from([("table".into(), "table".into())]));
+    render.strings(RenderStrings::default());
+    render.footnote_style(FootnoteStyle::default());
 
     pub struct MockAdapter {}
     impl SyntaxHighlighterAdapter for MockAdapter {
@@ -151,16 +237,74 @@ fn exercise_full_api() {
         }
     }
 
+    impl LinkTitleAdapter for MockAdapter {
+        fn transform(&self, _title: &str, _url: &str) -> String {
+            unreachable!()
+        }
+    }
+
+    impl UrlRewriter for MockAdapter {
+        fn rewrite(&self, _url: &str) -> String {
+            unreachable!()
+        }
+    }
+
+    impl TemplateResolver for MockAdapter {
+        fn resolve(&self, _name: &str, _filters: &[String]) -> Option<String> {
+            unreachable!()
+        }
+    }
+
+    impl ImageDimensionAdapter for MockAdapter {
+        fn dimensions(&self, _url: &str) -> Option<ImageDimensions> {
+            unreachable!()
+        }
+    }
+
+    impl ImageResolver for MockAdapter {
+        fn resolve(&self, _image: &ImageMeta) -> Option<String> {
+            unreachable!()
+        }
+    }
+
+    impl TableCellAdapter for MockAdapter {
+        fn enter(
+            &self,
+            _output: &mut dyn Write,
+            _cell: &TableCellMeta,
+            _sourcepos: Option<Sourcepos>,
+        ) -> io::Result<()> {
+            unreachable!()
+        }
+
+        fn exit(&self, _output: &mut dyn Write, _cell: &TableCellMeta) -> io::Result<()> {
+            unreachable!()
+        }
+    }
+
     let mock_adapter = MockAdapter {};
 
     let mut render_plugins = RenderPluginsBuilder::default();
     render_plugins.codefence_syntax_highlighter(Some(&mock_adapter));
     render_plugins.heading_adapter(Some(&mock_adapter));
+    render_plugins.link_title_adapter(Some(&mock_adapter));
+    render_plugins.link_url_rewriter(Some(&mock_adapter));
+    render_plugins.image_url_rewriter(Some(&mock_adapter));
+    render_plugins.image_resolver(Some(&mock_adapter));
+    render_plugins.template_resolver(Some(&mock_adapter));
+    render_plugins.image_dimension_adapter(Some(&mock_adapter));
+    render_plugins.table_cell_adapter(Some(&mock_adapter));
 
     let mut plugins = PluginsBuilder::default();
     plugins.render(render_plugins.build().unwrap());
 
     let _: String = markdown_to_html("# Yes", &default_options);
+    let _: &str = markdown_to_html_with_buffers(
+        "# Yes",
+        &default_options,
+        &default_plugins,
+        &mut render_buffers,
+    );
 
     //
 
@@ -172,6 +316,9 @@ fn exercise_full_api() {
     match &ast.value {
         nodes::NodeValue::Document => {}
         nodes::NodeValue::FrontMatter(_) => {}
+        nodes::NodeValue::Truncated(omitted) => {
+            let _: usize = *omitted;
+        }
         nodes::NodeValue::BlockQuote => {}
         nodes::NodeValue::List(nl) | nodes::NodeValue::Item(nl) => {
             match nl.list_type {
@@ -204,11 +351,13 @@ fn exercise_full_api() {
         nodes::NodeValue::Heading(nh) => {
             let _: u8 = nh.level;
             let _: bool = nh.setext;
+            let _: &nodes::NodeAttributes = &nh.attributes;
         }
         nodes::NodeValue::ThematicBreak => {}
         nodes::NodeValue::FootnoteDefinition(nfd) => {
             let _: &String = &nfd.name;
             let _: u32 = nfd.total_references;
+            let _: u32 = nfd.ix;
         }
         nodes::NodeValue::Table(nt) => {
             let _: &Vec<nodes::TableAlignment> = &nt.alignments;
@@ -240,10 +389,15 @@ fn exercise_full_api() {
         nodes::NodeValue::HtmlInline(html) => {
             let _: &String = html;
         }
+        nodes::NodeValue::Entity(entity) => {
+            let _: &String = &entity.literal;
+            let _: &String = &entity.characters;
+        }
         nodes::NodeValue::Emph => {}
         nodes::NodeValue::Strong => {}
         nodes::NodeValue::Strikethrough => {}
         nodes::NodeValue::Superscript => {}
+        nodes::NodeValue::Subscript => {}
         nodes::NodeValue::Link(nl) | nodes::NodeValue::Image(nl) => {
             let _: String = nl.url;
             let _: String = nl.title;
@@ -275,5 +429,49 @@ fn exercise_full_api() {
         nodes::NodeValue::EscapedTag(data) => {
             let _: &String = data;
         }
+        nodes::NodeValue::AbbreviationDefinition(nad) => {
+            let _: &String = &nad.label;
+            let _: &String = &nad.title;
+        }
+        nodes::NodeValue::TemplateVariable(ntv) => {
+            let _: &String = &ntv.name;
+            let _: &Vec<String> = &ntv.filters;
+        }
+        nodes::NodeValue::TemplateTag(literal) => {
+            let _: &String = literal;
+        }
+        nodes::NodeValue::Container(nc) => {
+            let _: usize = nc.fence_length;
+            let _: usize = nc.fence_offset;
+            let _: nodes::ContainerKind = nc.kind;
+            let _: &String = &nc.name;
+            let _: &String = &nc.info;
+            let _: &Option<String> = &nc.attributes.id;
+            let _: &Vec<String> = &nc.attributes.classes;
+            let _: &Vec<(String, String)> = &nc.attributes.attributes;
+        }
+        nodes::NodeValue::Alert(nalert) => {
+            let _: nodes::AlertType = nalert.alert_type;
+            let _: &Option<String> = &nalert.title;
+        }
+        nodes::NodeValue::Citation(nc) => {
+            for item in &nc.items {
+                let _: &String = &item.prefix;
+                let _: &String = &item.key;
+                let _: &String = &item.locator;
+            }
+        }
+        nodes::NodeValue::ComponentBlock(nc) | nodes::NodeValue::ComponentInline(nc) => {
+            let _: &String = &nc.name;
+            let _: &String = &nc.literal;
+        }
+        nodes::NodeValue::Raw(literal) => {
+            let _: &String = literal;
+        }
+        nodes::NodeValue::ExtensionBlock(ne) | nodes::NodeValue::ExtensionInline(ne) => {
+            let _: &String = &ne.extension;
+            let _: &String = &ne.literal;
+            let _: Option<&u32> = ne.data.as_ref().and_then(|data| data.downcast_ref());
+        }
     }
 }
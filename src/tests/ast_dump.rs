@@ -0,0 +1,44 @@
+use super::*;
+
+#[test]
+fn ast_dump() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.tasklist = true;
+    options.extension.footnotes = true;
+    let root = parse_document(
+        &arena,
+        concat!(
+            "Title\n",
+            "=====\n",
+            "\n",
+            "    ünïcödé ünïcödé ünïcödé ünïcödé ünïcödé ünïcödé\n",
+            "\n",
+            "- [x] a `code` <b>[^1]\n",
+            "\n",
+            "[^1]: Note.\n",
+        ),
+        &options,
+    );
+
+    assert_eq!(
+        root.ast_dump(),
+        concat!(
+            "document (1:1-8:11)\n",
+            "  heading level=1 setext=true (1:1-2:5)\n",
+            "    text \"Title\" (1:1-1:5)\n",
+            "  code_block fenced=false \"ünïcödé ünïcödé ünïcödé ünïcödé ünïcödé \"… (4:5-5:0)\n",
+            "  list type=bullet tight=true (6:1-7:0)\n",
+            "    taskitem checked=true (6:1-7:0)\n",
+            "      paragraph (6:7-6:22)\n",
+            "        text \"a \" (6:7-6:8)\n",
+            "        code \"code\" (6:10-6:13)\n",
+            "        text \" \" (6:15-6:15)\n",
+            "        html_inline \"<b>\" (6:16-6:18)\n",
+            "        footnote_reference name=\"1\" (6:19-6:22)\n",
+            "  footnote_definition name=\"1\" (8:1-8:11)\n",
+            "    paragraph (8:7-8:11)\n",
+            "      text \"Note.\" (8:7-8:11)\n",
+        )
+    );
+}
@@ -395,3 +395,24 @@ fn autolink_fuzz_we() {
         no_roundtrip,
     );
 }
+
+#[test]
+fn autolink_punycode_hosts() {
+    html_opts!(
+        [extension.autolink, render.punycode_hosts],
+        concat!(
+            "https://münchen.example:8080/straße?q=ü\n",
+            "www.例え.jp\n",
+            "[m](mailto:info@Bücher.example?subject=é)\n",
+            "[x](https://user@bücher.example#frag)\n",
+            "[y](/relative/ü)\n",
+        ),
+        concat!(
+            "<p><a href=\"https://xn--mnchen-3ya.example:8080/stra%C3%9Fe?q=%C3%BC\">https://münchen.example:8080/straße?q=ü</a>\n",
+            "<a href=\"http://www.xn--r8jz45g.jp\">www.例え.jp</a>\n",
+            "<a href=\"mailto:info@xn--bcher-kva.example?subject=%C3%A9\">m</a>\n",
+            "<a href=\"https://user@xn--bcher-kva.example#frag\">x</a>\n",
+            "<a href=\"/relative/%C3%BC\">y</a></p>\n",
+        ),
+    );
+}
@@ -0,0 +1,154 @@
+use crate::bibliography::{resolve_citations, Author, Bibliography, Reference};
+
+use super::*;
+
+#[test]
+fn citations() {
+    html_opts!(
+        [extension.citations],
+        concat!(
+            "As [see @doe99, pp. 33-35; also @smith04 chap. 1] and [@knuth84].\n",
+            "\n",
+            "Not [@] or [user@example.com] or [@link](/url) or [@ref][x] or \\[@esc].\n",
+            "\n",
+            "[x]: /ref\n",
+        ),
+        concat!(
+            "<p>As <span class=\"citation\" data-cites=\"doe99 smith04\">\
+             [see @doe99, pp. 33-35; also @smith04, chap. 1]</span> and \
+             <span class=\"citation\" data-cites=\"knuth84\">[@knuth84]</span>.</p>\n",
+            "<p>Not [@] or [user@example.com] or <a href=\"/url\">@link</a> or \
+             <a href=\"/ref\">@ref</a> or [@esc].</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn citations_sourcepos() {
+    assert_ast_match!(
+        [extension.citations],
+        "Hi [@a; @b.].\n",
+        (document (1:1-1:13) [
+            (paragraph (1:1-1:13) [
+                (text (1:1-1:3) "Hi ")
+                (citation (1:4-1:12))
+                (text (1:13-1:13) ".")
+            ])
+        ])
+    );
+}
+
+fn bibliography() -> HashMap<String, Reference> {
+    let author = |family: &str, given: &str| Author {
+        family: family.to_string(),
+        given: given.to_string(),
+    };
+
+    let mut bibliography = HashMap::new();
+    bibliography.insert(
+        "knuth84".to_string(),
+        Reference {
+            authors: vec![author("Knuth", "Donald E.")],
+            year: "1984".to_string(),
+            title: "Literate Programming".to_string(),
+            container: "The Computer Journal".to_string(),
+            url: String::new(),
+        },
+    );
+    bibliography.insert(
+        "ak".to_string(),
+        Reference {
+            authors: vec![author("Aho", "Alfred"), author("Kernighan", "Brian")],
+            year: "1988".to_string(),
+            title: "The AWK Programming Language".to_string(),
+            ..Default::default()
+        },
+    );
+    bibliography.insert(
+        "many".to_string(),
+        Reference {
+            authors: vec![
+                author("Zed", ""),
+                author("Young", "Yan"),
+                author("Xu", "Xi"),
+            ],
+            title: "Collected Works".to_string(),
+            url: "https://example.com/works".to_string(),
+            ..Default::default()
+        },
+    );
+    bibliography
+}
+
+#[test]
+fn resolve_citations_to_references() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.citations = true;
+    let root = parse_document(
+        &arena,
+        concat!(
+            "# Intro\n",
+            "\n",
+            "See @knuth84 and [e.g. @many, p. 2; @ak].\n",
+            "\n",
+            "> Again [@knuth84; @nope; @nope].\n",
+        ),
+        &options,
+    );
+
+    let resolved = resolve_citations(&arena, root, &bibliography());
+    assert_eq!(resolved.missing, ["nope"]);
+    root.append(resolved.references);
+
+    let mut output = vec![];
+    format_commonmark(root, &options, &mut output).unwrap();
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        concat!(
+            "# Intro\n",
+            "\n",
+            "See @knuth84 and (e.g. Zed et al., p. 2; Aho and Kernighan 1988).\n",
+            "\n",
+            "> Again (Knuth 1984; nope?; nope?).\n",
+            "\n",
+            "- Aho, Alfred and Brian Kernighan. 1988. *The AWK Programming Language*.\n",
+            "- Knuth, Donald E. 1984. *Literate Programming*. The Computer Journal.\n",
+            "- Zed, Yan Young and Xi Xu. *Collected Works*. <https://example.com/works>\n",
+        ),
+        "commonmark",
+        "",
+    );
+}
+
+#[test]
+fn resolve_citations_custom_bibliography() {
+    struct Keys;
+
+    impl Bibliography for Keys {
+        fn reference(&self, key: &str) -> Option<Reference> {
+            Some(Reference {
+                title: key.to_uppercase(),
+                ..Default::default()
+            })
+        }
+    }
+
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.citations = true;
+    let root = parse_document(&arena, "No citations.\n", &options);
+    let resolved = resolve_citations(&arena, root, &Keys);
+    assert!(resolved.references.first_child().is_none());
+    assert!(resolved.missing.is_empty());
+
+    let root = parse_document(&arena, "[@b] [@a]\n", &options);
+    let resolved = resolve_citations(&arena, root, &Keys);
+    root.append(resolved.references);
+    let mut output = vec![];
+    format_html(root, &options, &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "<p>(B) (A)</p>\n<ul>\n<li><em>A</em>.</li>\n<li><em>B</em>.</li>\n</ul>\n"
+    );
+}
@@ -0,0 +1,127 @@
+use super::*;
+use crate::nodes::NodeAttributes;
+
+#[test]
+fn block_attributes_headings() {
+    html_opts!(
+        [extension.block_attributes],
+        concat!(
+            "# Intro {#intro}\n",
+            "\n",
+            "## Setup {.big .red} ##\n",
+            "\n",
+            "Usage {#usage}\n",
+            "=====\n",
+            "\n",
+            "### Not{#attributes}\n",
+            "\n",
+            "### Nor {these}\n",
+        ),
+        concat!(
+            "<h1 id=\"intro\">Intro</h1>\n",
+            "<h2 class=\"big red\">Setup</h2>\n",
+            "<h1 id=\"usage\">Usage</h1>\n",
+            "<h3>Not{#attributes}</h3>\n",
+            "<h3>Nor {these}</h3>\n",
+        ),
+    );
+}
+
+#[test]
+fn block_attributes_disabled() {
+    html("# Intro {#intro}\n", "<h1>Intro {#intro}</h1>\n");
+}
+
+#[test]
+fn block_attributes_code_blocks() {
+    html_opts!(
+        [extension.block_attributes],
+        concat!(
+            "``` rust {#main}\n",
+            "fn main() {}\n",
+            "```\n",
+            "\n",
+            "~~~ {.numbered}\n",
+            "text\n",
+            "~~~\n",
+        ),
+        concat!(
+            "<pre id=\"main\"><code class=\"language-rust\">fn main() {}\n",
+            "</code></pre>\n",
+            "<pre class=\"numbered\"><code>text\n",
+            "</code></pre>\n",
+        ),
+    );
+}
+
+#[test]
+fn block_attributes_header_ids() {
+    html_opts_i(
+        "# Intro {#start}\n\n# Start\n\n# Intro\n",
+        concat!(
+            "<h1><a href=\"#start\" aria-hidden=\"true\" class=\"anchor\" id=\"user-content-start\"></a>Intro</h1>\n",
+            "<h1><a href=\"#start-1\" aria-hidden=\"true\" class=\"anchor\" id=\"user-content-start-1\"></a>Start</h1>\n",
+            "<h1><a href=\"#intro\" aria-hidden=\"true\" class=\"anchor\" id=\"user-content-intro\"></a>Intro</h1>\n",
+        ),
+        true,
+        |opts| {
+            opts.extension.block_attributes = true;
+            opts.extension.header_ids = Some("user-content-".to_owned());
+        },
+    );
+}
+
+#[test]
+fn block_attributes_unsafe() {
+    html_opts!(
+        [extension.block_attributes],
+        "## Title {onclick=\"alert(1)\"}\n",
+        "<h2>Title</h2>\n",
+    );
+    html_opts!(
+        [extension.block_attributes, render.unsafe_],
+        "## Title {data-x=\"a b\" lang=en}\n",
+        "<h2 data-x=\"a b\" lang=\"en\">Title</h2>\n",
+    );
+}
+
+#[test]
+fn block_attributes_ast() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.block_attributes = true;
+    let root = parse_document(
+        &arena,
+        "# Title {#t .a .b k=v}\n\n```rust {.c}\nx\n```\n",
+        &options,
+    );
+
+    let heading = root.first_child().unwrap();
+    match heading.data.borrow().value {
+        NodeValue::Heading(ref nh) => assert_eq!(
+            nh.attributes,
+            NodeAttributes {
+                id: Some("t".to_string()),
+                classes: vec!["a".to_string(), "b".to_string()],
+                attributes: vec![("k".to_string(), "v".to_string())],
+            }
+        ),
+        ref value => panic!("expected a heading, got {:?}", value),
+    }
+    assert_eq!(heading.text_content(), "Title");
+
+    match root.last_child().unwrap().data.borrow().value {
+        NodeValue::CodeBlock(ref ncb) => {
+            assert_eq!(ncb.info, "rust");
+            assert_eq!(ncb.attributes.classes, ["c"]);
+        }
+        ref value => panic!("expected a code block, got {:?}", value),
+    }
+
+    let mut output = vec![];
+    format_commonmark(root, &options, &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "# Title {#t .a .b k=v}\n\n``` rust {.c}\nx\n```\n"
+    );
+}
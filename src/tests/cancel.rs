@@ -0,0 +1,93 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cancel::{CancellationToken, Cancelled, RenderError};
+
+use super::*;
+
+#[test]
+fn uncancelled_matches_uncancellable() {
+    let mut options = Options::default();
+    options.extension.footnotes = true;
+    options.extension.table = true;
+    let input = "# Hi[^1]\n\n| a | b |\n|---|---|\n| *c* | d |\n\n[^1]: There.\n";
+
+    let token = CancellationToken::with_timeout(Duration::from_secs(60));
+    assert_eq!(
+        markdown_to_html_cancellable(input, &options, &Plugins::default(), &token).unwrap(),
+        markdown_to_html(input, &options)
+    );
+}
+
+#[test]
+fn cancelled_before_parsing() {
+    let token = CancellationToken::new();
+    token.cancel();
+    assert!(token.is_cancelled());
+
+    let arena = Arena::new();
+    for input in ["", "one\n", "one\ntwo"] {
+        assert_eq!(
+            parse_document_cancellable(&arena, input, &Options::default(), &token).err(),
+            Some(Cancelled)
+        );
+    }
+}
+
+#[test]
+fn deadline_passed() {
+    let token = CancellationToken::with_deadline(Instant::now());
+    assert!(token.is_cancelled());
+    assert!(matches!(
+        markdown_to_html_cancellable("hi", &Options::default(), &Plugins::default(), &token),
+        Err(RenderError::Cancelled)
+    ));
+
+    assert!(!CancellationToken::with_timeout(Duration::from_secs(60)).is_cancelled());
+}
+
+#[test]
+fn cancelled_while_rendering() {
+    let arena = Arena::new();
+    let options = Options::default();
+    let root = parse_document(&arena, &"*a* ".repeat(1000), &options);
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let mut html = vec![];
+    let err = format_html_cancellable(root, &options, &mut html, &Plugins::default(), &token)
+        .unwrap_err();
+    assert!(Cancelled::is_cancelled(&err));
+    assert_eq!(err.to_string(), "cancelled");
+    assert!(html.is_empty());
+}
+
+#[test]
+fn render_error_not_cancelled() {
+    let mut options = Options::default();
+    options.extension.header_ids = Some("".to_owned());
+    options.extension.header_id_collisions = HeaderIdCollisions::Error;
+
+    let token = CancellationToken::new();
+    let err = markdown_to_html_cancellable("# Hi\n\n# Hi\n", &options, &Plugins::default(), &token)
+        .unwrap_err();
+    assert!(matches!(err, RenderError::Io(_)));
+    assert_eq!(err.to_string(), "duplicate heading id \"hi\"");
+}
+
+#[test]
+fn cancelled_by_a_clone_on_another_thread() {
+    let input = "- *a*\n".repeat(200_000);
+    let token = CancellationToken::new();
+    let canceller = {
+        let token = token.clone();
+        thread::spawn(move || token.cancel())
+    };
+    canceller.join().unwrap();
+
+    let arena = Arena::new();
+    assert_eq!(
+        parse_document_cancellable(&arena, &input, &Options::default(), &token).err(),
+        Some(Cancelled)
+    );
+}
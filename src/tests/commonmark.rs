@@ -12,6 +12,34 @@ fn commonmark_removes_redundant_strong() {
     commonmark(input, output, None);
 }
 
+#[test]
+fn commonmark_preserves_emphasis_delimiters() {
+    let input = "_a_ *b* __c__ **d** ___e___ *f **g** h*\n";
+    commonmark(input, input, None);
+}
+
+#[test]
+fn emphasis_delimiters_recorded() {
+    let arena = Arena::new();
+    let root = parse_document(&arena, "___a___ *b*\n", &Options::default());
+    let delims = root
+        .descendants()
+        .filter_map(|node| {
+            let ast = node.data.borrow();
+            let ne = ast.emphasis?;
+            Some((
+                ast.value.xml_node_name(),
+                ne.delim_char,
+                ne.delim_run_length,
+            ))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        delims,
+        [("emph", b'_', 3), ("strong", b'_', 3), ("emph", b'*', 1)]
+    );
+}
+
 #[test]
 fn commonmark_avoids_spurious_backslash() {
     let arena = Arena::new();
@@ -79,3 +107,133 @@ fn commonmark_relist() {
         Some(&options),
     );
 }
+
+#[test]
+fn commonmark_preserve_markers() {
+    let mut options = Options::default();
+    options.render.list_style = ListStyleType::Preserve;
+    options.render.heading_style = HeadingStyleType::Preserve;
+    options.render.code_fence_style = CodeFenceStyleType::Preserve;
+
+    commonmark(
+        concat!(
+            "Title\n",
+            "=====\n",
+            "\n",
+            "* a\n",
+            "* b\n",
+            "\n",
+            "+ c\n",
+            "\n",
+            "~~~~ rust\n",
+            "code\n",
+            "~~~~\n",
+            "\n",
+            "> Sub *é*\n",
+            "> ---\n",
+            "\n",
+            "```\n",
+            "    indented\n",
+            "```\n",
+            "\n",
+            "    indented\n",
+        ),
+        concat!(
+            "Title\n",
+            "=====\n",
+            "\n",
+            "* a\n",
+            "* b\n",
+            "\n",
+            "<!-- end list -->\n",
+            "\n",
+            "+ c\n",
+            "\n",
+            "<!-- end list -->\n",
+            "\n",
+            "~~~~ rust\n",
+            "code\n",
+            "~~~~\n",
+            "\n",
+            "> Sub *é*\n",
+            "> -------\n",
+            "\n",
+            "```\n",
+            "    indented\n",
+            "```\n",
+            "\n",
+            "    indented\n",
+        ),
+        Some(&options),
+    );
+
+    // Setext headings can't be empty.
+    let arena = Arena::new();
+    let root = parse_document(&arena, "Gone\n---\n", &options);
+    root.first_child().unwrap().first_child().unwrap().detach();
+    let mut output = vec![];
+    cm::format_document(root, &options, &mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "## \n");
+}
+
+#[test]
+fn commonmark_marker_styles() {
+    let mut options = Options::default();
+    options.render.list_style = ListStyleType::Star;
+    options.render.ordered_list_style = OrderedListStyleType::Paren;
+    options.render.heading_style = HeadingStyleType::Setext;
+    options.render.code_fence_style = CodeFenceStyleType::Tilde;
+    options.render.code_fence_length = 4;
+
+    commonmark(
+        concat!(
+            "# Title\n",
+            "\n",
+            "Section\n",
+            "-------\n",
+            "\n",
+            "### Sub\n",
+            "\n",
+            "- a\n",
+            "\n",
+            "1. b\n",
+            "2. c\n",
+            "\n",
+            "```rust\n",
+            "~~~~~\n",
+            "```\n",
+        ),
+        concat!(
+            "Title\n",
+            "=====\n",
+            "\n",
+            "Section\n",
+            "-------\n",
+            "\n",
+            "### Sub\n",
+            "\n",
+            "* a\n",
+            "\n",
+            "<!-- end list -->\n",
+            "\n",
+            "1)  b\n",
+            "2)  c\n",
+            "\n",
+            "<!-- end list -->\n",
+            "\n",
+            "~~~~~~ rust\n",
+            "~~~~~\n",
+            "~~~~~~\n",
+        ),
+        Some(&options),
+    );
+
+    // Each style can be preserved separately.
+    options.render.heading_style = HeadingStyleType::Preserve;
+    options.render.code_fence_style = CodeFenceStyleType::Preserve;
+    commonmark(
+        "# Title\n\n```rust\nx\n```\n\n+ a\n",
+        "# Title\n\n``` rust\nx\n```\n\n* a\n",
+        Some(&options),
+    );
+}
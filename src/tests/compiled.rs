@@ -0,0 +1,74 @@
+use std::thread;
+
+use crate::compiled::{CompiledOptions, OptionsError};
+use crate::extensions::ComrakExtension;
+use crate::plugins::cache::InMemoryCodeBlockCache;
+
+use super::*;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn options_are_send_and_sync() {
+    assert_send_sync::<Options>();
+    assert_send_sync::<Plugins>();
+    assert_send_sync::<CompiledOptions>();
+}
+
+#[test]
+fn compiled_options_shared_between_threads() {
+    let mut options = Options::gfm();
+    options.extension.footnotes = true;
+    let compiled = CompiledOptions::new(options, Plugins::default()).unwrap();
+
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let compiled = compiled.clone();
+            thread::spawn(move || {
+                compiled.markdown_to_html(&format!("- [x] {}[^n]\n\n[^n]: N\n", i))
+            })
+        })
+        .collect();
+    for (i, handle) in handles.into_iter().enumerate() {
+        let html = handle.join().unwrap();
+        assert_eq!(
+            html,
+            markdown_to_html(&format!("- [x] {}[^n]\n\n[^n]: N\n", i), compiled.options())
+        );
+        assert!(html.contains("checked=\"\""));
+    }
+}
+
+#[test]
+fn compiled_options_errors() {
+    let mut options = Options::default();
+    options.extension.front_matter_delimiter = Some(String::new());
+    assert_eq!(
+        CompiledOptions::new(options, Plugins::default()).unwrap_err(),
+        OptionsError::EmptyFrontMatterDelimiter
+    );
+
+    let mut plugins = Plugins::default();
+    plugins.render.codefence_highlight_cache =
+        Some(Box::leak(Box::new(InMemoryCodeBlockCache::new())));
+    assert_eq!(
+        CompiledOptions::new(Options::default(), plugins).unwrap_err(),
+        OptionsError::HighlightCacheWithoutHighlighter
+    );
+
+    struct Named;
+    impl ComrakExtension for Named {
+        fn name(&self) -> &str {
+            "named"
+        }
+    }
+    let mut options = Options::default();
+    options.extensions.register(Box::new(Named));
+    options.extensions.register(Box::new(Named));
+    let err = CompiledOptions::new(options, Plugins::default()).unwrap_err();
+    assert_eq!(err, OptionsError::DuplicateExtension("named".into()));
+    assert_eq!(
+        err.to_string(),
+        "more than one extension is named \"named\""
+    );
+}
@@ -0,0 +1,187 @@
+use crate::conditionals::resolve_conditionals;
+use crate::nodes::ContainerKind;
+
+use super::*;
+
+fn resolved(input: &str, flags: &[(&str, &str)]) -> String {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.conditional_blocks = true;
+    let root = parse_document(&arena, input, &options);
+    resolve_conditionals(root, |ncond| ncond.matches(flags));
+    let mut html = vec![];
+    format_html(root, &options, &mut html).unwrap();
+    String::from_utf8(html).unwrap()
+}
+
+#[test]
+fn conditional_blocks() {
+    html_opts!(
+        [extension.conditional_blocks],
+        concat!(
+            "::: if feature=\"enterprise\"\n",
+            "# SSO\n",
+            "\n",
+            "- one\n",
+            "- two\n",
+            ":::\n",
+            "after\n",
+        ),
+        concat!(
+            "<h1>SSO</h1>\n",
+            "<ul>\n",
+            "<li>one</li>\n",
+            "<li>two</li>\n",
+            "</ul>\n",
+            "<p>after</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn conditional_blocks_not_opened() {
+    html_opts!(
+        [extension.conditional_blocks],
+        concat!(
+            ":: if a\n",
+            "\n",
+            "::: if\n",
+            "\n",
+            "::: iffy\n",
+            "\n",
+            "::: if a=\"b\n",
+            "\n",
+            "    ::: if a\n",
+            "\n",
+            ":::\n",
+        ),
+        concat!(
+            "<p>:: if a</p>\n",
+            "<p>::: if</p>\n",
+            "<p>::: iffy</p>\n",
+            "<p>::: if a=&quot;b</p>\n",
+            "<pre><code>::: if a\n",
+            "</code></pre>\n",
+            "<p>:::</p>\n",
+        ),
+        no_roundtrip,
+    );
+}
+
+#[test]
+fn conditional_blocks_resolved() {
+    let input = concat!(
+        "::: if edition=\"enterprise\"\n",
+        "Enterprise.\n",
+        ":::\n",
+        "\n",
+        "::: if edition=community beta\n",
+        "Community beta.\n",
+        ":::\n",
+        "\n",
+        "::: if beta\n",
+        "Beta.\n",
+        ":::\n",
+    );
+
+    assert_eq!(resolved(input, &[]), "");
+    assert_eq!(
+        resolved(input, &[("edition", "enterprise"), ("beta", "")]),
+        "<p>Enterprise.</p>\n<p>Beta.</p>\n"
+    );
+    assert_eq!(
+        resolved(input, &[("edition", "community"), ("beta", "yes")]),
+        "<p>Community beta.</p>\n<p>Beta.</p>\n"
+    );
+}
+
+#[test]
+fn conditional_blocks_nested() {
+    let input = concat!(
+        "::: if a\n",
+        "A\n",
+        "::: if b\n",
+        "B\n",
+        ":::\n",
+        "A again\n",
+        ":::\n",
+        "outside\n",
+    );
+
+    assert_eq!(
+        resolved(input, &[("a", ""), ("b", "")]),
+        "<p>A</p>\n<p>B</p>\n<p>A again</p>\n<p>outside</p>\n"
+    );
+    assert_eq!(
+        resolved(input, &[("a", "")]),
+        "<p>A</p>\n<p>A again</p>\n<p>outside</p>\n"
+    );
+    assert_eq!(resolved(input, &[("b", "")]), "<p>outside</p>\n");
+
+    assert_eq!(
+        resolved("::::: if a\n::: if b\nB\n:::::\noutside\n", &[("a", "")],),
+        "<p>outside</p>\n"
+    );
+}
+
+#[test]
+fn conditional_blocks_in_lists() {
+    html_opts!(
+        [extension.conditional_blocks],
+        concat!(
+            "- item\n",
+            "\n",
+            "  ::: if a\n",
+            "  inside\n",
+            "  :::\n",
+            "- next\n",
+        ),
+        concat!(
+            "<ul>\n",
+            "<li>\n",
+            "<p>item</p>\n",
+            "<p>inside</p>\n",
+            "</li>\n",
+            "<li>\n",
+            "<p>next</p>\n",
+            "</li>\n",
+            "</ul>\n",
+        ),
+    );
+}
+
+#[test]
+fn conditional_blocks_in_ast() {
+    assert_ast_match!(
+        [extension.conditional_blocks],
+        "::: if os=linux arch=\"x86 64\"\n"
+        "text\n"
+        ":::\n",
+        (document (1:1-3:3) [
+            (conditional (1:1-3:3) [
+                (paragraph (2:1-2:4) [
+                    (text (2:1-2:4) "text")
+                ])
+            ])
+        ])
+    );
+
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.conditional_blocks = true;
+    let root = parse_document(
+        &arena,
+        "::: if os=linux arch=\"x86 64\" gpu\n:::\n",
+        &options,
+    );
+    match root.first_child().unwrap().data.borrow().value {
+        NodeValue::Container(ref ncond) => {
+            assert_eq!(ncond.kind, ContainerKind::Conditional);
+            assert_eq!(ncond.get("os"), Some("linux"));
+            assert_eq!(ncond.get("arch"), Some("x86 64"));
+            assert_eq!(ncond.get("gpu"), Some(""));
+            assert_eq!(ncond.condition(), "os=\"linux\" arch=\"x86 64\" gpu");
+        }
+        ref other => panic!("expected conditional, got {:?}", other),
+    };
+}
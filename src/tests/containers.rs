@@ -0,0 +1,198 @@
+use super::*;
+
+#[test]
+fn container_blocks() {
+    html_opts!(
+        [extension.container_blocks],
+        concat!(
+            "::: warning\n",
+            "Mind the *gap*.\n",
+            "\n",
+            "- one\n",
+            ":::\n",
+            "after\n",
+        ),
+        concat!(
+            "<div class=\"warning\">\n",
+            "<p>Mind the <em>gap</em>.</p>\n",
+            "<ul>\n",
+            "<li>one</li>\n",
+            "</ul>\n",
+            "</div>\n",
+            "<p>after</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn container_blocks_attributes() {
+    html_opts!(
+        [extension.container_blocks],
+        concat!(
+            "::: tip {.wide .dark}\n",
+            "a\n",
+            ":::\n",
+            "\n",
+            ":::{#only-id}\n",
+            "b\n",
+            ":::\n",
+        ),
+        concat!(
+            "<div class=\"tip wide dark\">\n",
+            "<p>a</p>\n",
+            "</div>\n",
+            "<div id=\"only-id\">\n",
+            "<p>b</p>\n",
+            "</div>\n",
+        ),
+    );
+    html_opts!(
+        [extension.container_blocks, render.unsafe_],
+        "::: tabs {data-group=\"os\"}\nc\n:::\n",
+        "<div class=\"tabs\" data-group=\"os\">\n<p>c</p>\n</div>\n",
+    );
+}
+
+#[test]
+fn container_blocks_not_opened() {
+    html_opts!(
+        [extension.container_blocks],
+        concat!(
+            ":: warning\n",
+            "\n",
+            "::: two words\n",
+            "\n",
+            "::: {not attributes}\n",
+            "\n",
+            "    ::: warning\n",
+            "\n",
+            ":::\n",
+        ),
+        concat!(
+            "<p>:: warning</p>\n",
+            "<p>::: two words</p>\n",
+            "<p>::: {not attributes}</p>\n",
+            "<pre><code>::: warning\n",
+            "</code></pre>\n",
+            "<p>:::</p>\n",
+        ),
+        no_roundtrip,
+    );
+}
+
+#[test]
+fn container_blocks_nested() {
+    html_opts!(
+        [extension.container_blocks, extension.details_blocks],
+        concat!(
+            ":::: tabs\n",
+            "::: details Linux\n",
+            "::: tab\n",
+            "apt\n",
+            ":::\n",
+            ":::\n",
+            "outer\n",
+            "::::\n",
+            "outside\n",
+        ),
+        concat!(
+            "<div class=\"tabs\">\n",
+            "<details>\n",
+            "<summary>Linux</summary>\n",
+            "<div class=\"tab\">\n",
+            "<p>apt</p>\n",
+            "</div>\n",
+            "</details>\n",
+            "<p>outer</p>\n",
+            "</div>\n",
+            "<p>outside</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn container_blocks_in_ast() {
+    assert_ast_match!(
+        [extension.container_blocks],
+        "> ::: note\n"
+        "> text\n"
+        "> :::\n",
+        (document (1:1-3:5) [
+            (block_quote (1:1-3:5) [
+                (container (1:3-3:5) [
+                    (paragraph (2:3-2:6) [
+                        (text (2:3-2:6) "text")
+                    ])
+                ])
+            ])
+        ])
+    );
+
+    let mut options = Options::default();
+    options.extension.container_blocks = true;
+    assert_eq!(
+        markdown_to_commonmark_xml("::: note {#n .x}\n:::\n", &options),
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE document SYSTEM \"CommonMark.dtd\">\n",
+            "<document xmlns=\"http://commonmark.org/xml/1.0\">\n",
+            "  <container name=\"note\" attributes=\"{#n .x}\" />\n",
+            "</document>\n",
+        )
+    );
+    assert_eq!(
+        markdown_to_commonmark("::: note {#n .x}\ntext\n:::\n", &options),
+        "::: note {#n .x}\n\ntext\n\n:::\n"
+    );
+}
+
+#[test]
+fn container_blocks_kinds() {
+    use crate::nodes::ContainerKind;
+
+    let kinds = |input: &str, options: &Options| -> Vec<(ContainerKind, String, String)> {
+        let arena = Arena::new();
+        let root = parse_document(&arena, input, options);
+        root.children()
+            .map(|node| match node.data.borrow().value {
+                NodeValue::Container(ref nc) => (nc.kind, nc.name.clone(), nc.info.clone()),
+                ref other => panic!("expected container, got {:?}", other),
+            })
+            .collect()
+    };
+    let input = "::: note\n:::\n\n::: if os=linux\n:::\n\n::: details More\n:::\n";
+
+    let mut options = Options::default();
+    options.extension.container_blocks = true;
+    options.extension.conditional_blocks = true;
+    options.extension.details_blocks = true;
+    assert_eq!(
+        kinds(input, &options),
+        vec![
+            (ContainerKind::Named, "note".to_string(), "".to_string()),
+            (
+                ContainerKind::Conditional,
+                "if".to_string(),
+                "os=linux".to_string()
+            ),
+            (
+                ContainerKind::Details,
+                "details".to_string(),
+                "More".to_string()
+            ),
+        ]
+    );
+
+    // Without their own options, `if` and `details` are only names, and the
+    // rest of their fences isn't an attribute block.
+    options.extension.conditional_blocks = false;
+    options.extension.details_blocks = false;
+    assert_eq!(
+        kinds("::: details\n:::\n", &options),
+        vec![(ContainerKind::Named, "details".to_string(), "".to_string())]
+    );
+    assert_eq!(
+        markdown_to_html("::: details More\n:::\n", &options),
+        "<p>::: details More\n:::</p>\n"
+    );
+}
@@ -39,6 +39,34 @@ fn codefence() {
     );
 }
 
+#[test]
+fn codefence_class_prefix() {
+    html_opts_i(
+        concat!("``` rust yum\n", "fn main();\n", "```\n"),
+        "<pre><code class=\"lang-rust\">fn main();\n</code></pre>\n",
+        true,
+        |opts| opts.render.code_class_prefix = Some("lang-".to_owned()),
+    );
+    html_opts_i(
+        concat!("``` rust yum\n", "fn main();\n", "```\n"),
+        "<pre lang=\"rust\"><code>fn main();\n</code></pre>\n",
+        true,
+        |opts| {
+            opts.render.code_class_prefix = Some("lang-".to_owned());
+            opts.render.github_pre_lang = true;
+        },
+    );
+    html_opts_i(
+        concat!("``` math\n", "x^2\n", "```\n"),
+        "<pre><code class=\"math\" data-math-style=\"display\">x^2\n</code></pre>\n",
+        true,
+        |opts| {
+            opts.extension.math_code = true;
+            opts.render.code_class_prefix = Some(String::new());
+        },
+    );
+}
+
 #[test]
 fn lists() {
     html(
@@ -368,6 +396,51 @@ fn entities() {
     );
 }
 
+#[test]
+fn entities_preserved() {
+    html_opts!(
+        [parse.preserve_entities],
+        concat!(
+            "This is &amp;, &copy;, &trade;, \\&trade;, &xyz;, &NotEqualTilde;.\n",
+            "\n",
+            "&#8734; &#x221e; &#0; &CounterClockwiseContourIntegral;\n"
+        ),
+        concat!(
+            "<p>This is &amp;, ©, ™, &amp;trade;, &amp;xyz;, \u{2242}\u{338}.</p>\n",
+            "<p>∞ ∞ \u{fffd} \u{2233}</p>\n"
+        ),
+    );
+
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.parse.preserve_entities = true;
+    options.extension.table = true;
+    let root = parse_document(&arena, "| &lt; |\n| - |\n", &options);
+    assert!(root.validate().is_ok());
+
+    options.extension.table = false;
+    commonmark(
+        "&copy; &#x41; &#65; &CounterClockwiseContourIntegral;\n",
+        "&copy; &#x41; &#65; &CounterClockwiseContourIntegral;\n",
+        Some(&options),
+    );
+    commonmark("&copy; &#x41;\n", "© A\n", None);
+
+    xml_opts(
+        "&amp;\n",
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE document SYSTEM \"CommonMark.dtd\">\n",
+            "<document xmlns=\"http://commonmark.org/xml/1.0\">\n",
+            "  <paragraph>\n",
+            "    <entity literal=\"&amp;amp;\" xml:space=\"preserve\">&amp;</entity>\n",
+            "  </paragraph>\n",
+            "</document>\n"
+        ),
+        |opts| opts.parse.preserve_entities = true,
+    );
+}
+
 #[test]
 fn links() {
     html(
@@ -451,6 +524,56 @@ fn reference_links_casefold() {
     );
 }
 
+#[test]
+fn reference_definitions() {
+    let arena = Arena::new();
+    let mut context = ParseContext::new();
+    parse_document_with_context(
+        &arena,
+        concat!(
+            "See [one] and [Two][].\n",
+            "\n",
+            "> [one]: /1\n",
+            ">   \"First\"\n",
+            "> [three]:\n",
+            ">    /3\n",
+            "\n",
+            "  [TWO]: /2\n",
+            "[One]: /ignored\n",
+            "Heading\n",
+            "===\n",
+        ),
+        &Options::default(),
+        &mut context,
+    );
+
+    let definitions: Vec<_> = context
+        .definitions()
+        .iter()
+        .map(|d| {
+            (
+                d.label.as_str(),
+                d.url.as_str(),
+                d.title.as_str(),
+                d.sourcepos,
+            )
+        })
+        .collect();
+    assert_eq!(
+        definitions,
+        [
+            ("one", "/1", "First", (3, 3, 4, 11).into()),
+            ("three", "/3", "", (5, 3, 6, 7).into()),
+            ("TWO", "/2", "", (8, 3, 8, 11).into()),
+            ("One", "/ignored", "", (9, 1, 9, 15).into()),
+        ]
+    );
+
+    assert!(context.is_referenced("ONE"));
+    assert!(context.is_referenced("two"));
+    assert!(!context.is_referenced("three"));
+}
+
 #[test]
 fn safety() {
     html(
@@ -516,6 +639,38 @@ fn case_insensitive_safety() {
     );
 }
 
+#[test]
+fn url_schemes() {
+    html_opts_i(
+        concat!(
+            "[a](http://a) [b](Mailto:b@b) [c](irc://c) [d](/d:x) [e](e?f:g) [f](#f:g)\n",
+            "[g](data:image/png/x) ![h](ftp://h) [[i]] <https://i> [j](1j:k)\n",
+        ),
+        concat!(
+            "<p><a href=\"http://a\">a</a> <a href=\"Mailto:b@b\">b</a> <a href=\"\">c</a> <a href=\"/d:x\">d</a> <a href=\"e?f:g\">e</a> <a href=\"#f:g\">f</a>\n",
+            "<a href=\"\">g</a> <img src=\"\" alt=\"h\" /> <a href=\"i\" data-wikilink=\"true\">i</a> <a href=\"\">https://i</a> <a href=\"1j:k\">j</a></p>\n",
+        ),
+        true,
+        |opts| {
+            opts.extension.wikilinks_title_after_pipe = true;
+            opts.render.url_schemes = Some(vec!["http".into(), "mailto".into()]);
+        },
+    );
+}
+
+#[test]
+fn url_schemes_unsafe() {
+    html_opts_i(
+        "[a](javascript:a) [b](vbscript:b)\n",
+        "<p><a href=\"javascript:a\">a</a> <a href=\"\">b</a></p>\n",
+        true,
+        |opts| {
+            opts.render.unsafe_ = true;
+            opts.render.url_schemes = Some(vec!["javascript".into()]);
+        },
+    );
+}
+
 #[test]
 fn link_sourcepos_baseline() {
     assert_ast_match!(
@@ -750,3 +905,227 @@ fn emphasis_sourcepos_double_4() {
         ])
     );
 }
+
+#[test]
+fn tab_width() {
+    let input = "- a\n\n\tb\n\n-\tc\n";
+    html(
+        input,
+        concat!(
+            "<ul>\n",
+            "<li>\n<p>a</p>\n<p>b</p>\n</li>\n",
+            "<li>\n<p>c</p>\n</li>\n",
+            "</ul>\n",
+        ),
+    );
+    html_opts_i(
+        input,
+        concat!(
+            "<ul>\n",
+            "<li>\n<p>a</p>\n<pre><code>  b\n</code></pre>\n</li>\n",
+            "<li>\n<pre><code>  c\n</code></pre>\n</li>\n",
+            "</ul>\n",
+        ),
+        false,
+        |opts| opts.parse.tab_width = 8,
+    );
+}
+
+#[test]
+fn bytes_strict_utf8() {
+    let arena = Arena::new();
+    let options = Options::default();
+
+    let root = parse_document_bytes(&arena, "*caf\u{e9}*\n".as_bytes(), &options).unwrap();
+    let mut output = vec![];
+    format_html(root, &options, &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "<p><em>caf\u{e9}</em></p>\n"
+    );
+
+    let err = parse_document_bytes(&arena, b"ok\n\xc3\x28 not ok\n", &options).unwrap_err();
+    assert_eq!(err.valid_up_to(), 3);
+}
+
+#[test]
+fn bytes_lossy_utf8() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.parse.lossy_utf8 = true;
+
+    let root = parse_document_bytes(&arena, b"ok\n\xc3\x28 not *ok*\n", &options).unwrap();
+    let mut output = vec![];
+    format_html(root, &options, &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "<p>ok\n\u{fffd}( not <em>ok</em></p>\n"
+    );
+}
+
+#[test]
+fn bytes_bom_skipped() {
+    let arena = Arena::new();
+    let options = Options::default();
+
+    let root = parse_document_bytes(&arena, b"\xef\xbb\xbf# Hi\n", &options).unwrap();
+    let mut output = vec![];
+    format_html(root, &options, &mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "<h1>Hi</h1>\n");
+}
+
+#[test]
+fn reused_render_buffers() {
+    let mut options = Options::default();
+    options.extension.header_ids = Some("".to_string());
+    let plugins = Plugins::default();
+    let mut buffers = html::RenderBuffers::new();
+
+    assert_eq!(
+        markdown_to_html_with_buffers("# Hello, world\n", &options, &plugins, &mut buffers),
+        concat!(
+            "<h1><a href=\"#hello-world\" aria-hidden=\"true\" class=\"anchor\" id=\"hello-world\"></a>",
+            "Hello, world</h1>\n"
+        )
+    );
+    // Each render starts afresh, including header ID deduplication.
+    assert_eq!(
+        markdown_to_html_with_buffers("# Hello\n", &options, &plugins, &mut buffers),
+        "<h1><a href=\"#hello\" aria-hidden=\"true\" class=\"anchor\" id=\"hello\"></a>Hello</h1>\n"
+    );
+
+    buffers.clear();
+    assert!(buffers.output().is_empty());
+}
+
+#[test]
+fn format_html_to_fmt_write() {
+    let mut options = Options::default();
+    options.extension.header_ids = Some("".to_string());
+    let arena = Arena::new();
+    let root = parse_document(&arena, "# Café ☕\n\n*Ünïcödé* & `<ß>`\n", &options);
+
+    let mut expected = vec![];
+    format_html(root, &options, &mut expected).unwrap();
+    let mut html = String::new();
+    format_html_fmt(root, &options, &mut html, &Plugins::default()).unwrap();
+    assert_eq!(html, String::from_utf8(expected).unwrap());
+}
+
+#[test]
+fn format_html_of_node() {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.abbreviations = true;
+    options.extension.header_ids = Some("h-".to_string());
+    let arena = Arena::new();
+    let root = parse_document(
+        &arena,
+        "# A\n\n# A\n\n| x | y |\n| - | -: |\n| 1 | 2 |\n\n![*HTML* alt](i.png)\n\n*[HTML]: Hypertext\n",
+        &options,
+    );
+    let render = |node| {
+        let mut html = vec![];
+        format_html_node(node, &options, &mut html, &Plugins::default()).unwrap();
+        String::from_utf8(html).unwrap()
+    };
+
+    let heading = root.children().nth(1).unwrap();
+    assert_eq!(
+        render(heading),
+        "<h1><a href=\"#a-1\" aria-hidden=\"true\" class=\"anchor\" id=\"h-a-1\"></a>A</h1>\n"
+    );
+
+    let cell = root
+        .children()
+        .nth(2)
+        .unwrap()
+        .last_child()
+        .unwrap()
+        .last_child()
+        .unwrap();
+    assert_eq!(render(cell), "<td align=\"right\">2</td>");
+
+    let image = root.children().nth(3).unwrap().first_child().unwrap();
+    assert_eq!(render(image), "<img src=\"i.png\" alt=\"HTML alt\" />");
+    assert_eq!(render(image.first_child().unwrap()), "HTML");
+}
+
+#[test]
+fn parse_inline_only() {
+    let mut options = Options::default();
+    options.extension.autolink = true;
+    let arena = Arena::new();
+    let text = "- a  \nsee www.example.com\r\n`x`\0";
+    let nodes = parse_inline(&arena, text, &options);
+
+    let mut html = vec![];
+    for node in &nodes {
+        assert!(node.parent().is_none());
+        format_html(node, &options, &mut html).unwrap();
+    }
+    assert_eq!(
+        String::from_utf8(html).unwrap(),
+        "- a<br />\nsee <a href=\"http://www.example.com\">www.example.com</a>\n<code>x</code>\u{fffd}"
+    );
+
+    let code = nodes[5].data.borrow();
+    assert_eq!(code.sourcepos, (3, 2, 3, 2).into());
+    assert_eq!(&text[code.start_offset..code.end_offset], "x");
+}
+
+#[test]
+fn ast_builder() {
+    let arena = Arena::new();
+    let mut builder = nodes::AstBuilder::new(&arena);
+    builder
+        .child(NodeValue::Heading(nodes::NodeHeading::new(1)), |b| {
+            b.text("Changes");
+        })
+        .child(NodeValue::List(nodes::NodeList::ordered(9)), |b| {
+            for (i, change) in ["One", "Two"].iter().enumerate() {
+                b.child(NodeValue::Item(nodes::NodeList::ordered(9 + i)), |b| {
+                    b.child(NodeValue::Paragraph, |b| {
+                        b.text(*change).leaf(NodeValue::SoftBreak).text("more");
+                    });
+                });
+            }
+        })
+        .leaf(NodeValue::CodeBlock(nodes::NodeCodeBlock::fenced(
+            "rust",
+            "fn main() {}\n",
+        )));
+
+    let options = Options::default();
+    let mut html = vec![];
+    format_html(builder.root(), &options, &mut html).unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert_eq!(
+        html,
+        "<h1>Changes</h1>\n<ol start=\"9\">\n<li>One\nmore</li>\n<li>Two\nmore</li>\n</ol>\n\
+         <pre><code class=\"language-rust\">fn main() {}\n</code></pre>\n"
+    );
+
+    let mut md = vec![];
+    format_commonmark(builder.root(), &options, &mut md).unwrap();
+    assert_eq!(
+        String::from_utf8(md).unwrap(),
+        "# Changes\n\n9.  One\n    more\n10. Two\n    more\n\n<!-- end list -->\n\n``` rust\nfn main() {}\n```\n"
+    );
+}
+
+#[test]
+fn text_content() {
+    let mut options = Options::default();
+    options.extension.math_dollars = true;
+    let arena = Arena::new();
+    let root = parse_document(
+        &arena,
+        "> Quoted  \n> *text* $x^2$\n\n- [one](/1)\n- ![two](/2)\n\n```\ncode\n```\n",
+        &options,
+    );
+    assert_eq!(root.text_content(), "Quoted text x^2\none\ntwo\ncode");
+
+    let item = root.children().nth(1).unwrap().last_child().unwrap();
+    assert_eq!(item.text_content(), "two");
+}
@@ -0,0 +1,124 @@
+use super::*;
+
+#[test]
+fn details_blocks() {
+    html_opts!(
+        [extension.details_blocks],
+        concat!(
+            "::: details Show *the* answer & more\n",
+            "# Answer\n",
+            "\n",
+            "- one\n",
+            "- two\n",
+            ":::\n",
+            "after\n",
+        ),
+        concat!(
+            "<details>\n",
+            "<summary>Show *the* answer &amp; more</summary>\n",
+            "<h1>Answer</h1>\n",
+            "<ul>\n",
+            "<li>one</li>\n",
+            "<li>two</li>\n",
+            "</ul>\n",
+            "</details>\n",
+            "<p>after</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn details_blocks_without_summary() {
+    html_opts!(
+        [extension.details_blocks],
+        "::: details\nHidden.\n:::\n",
+        "<details>\n<p>Hidden.</p>\n</details>\n",
+    );
+}
+
+#[test]
+fn details_blocks_not_opened() {
+    html_opts!(
+        [extension.details_blocks],
+        concat!(
+            ":: details a\n",
+            "\n",
+            "::: detailed a\n",
+            "\n",
+            "    ::: details a\n",
+            "\n",
+            ":::\n",
+        ),
+        concat!(
+            "<p>:: details a</p>\n",
+            "<p>::: detailed a</p>\n",
+            "<pre><code>::: details a\n",
+            "</code></pre>\n",
+            "<p>:::</p>\n",
+        ),
+        no_roundtrip,
+    );
+}
+
+#[test]
+fn details_blocks_nested() {
+    html_opts!(
+        [extension.details_blocks, extension.conditional_blocks],
+        concat!(
+            "::::: details Outer\n",
+            "::: if beta\n",
+            "::: details Inner\n",
+            "inner\n",
+            ":::\n",
+            ":::\n",
+            "outer\n",
+            ":::::\n",
+            "outside\n",
+        ),
+        concat!(
+            "<details>\n",
+            "<summary>Outer</summary>\n",
+            "<details>\n",
+            "<summary>Inner</summary>\n",
+            "<p>inner</p>\n",
+            "</details>\n",
+            "<p>outer</p>\n",
+            "</details>\n",
+            "<p>outside</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn details_blocks_in_ast() {
+    assert_ast_match!(
+        [extension.details_blocks],
+        "- ::: details More\n"
+        "  text\n"
+        "  :::\n",
+        (document (1:1-3:5) [
+            (list (1:1-3:5) [
+                (item (1:1-3:5) [
+                    (details (1:3-3:5) [
+                        (paragraph (2:3-2:6) [
+                            (text (2:3-2:6) "text")
+                        ])
+                    ])
+                ])
+            ])
+        ])
+    );
+
+    let mut options = Options::default();
+    options.extension.details_blocks = true;
+    assert_eq!(
+        markdown_to_commonmark_xml("::: details \"More\"\n:::\n", &options),
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE document SYSTEM \"CommonMark.dtd\">\n",
+            "<document xmlns=\"http://commonmark.org/xml/1.0\">\n",
+            "  <details summary=\"&quot;More&quot;\" />\n",
+            "</document>\n",
+        )
+    );
+}
@@ -0,0 +1,70 @@
+use super::*;
+use std::sync::{Arc, Mutex};
+
+#[track_caller]
+fn diagnostics(input: &str, opts: impl Fn(&mut Options), expected: &[(Sourcepos, &str)]) {
+    let mut found = vec![];
+    let mut cb = |d: Diagnostic| found.push((d.sourcepos, d.message));
+    let arena = Arena::new();
+    let mut options = Options::default();
+    opts(&mut options);
+    options.parse.diagnostic_callback = Some(Arc::new(Mutex::new(&mut cb)));
+    parse_document(&arena, input, &options);
+    drop(options);
+
+    let expected: Vec<(Sourcepos, String)> = expected
+        .iter()
+        .map(|&(sourcepos, message)| (sourcepos, message.to_string()))
+        .collect();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn diagnostics_none() {
+    diagnostics(
+        "# Title\n\n```\ncode\n```\n\n[a]: /a\n[b]: /b\n",
+        |_| {},
+        &[],
+    );
+}
+
+#[test]
+fn diagnostics_unclosed_fence() {
+    diagnostics(
+        "text\n\n```rust\ncode\n",
+        |_| {},
+        &[((3, 1, 4, 4).into(), "fenced code block is not closed")],
+    );
+    diagnostics(
+        "> ~~~\n> code\n",
+        |_| {},
+        &[((1, 3, 2, 6).into(), "fenced code block is not closed")],
+    );
+}
+
+#[test]
+fn diagnostics_duplicate_definitions() {
+    diagnostics(
+        "[a]: /one\n\n[b]: /two\n[A]: /three\n\n[a]\n",
+        |_| {},
+        &[(
+            (4, 1, 4, 11).into(),
+            "duplicate link reference definition [A]",
+        )],
+    );
+}
+
+#[test]
+fn diagnostics_undefined_footnotes() {
+    diagnostics(
+        "One[^1], two[^2] and three[^nope].\n\n[^1]: Note.\n",
+        |opts| opts.extension.footnotes = true,
+        &[
+            ((1, 13, 1, 16).into(), "undefined footnote reference [^2]"),
+            (
+                (1, 27, 1, 33).into(),
+                "undefined footnote reference [^nope]",
+            ),
+        ],
+    );
+}
@@ -0,0 +1,196 @@
+use super::*;
+
+#[track_caller]
+fn djot(input: &str, expected: &str, opts: impl Fn(&mut Options)) {
+    formatted(format_djot, "djot", input, expected, opts);
+}
+
+#[test]
+fn djot_blocks() {
+    djot(
+        concat!(
+            "Title\n",
+            "=====\n",
+            "\n",
+            "### Sub *heading*\n",
+            "\n",
+            "> Quoted\n",
+            "> text.\n",
+            ">\n",
+            "> > Nested.\n",
+            "\n",
+            "- one\n",
+            "- two\n",
+            "\n",
+            "3) three\n",
+            "\n",
+            "   more\n",
+            "4) four\n",
+            "\n",
+            "```rust main\n",
+            "let s = \"```\";\n",
+            "```\n",
+            "\n",
+            "<div>\n",
+            "raw\n",
+            "</div>\n",
+            "\n",
+            "***\n",
+        ),
+        concat!(
+            "# Title\n",
+            "\n",
+            "### Sub _heading_\n",
+            "\n",
+            "> Quoted\n",
+            "> text.\n",
+            ">\n",
+            "> > Nested.\n",
+            "\n",
+            "- one\n",
+            "- two\n",
+            "\n",
+            "3) three\n",
+            "\n",
+            "   more\n",
+            "\n",
+            "4) four\n",
+            "\n",
+            "```` rust\n",
+            "let s = \"```\";\n",
+            "````\n",
+            "\n",
+            "``` =html\n",
+            "<div>\n",
+            "raw\n",
+            "</div>\n",
+            "```\n",
+            "\n",
+            "* * *\n",
+        ),
+        |_| {},
+    );
+}
+
+#[test]
+fn djot_inlines() {
+    djot(
+        concat!(
+            "*emph* **strong** `co`de` ``` `` ``` and <b>html</b>\\\n",
+            "[link](/url \"the \\\"title\\\"\") ![img](/i.png) <https://example.com>\n",
+            "Escape * _ [x] {y} ~ ^ | \\\\ < and a. b.\n",
+        ),
+        concat!(
+            "_emph_ *strong* `co`de\\` ``` `` ``` and `<b>`{=html}html`</b>`{=html}\\\n",
+            "[link](/url){title=\"the \\\"title\\\"\"} ![img](/i.png) <https://example.com>\n",
+            "Escape \\* \\_ \\[x\\] \\{y\\} \\~ \\^ \\| \\\\ \\< and a. b.\n",
+        ),
+        |_| {},
+    );
+}
+
+#[test]
+fn djot_escapes_block_starts() {
+    djot(
+        concat!(
+            "\\# not a heading\n",
+            "\n",
+            "\\- not a list\n",
+            "\n",
+            "1\\. not a list\n",
+            "\n",
+            "i\\) not a list\n",
+            "\n",
+            "e.g. this\n",
+            "\n",
+            "Done.\n",
+        ),
+        concat!(
+            "\\# not a heading\n",
+            "\n",
+            "\\- not a list\n",
+            "\n",
+            "1\\. not a list\n",
+            "\n",
+            "i\\) not a list\n",
+            "\n",
+            "e.g. this\n",
+            "\n",
+            "Done.\n",
+        ),
+        |_| {},
+    );
+}
+
+#[test]
+fn djot_extensions() {
+    djot(
+        concat!(
+            "| a | b |\n",
+            "|:--|--:|\n",
+            "| ~~c~~ | ^d^ |\n",
+            "\n",
+            "- [ ] todo\n",
+            "- [x] done\n",
+            "\n",
+            "Term\n",
+            "\n",
+            ": Details.\n",
+            "\n",
+            "Note[^1], $x$ and $$y$$, __u__ ||s||.\n",
+            "\n",
+            "::: if os=\"linux\" beta\n",
+            "Linux.\n",
+            ":::\n",
+            "\n",
+            "*[HTML]: HyperText Markup Language\n",
+            "\n",
+            "[^1]: A note\n",
+            "    over lines.\n",
+        ),
+        concat!(
+            "| a | b |\n",
+            "|:--|--:|\n",
+            "| {-c-} | ^d^ |\n",
+            "\n",
+            "- [ ] todo\n",
+            "- [x] done\n",
+            "\n",
+            ": Term\n",
+            "\n",
+            "  Details.\n",
+            "\n",
+            "Note[^1], $`x` and $$`y`, {+u+} [s]{.spoiler}.\n",
+            "\n",
+            "{os=\"linux\" beta=\"\"}\n",
+            ":::\n",
+            "Linux.\n",
+            ":::\n",
+            "\n",
+            "[^1]: A note\n",
+            "  over lines.\n",
+        ),
+        |options| {
+            options.extension.table = true;
+            options.extension.strikethrough = true;
+            options.extension.superscript = true;
+            options.extension.tasklist = true;
+            options.extension.description_lists = true;
+            options.extension.footnotes = true;
+            options.extension.math_dollars = true;
+            options.extension.underline = true;
+            options.extension.spoiler = true;
+            options.extension.conditional_blocks = true;
+            options.extension.abbreviations = true;
+        },
+    );
+}
+
+#[test]
+fn markdown_to_djot_api() {
+    assert_eq!(
+        markdown_to_djot("Some *text*.", &Options::default()),
+        "Some _text_.\n"
+    );
+    assert_eq!(markdown_to_djot("", &Options::default()), "");
+}
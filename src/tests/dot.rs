@@ -0,0 +1,43 @@
+use super::*;
+
+#[test]
+fn dot_nesting_and_literals() {
+    let arena = Arena::new();
+    let root = parse_document(
+        &arena,
+        "> - a\n>\n> ```\n> \"quoted\" \\ and a long line of code\n> ```\n\n[b](/url)\n",
+        &Options::default(),
+    );
+    let mut output = vec![];
+    format_dot(root, &Options::default(), &mut output).unwrap();
+
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        concat!(
+            "digraph ast {\n",
+            "  node [shape=box, fontname=\"monospace\"];\n",
+            "  n0 [label=\"document\\n1:1-7:9\"];\n",
+            "  n1 [label=\"block_quote\\n1:1-5:5\"];\n",
+            "  n0 -> n1;\n",
+            "  n2 [label=\"list\\n1:3-2:1\"];\n",
+            "  n1 -> n2;\n",
+            "  n3 [label=\"item\\n1:3-2:1\"];\n",
+            "  n2 -> n3;\n",
+            "  n4 [label=\"paragraph\\n1:5-1:5\"];\n",
+            "  n3 -> n4;\n",
+            "  n5 [label=\"text \\\"a\\\"\\n1:5-1:5\"];\n",
+            "  n4 -> n5;\n",
+            "  n6 [label=\"code_block \\\"\\\"quoted\\\" \\\\ and a lon…\\\"\\n3:3-5:5\"];\n",
+            "  n1 -> n6;\n",
+            "  n7 [label=\"paragraph\\n7:1-7:9\"];\n",
+            "  n0 -> n7;\n",
+            "  n8 [label=\"link \\\"/url\\\"\\n7:1-7:9\"];\n",
+            "  n7 -> n8;\n",
+            "  n9 [label=\"text \\\"b\\\"\\n7:2-7:2\"];\n",
+            "  n8 -> n9;\n",
+            "}\n",
+        ),
+        "dot",
+        "",
+    );
+}
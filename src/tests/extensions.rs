@@ -0,0 +1,220 @@
+use crate::extensions::{BlockContinuation, ComrakExtension};
+use crate::nodes::NodeExtension;
+
+use super::*;
+
+// Fenced blocks, `%%% lang` to `%%%`, rendered as a div of the language.
+struct Diagrams;
+
+impl ComrakExtension for Diagrams {
+    fn name(&self) -> &str {
+        "diagrams"
+    }
+
+    fn open_block(&self, line: &str) -> Option<NodeExtension> {
+        let lang = line.strip_prefix("%%%")?.trim();
+        Some(NodeExtension::default().with_data(lang.to_string()))
+    }
+
+    fn continue_block(&self, _node: &NodeExtension, line: &str) -> BlockContinuation {
+        if line.trim() == "%%%" {
+            BlockContinuation::Close
+        } else {
+            BlockContinuation::Continue
+        }
+    }
+
+    fn render_html<'a>(
+        &self,
+        node: &'a AstNode<'a>,
+        entering: bool,
+        output: &mut dyn Write,
+    ) -> io::Result<bool> {
+        match node.data.borrow().value {
+            NodeValue::ExtensionBlock(ref ne) if ne.extension == "diagrams" => {
+                if entering {
+                    let lang: &String = ne.data.as_ref().unwrap().downcast_ref().unwrap();
+                    let lines: Vec<&str> = ne.literal.lines().collect();
+                    writeln!(output, "<div class=\"{}\">", lang)?;
+                    writeln!(output, "{}", lines[1..lines.len() - 1].join("\n"))?;
+                    writeln!(output, "</div>")?;
+                }
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+// Blocks of lines starting with `!!!`, ending at a blank line, and `@name`
+// mentions.  Neither is rendered by the extension.
+struct Notes;
+
+impl ComrakExtension for Notes {
+    fn name(&self) -> &str {
+        "notes"
+    }
+
+    fn open_block(&self, line: &str) -> Option<NodeExtension> {
+        line.starts_with("!!!").then(NodeExtension::default)
+    }
+
+    fn continue_block(&self, _node: &NodeExtension, line: &str) -> BlockContinuation {
+        if line.trim().is_empty() {
+            BlockContinuation::End
+        } else {
+            BlockContinuation::Continue
+        }
+    }
+
+    fn inline_chars(&self) -> &[u8] {
+        b"@"
+    }
+
+    fn parse_inline(&self, input: &str) -> Option<(usize, NodeExtension)> {
+        let len = 1 + input[1..]
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(input.len() - 1);
+        (len > 1).then(|| (len, NodeExtension::default()))
+    }
+}
+
+// Wraps blocks in a `<section>`, keeping their usual rendering.
+struct Sections;
+
+impl ComrakExtension for Sections {
+    fn name(&self) -> &str {
+        "sections"
+    }
+
+    fn render_html<'a>(
+        &self,
+        node: &'a AstNode<'a>,
+        entering: bool,
+        output: &mut dyn Write,
+    ) -> io::Result<bool> {
+        if entering && node.data.borrow().value.block() && node.parent().is_some() {
+            output.write_all(b"<section>\n")?;
+        }
+        Ok(false)
+    }
+
+    fn render_html_after<'a>(
+        &self,
+        node: &'a AstNode<'a>,
+        entering: bool,
+        output: &mut dyn Write,
+    ) -> io::Result<()> {
+        if !entering && node.data.borrow().value.block() && node.parent().is_some() {
+            output.write_all(b"</section>\n")?;
+        }
+        Ok(())
+    }
+}
+
+fn options() -> Options<'static> {
+    let mut options = Options::default();
+    options.extensions.register(Box::new(Diagrams));
+    options.extensions.register(Box::new(Notes));
+    options
+}
+
+#[test]
+fn extension_blocks() {
+    html_opts_w(
+        concat!(
+            "Intro\n",
+            "%%% mermaid\n",
+            "graph TD\n",
+            "\n",
+            "  A --> B\n",
+            "%%%\n",
+            "\n",
+            "> !!! Careful <now>\n",
+            "> still the note\n",
+            "\n",
+            "!!! Top\n",
+            "\n",
+            "after\n",
+        ),
+        concat!(
+            "<p>Intro</p>\n",
+            "<div class=\"mermaid\">\n",
+            "graph TD\n",
+            "\n",
+            "  A --> B\n",
+            "</div>\n",
+            "<blockquote>\n",
+            "!!! Careful &lt;now&gt;\n",
+            "still the note\n",
+            "</blockquote>\n",
+            "!!! Top\n",
+            "<p>after</p>\n",
+        ),
+        true,
+        &options(),
+    );
+}
+
+#[test]
+fn extension_inlines() {
+    html_opts_w(
+        "*Thanks* @kivikakk, @ and me@.\n",
+        "<p><em>Thanks</em> @kivikakk, @ and me@.</p>\n",
+        true,
+        &options(),
+    );
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, "Hi @you!\n", &options());
+    let mention = root.first_child().unwrap().children().nth(1).unwrap();
+    let ast = mention.data.borrow();
+    assert_eq!(ast.sourcepos, (1, 4, 1, 7).into());
+    match ast.value {
+        NodeValue::ExtensionInline(ref ne) => {
+            assert_eq!(ne.extension, "notes");
+            assert_eq!(ne.literal, "@you");
+        }
+        ref other => panic!("expected extension inline, got {:?}", other),
+    }
+}
+
+#[test]
+fn extension_blocks_in_ast() {
+    let arena = Arena::new();
+    let root = parse_document(&arena, "%%% dot\na -> b\n%%%\nafter\n", &options());
+    let block = root.first_child().unwrap();
+    let ast = block.data.borrow();
+    assert_eq!(ast.sourcepos, (1, 1, 3, 3).into());
+    match ast.value {
+        NodeValue::ExtensionBlock(ref ne) => {
+            assert_eq!(ne.extension, "diagrams");
+            assert_eq!(ne.literal, "%%% dot\na -> b\n%%%\n");
+        }
+        ref other => panic!("expected extension block, got {:?}", other),
+    }
+    assert!(block.next_sibling().is_some());
+}
+
+#[test]
+fn extension_render_after() {
+    // The sections wrap the diagram rendered by the later extension, too.
+    let mut options = Options::default();
+    options.extensions.register(Box::new(Sections));
+    options.extensions.register(Box::new(Diagrams));
+    html_opts_w(
+        "# Hi\n\n%%% dot\na -> b\n%%%\n",
+        concat!(
+            "<section>\n",
+            "<h1>Hi</h1>\n",
+            "</section>\n",
+            "<section>\n",
+            "<div class=\"dot\">\n",
+            "a -> b\n",
+            "</div>\n",
+            "</section>\n",
+        ),
+        false,
+        &options,
+    );
+}
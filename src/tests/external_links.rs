@@ -0,0 +1,50 @@
+use super::*;
+
+#[test]
+fn external_links() {
+    html_opts_i(
+        concat!(
+            "[a](https://example.com/a) [b](http://user@EXAMPLE.com:8080/b) [c](/c)\n",
+            "[d](https://other.example/d \"D\") <https://other.example> [e](mailto:e@other.example)\n",
+            "[f](/f?u=https://other.example) [g](javascript://other.example)\n",
+        ),
+        concat!(
+            "<p><a href=\"https://example.com/a\">a</a> <a href=\"http://user@EXAMPLE.com:8080/b\">b</a> <a href=\"/c\">c</a>\n",
+            "<a href=\"https://other.example/d\" title=\"D\" rel=\"nofollow noopener\" target=\"_blank\">d</a> ",
+            "<a href=\"https://other.example\" rel=\"nofollow noopener\" target=\"_blank\">https://other.example</a> ",
+            "<a href=\"mailto:e@other.example\">e</a>\n",
+            "<a href=\"/f?u=https://other.example\">f</a> <a href=\"\">g</a></p>\n",
+        ),
+        false,
+        |opts| {
+            opts.render.external_link_base = Some("https://example.com".into());
+            opts.render.external_link_rel = true;
+            opts.render.external_link_target = true;
+        },
+    );
+}
+
+#[test]
+fn external_links_without_base() {
+    html_opts_i(
+        "[a](https://example.com/a) [b](b)\n",
+        "<p><a href=\"https://example.com/a\" target=\"_blank\">a</a> <a href=\"b\">b</a></p>\n",
+        true,
+        |opts| opts.render.external_link_target = true,
+    );
+}
+
+#[test]
+fn external_links_rewritten() {
+    let mut options = Options::default();
+    options.render.external_link_base = Some("https://example.com/".into());
+    options.render.external_link_rel = true;
+    let rewriter = |url: &str| format!("https://example.com{}", url);
+    let mut plugins = Plugins::default();
+    plugins.render.link_url_rewriter = Some(&rewriter);
+
+    assert_eq!(
+        markdown_to_html_with_plugins("[a](/a)\n", &options, &plugins),
+        "<p><a href=\"https://example.com/a\">a</a></p>\n"
+    );
+}
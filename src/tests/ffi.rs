@@ -0,0 +1,213 @@
+use crate::ffi::*;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_void};
+
+extern "C" {
+    fn free(ptr: *mut c_void);
+}
+
+// Takes a string returned by a renderer, freeing it.
+unsafe fn take(s: *mut c_char) -> String {
+    let owned = CStr::from_ptr(s).to_str().unwrap().to_string();
+    free(s as *mut c_void);
+    owned
+}
+
+unsafe fn string(s: *const c_char) -> Option<&'static str> {
+    if s.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(s).to_str().unwrap())
+    }
+}
+
+#[test]
+fn ffi_markdown_to_html() {
+    let input = "Hello, *world*! <b>hi</b>\n";
+    unsafe {
+        assert_eq!(
+            take(cmark_markdown_to_html(
+                input.as_ptr() as *const c_char,
+                input.len(),
+                0
+            )),
+            "<p>Hello, <em>world</em>! <!-- raw HTML omitted -->hi<!-- raw HTML omitted --></p>\n"
+        );
+        assert_eq!(
+            take(cmark_markdown_to_html(
+                input.as_ptr() as *const c_char,
+                input.len(),
+                CMARK_OPT_UNSAFE
+            )),
+            "<p>Hello, <em>world</em>! <b>hi</b></p>\n"
+        );
+    }
+}
+
+#[test]
+fn ffi_parser_extensions() {
+    unsafe {
+        cmark_gfm_core_extensions_ensure_registered();
+        let parser = cmark_parser_new(CMARK_OPT_SMART);
+        for name in [&b"table\0"[..], b"strikethrough\0"] {
+            let extension = cmark_find_syntax_extension(name.as_ptr() as *const c_char);
+            assert!(!extension.is_null());
+            assert_eq!(cmark_parser_attach_syntax_extension(parser, extension), 1);
+        }
+        assert!(cmark_find_syntax_extension(b"nope\0".as_ptr() as *const c_char).is_null());
+
+        for chunk in ["| a |\n|", "---|\n| ~~b~~ \"c\" |\n"] {
+            cmark_parser_feed(parser, chunk.as_ptr() as *const c_char, chunk.len());
+        }
+        let document = cmark_parser_finish(parser);
+        cmark_parser_free(parser);
+
+        let table = cmark_node_first_child(document);
+        assert_eq!(cmark_node_get_type(table), CMARK_NODE_TABLE);
+        assert_eq!(string(cmark_node_get_type_string(table)), Some("table"));
+        assert_eq!(
+            take(cmark_render_html(table, 0, std::ptr::null_mut())),
+            concat!(
+                "<table>\n<thead>\n<tr>\n<th>a</th>\n</tr>\n</thead>\n",
+                "<tbody>\n<tr>\n<td><del>b</del> “c”</td>\n</tr>\n</tbody>\n</table>\n"
+            )
+        );
+        assert_eq!(
+            take(cmark_render_commonmark(document, 0, 0)),
+            "| a |\n| --- |\n| ~b~ “c” |\n"
+        );
+        cmark_node_free(document);
+    }
+}
+
+#[test]
+fn ffi_nodes_and_iteration() {
+    let input = "# Title\n\n1. [link](/url \"t\")\n\n```rust\ncode\n```\n";
+    unsafe {
+        let document = cmark_parse_document(input.as_ptr() as *const c_char, input.len(), 0);
+        assert_eq!(cmark_node_get_type(document), CMARK_NODE_DOCUMENT);
+
+        let mut events = vec![];
+        let iter = cmark_iter_new(document);
+        loop {
+            let event = cmark_iter_next(iter);
+            if event == CMARK_EVENT_DONE {
+                break;
+            }
+            let node = cmark_iter_get_node(iter);
+            assert_eq!(cmark_iter_get_event_type(iter), event);
+            events.push((
+                event == CMARK_EVENT_ENTER,
+                string(cmark_node_get_type_string(node)).unwrap(),
+            ));
+        }
+        assert_eq!(cmark_iter_get_root(iter), document);
+        cmark_iter_free(iter);
+        assert_eq!(
+            events,
+            vec![
+                (true, "document"),
+                (true, "heading"),
+                (true, "text"),
+                (false, "heading"),
+                (true, "list"),
+                (true, "item"),
+                (true, "paragraph"),
+                (true, "link"),
+                (true, "text"),
+                (false, "link"),
+                (false, "paragraph"),
+                (false, "item"),
+                (false, "list"),
+                (true, "code_block"),
+                (false, "document"),
+            ]
+        );
+
+        let heading = cmark_node_first_child(document);
+        assert_eq!(cmark_node_get_heading_level(heading), 1);
+        assert_eq!(string(cmark_node_get_literal(heading)), None);
+        assert_eq!(
+            string(cmark_node_get_literal(cmark_node_first_child(heading))),
+            Some("Title")
+        );
+
+        let list = cmark_node_next(heading);
+        assert_eq!(cmark_node_get_list_type(list), CMARK_ORDERED_LIST);
+        assert_eq!(cmark_node_get_list_start(list), 1);
+        let link = cmark_node_first_child(cmark_node_first_child(cmark_node_first_child(list)));
+        assert_eq!(cmark_node_get_type(link), CMARK_NODE_LINK);
+        assert_eq!(string(cmark_node_get_url(link)), Some("/url"));
+        assert_eq!(string(cmark_node_get_title(link)), Some("t"));
+        assert_eq!(cmark_node_get_start_line(link), 3);
+        assert_eq!(cmark_node_get_start_column(link), 4);
+        assert_eq!(cmark_node_get_end_column(link), 19);
+
+        let code = cmark_node_last_child(document);
+        assert_eq!(cmark_node_previous(code), list);
+        assert_eq!(cmark_node_parent(code), document);
+        assert_eq!(string(cmark_node_get_fence_info(code)), Some("rust"));
+        assert_eq!(string(cmark_node_get_literal(code)), Some("code\n"));
+        assert_eq!(cmark_node_get_end_line(code), 7);
+
+        // Freeing a node other than the document unlinks it, but it can still be used.
+        cmark_node_free(list);
+        assert_eq!(cmark_node_next(heading), code);
+        assert_eq!(cmark_node_parent(list), std::ptr::null_mut());
+        assert_eq!(string(cmark_node_get_url(link)), Some("/url"));
+        assert_eq!(
+            take(cmark_render_html(list, 0, std::ptr::null_mut())),
+            "<ol>\n<li><a href=\"/url\" title=\"t\">link</a></li>\n</ol>\n"
+        );
+        assert_eq!(
+            take(cmark_render_xml(document, 0)).lines().nth(2),
+            Some("<document xmlns=\"http://commonmark.org/xml/1.0\">")
+        );
+        assert_eq!(
+            take(cmark_render_html(
+                document,
+                CMARK_OPT_SOURCEPOS,
+                std::ptr::null_mut()
+            )),
+            concat!(
+                "<h1 data-sourcepos=\"1:1-1:7\">Title</h1>\n",
+                "<pre data-sourcepos=\"5:1-7:3\"><code class=\"language-rust\">code\n</code></pre>\n"
+            )
+        );
+        cmark_node_free(document);
+    }
+}
+
+#[test]
+fn ffi_parser_feed_split_characters() {
+    let input = "# Caf\u{e9}\n\n*\u{1f980}*\n".as_bytes();
+    unsafe {
+        let parser = cmark_parser_new(0);
+        for _ in 0..2 {
+            for chunk in input.chunks(3) {
+                cmark_parser_feed(parser, chunk.as_ptr() as *const c_char, chunk.len());
+            }
+            let document = cmark_parser_finish(parser);
+            assert_eq!(
+                take(cmark_render_html(document, 0, std::ptr::null_mut())),
+                "<h1>Caf\u{e9}</h1>\n<p><em>\u{1f980}</em></p>\n"
+            );
+            cmark_node_free(document);
+        }
+
+        // Invalid UTF-8 is replaced, and so is a character left unfinished.
+        for chunk in [&b"a\xff"[..], b"b\n\nc\xe2\x82"] {
+            cmark_parser_feed(parser, chunk.as_ptr() as *const c_char, chunk.len());
+        }
+        let document = cmark_parser_finish(parser);
+        assert_eq!(
+            take(cmark_render_html(document, 0, std::ptr::null_mut())),
+            "<p>a\u{fffd}b</p>\n<p>c\u{fffd}</p>\n"
+        );
+        cmark_node_free(document);
+
+        // A parser can be freed part way through a document.
+        cmark_parser_feed(parser, input.as_ptr() as *const c_char, 4);
+        cmark_parser_free(parser);
+    }
+}
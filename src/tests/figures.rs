@@ -0,0 +1,129 @@
+use super::*;
+
+#[test]
+fn number_figures() {
+    let arena = Arena::new();
+    let options = Options::default();
+    let root = parse_document(
+        &arena,
+        concat!(
+            "![](a.png \"First\")\n",
+            "\n",
+            "![no caption](b.png)\n",
+            "\n",
+            "> - ![](c.png \"Nested *not emphasised*\")\n",
+        ),
+        &options,
+    );
+
+    let list = crate::figures::number_figures(&arena, root, "Fig.");
+    root.append(list);
+
+    let mut output = vec![];
+    format_commonmark(root, &options, &mut output).unwrap();
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        concat!(
+            "![](a.png \"First\")\n",
+            "\n",
+            "![no caption](b.png)\n",
+            "\n",
+            "> - ![](c.png \"Nested *not emphasised*\")\n",
+            "\n",
+            "1.  [First](#fig-1)\n",
+            "2.  [Nested \\*not emphasised\\*](#fig-2)\n",
+        ),
+        "number_figures",
+        "",
+    );
+
+    let nested = root.descendants().filter(
+        |node| matches!(node.data.borrow().value, NodeValue::Image(ref nl) if nl.url == "c.png"),
+    );
+    assert_eq!(
+        nested
+            .map(|node| node.data.borrow().figure.clone())
+            .collect::<Vec<_>>(),
+        vec![Some(crate::nodes::NodeFigure {
+            label: "Fig. 2".to_string(),
+            id: "fig-2".to_string(),
+        })]
+    );
+}
+
+#[test]
+fn number_tables() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    let root = parse_document(
+        &arena,
+        concat!(
+            "| a |\n",
+            "|---|\n",
+            "\n",
+            "Table: *First* table\n",
+            "\n",
+            "| b |\n",
+            "|---|\n",
+            "\n",
+            "No caption.\n",
+            "\n",
+            "| c |\n",
+            "|---|\n",
+            "\n",
+            "Table:Third\n",
+        ),
+        &options,
+    );
+
+    let list = crate::figures::number_tables(&arena, root, "Table");
+    root.append(list);
+
+    let mut output = vec![];
+    format_html(root, &options, &mut output).unwrap();
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        concat!(
+            "<table id=\"table-1\">\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "</table>\n",
+            "<p>Table 1: <em>First</em> table</p>\n",
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "</table>\n",
+            "<p>No caption.</p>\n",
+            "<table id=\"table-2\">\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>c</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "</table>\n",
+            "<p>Table 2: Third</p>\n",
+            "<ol>\n",
+            "<li><a href=\"#table-1\">First table</a></li>\n",
+            "<li><a href=\"#table-2\">Third</a></li>\n",
+            "</ol>\n",
+        ),
+        "number_tables",
+        "",
+    );
+}
+
+#[test]
+fn number_figures_empty() {
+    let arena = Arena::new();
+    let root = parse_document(&arena, "No figures.\n", &Options::default());
+
+    let list = crate::figures::number_figures(&arena, root, "Figure");
+    assert!(list.first_child().is_none());
+}
@@ -281,3 +281,311 @@ fn sourcepos() {
         ])
     );
 }
+
+#[test]
+fn footnote_placement_section() {
+    html_opts_i(
+        concat!(
+            "Intro[^a].\n",
+            "\n",
+            "# One\n",
+            "\n",
+            "Text[^b].\n",
+            "\n",
+            "## One point one\n",
+            "\n",
+            "More[^c] and[^b].\n",
+            "\n",
+            "# Two\n",
+            "\n",
+            "Last[^d].\n",
+            "\n",
+            "[^a]: A.\n",
+            "[^b]: B[^e].\n",
+            "[^c]: C.\n",
+            "[^d]: D.\n",
+            "[^e]: E.\n",
+        ),
+        concat!(
+            "<p>Intro<sup class=\"footnote-ref\"><a href=\"#fn-a\" id=\"fnref-a\" data-footnote-ref>1</a></sup>.</p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-a\">\n",
+            "<p>A. <a href=\"#fnref-a\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+            "<h1>One</h1>\n",
+            "<p>Text<sup class=\"footnote-ref\"><a href=\"#fn-b\" id=\"fnref-b\" data-footnote-ref>2</a></sup>.</p>\n",
+            "<h2>One point one</h2>\n",
+            "<p>More<sup class=\"footnote-ref\"><a href=\"#fn-c\" id=\"fnref-c\" data-footnote-ref>3</a></sup> \
+             and<sup class=\"footnote-ref\"><a href=\"#fn-b\" id=\"fnref-b-2\" data-footnote-ref>2</a></sup>.</p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol start=\"2\">\n",
+            "<li id=\"fn-b\">\n",
+            "<p>B<sup class=\"footnote-ref\"><a href=\"#fn-e\" id=\"fnref-e\" data-footnote-ref>4</a></sup>. \
+             <a href=\"#fnref-b\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"2\" aria-label=\"Back to reference 2\">↩</a> \
+             <a href=\"#fnref-b-2\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"2-2\" aria-label=\"Back to reference 2-2\">↩<sup class=\"footnote-ref\">2</sup></a></p>\n",
+            "</li>\n",
+            "<li id=\"fn-c\">\n",
+            "<p>C. <a href=\"#fnref-c\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"3\" aria-label=\"Back to reference 3\">↩</a></p>\n",
+            "</li>\n",
+            "<li id=\"fn-e\">\n",
+            "<p>E. <a href=\"#fnref-e\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"4\" aria-label=\"Back to reference 4\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+            "<h1>Two</h1>\n",
+            "<p>Last<sup class=\"footnote-ref\"><a href=\"#fn-d\" id=\"fnref-d\" data-footnote-ref>5</a></sup>.</p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol start=\"5\">\n",
+            "<li id=\"fn-d\">\n",
+            "<p>D. <a href=\"#fnref-d\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"5\" aria-label=\"Back to reference 5\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+        ),
+        false,
+        |opts| {
+            opts.extension.footnotes = true;
+            opts.extension.footnote_placement = FootnotePlacement::Section;
+        },
+    );
+}
+
+#[test]
+fn footnote_backref_label() {
+    html_opts_i(
+        "A[^1] and B[^1].\n\n[^1]: Both.\n",
+        concat!(
+            "<p>A<sup class=\"footnote-ref\"><a href=\"#fn-1\" id=\"fnref-1\" data-footnote-ref>1</a></sup> \
+             and B<sup class=\"footnote-ref\"><a href=\"#fn-1\" id=\"fnref-1-2\" data-footnote-ref>1</a></sup>.</p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-1\">\n",
+            "<p>Both. <a href=\"#fnref-1\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Zur &quot;Stelle&quot; 1\">↩</a> \
+             <a href=\"#fnref-1-2\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1-2\" aria-label=\"Zur &quot;Stelle&quot; 1-2\">↩<sup class=\"footnote-ref\">2</sup></a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+        ),
+        true,
+        |opts| {
+            opts.extension.footnotes = true;
+            opts.render.strings.footnote_backref = "Zur \"Stelle\"".into();
+        },
+    );
+}
+
+#[test]
+fn footnote_style() {
+    html_opts_i(
+        "A[^1] and B[^1].\n\n[^1]: Both.\n",
+        concat!(
+            "<p>A<sup class=\"footnote-ref\"><a href=\"#doc-fn-1\" id=\"doc-fnref-1\" data-footnote-ref>1</a></sup> \
+             and B<sup class=\"footnote-ref\"><a href=\"#doc-fn-1\" id=\"doc-fnref-1-2\" data-footnote-ref>1</a></sup>.</p>\n",
+            "<aside data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"doc-fn-1\">\n",
+            "<p>Both. <a href=\"#doc-fnref-1\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">&lt;up&gt;</a> \
+             <a href=\"#doc-fnref-1-2\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1-2\" aria-label=\"Back to reference 1-2\">&lt;up&gt;<sup class=\"footnote-ref\">2</sup></a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</aside>\n",
+        ),
+        true,
+        |opts| {
+            opts.extension.footnotes = true;
+            opts.render.footnote_style.section_element = "aside".into();
+            opts.render.footnote_style.section_class = "".into();
+            opts.render.footnote_style.backref = "<up>".into();
+            opts.render.footnote_style.id_prefix = "doc-".into();
+        },
+    );
+}
+
+#[test]
+fn footnote_style_without_backrefs() {
+    html_opts_i(
+        "A[^1] and B[^2].\n\n[^1]: One.\n\n[^2]:\n    ```\n    code\n    ```\n",
+        concat!(
+            "<p>A<sup class=\"footnote-ref\"><a href=\"#fn-1\" id=\"fnref-1\" data-footnote-ref>1</a></sup> \
+             and B<sup class=\"footnote-ref\"><a href=\"#fn-2\" id=\"fnref-2\" data-footnote-ref>2</a></sup>.</p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-1\">\n",
+            "<p>One.</p>\n",
+            "</li>\n",
+            "<li id=\"fn-2\">\n",
+            "<pre><code>code\n",
+            "</code></pre>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+        ),
+        true,
+        |opts| {
+            opts.extension.footnotes = true;
+            opts.render.footnote_style.backrefs = false;
+        },
+    );
+}
+
+#[test]
+fn footnotes_with_shared_context() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.footnotes = true;
+    options.extension.footnote_placement = FootnotePlacement::Section;
+    let mut context = ParseContext::new();
+
+    parse_document_with_context(
+        &arena,
+        "[glossary]: /glossary \"Glossary\"\n[term]: /first\n",
+        &options,
+        &mut context,
+    );
+    assert_eq!(context.reference("GLOSSARY").unwrap().url, "/glossary");
+    assert_eq!(context.footnote_count(), 0);
+
+    let one = parse_document_with_context(
+        &arena,
+        "A[^x] and B[^y].\n\n[^x]: X.\n[^y]: Y.\n[^unused]: U.\n",
+        &options,
+        &mut context,
+    );
+    assert_eq!(context.footnote_count(), 2);
+
+    let two = parse_document_with_context(
+        &arena,
+        "# Head\n\nSee [term] in the [glossary], too[^z].\n\n[term]: /second\n[^z]: Z.\n",
+        &options,
+        &mut context,
+    );
+    assert_eq!(context.footnote_count(), 3);
+    assert_eq!(context.reference("term").unwrap().url, "/first");
+
+    let mut html = vec![];
+    format_html(one, &options, &mut html).unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert!(html.contains("<ol>\n<li id=\"fn-x\">"));
+    assert!(html.contains("data-footnote-backref-idx=\"2\""));
+    assert!(!html.contains("fn-unused"));
+
+    compare_strs(
+        &{
+            let mut html = vec![];
+            format_html(two, &options, &mut html).unwrap();
+            String::from_utf8(html).unwrap()
+        },
+        concat!(
+            "<h1>Head</h1>\n",
+            "<p>See <a href=\"/first\">term</a> in the <a href=\"/glossary\" title=\"Glossary\">glossary</a>, too\
+             <sup class=\"footnote-ref\"><a href=\"#fn-z\" id=\"fnref-z\" data-footnote-ref>3</a></sup>.</p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol start=\"3\">\n",
+            "<li id=\"fn-z\">\n",
+            "<p>Z. <a href=\"#fnref-z\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"3\" aria-label=\"Back to reference 3\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+        ),
+        "html",
+        "",
+    );
+}
+
+fn inline_footnotes(options: &mut Options) {
+    options.extension.footnotes = true;
+    options.extension.inline_footnotes = true;
+}
+
+#[test]
+fn inline_footnote() {
+    html_opts_i(
+        "A^[With *emphasis* and [a link](/x).] and B[^b].\n\n[^b]: Cites^[Inside.]\n",
+        concat!(
+            "<p>A<sup class=\"footnote-ref\"><a href=\"#fn-inline-1\" id=\"fnref-inline-1\" data-footnote-ref>1</a></sup> \
+             and B<sup class=\"footnote-ref\"><a href=\"#fn-b\" id=\"fnref-b\" data-footnote-ref>2</a></sup>.</p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-inline-1\">\n",
+            "<p>With <em>emphasis</em> and <a href=\"/x\">a link</a>. \
+             <a href=\"#fnref-inline-1\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a></p>\n",
+            "</li>\n",
+            "<li id=\"fn-b\">\n",
+            "<p>Cites<sup class=\"footnote-ref\"><a href=\"#fn-inline-2\" id=\"fnref-inline-2\" data-footnote-ref>3</a></sup> \
+             <a href=\"#fnref-b\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"2\" aria-label=\"Back to reference 2\">↩</a></p>\n",
+            "</li>\n",
+            "<li id=\"fn-inline-2\">\n",
+            "<p>Inside. \
+             <a href=\"#fnref-inline-2\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"3\" aria-label=\"Back to reference 3\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+        ),
+        true,
+        inline_footnotes,
+    );
+}
+
+#[test]
+fn inline_footnote_names() {
+    html_opts_i(
+        "A^[Generated.] and B[^inline-1].\n\n[^inline-1]: Defined.\n",
+        concat!(
+            "<p>A<sup class=\"footnote-ref\"><a href=\"#fn-inline-2\" id=\"fnref-inline-2\" data-footnote-ref>1</a></sup> \
+             and B<sup class=\"footnote-ref\"><a href=\"#fn-inline-1\" id=\"fnref-inline-1\" data-footnote-ref>2</a></sup>.</p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-inline-2\">\n",
+            "<p>Generated. <a href=\"#fnref-inline-2\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a></p>\n",
+            "</li>\n",
+            "<li id=\"fn-inline-1\">\n",
+            "<p>Defined. <a href=\"#fnref-inline-1\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"2\" aria-label=\"Back to reference 2\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+        ),
+        true,
+        inline_footnotes,
+    );
+}
+
+#[test]
+fn inline_footnote_not_matched() {
+    html_opts_i(
+        "Empty ^[] and ^ [spaced] and ^[unclosed\n",
+        "<p>Empty ^[] and ^ [spaced] and ^[unclosed</p>\n",
+        true,
+        inline_footnotes,
+    );
+    html_opts_i(
+        "Disabled^[note]\n",
+        "<p>Disabled^[note]</p>\n",
+        true,
+        |opts| opts.extension.inline_footnotes = true,
+    );
+}
+
+#[test]
+fn inline_footnote_sourcepos() {
+    crate::tests::assert_ast_match_i(
+        "Hi^[*there*].\n",
+        ast!((document (1:1-1:13) [
+            (paragraph (1:1-1:13) [
+                (text (1:1-1:2) "Hi")
+                (footnote_reference (1:3-1:12))
+                (text (1:13-1:13) ".")
+            ])
+            (footnote_definition (1:3-1:12) [
+                (paragraph (1:3-1:12) [
+                    (emph (1:5-1:11) [
+                        (text (1:6-1:10) "there")
+                    ])
+                ])
+            ])
+        ])),
+        inline_footnotes,
+    );
+}
@@ -0,0 +1,123 @@
+use serde::Deserialize;
+
+use crate::front_matter::front_matter_as;
+
+use super::*;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Meta {
+    title: String,
+    draft: bool,
+    weight: f64,
+    tags: Vec<String>,
+}
+
+fn parse<'a>(arena: &'a Arena<AstNode<'a>>, input: &str, delimiter: &str) -> &'a AstNode<'a> {
+    let mut options = Options::default();
+    options.extension.front_matter_delimiter = Some(delimiter.to_owned());
+    parse_document(arena, input, &options)
+}
+
+#[test]
+fn yaml() {
+    let arena = Arena::new();
+    let root = parse(
+        &arena,
+        concat!(
+            "---\n",
+            "title: Hello\n",
+            "draft: true\n",
+            "weight: 1.5\n",
+            "tags: [a, b]\n",
+            "---\n",
+            "\n",
+            "# Hello\n",
+        ),
+        "---",
+    );
+
+    assert_eq!(
+        front_matter_as::<Meta>(root).unwrap(),
+        Some(Meta {
+            title: "Hello".to_owned(),
+            draft: true,
+            weight: 1.5,
+            tags: vec!["a".to_owned(), "b".to_owned()],
+        })
+    );
+}
+
+#[test]
+fn toml() {
+    let arena = Arena::new();
+    let root = parse(
+        &arena,
+        concat!(
+            "+++\r\n",
+            "title = \"Hello\"\r\n",
+            "draft = false\r\n",
+            "weight = 2.0\r\n",
+            "tags = []\r\n",
+            "+++\r\n",
+            "Body.\r\n",
+        ),
+        "+++",
+    );
+
+    assert_eq!(
+        front_matter_as::<Meta>(root).unwrap(),
+        Some(Meta {
+            title: "Hello".to_owned(),
+            draft: false,
+            weight: 2.0,
+            tags: vec![],
+        })
+    );
+}
+
+#[test]
+fn none() {
+    let arena = Arena::new();
+    let root = parse(&arena, "# No front matter\n", "---");
+
+    assert_eq!(front_matter_as::<Meta>(root).unwrap(), None);
+}
+
+#[test]
+fn toml_error_position() {
+    let arena = Arena::new();
+    let root = parse(
+        &arena,
+        concat!("+++\n", "title = \"Hello\"\n", "draft = maybe\n", "+++\n"),
+        "+++",
+    );
+
+    let err = front_matter_as::<Meta>(root).unwrap_err();
+    assert_eq!(err.position(), Some((3, 9).into()));
+}
+
+#[test]
+fn yaml_error_position() {
+    let arena = Arena::new();
+    let root = parse(
+        &arena,
+        concat!("---\n", "title: Hello\n", "  draft: true\n", "---\n"),
+        "---",
+    );
+
+    let err = front_matter_as::<Meta>(root).unwrap_err();
+    assert_eq!(err.position().map(|p| p.line), Some(3));
+}
+
+#[test]
+fn yaml_type_error() {
+    let arena = Arena::new();
+    let root = parse(&arena, concat!("---\n", "title: [1]\n", "---\n"), "---");
+
+    let err = front_matter_as::<Meta>(root).unwrap_err();
+    assert_eq!(err.position(), Some((2, 8).into()));
+    assert_eq!(
+        err.message(),
+        "title: invalid type: sequence, expected a string"
+    );
+}
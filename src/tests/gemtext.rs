@@ -0,0 +1,134 @@
+use super::*;
+
+#[track_caller]
+fn gemtext(input: &str, expected: &str, opts: impl Fn(&mut Options)) {
+    formatted(format_gemtext, "gemtext", input, expected, opts);
+}
+
+#[test]
+fn gemtext_blocks() {
+    gemtext(
+        concat!(
+            "Title\n",
+            "=====\n",
+            "\n",
+            "#### Deep *heading*\n",
+            "\n",
+            "> Quoted\n",
+            "> text.\n",
+            ">\n",
+            "> ```\n",
+            "> code\n",
+            "> ```\n",
+            "\n",
+            "- one\n",
+            "  - nested\n",
+            "- two\n",
+            "\n",
+            "3) three\n",
+            "4) four\n",
+            "\n",
+            "```rust main\n",
+            "let x = 1;\n",
+            "```\n",
+            "\n",
+            "<div>\n",
+            "dropped\n",
+            "</div>\n",
+            "\n",
+            "***\n",
+        ),
+        concat!(
+            "# Title\n",
+            "\n",
+            "### Deep heading\n",
+            "\n",
+            "> Quoted text.\n",
+            ">\n",
+            "```\n",
+            "code\n",
+            "```\n",
+            "\n",
+            "* one\n",
+            "* nested\n",
+            "* two\n",
+            "\n",
+            "3. three\n",
+            "4. four\n",
+            "\n",
+            "```rust\n",
+            "let x = 1;\n",
+            "```\n",
+        ),
+        |_| {},
+    );
+}
+
+#[test]
+fn gemtext_links() {
+    gemtext(
+        concat!(
+            "See *the* [`docs`](https://a.org/d \"Docs\") or <https://b.org>,\n",
+            "![a *cat*](cat.png)\\\n",
+            "and [[Home]].\n",
+            "\n",
+            "> Quoted [link](/q).\n",
+            "\n",
+            "- Item [link](/i).\n",
+        ),
+        concat!(
+            "See the docs or https://b.org,\n",
+            "and Home.\n",
+            "=> https://a.org/d docs\n",
+            "=> https://b.org\n",
+            "=> cat.png a cat\n",
+            "=> Home Home\n",
+            "\n",
+            "> Quoted link.\n",
+            "=> /q link\n",
+            "\n",
+            "* Item link.\n",
+            "=> /i link\n",
+        ),
+        |options| options.extension.wikilinks_title_after_pipe = true,
+    );
+}
+
+#[test]
+fn gemtext_extensions() {
+    gemtext(
+        concat!(
+            "| a | b |\n",
+            "|---|---|\n",
+            "| ~c~ | d |\n",
+            "\n",
+            "Note[^n].\n",
+            "\n",
+            "[^n]: *Foot*.\n",
+        ),
+        concat!(
+            "```\n",
+            "| a | b |\n",
+            "| c | d |\n",
+            "```\n",
+            "\n",
+            "Note[n].\n",
+            "\n",
+            "[n] Foot.\n",
+        ),
+        |options| {
+            options.extension.table = true;
+            options.extension.strikethrough = true;
+            options.extension.footnotes = true;
+        },
+    );
+}
+
+#[test]
+fn markdown_to_gemtext_api() {
+    assert_eq!(
+        markdown_to_gemtext("# Hi\n\n*there*\n", &Options::default()),
+        "# Hi\n\nthere\n"
+    );
+    assert_eq!(markdown_to_gemtext("", &Options::default()), "");
+}
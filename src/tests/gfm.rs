@@ -0,0 +1,26 @@
+use super::*;
+
+#[test]
+fn gfm_spec() {
+    let mut options = Options::gfm();
+    options.render.unsafe_ = true;
+    spec_examples(include_str!("fixtures/gfm.md"), &options);
+
+    // Every extension example of the vendored GFM spec renders with
+    // `Options::gfm()` alone.
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("vendor/cmark-gfm/test/spec.txt");
+    if let Ok(spec) = std::fs::read_to_string(path) {
+        for example in crate::spec::examples(&spec) {
+            if example.extensions.is_empty() {
+                continue;
+            }
+            compare_strs(
+                &markdown_to_html(&example.markdown, &options),
+                &example.html,
+                &format!("spec example {}", example.number),
+                &example.markdown,
+            );
+        }
+    }
+}
@@ -25,3 +25,140 @@ fn header_ids() {
         |opts| opts.extension.header_ids = Some("user-content-".to_owned()),
     );
 }
+
+#[test]
+fn collect_anchors_matches_rendered_ids() {
+    let input = concat!("# Hi.\n", "\n", "> ## Hi *there*\n", "\n", "Hi.\n", "---\n",);
+
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.header_ids = Some("".to_owned());
+    let root = parse_document(&arena, input, &options);
+
+    let anchors = html::collect_anchors(root, &options);
+    assert_eq!(
+        anchors
+            .iter()
+            .map(|a| (a.id.as_str(), a.text.as_str(), a.level, a.sourcepos))
+            .collect::<Vec<_>>(),
+        vec![
+            ("hi", "Hi.", 1, (1, 1, 1, 5).into()),
+            ("hi-there", "Hi there", 2, (3, 3, 3, 15).into()),
+            ("hi-1", "Hi.", 2, (5, 1, 6, 3).into()),
+        ]
+    );
+
+    let mut output = vec![];
+    format_html(root, &options, &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    for anchor in anchors {
+        assert!(output.contains(&format!("id=\"{}\"", anchor.id)));
+    }
+}
+
+#[test]
+fn header_id_collisions_hash() {
+    html_opts_i(
+        concat!("# Hi\n", "# Hi\n", "# Hi\n"),
+        concat!(
+            "<h1><a href=\"#hi\" aria-hidden=\"true\" class=\"anchor\" id=\"hi\"></a>Hi</h1>\n",
+            "<h1><a href=\"#hi-3e854b\" aria-hidden=\"true\" class=\"anchor\" id=\"hi-3e854b\"></a>Hi</h1>\n",
+            "<h1><a href=\"#hi-b9c818\" aria-hidden=\"true\" class=\"anchor\" id=\"hi-b9c818\"></a>Hi</h1>\n",
+        ),
+        true,
+        |opts| {
+            opts.extension.header_ids = Some("".to_owned());
+            opts.extension.header_id_collisions = HeaderIdCollisions::Hash;
+        },
+    );
+}
+
+#[test]
+fn header_id_collisions_error() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.header_ids = Some("user-content-".to_owned());
+    options.extension.header_id_collisions = HeaderIdCollisions::Error;
+
+    let root = parse_document(&arena, "# Hi\n\n# Hello\n", &options);
+    let mut output = vec![];
+    format_html(root, &options, &mut output).unwrap();
+
+    let root = parse_document(&arena, "# Hi\n\n# Hi\n", &options);
+    let err = format_html(root, &options, &mut vec![]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "duplicate heading id \"user-content-hi\"");
+
+    options.extension.header_ids_reserved = vec!["user-content-hello".to_owned()];
+    let root = parse_document(&arena, "# Hello\n", &options);
+    assert!(format_html(root, &options, &mut vec![]).is_err());
+
+    assert_eq!(
+        markdown_to_html("# Hi\n\n# Hi\n", &options),
+        concat!(
+            "<h1><a href=\"#hi\" aria-hidden=\"true\" class=\"anchor\" id=\"user-content-hi\"></a>Hi</h1>\n",
+            "<h1><a href=\"#hi-1\" aria-hidden=\"true\" class=\"anchor\" id=\"user-content-hi-1\"></a>Hi</h1>\n",
+        )
+    );
+}
+
+#[test]
+fn header_ids_reserved() {
+    html_opts_i(
+        concat!("# Hi\n", "# Hi\n", "# Top\n"),
+        concat!(
+            "<h1><a href=\"#hi-1\" aria-hidden=\"true\" class=\"anchor\" id=\"x-hi-1\"></a>Hi</h1>\n",
+            "<h1><a href=\"#hi-3\" aria-hidden=\"true\" class=\"anchor\" id=\"x-hi-3\"></a>Hi</h1>\n",
+            "<h1><a href=\"#top\" aria-hidden=\"true\" class=\"anchor\" id=\"x-top\"></a>Top</h1>\n",
+        ),
+        true,
+        |opts| {
+            opts.extension.header_ids = Some("x-".to_owned());
+            opts.extension.header_ids_reserved =
+                vec!["x-hi".to_owned(), "x-hi-2".to_owned(), "top".to_owned()];
+        },
+    );
+}
+
+#[test]
+fn header_id_style() {
+    // "Été" precomposed, then decomposed into base letters and combining
+    // accents.
+    let input = "# \u{c9}t\u{e9}\n\n# E\u{301}te\u{301}\n\n# 日本語の見出し\n";
+    html_opts_i(
+        input,
+        concat!(
+            "<h1><a href=\"#été\" aria-hidden=\"true\" class=\"anchor\" id=\"été\"></a>\u{c9}t\u{e9}</h1>\n",
+            "<h1><a href=\"#été-1\" aria-hidden=\"true\" class=\"anchor\" id=\"été-1\"></a>E\u{301}te\u{301}</h1>\n",
+            "<h1><a href=\"#日本語の見出し\" aria-hidden=\"true\" class=\"anchor\" id=\"日本語の見出し\"></a>日本語の見出し</h1>\n",
+        ),
+        true,
+        |opts| opts.extension.header_ids = Some("".to_owned()),
+    );
+    html_opts_i(
+        input,
+        concat!(
+            "<h1><a href=\"#ete\" aria-hidden=\"true\" class=\"anchor\" id=\"ete\"></a>\u{c9}t\u{e9}</h1>\n",
+            "<h1><a href=\"#ete-1\" aria-hidden=\"true\" class=\"anchor\" id=\"ete-1\"></a>E\u{301}te\u{301}</h1>\n",
+            "<h1><a href=\"#ri-ben-yu-nojian-chu-si\" aria-hidden=\"true\" class=\"anchor\" id=\"ri-ben-yu-nojian-chu-si\"></a>日本語の見出し</h1>\n",
+        ),
+        true,
+        |opts| {
+            opts.extension.header_ids = Some("".to_owned());
+            opts.extension.header_id_style = HeaderIdStyle::Ascii;
+        },
+    );
+    html_opts_i(
+        input,
+        concat!(
+            "<h1><a href=\"#%C3%A9t%C3%A9\" aria-hidden=\"true\" class=\"anchor\" id=\"%C3%A9t%C3%A9\"></a>\u{c9}t\u{e9}</h1>\n",
+            "<h1><a href=\"#%C3%A9t%C3%A9-1\" aria-hidden=\"true\" class=\"anchor\" id=\"%C3%A9t%C3%A9-1\"></a>E\u{301}te\u{301}</h1>\n",
+            "<h1><a href=\"#%E6%97%A5%E6%9C%AC%E8%AA%9E%E3%81%AE%E8%A6%8B%E5%87%BA%E3%81%97\" aria-hidden=\"true\" class=\"anchor\" id=\"%E6%97%A5%E6%9C%AC%E8%AA%9E%E3%81%AE%E8%A6%8B%E5%87%BA%E3%81%97\"></a>日本語の見出し</h1>\n",
+        ),
+        true,
+        |opts| {
+            opts.extension.header_ids = Some("".to_owned());
+            opts.extension.header_id_style = HeaderIdStyle::PercentEncoded;
+        },
+    );
+}
@@ -0,0 +1,73 @@
+use super::*;
+
+#[test]
+fn html_comments_keep() {
+    html_opts_i(
+        concat!(
+            "<!-- a -->\n",
+            "<!-- b\n",
+            "c --> <!---->\n",
+            "\n",
+            "<!-- d --> <i>e</i>\n",
+            "\n",
+            "<!-- f --!> <script>g</script> -->\n",
+            "\n",
+            "h <!-- i --> <!-- j --!> -->\n",
+        ),
+        concat!(
+            "<!-- a -->\n",
+            "<!-- b\n",
+            "c --> <!---->\n",
+            "&lt;!-- d --&gt; &lt;i&gt;e&lt;/i&gt;\n",
+            "&lt;!-- f --!&gt; &lt;script&gt;g&lt;/script&gt; --&gt;\n",
+            "<p>h <!-- i --> &lt;!-- j --!&gt; --&gt;</p>\n",
+        ),
+        false,
+        |opts| {
+            opts.render.html_comments = HtmlCommentStyle::Keep;
+            opts.render.escape = true;
+        },
+    );
+}
+
+#[test]
+fn html_comments_keep_early_close() {
+    html_opts_i(
+        concat!(
+            "<!--><img src=x onerror=alert(1)>-->\n",
+            "\n",
+            "<!---><img src=x onerror=alert(1)>-->\n",
+            "\n",
+            "a <!--><img src=x onerror=alert(1)>--> <!---><b>c</b>-->\n",
+        ),
+        concat!(
+            "<!-- raw HTML omitted -->\n",
+            "<!-- raw HTML omitted -->\n",
+            "<p>a <!-- raw HTML omitted --><!-- raw HTML omitted -->--&gt; ",
+            "<!-- raw HTML omitted --><!-- raw HTML omitted -->c<!-- raw HTML omitted -->--&gt;</p>\n",
+        ),
+        false,
+        |opts| opts.render.html_comments = HtmlCommentStyle::Keep,
+    );
+}
+
+#[test]
+fn html_comments_strip() {
+    html_opts_i(
+        concat!(
+            "a\n",
+            "\n",
+            "<!-- b -->\n",
+            "\n",
+            "c <!-- d -->\n",
+            "\n",
+            "<!-- e --> <i>f</i>\n",
+        ),
+        concat!("<p>a</p>\n", "<p>c </p>\n", "<!-- e --> <i>f</i>\n",),
+        false,
+        |opts| {
+            opts.render.html_comments = HtmlCommentStyle::Strip;
+            opts.render.unsafe_ = true;
+        },
+    );
+}
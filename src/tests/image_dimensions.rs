@@ -0,0 +1,72 @@
+use crate::adapters::{ImageDimensionAdapter, ImageDimensions};
+
+use super::*;
+
+fn image_dimensions(options: &mut Options) {
+    options.extension.image_dimensions = true;
+}
+
+#[test]
+fn image_dimensions_syntax() {
+    html_opts_i(
+        concat!(
+            "![a](/a.png =640x480) ![b](/b.png =640x \"B\") ![c](</c d.png> =x480)\n",
+            "\n",
+            "![e](/e.png =x) ![f](/f.png =640) ![g](/g.png=1x2) [h](/h =1x2)\n",
+            "\n",
+            "![i](/i.png =99999999999x1)\n",
+        ),
+        concat!(
+            "<p><img src=\"/a.png\" alt=\"a\" width=\"640\" height=\"480\" /> \
+             <img src=\"/b.png\" alt=\"b\" title=\"B\" width=\"640\" /> \
+             <img src=\"/c%20d.png\" alt=\"c\" height=\"480\" /></p>\n",
+            "<p>![e](/e.png =x) ![f](/f.png =640) <img src=\"/g.png=1x2\" alt=\"g\" /> [h](/h =1x2)</p>\n",
+            "<p>![i](/i.png =99999999999x1)</p>\n",
+        ),
+        true,
+        image_dimensions,
+    );
+}
+
+#[test]
+fn image_dimensions_disabled() {
+    html("![a](/a.png =640x480)\n", "<p>![a](/a.png =640x480)</p>\n");
+}
+
+#[test]
+fn image_dimensions_override_adapter() {
+    struct Sizes;
+    impl ImageDimensionAdapter for Sizes {
+        fn dimensions(&self, _url: &str) -> Option<ImageDimensions> {
+            Some(ImageDimensions {
+                width: Some(1),
+                height: Some(2),
+                srcset: Some("/x@2x.png 2x".to_string()),
+            })
+        }
+    }
+
+    let mut options = Options::default();
+    options.extension.image_dimensions = true;
+    options.render.lazy_images = true;
+    let mut plugins = Plugins::default();
+    plugins.render.image_dimension_adapter = Some(&Sizes);
+
+    assert_eq!(
+        markdown_to_html_with_plugins("![a](/x.png =640x) ![b](/x.png)\n", &options, &plugins),
+        concat!(
+            "<p><img src=\"/x.png\" alt=\"a\" width=\"640\" srcset=\"/x@2x.png 2x\" loading=\"lazy\" decoding=\"async\" /> ",
+            "<img src=\"/x.png\" alt=\"b\" width=\"1\" height=\"2\" srcset=\"/x@2x.png 2x\" loading=\"lazy\" decoding=\"async\" /></p>\n",
+        )
+    );
+}
+
+#[test]
+fn image_dimensions_commonmark() {
+    let mut options = Options::default();
+    options.extension.image_dimensions = true;
+    assert_eq!(
+        markdown_to_commonmark("![a](/a.png =640x480 \"T\") ![b](/b.png =x10)\n", &options),
+        "![a](/a.png =640x480 \"T\") ![b](/b.png =x10)\n"
+    );
+}
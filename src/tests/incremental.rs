@@ -0,0 +1,231 @@
+use super::*;
+use crate::incremental::{diff, BlockChange, Document};
+use std::ops::Range;
+
+// Applies each edit to a document made from `input`, checking the AST and
+// node offsets match those of parsing the edited text whole.
+#[track_caller]
+fn edits_match(input: &str, edits: &[(Range<usize>, &str)], options: &Options) {
+    let arena = Arena::new();
+    let mut document = Document::new(&arena, input, options);
+    let mut text = input.to_string();
+    for (range, replacement) in edits {
+        text.replace_range(range.clone(), replacement);
+        document.edit(range.clone(), replacement);
+        assert_eq!(document.text(), text);
+
+        let arena = Arena::new();
+        let root = parse_document(&arena, &text, options);
+        assert_eq!(
+            nodes(document.root(), options),
+            nodes(root, options),
+            "{:?} after replacing {:?} with {:?}",
+            text,
+            range,
+            replacement
+        );
+    }
+}
+
+// Checks every insertion of each of `inserts` into `input`, and every
+// deletion of a character from it.
+#[track_caller]
+fn single_edits_match(input: &str, inserts: &[&str], options: &Options) {
+    let boundaries: Vec<_> = (0..=input.len())
+        .filter(|&i| input.is_char_boundary(i))
+        .collect();
+    for &i in &boundaries {
+        for insert in inserts {
+            edits_match(input, &[(i..i, insert)], options);
+        }
+    }
+    for pair in boundaries.windows(2) {
+        edits_match(input, &[(pair[0]..pair[1], "")], options);
+    }
+}
+
+fn nodes<'a>(root: &'a AstNode<'a>, options: &Options) -> (String, Vec<(usize, usize)>) {
+    let mut options = options.clone();
+    options.render.sourcepos = true;
+    let mut xml = vec![];
+    format_xml(root, &options, &mut xml).unwrap();
+    let offsets = root
+        .descendants()
+        .map(|node| {
+            let ast = node.data.borrow();
+            (ast.start_offset, ast.end_offset)
+        })
+        .collect();
+    (String::from_utf8(xml).unwrap(), offsets)
+}
+
+#[test]
+fn incremental() {
+    let options = Options::default();
+    single_edits_match(
+        "# Title\n\npara\ngraph\n\n> quote\nlazy\n\n- a\n\n  b\n- c\n\n```\ncode\n```\n\n    indented\n\ntext\n",
+        &["\n", "`", ">", "-", "=", "#", "a", " ", "<!--"],
+        &options,
+    );
+    single_edits_match(
+        "a\r\n\r\nb\rc\n\n<div>\n\nd",
+        &["\n", "\r", "<div>"],
+        &options,
+    );
+}
+
+#[test]
+fn incremental_extensions() {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.multiline_block_quotes = true;
+    options.extension.math_dollars = true;
+    options.extension.description_lists = true;
+    single_edits_match(
+        "| a | b |\n| - | - |\n| c | d |\n\n>>>\nquote\n>>>\n\n$$\nx\n$$\n\nterm\n\n: details\n",
+        &["\n", "|", ">>>", "$$", ": "],
+        &options,
+    );
+}
+
+#[test]
+fn incremental_references() {
+    let options = Options::default();
+    edits_match(
+        "[a]\n\n[b]\n\n[a]: /a\n",
+        &[
+            (8..8, "\n[b]: /b\n"),
+            (0..0, "Intro.\n\n"),
+            (17..21, ""),
+            (0..3, "[b]"),
+        ],
+        &options,
+    );
+}
+
+#[test]
+fn incremental_front_matter_and_footnotes() {
+    let mut options = Options::default();
+    options.extension.front_matter_delimiter = Some("---".to_string());
+    options.extension.footnotes = true;
+    edits_match(
+        "Intro[^1].\n\n---\ntitle\n\n[^1]: Note.\n\nText[^2].\n\n[^2]: Other.\n",
+        &[(0..0, "---\n"), (4..4, "a: b\n---\n\n"), (0..0, "\n")],
+        &options,
+    );
+}
+
+#[test]
+fn incremental_keeps_unchanged_blocks() {
+    let arena = Arena::new();
+    let options = Options::default();
+    let mut document = Document::new(&arena, "# A\n\nb\n\nc\n\nd\n\n# E\n", &options);
+    let before: Vec<_> = document.root().children().collect();
+
+    let changed = document.edit(8..9, "C");
+    assert_eq!(changed.len(), 2);
+    let after: Vec<_> = document.root().children().collect();
+    assert_eq!(after.len(), 5);
+    assert!(after[0].same_node(before[0]));
+    assert!(after[3].same_node(before[3]));
+    assert!(after[4].same_node(before[4]));
+    assert!(!after[2].same_node(before[2]));
+
+    // An unclosed fence swallows the rest of the document.
+    let changed = document.edit(5..5, "```\n");
+    assert_eq!(changed.len(), 2);
+    let after: Vec<_> = document.root().children().collect();
+    assert_eq!(after.len(), 2);
+    assert!(after[1].same_node(changed[1]));
+}
+
+// Checks that patching the blocks of `old`'s HTML with the changes `diff`
+// reports gives `new`'s HTML, returning the changes.
+#[track_caller]
+fn diff_patches(old: &str, new: &str, options: &Options) -> Vec<BlockChange> {
+    let arena = Arena::new();
+    let (old_root, new_root) = (
+        parse_document(&arena, old, options),
+        parse_document(&arena, new, options),
+    );
+    let changes = diff(old_root, new_root, options);
+    let mut patched = blocks_html(old_root, options);
+    for change in changes.iter().rev() {
+        let html = change.blocks.iter().map(|block| block.html.clone());
+        patched.splice(change.old.clone(), html);
+    }
+    assert_eq!(
+        patched,
+        blocks_html(new_root, options),
+        "{:?} to {:?}",
+        old,
+        new
+    );
+    changes
+}
+
+#[test]
+fn diff_blocks() {
+    let options = Options::default();
+    assert_eq!(diff_patches("a\n\nb\n", "a\n\nb\n", &options), vec![]);
+
+    let changes = diff_patches("a\n\nb\n\nc\n", "a\n\nc\n", &options);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(
+        (changes[0].old.clone(), changes[0].new.clone()),
+        (1..2, 1..1)
+    );
+    assert!(changes[0].blocks.is_empty());
+
+    let changes = diff_patches("a\n\nc\n", "a\n\n## b\n\nc\n", &options);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(
+        (changes[0].old.clone(), changes[0].new.clone()),
+        (1..1, 1..2)
+    );
+    assert_eq!(changes[0].blocks[0].sourcepos, (3, 1, 3, 4).into());
+    assert_eq!(changes[0].blocks[0].html, "<h2>b</h2>\n");
+
+    let changes = diff_patches(
+        "# a\n\nb\n\nc\n\nd\n\ne\n",
+        "# A\n\nb\n\nc\n\nD\n\nx\n\ne\n",
+        &options,
+    );
+    let ranges: Vec<_> = changes
+        .iter()
+        .map(|c| (c.old.clone(), c.new.clone()))
+        .collect();
+    assert_eq!(ranges, vec![(0..1, 0..1), (3..4, 3..5)]);
+
+    diff_patches("a\n\nb\n\nc\n", "c\n\nb\n\na\n", &options);
+    diff_patches("a\n\nb\n", "", &options);
+    diff_patches("", "a\n\nb\n", &options);
+}
+
+#[test]
+fn diff_blocks_sourcepos() {
+    let mut options = Options::default();
+    options.render.sourcepos = true;
+
+    // The blocks after an inserted line move, changing their attributes.
+    let changes = diff_patches("a\n\nb\n\nc\n", "a\nx\n\nb\n\nc\n", &options);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(
+        (changes[0].old.clone(), changes[0].new.clone()),
+        (0..3, 0..3)
+    );
+    assert_eq!(
+        changes[0].blocks[2].html,
+        "<p data-sourcepos=\"6:1-6:1\">c</p>\n"
+    );
+}
+
+fn blocks_html<'a>(root: &'a AstNode<'a>, options: &Options) -> Vec<String> {
+    root.children()
+        .map(|block| {
+            let mut html = vec![];
+            format_html_node(block, options, &mut html, &Plugins::default()).unwrap();
+            String::from_utf8(html).unwrap()
+        })
+        .collect()
+}
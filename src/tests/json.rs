@@ -0,0 +1,77 @@
+use crate::json::{from_json, to_json};
+
+use super::*;
+
+#[test]
+fn json_roundtrip() {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.tasklist = true;
+    options.extension.footnotes = true;
+    options.extension.math_dollars = true;
+    options.render.sourcepos = true;
+
+    let input = concat!(
+        "# Title\n",
+        "\n",
+        "- [x] done\n",
+        "- [ ] *not* [yet](/y \"t\")\n",
+        "\n",
+        "| a | b |\n",
+        "|:--|--:|\n",
+        "| $x$ | `c` |\n",
+        "\n",
+        "Note[^1].\n",
+        "\n",
+        "```rust\n",
+        "fn main() {}\n",
+        "```\n",
+        "\n",
+        "[^1]: Here.\n",
+    );
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, input, &options);
+    let json = to_json(root);
+    let copy = from_json(&arena, &json).unwrap();
+
+    assert_eq!(to_json(copy), json);
+
+    let mut expected = vec![];
+    format_html(root, &options, &mut expected).unwrap();
+    let mut actual = vec![];
+    format_html(copy, &options, &mut actual).unwrap();
+    compare_strs(
+        &String::from_utf8(actual).unwrap(),
+        &String::from_utf8(expected).unwrap(),
+        "json",
+        input,
+    );
+}
+
+#[test]
+fn json_format() {
+    let arena = Arena::new();
+    let root = parse_document(&arena, "*a*\n", &Options::default());
+    assert_eq!(
+        to_json(root.first_child().unwrap()),
+        concat!(
+            r#"{"value":"Paragraph","#,
+            r#""sourcepos":{"start":{"line":1,"column":1},"end":{"line":1,"column":3}},"#,
+            r#""start_offset":0,"end_offset":3,"children":[{"#,
+            r#""value":"Emph","#,
+            r#""sourcepos":{"start":{"line":1,"column":1},"end":{"line":1,"column":3}},"#,
+            r#""start_offset":0,"end_offset":3,"#,
+            r#""emphasis":{"delim_char":42,"delim_run_length":1},"children":[{"#,
+            r#""value":{"Text":"a"},"#,
+            r#""sourcepos":{"start":{"line":1,"column":2},"end":{"line":1,"column":2}},"#,
+            r#""start_offset":1,"end_offset":2}]}]}"#,
+        )
+    );
+}
+
+#[test]
+fn json_invalid() {
+    let arena = Arena::new();
+    assert!(from_json(&arena, r#"{"value":"Nonsense"}"#).is_err());
+}
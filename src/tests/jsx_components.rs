@@ -0,0 +1,76 @@
+use super::*;
+
+#[test]
+fn jsx_component_blocks() {
+    html_opts!(
+        [extension.jsx_components, render.unsafe_],
+        concat!(
+            "# Title\n",
+            "\n",
+            "<Callout kind=\"warn\" onClose={() => x > 1}>\n",
+            "\n",
+            "*Kept* as is.\n",
+            "\n",
+            "<Callout>nested</Callout>\n",
+            "</Callout>\n",
+            "After.\n",
+            "\n",
+            "<Chart\n",
+            "  data={[1, 2]}\n",
+            "  label=\"a > b\"\n",
+            "/>\n",
+            "\n",
+            "<table><tr><td>html</td></tr></table>\n",
+        ),
+        concat!(
+            "<h1>Title</h1>\n",
+            "<Callout kind=\"warn\" onClose={() => x > 1}>\n",
+            "\n",
+            "*Kept* as is.\n",
+            "\n",
+            "<Callout>nested</Callout>\n",
+            "</Callout>\n",
+            "<p>After.</p>\n",
+            "<Chart\n",
+            "  data={[1, 2]}\n",
+            "  label=\"a > b\"\n",
+            "/>\n",
+            "<table><tr><td>html</td></tr></table>\n",
+        ),
+    );
+}
+
+#[test]
+fn jsx_component_inlines() {
+    html_opts!(
+        [extension.jsx_components, render.unsafe_],
+        "Press <Kbd keys={['*', '_']}>*Ctrl*</Kbd> or <Icon name=\"x\" />, not <Unclosed and <b>*this*</b>.\n",
+        "<p>Press <Kbd keys={['*', '_']}>*Ctrl*</Kbd> or <Icon name=\"x\" />, not &lt;Unclosed and <b><em>this</em></b>.</p>\n",
+    );
+}
+
+#[test]
+fn jsx_components_omitted_when_safe() {
+    html_opts!(
+        [extension.jsx_components],
+        "<Script />\n\nA <Widget />.\n",
+        "<!-- raw HTML omitted -->\n<p>A <!-- raw HTML omitted -->.</p>\n",
+    );
+}
+
+#[test]
+fn jsx_component_sourcepos() {
+    assert_ast_match!(
+        [extension.jsx_components],
+        "> <A>\n> b\n> </A>\n\nc <B/>\n",
+        (document (1:1-5:6) [
+            (block_quote (1:1-3:6) [
+                (component_block (1:3-3:6))
+            ])
+            (paragraph (5:1-5:6) [
+                (text (5:1-5:2) "c ")
+                (component_inline (5:3-5:6))
+            ])
+        ])
+    );
+}
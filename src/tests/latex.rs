@@ -0,0 +1,175 @@
+use super::*;
+
+#[track_caller]
+fn latex(input: &str, expected: &str, opts: impl Fn(&mut Options)) {
+    formatted(format_latex, "latex", input, expected, opts);
+}
+
+#[test]
+fn latex_blocks() {
+    latex(
+        concat!(
+            "Title\n",
+            "=====\n",
+            "\n",
+            "#### Sub *heading*\n",
+            "\n",
+            "> Quoted\n",
+            "> text.\n",
+            "\n",
+            "- one\n",
+            "  - nested\n",
+            "- [two]\n",
+            "\n",
+            "3) three\n",
+            "\n",
+            "   more\n",
+            "4) four\n",
+            "\n",
+            "```rust main\n",
+            "let s = \"\\\\\";\n",
+            "```\n",
+            "\n",
+            "<div>\n",
+            "dropped\n",
+            "</div>\n",
+            "\n",
+            "***\n",
+        ),
+        concat!(
+            "\\section{Title}\n",
+            "\n",
+            "\\paragraph{Sub \\emph{heading}}\n",
+            "\n",
+            "\\begin{quote}\n",
+            "Quoted\n",
+            "text.\n",
+            "\\end{quote}\n",
+            "\n",
+            "\\begin{itemize}\n",
+            "\\item one\n",
+            "\n",
+            "\\begin{itemize}\n",
+            "\\item nested\n",
+            "\\end{itemize}\n",
+            "\\item{} [two]\n",
+            "\\end{itemize}\n",
+            "\n",
+            "\\begin{enumerate}\n",
+            "\\item[3)] three\n",
+            "\n",
+            "more\n",
+            "\\item[4)] four\n",
+            "\\end{enumerate}\n",
+            "\n",
+            "\\begin{verbatim}\n",
+            "let s = \"\\\\\";\n",
+            "\\end{verbatim}\n",
+            "\n",
+            "\\noindent\\rule{\\linewidth}{0.4pt}\n",
+        ),
+        |_| {},
+    );
+}
+
+#[test]
+fn latex_inlines() {
+    latex(
+        concat!(
+            "*a* **b** `c_d` [f](/u#x \"t\") <https://x.org/50%>\\\n",
+            "![g *h*](i.png) $x^2$ $$\\sum_i$$\n",
+        ),
+        concat!(
+            "\\emph{a} \\textbf{b} \\texttt{c\\_d} \\href{/u\\#x}{f} \\url{https://x.org/50\\%}\\\\\n",
+            "\\includegraphics{i.png} $x^2$ \\[\\sum_i\\]\n",
+        ),
+        |options| options.extension.math_dollars = true,
+    );
+}
+
+#[test]
+fn latex_escapes() {
+    latex(
+        "#1 & 50% of $5 {x} a_b ~ ^ \\\\ <a|b> a--b a---b\n",
+        concat!(
+            "\\#1 \\& 50\\% of \\$5 \\{x\\} a\\_b \\textasciitilde{} \\textasciicircum{} ",
+            "\\textbackslash{} \\textless{}a\\textbar{}b\\textgreater{} a-{}-b a-{}-{}-b\n",
+        ),
+        |_| {},
+    );
+}
+
+#[test]
+fn latex_extensions() {
+    latex(
+        concat!(
+            "Term\n",
+            "\n",
+            ": Details.\n",
+            "\n",
+            "- [x] done\n",
+            "- [ ] todo\n",
+            "\n",
+            "| a | b |\n",
+            "|:--|--:|\n",
+            "| ~c~ | d |\n",
+            "\n",
+            "Note[^n].\n",
+            "\n",
+            "[^n]: *Foot*.\n",
+        ),
+        concat!(
+            "\\begin{description}\n",
+            "\\item[{Term}] Details.\n",
+            "\\end{description}\n",
+            "\n",
+            "\\begin{itemize}\n",
+            "\\item[{[x]}] done\n",
+            "\\item[{[ ]}] todo\n",
+            "\\end{itemize}\n",
+            "\n",
+            "\\begin{tabular}{lr}\n",
+            "a & b \\\\\n",
+            "\\hline\n",
+            "\\sout{c} & d \\\\\n",
+            "\\end{tabular}\n",
+            "\n",
+            "Note\\footnote{\\emph{Foot}.}.\n",
+        ),
+        |options| {
+            options.extension.description_lists = true;
+            options.extension.tasklist = true;
+            options.extension.table = true;
+            options.extension.strikethrough = true;
+            options.extension.footnotes = true;
+        },
+    );
+}
+
+#[test]
+fn latex_table_colspans() {
+    latex(
+        "| a | b | c |\n|---|:-:|--:|\n| d || e |\n| f | g ||\n",
+        concat!(
+            "\\begin{tabular}{lcr}\n",
+            "a & b & c \\\\\n",
+            "\\hline\n",
+            "\\multicolumn{2}{l}{d} & e \\\\\n",
+            "f & \\multicolumn{2}{c}{g} \\\\\n",
+            "\\end{tabular}\n",
+        ),
+        |options| {
+            options.extension.table = true;
+            options.extension.table_colspans = true;
+        },
+    );
+}
+
+#[test]
+fn markdown_to_latex_api() {
+    assert_eq!(
+        markdown_to_latex("# Hi\n\n*there*\n", &Options::default()),
+        "\\section{Hi}\n\n\\emph{there}\n"
+    );
+    assert_eq!(markdown_to_latex("", &Options::default()), "");
+}
@@ -0,0 +1,39 @@
+use super::*;
+use crate::links::{collect_links, Link, LinkKind};
+
+#[test]
+fn links() {
+    let mut options = Options::default();
+    options.extension.autolink = true;
+    options.extension.wikilinks_title_after_pipe = true;
+    let arena = Arena::new();
+    let root = parse_document(
+        &arena,
+        "[![*Logo*](logo.png 'L')][home] <https://a.example>\n\n- [[Page|the page]]\n\n[home]: / \"Home\"\n",
+        &options,
+    );
+
+    let link =
+        |kind, url: &str, title: &str, text: &str, sourcepos: (usize, usize, usize, usize)| Link {
+            kind,
+            url: url.to_string(),
+            title: title.to_string(),
+            text: text.to_string(),
+            sourcepos: sourcepos.into(),
+        };
+    assert_eq!(
+        collect_links(root),
+        vec![
+            link(LinkKind::Link, "/", "Home", "Logo", (1, 1, 1, 31)),
+            link(LinkKind::Image, "logo.png", "L", "Logo", (1, 2, 1, 24)),
+            link(
+                LinkKind::Link,
+                "https://a.example",
+                "",
+                "https://a.example",
+                (1, 34, 1, 52)
+            ),
+            link(LinkKind::WikiLink, "Page", "", "the page", (3, 3, 3, 19)),
+        ]
+    );
+}
@@ -0,0 +1,390 @@
+use crate::lint::{Finding, Fix, Linter, OptionError, Rule, Severity, Violation};
+
+use super::*;
+
+fn check(linter: &Linter, md: &str) -> Vec<Finding> {
+    let arena = Arena::new();
+    let options = Options::default();
+    let root = parse_document(&arena, md, &options);
+    linter.check(root, md, &options)
+}
+
+fn rules_found(findings: &[Finding]) -> Vec<(&'static str, usize)> {
+    findings
+        .iter()
+        .map(|f| (f.rule, f.sourcepos.start.line))
+        .collect()
+}
+
+#[test]
+fn builtin_rules() {
+    let findings = check(
+        &Linter::with_builtin_rules(),
+        concat!(
+            "# One\n",
+            "\n",
+            "### Three\n",
+            "\n",
+            "```\n",
+            "code\n",
+            "```\n",
+            "\n",
+            "``` rust\n",
+            "code\n",
+            "```\n",
+            "\n",
+            "[a]() [b](#) [c](#c)\n",
+            "\n",
+            "## Two\n",
+        ),
+    );
+    assert_eq!(
+        rules_found(&findings),
+        vec![
+            ("heading-increment", 3),
+            ("fenced-code-language", 5),
+            ("no-empty-links", 13),
+            ("no-empty-links", 13),
+        ]
+    );
+    assert_eq!(
+        findings[0].message,
+        "heading level 3 follows level 1; expected at most 2"
+    );
+    assert!(findings.iter().all(|f| f.severity == Severity::Warning));
+}
+
+#[test]
+fn configuration() {
+    let mut linter = Linter::with_builtin_rules();
+    assert_eq!(
+        linter.rule_names().collect::<Vec<_>>(),
+        vec![
+            "heading-increment",
+            "fenced-code-language",
+            "no-empty-links",
+            "no-trailing-space-breaks",
+            "no-bare-urls",
+            "no-duplicate-headings"
+        ]
+    );
+    assert!(linter.set_severity("no-empty-links", Severity::Error));
+    assert!(linter.set_enabled("heading-increment", false));
+    assert!(!linter.set_enabled("no-such-rule", false));
+
+    let findings = check(&linter, "# One\n\n### Three [x]()\n");
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].rule, "no-empty-links");
+    assert_eq!(findings[0].severity, Severity::Error);
+}
+
+#[test]
+fn rule_options() {
+    let md = concat!(
+        "# One\n",
+        "\n",
+        "## Examples\n",
+        "\n",
+        "# Two\n",
+        "\n",
+        "## Examples\n",
+        "\n",
+        "## Examples\n",
+        "\n",
+        "``` rust\n",
+        "```\n",
+        "\n",
+        "``` python\n",
+        "```\n",
+    );
+
+    let mut linter = Linter::with_builtin_rules();
+    assert_eq!(
+        rules_found(&check(&linter, md)),
+        vec![("no-duplicate-headings", 7), ("no-duplicate-headings", 9)]
+    );
+
+    linter
+        .set_option("no-duplicate-headings", "siblings-only", "true")
+        .unwrap();
+    linter
+        .set_option("fenced-code-language", "languages", "rust, toml")
+        .unwrap();
+    let findings = check(&linter, md);
+    assert_eq!(
+        rules_found(&findings),
+        vec![("no-duplicate-headings", 9), ("fenced-code-language", 14)]
+    );
+    assert_eq!(
+        findings[1].message,
+        "fenced code block language python isn't allowed"
+    );
+
+    assert_eq!(
+        linter.set_option("no-duplicate-headings", "siblings-only", "yes"),
+        Err(OptionError::InvalidValue {
+            option: "siblings-only".to_string(),
+            value: "yes".to_string(),
+        })
+    );
+    assert_eq!(
+        linter.set_option("no-empty-links", "siblings-only", "true"),
+        Err(OptionError::UnknownOption("siblings-only".to_string()))
+    );
+    assert_eq!(
+        linter.set_option("no-such-rule", "siblings-only", "true"),
+        Err(OptionError::UnknownRule("no-such-rule".to_string()))
+    );
+}
+
+#[test]
+fn custom_rule() {
+    struct NoTodo;
+
+    impl Rule for NoTodo {
+        fn name(&self) -> &'static str {
+            "no-todo"
+        }
+
+        fn default_severity(&self) -> Severity {
+            Severity::Info
+        }
+
+        fn check<'a>(
+            &self,
+            root: &'a AstNode<'a>,
+            _source: &str,
+            _options: &Options,
+        ) -> Vec<Violation> {
+            root.descendants()
+                .filter_map(|node| {
+                    let ast = node.data.borrow();
+                    match ast.value {
+                        NodeValue::Text(ref t) if t.contains("TODO") => {
+                            Some(Violation::new(ast.sourcepos, "TODO left in text"))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect()
+        }
+    }
+
+    let mut linter = Linter::new();
+    linter.add_rule(NoTodo);
+    let findings = check(&linter, "Fine.\n\n- TODO: this\n");
+    assert_eq!(
+        findings,
+        vec![Finding {
+            rule: "no-todo",
+            severity: Severity::Info,
+            sourcepos: (3, 3, 3, 12).into(),
+            message: "TODO left in text".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn suppression_comments() {
+    let linter = Linter::with_builtin_rules();
+    let findings = check(
+        &linter,
+        concat!(
+            "[a]()\n",
+            "\n",
+            "<!-- comrak-disable no-empty-links -->\n",
+            "\n",
+            "[b]()\n",
+            "\n",
+            "```\n",
+            "```\n",
+            "\n",
+            "<!-- comrak-enable no-empty-links -->\n",
+            "\n",
+            "[c]()\n",
+            "\n",
+            "<!-- comrak-disable-next-line -->\n",
+            "[d]()\n",
+            "[e]()\n",
+            "\n",
+            "<!-- comrak-disable -->\n",
+            "\n",
+            "[f]() <!-- comrak-enable fenced-code-language -->\n",
+            "\n",
+            "```\n",
+            "```\n",
+        ),
+    );
+    assert_eq!(
+        rules_found(&findings),
+        vec![
+            ("no-empty-links", 1),
+            ("fenced-code-language", 7),
+            ("no-empty-links", 12),
+            ("no-empty-links", 16),
+            ("fenced-code-language", 22),
+        ]
+    );
+}
+
+#[test]
+fn fixes() {
+    struct NoTodo;
+
+    impl Rule for NoTodo {
+        fn name(&self) -> &'static str {
+            "no-todo"
+        }
+
+        fn check<'a>(
+            &self,
+            root: &'a AstNode<'a>,
+            _source: &str,
+            _options: &Options,
+        ) -> Vec<Violation> {
+            let mut violations = vec![];
+            for node in root.descendants() {
+                let ast = node.data.borrow();
+                if let NodeValue::Text(ref t) = ast.value {
+                    if let Some(i) = t.find("TODO") {
+                        let start = ast.sourcepos.start.column + i;
+                        let sourcepos = (
+                            ast.sourcepos.start.line,
+                            start,
+                            ast.sourcepos.start.line,
+                            start + 3,
+                        )
+                            .into();
+                        violations.push(
+                            Violation::new(sourcepos, "TODO left in text")
+                                .with_fix(Fix::replace(sourcepos, "DONE")),
+                        );
+                        // A conflicting fix, which is skipped.
+                        violations.push(
+                            Violation::new(sourcepos, "TODO left in text")
+                                .with_fix(Fix::replace(sourcepos, "XXXX")),
+                        );
+                    }
+                }
+            }
+            violations
+        }
+    }
+
+    let mut linter = Linter::with_builtin_rules();
+    linter.add_rule(NoTodo);
+    let fixed = linter.fix(
+        concat!(
+            "# One\n",
+            "\n",
+            "### Three TODO\n",
+            "\n",
+            "##### Five\n",
+            "\n",
+            "TODO: [this]() and [*that*](#).\n",
+            "\n",
+            "```\n",
+            "```\n",
+        ),
+        &Options::default(),
+    );
+    assert_eq!(
+        fixed.output,
+        concat!(
+            "# One\n",
+            "\n",
+            "## Three DONE\n",
+            "\n",
+            "#### Five\n",
+            "\n",
+            "DONE: this and *that*.\n",
+            "\n",
+            "```\n",
+            "```\n",
+        )
+    );
+    assert_eq!(fixed.applied, 6);
+    assert_eq!(
+        rules_found(&fixed.findings),
+        vec![("heading-increment", 5), ("fenced-code-language", 9),]
+    );
+}
+
+#[test]
+fn source_rules() {
+    let linter = Linter::with_builtin_rules();
+    let source = concat!(
+        "# Links\n",
+        "\n",
+        "See https://example.com/a, www.example.org and <https://example.com/b>.  \n",
+        "Also [https://example.com/c](/c) and `https://example.com/d`\n",
+        "or &amp; https://example.com/e.\n",
+        "\n",
+        "> quoted   \r\n",
+        "> text\r\n",
+        "\n",
+        "## Links\n",
+    );
+    let options = Options::default();
+    let arena = Arena::new();
+    let root = parse_document(&arena, source, &options);
+
+    let findings = linter.check(root, source, &options);
+    let found: Vec<_> = findings
+        .iter()
+        .map(|f| (f.rule, f.sourcepos, f.message.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (
+                "no-bare-urls",
+                (3, 5, 3, 25).into(),
+                "bare URL https://example.com/a"
+            ),
+            (
+                "no-bare-urls",
+                (3, 28, 3, 42).into(),
+                "bare URL www.example.org"
+            ),
+            (
+                "no-trailing-space-breaks",
+                (3, 72, 3, 73).into(),
+                "hard line break made with trailing spaces"
+            ),
+            (
+                "no-bare-urls",
+                (5, 1, 5, 31).into(),
+                "bare URL https://example.com/e"
+            ),
+            (
+                "no-trailing-space-breaks",
+                (7, 9, 7, 11).into(),
+                "hard line break made with trailing spaces"
+            ),
+            (
+                "no-duplicate-headings",
+                (10, 1, 10, 8).into(),
+                "heading \"Links\" duplicates the one on line 1"
+            ),
+        ]
+    );
+
+    let fixed = linter.fix(source, &options);
+    assert_eq!(
+        fixed.output,
+        concat!(
+            "# Links\n",
+            "\n",
+            "See <https://example.com/a>, [www.example.org](http://www.example.org) and <https://example.com/b>.\\\n",
+            "Also [https://example.com/c](/c) and `https://example.com/d`\n",
+            "or & https://example.com/e.\n",
+            "\n",
+            "> quoted\\\n",
+            "> text\n",
+            "\n",
+            "## Links\n",
+        )
+    );
+    assert_eq!(fixed.applied, 4);
+}
@@ -0,0 +1,157 @@
+use super::*;
+
+fn limited(limit: usize, oversized_input: OversizedInput) -> Options<'static> {
+    let mut options = Options::default();
+    options.parse.max_input_size = Some(limit);
+    options.parse.oversized_input = oversized_input;
+    options
+}
+
+fn last_value<'a>(root: &'a AstNode<'a>) -> Option<NodeValue> {
+    root.last_child()
+        .map(|node| node.data.borrow().value.clone())
+}
+
+#[test]
+fn max_input_size_truncates_at_blank_line() {
+    let options = limited(24, OversizedInput::Truncate);
+    let input = "# Title\n\nSome text\nmore\n\nLast paragraph.\n";
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, input, &options);
+    assert_eq!(
+        last_value(root),
+        Some(NodeValue::Truncated(input.len() - 9))
+    );
+    assert!(root.validate().is_ok());
+
+    let mut html = vec![];
+    format_html(root, &options, &mut html).unwrap();
+    assert_eq!(String::from_utf8(html).unwrap(), "<h1>Title</h1>\n");
+}
+
+#[test]
+fn max_input_size_truncates_at_line_break() {
+    let options = limited(9, OversizedInput::Truncate);
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, "one\ntwo\nthree", &options);
+    assert_eq!(last_value(root), Some(NodeValue::Truncated(5)));
+
+    let truncated = root.last_child().unwrap().data.borrow().clone();
+    assert_eq!(truncated.sourcepos, (3, 1, 3, 0).into());
+    assert_eq!((truncated.start_offset, truncated.end_offset), (8, 8));
+
+    assert_eq!(
+        markdown_to_html("one\ntwo\nthree", &options),
+        "<p>one\ntwo</p>\n"
+    );
+}
+
+#[test]
+fn max_input_size_truncates_at_char_boundary() {
+    let options = limited(2, OversizedInput::Truncate);
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, "héllo", &options);
+    assert_eq!(last_value(root), Some(NodeValue::Truncated(5)));
+    assert_eq!(markdown_to_html("héllo", &options), "<p>h</p>\n");
+}
+
+#[test]
+fn max_input_size_not_exceeded() {
+    let options = limited(6, OversizedInput::Reject);
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, "Hello\n", &options);
+    assert_eq!(last_value(root), Some(NodeValue::Paragraph));
+    assert!(try_parse_document(&arena, "Hello\n", &options).is_ok());
+}
+
+#[test]
+fn max_input_size_rejects() {
+    let options = limited(4, OversizedInput::Reject);
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, "Hello\n", &options);
+    assert_eq!(root.children().count(), 1);
+    assert_eq!(last_value(root), Some(NodeValue::Truncated(6)));
+    assert_eq!(markdown_to_html("Hello\n", &options), "");
+
+    let err = try_parse_document(&arena, "Hello\n", &options).unwrap_err();
+    assert_eq!(err, InputTooLarge { size: 6, limit: 4 });
+    assert_eq!(
+        err.to_string(),
+        "input of 6 bytes exceeds the limit of 4 bytes"
+    );
+}
+
+#[test]
+fn max_input_size_xml() {
+    let options = limited(9, OversizedInput::Truncate);
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, "one\ntwo\nthree", &options);
+    let mut xml = vec![];
+    format_xml(root, &options, &mut xml).unwrap();
+    assert_eq!(
+        String::from_utf8(xml).unwrap(),
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE document SYSTEM \"CommonMark.dtd\">\n",
+            "<document xmlns=\"http://commonmark.org/xml/1.0\">\n",
+            "  <paragraph>\n",
+            "    <text xml:space=\"preserve\">one</text>\n",
+            "    <softbreak />\n",
+            "    <text xml:space=\"preserve\">two</text>\n",
+            "  </paragraph>\n",
+            "  <truncated omitted=\"5\" />\n",
+            "</document>\n",
+        )
+    );
+}
+
+#[test]
+fn max_input_size_streaming() {
+    let options = limited(12, OversizedInput::Truncate);
+
+    let arena = Arena::new();
+    let mut parser = StreamingParser::new(&arena, &options);
+    parser.feed("one\ntw");
+    parser.feed("o\nthree\nfour\n");
+    let root = parser.finish();
+    assert_eq!(last_value(root), Some(NodeValue::Truncated(11)));
+
+    let mut html = vec![];
+    format_html(root, &options, &mut html).unwrap();
+    assert_eq!(String::from_utf8(html).unwrap(), "<p>one\ntwo</p>\n");
+}
+
+#[test]
+fn max_input_size_streaming_drops_partial_line() {
+    let options = limited(8, OversizedInput::Truncate);
+
+    let arena = Arena::new();
+    let mut parser = StreamingParser::new(&arena, &options);
+    parser.feed("one\ntw");
+    parser.feed("o and more\n");
+    let root = parser.finish();
+    assert_eq!(last_value(root), Some(NodeValue::Truncated(13)));
+
+    let mut html = vec![];
+    format_html(root, &options, &mut html).unwrap();
+    assert_eq!(String::from_utf8(html).unwrap(), "<p>one</p>\n");
+}
+
+#[test]
+fn max_input_size_streaming_rejects() {
+    let options = limited(8, OversizedInput::Reject);
+
+    let arena = Arena::new();
+    let mut parser = StreamingParser::new(&arena, &options);
+    parser.feed("one\n\n");
+    parser.feed("two\nthree\n");
+    let root = parser.finish();
+    assert_eq!(root.children().count(), 1);
+    assert_eq!(last_value(root), Some(NodeValue::Truncated(15)));
+}
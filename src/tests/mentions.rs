@@ -0,0 +1,68 @@
+use super::*;
+
+fn mentions(options: &mut Options) {
+    options.extension.mentions = Some("https://github.com/{user}".to_string());
+}
+
+#[test]
+fn mentions_basic() {
+    html_opts_i(
+        "@octocat, cc @the-org-bot and (@a1).\n",
+        concat!(
+            "<p><a href=\"https://github.com/octocat\">@octocat</a>, ",
+            "cc <a href=\"https://github.com/the-org-bot\">@the-org-bot</a> ",
+            "and (<a href=\"https://github.com/a1\">@a1</a>).</p>\n"
+        ),
+        true,
+        mentions,
+    );
+}
+
+#[test]
+fn mentions_boundaries() {
+    html_opts_i(
+        concat!(
+            "me@example.com a_@b x.@c /@d @@e @-f @ g\n",
+            "@trailing- @",
+            "0123456789012345678901234567890123456789\n"
+        ),
+        concat!(
+            "<p>me@example.com a_@b x.@c /@d @@e @-f @ g\n",
+            "<a href=\"https://github.com/trailing\">@trailing</a>- ",
+            "@0123456789012345678901234567890123456789</p>\n"
+        ),
+        true,
+        mentions,
+    );
+}
+
+#[test]
+fn mentions_not_in_code_or_links() {
+    html_opts_i(
+        "`@code` [@text](/url) <https://x.org/@path> **@strong**\n",
+        concat!(
+            "<p><code>@code</code> <a href=\"/url\">@text</a> ",
+            "<a href=\"https://x.org/@path\">https://x.org/@path</a> ",
+            "<strong><a href=\"https://github.com/strong\">@strong</a></strong></p>\n"
+        ),
+        true,
+        mentions,
+    );
+}
+
+#[test]
+fn mentions_sourcepos() {
+    crate::tests::assert_ast_match_i(
+        "hi @bob!\n",
+        ast!((document (1:1-1:8) [
+            (paragraph (1:1-1:8) [
+                (text (1:1-1:3) "hi ")
+                (link (1:4-1:7) [
+                    (text (1:4-1:7) "@bob")
+                ])
+                (text (1:8-1:8) "!")
+            ])
+        ])),
+        |opts| opts.extension.mentions = Some("/u/{user}".to_string()),
+    );
+}
@@ -0,0 +1,120 @@
+use crate::merge::{merge_documents, MergeSource, ReferenceConflicts};
+
+use super::*;
+
+fn merged_html(sources: &[MergeSource], references: ReferenceConflicts) -> String {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.footnotes = true;
+    options.extension.front_matter_delimiter = Some("---".to_string());
+    let root = merge_documents(&arena, sources, &options, references);
+    let mut html = vec![];
+    format_html(root, &options, &mut html).unwrap();
+    String::from_utf8(html).unwrap()
+}
+
+fn reference_sources() -> Vec<MergeSource<'static>> {
+    vec![
+        MergeSource {
+            source: "[a] [b]\n\n[a]: /one-a\n",
+            ..Default::default()
+        },
+        MergeSource {
+            source: "[a] [b] [c]\n\n[a]: /two-a\n[b]: /two-b\n",
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn references_first() {
+    assert_eq!(
+        merged_html(&reference_sources(), ReferenceConflicts::First),
+        concat!(
+            "<p><a href=\"/one-a\">a</a> <a href=\"/two-b\">b</a></p>\n",
+            "<p><a href=\"/one-a\">a</a> <a href=\"/two-b\">b</a> [c]</p>\n",
+        )
+    );
+}
+
+#[test]
+fn references_local() {
+    assert_eq!(
+        merged_html(&reference_sources(), ReferenceConflicts::Local),
+        concat!(
+            "<p><a href=\"/one-a\">a</a> <a href=\"/two-b\">b</a></p>\n",
+            "<p><a href=\"/two-a\">a</a> <a href=\"/two-b\">b</a> [c]</p>\n",
+        )
+    );
+}
+
+#[test]
+fn references_isolated() {
+    assert_eq!(
+        merged_html(&reference_sources(), ReferenceConflicts::Isolated),
+        concat!(
+            "<p><a href=\"/one-a\">a</a> [b]</p>\n",
+            "<p><a href=\"/two-a\">a</a> <a href=\"/two-b\">b</a> [c]</p>\n",
+        )
+    );
+}
+
+#[test]
+fn headings_footnotes_and_front_matter() {
+    let sources = [
+        MergeSource {
+            source: "---\ntitle: Book\n---\n# Book\n\nIntro[^n].\n\n[^n]: One.\n",
+            footnote_prefix: Some("intro-".to_string()),
+            ..Default::default()
+        },
+        MergeSource {
+            source: "---\ntitle: Chapter\n---\nChapter\n=======\n\n##### Deep[^n]\n\n[^n]: Two.\n",
+            demote_headings: 2,
+            footnote_prefix: Some("ch1-".to_string()),
+        },
+    ];
+    assert_eq!(
+        merged_html(&sources, ReferenceConflicts::First),
+        concat!(
+            "<h1>Book</h1>\n",
+            "<p>Intro<sup class=\"footnote-ref\"><a href=\"#fn-intro-n\" id=\"fnref-intro-n\" data-footnote-ref>1</a></sup>.</p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-intro-n\">\n",
+            "<p>One. <a href=\"#fnref-intro-n\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+            "<h3>Chapter</h3>\n",
+            "<h6>Deep<sup class=\"footnote-ref\"><a href=\"#fn-ch1-n\" id=\"fnref-ch1-n\" data-footnote-ref>2</a></sup></h6>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol start=\"2\">\n",
+            "<li id=\"fn-ch1-n\">\n",
+            "<p>Two. <a href=\"#fnref-ch1-n\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"2\" aria-label=\"Back to reference 2\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+        )
+    );
+
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.front_matter_delimiter = Some("---".to_string());
+    let root = merge_documents(&arena, &sources, &options, ReferenceConflicts::First);
+    let front_matter: Vec<String> = root
+        .children()
+        .filter_map(|n| match n.data.borrow().value {
+            NodeValue::FrontMatter(ref fm) => Some(fm.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(front_matter, vec!["---\ntitle: Book\n---\n".to_string()]);
+    let headings: Vec<(u8, bool)> = root
+        .children()
+        .filter_map(|n| match n.data.borrow().value {
+            NodeValue::Heading(ref nh) => Some((nh.level, nh.setext)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(headings, vec![(1, false), (3, false), (6, false)]);
+}
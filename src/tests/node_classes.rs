@@ -0,0 +1,92 @@
+use super::*;
+
+fn classes(options: &mut Options) {
+    options.extension.table = true;
+    options.extension.spoiler = true;
+    let node_classes = &mut options.render.node_classes;
+    node_classes.insert("table".into(), "table table-striped".into());
+    node_classes.insert("block_quote".into(), "quote".into());
+    node_classes.insert("code_block".into(), "code".into());
+    node_classes.insert("link".into(), " underline ".into());
+    node_classes.insert("spoiler".into(), "blur".into());
+    node_classes.insert("paragraph".into(), "".into());
+}
+
+#[test]
+fn node_classes() {
+    html_opts_i(
+        concat!(
+            "| a |\n",
+            "|---|\n",
+            "| b |\n",
+            "\n",
+            "> [link](/url) ||hidden||\n",
+            "\n",
+            "```rust\n",
+            "fn main() {}\n",
+            "```\n",
+        ),
+        concat!(
+            "<table class=\"table table-striped\">\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>b</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n",
+            "<blockquote class=\"quote\">\n",
+            "<p><a class=\"underline\" href=\"/url\">link</a> <span class=\"spoiler blur\">hidden</span></p>\n",
+            "</blockquote>\n",
+            "<pre class=\"code\"><code class=\"language-rust\">fn main() {}\n",
+            "</code></pre>\n",
+        ),
+        false,
+        classes,
+    );
+}
+
+#[test]
+fn node_classes_with_sourcepos() {
+    html_opts_i(
+        "# Title\n",
+        "<h1 class=\"title\" data-sourcepos=\"1:1-1:7\">Title</h1>\n",
+        false,
+        |options| {
+            options.render.sourcepos = true;
+            options
+                .render
+                .node_classes
+                .insert("heading".into(), "title".into());
+        },
+    );
+}
+
+#[test]
+fn node_classes_footnote_reference() {
+    html_opts_i(
+        "a[^1]\n\n[^1]: b\n",
+        concat!(
+            "<p>a<sup class=\"footnote-ref fr\"><a href=\"#fn-1\" id=\"fnref-1\" data-footnote-ref>1</a></sup></p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-1\">\n",
+            "<p>b <a href=\"#fnref-1\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+        ),
+        false,
+        |options| {
+            options.extension.footnotes = true;
+            options
+                .render
+                .node_classes
+                .insert("footnote_reference".into(), "fr".into());
+        },
+    );
+}
@@ -0,0 +1,64 @@
+use super::*;
+
+// The source of each node, as sliced by its byte offsets, with its type.
+fn sources<'a>(input: &'a str, options: &Options) -> Vec<(&'static str, &'a str)> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, input, options);
+    root.descendants()
+        .map(|node| {
+            let ast = node.data.borrow();
+            (
+                ast.value.xml_node_name(),
+                &input[ast.start_offset..ast.end_offset],
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn offsets() {
+    let input = "# Héllo *wörld*\r\n\r\n> - `a`\r\n>   b\r\n\r\n\tcode\n";
+    assert_eq!(
+        sources(input, &Options::default()),
+        [
+            ("document", input.trim_end()),
+            ("heading", "# Héllo *wörld*"),
+            ("text", "Héllo "),
+            ("emph", "*wörld*"),
+            ("text", "wörld"),
+            ("block_quote", "> - `a`\r\n>   b"),
+            ("list", "- `a`\r\n>   b"),
+            ("item", "- `a`\r\n>   b"),
+            ("paragraph", "`a`\r\n>   b"),
+            ("code", "a"),
+            ("softbreak", "\r"),
+            ("text", "b"),
+            ("code_block", "code"),
+        ]
+    );
+}
+
+#[test]
+fn offsets_after_front_matter() {
+    let mut options = Options::default();
+    options.extension.front_matter_delimiter = Some("---".into());
+    let input = "---\ntitle: x\n---\n\nSome *text*\n";
+    assert_eq!(
+        sources(input, &options),
+        [
+            ("document", "Some *text*"),
+            ("frontmatter", "---\ntitle: x\n---\n\n"),
+            ("paragraph", "Some *text*"),
+            ("text", "Some "),
+            ("emph", "*text*"),
+            ("text", "text"),
+        ]
+    );
+}
+
+#[test]
+fn offsets_of_new_nodes() {
+    let node = AstNode::from(NodeValue::Paragraph);
+    let ast = node.data.borrow();
+    assert_eq!((ast.start_offset, ast.end_offset), (0, 0));
+}
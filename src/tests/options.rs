@@ -50,6 +50,77 @@ fn width_breaks() {
     commonmark(input, output, Some(&options));
 }
 
+#[test]
+fn soft_breaks() {
+    let input = "a\nb *c\nd*\n";
+    html_opts_i(input, "<p>a\nb <em>c\nd</em></p>\n", true, |_| {});
+    html_opts_i(input, "<p>a b <em>c d</em></p>\n", true, |opts| {
+        opts.render.soft_breaks = SoftBreakStyle::Space
+    });
+    html_opts_i(input, "<p>a<br />b <em>c<br />d</em></p>\n", true, |opts| {
+        opts.render.soft_breaks = SoftBreakStyle::LineBreak
+    });
+    html_opts_i(
+        input,
+        "<p>a<br />\nb <em>c<br />\nd</em></p>\n",
+        true,
+        |opts| {
+            opts.render.soft_breaks = SoftBreakStyle::Space;
+            opts.render.hardbreaks = true;
+        },
+    );
+}
+
+#[test]
+fn cjk_soft_breaks() {
+    let input = "日本語の\n*文章*です。\n漢字\nand\n한국어\n문장\n";
+    html_opts_i(
+        input,
+        "<p>日本語の\n<em>文章</em>です。\n漢字\nand\n한국어\n문장</p>\n",
+        true,
+        |_| {},
+    );
+    html_opts_i(
+        input,
+        "<p>日本語の<em>文章</em>です。漢字\nand\n한국어\n문장</p>\n",
+        true,
+        |opts| opts.render.cjk_soft_breaks = true,
+    );
+    html_opts_i(
+        input,
+        "<p>日本語の<em>文章</em>です。漢字 and 한국어 문장</p>\n",
+        true,
+        |opts| {
+            opts.render.cjk_soft_breaks = true;
+            opts.render.soft_breaks = SoftBreakStyle::Space;
+        },
+    );
+    html_opts_i(
+        "中文\n中文\n",
+        "<p>中文<br />\n中文</p>\n",
+        true,
+        |opts| {
+            opts.render.cjk_soft_breaks = true;
+            opts.render.hardbreaks = true;
+        },
+    );
+}
+
+#[test]
+fn cjk_soft_breaks_wrapping() {
+    let mut options = Options::default();
+    options.render.width = 80;
+    let input = "日本語の\n文章です。\nThen\nEnglish.\n";
+    commonmark(input, "日本語の 文章です。 Then English.\n", Some(&options));
+
+    options.render.cjk_soft_breaks = true;
+    commonmark(
+        input,
+        "日本語の\n文章です。 Then English.\n",
+        Some(&options),
+    );
+}
+
 #[test]
 fn smart_chars() {
     html_opts!(
@@ -65,6 +136,32 @@ fn smart_chars() {
     );
 }
 
+#[test]
+fn smart_quotes() {
+    let input = "\"Hi,\" it's 'quiet' \"'here'\".\n";
+    html_opts_i(
+        input,
+        "<p>„Hi,“ it’s ‚quiet‘ „‚here‘“.</p>\n",
+        true,
+        |opts| {
+            opts.parse.smart = true;
+            opts.parse.smart_quotes = SmartQuotes::german();
+        },
+    );
+    html_opts_i(
+        input,
+        "<p>«Hi,» it's ‹quiet› «‹here›».</p>\n",
+        true,
+        |opts| {
+            opts.parse.smart = true;
+            opts.parse.smart_quotes = SmartQuotes {
+                apostrophe: "'".into(),
+                ..SmartQuotes::french()
+            };
+        },
+    );
+}
+
 #[test]
 fn broken_link_callback() {
     let arena = Arena::new();
@@ -99,3 +196,98 @@ fn broken_link_callback() {
         A [broken link] renders as text.</p>\n"
     );
 }
+
+#[test]
+fn non_ascii() {
+    let input = "# Süß\n\n``` 日本\nñ\n```\n\n[«x»](/é \"ü\")\n";
+    html_opts_i(
+        input,
+        concat!(
+            "<h1>Süß</h1>\n",
+            "<pre><code class=\"language-日本\">ñ\n</code></pre>\n",
+            "<p><a href=\"/%C3%A9\" title=\"ü\">«x»</a></p>\n",
+        ),
+        true,
+        |_| {},
+    );
+    html_opts_i(
+        input,
+        concat!(
+            "<h1>S&#xFC;&#xDF;</h1>\n",
+            "<pre><code class=\"language-&#x65E5;&#x672C;\">&#xF1;\n</code></pre>\n",
+            "<p><a href=\"/%C3%A9\" title=\"&#xFC;\">&#xAB;x&#xBB;</a></p>\n",
+        ),
+        true,
+        |opts| opts.render.non_ascii = NonAsciiStyle::Numeric,
+    );
+    html_opts_i(
+        input,
+        concat!(
+            "<h1>S&uuml;&szlig;</h1>\n",
+            "<pre><code class=\"language-&#x65E5;&#x672C;\">&ntilde;\n</code></pre>\n",
+            "<p><a href=\"/%C3%A9\" title=\"&uuml;\">&laquo;x&raquo;</a></p>\n",
+        ),
+        true,
+        |opts| opts.render.non_ascii = NonAsciiStyle::Named,
+    );
+    html_opts_i(
+        "# Süß\n",
+        "<h1><a href=\"#s&#xFC;&#xDF;\" aria-hidden=\"true\" class=\"anchor\" id=\"s&#xFC;&#xDF;\"></a>S&#xFC;&#xDF;</h1>\n",
+        true,
+        |opts| {
+            opts.extension.header_ids = Some("".to_string());
+            opts.render.non_ascii = NonAsciiStyle::Numeric;
+        },
+    );
+}
+
+#[test]
+fn link_titles() {
+    let input = "[a](/b \"c\") ![d](/e.png \"f\")\n";
+    html_opts_i(
+        input,
+        "<p><a href=\"/b\" aria-label=\"c\">a</a> <img src=\"/e.png\" alt=\"d\" aria-label=\"f\" /></p>\n",
+        false,
+        |opts| opts.render.link_titles = LinkTitleStyle::AriaLabel,
+    );
+    html_opts_i(
+        input,
+        "<p><a href=\"/b\">a</a> <img src=\"/e.png\" alt=\"d\" /></p>\n",
+        false,
+        |opts| opts.render.link_titles = LinkTitleStyle::Omit,
+    );
+    html_opts_i(
+        input,
+        "<p><a href=\"/b\">a</a> <figure><img src=\"/e.png\" alt=\"d\" /><figcaption>f</figcaption></figure></p>\n",
+        false,
+        |opts| {
+            opts.render.link_titles = LinkTitleStyle::Omit;
+            opts.render.figure_with_caption = true;
+        },
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn options_from_json() {
+    let options: Options = serde_json::from_str(
+        r#"{
+            "extension": {"table": true, "header_ids": "h-"},
+            "parse": {"smart": true},
+            "render": {"unsafe": true, "list_style": "star", "soft_breaks": "line_break"}
+        }"#,
+    )
+    .unwrap();
+    assert!(options.extension.table);
+    assert!(!options.extension.strikethrough);
+    assert_eq!(options.extension.header_ids.as_deref(), Some("h-"));
+    assert!(options.parse.smart);
+    assert!(options.render.unsafe_);
+    assert!(matches!(options.render.list_style, ListStyleType::Star));
+    assert_eq!(options.render.soft_breaks, SoftBreakStyle::LineBreak);
+    assert_eq!(options.render.strings, RenderStrings::default());
+
+    let options: Options = serde_json::from_str("{}").unwrap();
+    assert!(!options.render.unsafe_);
+    assert!(serde_json::from_str::<Options>(r#"{"render": {"list_style": "bullet"}}"#).is_err());
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use std::thread;
+
+use crate::owned::OwnedNode;
+
+use super::*;
+
+#[test]
+fn owned_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<OwnedNode>();
+}
+
+#[test]
+fn owned_roundtrip() {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.tasklist = true;
+    options.extension.footnotes = true;
+    options.render.sourcepos = true;
+
+    let input = concat!(
+        "# Title\n",
+        "\n",
+        "- [x] done\n",
+        "- [ ] *not* [yet](/y \"t\")\n",
+        "\n",
+        "| a | b |\n",
+        "|:--|--:|\n",
+        "| c | `d` |\n",
+        "\n",
+        "Note[^1].\n",
+        "\n",
+        "[^1]: Here.\n",
+    );
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, input, &options);
+    let mut expected = vec![];
+    format_html(root, &options, &mut expected).unwrap();
+    let expected = String::from_utf8(expected).unwrap();
+
+    let tree = Arc::new(OwnedNode::from_node(root));
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            let tree = Arc::clone(&tree);
+            let options = options.clone();
+            thread::spawn(move || {
+                let arena = Arena::new();
+                let mut html = vec![];
+                format_html(tree.to_node(&arena), &options, &mut html).unwrap();
+                String::from_utf8(html).unwrap()
+            })
+        })
+        .collect();
+    for handle in handles {
+        compare_strs(&handle.join().unwrap(), &expected, "owned", input);
+    }
+
+    let arena = Arena::new();
+    let copy = Arc::try_unwrap(tree).unwrap().into_node(&arena);
+    assert_eq!(OwnedNode::from_node(copy), OwnedNode::from_node(root));
+}
+
+#[test]
+fn owned_descendants() {
+    let arena = Arena::new();
+    let root = parse_document(&arena, "> a *b*\n\nc\n", &Options::default());
+    let tree = OwnedNode::from_node(root);
+
+    let names: Vec<_> = tree
+        .descendants()
+        .map(|node| node.ast.value.xml_node_name())
+        .collect();
+    let expected: Vec<_> = root
+        .descendants()
+        .map(|node| node.data.borrow().value.xml_node_name())
+        .collect();
+    assert_eq!(names, expected);
+    assert_eq!(names[0], "document");
+    assert_eq!(names.len(), 8);
+}
@@ -0,0 +1,121 @@
+use super::*;
+
+#[track_caller]
+fn parallel_matches_sequential(input: &str, options: &Options, plugins: &Plugins) {
+    let arena = Arena::new();
+    let root = parse_document(&arena, input, options);
+
+    let mut sequential = vec![];
+    html::format_document_with_plugins(root, options, &mut sequential, plugins).unwrap();
+    let mut parallel = vec![];
+    html::format_document_parallel(root, options, &mut parallel, plugins).unwrap();
+
+    // The blocks rendered in parallel are put back where they were.
+    assert!(root.validate().is_ok());
+    let mut again = vec![];
+    html::format_document_with_plugins(root, options, &mut again, plugins).unwrap();
+    assert_eq!(again, sequential);
+
+    compare_strs(
+        &String::from_utf8(parallel).unwrap(),
+        &String::from_utf8(sequential).unwrap(),
+        "parallel",
+        input,
+    );
+}
+
+#[test]
+fn parallel_rendering() {
+    let mut input = String::new();
+    for i in 0..50 {
+        input.push_str(&format!(
+            "# Chapter {}\n\nSome *text* with a [link][ref].\n\n- a\n- b\n\n  c\n\n> quote\n\n```rust\nfn f() {{}}\n```\n\n| a | b |\n|---|---|\n| {} | x |\n\n<div>\nhtml\n</div>\n\n",
+            i, i
+        ));
+    }
+    input.push_str("[ref]: https://example.com\n");
+
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.render.unsafe_ = true;
+    parallel_matches_sequential(&input, &options, &Plugins::default());
+
+    options.render.sourcepos = true;
+    parallel_matches_sequential(&input, &options, &Plugins::default());
+}
+
+#[test]
+fn parallel_rendering_abbreviations() {
+    let input = "*[HTML]: Hyper Text Markup Language\n\nHTML one.\n\nHTML two.\n";
+
+    let mut options = Options::default();
+    options.extension.abbreviations = true;
+    parallel_matches_sequential(input, &options, &Plugins::default());
+}
+
+#[test]
+fn parallel_rendering_falls_back() {
+    let input = "# Dup\n\n# Dup\n\nA[^1] B[^2]\n\n[^2]: two\n[^1]: one\n";
+
+    let mut options = Options::default();
+    options.extension.header_ids = Some("".to_string());
+    options.extension.footnotes = true;
+    parallel_matches_sequential(input, &options, &Plugins::default());
+}
+
+#[test]
+fn parallel_rendering_without_trailing_newline() {
+    use crate::adapters::{HeadingAdapter, HeadingMeta};
+
+    struct NoNewline;
+
+    impl HeadingAdapter for NoNewline {
+        fn enter(
+            &self,
+            output: &mut dyn Write,
+            _heading: &HeadingMeta,
+            _sourcepos: Option<Sourcepos>,
+        ) -> io::Result<()> {
+            output.write_all(b"<h>")
+        }
+
+        fn exit(&self, output: &mut dyn Write, _heading: &HeadingMeta) -> io::Result<()> {
+            output.write_all(b"</h>")
+        }
+    }
+
+    let adapter = NoNewline;
+    let mut plugins = Plugins::default();
+    plugins.render.heading_adapter = Some(&adapter);
+
+    parallel_matches_sequential("# one\n\n# two\n\npara\n", &Options::default(), &plugins);
+}
+
+#[test]
+fn render_many() {
+    let documents: Vec<String> = (0..200)
+        .map(|i| {
+            format!(
+                "# Page {}\n\n{}\n\n| a | b |\n|---|---|\n| {} | ~~x~~ |\n",
+                i,
+                "word ".repeat(i % 17),
+                i
+            )
+        })
+        .collect();
+
+    let options = Options::gfm();
+    let plugins = Plugins::default();
+    let html = markdown_to_html_many(&documents, &options, &plugins);
+
+    assert_eq!(html.len(), documents.len());
+    for (md, html) in documents.iter().zip(&html) {
+        compare_strs(
+            html,
+            &markdown_to_html_with_plugins(md, &options, &plugins),
+            "many",
+            md,
+        );
+    }
+    assert!(markdown_to_html_many::<&str>(&[], &options, &plugins).is_empty());
+}
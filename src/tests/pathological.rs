@@ -53,6 +53,7 @@ fn pathological_table_columns_2() {
             extension,
             parse: Default::default(),
             render: RenderOptions::default(),
+            extensions: Default::default(),
         },
     );
 }
@@ -67,3 +68,140 @@ fn pathological_footnotes() {
 
     html_opts!([extension.footnotes], &input, &exp);
 }
+
+// input: python3 -c 'n = 100000; print(">" * n + " a")'
+#[test]
+#[timeout(4000)]
+fn pathological_block_quotes() {
+    let n = 100_000;
+    let input = format!("{} a\n", ">".repeat(n));
+    let options = Options::default();
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, &input, &options);
+    assert_eq!(root.descendants().count(), 256 + 3);
+
+    format_all(root, &options);
+}
+
+// input: python3 -c 'n = 100000; print("*" * n + "a" + "*" * n)'
+#[test]
+#[timeout(8000)]
+fn pathological_inline_nesting() {
+    let n = 100_000;
+    let input = format!("{}a{}", "*".repeat(n), "*".repeat(n));
+    let options = Options::default();
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, &input, &options);
+    assert_eq!(root.descendants().count(), 256 + 3);
+
+    format_all(root, &options);
+}
+
+// Formatting a deeply nested tree mustn't overflow the stack.
+fn format_all<'a>(root: &'a AstNode<'a>, options: &Options) {
+    type Formatter = for<'a> fn(&'a AstNode<'a>, &Options, &mut dyn Write) -> io::Result<()>;
+    let formatters: [Formatter; 9] = [
+        format_html,
+        format_xml,
+        format_djot,
+        format_gemtext,
+        format_latex,
+        format_slack,
+        format_text,
+        format_typst,
+        format_commonmark,
+    ];
+    for format in formatters {
+        let mut output = vec![];
+        format(root, options, &mut output).unwrap();
+    }
+}
+
+#[test]
+fn max_nesting() {
+    let mut options = Options::default();
+    options.parse.max_nesting = 3;
+    assert_eq!(
+        markdown_to_html("- > - > a\n", &options),
+        concat!(
+            "<ul>\n<li>\n<blockquote>\n<ul>\n<li>&gt; a</li>\n</ul>\n",
+            "</blockquote>\n</li>\n</ul>\n",
+        )
+    );
+}
+
+#[test]
+fn max_nesting_inlines() {
+    let mut options = Options::default();
+    options.parse.max_nesting = 2;
+    assert_eq!(
+        markdown_to_html("> *a [b *c* d](u) e*\n", &options),
+        "<blockquote>\n<p><em>a <a href=\"u\">b c d</a> e</em></p>\n</blockquote>\n"
+    );
+}
+
+// input: python3 -c 'n = 50000; print("[a" * n)'
+#[test]
+#[timeout(4000)]
+fn pathological_citation_openers() {
+    let n = 50_000;
+    let input = "[a".repeat(n);
+    let exp = format!("<p>{}</p>\n", input);
+
+    html_opts!([extension.citations], &input, &exp, no_roundtrip);
+}
+
+// input: python3 -c 'n = 50000; print("{{a " * n)'
+#[test]
+#[timeout(4000)]
+fn pathological_template_placeholders() {
+    let n = 50_000;
+    let input = "{{a ".repeat(n);
+    let exp = format!("<p>{}</p>\n", input.trim_end());
+
+    html_opts!([extension.template_variables], &input, &exp, no_roundtrip);
+}
+
+// input: python3 -c 'n = 50000; print("[a]: u\n" * n)'
+#[test]
+#[timeout(4000)]
+fn pathological_reference_definitions() {
+    let n = 50_000;
+    let input = "[a]: u\n".repeat(n);
+
+    html(&input, "");
+}
+
+// input: python3 -c 'n = 30000; print("|" + "x|" * n + "\n|" + "-|" * n)'
+#[test]
+#[timeout(4000)]
+fn pathological_table_cell_alignments() {
+    let n = 30_000;
+    let input = format!("|{}\n|{}\n", "x|".repeat(n), ":-|".repeat(n));
+
+    let mut options = Options::default();
+    options.extension.table = true;
+    let html = markdown_to_html(&input, &options);
+    assert_eq!(html.matches(" align=\"left\"").count(), n);
+    let xml = markdown_to_commonmark_xml(&input, &options);
+    assert_eq!(xml.matches(" align=\"left\"").count(), n);
+}
+
+// input: python3 -c 'n = 30000; print("*a **a " * n + "b" + " a** a*" * n)'
+#[test]
+#[timeout(4000)]
+fn pathological_nested_emphasis_postprocessing() {
+    let n = 30_000;
+    let input = format!("{}b{}", "*a **a ".repeat(n), " a** a*".repeat(n));
+
+    let mut options = Options::default();
+    options.extension.footnotes = true;
+    options.extension.conditional_blocks = true;
+    let arena = Arena::new();
+    let root = parse_document(&arena, &input, &options);
+    crate::conditionals::resolve_conditionals(root, |_| true);
+    let mut output = vec![];
+    format_html(root, &options, &mut output).unwrap();
+}
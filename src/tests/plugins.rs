@@ -1,6 +1,10 @@
 use crate::{
-    adapters::{HeadingAdapter, HeadingMeta, SyntaxHighlighterAdapter},
+    adapters::{
+        HeadingAdapter, HeadingMeta, ImageDimensionAdapter, ImageDimensions, ImageMeta,
+        LinkTitleAdapter, SyntaxHighlighterAdapter, TableCellAdapter, TableCellMeta,
+    },
     nodes::Sourcepos,
+    plugins::embed::{data_uri, FileImageEmbedder},
 };
 
 use super::*;
@@ -49,6 +53,75 @@ fn syntax_highlighter_plugin() {
     html_plugins(input, expected, &plugins);
 }
 
+#[test]
+fn syntax_highlighter_info_string() {
+    // Writes attributes in order, so the output is stable.
+    pub struct SortingAdapter {}
+
+    impl SyntaxHighlighterAdapter for SortingAdapter {
+        fn write_highlighted(
+            &self,
+            output: &mut dyn Write,
+            _lang: Option<&str>,
+            code: &str,
+        ) -> io::Result<()> {
+            output.write_all(code.as_bytes())
+        }
+
+        fn write_pre_tag(
+            &self,
+            output: &mut dyn Write,
+            attributes: HashMap<String, String>,
+        ) -> io::Result<()> {
+            let attributes: std::collections::BTreeMap<_, _> = attributes.into_iter().collect();
+            html::write_opening_tag(output, "pre", attributes)
+        }
+
+        fn write_code_tag(
+            &self,
+            output: &mut dyn Write,
+            attributes: HashMap<String, String>,
+        ) -> io::Result<()> {
+            let attributes: std::collections::BTreeMap<_, _> = attributes.into_iter().collect();
+            html::write_opening_tag(output, "code", attributes)
+        }
+    }
+
+    let adapter = SortingAdapter {};
+    let mut plugins = Plugins::default();
+    plugins.render.codefence_syntax_highlighter = Some(&adapter);
+
+    let render = |github_pre_lang: bool| {
+        let arena = Arena::new();
+        let mut options = Options::default();
+        options.render.full_info_string = true;
+        options.render.github_pre_lang = github_pre_lang;
+        let root = parse_document(
+            &arena,
+            "``` rust,no_run {linenos}\nx\n```\n\n```\ny\n```\n",
+            &options,
+        );
+        let mut output = vec![];
+        html::format_document_with_plugins(root, &options, &mut output, &plugins).unwrap();
+        String::from_utf8(output).unwrap()
+    };
+
+    assert_eq!(
+        render(false),
+        concat!(
+            "<pre><code class=\"language-rust,no_run\" data-meta=\"{linenos}\">x\n</code></pre>\n",
+            "<pre><code>y\n</code></pre>\n"
+        )
+    );
+    assert_eq!(
+        render(true),
+        concat!(
+            "<pre data-meta=\"{linenos}\" lang=\"rust,no_run\"><code>x\n</code></pre>\n",
+            "<pre><code>y\n</code></pre>\n"
+        )
+    );
+}
+
 #[test]
 fn heading_adapter_plugin() {
     struct MockAdapter;
@@ -86,6 +159,264 @@ fn heading_adapter_plugin() {
     }
 }
 
+#[test]
+fn table_cell_adapter_plugin() {
+    struct MockAdapter;
+
+    impl TableCellAdapter for MockAdapter {
+        fn enter(
+            &self,
+            output: &mut dyn Write,
+            cell: &TableCellMeta,
+            sourcepos: Option<Sourcepos>,
+        ) -> io::Result<()> {
+            let tag = if cell.header { "th" } else { "td" };
+            write!(
+                output,
+                "<{} class=\"col-{} {:?}\" data-span=\"{}\"",
+                tag, cell.column, cell.alignment, cell.colspan
+            )?;
+            if let Some(sourcepos) = sourcepos {
+                write!(output, " data-sourcepos=\"{}\"", sourcepos)?;
+            }
+            output.write_all(b">")
+        }
+
+        fn exit(&self, output: &mut dyn Write, cell: &TableCellMeta) -> io::Result<()> {
+            write!(output, "</{}>", if cell.header { "th" } else { "td" })
+        }
+    }
+
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.table_colspans = true;
+    let mut plugins = Plugins::default();
+    let adapter = MockAdapter {};
+    plugins.render.table_cell_adapter = Some(&adapter);
+
+    let input = "| a | b | c |\n|---|--:|---|\n| *d* || e |\n";
+    assert_eq!(
+        markdown_to_html_with_plugins(input, &options, &plugins),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th class=\"col-0 None\" data-span=\"1\">a</th>\n",
+            "<th class=\"col-1 Right\" data-span=\"1\">b</th>\n",
+            "<th class=\"col-2 None\" data-span=\"1\">c</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td class=\"col-0 None\" data-span=\"2\"><em>d</em></td>\n",
+            "<td class=\"col-2 None\" data-span=\"1\">e</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        )
+    );
+
+    options.render.sourcepos = true;
+    let html = markdown_to_html_with_plugins(input, &options, &plugins);
+    assert!(html
+        .contains("<td class=\"col-2 None\" data-span=\"1\" data-sourcepos=\"3:9-3:11\">e</td>"));
+}
+
+#[test]
+fn link_title_adapter_plugin() {
+    struct MockAdapter;
+
+    impl LinkTitleAdapter for MockAdapter {
+        fn transform(&self, title: &str, url: &str) -> String {
+            if url.starts_with("/drop") {
+                String::new()
+            } else {
+                format!("{} ({})", title.trim(), url)
+            }
+        }
+    }
+
+    let mut plugins = Plugins::default();
+    let adapter = MockAdapter;
+    plugins.render.link_title_adapter = Some(&adapter);
+
+    html_plugins(
+        concat!(
+            "[a](/x \" one \") [b](/drop \"two\") [c](/y)\n",
+            "\n",
+            "![d](/z.png \"three\")\n",
+        ),
+        concat!(
+            "<p><a href=\"/x\" title=\"one (/x)\">a</a> <a href=\"/drop\">b</a> <a href=\"/y\">c</a></p>\n",
+            "<p><img src=\"/z.png\" alt=\"d\" title=\"three (/z.png)\" /></p>\n",
+        ),
+        &plugins,
+    );
+}
+
+#[test]
+fn url_rewriter_plugins() {
+    let links = |url: &str| url.replace("/docs/", "https://docs.example.com/");
+    let images = |url: &str| match url {
+        "/evil.png" => "javascript:alert(1)".to_string(),
+        _ => format!("https://cdn.example.com{}", url),
+    };
+
+    let mut plugins = Plugins::default();
+    plugins.render.link_url_rewriter = Some(&links);
+    plugins.render.image_url_rewriter = Some(&images);
+
+    html_plugins(
+        concat!(
+            "[a](/docs/a \"A\") [![b](/b.png)](/docs/b) <http://x.org/docs/>\n",
+            "\n",
+            "![c](/evil.png)\n",
+        ),
+        concat!(
+            "<p><a href=\"https://docs.example.com/a\" title=\"A\">a</a> \
+             <a href=\"https://docs.example.com/b\"><img src=\"https://cdn.example.com/b.png\" alt=\"b\" /></a> \
+             <a href=\"http://x.orghttps://docs.example.com/\">http://x.org/docs/</a></p>\n",
+            "<p><img src=\"\" alt=\"c\" /></p>\n",
+        ),
+        &plugins,
+    );
+}
+
+#[test]
+fn image_resolver_plugin() {
+    let resolver = |image: &ImageMeta| match image.url.as_str() {
+        "/a.png" => Some(format!("/{}/{}.png", image.alt, image.title)),
+        "/evil.png" => Some("data:image/svg+xml,<svg/>".to_string()),
+        _ => None,
+    };
+    let images = |url: &str| format!("https://cdn.example.com{}", url);
+
+    let mut plugins = Plugins::default();
+    plugins.render.image_resolver = Some(&resolver);
+    plugins.render.image_url_rewriter = Some(&images);
+
+    html_plugins(
+        "![*a* `b`](/a.png \"t\") ![c](/c.png) ![d](/evil.png)\n",
+        "<p><img src=\"/a%20b/t.png\" alt=\"a b\" title=\"t\" /> \
+         <img src=\"https://cdn.example.com/c.png\" alt=\"c\" /> <img src=\"\" alt=\"d\" /></p>\n",
+        &plugins,
+    );
+}
+
+#[test]
+fn file_image_embedder_plugin() {
+    let root = std::env::temp_dir().join(format!("comrak-embed-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("img dir")).unwrap();
+    std::fs::write(root.join("img dir/a.png"), b"PNG").unwrap();
+    std::fs::write(root.join("b.gif"), b"GIF89a").unwrap();
+    std::fs::write(root.join("c.txt"), b"text").unwrap();
+
+    let embedder = FileImageEmbedder::new(&root);
+    let mut plugins = Plugins::default();
+    plugins.render.image_resolver = Some(&embedder);
+    let input = "![a](img%20dir/a.png?v=1) ![b](b.gif) ![c](c.txt) ![d](missing.png) \
+                 ![e](https://example.com/e.png) ![f](/b.gif)\n";
+    html_plugins(
+        input,
+        "<p><img src=\"data:image/png;base64,UE5H\" alt=\"a\" /> \
+         <img src=\"data:image/gif;base64,R0lGODlh\" alt=\"b\" /> <img src=\"c.txt\" alt=\"c\" /> \
+         <img src=\"missing.png\" alt=\"d\" /> <img src=\"https://example.com/e.png\" alt=\"e\" /> \
+         <img src=\"/b.gif\" alt=\"f\" /></p>\n",
+        &plugins,
+    );
+
+    let small = FileImageEmbedder {
+        max_size: Some(4),
+        ..embedder.clone()
+    };
+    plugins.render.image_resolver = Some(&small);
+    html_plugins(
+        "![a](img%20dir/a.png) ![b](b.gif)\n",
+        "<p><img src=\"data:image/png;base64,UE5H\" alt=\"a\" /> <img src=\"b.gif\" alt=\"b\" /></p>\n",
+        &plugins,
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn file_image_embedder_traversal() {
+    let dir = std::env::temp_dir().join(format!("comrak-embed-traversal-{}", std::process::id()));
+    let root = dir.join("root");
+    std::fs::create_dir_all(root.join("img")).unwrap();
+    std::fs::write(dir.join("secret.png"), b"PNG").unwrap();
+    std::fs::write(root.join("a.png"), b"PNG").unwrap();
+
+    let embedder = FileImageEmbedder::new(&root);
+    let mut plugins = Plugins::default();
+    plugins.render.image_resolver = Some(&embedder);
+    html_plugins(
+        "![a](../secret.png) ![b](img/../../secret.png) ![c](..%2Fsecret.png) \
+         ![d](%2E%2E/secret.png) ![e](img/%2E%2E/a.png) ![f](./a.png)\n",
+        "<p><img src=\"../secret.png\" alt=\"a\" /> <img src=\"img/../../secret.png\" alt=\"b\" /> \
+         <img src=\"..%2Fsecret.png\" alt=\"c\" /> <img src=\"%2E%2E/secret.png\" alt=\"d\" /> \
+         <img src=\"img/%2E%2E/a.png\" alt=\"e\" /> <img src=\"data:image/png;base64,UE5H\" alt=\"f\" /></p>\n",
+        &plugins,
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn data_uris() {
+    assert_eq!(data_uri("a/b", b""), "data:a/b;base64,");
+    assert_eq!(data_uri("a/b", b"f"), "data:a/b;base64,Zg==");
+    assert_eq!(data_uri("a/b", b"fo"), "data:a/b;base64,Zm8=");
+    assert_eq!(data_uri("a/b", b"foo"), "data:a/b;base64,Zm9v");
+    assert_eq!(data_uri("a/b", &[0xfb, 0xff]), "data:a/b;base64,+/8=");
+}
+
+#[test]
+fn image_dimension_adapter_plugin() {
+    struct MockAdapter;
+
+    impl ImageDimensionAdapter for MockAdapter {
+        fn dimensions(&self, url: &str) -> Option<ImageDimensions> {
+            match url {
+                "/a.png" => Some(ImageDimensions {
+                    width: Some(640),
+                    height: Some(480),
+                    srcset: Some("/a.png 1x, /a@2x.png?q=\"2\" 2x".to_string()),
+                }),
+                "/b.png" => Some(ImageDimensions {
+                    width: Some(16),
+                    ..Default::default()
+                }),
+                "javascript:alert(1)" => Some(ImageDimensions {
+                    srcset: Some("evil.png".to_string()),
+                    ..Default::default()
+                }),
+                _ => None,
+            }
+        }
+    }
+
+    let mut plugins = Plugins::default();
+    let adapter = MockAdapter;
+    plugins.render.image_dimension_adapter = Some(&adapter);
+
+    html_plugins(
+        concat!(
+            "![a](/a.png \"A\") ![b](/b.png) ![c](/c.png)\n",
+            "\n",
+            "![d](javascript:alert(1)) [![e](/b.png)](/a.png)\n",
+        ),
+        concat!(
+            "<p><img src=\"/a.png\" alt=\"a\" title=\"A\" width=\"640\" height=\"480\" \
+             srcset=\"/a.png 1x, /a@2x.png?q=&quot;2&quot; 2x\" /> \
+             <img src=\"/b.png\" alt=\"b\" width=\"16\" /> <img src=\"/c.png\" alt=\"c\" /></p>\n",
+            "<p><img src=\"\" alt=\"d\" /> \
+             <a href=\"/a.png\"><img src=\"/b.png\" alt=\"e\" width=\"16\" /></a></p>\n",
+        ),
+        &plugins,
+    );
+}
+
 #[test]
 #[cfg(feature = "syntect")]
 fn syntect_plugin_with_base16_ocean_dark_theme() {
@@ -124,3 +455,102 @@ fn syntect_plugin_with_css_classes() {
 
     html_plugins(input, expected, &plugins);
 }
+
+#[test]
+fn syntax_highlighter_plugin_with_cache() {
+    use crate::plugins::cache::InMemoryCodeBlockCache;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingAdapter {
+        calls: AtomicUsize,
+    }
+
+    impl SyntaxHighlighterAdapter for CountingAdapter {
+        fn write_highlighted(
+            &self,
+            output: &mut dyn Write,
+            lang: Option<&str>,
+            code: &str,
+        ) -> io::Result<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            write!(output, "<!--{}--><span>{}</span>", lang.unwrap(), code)
+        }
+
+        fn write_pre_tag(
+            &self,
+            output: &mut dyn Write,
+            attributes: HashMap<String, String>,
+        ) -> io::Result<()> {
+            html::write_opening_tag(output, "pre", attributes)
+        }
+
+        fn write_code_tag(
+            &self,
+            output: &mut dyn Write,
+            attributes: HashMap<String, String>,
+        ) -> io::Result<()> {
+            html::write_opening_tag(output, "code", attributes)
+        }
+    }
+
+    let input = concat!(
+        "```rust\n",
+        "fn main<'a>();\n",
+        "```\n",
+        "\n",
+        "```rust\n",
+        "fn main<'a>();\n",
+        "```\n",
+        "\n",
+        "```python\n",
+        "fn main<'a>();\n",
+        "```\n",
+    );
+    let expected = concat!(
+        "<pre><code class=\"language-rust\"><!--rust--><span>fn main<'a>();\n</span></code></pre>\n",
+        "<pre><code class=\"language-rust\"><!--rust--><span>fn main<'a>();\n</span></code></pre>\n",
+        "<pre><code class=\"language-python\"><!--python--><span>fn main<'a>();\n</span></code></pre>\n",
+    );
+
+    let adapter = CountingAdapter {
+        calls: AtomicUsize::new(0),
+    };
+    let cache = InMemoryCodeBlockCache::new();
+    let mut plugins = Plugins::default();
+    plugins.render.codefence_syntax_highlighter = Some(&adapter);
+    plugins.render.codefence_highlight_cache = Some(&cache);
+
+    html_plugins(input, expected, &plugins);
+    assert_eq!(cache.len(), 2);
+    let calls = adapter.calls.load(Ordering::SeqCst);
+
+    html_plugins(input, expected, &plugins);
+    assert_eq!(adapter.calls.load(Ordering::SeqCst), calls);
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+#[cfg(feature = "syntect")]
+fn syntect_plugin_with_shared_cache() {
+    use crate::plugins::cache::InMemoryCodeBlockCache;
+    use crate::plugins::syntect::SyntectAdapter;
+
+    let cache = InMemoryCodeBlockCache::new();
+    let input = "```rust\nfn main() {}\n```\n";
+    let render = |adapter: &SyntectAdapter| {
+        let mut plugins = Plugins::default();
+        plugins.render.codefence_syntax_highlighter = Some(adapter);
+        plugins.render.codefence_highlight_cache = Some(&cache);
+        markdown_to_html_with_plugins(input, &Options::default(), &plugins)
+    };
+
+    // Highlighting with classes and with a theme are cached apart.
+    let classes = render(&SyntectAdapter::new(None));
+    let inline = render(&SyntectAdapter::new(Some("base16-ocean.dark")));
+    assert_eq!(cache.len(), 2);
+    assert!(classes.contains("<span class=\""));
+    assert!(!inline.contains("<span class=\""));
+    assert_eq!(render(&SyntectAdapter::new(None)), classes);
+}
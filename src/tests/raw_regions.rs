@@ -0,0 +1,84 @@
+use super::*;
+
+#[test]
+fn raw_regions() {
+    html_opts!(
+        [extension.raw_regions, extension.table, render.unsafe_],
+        concat!(
+            "Before *a*\n",
+            "<!-- comrak:off -->\n",
+            "| not | a table |\n",
+            "|-----|---------|\n",
+            "\n",
+            "   *not*  emphasis\n",
+            "<!--comrak:on-->  \n",
+            "After *b*\n",
+        ),
+        concat!(
+            "<p>Before <em>a</em></p>\n",
+            "| not | a table |\n",
+            "|-----|---------|\n",
+            "\n",
+            "   *not*  emphasis\n",
+            "<p>After <em>b</em></p>\n",
+        ),
+    );
+}
+
+#[test]
+fn raw_regions_unclosed() {
+    html_opts!(
+        [extension.raw_regions, render.unsafe_],
+        concat!("> <!-- comrak:off -->\n", "> **a**\n", "\n", "**b**\n",),
+        concat!(
+            "<blockquote>\n",
+            "**a**\n",
+            "</blockquote>\n",
+            "<p><strong>b</strong></p>\n",
+        ),
+    );
+}
+
+#[test]
+fn raw_regions_not_markers() {
+    html_opts!(
+        [extension.raw_regions, render.unsafe_],
+        concat!(
+            "<!-- comrak:on -->\n",
+            "\n",
+            "    <!-- comrak:off -->\n",
+            "\n",
+            "<!-- comrak:off --> *a*\n",
+        ),
+        concat!(
+            "<!-- comrak:on -->\n",
+            "<pre><code>&lt;!-- comrak:off --&gt;\n",
+            "</code></pre>\n",
+            "<!-- comrak:off --> *a*\n",
+        ),
+    );
+}
+
+#[test]
+fn raw_regions_roundtrip() {
+    let mut options = Options::default();
+    options.extension.raw_regions = true;
+    assert_eq!(
+        markdown_to_commonmark("- <!-- comrak:off -->\n  __a__\n\n  * b\n- c\n", &options),
+        "- \n  <!-- comrak:off -->\n  __a__\n  \n  * b\n  <!-- comrak:on -->\n- c\n"
+    );
+}
+
+#[test]
+fn raw_regions_sourcepos() {
+    assert_ast_match!(
+        [extension.raw_regions],
+        "a\n\n<!-- comrak:off -->\n*b*\n<!-- comrak:on -->\n",
+        (document (1:1-5:18) [
+            (paragraph (1:1-1:1) [
+                (text (1:1-1:1) "a")
+            ])
+            (raw (3:1-5:18))
+        ])
+    );
+}
@@ -0,0 +1,102 @@
+use super::*;
+use crate::sanitize::{sanitize_html, Allowlist, HtmlSanitizer};
+
+fn sanitized(input: &str, sanitizer: &dyn HtmlSanitizer, configure: fn(&mut Options)) -> String {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    configure(&mut options);
+    let root = parse_document(&arena, input, &options);
+    sanitize_html(root, sanitizer);
+
+    options.render.unsafe_ = true;
+    let mut html = vec![];
+    format_html(root, &options, &mut html).unwrap();
+    String::from_utf8(html).unwrap()
+}
+
+#[test]
+fn sanitize_allowlist() {
+    assert_eq!(
+        sanitized(
+            concat!(
+                "<DIV Class=x ALIGN='center' style=\"color: red\">\n",
+                "<!-- hidden --><style>p { color: red }</style><iframe src=x></iframe>\n",
+                "<img src=\"&#x6A;avascript:x\" alt='a \"b\"' /><img src=\"java\tscript:x\"><img src=/ok.png>\n",
+                "<a href=mailto:a@example.com>a < b</a>\n",
+                "</div>\n",
+                "\n",
+                "<b onmouseover=\"x()\">*b*</b> <span title=\"&lt;i&gt;\">`<code>`</span> <?php x ?> <x-y>z</x-y>\n",
+                "\n",
+                "```\n<script>x</script>\n```\n",
+            ),
+            &Allowlist::default(),
+            |_| (),
+        ),
+        concat!(
+            "<div align=\"center\">\n",
+            "\n",
+            "<img alt=\"a &quot;b&quot;\" /><img><img src=\"/ok.png\">\n",
+            "<a href=\"mailto:a@example.com\">a &lt; b</a>\n",
+            "</div>\n",
+            "<p><b><em>b</em></b> <span title=\"&lt;i&gt;\"><code>&lt;code&gt;</code></span>  z</p>\n",
+            "<pre><code>&lt;script&gt;x&lt;/script&gt;\n</code></pre>\n",
+        )
+    );
+}
+
+#[test]
+fn sanitize_allowlist_attribute_blocks() {
+    let mut allowlist = Allowlist::default();
+    allowlist.attributes.insert("lang".into());
+    assert_eq!(
+        sanitized(
+            "# Hi {#hi lang=en onclick=x()}\n\n``` rust {lang=en style=x}\nx\n```\n",
+            &allowlist,
+            |options| options.extension.block_attributes = true,
+        ),
+        concat!(
+            "<h1 id=\"hi\" lang=\"en\">Hi</h1>\n",
+            "<pre lang=\"en\"><code class=\"language-rust\">x\n</code></pre>\n",
+        )
+    );
+}
+
+#[test]
+fn sanitize_allowlist_container_attributes() {
+    assert_eq!(
+        sanitized(
+            "::: warning {#w .big title=x onclick=\"alert(1)\"}\nHi\n:::\n",
+            &Allowlist::default(),
+            |options| {
+                options.extension.container_blocks = true;
+                options.extension.block_attributes = true;
+            },
+        ),
+        "<div id=\"w\" class=\"warning big\" title=\"x\">\n<p>Hi</p>\n</div>\n"
+    );
+}
+
+#[test]
+fn sanitize_allowlist_template_tags() {
+    assert_eq!(
+        sanitized(
+            "{% <img src=x onerror=alert(1)> %} {% if <b onclick=x()>a</b> %}\n",
+            &Allowlist::default(),
+            |options| options.extension.template_tags = true,
+        ),
+        "<p>{% <img src=\"x\"> %} {% if <b>a</b> %}</p>\n"
+    );
+}
+
+#[test]
+fn sanitize_closure() {
+    let upper = |html: &str| html.to_uppercase();
+    assert_eq!(
+        sanitized(
+            "<div>\nhi\n</div>\n\n<i>a</i> {.b c=d}\n",
+            &upper,
+            |options| options.extension.block_attributes = true,
+        ),
+        "<DIV>\nHI\n</DIV>\n<p><I>a</I> {.b c=d}</p>\n"
+    );
+}
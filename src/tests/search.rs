@@ -0,0 +1,114 @@
+use crate::search::{search_records, SearchRecord};
+
+use super::*;
+
+#[test]
+fn search_records_by_section() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.header_ids = Some("doc-".to_string());
+    options.extension.footnotes = true;
+    options.extension.table = true;
+    let root = parse_document(
+        &arena,
+        concat!(
+            "Preamble with *emphasis*\n",
+            "over two lines.\n",
+            "\n",
+            "# Guide\n",
+            "\n",
+            "## Setup\n",
+            "\n",
+            "Install it[^1].\n",
+            "\n",
+            "<div>raw</div>\n",
+            "\n",
+            "```\n",
+            "make\n",
+            "```\n",
+            "\n",
+            "### Linux\n",
+            "\n",
+            "| a | b |\n",
+            "|---|---|\n",
+            "| `x` | y |\n",
+            "\n",
+            "## Setup\n",
+            "\n",
+            "[^1]: A note.\n",
+        ),
+        &options,
+    );
+
+    assert_eq!(
+        search_records(root, &options),
+        vec![
+            SearchRecord {
+                anchor: "".to_string(),
+                heading_path: vec![],
+                text: "Preamble with emphasis over two lines.".to_string(),
+                code: vec![],
+            },
+            SearchRecord {
+                anchor: "doc-guide".to_string(),
+                heading_path: vec!["Guide".to_string()],
+                text: "".to_string(),
+                code: vec![],
+            },
+            SearchRecord {
+                anchor: "doc-setup".to_string(),
+                heading_path: vec!["Guide".to_string(), "Setup".to_string()],
+                text: "Install it.".to_string(),
+                code: vec!["make\n".to_string()],
+            },
+            SearchRecord {
+                anchor: "doc-linux".to_string(),
+                heading_path: vec![
+                    "Guide".to_string(),
+                    "Setup".to_string(),
+                    "Linux".to_string()
+                ],
+                text: "a\nb\nx\ny".to_string(),
+                code: vec!["x".to_string()],
+            },
+            SearchRecord {
+                anchor: "doc-setup-1".to_string(),
+                heading_path: vec!["Guide".to_string(), "Setup".to_string()],
+                text: "".to_string(),
+                code: vec![],
+            },
+        ]
+    );
+}
+
+#[test]
+fn search_records_without_preamble() {
+    let arena = Arena::new();
+    let options = Options::default();
+    let root = parse_document(&arena, "<!-- hidden -->\n\n## Only\n\nText.\n", &options);
+
+    let records = search_records(root, &options);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].anchor, "only");
+    assert_eq!(records[0].text, "Text.");
+
+    let root = parse_document(&arena, "", &options);
+    assert!(search_records(root, &options).is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn search_records_serialize() {
+    let record = SearchRecord {
+        anchor: "a".to_string(),
+        heading_path: vec!["A".to_string()],
+        text: "t".to_string(),
+        code: vec![],
+    };
+    let json = serde_json::to_string(&record).unwrap();
+    assert_eq!(
+        json,
+        r#"{"anchor":"a","heading_path":["A"],"text":"t","code":[]}"#
+    );
+    assert_eq!(serde_json::from_str::<SearchRecord>(&json).unwrap(), record);
+}
@@ -0,0 +1,156 @@
+use crate::sections::{extract_section, split_sections};
+
+use super::*;
+
+fn commonmark<'a>(node: &'a AstNode<'a>, options: &Options) -> String {
+    let mut md = vec![];
+    format_commonmark(node, options, &mut md).unwrap();
+    String::from_utf8(md).unwrap()
+}
+
+const DOC: &str = concat!(
+    "# Guide\n",
+    "\n",
+    "## Install\n",
+    "\n",
+    "Run it.\n",
+    "\n",
+    "## Usage `basic`\n",
+    "\n",
+    "Use it.\n",
+    "\n",
+    "#### Deep\n",
+    "\n",
+    "Deeper.\n",
+    "\n",
+    "Install\n",
+    "-------\n",
+    "\n",
+    "Again.\n",
+    "\n",
+    "# Appendix\n",
+);
+
+#[test]
+fn extract_section_by_title() {
+    let arena = Arena::new();
+    let options = Options::default();
+    let root = parse_document(&arena, DOC, &options);
+
+    let section = extract_section(&arena, root, &options, "  Usage basic ").unwrap();
+    assert_eq!(
+        commonmark(section, &options),
+        "## Usage `basic`\n\nUse it.\n\n#### Deep\n\nDeeper.\n"
+    );
+    assert_eq!(
+        commonmark(root, &options),
+        concat!(
+            "# Guide\n\n## Install\n\nRun it.\n\n",
+            "## Install\n\nAgain.\n\n# Appendix\n",
+        )
+    );
+}
+
+#[test]
+fn extract_section_by_slug() {
+    let arena = Arena::new();
+    let options = Options::default();
+    let root = parse_document(&arena, DOC, &options);
+
+    let section = extract_section(&arena, root, &options, "install-1").unwrap();
+    assert_eq!(commonmark(section, &options), "## Install\n\nAgain.\n");
+
+    let section = extract_section(&arena, root, &options, "#appendix").unwrap();
+    assert_eq!(commonmark(section, &options), "# Appendix\n");
+
+    assert!(extract_section(&arena, root, &options, "missing").is_none());
+    assert_eq!(
+        commonmark(root, &options),
+        concat!(
+            "# Guide\n\n## Install\n\nRun it.\n\n",
+            "## Usage `basic`\n\nUse it.\n\n#### Deep\n\nDeeper.\n",
+        )
+    );
+}
+
+#[test]
+fn extract_section_with_header_ids_prefix() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.header_ids = Some("user-content-".to_string());
+    let root = parse_document(&arena, DOC, &options);
+
+    let section = extract_section(&arena, root, &options, "#user-content-deep").unwrap();
+    assert_eq!(commonmark(section, &options), "#### Deep\n\nDeeper.\n");
+
+    let section = extract_section(&arena, root, &options, "install").unwrap();
+    assert_eq!(commonmark(section, &options), "## Install\n\nRun it.\n");
+}
+
+#[test]
+fn extract_section_in_container() {
+    let arena = Arena::new();
+    let options = Options::default();
+    let root = parse_document(&arena, "> ## Note\n>\n> Inside.\n\nOutside.\n", &options);
+
+    let section = extract_section(&arena, root, &options, "Note").unwrap();
+    assert_eq!(commonmark(section, &options), "## Note\n\nInside.\n");
+    let blockquote = root.first_child().unwrap();
+    assert!(blockquote.first_child().is_none());
+    assert_eq!(root.children().count(), 2);
+}
+
+#[test]
+fn split_sections_at_level() {
+    let arena = Arena::new();
+    let options = Options::default();
+    let root = parse_document(&arena, DOC, &options);
+
+    let sections = split_sections(&arena, root, 2);
+    assert!(root.first_child().is_none());
+
+    let split: Vec<(Option<String>, String)> = sections
+        .iter()
+        .map(|section| {
+            (
+                section.heading.map(|h| commonmark(h, &options)),
+                commonmark(section.body, &options),
+            )
+        })
+        .collect();
+    assert_eq!(
+        split,
+        vec![
+            (Some("# Guide\n".to_string()), "".to_string()),
+            (Some("## Install\n".to_string()), "Run it.\n".to_string()),
+            (
+                Some("## Usage `basic`\n".to_string()),
+                "Use it.\n\n#### Deep\n\nDeeper.\n".to_string()
+            ),
+            (Some("## Install\n".to_string()), "Again.\n".to_string()),
+            (Some("# Appendix\n".to_string()), "".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn split_sections_preamble() {
+    let arena = Arena::new();
+    let options = Options::default();
+    let root = parse_document(
+        &arena,
+        "Before.\n\n> # Quoted\n\n## Two\n\nAfter.\n",
+        &options,
+    );
+
+    let sections = split_sections(&arena, root, 1);
+    assert_eq!(sections.len(), 1);
+    assert!(sections[0].heading.is_none());
+    assert_eq!(
+        commonmark(sections[0].body, &options),
+        "Before.\n\n> # Quoted\n\n## Two\n\nAfter.\n"
+    );
+
+    let root = parse_document(&arena, "", &options);
+    assert!(split_sections(&arena, root, 6).is_empty());
+}
@@ -0,0 +1,106 @@
+use super::*;
+
+#[track_caller]
+fn slack(input: &str, expected: &str, opts: impl Fn(&mut Options)) {
+    formatted(format_slack, "slack", input, expected, opts);
+}
+
+#[test]
+fn slack_blocks() {
+    slack(
+        concat!(
+            "# Release *notes*\n",
+            "\n",
+            "> Quoted\n",
+            "> text.\n",
+            "\n",
+            "- one\n",
+            "  1. nested\n",
+            "  2. again\n",
+            "- two\n",
+            "\n",
+            "```rust\n",
+            "if a < b && c {}\n",
+            "```\n",
+            "\n",
+            "<div>dropped</div>\n",
+            "\n",
+            "***\n",
+        ),
+        concat!(
+            "*Release _notes_*\n",
+            "\n",
+            "> Quoted text.\n",
+            "\n",
+            "• one\n",
+            "    1. nested\n",
+            "    2. again\n",
+            "• two\n",
+            "\n",
+            "```\n",
+            "if a &lt; b &amp;&amp; c {}\n",
+            "```\n",
+        ),
+        |_| {},
+    );
+}
+
+#[test]
+fn slack_inlines() {
+    slack(
+        concat!(
+            "*a* **b** ~~c~~ `d` [e *f*](https://x.org/?a|b) <https://y.org>\\\n",
+            "![g](g.png) [h|i](/j) 1 < 2 & 3 > 2\n",
+        ),
+        concat!(
+            "_a_ *b* ~c~ `d` <https://x.org/?a%7Cb|e _f_> <https://y.org>\n",
+            "<g.png|g> </j|h¦i> 1 &lt; 2 &amp; 3 &gt; 2\n",
+        ),
+        |options| options.extension.strikethrough = true,
+    );
+}
+
+#[test]
+fn slack_extensions() {
+    slack(
+        concat!(
+            "- [x] done\n",
+            "- [ ] todo\n",
+            "\n",
+            "| a | b |\n",
+            "|---|---|\n",
+            "| *c* | d |\n",
+            "\n",
+            "Note[^n].\n",
+            "\n",
+            "[^n]: *Foot*.\n",
+        ),
+        concat!(
+            "• ☑ done\n",
+            "• ☐ todo\n",
+            "\n",
+            "```\n",
+            "| a | b |\n",
+            "| c | d |\n",
+            "```\n",
+            "\n",
+            "Note[n].\n",
+            "\n",
+            "[n] _Foot_.\n",
+        ),
+        |options| {
+            options.extension.tasklist = true;
+            options.extension.table = true;
+            options.extension.footnotes = true;
+        },
+    );
+}
+
+#[test]
+fn markdown_to_slack_api() {
+    assert_eq!(
+        markdown_to_slack("# Hi\n\n*there*\n", &Options::default()),
+        "*Hi*\n\n_there_\n"
+    );
+    assert_eq!(markdown_to_slack("", &Options::default()), "");
+}
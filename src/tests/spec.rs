@@ -0,0 +1,150 @@
+use super::*;
+use crate::spec::{examples, Example};
+
+#[test]
+fn spec_parse_examples() {
+    let spec = concat!(
+        "---\n",
+        "title: Test\n",
+        "---\n",
+        "\n",
+        "# Tabs\n",
+        "\n",
+        "```````````````````````````````` example\n",
+        "→foo\n",
+        ".\n",
+        "<pre><code>foo\n",
+        "</code></pre>\n",
+        "````````````````````````````````\n",
+        "\n",
+        "## Tables\n",
+        "\n",
+        "```````````````````````````````` example table\n",
+        ".\n",
+        "````````````````````````````````\n",
+    );
+
+    assert_eq!(
+        examples(spec),
+        vec![
+            Example {
+                number: 1,
+                line: 7,
+                section: "Tabs".into(),
+                extensions: vec![],
+                markdown: "\tfoo\n".into(),
+                html: "<pre><code>foo\n</code></pre>\n".into(),
+            },
+            Example {
+                number: 2,
+                line: 16,
+                section: "Tables".into(),
+                extensions: vec!["table".into()],
+                markdown: "".into(),
+                html: "".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn spec_skips_examples() {
+    let spec = concat!(
+        "```````````````````````````````` example disabled\n",
+        "a\n",
+        ".\n",
+        "<p>b</p>\n",
+        "````````````````````````````````\n",
+        "```````````````````````````````` example strikethrough\n",
+        "~a~\n",
+        ".\n",
+        "<p>~a~</p>\n",
+        "````````````````````````````````\n",
+    );
+
+    let report = crate::spec::run(spec, &Options::default());
+    assert_eq!(
+        (report.passed(), report.failed(), report.skipped()),
+        (0, 1, 1)
+    );
+    assert_eq!(
+        report.failures().next().unwrap().output.as_deref(),
+        Some("<p><del>a</del></p>\n")
+    );
+}
+
+#[test]
+fn spec_math_code() {
+    let mut options = Options::default();
+    options.extension.math_code = true;
+    spec_examples(include_str!("fixtures/math_code.md"), &options);
+}
+
+#[test]
+fn spec_math_dollars() {
+    let mut options = Options::default();
+    options.extension.math_dollars = true;
+    spec_examples(include_str!("fixtures/math_dollars.md"), &options);
+}
+
+#[test]
+fn spec_multiline_blockquote() {
+    let mut options = Options::default();
+    options.extension.multiline_block_quotes = true;
+    spec_examples(include_str!("fixtures/multiline_blockquote.md"), &options);
+}
+
+#[test]
+fn spec_wikilinks() {
+    let mut options = Options::default();
+    options.extension.wikilinks_title_after_pipe = true;
+    spec_examples(
+        include_str!("fixtures/wikilinks_title_after_pipe.md"),
+        &options,
+    );
+
+    let mut options = Options::default();
+    options.extension.wikilinks_title_before_pipe = true;
+    spec_examples(
+        include_str!("fixtures/wikilinks_title_before_pipe.md"),
+        &options,
+    );
+}
+
+// The specs in the vendor/ submodules, run as by script/cibuild if they're
+// checked out.
+fn vendored_spec(path: &str, options: &Options) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("vendor")
+        .join(path);
+    if let Ok(spec) = std::fs::read_to_string(path) {
+        spec_examples(&spec, options);
+    }
+}
+
+#[test]
+fn spec_commonmark() {
+    vendored_spec("commonmark-spec/spec.txt", &Options::default());
+}
+
+#[test]
+fn spec_cmark_gfm() {
+    let mut options = Options::default();
+    options.render.gfm_quirks = true;
+    vendored_spec("cmark-gfm/test/spec.txt", &options);
+
+    let mut options = Options::default();
+    options.parse.smart = true;
+    vendored_spec("cmark-gfm/test/smart_punct.txt", &options);
+
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.autolink = true;
+    options.extension.tagfilter = true;
+    options.extension.footnotes = true;
+    options.extension.tasklist = true;
+    vendored_spec("cmark-gfm/test/extensions.txt", &options);
+
+    vendored_spec("cmark-gfm/test/regression.txt", &Options::default());
+}
@@ -0,0 +1,138 @@
+use crate::stats::{document_stats, largest_block, memory_usage, DocumentStats, Stats};
+
+use super::*;
+
+#[test]
+fn stats_largest_block() {
+    let mut options = Options::default();
+    options.extension.table = true;
+    let (_, stats) = markdown_to_html_with_stats(
+        concat!(
+            "> - One\n",
+            ">   two\n",
+            "\n",
+            "| a |\n",
+            "|---|\n",
+            "| b |\n",
+            "\n",
+            "```\n",
+            "c\n",
+            "```\n",
+        ),
+        &options,
+        &Plugins::default(),
+    );
+    let largest = stats.largest_block.unwrap();
+    assert_eq!((largest.start.line, largest.end.line), (4, 6));
+}
+
+#[test]
+fn stats_empty_document() {
+    let (html, stats) = markdown_to_html_with_stats("", &Options::default(), &Plugins::default());
+    assert_eq!(html, "");
+    assert_eq!(
+        stats,
+        Stats {
+            nodes: 1,
+            parse_time: stats.parse_time,
+            render_time: stats.render_time,
+            ..Stats::default()
+        }
+    );
+}
+
+#[test]
+fn stats_counts_merged_nodes() {
+    // The brackets are parsed as separate text nodes before being merged.
+    let arena = Arena::new();
+    let root = parse_document(&arena, "a [b] c\n", &Options::default());
+    assert_eq!(root.descendants().count(), 3);
+    assert!(arena.len() > 3);
+
+    let (_, stats) =
+        markdown_to_html_with_stats("a [b] c\n", &Options::default(), &Plugins::default());
+    assert_eq!(stats.nodes, arena.len());
+    assert_eq!(largest_block(root), stats.largest_block);
+}
+
+#[test]
+fn memory_usage_grows_with_document() {
+    let arena = Arena::new();
+    let small = parse_document(&arena, "a\n", &Options::default());
+    let small = memory_usage(&arena, small);
+
+    let arena = Arena::new();
+    let large = "*word* ".repeat(1000) + "\n\n```\n" + &"code\n".repeat(1000) + "```\n";
+    let large = parse_document(&arena, &large, &Options::default());
+    let large = memory_usage(&arena, large);
+
+    assert_eq!(small.nodes, 3);
+    assert!(large.arena_capacity >= large.nodes);
+    assert!(large.arena_bytes > small.arena_bytes);
+    assert!(large.literal_bytes >= 5000 + 5000);
+    assert_eq!(large.total_bytes(), large.arena_bytes + large.literal_bytes);
+}
+
+#[test]
+fn document_stats_prose() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.footnotes = true;
+    options.extension.math_dollars = true;
+    options.extension.wikilinks_title_after_pipe = true;
+    options.extension.autolink = true;
+    let root = parse_document(
+        &arena,
+        concat!(
+            "Don't -- stop — *now*[^1]. $x + y$ <b>bold</b>\n",
+            "\n",
+            "| a | b |\n",
+            "|---|---|\n",
+            "| [[Page]] | www.example.com |\n",
+            "\n",
+            "<div>\n",
+            "skipped\n",
+            "</div>\n",
+            "\n",
+            "    indented code\n",
+            "\n",
+            "[^1]: A note.\n",
+        ),
+        &options,
+    );
+    assert_eq!(
+        document_stats(root),
+        DocumentStats {
+            words: 9,
+            characters: 32,
+            headings: 0,
+            images: 0,
+            links: 2,
+            code_blocks: 1,
+        }
+    );
+}
+
+#[test]
+fn document_stats_cjk() {
+    let arena = Arena::new();
+    let root = parse_document(&arena, "日本語の文章。\nHello 世界\n", &Options::default());
+    let stats = document_stats(root);
+    assert_eq!((stats.words, stats.characters), (9, 14));
+}
+
+#[test]
+fn document_stats_reading_time() {
+    let stats = DocumentStats {
+        words: 201,
+        ..DocumentStats::default()
+    };
+    assert_eq!(stats.reading_time(200).as_secs(), 61);
+    assert_eq!(stats.reading_time(0).as_secs(), 201 * 60);
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, "", &Options::default());
+    assert_eq!(document_stats(root), DocumentStats::default());
+    assert_eq!(document_stats(root).reading_time(200).as_secs(), 0);
+}
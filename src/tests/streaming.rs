@@ -0,0 +1,55 @@
+use super::*;
+
+// Parses `input` in two pieces split at every point, checking the AST and
+// node offsets match those of parsing it whole.
+#[track_caller]
+fn streaming_matches(input: &str, options: &Options) {
+    let arena = Arena::new();
+    let root = parse_document(&arena, input, options);
+    let expected = nodes(root, options);
+
+    for split in (0..=input.len()).filter(|&i| input.is_char_boundary(i)) {
+        let arena = Arena::new();
+        let mut parser = StreamingParser::new(&arena, options);
+        parser.feed(&input[..split]);
+        parser.feed(&input[split..]);
+        let root = parser.finish();
+        assert_eq!(nodes(root, options), expected, "split at {}", split);
+    }
+}
+
+fn nodes<'a>(root: &'a AstNode<'a>, options: &Options) -> (String, Vec<(usize, usize)>) {
+    let mut options = options.clone();
+    options.render.sourcepos = true;
+    let mut xml = vec![];
+    format_xml(root, &options, &mut xml).unwrap();
+    let offsets = root
+        .descendants()
+        .map(|node| {
+            let ast = node.data.borrow();
+            (ast.start_offset, ast.end_offset)
+        })
+        .collect();
+    (String::from_utf8(xml).unwrap(), offsets)
+}
+
+#[test]
+fn streaming() {
+    let options = Options::default();
+    streaming_matches(
+        "# Title\n\n> quoted\n> *text*\n\n- [link]\n  ```\n  code\n  ```\n\n[link]: /url\n",
+        &options,
+    );
+    streaming_matches("a\r\nb\rc\n\r\n\tdé\0f", &options);
+}
+
+#[test]
+fn streaming_front_matter() {
+    let mut options = Options::default();
+    options.extension.front_matter_delimiter = Some("---".to_string());
+    streaming_matches("---\ntitle: x\n---\n\n# Body\n", &options);
+    streaming_matches("---\r\ntitle: x\r\n---\r\n\r\nBody\r\n", &options);
+    streaming_matches("\u{feff}---\na\n---\nb\n", &options);
+    streaming_matches("--- not front matter\n---\n", &options);
+    streaming_matches("---\nunclosed\n", &options);
+}
@@ -0,0 +1,31 @@
+use super::*;
+
+#[test]
+fn subscript() {
+    html_opts!(
+        [extension.subscript],
+        concat!("H~2~O, but ~~not this~~.\n"),
+        concat!("<p>H<sub>2</sub>O, but ~~not this~~.</p>\n"),
+    );
+}
+
+#[test]
+fn subscript_with_strikethrough() {
+    html_opts!(
+        [extension.subscript, extension.strikethrough],
+        concat!("H~2~O is ~~not~~ water, ~~nor~ ~~~this~~~.\n"),
+        concat!("<p>H<sub>2</sub>O is <del>not</del> water, ~~nor~ ~~~this~~~.</p>\n"),
+    );
+}
+
+#[test]
+fn subscript_commonmark() {
+    let mut options = Options::default();
+    options.extension.subscript = true;
+    options.extension.strikethrough = true;
+    commonmark(
+        "H~2~O is ~~not~~ water.\n",
+        "H~2~O is ~~not~~ water.\n",
+        Some(&options),
+    );
+}
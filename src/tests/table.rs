@@ -1,4 +1,7 @@
+use std::sync::{Arc, Mutex};
+
 use super::*;
+use crate::nodes::TableAlignment;
 
 #[test]
 fn table() {
@@ -218,3 +221,309 @@ fn sourcepos_with_preceding_para_offset() {
         ])
     );
 }
+
+#[test]
+fn row_mismatch_truncate() {
+    html_opts_i(
+        concat!(
+            "| a | b |\n",
+            "|---|---|\n",
+            "| c | d |\n",
+            "| e | f | g |\n",
+            "| h | i |\n"
+        ),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "<th>b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>c</td>\n",
+            "<td>d</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n",
+            "<p>| e | f | g |\n",
+            "| h | i |</p>\n",
+        ),
+        false,
+        |opts| {
+            opts.extension.table = true;
+            opts.extension.table_row_mismatch = TableRowMismatch::Truncate;
+        },
+    );
+}
+
+#[test]
+fn row_mismatch_paragraph() {
+    html_opts_i(
+        concat!(
+            "x\n",
+            "| a | b |\n",
+            "|---|---|\n",
+            "| *c* | d | e |\n",
+            "\n",
+            "| a | b |\n",
+            "|---|---|\n",
+            "| c | d |\n",
+        ),
+        concat!(
+            "<p>x</p>\n",
+            "<p>| a | b |\n",
+            "|---|---|\n",
+            "| <em>c</em> | d | e |</p>\n",
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "<th>b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>c</td>\n",
+            "<td>d</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n",
+        ),
+        false,
+        |opts| {
+            opts.extension.table = true;
+            opts.extension.table_row_mismatch = TableRowMismatch::Paragraph;
+        },
+    );
+}
+
+#[test]
+fn row_mismatch_paragraph_sourcepos() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.table_row_mismatch = TableRowMismatch::Paragraph;
+    let root = parse_document(&arena, "| a | b |\n|---|---|\n| c |\n", &options);
+
+    let para = root.first_child().unwrap();
+    assert_eq!(para.data.borrow().value, NodeValue::Paragraph);
+    assert_eq!(para.data.borrow().sourcepos, (1, 1, 3, 5).into());
+    assert!(para.next_sibling().is_none());
+}
+
+#[test]
+fn row_mismatch_diagnostics() {
+    for policy in [
+        TableRowMismatch::Pad,
+        TableRowMismatch::Truncate,
+        TableRowMismatch::Paragraph,
+    ] {
+        let mut found = vec![];
+        let mut cb = |d: Diagnostic| found.push((d.sourcepos, d.message));
+        let arena = Arena::new();
+        let mut options = Options::default();
+        options.extension.table = true;
+        options.extension.table_row_mismatch = policy;
+        options.parse.diagnostic_callback = Some(Arc::new(Mutex::new(&mut cb)));
+        parse_document(
+            &arena,
+            "| a | b |\n|---|---|\n  | c | d | e |\r\n| f |\n",
+            &options,
+        );
+        drop(options);
+
+        let mut expected: Vec<(Sourcepos, String)> = vec![(
+            (3, 3, 3, 15).into(),
+            "table row has 3 cells, expected 2".to_string(),
+        )];
+        if policy != TableRowMismatch::Truncate {
+            expected.push((
+                (4, 1, 4, 5).into(),
+                "table row has 1 cell, expected 2".to_string(),
+            ));
+        }
+        assert_eq!(found, expected, "{:?}", policy);
+    }
+}
+
+#[test]
+fn table_cell_info() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    let root = parse_document(
+        &arena,
+        "| a | b | c |\n| :- | :-: | - |\n| d | e | f |\n| g |\n",
+        &options,
+    );
+
+    let table = root.first_child().unwrap();
+    match table.data.borrow().value {
+        NodeValue::Table(ref nt) => {
+            assert_eq!(
+                nt.alignments,
+                [
+                    TableAlignment::Left,
+                    TableAlignment::Center,
+                    TableAlignment::None
+                ]
+            );
+            assert_eq!(nt.num_columns, 3);
+            assert_eq!(nt.num_rows, 2);
+        }
+        ref value => panic!("expected a table, got {:?}", value),
+    }
+
+    let cells: Vec<_> = table
+        .descendants()
+        .filter_map(|node| node.table_cell_info())
+        .map(|info| (info.column, info.alignment, info.header))
+        .collect();
+    assert_eq!(
+        cells,
+        [
+            (0, TableAlignment::Left, true),
+            (1, TableAlignment::Center, true),
+            (2, TableAlignment::None, true),
+            (0, TableAlignment::Left, false),
+            (1, TableAlignment::Center, false),
+            (2, TableAlignment::None, false),
+            (0, TableAlignment::Left, false),
+            (1, TableAlignment::Center, false),
+            (2, TableAlignment::None, false),
+        ]
+    );
+    assert_eq!(table.table_cell_info(), None);
+}
+
+#[test]
+fn colspans() {
+    html_opts!(
+        [extension.table, extension.table_colspans],
+        concat!(
+            "| a || b |\n",
+            "|---|:-:|---|\n",
+            "| c | d ||\n",
+            "| e | | f |\n",
+            "| g |||\n"
+        ),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th colspan=\"2\">a</th>\n",
+            "<th>b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>c</td>\n",
+            "<td colspan=\"2\" align=\"center\">d</td>\n",
+            "</tr>\n",
+            "<tr>\n",
+            "<td>e</td>\n",
+            "<td align=\"center\"></td>\n",
+            "<td>f</td>\n",
+            "</tr>\n",
+            "<tr>\n",
+            "<td colspan=\"3\">g</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+    );
+}
+
+#[test]
+fn colspans_disabled() {
+    html_opts!(
+        [extension.table],
+        concat!("| a || b |\n", "|---|---|---|\n"),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "<th></th>\n",
+            "<th>b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "</table>\n"
+        ),
+    );
+}
+
+#[test]
+fn colspans_mismatch() {
+    html_opts!(
+        [extension.table, extension.table_colspans],
+        concat!("| a | b |\n", "|---|---|\n", "| c ||||\n", "| d |\n"),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "<th>b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td colspan=\"2\">c</td>\n",
+            "</tr>\n",
+            "<tr>\n",
+            "<td>d</td>\n",
+            "<td></td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+    );
+
+    html_opts_i(
+        concat!("| a | b |\n", "|---|---|\n", "| c ||\n", "| d |||\n"),
+        concat!("<p>| a | b |\n", "|---|---|\n", "| c ||\n", "| d |||</p>\n"),
+        false,
+        |opts| {
+            opts.extension.table = true;
+            opts.extension.table_colspans = true;
+            opts.extension.table_row_mismatch = TableRowMismatch::Paragraph;
+        },
+    );
+}
+
+#[test]
+fn colspans_cell_info() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.table_colspans = true;
+    let root = parse_document(
+        &arena,
+        "| a | b | c |\n| :- | :-: | -: |\n| d || e |\n",
+        &options,
+    );
+
+    let cells: Vec<_> = root
+        .descendants()
+        .filter_map(|node| match node.data.borrow().value {
+            NodeValue::TableCell => {
+                let info = node.table_cell_info().unwrap();
+                let colspan = node.data.borrow().table_cell.map_or(1, |ntc| ntc.colspan);
+                Some((info.column, info.alignment, colspan))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        cells,
+        [
+            (0, TableAlignment::Left, 1),
+            (1, TableAlignment::Center, 1),
+            (2, TableAlignment::Right, 1),
+            (0, TableAlignment::Left, 2),
+            (2, TableAlignment::Right, 1),
+        ]
+    );
+}
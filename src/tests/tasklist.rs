@@ -153,3 +153,65 @@ fn sourcepos() {
         ])
     );
 }
+
+#[test]
+fn task_progress_by_section() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.tasklist = true;
+    options.parse.relaxed_tasklist_matching = true;
+
+    let root = parse_document(
+        &arena,
+        concat!(
+            "- [x] intro\n",
+            "\n",
+            "## One\n",
+            "\n",
+            "- [ ] a\n",
+            "- [!] b\n",
+            "\n",
+            "### Nested\n",
+            "\n",
+            "> - [x] c\n",
+            "\n",
+            "## Two\n",
+            "\n",
+            "No tasks here.\n",
+        ),
+        &options,
+    );
+
+    assert_eq!(
+        root.task_progress(),
+        nodes::TaskProgress {
+            completed: 3,
+            total: 4
+        }
+    );
+
+    let sections = root.task_progress_by_section();
+    let summary = sections
+        .iter()
+        .map(|s| {
+            (
+                s.heading.map(|h| h.data.borrow().sourcepos.start.line),
+                s.progress.completed,
+                s.progress.total,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![(None, 1, 1), (Some(3), 2, 3), (Some(12), 0, 0)]
+    );
+}
+
+#[test]
+fn task_progress_without_tasks() {
+    let arena = Arena::new();
+    let root = parse_document(&arena, "- [x] not a task\n", &Options::default());
+
+    assert_eq!(root.task_progress(), nodes::TaskProgress::default());
+    assert_eq!(root.task_progress_by_section().len(), 1);
+}
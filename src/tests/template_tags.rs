@@ -0,0 +1,74 @@
+use super::*;
+
+#[test]
+fn template_tags() {
+    html_opts!(
+        [extension.template_tags, render.unsafe_],
+        concat!(
+            "{% for post in site.posts %}\n",
+            "* [{{ post.title }}](/posts) {%- if post.*new* -%} new {%- endif %}\n",
+            "{% endfor %}\n",
+        ),
+        concat!(
+            "<p>{% for post in site.posts %}</p>\n",
+            "<ul>\n",
+            "<li><a href=\"/posts\">{{ post.title }}</a> {%- if post.*new* -%} new {%- endif %}\n",
+            "{% endfor %}</li>\n",
+            "</ul>\n",
+        ),
+    );
+}
+
+#[test]
+fn template_tags_escaped_when_not_unsafe() {
+    html_opts!(
+        [extension.template_tags],
+        "{% if a > b %}_a_{% endif %} {{ \"<b>\" }}\n",
+        "<p>{% if a &gt; b %}<em>a</em>{% endif %} {{ &quot;&lt;b&gt;&quot; }}</p>\n",
+    );
+}
+
+#[test]
+fn template_tags_unclosed() {
+    html_opts!(
+        [extension.template_tags, render.unsafe_],
+        "{% if *a*\n%} {{ b %} {% c }}\n",
+        "<p>{% if <em>a</em>\n%} {{ b %} {% c }}</p>\n",
+    );
+}
+
+#[test]
+fn template_tags_roundtrip() {
+    let mut options = Options::default();
+    options.extension.template_tags = true;
+    let input = "{{ page.title | *escape* }} \\*{%- include note_[1].html -%}\\*\n";
+    assert_eq!(markdown_to_commonmark(input, &options), input);
+}
+
+#[test]
+fn template_tags_with_template_variables() {
+    html_opts!(
+        [
+            extension.template_tags,
+            extension.template_variables,
+            render.unsafe_
+        ],
+        "{{ name | upcase }} {{ 'name' | upcase }}\n",
+        "<p>{{name | upcase}} {{ 'name' | upcase }}</p>\n",
+    );
+}
+
+#[test]
+fn template_tags_in_ast() {
+    assert_ast_match!(
+        [extension.template_tags],
+        "a {% b %} {c}\n",
+        (document (1:1-1:13) [
+            (paragraph (1:1-1:13) [
+                (text (1:1-1:2) "a ")
+                (template_tag (1:3-1:9))
+                (text (1:10-1:13) " {c}")
+            ])
+        ])
+    );
+}
@@ -0,0 +1,96 @@
+use crate::adapters::TemplateResolver;
+
+use super::*;
+
+#[test]
+fn template_variables() {
+    html_opts!(
+        [extension.template_variables],
+        concat!(
+            "Dear {{ first_name }} {{last_name|upcase}}, *welcome*.\n",
+            "\n",
+            "{{ total | round: 2 | currency }} and {{}} and {{ not valid }}\n",
+        ),
+        concat!(
+            "<p>Dear {{first_name}} {{last_name | upcase}}, <em>welcome</em>.</p>\n",
+            "<p>{{total | round: 2 | currency}} and {{}} and {{ not valid }}</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn template_variables_not_split_by_emphasis() {
+    html_opts!(
+        [extension.template_variables],
+        "{{ user_first_name }} is *{{ user_last_name }}*\n",
+        "<p>{{user_first_name}} is <em>{{user_last_name}}</em></p>\n",
+    );
+    html_opts!(
+        [extension.template_variables],
+        "*{{ items | join: \"*\" }}*\n",
+        "<p><em>{{items | join: &quot;*&quot;}}</em></p>\n",
+    );
+}
+
+#[test]
+fn template_variables_in_code() {
+    html_opts!(
+        [extension.template_variables],
+        "`{{ name }}`\n\n    {{ name }}\n",
+        "<p><code>{{ name }}</code></p>\n<pre><code>{{ name }}\n</code></pre>\n",
+    );
+}
+
+#[test]
+fn template_variable_resolver() {
+    struct Vars;
+
+    impl TemplateResolver for Vars {
+        fn resolve(&self, name: &str, filters: &[String]) -> Option<String> {
+            let mut value = match name {
+                "user.name" => "<Ann>".to_string(),
+                _ => return None,
+            };
+            for filter in filters {
+                match filter.as_str() {
+                    "upcase" => value = value.to_uppercase(),
+                    "quote" => value = format!("\"{}\"", value),
+                    _ => return None,
+                }
+            }
+            Some(value)
+        }
+    }
+
+    let mut options = Options::default();
+    options.extension.template_variables = true;
+    let mut plugins = Plugins::default();
+    plugins.render.template_resolver = Some(&Vars);
+
+    assert_eq!(
+        markdown_to_html_with_plugins(
+            "# {{ user.name | upcase | quote }}\n\n[{{user.name}}](/u) {{ user.name | bogus }} {{ other }}\n",
+            &options,
+            &plugins
+        ),
+        concat!(
+            "<h1>&quot;&lt;ANN&gt;&quot;</h1>\n",
+            "<p><a href=\"/u\">&lt;Ann&gt;</a> {{user.name | bogus}} {{other}}</p>\n",
+        )
+    );
+}
+
+#[test]
+fn template_variables_in_ast() {
+    assert_ast_match!(
+        [extension.template_variables],
+        "a {{ b | c }} {d}\n",
+        (document (1:1-1:17) [
+            (paragraph (1:1-1:17) [
+                (text (1:1-1:2) "a ")
+                (template_variable (1:3-1:13))
+                (text (1:14-1:17) " {d}")
+            ])
+        ])
+    );
+}
@@ -0,0 +1,95 @@
+use super::*;
+
+#[track_caller]
+fn text(input: &str, expected: &str, opts: impl Fn(&mut Options)) {
+    formatted(format_text, "text", input, expected, opts);
+}
+
+#[test]
+fn text_blocks() {
+    text(
+        concat!(
+            "# The *title*\n",
+            "\n",
+            "> Quoted\n",
+            "> text.\\\n",
+            "> Broken.\n",
+            "\n",
+            "- one\n",
+            "  - nested\n",
+            "- two\n",
+            "\n",
+            "```rust\n",
+            "let x = 1;\n",
+            "```\n",
+            "\n",
+            "<div>\n",
+            "dropped\n",
+            "</div>\n",
+            "\n",
+            "***\n",
+            "\n",
+            "| a | b |\n",
+            "|---|---|\n",
+            "| c | `d` |\n",
+        ),
+        concat!(
+            "The title\n",
+            "\n",
+            "Quoted text.\n",
+            "Broken.\n",
+            "\n",
+            "one\n",
+            "\n",
+            "nested\n",
+            "\n",
+            "two\n",
+            "\n",
+            "let x = 1;\n",
+            "\n",
+            "a b\n",
+            "\n",
+            "c d\n",
+        ),
+        |options| options.extension.table = true,
+    );
+}
+
+#[test]
+fn text_inlines() {
+    text(
+        concat!(
+            "**Bold** [link](/u) [](/empty) <https://x.org> ![alt *text*](i.png) ",
+            "<b>raw</b> note[^1] ~~gone~~\n",
+            "\n",
+            "[^1]: The note.\n",
+        ),
+        concat!(
+            "Bold link  https://x.org alt text raw note gone\n",
+            "\n",
+            "The note.\n",
+        ),
+        |options| {
+            options.extension.footnotes = true;
+            options.extension.strikethrough = true;
+        },
+    );
+
+    text(
+        "[link](/u) [](/empty) <https://x.org> [[Wiki]]\n",
+        "link (/u) (/empty) https://x.org Wiki\n",
+        |options| {
+            options.render.text_link_urls = true;
+            options.extension.wikilinks_title_after_pipe = true;
+        },
+    );
+}
+
+#[test]
+fn markdown_to_text_api() {
+    assert_eq!(
+        markdown_to_text("# Hi\n\n*there*\n", &Options::default()),
+        "Hi\n\nthere\n"
+    );
+    assert_eq!(markdown_to_text("<!-- x -->\n", &Options::default()), "");
+}
@@ -0,0 +1,75 @@
+use crate::toc::{insert_toc, table_of_contents};
+
+use super::*;
+
+#[test]
+fn table_of_contents_nesting() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.header_ids = Some("user-content-".to_string());
+    let root = parse_document(
+        &arena,
+        concat!(
+            "## Before\n",
+            "# One *a*\n",
+            "### Deep\n",
+            "## Two\n",
+            "# One a\n",
+        ),
+        &options,
+    );
+
+    let list = table_of_contents(&arena, root, &options);
+    let mut output = vec![];
+    format_commonmark(list, &options, &mut output).unwrap();
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        concat!(
+            "- [Before](#user-content-before)\n",
+            "- [One a](#user-content-one-a)\n",
+            "  - [Deep](#user-content-deep)\n",
+            "  - [Two](#user-content-two)\n",
+            "- [One a](#user-content-one-a-1)\n",
+        ),
+        "toc",
+        "",
+    );
+}
+
+#[test]
+fn insert_toc_placeholders() {
+    let mut options = Options::default();
+    options.extension.header_ids = Some("".to_string());
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, "[TOC]\n\n# A\n\n> [TOC]\n\n[TOC] here\n", &options);
+    assert!(insert_toc(&arena, root, &options));
+    let mut output = vec![];
+    format_html(root, &options, &mut output).unwrap();
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        concat!(
+            "<ul>\n",
+            "<li><a href=\"#a\">A</a></li>\n",
+            "</ul>\n",
+            "<h1><a href=\"#a\" aria-hidden=\"true\" class=\"anchor\" id=\"a\"></a>A</h1>\n",
+            "<blockquote>\n",
+            "<ul>\n",
+            "<li><a href=\"#a\">A</a></li>\n",
+            "</ul>\n",
+            "</blockquote>\n",
+            "<p>[TOC] here</p>\n",
+        ),
+        "insert_toc",
+        "",
+    );
+
+    let root = parse_document(&arena, "[TOC]\n\nNo headings.\n", &options);
+    assert!(insert_toc(&arena, root, &options));
+    let mut output = vec![];
+    format_html(root, &options, &mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "<p>No headings.</p>\n");
+
+    let root = parse_document(&arena, "# A\n", &options);
+    assert!(!insert_toc(&arena, root, &options));
+}
@@ -0,0 +1,175 @@
+use super::*;
+
+#[track_caller]
+fn typst(input: &str, expected: &str, opts: impl Fn(&mut Options)) {
+    formatted(format_typst, "typst", input, expected, opts);
+}
+
+#[test]
+fn typst_blocks() {
+    typst(
+        concat!(
+            "Title\n",
+            "=====\n",
+            "\n",
+            "### Sub *heading*\n",
+            "\n",
+            "> Quoted\n",
+            "> text.\n",
+            "\n",
+            "- one\n",
+            "  - nested\n",
+            "- two\n",
+            "\n",
+            "3) three\n",
+            "\n",
+            "   more\n",
+            "4) four\n",
+            "\n",
+            "```rust main\n",
+            "let s = \"```\";\n",
+            "```\n",
+            "\n",
+            "<div>\n",
+            "dropped\n",
+            "</div>\n",
+            "\n",
+            "***\n",
+        ),
+        concat!(
+            "= Title\n",
+            "\n",
+            "=== Sub #emph[heading]\n",
+            "\n",
+            "#quote(block: true)[\n",
+            "Quoted\n",
+            "text.\n",
+            "]\n",
+            "\n",
+            "- one\n",
+            "  - nested\n",
+            "- two\n",
+            "\n",
+            "3. three\n",
+            "\n",
+            "   more\n",
+            "\n",
+            "4. four\n",
+            "\n",
+            "````rust\n",
+            "let s = \"```\";\n",
+            "````\n",
+            "\n",
+            "#line(length: 100%)\n",
+        ),
+        |_| {},
+    );
+}
+
+#[test]
+fn typst_inlines() {
+    typst(
+        concat!(
+            "*a* **b** `c` ``d ` e`` [f](/u \"t\") <https://x.org>\\\n",
+            "![g *h*](i.png) $x^2$ $$sum_i$$\n",
+        ),
+        concat!(
+            "#emph[a] #strong[b] `c` #raw(\"d ` e\") #link(\"/u\")[f] #link(\"https://x.org\")\\\n",
+            "#image(\"i.png\", alt: \"g h\") $x^2$ $ sum_i $\n",
+        ),
+        |options| options.extension.math_dollars = true,
+    );
+}
+
+#[test]
+fn typst_escapes() {
+    typst(
+        concat!(
+            "#tag @ref $5 <3 a/b a//b a--b \\* \\_ \\[x\\]\n",
+            "\\- not a list\n",
+            "\\+ nor this\n",
+            "1\\. nor this\n",
+            "= nor a heading\n",
+        ),
+        concat!(
+            "\\#tag \\@ref \\$5 \\<3 a/b a\\//b a\\--b \\* \\_ \\[x\\]\n",
+            "\\- not a list\n",
+            "\\+ nor this\n",
+            "1\\. nor this\n",
+            "\\= nor a heading\n",
+        ),
+        |_| {},
+    );
+}
+
+#[test]
+fn typst_extensions() {
+    typst(
+        concat!(
+            "Term\n",
+            "\n",
+            ": Details.\n",
+            "\n",
+            "- [x] done\n",
+            "- [ ] todo\n",
+            "\n",
+            "| a | b |\n",
+            "|:--|--:|\n",
+            "| ~c~ | d |\n",
+            "\n",
+            "Note[^n].\n",
+            "\n",
+            "[^n]: *Foot*.\n",
+        ),
+        concat!(
+            "/ Term: Details.\n",
+            "\n",
+            "- ☒ done\n",
+            "- ☐ todo\n",
+            "\n",
+            "#table(\n",
+            "  columns: 2,\n",
+            "  align: (left, right,),\n",
+            "  table.header([a], [b]),\n",
+            "  [#strike[c]], [d],\n",
+            ")\n",
+            "\n",
+            "Note#footnote[#emph[Foot].].\n",
+        ),
+        |options| {
+            options.extension.description_lists = true;
+            options.extension.tasklist = true;
+            options.extension.table = true;
+            options.extension.strikethrough = true;
+            options.extension.footnotes = true;
+        },
+    );
+}
+
+#[test]
+fn typst_table_colspans() {
+    typst(
+        "| a || b |\n|---|---|---|\n| c | d | e |\n",
+        concat!(
+            "#table(\n",
+            "  columns: 3,\n",
+            "  align: (auto, auto, auto,),\n",
+            "  table.header(table.cell(colspan: 2)[a], [b]),\n",
+            "  [c], [d], [e],\n",
+            ")\n",
+        ),
+        |options| {
+            options.extension.table = true;
+            options.extension.table_colspans = true;
+        },
+    );
+}
+
+#[test]
+fn markdown_to_typst_api() {
+    assert_eq!(
+        markdown_to_typst("# Hi\n\n*there*\n", &Options::default()),
+        "= Hi\n\n#emph[there]\n"
+    );
+    assert_eq!(markdown_to_typst("", &Options::default()), "");
+}
@@ -0,0 +1,106 @@
+use crate::workspace::{BrokenReason, Target, Workspace};
+
+use super::*;
+
+#[test]
+fn workspace_resolve() {
+    let options = Options::default();
+    let workspace = Workspace::new(&options);
+
+    assert_eq!(
+        workspace.resolve("a/b.md", "https://example.com/x.md"),
+        Some(Target::External)
+    );
+    assert_eq!(
+        workspace.resolve("a/b.md", "mailto:someone@example.com"),
+        Some(Target::External)
+    );
+    assert_eq!(
+        workspace.resolve("a/b.md", "//example.com"),
+        Some(Target::External)
+    );
+    assert_eq!(
+        workspace.resolve("a/b.md", "../c/./d.md?raw=1#top"),
+        Some(Target::Local {
+            path: "c/d.md".to_string(),
+            fragment: Some("top".to_string()),
+        })
+    );
+    assert_eq!(
+        workspace.resolve("a/b.md", "/img/x.png"),
+        Some(Target::Local {
+            path: "img/x.png".to_string(),
+            fragment: None,
+        })
+    );
+    assert_eq!(
+        workspace.resolve("a/b.md", "#intro"),
+        Some(Target::Local {
+            path: "a/b.md".to_string(),
+            fragment: Some("intro".to_string()),
+        })
+    );
+    assert_eq!(workspace.resolve("a/b.md", "../../c.md"), None);
+}
+
+#[test]
+fn workspace_check() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.header_ids = Some("h-".to_string());
+
+    let mut workspace = Workspace::new(&options);
+    workspace.add_document(
+        "./docs/index.md",
+        parse_document(
+            &arena,
+            concat!(
+                "# Docs\n",
+                "\n",
+                "- [Self](#h-docs)\n",
+                "- [Unprefixed](#docs)\n",
+                "- [Page](page.md)\n",
+                "- [Page section](page.md#h-details)\n",
+                "- [Missing](missing.md#h-details)\n",
+                "- [Above](../../outside.md)\n",
+                "- ![Logo](/assets/logo.png)\n",
+                "- [Site](https://example.com/missing.md)\n",
+            ),
+            &options,
+        ),
+    );
+    workspace.add_document(
+        "docs/page.md",
+        parse_document(&arena, "## Details\n\n[Home](index.md#)\n", &options),
+    );
+    workspace.add_file("assets/logo.png");
+
+    let broken = workspace
+        .check()
+        .into_iter()
+        .map(|b| (b.document, b.sourcepos, b.url, b.reason))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        broken,
+        vec![
+            (
+                "docs/index.md".to_string(),
+                (4, 3, 4, 21).into(),
+                "#docs".to_string(),
+                BrokenReason::MissingAnchor
+            ),
+            (
+                "docs/index.md".to_string(),
+                (7, 3, 7, 33).into(),
+                "missing.md#h-details".to_string(),
+                BrokenReason::MissingDocument
+            ),
+            (
+                "docs/index.md".to_string(),
+                (8, 3, 8, 27).into(),
+                "../../outside.md".to_string(),
+                BrokenReason::MissingDocument
+            ),
+        ]
+    );
+}
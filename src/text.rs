@@ -0,0 +1,142 @@
+use crate::cm::is_autolink;
+use crate::nodes::{AstNode, NodeCode, NodeEntity, NodeLink, NodeMath, NodeValue, NodeWikiLink};
+use crate::parser::{Options, Plugins};
+use std::io::{self, Write};
+
+/// Formats an AST as plain text, modified by the given options, e.g. for
+/// excerpts and search indexing.
+///
+/// All markup is removed.  Each block of text becomes a paragraph, with soft
+/// line breaks joined by spaces; lists, block quotes and other containers
+/// contribute only their contents.  Links are written as their text, followed
+/// by their URL in parentheses with `render.text_link_urls`, and images as
+/// their alt text.  Raw HTML, components, extension nodes, front matter,
+/// thematic breaks, footnote references and abbreviation definitions are
+/// dropped.
+pub fn format_document<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    format_document_with_plugins(root, options, output, &Plugins::default())
+}
+
+/// Formats an AST as plain text, modified by the given options. Accepts custom
+/// plugins.
+pub fn format_document_with_plugins<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+    _plugins: &Plugins,
+) -> io::Result<()> {
+    let mut paragraphs = vec![];
+    TextFormatter { options }.block(root, &mut paragraphs);
+    let text = paragraphs.join("\n\n");
+    output.write_all(text.as_bytes())?;
+    if !text.is_empty() {
+        output.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+struct TextFormatter<'o, 'c> {
+    options: &'o Options<'c>,
+}
+
+impl<'o, 'c> TextFormatter<'o, 'c> {
+    // Adds the paragraphs of a block node to `paragraphs`.
+    fn block<'a>(&self, node: &'a AstNode<'a>, paragraphs: &mut Vec<String>) {
+        match node.data.borrow().value {
+            NodeValue::CodeBlock(ref ncb) => push(paragraphs, ncb.literal.trim_end().to_string()),
+            NodeValue::Paragraph | NodeValue::Heading(_) | NodeValue::DescriptionTerm => {
+                push(paragraphs, self.inlines(node))
+            }
+            NodeValue::TableRow(_) => {
+                let cells: Vec<String> = node
+                    .children()
+                    .map(|cell| self.inlines(cell))
+                    .filter(|cell| !cell.is_empty())
+                    .collect();
+                push(paragraphs, cells.join(" "));
+            }
+            NodeValue::Container(ref nc) => {
+                push(paragraphs, nc.summary().unwrap_or_default().to_string());
+                self.blocks(node, paragraphs);
+            }
+            NodeValue::Alert(ref nalert) => {
+                push(paragraphs, nalert.title().to_string());
+                self.blocks(node, paragraphs);
+            }
+            NodeValue::FrontMatter(_)
+            | NodeValue::Truncated(_)
+            | NodeValue::HtmlBlock(_)
+            | NodeValue::ComponentBlock(_)
+            | NodeValue::ExtensionBlock(_)
+            | NodeValue::Raw(_)
+            | NodeValue::ThematicBreak
+            | NodeValue::AbbreviationDefinition(_) => {}
+            _ => self.blocks(node, paragraphs),
+        }
+    }
+
+    fn blocks<'a>(&self, node: &'a AstNode<'a>, paragraphs: &mut Vec<String>) {
+        for child in node.children() {
+            self.block(child, paragraphs);
+        }
+    }
+
+    fn inlines<'a>(&self, node: &'a AstNode<'a>) -> String {
+        let mut s = String::new();
+        for child in node.children() {
+            self.inline(child, &mut s);
+        }
+        s
+    }
+
+    fn inline<'a>(&self, node: &'a AstNode<'a>, s: &mut String) {
+        match node.data.borrow().value {
+            NodeValue::Text(ref literal)
+            | NodeValue::Entity(NodeEntity {
+                characters: ref literal,
+                ..
+            })
+            | NodeValue::Code(NodeCode { ref literal, .. })
+            | NodeValue::Math(NodeMath { ref literal, .. })
+            | NodeValue::EscapedTag(ref literal)
+            | NodeValue::TemplateTag(ref literal) => s.push_str(literal),
+            NodeValue::SoftBreak => s.push(' '),
+            NodeValue::LineBreak => s.push('\n'),
+            NodeValue::HtmlInline(_)
+            | NodeValue::ComponentInline(_)
+            | NodeValue::ExtensionInline(_)
+            | NodeValue::FootnoteReference(_) => {}
+            NodeValue::Link(ref nl) if is_autolink(node, nl) => s.push_str(&self.inlines(node)),
+            NodeValue::Link(NodeLink { ref url, .. })
+            | NodeValue::WikiLink(NodeWikiLink { ref url }) => {
+                let text = self.inlines(node);
+                s.push_str(&text);
+                if self.options.render.text_link_urls && !url.is_empty() && *url != text {
+                    if !text.is_empty() {
+                        s.push(' ');
+                    }
+                    s.push('(');
+                    s.push_str(url);
+                    s.push(')');
+                }
+            }
+            #[cfg(feature = "shortcodes")]
+            NodeValue::ShortCode(ref nsc) => s.push_str(&nsc.emoji),
+            NodeValue::TemplateVariable(ref ntv) => s.push_str(&ntv.placeholder()),
+            NodeValue::Citation(ref nc) => s.push_str(&nc.source()),
+            _ => s.push_str(&self.inlines(node)),
+        }
+    }
+}
+
+// Adds a paragraph, unless it's blank.
+fn push(paragraphs: &mut Vec<String>, paragraph: String) {
+    let paragraph = paragraph.trim();
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph.to_string());
+    }
+}
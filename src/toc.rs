@@ -0,0 +1,155 @@
+//! Generation of a table of contents from a document's headings.
+//!
+//! The headings themselves, with their text, anchor, level and position, are
+//! returned by [`collect_anchors`].  The functions here build a nested list
+//! of links to them, which points at the `id` attributes rendered with
+//! [`ExtensionOptions::header_ids`](crate::ExtensionOptions::header_ids).
+
+use crate::html::collect_anchors;
+use crate::nodes::{AstNode, NodeLink, NodeList, NodeValue};
+use crate::Options;
+use typed_arena::Arena;
+
+/// The text of a paragraph replaced by [`insert_toc`].
+pub const TOC_PLACEHOLDER: &str = "[TOC]";
+
+/// Builds a bullet list of links to the headings of the document, nested by
+/// heading level.
+///
+/// The list is not attached to the document; insert it wherever the table of
+/// contents should appear.  If the document has no headings, the list is
+/// empty.
+///
+/// ```
+/// # use comrak::{format_html, parse_document, Arena, Options};
+/// # use comrak::toc::table_of_contents;
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.header_ids = Some("".to_string());
+/// let root = parse_document(&arena, "# Intro\n## Setup\n# Usage\n", &options);
+///
+/// let list = table_of_contents(&arena, root, &options);
+/// root.prepend(list);
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert!(String::from_utf8(html).unwrap().starts_with(
+///     "<ul>\n<li><a href=\"#intro\">Intro</a>\n\
+///      <ul>\n<li><a href=\"#setup\">Setup</a></li>\n</ul>\n</li>\n\
+///      <li><a href=\"#usage\">Usage</a></li>\n</ul>\n"
+/// ));
+/// ```
+pub fn table_of_contents<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    root: &'a AstNode<'a>,
+    options: &Options,
+) -> &'a AstNode<'a> {
+    let prefix = options.extension.header_ids.as_deref().unwrap_or("");
+    let list = alloc(arena, NodeValue::List(NodeList::bullet()));
+
+    // The lists being appended to, innermost last, with the level of the
+    // headings they contain.
+    let mut stack: Vec<(u8, &'a AstNode<'a>)> = vec![];
+    for anchor in collect_anchors(root, options) {
+        while stack.len() > 1 && stack.last().unwrap().0 > anchor.level {
+            let (_, sublist) = stack.pop().unwrap();
+            // A heading between the levels of two lists joins the inner one.
+            if stack.last().unwrap().0 < anchor.level {
+                stack.push((anchor.level, sublist));
+            }
+        }
+        if let Some(outermost) = stack.first_mut() {
+            outermost.0 = outermost.0.min(anchor.level);
+        }
+
+        let parent = match stack.last() {
+            None => {
+                stack.push((anchor.level, list));
+                list
+            }
+            Some(&(level, parent)) if anchor.level > level => match parent.last_child() {
+                Some(item) => {
+                    let sublist = alloc(arena, NodeValue::List(NodeList::bullet()));
+                    item.append(sublist);
+                    stack.push((anchor.level, sublist));
+                    sublist
+                }
+                None => parent,
+            },
+            Some(&(_, parent)) => parent,
+        };
+
+        let link = alloc(
+            arena,
+            NodeValue::Link(NodeLink::new(format!("#{}{}", prefix, anchor.id), "")),
+        );
+        link.append(alloc(arena, NodeValue::Text(anchor.text)));
+        let paragraph = alloc(arena, NodeValue::Paragraph);
+        paragraph.append(link);
+        let item = alloc(arena, NodeValue::Item(NodeList::bullet()));
+        item.append(paragraph);
+        parent.append(item);
+    }
+
+    list
+}
+
+/// Replaces each paragraph consisting of only [`TOC_PLACEHOLDER`] with a
+/// [`table_of_contents`], returning whether any were found.  Placeholders in
+/// a document without headings are removed.
+///
+/// ```
+/// # use comrak::{format_html, parse_document, Arena, Options};
+/// # use comrak::toc::insert_toc;
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.header_ids = Some("".to_string());
+/// let root = parse_document(&arena, "[TOC]\n\n# Intro\n", &options);
+/// assert!(insert_toc(&arena, root, &options));
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert!(String::from_utf8(html)
+///     .unwrap()
+///     .starts_with("<ul>\n<li><a href=\"#intro\">Intro</a></li>\n</ul>\n"));
+/// ```
+pub fn insert_toc<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    root: &'a AstNode<'a>,
+    options: &Options,
+) -> bool {
+    let placeholders: Vec<_> = root
+        .descendants()
+        .filter(|node| is_placeholder(node))
+        .collect();
+
+    for placeholder in &placeholders {
+        let list = table_of_contents(arena, root, options);
+        if list.first_child().is_some() {
+            placeholder.replace_with(list);
+        } else {
+            placeholder.detach();
+        }
+    }
+
+    !placeholders.is_empty()
+}
+
+fn is_placeholder<'a>(node: &'a AstNode<'a>) -> bool {
+    if !matches!(node.data.borrow().value, NodeValue::Paragraph) {
+        return false;
+    }
+
+    let mut text = String::new();
+    for child in node.children() {
+        match child.data.borrow().value {
+            NodeValue::Text(ref literal) => text.push_str(literal),
+            _ => return false,
+        }
+    }
+    text == TOC_PLACEHOLDER
+}
+
+fn alloc<'a>(arena: &'a Arena<AstNode<'a>>, value: NodeValue) -> &'a AstNode<'a> {
+    arena.alloc(value.into())
+}
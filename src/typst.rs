@@ -0,0 +1,547 @@
+use crate::cm::is_autolink;
+use crate::line_writer::LineWriter;
+use crate::nodes::{
+    table_cell_colspan, AstNode, ListDelimType, ListType, NodeCodeBlock, NodeEntity, NodeLink,
+    NodeList, NodeMath, NodeValue, NodeWikiLink, TableAlignment,
+};
+use crate::parser::{Options, Plugins};
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Formats an AST as [Typst](https://typst.app) markup, modified by the given
+/// options.
+///
+/// Math is passed through as written, so should use Typst's math syntax
+/// rather than LaTeX's.  Footnote references are written as `#footnote` calls
+/// containing their definitions.  Raw HTML, components, extension nodes, front
+/// matter and abbreviation definitions are dropped; other constructs without a
+/// Typst equivalent are written as their contents.
+pub fn format_document<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    format_document_with_plugins(root, options, output, &Plugins::default())
+}
+
+/// Formats an AST as Typst markup, modified by the given options. Accepts
+/// custom plugins.
+pub fn format_document_with_plugins<'a>(
+    root: &'a AstNode<'a>,
+    _options: &Options,
+    output: &mut dyn Write,
+    _plugins: &Plugins,
+) -> io::Result<()> {
+    let mut footnotes = HashMap::new();
+    for node in root.descendants() {
+        if let NodeValue::FootnoteDefinition(ref nfd) = node.data.borrow().value {
+            footnotes.insert(nfd.name.clone(), node);
+        }
+    }
+
+    let mut f = TypstFormatter {
+        w: LineWriter::new(),
+        footnotes,
+        open_footnotes: vec![],
+        numbers: vec![],
+        single_line: false,
+        inline_start: false,
+        start: 0,
+    };
+    f.format(root);
+    f.w.finish(output)
+}
+
+struct TypstFormatter<'a> {
+    w: LineWriter,
+    footnotes: HashMap<String, &'a AstNode<'a>>,
+    // The footnotes whose definitions are being written, so that a definition
+    // referring to itself isn't written within itself.
+    open_footnotes: Vec<String>,
+    // The number of the next item of each list being written.
+    numbers: Vec<usize>,
+    // Whether line breaks are written as spaces, as in headings and table
+    // cells.
+    single_line: bool,
+    // Whether nothing has been written on the current line of the innermost
+    // block or markup being written, where text could start a block.
+    inline_start: bool,
+    // Where the heading being written starts in the output.
+    start: usize,
+}
+
+impl<'a> TypstFormatter<'a> {
+    fn format(&mut self, root: &'a AstNode<'a>) {
+        enum Phase {
+            Pre,
+            // The state of the enclosing markup, restored if nothing was
+            // written within.
+            Post { inline_start: bool, len: usize },
+            Footnote { single_line: bool },
+        }
+        let mut stack = vec![(root, Phase::Pre)];
+
+        while let Some((node, phase)) = stack.pop() {
+            let block = is_block(&node.data.borrow().value);
+            match phase {
+                Phase::Pre => {
+                    if block {
+                        self.w.open(separator(node));
+                    }
+                    let (inline_start, len) = (self.inline_start, self.w.len());
+                    if let NodeValue::FootnoteReference(ref nfr) = node.data.borrow().value {
+                        let definition = self.footnotes.get(&nfr.name).copied();
+                        if let Some(definition) = definition {
+                            if !self.open_footnotes.contains(&nfr.name) {
+                                self.write("#footnote[");
+                                self.w.open(Some("\n\n"));
+                                stack.push((
+                                    node,
+                                    Phase::Footnote {
+                                        single_line: self.single_line,
+                                    },
+                                ));
+                                for ch in definition.reverse_children() {
+                                    stack.push((ch, Phase::Pre));
+                                }
+                                self.open_footnotes.push(nfr.name.clone());
+                            }
+                        }
+                    } else if self.format_node(node, true) {
+                        stack.push((node, Phase::Post { inline_start, len }));
+                        for ch in node.reverse_children() {
+                            stack.push((ch, Phase::Pre));
+                        }
+                    } else if block {
+                        self.w.close();
+                    }
+                }
+                Phase::Post { inline_start, len } => {
+                    self.format_node(node, false);
+                    if block {
+                        self.w.close();
+                    } else if self.w.len() == len {
+                        self.inline_start = inline_start;
+                    }
+                }
+                Phase::Footnote { single_line } => {
+                    self.w.close();
+                    self.write("]");
+                    self.open_footnotes.pop();
+                    self.single_line = single_line;
+                }
+            }
+        }
+    }
+
+    // Writes the start or end of `node`, returning whether its children
+    // should be written.
+    fn format_node(&mut self, node: &'a AstNode<'a>, entering: bool) -> bool {
+        match node.data.borrow().value {
+            NodeValue::Document => (),
+            NodeValue::BlockQuote | NodeValue::MultilineBlockQuote(_) => {
+                if entering {
+                    self.write("#quote(block: true)[");
+                    self.w.start_contents("\n");
+                } else {
+                    let empty = self.w.end_contents();
+                    self.write(if empty { "\n\n]" } else { "\n]" });
+                }
+            }
+            NodeValue::List(ref nl) => {
+                if entering {
+                    self.numbers.push(nl.start);
+                } else {
+                    self.numbers.pop();
+                }
+            }
+            NodeValue::Item(_) | NodeValue::TaskItem(_) => {
+                if entering {
+                    let number = self.numbers.last_mut().unwrap();
+                    let mut marker = match node.parent().unwrap().data.borrow().value {
+                        NodeValue::List(ref nl) => list_marker(nl, *number),
+                        _ => "-".to_string(),
+                    };
+                    *number += 1;
+                    if let NodeValue::TaskItem(symbol) = node.data.borrow().value {
+                        marker.push_str(if symbol.is_some() { " ☒" } else { " ☐" });
+                    }
+                    marker.push(' ');
+                    let indent = " ".repeat(marker.chars().count());
+                    self.w.push_prefix(marker, indent);
+                } else {
+                    if !self.w.block_written() {
+                        self.w.write_prefix();
+                    }
+                    self.w.pop_prefix();
+                }
+            }
+            NodeValue::DescriptionList | NodeValue::DescriptionItem(_) => (),
+            NodeValue::DescriptionTerm => {
+                if entering {
+                    self.write("/ ");
+                    self.w.start_contents("");
+                } else {
+                    self.write(":");
+                }
+                self.w.set_single_line(entering);
+            }
+            NodeValue::DescriptionDetails => {
+                if entering {
+                    self.w.push_prefix(" ".to_string(), "  ".to_string());
+                } else {
+                    self.w.pop_prefix();
+                }
+            }
+            NodeValue::CodeBlock(ref ncb) => {
+                self.write(&code_block(ncb));
+                return false;
+            }
+            NodeValue::Paragraph => {
+                self.inline_start = true;
+                self.single_line = false;
+            }
+            NodeValue::Heading(ref nh) => {
+                if entering {
+                    self.write(&"=".repeat(nh.level as usize));
+                    self.start = self.w.len();
+                    self.write(" ");
+                    self.inline_start = true;
+                } else {
+                    self.w.trim_end(self.start);
+                }
+                self.single_line = entering;
+            }
+            NodeValue::ThematicBreak => {
+                self.write("#line(length: 100%)");
+                return false;
+            }
+            NodeValue::Table(ref nt) => {
+                if entering {
+                    let alignments: Vec<&str> = nt
+                        .alignments
+                        .iter()
+                        .map(|alignment| match alignment {
+                            TableAlignment::None => "auto",
+                            TableAlignment::Left => "left",
+                            TableAlignment::Center => "center",
+                            TableAlignment::Right => "right",
+                        })
+                        .collect();
+                    self.write(&format!(
+                        "#table(\n  columns: {},\n  align: ({},),\n",
+                        nt.num_columns,
+                        alignments.join(", ")
+                    ));
+                } else {
+                    self.write(")");
+                }
+            }
+            NodeValue::TableRow(header) => match (header, entering) {
+                (true, true) => self.write("  table.header("),
+                (true, false) => self.write("),\n"),
+                (false, true) => self.write("  "),
+                (false, false) => self.write(",\n"),
+            },
+            NodeValue::TableCell => {
+                if entering {
+                    if node.previous_sibling().is_some() {
+                        self.write(", ");
+                    }
+                    match table_cell_colspan(node) {
+                        1 => self.write("["),
+                        colspan => self.write(&format!("table.cell(colspan: {})[", colspan)),
+                    }
+                    self.inline_start = true;
+                } else {
+                    self.write("]");
+                }
+                self.single_line = entering;
+            }
+            NodeValue::FrontMatter(_)
+            | NodeValue::Truncated(_)
+            | NodeValue::HtmlBlock(_)
+            | NodeValue::ComponentBlock(_)
+            | NodeValue::ExtensionBlock(_)
+            | NodeValue::Raw(_)
+            | NodeValue::FootnoteDefinition(_)
+            | NodeValue::AbbreviationDefinition(_) => return false,
+            NodeValue::Alert(ref nalert) => {
+                if entering {
+                    let mut s = "#quote(block: true)[\n#strong[".to_string();
+                    escape(nalert.title(), &mut s);
+                    s.push(']');
+                    self.write(&s);
+                } else {
+                    self.write("\n]");
+                }
+            }
+            NodeValue::Container(ref nc) => {
+                if let Some(summary) = nc.summary().filter(|_| entering) {
+                    let mut s = "#strong[".to_string();
+                    escape(summary, &mut s);
+                    s.push(']');
+                    self.write(&s);
+                }
+            }
+
+            NodeValue::Text(ref literal) => {
+                let mut s = String::new();
+                escape(literal, &mut s);
+                if self.inline_start {
+                    if let Some(at) = block_start_escape(&s) {
+                        s.insert(at, '\\');
+                    }
+                }
+                self.write(&s);
+                return false;
+            }
+            NodeValue::SoftBreak | NodeValue::LineBreak if self.single_line => {
+                self.write(" ");
+                return false;
+            }
+            NodeValue::SoftBreak => {
+                self.write("\n");
+                return false;
+            }
+            NodeValue::LineBreak => {
+                self.write("\\\n");
+                return false;
+            }
+            NodeValue::Code(ref nc) => {
+                let mut s = String::new();
+                raw(&nc.literal, &mut s);
+                self.write(&s);
+                return false;
+            }
+            NodeValue::HtmlInline(_)
+            | NodeValue::ComponentInline(_)
+            | NodeValue::ExtensionInline(_) => return false,
+            NodeValue::Emph => self.wrap(entering, "#emph[", "]"),
+            NodeValue::Strong => self.wrap(entering, "#strong[", "]"),
+            NodeValue::Strikethrough => self.wrap(entering, "#strike[", "]"),
+            NodeValue::Superscript => self.wrap(entering, "#super[", "]"),
+            NodeValue::Subscript => self.wrap(entering, "#sub[", "]"),
+            NodeValue::Underline => self.wrap(entering, "#underline[", "]"),
+            NodeValue::Link(ref nl) if is_autolink(node, nl) => {
+                self.write(&format!("#link({})", quote(&nl.url)));
+                return false;
+            }
+            NodeValue::Link(NodeLink { ref url, .. })
+            | NodeValue::WikiLink(NodeWikiLink { ref url }) => {
+                self.wrap(entering, &format!("#link({})[", quote(url)), "]");
+            }
+            NodeValue::Image(ref nl) => {
+                let alt = node.text_content();
+                let mut s = "#image(".to_string();
+                s.push_str(&quote(&nl.url));
+                if !alt.is_empty() {
+                    s.push_str(", alt: ");
+                    s.push_str(&quote(&alt));
+                }
+                s.push(')');
+                self.write(&s);
+                return false;
+            }
+            NodeValue::Math(NodeMath {
+                ref literal,
+                display_math,
+                ..
+            }) => {
+                if display_math {
+                    self.write(&format!("$ {} $", literal.trim()));
+                } else {
+                    self.write(&format!("${}$", literal.trim()));
+                }
+                return false;
+            }
+            #[cfg(feature = "shortcodes")]
+            NodeValue::ShortCode(ref nsc) => {
+                self.write(&nsc.emoji);
+                return false;
+            }
+            NodeValue::EscapedTag(ref literal)
+            | NodeValue::TemplateTag(ref literal)
+            | NodeValue::Entity(NodeEntity {
+                characters: ref literal,
+                ..
+            }) => {
+                let mut s = String::new();
+                escape(literal, &mut s);
+                self.write(&s);
+                return false;
+            }
+            NodeValue::TemplateVariable(ref ntv) => {
+                let mut s = String::new();
+                escape(&ntv.placeholder(), &mut s);
+                self.write(&s);
+                return false;
+            }
+            NodeValue::Citation(ref nc) => {
+                let mut s = String::new();
+                escape(&nc.source(), &mut s);
+                self.write(&s);
+                return false;
+            }
+            _ => {
+                if entering {
+                    self.inline_start = true;
+                }
+            }
+        }
+        true
+    }
+
+    fn write(&mut self, s: &str) {
+        if !s.is_empty() {
+            self.w.write(s);
+            self.inline_start = s.ends_with('\n');
+        }
+    }
+
+    // Writes the start or end of markup containing inlines.
+    fn wrap(&mut self, entering: bool, open: &str, close: &str) {
+        if entering {
+            self.write(open);
+            self.inline_start = true;
+        } else {
+            self.write(close);
+        }
+    }
+}
+
+fn is_block(value: &NodeValue) -> bool {
+    value.block() || matches!(value, NodeValue::FrontMatter(_) | NodeValue::Truncated(_))
+}
+
+// The separator between the children of a block.
+fn separator<'a>(node: &'a AstNode<'a>) -> Option<&'static str> {
+    let tight = |list: &'a AstNode<'a>| match list.data.borrow().value {
+        NodeValue::List(ref nl) => nl.tight,
+        _ => false,
+    };
+    match node.data.borrow().value {
+        NodeValue::List(_) if tight(node) => Some("\n"),
+        NodeValue::Item(_) | NodeValue::TaskItem(_) if tight(node.parent().unwrap()) => Some("\n"),
+        NodeValue::DescriptionList => Some("\n"),
+        NodeValue::DescriptionTerm => Some(" "),
+        NodeValue::DescriptionItem(_)
+        | NodeValue::Paragraph
+        | NodeValue::Heading(..)
+        | NodeValue::Table(..)
+        | NodeValue::TableRow(..)
+        | NodeValue::TableCell => None,
+        _ => Some("\n\n"),
+    }
+}
+
+fn list_marker(nl: &NodeList, number: usize) -> String {
+    match nl.list_type {
+        ListType::Bullet => "-".to_string(),
+        ListType::Ordered => match nl.delimiter {
+            ListDelimType::Period | ListDelimType::Paren => format!("{}.", number),
+        },
+    }
+}
+
+fn code_block(ncb: &NodeCodeBlock) -> String {
+    let fence = "`".repeat(fence_length(&ncb.literal, 3));
+    let lang = ncb.info.split_whitespace().next().unwrap_or("");
+    let mut s = fence.clone();
+    if lang
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '+' | '#' | '.'))
+    {
+        s.push_str(lang);
+    }
+    s.push('\n');
+    s.push_str(&ncb.literal);
+    if !ncb.literal.is_empty() && !ncb.literal.ends_with('\n') {
+        s.push('\n');
+    }
+    s.push_str(&fence);
+    s
+}
+
+// The length of a run of backticks longer than any in `literal`, and at least
+// `min`.
+fn fence_length(literal: &str, min: usize) -> usize {
+    let longest = literal
+        .as_bytes()
+        .split(|&b| b != b'`')
+        .map(<[u8]>::len)
+        .max()
+        .unwrap_or(0);
+    (longest + 1).max(min)
+}
+
+// Writes `literal` as inline raw text.  Typst's single-backtick raw text can't
+// contain backticks, so those are written with the `raw` function instead.
+fn raw(literal: &str, s: &mut String) {
+    if literal.contains('`') || literal.is_empty() {
+        s.push_str("#raw(");
+        s.push_str(&quote(literal));
+        s.push(')');
+    } else {
+        s.push('`');
+        s.push_str(literal);
+        s.push('`');
+    }
+}
+
+fn quote(value: &str) -> String {
+    let mut s = "\"".to_string();
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                s.push('\\');
+                s.push(c);
+            }
+            '\n' => s.push_str("\\n"),
+            _ => s.push(c),
+        }
+    }
+    s.push('"');
+    s
+}
+
+fn escape(literal: &str, s: &mut String) {
+    let mut chars = literal.chars().peekable();
+    while let Some(c) = chars.next() {
+        let next = chars.peek().copied();
+        let special = match c {
+            '\\' | '*' | '_' | '`' | '$' | '#' | '@' | '<' | '[' | ']' | '~' => true,
+            // Comments, and en dashes and soft hyphens.
+            '/' => matches!(next, Some('/' | '*')),
+            '-' => matches!(next, Some('-' | '?')),
+            _ => false,
+        };
+        if special {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+}
+
+// Where to escape the start of a line of text which would otherwise begin a
+// heading, list item or term.
+fn block_start_escape(line: &str) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let marker = match bytes.first() {
+        Some(b'=' | b'-' | b'+' | b'/') => 1,
+        _ => bytes.iter().take_while(|c| c.is_ascii_digit()).count(),
+    };
+    let is_marker = marker > 0
+        && match bytes[0] {
+            b'=' => true,
+            b'0'..=b'9' => {
+                bytes.get(marker) == Some(&b'.')
+                    && matches!(bytes.get(marker + 1), None | Some(b' '))
+            }
+            _ => matches!(bytes.get(marker), None | Some(b' ')),
+        };
+    if !is_marker {
+        return None;
+    }
+    Some(if bytes[0].is_ascii_digit() { marker } else { 0 })
+}
@@ -0,0 +1,226 @@
+//! Resolution and checking of links between documents.
+//!
+//! A [`Workspace`] holds a set of parsed documents keyed by their paths, which
+//! are `/`-separated and relative to the root of the workspace, e.g.
+//! `guide/install.md`.  Relative link destinations are resolved against the
+//! directory of the linking document, and destinations starting with `/`
+//! against the root of the workspace.  Fragments are matched against the
+//! heading anchors of the target document, as rendered with
+//! [`header_ids`](crate::ExtensionOptions::header_ids).
+//!
+//! ```
+//! # use comrak::{parse_document, Arena, Options};
+//! # use comrak::workspace::{BrokenReason, Workspace};
+//! let arena = Arena::new();
+//! let mut options = Options::default();
+//! options.extension.header_ids = Some("".to_string());
+//!
+//! let mut workspace = Workspace::new(&options);
+//! workspace.add_document(
+//!     "index.md",
+//!     parse_document(&arena, "See [install](guide/install.md#linux) and [usage](usage.md).\n", &options),
+//! );
+//! workspace.add_document(
+//!     "guide/install.md",
+//!     parse_document(&arena, "# Install\n\n## Linux\n\n[Back](../index.md#nowhere)\n", &options),
+//! );
+//!
+//! let broken = workspace.check();
+//! assert_eq!(broken.len(), 2);
+//! assert_eq!(broken[0].document, "guide/install.md");
+//! assert_eq!(broken[0].reason, BrokenReason::MissingAnchor);
+//! assert_eq!(broken[1].document, "index.md");
+//! assert_eq!(broken[1].url, "usage.md");
+//! assert_eq!(broken[1].reason, BrokenReason::MissingDocument);
+//! ```
+
+use crate::html::collect_anchors;
+use crate::links::{collect_links, LinkKind};
+use crate::nodes::{AstNode, Sourcepos};
+use crate::Options;
+use std::collections::{BTreeMap, HashSet};
+
+/// What a link destination refers to.  See [`Workspace::resolve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// A destination outside the workspace: one with a scheme, such as
+    /// `https:` or `mailto:`, or a protocol-relative one.
+    External,
+    /// A document or file in the workspace.
+    Local {
+        /// The normalized path of the target, relative to the workspace root.
+        path: String,
+        /// The fragment of the destination, if any, without the `#`.
+        fragment: Option<String>,
+    },
+}
+
+/// Why a link is broken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrokenReason {
+    /// The destination's path isn't a document or file in the workspace, or
+    /// climbs above the workspace root.
+    MissingDocument,
+    /// The destination's document exists, but has no heading with the
+    /// destination's fragment as its anchor.
+    MissingAnchor,
+}
+
+/// A link or image whose destination doesn't resolve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    /// The path of the document containing the link.
+    pub document: String,
+
+    /// The source position of the link in that document.
+    pub sourcepos: Sourcepos,
+
+    /// The destination of the link, as written.
+    pub url: String,
+
+    /// Why the link is broken.
+    pub reason: BrokenReason,
+}
+
+#[derive(Debug)]
+struct Document<'a> {
+    root: &'a AstNode<'a>,
+    anchors: HashSet<String>,
+}
+
+/// A set of parsed documents whose links may refer to one another.
+#[derive(Debug)]
+pub struct Workspace<'a, 'o> {
+    options: &'o Options<'o>,
+    documents: BTreeMap<String, Document<'a>>,
+    files: HashSet<String>,
+}
+
+impl<'a, 'o> Workspace<'a, 'o> {
+    /// Creates an empty workspace.  `options` should be those the documents
+    /// are parsed and rendered with, as they determine heading anchors.
+    pub fn new(options: &'o Options<'o>) -> Self {
+        Workspace {
+            options,
+            documents: BTreeMap::new(),
+            files: HashSet::new(),
+        }
+    }
+
+    /// Adds a parsed document at `path`, replacing any document already
+    /// there.
+    pub fn add_document(&mut self, path: &str, root: &'a AstNode<'a>) {
+        let prefix = self.options.extension.header_ids.as_deref().unwrap_or("");
+        let anchors = collect_anchors(root, self.options)
+            .into_iter()
+            .map(|anchor| format!("{}{}", prefix, anchor.id))
+            .collect();
+        self.documents.insert(
+            normalize("", path).unwrap_or_default(),
+            Document { root, anchors },
+        );
+    }
+
+    /// Registers a file at `path` that isn't a Markdown document, such as an
+    /// image, so links to it aren't reported as broken.  Fragments of links
+    /// to such files aren't checked.
+    pub fn add_file(&mut self, path: &str) {
+        self.files.insert(normalize("", path).unwrap_or_default());
+    }
+
+    /// Resolves `url`, a link destination in the document at `from`.  Returns
+    /// `None` if a relative destination climbs above the workspace root.  A
+    /// destination consisting only of a fragment refers to `from` itself.
+    /// Query strings are ignored.
+    pub fn resolve(&self, from: &str, url: &str) -> Option<Target> {
+        if url.starts_with("//") || has_scheme(url) {
+            return Some(Target::External);
+        }
+
+        let (url, fragment) = match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment.to_string())),
+            None => (url, None),
+        };
+        let url = url.split_once('?').map_or(url, |(url, _)| url);
+
+        let path = if url.is_empty() {
+            normalize("", from)?
+        } else if let Some(absolute) = url.strip_prefix('/') {
+            normalize("", absolute)?
+        } else {
+            let dir = from.rsplit_once('/').map_or("", |(dir, _)| dir);
+            normalize(dir, url)?
+        };
+        Some(Target::Local { path, fragment })
+    }
+
+    /// Checks the destination of every link and image in every document,
+    /// returning those that don't resolve to a document or file in the
+    /// workspace, or to a heading in the document.  The result is ordered by
+    /// document path, then source position.
+    pub fn check(&self) -> Vec<BrokenLink> {
+        let mut broken = vec![];
+        for (path, document) in &self.documents {
+            for link in collect_links(document.root) {
+                if link.kind == LinkKind::WikiLink {
+                    continue;
+                }
+                if let Some(reason) = self.check_url(path, &link.url) {
+                    broken.push(BrokenLink {
+                        document: path.clone(),
+                        sourcepos: link.sourcepos,
+                        url: link.url,
+                        reason,
+                    });
+                }
+            }
+        }
+        broken
+    }
+
+    fn check_url(&self, from: &str, url: &str) -> Option<BrokenReason> {
+        match self.resolve(from, url) {
+            None => Some(BrokenReason::MissingDocument),
+            Some(Target::External) => None,
+            Some(Target::Local { path, fragment }) => match self.documents.get(&path) {
+                Some(document) => match fragment {
+                    Some(ref fragment)
+                        if !fragment.is_empty() && !document.anchors.contains(fragment) =>
+                    {
+                        Some(BrokenReason::MissingAnchor)
+                    }
+                    _ => None,
+                },
+                None if self.files.contains(&path) => None,
+                None => Some(BrokenReason::MissingDocument),
+            },
+        }
+    }
+}
+
+fn has_scheme(url: &str) -> bool {
+    match url.split_once(':') {
+        Some((scheme, _)) => {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+// Joins `path` onto the directory `dir`, resolving `.` and `..` segments.
+fn normalize(dir: &str, path: &str) -> Option<String> {
+    let mut segments: Vec<&str> = vec![];
+    for segment in dir.split('/').chain(path.split('/')) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            _ => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
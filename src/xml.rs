@@ -1,4 +1,7 @@
-use crate::nodes::{AstNode, ListType, NodeCode, NodeMath, NodeTable, NodeValue};
+use crate::nodes::{
+    AstNode, ContainerKind, ListType, NodeCode, NodeComponent, NodeEntity, NodeMath, NodeTable,
+    NodeValue,
+};
 use crate::parser::{Options, Plugins};
 use once_cell::sync::Lazy;
 use std::cmp;
@@ -35,6 +38,8 @@ struct XmlFormatter<'o, 'c> {
     options: &'o Options<'c>,
     _plugins: &'o Plugins<'o>,
     indent: u32,
+    // The column of the last table cell entered.
+    table_cell_ix: usize,
 }
 
 impl<'o, 'c> XmlFormatter<'o, 'c> {
@@ -44,6 +49,7 @@ impl<'o, 'c> XmlFormatter<'o, 'c> {
             output,
             _plugins: plugins,
             indent: 0,
+            table_cell_ix: 0,
         }
     }
 
@@ -94,6 +100,10 @@ impl<'o, 'c> XmlFormatter<'o, 'c> {
                     let new_plain = if plain {
                         match node.data.borrow().value {
                             NodeValue::Text(ref literal)
+                            | NodeValue::Entity(NodeEntity {
+                                characters: ref literal,
+                                ..
+                            })
                             | NodeValue::Code(NodeCode { ref literal, .. })
                             | NodeValue::HtmlInline(ref literal) => {
                                 self.escape(literal.as_bytes())?;
@@ -151,10 +161,43 @@ impl<'o, 'c> XmlFormatter<'o, 'c> {
                 NodeValue::Document => self
                     .output
                     .write_all(b" xmlns=\"http://commonmark.org/xml/1.0\"")?,
+                NodeValue::ComponentBlock(NodeComponent {
+                    ref name,
+                    ref literal,
+                })
+                | NodeValue::ComponentInline(NodeComponent {
+                    ref name,
+                    ref literal,
+                }) => {
+                    self.output.write_all(b" name=\"")?;
+                    self.escape(name.as_bytes())?;
+                    self.output.write_all(b"\" xml:space=\"preserve\">")?;
+                    self.escape(literal.as_bytes())?;
+                    write!(self.output, "</{}", ast.value.xml_node_name())?;
+                    was_literal = true;
+                }
+                NodeValue::Entity(ref ne) => {
+                    self.output.write_all(b" literal=\"")?;
+                    self.escape(ne.literal.as_bytes())?;
+                    self.output.write_all(b"\" xml:space=\"preserve\">")?;
+                    self.escape(ne.characters.as_bytes())?;
+                    write!(self.output, "</{}", ast.value.xml_node_name())?;
+                    was_literal = true;
+                }
+                NodeValue::ExtensionBlock(ref ne) | NodeValue::ExtensionInline(ref ne) => {
+                    self.output.write_all(b" extension=\"")?;
+                    self.escape(ne.extension.as_bytes())?;
+                    self.output.write_all(b"\" xml:space=\"preserve\">")?;
+                    self.escape(ne.literal.as_bytes())?;
+                    write!(self.output, "</{}", ast.value.xml_node_name())?;
+                    was_literal = true;
+                }
                 NodeValue::Text(ref literal)
                 | NodeValue::Code(NodeCode { ref literal, .. })
                 | NodeValue::HtmlBlock(NodeHtmlBlock { ref literal, .. })
-                | NodeValue::HtmlInline(ref literal) => {
+                | NodeValue::Raw(ref literal)
+                | NodeValue::HtmlInline(ref literal)
+                | NodeValue::TemplateTag(ref literal) => {
                     self.output.write_all(b" xml:space=\"preserve\">")?;
                     self.escape(literal.as_bytes())?;
                     write!(self.output, "</{}", ast.value.xml_node_name())?;
@@ -174,6 +217,9 @@ impl<'o, 'c> XmlFormatter<'o, 'c> {
                     write!(self.output, " tight=\"{}\"", nl.tight)?;
                 }
                 NodeValue::FrontMatter(_) => (),
+                NodeValue::Truncated(omitted) => {
+                    write!(self.output, " omitted=\"{}\"", omitted)?;
+                }
                 NodeValue::BlockQuote => {}
                 NodeValue::MultilineBlockQuote(..) => {}
                 NodeValue::Item(..) => {}
@@ -207,6 +253,7 @@ impl<'o, 'c> XmlFormatter<'o, 'c> {
                 NodeValue::Emph => {}
                 NodeValue::Strikethrough => {}
                 NodeValue::Superscript => {}
+                NodeValue::Subscript => {}
                 NodeValue::Link(ref nl) | NodeValue::Image(ref nl) => {
                     self.output.write_all(b" destination=\"")?;
                     self.escape(nl.url.as_bytes())?;
@@ -221,6 +268,16 @@ impl<'o, 'c> XmlFormatter<'o, 'c> {
                     // noop
                 }
                 NodeValue::TableCell => {
+                    self.table_cell_ix = match node.previous_sibling() {
+                        Some(previous) => {
+                            self.table_cell_ix + crate::nodes::table_cell_colspan(previous)
+                        }
+                        None => 0,
+                    };
+                    let colspan = crate::nodes::table_cell_colspan(node);
+                    if colspan > 1 {
+                        write!(self.output, " colspan=\"{}\"", colspan)?;
+                    }
                     let mut ancestors = node.ancestors().skip(1);
 
                     let header_row = &ancestors.next().unwrap().data.borrow().value;
@@ -231,8 +288,7 @@ impl<'o, 'c> XmlFormatter<'o, 'c> {
                         NodeValue::Table(NodeTable { alignments, .. }),
                     ) = (header_row, table)
                     {
-                        let ix = node.preceding_siblings().count() - 1;
-                        if let Some(xml_align) = alignments[ix].xml_name() {
+                        if let Some(xml_align) = alignments[self.table_cell_ix].xml_name() {
                             write!(self.output, " align=\"{}\"", xml_align)?;
                         }
                     }
@@ -283,6 +339,64 @@ impl<'o, 'c> XmlFormatter<'o, 'c> {
                 NodeValue::EscapedTag(ref data) => {
                     self.output.write_all(data.as_bytes())?;
                 }
+                NodeValue::AbbreviationDefinition(ref nad) => {
+                    self.output.write_all(b" label=\"")?;
+                    self.escape(nad.label.as_bytes())?;
+                    self.output.write_all(b"\" title=\"")?;
+                    self.escape(nad.title.as_bytes())?;
+                    self.output.write_all(b"\"")?;
+                }
+                NodeValue::TemplateVariable(ref ntv) => {
+                    self.output.write_all(b" name=\"")?;
+                    self.escape(ntv.name.as_bytes())?;
+                    self.output.write_all(b"\"")?;
+                    if !ntv.filters.is_empty() {
+                        self.output.write_all(b" filters=\"")?;
+                        self.escape(ntv.filters.join(" | ").as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                }
+                NodeValue::Alert(ref nalert) => {
+                    write!(
+                        self.output,
+                        " alert_type=\"{}\"",
+                        nalert.alert_type.css_class()
+                    )?;
+                    if let Some(ref title) = nalert.title {
+                        self.output.write_all(b" title=\"")?;
+                        self.escape(title.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                }
+                NodeValue::Container(ref nc) if nc.kind == ContainerKind::Conditional => {
+                    self.output.write_all(b" condition=\"")?;
+                    self.escape(nc.condition().as_bytes())?;
+                    self.output.write_all(b"\"")?;
+                }
+                NodeValue::Container(ref nc) if nc.kind == ContainerKind::Details => {
+                    if let Some(summary) = nc.summary() {
+                        self.output.write_all(b" summary=\"")?;
+                        self.escape(summary.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                }
+                NodeValue::Container(ref nc) => {
+                    if !nc.name.is_empty() {
+                        self.output.write_all(b" name=\"")?;
+                        self.escape(nc.name.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                    if !nc.attributes.is_empty() {
+                        self.output.write_all(b" attributes=\"")?;
+                        self.escape(nc.attributes.to_string().as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                }
+                NodeValue::Citation(ref nc) => {
+                    self.output.write_all(b" source=\"")?;
+                    self.escape(nc.source().as_bytes())?;
+                    self.output.write_all(b"\"")?;
+                }
             }
 
             if node.first_child().is_some() {